@@ -0,0 +1,62 @@
+// Fraction lives in the binary, so its module is compiled into the benchmark directly
+#[allow(dead_code)]
+#[path = "../src/utils/types/fraction.rs"]
+mod fraction;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fraction::{Fraction, Rounding};
+use num_bigint::BigUint;
+
+const DECIMALS: u32 = 18;
+
+fn whole(value: BigUint) -> Fraction {
+    Fraction::new(value, Some(BigUint::from(1u64))).unwrap()
+}
+
+// ETH price from a USDC/ETH pair, scaled by 10^12 like liquidity::get_price
+fn eth_price() -> Fraction {
+    let reserve0 = BigUint::from(1_250u64) * BigUint::from(10u64).pow(DECIMALS);
+    let reserve1 = BigUint::from(4_312_500_000_000u64);
+    Fraction::new(reserve1, Some(reserve0)).unwrap() * whole(BigUint::from(10u64).pow(12))
+}
+
+// Starting market cap of a 1B supply launch, the computation of liquidity::parse_liquidity_params
+fn starting_mcap() -> Fraction {
+    let supply = whole(BigUint::from(1_000_000_000u64) * BigUint::from(10u64).pow(DECIMALS));
+    let decimals = whole(BigUint::from(10u64).pow(DECIMALS)) * whole(BigUint::from(10u64).pow(48));
+    let price = whole(BigUint::from(4_611_686_018_427u64)) * whole(BigUint::from(10u64).pow(DECIMALS));
+    ((price * eth_price()) * supply / decimals).unwrap()
+}
+
+fn arithmetic(c: &mut Criterion) {
+    let (price, supply) = (eth_price(), whole(BigUint::from(10u64).pow(27)));
+    c.bench_function("fraction_mul", |b| {
+        b.iter(|| black_box(price.clone()) * black_box(supply.clone()))
+    });
+    c.bench_function("fraction_div", |b| {
+        b.iter(|| black_box(supply.clone()) / black_box(price.clone()))
+    });
+    c.bench_function("fraction_starting_mcap", |b| b.iter(starting_mcap));
+}
+
+fn formatting(c: &mut Criterion) {
+    let mcap = starting_mcap();
+    // USD per raw unit of a fresh memecoin, mostly leading zeros
+    let unit_price = Fraction::new(
+        BigUint::from(37u64),
+        Some(BigUint::from(10u64).pow(DECIMALS + 3)),
+    )
+    .unwrap();
+    c.bench_function("fraction_format_mcap", |b| {
+        b.iter(|| black_box(&mcap).to_formatted_string().unwrap())
+    });
+    c.bench_function("fraction_format_unit_price", |b| {
+        b.iter(|| black_box(&unit_price).to_formatted_string().unwrap())
+    });
+    c.bench_function("fraction_significant_digits", |b| {
+        b.iter(|| black_box(&mcap).to_significant_digits(0, Rounding::RoundDown).unwrap())
+    });
+}
+
+criterion_group!(benches, arithmetic, formatting);
+criterion_main!(benches);
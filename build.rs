@@ -0,0 +1,23 @@
+use std::process::Command;
+
+// Embeds the commit being built as GIT_HASH, shown by the /meta endpoint. Builds without a
+// checkout (Docker contexts, tarballs) can pass it in, Render sets RENDER_GIT_COMMIT
+fn main() {
+    let hash = std::env::var("GIT_HASH")
+        .or_else(|_| std::env::var("RENDER_GIT_COMMIT"))
+        .ok()
+        .or_else(|| {
+            Command::new("git")
+                .args(["rev-parse", "HEAD"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=GIT_HASH={}", hash);
+    println!("cargo:rerun-if-env-changed=GIT_HASH");
+    println!("cargo:rerun-if-env-changed=RENDER_GIT_COMMIT");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+}
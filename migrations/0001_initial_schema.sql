@@ -0,0 +1,39 @@
+-- Tables created by Storage::connect before migrations existed, so IF NOT EXISTS keeps
+-- databases from those releases valid
+
+CREATE TABLE IF NOT EXISTS creations (
+    token_address TEXT PRIMARY KEY,
+    owner TEXT NOT NULL,
+    name TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    initial_supply TEXT NOT NULL,
+    block_number INTEGER NOT NULL,
+    block_timestamp INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS launches (
+    token_address TEXT PRIMARY KEY,
+    quote_token TEXT NOT NULL,
+    exchange_name TEXT NOT NULL,
+    block_number INTEGER NOT NULL,
+    block_timestamp INTEGER NOT NULL,
+    coin_info TEXT,
+    holders_data TEXT
+);
+
+CREATE INDEX IF NOT EXISTS launches_by_time ON launches (block_timestamp DESC);
+
+-- One tag per curator and token, re-tagging replaces the previous choice
+CREATE TABLE IF NOT EXISTS launch_tags (
+    token_address TEXT NOT NULL,
+    curator_id INTEGER NOT NULL,
+    tag TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (token_address, curator_id)
+);
+
+CREATE TABLE IF NOT EXISTS blacklist (
+    address TEXT PRIMARY KEY,
+    reason TEXT,
+    created_at INTEGER NOT NULL
+);
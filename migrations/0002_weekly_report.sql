@@ -0,0 +1,12 @@
+-- Price of a launch about a day after it, the basis of the weekly report's performance figures
+CREATE TABLE IF NOT EXISTS launch_snapshots (
+    token_address TEXT PRIMARY KEY,
+    price REAL NOT NULL,
+    captured_at INTEGER NOT NULL
+);
+
+-- Weeks whose report was posted, so restarts don't post it twice
+CREATE TABLE IF NOT EXISTS weekly_reports (
+    week_start INTEGER PRIMARY KEY,
+    posted_at INTEGER NOT NULL
+);
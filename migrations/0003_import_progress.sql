@@ -0,0 +1,9 @@
+-- Progress of the archive import per factory, so an interrupted import resumes where it stopped
+CREATE TABLE IF NOT EXISTS import_progress (
+    factory TEXT PRIMARY KEY,
+    next_block INTEGER NOT NULL,
+    creations INTEGER NOT NULL,
+    launches INTEGER NOT NULL,
+    failed INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
@@ -0,0 +1,19 @@
+-- Simulated buys made with /papertrade, valued by re-quoting the token
+CREATE TABLE IF NOT EXISTS paper_positions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    chat_id INTEGER NOT NULL,
+    token_address TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    usd_amount REAL NOT NULL,
+    -- USD per raw unit at the time of the buy, and the raw amount it bought
+    entry_price REAL NOT NULL,
+    quantity REAL NOT NULL,
+    opened_at INTEGER NOT NULL,
+    -- Latest price from the valuation job
+    last_price REAL,
+    valued_at INTEGER,
+    exit_price REAL,
+    closed_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_paper_positions_chat ON paper_positions (chat_id, closed_at);
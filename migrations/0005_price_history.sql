@@ -0,0 +1,10 @@
+-- Periodic quotes of recently launched tokens, drawn as charts in alerts and /sniQ replies
+CREATE TABLE IF NOT EXISTS price_history (
+    token_address TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    -- USD per raw unit, like MemecoinInfo.price
+    price REAL NOT NULL,
+    PRIMARY KEY (token_address, recorded_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_price_history_time ON price_history (recorded_at);
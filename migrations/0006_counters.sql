@@ -0,0 +1,6 @@
+-- Running totals shown on the public stats page, kept across restarts
+CREATE TABLE IF NOT EXISTS counters (
+    name TEXT PRIMARY KEY,
+    value INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
@@ -0,0 +1,9 @@
+-- Wallets chats follow with /follow, indexed by wallet to find who to tell about a transfer
+CREATE TABLE IF NOT EXISTS wallet_follows (
+    chat_id INTEGER NOT NULL,
+    wallet TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (chat_id, wallet)
+);
+
+CREATE INDEX IF NOT EXISTS idx_wallet_follows_wallet ON wallet_follows (wallet);
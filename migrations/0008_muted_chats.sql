@@ -0,0 +1,5 @@
+-- Chats an admin muted with /admin mute, they get no messages and their commands are ignored
+CREATE TABLE IF NOT EXISTS muted_chats (
+    chat_id INTEGER PRIMARY KEY,
+    created_at INTEGER NOT NULL
+);
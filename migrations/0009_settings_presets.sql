@@ -0,0 +1,6 @@
+-- Settings shared with /share_settings, imported by code with /import_settings
+CREATE TABLE IF NOT EXISTS settings_presets (
+    code TEXT PRIMARY KEY,
+    payload TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
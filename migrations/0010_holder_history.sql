@@ -0,0 +1,9 @@
+-- Holder counts of fresh launches, sampled while their holder velocity is measured
+CREATE TABLE IF NOT EXISTS holder_history (
+    token_address TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    holders INTEGER NOT NULL,
+    PRIMARY KEY (token_address, recorded_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_holder_history_time ON holder_history (recorded_at);
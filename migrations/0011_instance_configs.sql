@@ -0,0 +1,7 @@
+-- Effective configuration reported by each running replica, compared to catch drift
+CREATE TABLE IF NOT EXISTS instance_configs (
+    instance_id TEXT PRIMARY KEY,
+    config_hash TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    reported_at INTEGER NOT NULL
+);
@@ -0,0 +1,7 @@
+-- Last block whose factory events were handed over, so a restarted indexer resumes after it
+CREATE TABLE IF NOT EXISTS indexer_checkpoints (
+    stream TEXT PRIMARY KEY,
+    source TEXT NOT NULL,
+    block_number INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
@@ -0,0 +1,8 @@
+-- Launch card overrides per chat kind and experiment variant, edited with /admin template
+CREATE TABLE IF NOT EXISTS card_templates (
+    chat_kind TEXT NOT NULL,
+    variant TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (chat_kind, variant)
+);
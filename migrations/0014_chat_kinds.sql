@@ -0,0 +1,6 @@
+-- Telegram type of each chat, so launch cards pick the right template after a restart
+CREATE TABLE IF NOT EXISTS chat_kinds (
+    chat_id INTEGER PRIMARY KEY,
+    chat_kind TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
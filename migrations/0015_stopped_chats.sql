@@ -0,0 +1,5 @@
+-- Chats paused with /stop or that blocked the bot, purged once stopped for the retention period
+CREATE TABLE IF NOT EXISTS stopped_chats (
+    chat_id INTEGER PRIMARY KEY,
+    stopped_at INTEGER NOT NULL
+);
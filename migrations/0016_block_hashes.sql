@@ -0,0 +1,5 @@
+-- Hashes of the blocks the polling indexer checkpointed, compared against the chain to detect reorgs
+CREATE TABLE IF NOT EXISTS block_hashes (
+    block_number INTEGER PRIMARY KEY,
+    block_hash TEXT NOT NULL
+);
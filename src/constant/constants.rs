@@ -1,6 +1,9 @@
 use lazy_static::lazy_static;
 use std::collections::HashMap;
 
+use crate::constant::network::{Network, NETWORK};
+use crate::utils::address::canonicalize_address;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Selector {
     IsMemecoin,
@@ -74,6 +77,19 @@ pub enum TokenSymbol {
     DAI,
 }
 
+impl TokenSymbol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenSymbol::ETH => "ETH",
+            TokenSymbol::USDC => "USDC",
+            TokenSymbol::STRK => "STRK",
+            TokenSymbol::USDT => "USDT",
+            TokenSymbol::WBTC => "WBTC",
+            TokenSymbol::DAI => "DAI",
+        }
+    }
+}
+
 // Define the Token struct to hold token data.
 #[derive(Debug, Clone)]
 pub struct Token {
@@ -117,29 +133,70 @@ pub const USDT: Token = Token {
     usdc_pair: "0x5801bdad32f343035fb242e98d1e9371ae85bc1543962fedea16c59b35bd19b",
 };
 
+pub const WBTC: Token = Token {
+    address: "0x3fe2b97c1fd336e750087d68b9b867997fd64a2661ff3ca5a7c771641e8e7ac",
+    symbol: TokenSymbol::WBTC,
+    decimals: 8,
+    camel_cased: true,
+    usdc_pair: "",
+};
+
+pub const DAI: Token = Token {
+    address: "0x5574eb6b8789a91466f902c380d978e472db68170ff82a5b650b95a58ddf4ad",
+    symbol: TokenSymbol::DAI,
+    decimals: 18,
+    camel_cased: false,
+    usdc_pair: "",
+};
+
 lazy_static! {
     pub static ref QUOTE_TOKENS: HashMap<String, Token> = {
         let mut m = HashMap::new();
+        if NETWORK.network == Network::Sepolia {
+            // ETH and STRK share their mainnet addresses, but there are no JediSwap USDC pairs
+            // to price them against, so launch market caps aren't known there
+            m.insert(get_checksum_address(ETHER.address), Token { usdc_pair: "", ..ETHER });
+            m.insert(get_checksum_address(STRK.address), Token { usdc_pair: "", ..STRK });
+            return m;
+        }
         m.insert(get_checksum_address(ETHER.address), ETHER);
-        m.insert(get_checksum_address(STRK.address), USDC);
-        m.insert(get_checksum_address(USDC.address), STRK);
+        m.insert(get_checksum_address(STRK.address), STRK);
+        m.insert(get_checksum_address(USDC.address), USDC);
         m.insert(get_checksum_address(USDT.address), USDT);
+        m.insert(get_checksum_address(WBTC.address), WBTC);
+        m.insert(get_checksum_address(DAI.address), DAI);
         m
     };
 }
 
 pub fn get_checksum_address(address: &str) -> String {
-    address.to_string()
+    canonicalize_address(address).unwrap_or_else(|| address.to_string())
+}
+
+// Safe listed quote token at an address, whatever its casing or zero padding
+pub fn quote_token(address: &str) -> Option<&'static Token> {
+    QUOTE_TOKENS.get(&get_checksum_address(address))
 }
 
 pub const JEDISWAP_ETH_USDC_POOL: &str =
     "0x04d0390b777b424e43839cd1e744799f3de6c176c7e32c1812a41dbd9c19db6a";
+pub const JEDISWAP_FACTORY: &str = "0x00dad44c139a476c7a17fc8141e6db680e9abc9f56fe249a105094c44382c2fd";
 pub const DECIMALS: u32 = 18;
 pub const LIQUIDITY_LOCK_FOREVER_TIMESTAMP: u64 = 9999999999; // 20/11/2286
 pub const EKUBO_TICK_SIZE: f64 = 1.000001;
-pub const MULTICALL_AGGREGATOR_ADDRESS: &str =
-    "0x01a33330996310a1e3fa1df5b16c1e07f0491fdd20c441126e02613b948f0225";
-pub const MEMECOIN_FACTORY_ADDRESS: &str =
-    "0x01a46467a9246f45c8c340f1f155266a26a71c07bd55d36e8d1c7d0d438a2dbc";
-pub const EXCHANGE_ADDRESS: &str =
-    "0x2bd1cdd5f7f17726ae221845afd9580278eebc732bc136fe59d5d94365effd5";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_token_lookup() {
+        let strk = quote_token("0x04718F5A0FC34CC1AF16A1CDEE98FFB20C31F5CD61D6AB07201858F4287C938D").unwrap();
+        assert!(matches!(strk.symbol, TokenSymbol::STRK));
+        let usdc = quote_token(USDC.address).unwrap();
+        assert_eq!(usdc.decimals, 6);
+        assert!(quote_token(&format!("0x{:0>64}", &WBTC.address[2..])).is_some());
+        assert!(quote_token("0x1234").is_none());
+        assert!(quote_token("not an address").is_none());
+    }
+}
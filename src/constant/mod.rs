@@ -1 +1,2 @@
 pub mod constants;
+pub mod network;
@@ -0,0 +1,193 @@
+use std::fmt;
+
+use lazy_static::lazy_static;
+
+use crate::utils::address::canonicalize_address;
+
+lazy_static! {
+    // Validated at startup by main, so reaching the expect means the env changed underneath us
+    pub static ref NETWORK: NetworkConfig =
+        NetworkConfig::new().expect("Network configuration must be valid");
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Sepolia,
+}
+
+impl Network {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "mainnet" | "sn_main" => Some(Network::Mainnet),
+            "sepolia" | "sn_sepolia" | "testnet" => Some(Network::Sepolia),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Sepolia => "sepolia",
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NetworkError {
+    #[error("Unknown NETWORK {0}, expected mainnet or sepolia")]
+    UnknownNetwork(String),
+    #[error("{0} must be set when running on {1}")]
+    Missing(&'static str, Network),
+    #[error("{0} is not a valid address: {1}")]
+    InvalidAddress(&'static str, String),
+}
+
+// Defaults of one network, None where the deployment has to be configured explicitly
+struct NetworkDefaults {
+    rpc_url: &'static str,
+    ekubo_api_url: &'static str,
+    explorer_url: &'static str,
+    explorer_api: &'static str,
+    memecoin_factory: Option<&'static str>,
+    exchange: Option<&'static str>,
+    multicall_aggregator: Option<&'static str>,
+    ekubo_core: Option<&'static str>,
+    ekubo_positions: Option<&'static str>,
+    ekubo_router: Option<&'static str>,
+    avnu_exchange: Option<&'static str>,
+}
+
+const MAINNET: NetworkDefaults = NetworkDefaults {
+    rpc_url: "https://starknet-mainnet.public.blastapi.io/rpc/v0_7",
+    ekubo_api_url: "https://mainnet-api.ekubo.org",
+    explorer_url: "https://starkscan.co",
+    explorer_api: "https://voyager.online/api/contract",
+    memecoin_factory: Some("0x01a46467a9246f45c8c340f1f155266a26a71c07bd55d36e8d1c7d0d438a2dbc"),
+    exchange: Some("0x2bd1cdd5f7f17726ae221845afd9580278eebc732bc136fe59d5d94365effd5"),
+    multicall_aggregator: Some("0x01a33330996310a1e3fa1df5b16c1e07f0491fdd20c441126e02613b948f0225"),
+    ekubo_core: Some("0x00000005dd3d2f4429af886cd1a3b08289dbcea99a294197e9eb43b0e0325b4b"),
+    ekubo_positions: Some("0x02e0af29598b407c8716b17f6d2795eca1b471413fa03fb145a5e33722184067"),
+    ekubo_router: Some("0x0199741822c2dc722f6f605204f35e56dbc23bceed54818168c4c49e4fb8737e"),
+    avnu_exchange: Some("0x04270219d365d6b017231b52e92b3fb5d7c8378b05e9abc97724537a80e93b0f"),
+};
+
+// Testnet deployments get redeployed, so their addresses always come from the env
+const SEPOLIA: NetworkDefaults = NetworkDefaults {
+    rpc_url: "https://starknet-sepolia.public.blastapi.io/rpc/v0_7",
+    ekubo_api_url: "https://sepolia-api.ekubo.org",
+    explorer_url: "https://sepolia.starkscan.co",
+    explorer_api: "https://sepolia.voyager.online/api/contract",
+    memecoin_factory: None,
+    exchange: None,
+    multicall_aggregator: None,
+    ekubo_core: None,
+    ekubo_positions: None,
+    ekubo_router: None,
+    avnu_exchange: None,
+};
+
+// Chain specific addresses and endpoints, picked with NETWORK and overridable one by one
+#[derive(Debug, Clone)]
+pub struct NetworkConfig {
+    pub network: Network,
+    pub rpc_url: String,
+    pub ekubo_api_url: String,
+    pub explorer_url: String,
+    pub explorer_api: String,
+    pub memecoin_factory: String,
+    pub exchange: String,
+    pub multicall_aggregator: String,
+    pub ekubo_core: String,
+    pub ekubo_positions: String,
+    pub ekubo_router: String,
+    pub avnu_exchange: String,
+}
+
+impl NetworkConfig {
+    pub fn new() -> Result<Self, NetworkError> {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self, NetworkError> {
+        let network = match var("NETWORK") {
+            Some(name) => Network::parse(&name).ok_or(NetworkError::UnknownNetwork(name))?,
+            None => Network::Mainnet,
+        };
+        let defaults = match network {
+            Network::Mainnet => MAINNET,
+            Network::Sepolia => SEPOLIA,
+        };
+        let url = |key: &str, default: &str| {
+            var(key)
+                .map(|url| url.trim_end_matches('/').to_string())
+                .unwrap_or_else(|| default.to_string())
+        };
+        let address = |key: &'static str, default: Option<&str>| {
+            let value = var(key)
+                .or(default.map(String::from))
+                .ok_or(NetworkError::Missing(key, network))?;
+            canonicalize_address(&value).ok_or(NetworkError::InvalidAddress(key, value))
+        };
+        // Deployments set up before the factory was configurable point kanshi at it with CONTRACT_ADDRESS
+        let factory = var("CONTRACT_ADDRESS").or(defaults.memecoin_factory.map(String::from));
+
+        Ok(Self {
+            network,
+            // Overridden by STARKNET_RPC_URLS, see RpcConfig
+            rpc_url: defaults.rpc_url.to_string(),
+            ekubo_api_url: url("EKUBO_API_URL", defaults.ekubo_api_url),
+            explorer_url: url("EXPLORER", defaults.explorer_url),
+            explorer_api: url("EXPLORER_API", defaults.explorer_api),
+            memecoin_factory: address("MEMECOIN_FACTORY_ADDRESS", factory.as_deref())?,
+            exchange: address("EXCHANGE_ADDRESS", defaults.exchange)?,
+            multicall_aggregator: address("MULTICALL_AGGREGATOR_ADDRESS", defaults.multicall_aggregator)?,
+            ekubo_core: address("EKUBO_CORE_ADDRESS", defaults.ekubo_core)?,
+            ekubo_positions: address("EKUBO_POSITIONS_ADDRESS", defaults.ekubo_positions)?,
+            ekubo_router: address("EKUBO_ROUTER_ADDRESS", defaults.ekubo_router)?,
+            avnu_exchange: address("AVNU_EXCHANGE_ADDRESS", defaults.avnu_exchange)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config(vars: &[(&str, &str)]) -> Result<NetworkConfig, NetworkError> {
+        let vars: HashMap<String, String> =
+            vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        NetworkConfig::from_vars(|key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn test_sepolia_requires_explicit_addresses() {
+        let mainnet = config(&[]).unwrap();
+        assert_eq!(mainnet.network, Network::Mainnet);
+        assert_eq!(mainnet.ekubo_api_url, "https://mainnet-api.ekubo.org");
+
+        assert!(matches!(
+            config(&[("NETWORK", "sepolia")]),
+            Err(NetworkError::Missing("MEMECOIN_FACTORY_ADDRESS", Network::Sepolia))
+        ));
+        assert!(matches!(
+            config(&[("NETWORK", "goerli")]),
+            Err(NetworkError::UnknownNetwork(_))
+        ));
+
+        let overridden = config(&[("MEMECOIN_FACTORY_ADDRESS", "0x0abc")]).unwrap();
+        assert_eq!(overridden.memecoin_factory, canonicalize_address("0xabc").unwrap());
+        // kanshi's CONTRACT_ADDRESS still picks the factory, unless it is set explicitly
+        let legacy = config(&[("CONTRACT_ADDRESS", "0xdef")]).unwrap();
+        assert_eq!(legacy.memecoin_factory, canonicalize_address("0xdef").unwrap());
+        let both = config(&[("CONTRACT_ADDRESS", "0xdef"), ("MEMECOIN_FACTORY_ADDRESS", "0xabc")]).unwrap();
+        assert_eq!(both.memecoin_factory, canonicalize_address("0xabc").unwrap());
+    }
+}
@@ -0,0 +1,219 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use apibara_core::starknet::v1alpha2::Event;
+use async_trait::async_trait;
+use kanshi::{config::Config, dna::IndexerService, utils::conversions::apibara_field_as_felt};
+use starknet_core::types::Felt;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+
+use super::checkpoint::{state_file_block, Checkpoints};
+use super::streams::{StreamDecoder, StreamSpec};
+use super::{decode_event, decode_transfer, EventStream, IndexedEvent};
+use crate::storage::BlockRef;
+use crate::utils::call::get_block_timestamp;
+
+// One named stream and the kanshi configuration following its contract
+pub struct NamedStream {
+    pub spec: StreamSpec,
+    pub config: Config,
+    // Whether the stream's kanshi cursor is the factory checkpoint, true for the factory's events
+    pub checkpointed: bool,
+    // Where kanshi keeps the stream's cursor
+    pub state_file: String,
+    // Block the stream starts from, None for the chain head
+    pub start_block: Option<u64>,
+}
+
+// Events streamed by Apibara through kanshi, one IndexerService per named stream, all funneled
+// into the same channel. Block tracking, and so reorg handling, lives in kanshi: the simplified
+// stream hands us bare events, so their block is read from the stream's cursor, and the
+// checkpointed stream's cursor in the shared state file is copied to the checkpoint as it moves
+pub struct ApibaraStream {
+    // The services consume their configuration, so the stream can only run once
+    streams: Vec<NamedStream>,
+    checkpoints: Arc<Checkpoints>,
+    interval: Duration,
+}
+
+impl ApibaraStream {
+    pub fn new(streams: Vec<NamedStream>, checkpoints: Arc<Checkpoints>, interval: Duration) -> Self {
+        Self {
+            streams,
+            checkpoints,
+            interval,
+        }
+    }
+}
+
+#[async_trait]
+impl EventStream for ApibaraStream {
+    fn name(&self) -> &'static str {
+        "apibara"
+    }
+
+    // Runs until any named stream stops, the source taking over then covers them all
+    async fn run(&mut self, tx: &mpsc::UnboundedSender<IndexedEvent>) -> Result<(), anyhow::Error> {
+        if self.streams.is_empty() {
+            return Err(anyhow::Error::msg("Apibara stream already ran"));
+        }
+        let mut services = JoinSet::new();
+        for stream in self.streams.drain(..) {
+            let tx = tx.clone();
+            let checkpoints = Arc::clone(&self.checkpoints);
+            let interval = self.interval;
+            services.spawn(async move {
+                let name = stream.spec.name.clone();
+                let result = run_named(stream, &tx, checkpoints, interval).await;
+                (name, result)
+            });
+        }
+        match services.join_next().await {
+            Some(Ok((name, Ok(())))) => {
+                println!("Apibara stream {} ended", name);
+                Ok(())
+            }
+            Some(Ok((name, Err(e)))) => Err(e.context(format!("Apibara stream {}", name))),
+            Some(Err(e)) => Err(anyhow::Error::msg(format!("Apibara stream panicked: {}", e))),
+            None => Ok(()),
+        }
+    }
+}
+
+async fn run_named(
+    stream: NamedStream,
+    tx: &mpsc::UnboundedSender<IndexedEvent>,
+    checkpoints: Arc<Checkpoints>,
+    interval: Duration,
+) -> Result<(), anyhow::Error> {
+    let NamedStream {
+        spec,
+        config,
+        checkpointed,
+        state_file,
+        start_block,
+    } = stream;
+    let mut service = IndexerService::new(config).await;
+    let (raw_tx, mut raw_rx) = mpsc::unbounded_channel::<Event>();
+
+    let forward = async {
+        while let Some(event) = raw_rx.recv().await {
+            forward_event(&spec, &state_file, start_block, event, tx).await;
+        }
+    };
+    let checkpoint = async {
+        loop {
+            tokio::time::sleep(interval).await;
+            if checkpointed {
+                checkpoints.record_state_file("apibara").await;
+            }
+        }
+    };
+    let result = tokio::select! {
+        result = service.run_forever_simplified(&raw_tx) => result,
+        _ = forward => Ok(()),
+        _ = checkpoint => Ok(()),
+    };
+    // Events received right before the service stopped still go through
+    while let Ok(event) = raw_rx.try_recv() {
+        forward_event(&spec, &state_file, start_block, event, tx).await;
+    }
+    if checkpointed {
+        checkpoints.record_state_file("apibara").await;
+    }
+    result
+}
+
+async fn forward_event(
+    spec: &StreamSpec,
+    state_file: &str,
+    start_block: Option<u64>,
+    event: Event,
+    tx: &mpsc::UnboundedSender<IndexedEvent>,
+) {
+    let keys: Vec<Felt> = event.keys.iter().map(apibara_field_as_felt).collect();
+    // kanshi streams every event of the contract, the stream's filter keeps the ones it decodes
+    if !keys.first().is_some_and(|selector| spec.selectors.contains(selector)) {
+        return;
+    }
+    let data: Vec<Felt> = event.data.iter().map(apibara_field_as_felt).collect();
+    let from_address = event.from_address.as_ref().map(apibara_field_as_felt);
+    let decoded = match spec.decoder {
+        StreamDecoder::Events => decode_event(from_address.as_ref(), &keys, data),
+        StreamDecoder::Transfers => {
+            decode_transfer(from_address.unwrap_or(spec.contract), &keys, &data).map(Some)
+        }
+    };
+    match decoded {
+        Ok(Some(event)) => {
+            let _ = tx.send(IndexedEvent {
+                event,
+                block: stream_block(state_file, start_block).await,
+                stream: spec.name.clone(),
+                _processed: None,
+            });
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("Error decoding {} event ❗️ {:#}", spec.name, e),
+    }
+}
+
+// Block a stream is reading, the one after the last block kanshi processed or where the stream
+// started. None when it started at the chain head and hasn't processed a block yet
+async fn stream_block(state_file: &str, start_block: Option<u64>) -> Option<BlockRef> {
+    let block_number = state_file_block(state_file).await.map(|block| block + 1).or(start_block)?;
+    match get_block_timestamp(block_number).await {
+        Ok(block_timestamp) => Some(BlockRef {
+            block_number,
+            block_timestamp,
+        }),
+        Err(e) => {
+            eprintln!("Error reading the timestamp of block {} ❗️ {:?}", block_number, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::EventType;
+    use crate::utils::call::record_block_timestamp;
+    use crate::utils::event_parser::TransferEvent;
+
+    #[tokio::test]
+    async fn test_replayed_events_are_historical() {
+        let state_file = std::env::temp_dir()
+            .join(format!("apibara_state_{}.json", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let now = 10_000_000;
+        record_block_timestamp(700_000, now - 86_400);
+        record_block_timestamp(900_001, now - 12);
+
+        // A stream catching up from its starting block replays old events
+        let event = |block: Option<BlockRef>| IndexedEvent {
+            event: EventType::Transfer(TransferEvent {
+                token_address: Felt::ONE,
+                from: Felt::TWO,
+                to: Felt::THREE,
+                amount: "1".to_string(),
+            }),
+            block,
+            stream: "factory".to_string(),
+            _processed: None,
+        };
+        let replayed = event(stream_block(&state_file, Some(700_000)).await);
+        assert_eq!(replayed.block.map(|block| block.block_number), Some(700_000));
+        assert!(replayed.is_historical(1_800, now));
+
+        // Once kanshi's cursor is at the chain head its events are fresh
+        std::fs::write(&state_file, r#"{"last_processed_block":900000}"#).unwrap();
+        let live = event(stream_block(&state_file, Some(700_000)).await);
+        let _ = std::fs::remove_file(&state_file);
+        assert_eq!(live.block.map(|block| block.block_number), Some(900_001));
+        assert!(!live.is_historical(1_800, now));
+        assert!(stream_block(&state_file, None).await.is_none());
+    }
+}
@@ -0,0 +1,152 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{Storage, StorageError};
+
+// Factory events share one checkpoint whichever source indexed them
+const FACTORY_STREAM: &str = "factory";
+// Checkpointed block hashes kept to find where a reorg forked off, one per polled range
+const TRACKED_BLOCK_HASHES: usize = 64;
+
+// Same layout as the state kanshi keeps, so either source picks up where the other stopped
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexerState {
+    last_processed_block: u64,
+}
+
+// Where factory event indexing resumes after a restart. The checkpoint lives in the event
+// history database, the state file is still read and written for kanshi and older releases
+pub struct Checkpoints {
+    storage: Arc<Storage>,
+    state_file: String,
+    starting_block: Option<u64>,
+    // Set until the first stream has started over from `starting_block`
+    full_backfill: AtomicBool,
+}
+
+impl Checkpoints {
+    pub fn new(
+        storage: Arc<Storage>,
+        state_file: String,
+        starting_block: Option<u64>,
+        full_backfill: bool,
+    ) -> Self {
+        Self {
+            storage,
+            state_file,
+            starting_block,
+            full_backfill: AtomicBool::new(full_backfill),
+        }
+    }
+
+    pub fn starting_block(&self) -> Option<u64> {
+        self.starting_block
+    }
+
+    // Last block handed over to the consumer, from whichever source got furthest
+    pub async fn last_block(&self) -> Option<u64> {
+        let stored = match self.storage.checkpoint(FACTORY_STREAM).await {
+            Ok(checkpoint) => checkpoint.map(|(_, block)| block),
+            Err(e) => {
+                eprintln!("Failed to load indexer checkpoint: {}", e);
+                None
+            }
+        };
+        stored.max(state_file_block(&self.state_file).await)
+    }
+
+    // Block the next stream starts from, None when there's no checkpoint yet. A full backfill
+    // ignores the checkpoint once, streams taking over later resume from where it got to
+    pub async fn resume_block(&self) -> Option<u64> {
+        let checkpoint = match self.full_backfill.swap(false, Ordering::SeqCst) {
+            true => None,
+            false => self.last_block().await,
+        };
+        checkpoint.map(|block| block + 1).or(self.starting_block)
+    }
+
+    pub async fn save(&self, source: &str, block_number: u64) {
+        if let Err(e) = self.storage.save_checkpoint(FACTORY_STREAM, source, block_number).await {
+            eprintln!("Failed to save indexer checkpoint: {}", e);
+        }
+        let state = IndexerState {
+            last_processed_block: block_number,
+        };
+        let state = serde_json::to_string(&state).unwrap_or_default();
+        if let Err(e) = tokio::fs::write(&self.state_file, state).await {
+            eprintln!("Failed to save indexer state: {}", e);
+        }
+    }
+
+    pub async fn record_block_hash(&self, block_number: u64, block_hash: &str) {
+        if let Err(e) = self.storage.save_block_hash(block_number, block_hash, TRACKED_BLOCK_HASHES).await {
+            eprintln!("Failed to save block hash: {}", e);
+        }
+    }
+
+    // Hashes of the latest checkpointed blocks, newest first
+    pub async fn block_hashes(&self) -> Result<Vec<(u64, String)>, StorageError> {
+        self.storage.block_hashes().await
+    }
+
+    // Drops what was recorded after `block_number` and resumes right after it
+    pub async fn rollback(&self, source: &str, block_number: u64) -> Result<u64, StorageError> {
+        let dropped = self.storage.rollback_after(block_number).await?;
+        self.save(source, block_number).await;
+        Ok(dropped)
+    }
+
+    // Copies the cursor kanshi keeps in the state file into the database, as kanshi only
+    // hands us bare events
+    pub async fn record_state_file(&self, source: &str) {
+        let block_number = match state_file_block(&self.state_file).await {
+            Some(block_number) => block_number,
+            None => return,
+        };
+        let stored = self.storage.checkpoint(FACTORY_STREAM).await.ok().flatten();
+        if stored.is_none_or(|(_, block)| block_number > block) {
+            if let Err(e) = self.storage.save_checkpoint(FACTORY_STREAM, source, block_number).await {
+                eprintln!("Failed to save indexer checkpoint: {}", e);
+            }
+        }
+    }
+}
+
+// Last block processed according to a kanshi state file
+pub async fn state_file_block(state_file: &str) -> Option<u64> {
+    let state = tokio::fs::read_to_string(state_file).await.ok()?;
+    serde_json::from_str::<IndexerState>(&state)
+        .ok()
+        .map(|state| state.last_processed_block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StorageConfig;
+
+    #[tokio::test]
+    async fn test_resume_block() {
+        let storage = Arc::new(Storage::connect(StorageConfig::in_memory()).await.unwrap());
+        let state_file = std::env::temp_dir()
+            .join(format!("indexer_state_{}.json", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let checkpoints = Checkpoints::new(Arc::clone(&storage), state_file.clone(), Some(100), true);
+        checkpoints.save("polling", 500).await;
+
+        // The backfill starts over once, then the checkpoint wins again
+        assert_eq!(checkpoints.resume_block().await, Some(100));
+        assert_eq!(checkpoints.resume_block().await, Some(501));
+
+        // kanshi moving the state file forward is picked up
+        std::fs::write(&state_file, r#"{"last_processed_block":800}"#).unwrap();
+        checkpoints.record_state_file("apibara").await;
+        let stored = storage.checkpoint(FACTORY_STREAM).await.unwrap();
+        assert_eq!(stored, Some(("apibara".to_string(), 800)));
+        let _ = std::fs::remove_file(&state_file);
+        assert_eq!(checkpoints.resume_block().await, Some(801));
+    }
+}
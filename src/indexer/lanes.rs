@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::{oneshot, Semaphore};
+use tokio::task::JoinSet;
+
+// Keeps the events of a token in the order they were indexed while events of different tokens
+// are processed concurrently. Each event waits for the previous event of its token to be done
+#[derive(Default)]
+pub struct TokenLanes {
+    // Signal of the last event handed out for each token, it closes once that event is done
+    tails: HashMap<String, oneshot::Receiver<()>>,
+}
+
+impl TokenLanes {
+    // Takes the next turn in the token's lane, events must enter in the order they are processed
+    pub fn enter(&mut self, token_address: String) -> LaneTurn {
+        let (done, tail) = oneshot::channel();
+        let previous = self.tails.insert(token_address, tail);
+        LaneTurn {
+            previous,
+            _done: done,
+        }
+    }
+
+    // Forgets lanes whose last event is done, returns how many are still busy
+    pub fn prune(&mut self) -> usize {
+        self.tails
+            .retain(|_, tail| matches!(tail.try_recv(), Err(oneshot::error::TryRecvError::Empty)));
+        self.tails.len()
+    }
+}
+
+pub struct LaneTurn {
+    previous: Option<oneshot::Receiver<()>>,
+    // Dropped with the turn once the event is processed, which lets the next one in
+    _done: oneshot::Sender<()>,
+}
+
+impl LaneTurn {
+    // Resolves once every earlier event of the token is done
+    pub async fn wait(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            let _ = previous.await;
+        }
+    }
+}
+
+// Work spawned but not done yet, per worker. Once reached, spawning waits for some to finish so
+// a burst of events stays in the channel instead of piling up as tasks
+const PENDING_PER_WORKER: usize = 16;
+
+// Work spread over a bounded number of workers, the work of one token done in the order it was
+// spawned. Work waiting for its token's turn doesn't hold a worker
+pub struct WorkerPool {
+    size: usize,
+    workers: Arc<Semaphore>,
+    pending: Arc<Semaphore>,
+    lanes: TokenLanes,
+    tasks: JoinSet<()>,
+}
+
+impl WorkerPool {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            workers: Arc::new(Semaphore::new(size)),
+            pending: Arc::new(Semaphore::new(size * PENDING_PER_WORKER)),
+            lanes: TokenLanes::default(),
+            tasks: JoinSet::new(),
+        }
+    }
+
+    // Runs `work` once the token's earlier work is done and a worker is free. Waits first while
+    // too much work is already pending
+    pub async fn spawn<F>(&mut self, token_address: String, work: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.reap();
+        let pending = match Arc::clone(&self.pending).acquire_owned().await {
+            Ok(pending) => pending,
+            Err(_) => return,
+        };
+        let mut turn = self.lanes.enter(token_address);
+        let workers = Arc::clone(&self.workers);
+        self.tasks.spawn(async move {
+            turn.wait().await;
+            let permit = match workers.acquire_owned().await {
+                Ok(permit) => permit,
+                Err(_) => return,
+            };
+            work.await;
+            drop(permit);
+            drop(turn);
+            drop(pending);
+        });
+    }
+
+    // Work spawned but not running yet, waiting for its turn or a worker
+    pub fn waiting(&mut self) -> usize {
+        self.reap();
+        let busy = self.size - self.workers.available_permits();
+        self.tasks.len().saturating_sub(busy)
+    }
+
+    pub async fn join_all(&mut self) {
+        while self.tasks.join_next().await.is_some() {}
+    }
+
+    fn reap(&mut self) {
+        while let Some(result) = self.tasks.try_join_next() {
+            if let Err(e) = result {
+                eprintln!("Event worker panicked ❗️ {}", e);
+            }
+        }
+        self.lanes.prune();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lanes_keep_token_order() {
+        let mut lanes = TokenLanes::default();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks = tokio::task::JoinSet::new();
+        // The first event of token a is the slowest, b isn't held back by it but a's second event is
+        for (token, label, delay) in [("a", "a1", 50), ("b", "b1", 0), ("a", "a2", 0)] {
+            let mut turn = lanes.enter(token.to_string());
+            let order = Arc::clone(&order);
+            tasks.spawn(async move {
+                turn.wait().await;
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                order.lock().unwrap().push(label);
+            });
+        }
+        assert_eq!(lanes.prune(), 2);
+        while tasks.join_next().await.is_some() {}
+        assert_eq!(*order.lock().unwrap(), vec!["b1", "a1", "a2"]);
+        assert_eq!(lanes.prune(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_pool_keeps_workers_for_ready_tokens() {
+        let mut pool = WorkerPool::new(2);
+        let order = Arc::new(Mutex::new(Vec::new()));
+        // a2 waits for a1 without a worker, so b1 gets the second one right away
+        for (token, label, delay) in [("a", "a1", 50), ("a", "a2", 0), ("b", "b1", 0)] {
+            let order = Arc::clone(&order);
+            pool.spawn(token.to_string(), async move {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                order.lock().unwrap().push(label);
+            })
+            .await;
+        }
+        assert_eq!(pool.waiting(), 3);
+        pool.join_all().await;
+        assert_eq!(*order.lock().unwrap(), vec!["b1", "a1", "a2"]);
+        assert_eq!(pool.waiting(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_waits_while_too_much_is_pending() {
+        let mut pool = WorkerPool::new(1);
+        for i in 0..PENDING_PER_WORKER {
+            pool.spawn(format!("0x{}", i), tokio::time::sleep(Duration::from_millis(100)))
+                .await;
+        }
+        let spawn = pool.spawn("0xfull".to_string(), async {});
+        assert!(tokio::time::timeout(Duration::from_millis(20), spawn).await.is_err());
+        // A worker finishes its work within the 100ms, which makes room
+        let spawn = pool.spawn("0xfull".to_string(), async {});
+        assert!(tokio::time::timeout(Duration::from_millis(500), spawn).await.is_ok());
+        pool.join_all().await;
+    }
+}
@@ -0,0 +1,305 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use starknet_core::types::Felt;
+use tokio::sync::mpsc;
+
+use crate::constant::network::NETWORK;
+use crate::storage::{BlockRef, Storage};
+use crate::utils::address::to_canonical_hex;
+use crate::utils::call::get_latest_block;
+use crate::utils::event_parser::{CreationEvent, LaunchEvent, TransferEvent};
+use crate::watcher::wallets::WalletTracker;
+
+pub mod apibara;
+pub mod checkpoint;
+pub mod lanes;
+pub mod polling;
+pub mod streams;
+pub mod subscriptions;
+pub mod transfers;
+
+lazy_static::lazy_static! {
+    // Validated at startup by main, like NETWORK
+    pub static ref SUBSCRIPTIONS: subscriptions::Subscriptions =
+        subscriptions::Subscriptions::new(&NETWORK.memecoin_factory)
+            .expect("Event subscriptions must be valid");
+}
+
+#[derive(Debug)]
+pub enum EventType {
+    Creation(CreationEvent),
+    Launch(LaunchEvent),
+    // Only transfers involving a followed wallet are indexed
+    Transfer(TransferEvent),
+}
+
+// A decoded factory event, whichever source it came from
+#[derive(Debug)]
+pub struct IndexedEvent {
+    pub event: EventType,
+    // None when the source doesn't say which block the event is from
+    pub block: Option<BlockRef>,
+    // Named stream or source the event came from
+    pub stream: String,
+    // Dropped with the event once it is processed, so its source can tell when a block range is done
+    pub _processed: Option<mpsc::Sender<()>>,
+}
+
+impl IndexedEvent {
+    // Whether the event's block is older than `freshness_secs`. Events without a block are
+    // stamped with the chain head, so they count as fresh
+    pub fn is_historical(&self, freshness_secs: u64, now: u64) -> bool {
+        match &self.block {
+            Some(block) => freshness_secs > 0 && now.saturating_sub(block.block_timestamp) > freshness_secs,
+            None => false,
+        }
+    }
+
+    // Token the event is about, the events of one token are processed in order
+    pub fn token_address(&self) -> String {
+        to_canonical_hex(match &self.event {
+            EventType::Creation(event) => &event.memecoin_address,
+            EventType::Launch(event) => &event.memecoin_address,
+            EventType::Transfer(event) => &event.token_address,
+        })
+    }
+}
+
+// A source of factory events
+#[async_trait]
+pub trait EventStream: Send {
+    fn name(&self) -> &'static str;
+
+    // Sends events until the source gives out, an error means the next source should take over
+    async fn run(&mut self, tx: &mpsc::UnboundedSender<IndexedEvent>) -> Result<(), anyhow::Error>;
+}
+
+// Decodes a subscribed event from its keys and data, other events yield None
+pub fn decode_event(
+    from_address: Option<&Felt>,
+    keys: &[Felt],
+    data: Vec<Felt>,
+) -> Result<Option<EventType>, anyhow::Error> {
+    SUBSCRIPTIONS.decode(from_address, keys, data)
+}
+
+// Decodes a Transfer emitted by `token_address`
+pub fn decode_transfer(
+    token_address: Felt,
+    keys: &[Felt],
+    data: &[Felt],
+) -> Result<EventType, anyhow::Error> {
+    Ok(EventType::Transfer(
+        TransferEvent::from_starknet_event(token_address, keys, data).context("Parsing Transfer Event")?,
+    ))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventSource {
+    Apibara,
+    Polling,
+}
+
+// Configuration for where factory events come from
+#[derive(Clone)]
+pub struct IndexerConfig {
+    source: EventSource,
+    // Whether polling takes over once the Apibara stream ends, e.g. when credits run out
+    fallback: bool,
+    poll_interval: Duration,
+    state_file: String,
+    // Where indexing starts without a checkpoint, None for the chain head
+    starting_block: Option<u64>,
+    // Start over from `starting_block` instead of resuming from the checkpoint
+    pub full_backfill: bool,
+    // Events processed at once, so a token that is slow to aggregate doesn't hold back the others
+    pub aggregation_workers: usize,
+    // Events from blocks older than this are historical, e.g. when catching up from an old
+    // checkpoint: they are recorded but never alerted about. 0 alerts on everything
+    pub freshness_secs: u64,
+}
+
+impl IndexerConfig {
+    pub fn new() -> Self {
+        let poll_interval = std::env::var("POLL_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(10);
+
+        Self {
+            source: match std::env::var("EVENT_SOURCE").unwrap_or_default().to_lowercase().as_str() {
+                "polling" | "rpc" => EventSource::Polling,
+                _ => EventSource::Apibara,
+            },
+            fallback: std::env::var("EVENT_SOURCE_FALLBACK")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(true),
+            poll_interval: Duration::from_secs(poll_interval),
+            state_file: std::env::var("INDEXER_STATE_FILE")
+                .unwrap_or_else(|_| "indexer_state.json".to_string()),
+            starting_block: std::env::var("STARTING_BLOCK").ok().and_then(|v| v.parse::<u64>().ok()),
+            full_backfill: std::env::var("INDEXER_FULL_BACKFILL")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            aggregation_workers: std::env::var("AGGREGATION_WORKERS")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|workers| *workers > 0)
+                .unwrap_or(4),
+            freshness_secs: std::env::var("ALERT_FRESHNESS_MINUTES")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(30)
+                * 60,
+        }
+    }
+}
+
+// Where factory event indexing resumes, shared by the streams so each picks up after the last
+pub fn checkpoints(config: &IndexerConfig, storage: Arc<Storage>) -> Arc<checkpoint::Checkpoints> {
+    Arc::new(checkpoint::Checkpoints::new(
+        storage,
+        config.state_file.clone(),
+        config.starting_block,
+        config.full_backfill,
+    ))
+}
+
+// Event streams to run one after the other, the configured source first
+pub async fn event_streams(
+    config: &IndexerConfig,
+    checkpoints: &Arc<checkpoint::Checkpoints>,
+) -> Vec<Box<dyn EventStream>> {
+    let polling = || -> Box<dyn EventStream> {
+        Box::new(polling::PollingStream::new(config.poll_interval, Arc::clone(checkpoints)))
+    };
+    if config.source == EventSource::Polling {
+        return vec![polling()];
+    }
+    let mut streams: Vec<Box<dyn EventStream>> = Vec::new();
+    match apibara_streams(config, checkpoints).await {
+        Ok(named) => streams.push(Box::new(apibara::ApibaraStream::new(
+            named,
+            Arc::clone(checkpoints),
+            config.poll_interval,
+        ))),
+        Err(e) => eprintln!("Failed to load Apibara configuration ❗️ {:#}", e),
+    }
+    if config.fallback || streams.is_empty() {
+        streams.push(polling());
+    }
+    streams
+}
+
+// A kanshi configuration per named stream. The settings the streams share, e.g. the Apibara key,
+// are loaded once, each stream's contract, starting block and state file are set on its own copy.
+// The factory's event stream keeps the shared state file and so the checkpoint, the others get a
+// state file of their own
+async fn apibara_streams(
+    config: &IndexerConfig,
+    checkpoints: &Arc<checkpoint::Checkpoints>,
+) -> Result<Vec<apibara::NamedStream>, anyhow::Error> {
+    let specs = streams::streams_from_env(&SUBSCRIPTIONS, &NETWORK.memecoin_factory)?;
+    let factory = Felt::from_hex(&NETWORK.memecoin_factory)?;
+    let checkpointed = streams::checkpointed_stream(&specs, &factory)?;
+    // Without a checkpoint the streams start at the chain head, set explicitly so the block of
+    // their events is known from the start
+    let resume_block = match checkpoints.resume_block().await {
+        Some(block) => Some(block),
+        None => get_latest_block().await.ok().map(|(block, _)| block),
+    };
+    let shared = kanshi::config::Config::new().context("Loading the Apibara configuration")?;
+
+    let mut named = Vec::new();
+    for (index, spec) in specs.into_iter().enumerate() {
+        let state_file = match index == checkpointed {
+            true => config.state_file.clone(),
+            false => format!("{}.{}", config.state_file, spec.name),
+        };
+        let start_block = spec.start_block(resume_block);
+        let apibara_config = kanshi::config::Config {
+            contract_address: to_canonical_hex(&spec.contract),
+            starting_block: start_block.unwrap_or(shared.starting_block),
+            indexer_state_file: state_file.clone(),
+            ..shared.clone()
+        };
+        named.push(apibara::NamedStream {
+            spec,
+            config: apibara_config,
+            checkpointed: index == checkpointed,
+            state_file,
+            start_block,
+        });
+    }
+    Ok(named)
+}
+
+// Transfers of followed wallets are polled at the same pace as factory events
+pub fn transfer_stream(config: &IndexerConfig, wallets: Arc<WalletTracker>) -> transfers::TransferStream {
+    transfers::TransferStream::new(config.poll_interval, wallets)
+}
+
+// Runs the streams in order, moving on to the next one whenever a stream stops
+pub async fn run_event_streams(streams: Vec<Box<dyn EventStream>>, tx: mpsc::UnboundedSender<IndexedEvent>) {
+    for mut stream in streams {
+        println!("Indexing events from {} ✓", stream.name());
+        match stream.run(&tx).await {
+            Ok(()) => println!("Event stream {} ended", stream.name()),
+            Err(e) => eprintln!("Error running event stream {} ❗️ {:#}", stream.name(), e),
+        }
+        if tx.is_closed() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet::core::utils::get_selector_from_name;
+
+    #[test]
+    fn test_decode_transfer() {
+        let selector = get_selector_from_name("Transfer").unwrap();
+        let (token, from, to) = (Felt::from(1u64), Felt::from(2u64), Felt::from(3u64));
+        let amount = [Felt::from(1_500u64), Felt::ZERO];
+        // Same transfer with from and to as keys, then as data
+        for (keys, data) in [
+            (vec![selector, from, to], amount.to_vec()),
+            (vec![selector], vec![from, to, amount[0], amount[1]]),
+        ] {
+            match decode_transfer(token, &keys, &data).unwrap() {
+                EventType::Transfer(transfer) => {
+                    assert_eq!((transfer.from, transfer.to), (from, to));
+                    assert_eq!(transfer.amount, "1500");
+                }
+                other => panic!("Unexpected event {:?}", other),
+            }
+        }
+        assert!(decode_transfer(token, &[selector], &[from]).is_err());
+    }
+
+    #[test]
+    fn test_old_events_are_historical() {
+        let selector = get_selector_from_name("Transfer").unwrap();
+        let keys = [selector, Felt::from(2u64), Felt::from(3u64)];
+        let event = |block_timestamp: Option<u64>| IndexedEvent {
+            event: decode_transfer(Felt::ONE, &keys, &[Felt::ONE, Felt::ZERO]).unwrap(),
+            block: block_timestamp.map(|block_timestamp| BlockRef {
+                block_number: 1,
+                block_timestamp,
+            }),
+            stream: "polling".to_string(),
+            _processed: None,
+        };
+        let now = 10_000;
+        assert!(event(Some(now - 1_801)).is_historical(1_800, now));
+        assert!(!event(Some(now - 60)).is_historical(1_800, now));
+        assert!(!event(None).is_historical(1_800, now));
+        // A threshold of 0 turns the protection off
+        assert!(!event(Some(0)).is_historical(0, now));
+    }
+}
@@ -0,0 +1,198 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use super::checkpoint::Checkpoints;
+use super::{decode_event, EventStream, IndexedEvent, SUBSCRIPTIONS};
+use crate::storage::BlockRef;
+use crate::constant::network::NETWORK;
+use crate::utils::address::to_canonical_hex;
+use crate::utils::call::{get_block_hash, get_block_timestamp, get_deployment_block, get_latest_block};
+
+// Most blocks fetched per poll, so catching up after a long outage happens in steps
+const MAX_BLOCK_RANGE: u64 = 1000;
+
+// Factory events read from RPC nodes with starknet_getEvents, used when Apibara isn't available
+pub struct PollingStream {
+    interval: Duration,
+    checkpoints: Arc<Checkpoints>,
+}
+
+impl PollingStream {
+    pub fn new(interval: Duration, checkpoints: Arc<Checkpoints>) -> Self {
+        Self { interval, checkpoints }
+    }
+
+    // Sends the events of a block range, oldest first, and waits for them to be processed
+    async fn poll(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        tx: &mpsc::UnboundedSender<IndexedEvent>,
+    ) -> Result<(), anyhow::Error> {
+        let (processed, mut all_processed) = mpsc::channel::<()>(1);
+        for event in SUBSCRIPTIONS.events(from_block, to_block).await? {
+            let block_number = match event.block_number {
+                Some(block_number) => block_number,
+                None => continue,
+            };
+            let block_timestamp = get_block_timestamp(block_number).await?;
+            match decode_event(Some(&event.from_address), &event.keys, event.data) {
+                Ok(Some(decoded)) => {
+                    let block = BlockRef {
+                        block_number,
+                        block_timestamp,
+                    };
+                    tx.send(IndexedEvent {
+                        event: decoded,
+                        block: Some(block),
+                        stream: self.name().to_string(),
+                        _processed: Some(processed.clone()),
+                    })?;
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("Error decoding event at {} ❗️ {:#}", block_number, e),
+            }
+        }
+        // Resolves once the last event of the range is dropped by the consumer
+        drop(processed);
+        let _ = all_processed.recv().await;
+        Ok(())
+    }
+
+    // Rolls back to the newest checkpointed block still on the chain once the last one was reorged
+    // out, so the orphaned blocks' events are dropped and the new chain's replayed. Returns the
+    // block polling resumes after
+    async fn check_reorg(&self) -> Result<Option<u64>, anyhow::Error> {
+        let recorded = self.checkpoints.block_hashes().await?;
+        let (newest_block, newest_hash) = match recorded.first() {
+            Some(newest) => newest,
+            None => return Ok(None),
+        };
+        if current_hash(*newest_block).await?.as_ref() == Some(newest_hash) {
+            return Ok(None);
+        }
+
+        let mut on_chain = Vec::with_capacity(recorded.len());
+        for (block_number, _) in &recorded {
+            on_chain.push(current_hash(*block_number).await?);
+        }
+        let fork = fork_point(&recorded, &on_chain);
+        let dropped = self.checkpoints.rollback(self.name(), fork).await?;
+        println!(
+            "Chain reorg after block {}, block {} was orphaned: dropped {} event(s), replaying",
+            fork, newest_block, dropped
+        );
+        Ok(Some(fork))
+    }
+}
+
+async fn current_hash(block_number: u64) -> Result<Option<String>, anyhow::Error> {
+    Ok(get_block_hash(block_number).await?.map(|hash| to_canonical_hex(&hash)))
+}
+
+// Newest recorded block whose hash is still the chain's, recorded hashes newest first alongside
+// the chain's hashes of the same blocks. When none is left the reorg went deeper than the
+// hashes kept, polling then resumes before the oldest of them
+fn fork_point(recorded: &[(u64, String)], on_chain: &[Option<String>]) -> u64 {
+    recorded
+        .iter()
+        .zip(on_chain)
+        .find(|((_, recorded), on_chain)| on_chain.as_ref() == Some(recorded))
+        .map(|((block_number, _), _)| *block_number)
+        .unwrap_or_else(|| recorded.last().map_or(0, |(oldest, _)| oldest.saturating_sub(1)))
+}
+
+#[async_trait]
+impl EventStream for PollingStream {
+    fn name(&self) -> &'static str {
+        "polling"
+    }
+
+    async fn run(&mut self, tx: &mpsc::UnboundedSender<IndexedEvent>) -> Result<(), anyhow::Error> {
+        let mut next_block = self.checkpoints.resume_block().await;
+        loop {
+            if tx.is_closed() {
+                return Ok(());
+            }
+            // Only accepted blocks are read, pending events are picked up once their block is in
+            match get_latest_block().await {
+                Ok((latest_block, _)) => {
+                    if next_block.is_some() {
+                        match self.check_reorg().await {
+                            Ok(Some(fork)) => next_block = Some(fork + 1),
+                            Ok(None) => {}
+                            Err(e) => {
+                                eprintln!("Error checking for a chain reorg ❗️ {:#}", e);
+                                tokio::time::sleep(self.interval).await;
+                                continue;
+                            }
+                        }
+                    }
+                    // Without a checkpoint the factory's whole history is read, its old events
+                    // are recorded as historical
+                    let from_block = match next_block {
+                        Some(block) => block,
+                        None => match get_deployment_block(&NETWORK.memecoin_factory, latest_block).await {
+                            Ok(block) => *next_block.insert(block),
+                            Err(e) => {
+                                eprintln!("Error finding the factory deployment block ❗️ {:?}", e);
+                                tokio::time::sleep(self.interval).await;
+                                continue;
+                            }
+                        },
+                    };
+                    if from_block <= latest_block {
+                        let to_block = latest_block.min(from_block + MAX_BLOCK_RANGE - 1);
+                        // Read before the range, a reorg while it is polled shows up on the next check
+                        let to_block_hash = match current_hash(to_block).await {
+                            Ok(hash) => hash,
+                            Err(e) => {
+                                eprintln!("Error reading the hash of block {} ❗️ {:#}", to_block, e);
+                                None
+                            }
+                        };
+                        match self.poll(from_block, to_block, tx).await {
+                            Ok(()) => {
+                                self.checkpoints.save(self.name(), to_block).await;
+                                if let Some(hash) = to_block_hash {
+                                    self.checkpoints.record_block_hash(to_block, &hash).await;
+                                }
+                                next_block = Some(to_block + 1);
+                                if to_block < latest_block {
+                                    continue;
+                                }
+                            }
+                            Err(e) => {
+                                eprintln!("Error polling blocks {}-{} ❗️ {:#}", from_block, to_block, e)
+                            }
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Error reading the latest block ❗️ {:?}", e),
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fork_point() {
+        let recorded: Vec<(u64, String)> = [(300u64, "0xc"), (200, "0xb"), (100, "0xa")]
+            .iter()
+            .map(|(block, hash)| (*block, hash.to_string()))
+            .collect();
+        let hashes = |hashes: [Option<&str>; 3]| hashes.map(|hash| hash.map(str::to_string));
+
+        assert_eq!(fork_point(&recorded, &hashes([Some("0xd"), Some("0xb"), Some("0xa")])), 200);
+        // A block that is pending again isn't on the chain yet
+        assert_eq!(fork_point(&recorded, &hashes([None, Some("0xe"), Some("0xa")])), 100);
+        assert_eq!(fork_point(&recorded, &hashes([Some("0xd"), Some("0xe"), Some("0xf")])), 99);
+    }
+}
@@ -0,0 +1,245 @@
+use starknet::core::utils::get_selector_from_name;
+use starknet_core::types::Felt;
+
+use super::subscriptions::Subscriptions;
+use crate::utils::address::{canonicalize_address, to_canonical_hex};
+
+// How the events of a named stream are decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamDecoder {
+    // Through the EVENT_SUBSCRIPTIONS of the stream's contract
+    Events,
+    // ERC-20 Transfer events of the stream's token
+    Transfers,
+}
+
+impl StreamDecoder {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StreamDecoder::Events => "events",
+            StreamDecoder::Transfers => "transfers",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "events" => Some(StreamDecoder::Events),
+            "transfers" => Some(StreamDecoder::Transfers),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StreamError {
+    #[error("Invalid stream {0}, expected name=contract[:decoder][@starting_block]")]
+    Malformed(String),
+    #[error("Invalid stream name {0}, use lowercase letters, digits, - and _")]
+    InvalidName(String),
+    #[error("Stream {0} is defined twice")]
+    DuplicateName(String),
+    #[error("{0} is not a valid contract address")]
+    InvalidContract(String),
+    #[error("No decoder {0}, expected events or transfers")]
+    UnknownDecoder(String),
+    #[error("Invalid starting block {0}")]
+    InvalidStartingBlock(String),
+    #[error("Stream {0} has no subscribed events, add them to EVENT_SUBSCRIPTIONS")]
+    NoEvents(String),
+    #[error("No stream of the factory's events, add one e.g. factory=factory")]
+    NoFactoryStream,
+}
+
+// An Apibara stream of one contract, its events tagged with the stream's name
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamSpec {
+    pub name: String,
+    pub contract: Felt,
+    pub decoder: StreamDecoder,
+    // Blocks before this one are never read by the stream
+    pub starting_block: Option<u64>,
+    // Selectors forwarded to the decoder, other events of the contract are dropped
+    pub selectors: Vec<Felt>,
+}
+
+impl StreamSpec {
+    // Streams resume from the shared checkpoint, their own starting block being a floor
+    pub fn start_block(&self, resume_block: Option<u64>) -> Option<u64> {
+        match (self.starting_block, resume_block) {
+            (Some(starting_block), Some(resume_block)) => Some(starting_block.max(resume_block)),
+            (starting_block, resume_block) => starting_block.or(resume_block),
+        }
+    }
+}
+
+// Named Apibara streams set with APIBARA_STREAMS as a comma separated list of
+// name=contract[:decoder][@starting_block], e.g. "factory=factory,ekubo=0x5dd…:events@650000".
+// "factory" stands for the network's memecoin factory. Without it every subscribed contract gets
+// its own stream, the factory's named "factory"
+pub fn streams_from_env(subscriptions: &Subscriptions, factory: &str) -> Result<Vec<StreamSpec>, StreamError> {
+    match std::env::var("APIBARA_STREAMS").ok().filter(|spec| !spec.trim().is_empty()) {
+        Some(spec) => parse_streams(&spec, subscriptions, factory),
+        None => {
+            let factory = canonicalize_address(factory).unwrap_or_default();
+            let spec: Vec<String> = subscriptions
+                .contracts()
+                .iter()
+                .map(|contract| {
+                    let contract = to_canonical_hex(contract);
+                    match contract == factory {
+                        true => format!("factory={}", contract),
+                        false => format!("{}={}", stream_name(&contract), contract),
+                    }
+                })
+                .collect();
+            parse_streams(&spec.join(","), subscriptions, &factory)
+        }
+    }
+}
+
+// The stream whose cursor is the factory checkpoint, the factory's events stream. Other streams
+// don't see the factory's events, so the checkpoint can't follow them
+pub fn checkpointed_stream(streams: &[StreamSpec], factory: &Felt) -> Result<usize, StreamError> {
+    streams
+        .iter()
+        .position(|stream| stream.contract == *factory && stream.decoder == StreamDecoder::Events)
+        .ok_or(StreamError::NoFactoryStream)
+}
+
+// Default name of a contract's stream, the last digits of its address
+fn stream_name(contract: &str) -> String {
+    format!("contract-{}", &contract[contract.len().saturating_sub(6)..])
+}
+
+fn parse_streams(
+    spec: &str,
+    subscriptions: &Subscriptions,
+    factory: &str,
+) -> Result<Vec<StreamSpec>, StreamError> {
+    let transfer = get_selector_from_name("Transfer").unwrap_or_default();
+    let mut streams: Vec<StreamSpec> = Vec::new();
+    for entry in spec.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (name, target) = entry
+            .split_once('=')
+            .ok_or_else(|| StreamError::Malformed(entry.to_string()))?;
+        let name = name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        {
+            return Err(StreamError::InvalidName(name.to_string()));
+        }
+        if streams.iter().any(|stream| stream.name == name) {
+            return Err(StreamError::DuplicateName(name.to_string()));
+        }
+
+        let (target, starting_block) = match target.split_once('@') {
+            Some((target, block)) => {
+                let block = block
+                    .trim()
+                    .parse::<u64>()
+                    .map_err(|_| StreamError::InvalidStartingBlock(block.to_string()))?;
+                (target, Some(block))
+            }
+            None => (target, None),
+        };
+        let (contract, decoder) = match target.split_once(':') {
+            Some((contract, decoder)) => (
+                contract.trim(),
+                StreamDecoder::from_name(decoder.trim())
+                    .ok_or_else(|| StreamError::UnknownDecoder(decoder.trim().to_string()))?,
+            ),
+            None => (target.trim(), StreamDecoder::Events),
+        };
+        let contract = if contract.eq_ignore_ascii_case("factory") { factory } else { contract };
+        let contract = canonicalize_address(contract)
+            .map(|address| Felt::from_hex_unchecked(&address))
+            .ok_or_else(|| StreamError::InvalidContract(contract.to_string()))?;
+
+        let selectors: Vec<Felt> = match decoder {
+            StreamDecoder::Events => subscriptions
+                .iter()
+                .filter(|subscription| subscription.contract == contract)
+                .map(|subscription| subscription.selector)
+                .collect(),
+            StreamDecoder::Transfers => vec![transfer],
+        };
+        if selectors.is_empty() {
+            return Err(StreamError::NoEvents(name.to_string()));
+        }
+        streams.push(StreamSpec {
+            name: name.to_string(),
+            contract,
+            decoder,
+            starting_block,
+            selectors,
+        });
+    }
+    if streams.is_empty() {
+        return Err(StreamError::Malformed(spec.to_string()));
+    }
+    Ok(streams)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FACTORY: &str = "0x1a46467a9246f45c8c340f1f155266a26a71c07bd55d36e8d1c7d0d438a2dbc";
+
+    fn subscriptions() -> Subscriptions {
+        Subscriptions::parse(
+            "factory:MemecoinCreated,factory:MemecoinLaunched,0xabc:TokenCreated:memecoin_created",
+            FACTORY,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_streams() {
+        let streams = parse_streams(
+            "factory=factory, other=0xabc@700000, sdoge=0x123:transfers",
+            &subscriptions(),
+            FACTORY,
+        )
+        .unwrap();
+        let names: Vec<&str> = streams.iter().map(|stream| stream.name.as_str()).collect();
+        assert_eq!(names, vec!["factory", "other", "sdoge"]);
+        assert_eq!(streams[0].selectors.len(), 2);
+        assert_eq!((streams[1].selectors.len(), streams[1].starting_block), (1, Some(700000)));
+        assert_eq!(streams[2].decoder, StreamDecoder::Transfers);
+
+        let parse = |spec: &str| parse_streams(spec, &subscriptions(), FACTORY);
+        assert!(matches!(parse("factory"), Err(StreamError::Malformed(_))));
+        assert!(matches!(parse("Factory=factory"), Err(StreamError::InvalidName(_))));
+        assert!(matches!(parse("a=factory,a=0xabc"), Err(StreamError::DuplicateName(_))));
+        assert!(matches!(parse("a=factory:swaps"), Err(StreamError::UnknownDecoder(_))));
+        assert!(matches!(parse("a=factory@soon"), Err(StreamError::InvalidStartingBlock(_))));
+        // Contracts without subscribed events would stream nothing
+        assert!(matches!(parse("a=0x5dd"), Err(StreamError::NoEvents(_))));
+    }
+
+    #[test]
+    fn test_checkpointed_stream() {
+        let factory = Felt::from_hex(FACTORY).unwrap();
+        let streams = parse_streams("other=0xabc,factory=factory", &subscriptions(), FACTORY).unwrap();
+        assert_eq!(checkpointed_stream(&streams, &factory).unwrap(), 1);
+        // Transfers of the factory or other contracts' events never move the checkpoint
+        let streams = parse_streams("other=0xabc,fees=factory:transfers", &subscriptions(), FACTORY).unwrap();
+        assert!(matches!(
+            checkpointed_stream(&streams, &factory),
+            Err(StreamError::NoFactoryStream)
+        ));
+    }
+
+    #[test]
+    fn test_start_block() {
+        let stream = parse_streams("other=0xabc@700000", &subscriptions(), FACTORY).unwrap().remove(0);
+        assert_eq!(stream.start_block(None), Some(700000));
+        assert_eq!(stream.start_block(Some(650000)), Some(700000));
+        assert_eq!(stream.start_block(Some(800000)), Some(800000));
+        let factory = parse_streams("factory=factory", &subscriptions(), FACTORY).unwrap().remove(0);
+        assert_eq!(factory.start_block(None), None);
+    }
+}
@@ -0,0 +1,229 @@
+use std::fmt;
+
+use anyhow::Context;
+use starknet::core::utils::get_selector_from_name;
+use starknet_core::types::{EmittedEvent, Felt};
+
+use super::EventType;
+use crate::utils::address::{canonicalize_address, to_canonical_hex};
+use crate::utils::call::{get_contract_events, AggregateError};
+use crate::utils::event_parser::{CreationEvent, EventDecoder, FromStarknetEventData, LaunchEvent};
+
+// Subscriptions used when EVENT_SUBSCRIPTIONS isn't set, both events of the network's factory
+const DEFAULT_SUBSCRIPTIONS: &str = "factory:MemecoinCreated,factory:MemecoinLaunched";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SubscriptionError {
+    #[error("Invalid event subscription {0}, expected contract:event or contract:event:decoder")]
+    Malformed(String),
+    #[error("{0} is not a valid contract address")]
+    InvalidContract(String),
+    #[error("Invalid event name {0}")]
+    InvalidEvent(String),
+    #[error("No decoder for {0}, expected one of {1}")]
+    UnknownDecoder(String, String),
+    #[error("EVENT_SUBSCRIPTIONS has no subscription")]
+    Empty,
+}
+
+// An event of a contract the indexer follows and the decoder its data goes through
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventSubscription {
+    pub contract: Felt,
+    pub event: String,
+    pub selector: Felt,
+    pub decoder: EventDecoder,
+}
+
+impl EventSubscription {
+    fn decode(&self, data: Vec<Felt>) -> Result<EventType, anyhow::Error> {
+        let context = || format!("Parsing {} event", self.event);
+        Ok(match self.decoder {
+            EventDecoder::MemecoinCreated => {
+                EventType::Creation(CreationEvent::from_starknet_event_data(data).with_context(context)?)
+            }
+            EventDecoder::MemecoinLaunched => {
+                EventType::Launch(LaunchEvent::from_starknet_event_data(data).with_context(context)?)
+            }
+        })
+    }
+}
+
+impl fmt::Display for EventSubscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} ({})",
+            self.event,
+            to_canonical_hex(&self.contract),
+            self.decoder.name()
+        )
+    }
+}
+
+// Every (contract, event, decoder) the indexer follows, set with EVENT_SUBSCRIPTIONS as a comma
+// separated list of contract:event[:decoder]. "factory" stands for the network's memecoin factory
+// and the decoder defaults to the one of the event's name, so other launchpad factories or new
+// Unruggable versions are followed without code changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscriptions {
+    subscriptions: Vec<EventSubscription>,
+}
+
+impl Subscriptions {
+    pub fn new(factory: &str) -> Result<Self, SubscriptionError> {
+        Self::parse(
+            &std::env::var("EVENT_SUBSCRIPTIONS").unwrap_or_else(|_| DEFAULT_SUBSCRIPTIONS.to_string()),
+            factory,
+        )
+    }
+
+    pub fn parse(spec: &str, factory: &str) -> Result<Self, SubscriptionError> {
+        let mut subscriptions: Vec<EventSubscription> = Vec::new();
+        for entry in spec.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let parts: Vec<&str> = entry.split(':').map(str::trim).collect();
+            let (contract, event, decoder) = match parts[..] {
+                [contract, event] => (contract, event, None),
+                [contract, event, decoder] => (contract, event, Some(decoder)),
+                _ => return Err(SubscriptionError::Malformed(entry.to_string())),
+            };
+            let contract = if contract.eq_ignore_ascii_case("factory") { factory } else { contract };
+            let contract = canonicalize_address(contract)
+                .map(|address| Felt::from_hex_unchecked(&address))
+                .ok_or_else(|| SubscriptionError::InvalidContract(contract.to_string()))?;
+            let selector =
+                get_selector_from_name(event).map_err(|_| SubscriptionError::InvalidEvent(event.to_string()))?;
+            let decoder = match decoder {
+                Some(name) => EventDecoder::from_name(name),
+                None => EventDecoder::for_event(event),
+            }
+            .ok_or_else(|| {
+                let known: Vec<&str> = EventDecoder::ALL.iter().map(|decoder| decoder.name()).collect();
+                SubscriptionError::UnknownDecoder(decoder.unwrap_or(event).to_string(), known.join(", "))
+            })?;
+            let subscription = EventSubscription {
+                contract,
+                event: event.to_string(),
+                selector,
+                decoder,
+            };
+            if !subscriptions.contains(&subscription) {
+                subscriptions.push(subscription);
+            }
+        }
+        if subscriptions.is_empty() {
+            return Err(SubscriptionError::Empty);
+        }
+        Ok(Self { subscriptions })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &EventSubscription> {
+        self.subscriptions.iter()
+    }
+
+    // Followed contracts, in the order they were configured
+    pub fn contracts(&self) -> Vec<Felt> {
+        let mut contracts: Vec<Felt> = Vec::new();
+        for subscription in &self.subscriptions {
+            if !contracts.contains(&subscription.contract) {
+                contracts.push(subscription.contract);
+            }
+        }
+        contracts
+    }
+
+    // Decodes an event with the decoder of its subscription, events nobody subscribed to yield None.
+    // Sources that don't say which contract emitted the event match on the selector alone
+    pub fn decode(
+        &self,
+        from_address: Option<&Felt>,
+        keys: &[Felt],
+        data: Vec<Felt>,
+    ) -> Result<Option<EventType>, anyhow::Error> {
+        let selector = match keys.first() {
+            Some(selector) => selector,
+            None => return Ok(None),
+        };
+        let subscription = self.subscriptions.iter().find(|subscription| {
+            subscription.selector == *selector
+                && from_address.is_none_or(|address| *address == subscription.contract)
+        });
+        match subscription {
+            Some(subscription) => subscription.decode(data).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // Subscribed events emitted in a block range, oldest first
+    pub async fn events(&self, from_block: u64, to_block: u64) -> Result<Vec<EmittedEvent>, AggregateError> {
+        let mut events = Vec::new();
+        for contract in self.contracts() {
+            let selectors: Vec<Felt> = self
+                .subscriptions
+                .iter()
+                .filter(|subscription| subscription.contract == contract)
+                .map(|subscription| subscription.selector)
+                .collect();
+            events.extend(get_contract_events(contract, selectors, from_block, to_block).await?);
+        }
+        // Each contract's events are in order already, the stable sort interleaves them by block
+        events.sort_by_key(|event| event.block_number);
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FACTORY: &str = "0x1a46467a9246f45c8c340f1f155266a26a71c07bd55d36e8d1c7d0d438a2dbc";
+
+    #[test]
+    fn test_parse_subscriptions() {
+        let defaults = Subscriptions::parse(DEFAULT_SUBSCRIPTIONS, FACTORY).unwrap();
+        assert_eq!(defaults.contracts(), vec![Felt::from_hex_unchecked(FACTORY)]);
+        let decoders: Vec<EventDecoder> = defaults.iter().map(|subscription| subscription.decoder).collect();
+        assert_eq!(decoders, EventDecoder::ALL.to_vec());
+
+        // A second factory emitting a renamed creation event through the existing decoder
+        let subscriptions =
+            Subscriptions::parse("factory:MemecoinLaunched, 0xabc:TokenCreated:memecoin_created", FACTORY)
+                .unwrap();
+        assert_eq!(subscriptions.contracts().len(), 2);
+
+        assert!(matches!(
+            Subscriptions::parse("factory:TokenCreated", FACTORY),
+            Err(SubscriptionError::UnknownDecoder(..))
+        ));
+        assert!(matches!(
+            Subscriptions::parse("0xabc:MemecoinCreated:other", FACTORY),
+            Err(SubscriptionError::UnknownDecoder(..))
+        ));
+        assert!(matches!(
+            Subscriptions::parse("nope:MemecoinCreated", FACTORY),
+            Err(SubscriptionError::InvalidContract(_))
+        ));
+        assert!(matches!(
+            Subscriptions::parse("MemecoinCreated", FACTORY),
+            Err(SubscriptionError::Malformed(_))
+        ));
+        assert!(matches!(Subscriptions::parse(" , ", FACTORY), Err(SubscriptionError::Empty)));
+    }
+
+    #[test]
+    fn test_decode_dispatches_by_contract() {
+        let subscriptions =
+            Subscriptions::parse("factory:MemecoinLaunched, 0xabc:TokenCreated:memecoin_created", FACTORY)
+                .unwrap();
+        let created = get_selector_from_name("TokenCreated").unwrap();
+        let launched = get_selector_from_name("MemecoinLaunched").unwrap();
+        let (factory, other) = (Felt::from_hex_unchecked(FACTORY), Felt::from_hex_unchecked("0xabc"));
+
+        // The subscribed contract's event is decoded, truncated data being an error rather than skipped
+        assert!(subscriptions.decode(Some(&other), &[created], vec![]).is_err());
+        assert!(subscriptions.decode(Some(&factory), &[created], vec![]).unwrap().is_none());
+        assert!(subscriptions.decode(Some(&other), &[launched], vec![]).unwrap().is_none());
+        assert!(subscriptions.decode(None, &[launched], vec![]).is_err());
+        assert!(subscriptions.decode(None, &[], vec![]).unwrap().is_none());
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use starknet_core::types::{EmittedEvent, Felt};
+use tokio::sync::mpsc;
+
+use super::{decode_transfer, IndexedEvent};
+use crate::storage::BlockRef;
+use crate::utils::call::{get_block_timestamp, get_latest_block, get_wallet_transfer_events};
+use crate::watcher::wallets::WalletTracker;
+
+// Most blocks fetched per poll, so catching up happens in steps
+const MAX_BLOCK_RANGE: u64 = 1000;
+// Wallets per key filter, nodes reject event filters with too many keys
+const WALLETS_PER_QUERY: usize = 50;
+
+// Transfers sent or received by followed wallets, read from RPC nodes. Apibara streams follow
+// contracts rather than wallets, so these are polled whichever source indexes launches.
+// Transfers of tokens other than the factory's are dropped when processed
+pub struct TransferStream {
+    interval: Duration,
+    wallets: Arc<WalletTracker>,
+}
+
+impl TransferStream {
+    pub fn new(interval: Duration, wallets: Arc<WalletTracker>) -> Self {
+        Self { interval, wallets }
+    }
+
+    // Sends the transfers of a block range, oldest first
+    async fn poll(
+        &self,
+        wallets: &[Felt],
+        from_block: u64,
+        to_block: u64,
+        tx: &mpsc::UnboundedSender<IndexedEvent>,
+    ) -> Result<(), anyhow::Error> {
+        let followed: HashSet<&Felt> = wallets.iter().collect();
+        let mut events: Vec<EmittedEvent> = Vec::new();
+        for chunk in wallets.chunks(WALLETS_PER_QUERY) {
+            events.extend(get_wallet_transfer_events(chunk, true, from_block, to_block).await?);
+            // A transfer between two followed wallets was already returned as outgoing
+            let incoming = get_wallet_transfer_events(chunk, false, from_block, to_block).await?;
+            events.extend(
+                incoming
+                    .into_iter()
+                    .filter(|event| event.keys.get(1).is_none_or(|from| !followed.contains(from))),
+            );
+        }
+        events.sort_by_key(|event| event.block_number);
+
+        for event in events {
+            let block_number = match event.block_number {
+                Some(block_number) => block_number,
+                None => continue,
+            };
+            let block_timestamp = get_block_timestamp(block_number).await?;
+            match decode_transfer(event.from_address, &event.keys, &event.data) {
+                Ok(decoded) => {
+                    let block = BlockRef {
+                        block_number,
+                        block_timestamp,
+                    };
+                    tx.send(IndexedEvent {
+                        event: decoded,
+                        block: Some(block),
+                        stream: "transfers".to_string(),
+                        _processed: None,
+                    })?;
+                }
+                Err(e) => eprintln!("Error decoding transfer at {} ❗️ {:#}", block_number, e),
+            }
+        }
+        Ok(())
+    }
+
+    // Starts at the chain head, transfers made while the bot was down aren't replayed
+    pub async fn run(&self, tx: mpsc::UnboundedSender<IndexedEvent>) {
+        let mut next_block: Option<u64> = None;
+        loop {
+            if tx.is_closed() {
+                return;
+            }
+            match get_latest_block().await {
+                Ok((latest_block, _)) => {
+                    let from_block = *next_block.get_or_insert(latest_block);
+                    let wallets = self.wallets.wallets().await;
+                    if wallets.is_empty() {
+                        next_block = Some(latest_block + 1);
+                    } else if from_block <= latest_block {
+                        let to_block = latest_block.min(from_block + MAX_BLOCK_RANGE - 1);
+                        match self.poll(&wallets, from_block, to_block, &tx).await {
+                            Ok(()) => {
+                                next_block = Some(to_block + 1);
+                                if to_block < latest_block {
+                                    continue;
+                                }
+                            }
+                            Err(e) => {
+                                eprintln!("Error polling transfers {}-{} ❗️ {:#}", from_block, to_block, e)
+                            }
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Error reading the latest block ❗️ {:?}", e),
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+}
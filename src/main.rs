@@ -1,62 +1,145 @@
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
-use apibara_core::starknet::v1alpha2::{Event, FieldElement};
+use constant::network::NetworkConfig;
 use dotenv::dotenv;
-use kanshi::{
-    config::Config,
-    dna::IndexerService,
-    utils::conversions::{apibara_field_as_felt, felt_as_apibara_field},
+use indexer::{
+    checkpoints, event_streams, run_event_streams, transfer_stream, EventType, IndexedEvent, IndexerConfig,
 };
-use starknet::core::utils::get_selector_from_name;
-use starknet_core::types::Felt;
+use indexer::lanes::WorkerPool;
+use indexer::streams::streams_from_env;
+use indexer::subscriptions::Subscriptions;
+use notifier::{
+    discord::DiscordBot, webhook::WebhookNotifier, DispatcherConfig, NotificationDispatcher, Notifier,
+};
+use server::{launch_feed, run_server, LaunchFeed, ServerConfig};
+use storage::import::{run_import, ImportOptions};
+use storage::{BlockRef, Storage, StorageConfig, EVENTS_PROCESSED, EVENT_ERRORS};
 use telegram::{TelegramBot, TelegramConfig};
 use tokio::sync::mpsc;
 use tokio::task;
 use utils::{
-    event_parser::{CreationEvent, FromStarknetEventData, LaunchEvent},
+    address::to_canonical_hex,
+    call::{get_latest_block, record_block_timestamp},
+    cohort::compare_to_cohort,
+    correlation::find_clones,
     info_aggregator::aggregate_info,
+    pending::count_pending_buys,
+    rpc::{init_rpc_pool, RpcConfig},
+    types::common::{LaunchAlert, LaunchContext},
 };
+use watcher::drift::{DriftConfig, DriftJob, EffectiveConfig};
+use watcher::liquidity_guard::{LiquidityGuard, LiquidityGuardConfig};
+use watcher::lock::{LockWatcher, LockWatcherConfig};
+use watcher::report::{ReportConfig, ReportJob};
 
 mod constant;
+mod indexer;
+mod notifier;
+mod server;
+mod storage;
 mod telegram;
+mod trade;
 mod utils;
-
-lazy_static::lazy_static! {
-    pub static ref CREATION_EVENT: FieldElement = felt_as_apibara_field(&get_selector_from_name("MemecoinCreated").unwrap());
-    pub static ref LAUNCH_EVENT: FieldElement = felt_as_apibara_field(&get_selector_from_name("MemecoinLaunched").unwrap());
-}
-
-#[derive(Debug)]
-enum EventType {
-    Creation(CreationEvent),
-    Launch(LaunchEvent),
-}
+mod watcher;
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
 
-    let (tx, mut rx) = mpsc::unbounded_channel::<Event>();
+    let (tx, mut rx) = mpsc::unbounded_channel::<IndexedEvent>();
+
+    // Resolve the network first, every contract address and endpoint depends on it
+    match NetworkConfig::new() {
+        Ok(network) => {
+            println!("Network: {} ✓", network.network);
+            // Checked here so a typo in EVENT_SUBSCRIPTIONS stops startup rather than the indexer
+            match Subscriptions::new(&network.memecoin_factory) {
+                Ok(subscriptions) => {
+                    for subscription in subscriptions.iter() {
+                        println!("Subscribed to {} ✓", subscription);
+                    }
+                    match streams_from_env(&subscriptions, &network.memecoin_factory) {
+                        Ok(streams) => {
+                            for stream in streams {
+                                println!(
+                                    "Apibara stream {}: {} of {} ✓",
+                                    stream.name,
+                                    stream.decoder.name(),
+                                    to_canonical_hex(&stream.contract)
+                                );
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to load Apibara streams ❗️ {}", e);
+                            return;
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to load event subscriptions ❗️ {}", e);
+                    return;
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to load network configuration ❗️ {}", e);
+            return;
+        }
+    }
+
+    // Every chain read goes through the pool, the import included
+    let rpc_pool = match init_rpc_pool(RpcConfig::new()) {
+        Ok(pool) => {
+            println!("RPC pool: {} endpoint(s) ✓", pool.status().endpoints);
+            pool
+        }
+        Err(e) => {
+            eprintln!("Failed to build the RPC provider pool ❗️ {}", e);
+            return;
+        }
+    };
+
+    // `meme-sniper import [options]` records the factory history and exits, see ImportOptions
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("import") {
+        import_mode(&args[1..]).await;
+        return;
+    }
 
-    // Load configurations
-    let config = match Config::new() {
-        Ok(config) => {
-            println!("Configurations loaded ✓");
-            config
+    // Open the event history database
+    let storage = match Storage::connect(StorageConfig::new()).await {
+        Ok(storage) => {
+            match storage.schema_version().await {
+                Ok(version) => println!("Event history database opened, schema v{} ✓", version),
+                Err(_) => println!("Event history database opened ✓"),
+            }
+            Arc::new(storage)
         }
         Err(e) => {
-            eprintln!("Failed to load configuration ❗️ {}", e);
+            eprintln!("Failed to open event history database ❗️ {}", e);
             return;
         }
     };
 
-    // Create the IndexerService instance
-    let service = IndexerService::new(config);
+    // Pick the event sources, Apibara first unless configured otherwise. They resume after the
+    // last checkpoint, `--full-backfill` starts over from STARTING_BLOCK instead
+    let mut indexer_config = IndexerConfig::new();
+    indexer_config.full_backfill |= args.iter().any(|arg| arg == "--full-backfill");
+    if indexer_config.full_backfill {
+        println!("Full backfill requested, the indexer checkpoint is ignored");
+    }
+    let checkpoints = checkpoints(&indexer_config, Arc::clone(&storage));
+    let streams = event_streams(&indexer_config, &checkpoints).await;
+    println!(
+        "Event sources: {} ✓, {} aggregation workers",
+        streams.iter().map(|stream| stream.name()).collect::<Vec<_>>().join(", "),
+        indexer_config.aggregation_workers
+    );
 
     // Initialize Telegram bot
     let tg_config = TelegramConfig::new();
-    let tg_bot = match TelegramBot::new(tg_config) {
+    let tg_bot = match TelegramBot::new(tg_config, Arc::clone(&storage)) {
         Ok(bot) => {
             println!("Telegram bot initialized ✓");
             Arc::new(bot)
@@ -73,8 +156,53 @@ async fn main() {
         return;
     }
 
+    let lock_watcher = Arc::new(LockWatcher::new(LockWatcherConfig::new()));
+    let watchlist = tg_bot.watchlist();
+    let liquidity_guard = LiquidityGuard::new(LiquidityGuardConfig::new(), Arc::clone(&watchlist));
+    let price_alerts = tg_bot.price_alerts();
+    let paper_trading = tg_bot.paper_trading();
+    let price_history = tg_bot.price_history();
+
+    // Fan launch alerts out to Telegram, Discord and any configured webhooks
+    let mut notifiers: Vec<Arc<dyn Notifier>> = Vec::new();
+    if tg_bot.launch_alerts_enabled() {
+        notifiers.push(tg_bot.clone());
+    }
+    if let Some(discord) = DiscordBot::from_env() {
+        notifiers.push(Arc::new(discord));
+    }
+    for webhook in WebhookNotifier::from_env() {
+        notifiers.push(Arc::new(webhook));
+    }
+    if notifiers.is_empty() {
+        eprintln!("No notification destination is enabled, launches will only be recorded ❗️");
+    }
+    let dispatcher = Arc::new(NotificationDispatcher::new(DispatcherConfig::new(), notifiers));
+    println!("Notification destinations: {}", dispatcher.destinations().join(", "));
+
     // Create Arc clones for different tasks
     let tg_bot_updates = Arc::clone(&tg_bot);
+    let dispatcher_retries = Arc::clone(&dispatcher);
+    let storage_server = Arc::clone(&storage);
+    let dispatcher_server = Arc::clone(&dispatcher);
+    let checkpoints_server = Arc::clone(&checkpoints);
+    let launch_feed = launch_feed();
+    let launch_feed_server = launch_feed.clone();
+    let tg_bot_server = Arc::clone(&tg_bot);
+    let tg_bot_locks = Arc::clone(&tg_bot);
+    let tg_bot_maintenance = Arc::clone(&tg_bot);
+    let tg_bot_watchlist = Arc::clone(&tg_bot);
+    let tg_bot_liquidity = Arc::clone(&tg_bot);
+    let tg_bot_price_alerts = Arc::clone(&tg_bot);
+    let tg_bot_purge = Arc::clone(&tg_bot);
+    let tg_bot_digests = Arc::clone(&tg_bot);
+    let tg_bot_report = Arc::clone(&tg_bot);
+    let tg_bot_circuit = Arc::clone(&tg_bot);
+    let tg_bot_drift = Arc::clone(&tg_bot);
+    let effective = EffectiveConfig::collect(&dispatcher.destinations(), tg_bot.launch_alerts_enabled());
+    let drift_job = DriftJob::new(DriftConfig::new(), Arc::clone(&storage), effective);
+    let report_job = ReportJob::new(ReportConfig::new(), Arc::clone(&storage));
+    let lock_watcher_events = Arc::clone(&lock_watcher);
     let tg_bot_events = Arc::clone(&tg_bot);
 
     // Spawn Telegram bot handler in a separate task
@@ -84,61 +212,361 @@ async fn main() {
         }
     });
 
-    // Spawn the indexer service in a separate task
-    let indexer_handle = task::spawn(async move {
-        if let Err(e) = service.await.run_forever_simplified(&tx).await {
-            eprintln!("Error running Indexer ❗️ {:#}", e);
+    // Spawn the HTTP server in a separate task
+    let server_handle = task::spawn(async move {
+        let server = run_server(
+            ServerConfig::new(),
+            tg_bot_server,
+            dispatcher_server,
+            storage_server,
+            checkpoints_server,
+            launch_feed_server,
+        );
+        if let Err(e) = server.await {
+            eprintln!("Error running HTTP server ❗️ {}", e);
         }
     });
 
-    // Spawn the event consumer in a separate task
+    // Spawn the lock owner watcher in a separate task
+    task::spawn(async move {
+        lock_watcher.run(tg_bot_locks).await;
+    });
+
+    // Spawn the maintenance announcer in a separate task
+    task::spawn(async move {
+        tg_bot_maintenance.run_maintenance().await;
+    });
+
+    // Spawn the notification retry loop in a separate task
+    task::spawn(async move {
+        dispatcher_retries.run().await;
+    });
+
+    // Spawn the RPC endpoint health checks in a separate task
+    task::spawn(rpc_pool.run_health_checks());
+
+    // Spawn the watchlist monitor in a separate task
+    task::spawn(async move {
+        watchlist.run(tg_bot_watchlist).await;
+    });
+
+    // Spawn the liquidity guard of watched tokens in a separate task
+    task::spawn(async move {
+        liquidity_guard.run(tg_bot_liquidity).await;
+    });
+
+    // Spawn the price alert monitor in a separate task
+    task::spawn(async move {
+        price_alerts.run(tg_bot_price_alerts).await;
+    });
+
+    // Spawn the paper trading valuation job in a separate task
+    task::spawn(async move {
+        paper_trading.run().await;
+    });
+
+    // Spawn the price history collector in a separate task
+    task::spawn(async move {
+        price_history.run().await;
+    });
+
+    // Spawn the purge of long stopped chats in a separate task
+    task::spawn(async move {
+        tg_bot_purge.run_inactive_purge().await;
+    });
+
+    // Spawn the digest scheduler in a separate task
+    task::spawn(async move {
+        tg_bot_digests.run_digests().await;
+    });
+
+    // Spawn the load circuit breaker in a separate task
+    task::spawn(async move {
+        tg_bot_circuit.run_circuit_breaker().await;
+    });
+
+    // Spawn the config drift check between replicas in a separate task
+    task::spawn(async move {
+        drift_job.run(tg_bot_drift).await;
+    });
+
+    // Spawn the weekly report job in a separate task
+    task::spawn(async move {
+        report_job.run(tg_bot_report).await;
+    });
+
+    // Spawn the transfer poller of followed wallets in a separate task
+    let transfers = transfer_stream(&indexer_config, tg_bot.wallet_tracker());
+    let transfers_tx = tx.clone();
+    task::spawn(async move {
+        transfers.run(transfers_tx).await;
+    });
+
+    // Spawn the event sources in a separate task, the next one takes over when one stops
+    let indexer_handle = task::spawn(run_event_streams(streams, tx));
+
+    // Spawn the event consumer in a separate task. Events are handed to a pool of workers in the
+    // order they come in, launches of different tokens are aggregated concurrently while the
+    // events of one token wait for each other before taking a worker. Once too many are pending,
+    // the consumer waits for some to finish before taking the next event off the channel
+    let aggregation_workers = indexer_config.aggregation_workers;
+    let freshness_secs = indexer_config.freshness_secs;
     let consumer_handle = task::spawn(async move {
+        let mut pool = WorkerPool::new(aggregation_workers);
         while let Some(event) = rx.recv().await {
-            if let Err(e) = process_event(event, &tg_bot_events).await {
-                eprintln!("Error processing event ❗️ {}", e);
-            }
+            // Events waiting for their turn or a worker are queued as much as those in the channel
+            tg_bot_events.record_queue_depth(rx.len() + pool.waiting());
+            let dispatcher = Arc::clone(&dispatcher);
+            let storage = Arc::clone(&storage);
+            let lock_watcher = Arc::clone(&lock_watcher_events);
+            let tg_bot = Arc::clone(&tg_bot_events);
+            let launch_feed = launch_feed.clone();
+            pool.spawn(event.token_address(), async move {
+                let processed = process_event(
+                    event,
+                    &dispatcher,
+                    &storage,
+                    &lock_watcher,
+                    &tg_bot,
+                    &launch_feed,
+                    freshness_secs,
+                )
+                .await;
+                let counter = match processed {
+                    Ok(()) => EVENTS_PROCESSED,
+                    Err(e) => {
+                        eprintln!("Error processing event ❗️ {}", e);
+                        EVENT_ERRORS
+                    }
+                };
+                if let Err(e) = storage.increment_counter(counter, 1).await {
+                    eprintln!("Failed to count {} ❗️ {}", counter, e);
+                }
+            })
+            .await;
         }
+        pool.join_all().await;
     });
 
     // Wait for both tasks to complete
     tokio::select! {
         _ = indexer_handle => println!("Indexer task completed"),
         _ = consumer_handle => println!("Consumer task completed"),
+        _ = server_handle => println!("HTTP server task completed"),
     }
 }
 
-async fn process_event(event: Event, tg_bot: &Arc<TelegramBot>) -> Result<()> {
-    let event_selector = event.keys.first().context("No event selector")?;
-    let event_data: Vec<Felt> = event.data.iter().map(apibara_field_as_felt).collect();
-    match event_selector {
-        selector if *selector == *CREATION_EVENT => {
-            println!("New creation event: {:?}\n", event.from_address);
+async fn process_event(
+    event: IndexedEvent,
+    dispatcher: &Arc<NotificationDispatcher>,
+    storage: &Arc<Storage>,
+    lock_watcher: &Arc<LockWatcher>,
+    tg_bot: &Arc<TelegramBot>,
+    launch_feed: &LaunchFeed,
+    freshness_secs: u64,
+) -> Result<()> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    // Historical events only go to storage, users aren't told about them as if they were new
+    let historical = event.is_historical(freshness_secs, now);
+    let block = match event.block {
+        Some(block) => {
+            record_block_timestamp(block.block_number, block.block_timestamp);
+            block
+        }
+        None => current_block().await?,
+    };
+    match event.event {
+        EventType::Creation(decoded_data) => {
+            // Recorded for every token, so its launch alert can point back to it. Chats on the
+            // pre-launch radar are told right away
+            println!("New creation event from {}: {:?}\n", event.stream, decoded_data.memecoin_address);
+            let token_address = to_canonical_hex(&decoded_data.memecoin_address);
+            // Resuming from a checkpoint can replay the last events handed over
+            if let Ok(Some(_)) = storage.creation_block(&token_address).await {
+                println!("Skipping creation {} already indexed", token_address);
+                return Ok(());
+            }
+            if let Err(err) = storage.record_creation(&decoded_data, block).await {
+                println!("------- [Error] Storage -------");
+                println!("{:?}", err)
+            }
+            let owner = to_canonical_hex(&decoded_data.owner);
+            match storage.is_blacklisted(&[&token_address, &owner]).await {
+                Ok(true) => println!("Skipping blacklisted creation {}", token_address),
+                blacklisted => {
+                    if let Err(err) = blacklisted {
+                        println!("------- [Error] Storage -------");
+                        println!("{:?}", err)
+                    }
+                    if historical {
+                        println!("Creation {} is historical, not alerting", token_address);
+                    } else if tg_bot.launch_alerts_enabled() {
+                        if let Err(err) = tg_bot.broadcast_creation(&decoded_data).await {
+                            println!("------- [Error] Pre-launch Alert -------");
+                            println!("{:?}", err)
+                        }
+                    }
+                }
+            }
         }
 
-        selector if *selector == *LAUNCH_EVENT => {
-            let decoded_data = decode_launch_data(event_data).await?;
-            match aggregate_info(&decoded_data.memecoin_address.to_hex_string()).await {
+        EventType::Launch(decoded_data) => {
+            let token_address = to_canonical_hex(&decoded_data.memecoin_address);
+            if let Ok(Some(_)) = storage.launch_timestamp(&token_address).await {
+                println!("Skipping launch {} already indexed", token_address);
+                return Ok(());
+            }
+            // Snipers are already queued by now, so look at the pending block while aggregating
+            let (aggregated, pending_buys) =
+                tokio::join!(aggregate_info(&token_address), count_pending_buys(&token_address));
+            match aggregated {
                 Ok(data) => {
                     println!("{:?}", data.0);
-                    if let Err(err) = tg_bot.broadcast_event(data.0).await {
-                        println!("------- [Error] Telegram -------");
+                    if let Err(err) = storage
+                        .record_launch(&decoded_data, block, Some((&data.0, &data.1)))
+                        .await
+                    {
+                        println!("------- [Error] Storage -------");
+                        println!("{:?}", err)
+                    }
+                    tg_bot.index_launch(&data.0, block.block_timestamp).await;
+                    // The launch price and holder count start the token's histories
+                    if let Some(price) = data.0.price.parse::<f64>().ok().filter(|price| *price > 0f64) {
+                        if let Err(err) = storage.record_price(&token_address, price).await {
+                            println!("------- [Error] Storage -------");
+                            println!("{:?}", err)
+                        }
+                    }
+                    let holders = data.1.total_holders as u64;
+                    if let Err(err) = storage.record_holders(&token_address, holders).await {
+                        println!("------- [Error] Storage -------");
+                        println!("{:?}", err)
+                    }
+                    match storage.is_blacklisted(&[&token_address, &data.0.owner]).await {
+                        Ok(true) => println!("Skipping blacklisted launch {}", token_address),
+                        _ if historical => println!(
+                            "Launch {} is {} minutes old, recorded without alerting",
+                            token_address,
+                            now.saturating_sub(block.block_timestamp) / 60
+                        ),
+                        blacklisted => {
+                            if let Err(err) = blacklisted {
+                                println!("------- [Error] Storage -------");
+                                println!("{:?}", err)
+                            }
+                            let mut context = find_clones(storage, &data.0).await;
+                            context.extend(compare_to_cohort(storage, &data.0, block.block_timestamp).await);
+                            match storage.creation_block(&token_address).await {
+                                Ok(Some(created)) => context.push(LaunchContext::Created {
+                                    created_at: created.block_timestamp,
+                                    same_block: created.block_number == block.block_number,
+                                }),
+                                Ok(None) => {}
+                                Err(err) => {
+                                    println!("------- [Error] Storage -------");
+                                    println!("{:?}", err)
+                                }
+                            }
+                            match pending_buys {
+                                Ok(Some(count)) if count > 0 => {
+                                    context.push(LaunchContext::PendingBuys { count })
+                                }
+                                Ok(_) => {}
+                                Err(err) => {
+                                    println!("------- [Error] Pending Buys -------");
+                                    println!("{:?}", err)
+                                }
+                            }
+                            // Sending only fails while nobody is subscribed
+                            let _ = launch_feed.send(data.0.clone());
+                            let alert = LaunchAlert {
+                                info: data.0,
+                                holders: data.1,
+                                context,
+                            };
+                            let pending = dispatcher.dispatch_launch(alert).await;
+                            if pending > 0 {
+                                println!("------- [Error] Notifier -------");
+                                println!("{} destination(s) failed, queued for retry", pending)
+                            }
+                        }
+                    }
+                    if let Err(err) = lock_watcher.track(&token_address).await {
+                        println!("------- [Error] Lock Watcher -------");
                         println!("{:?}", err)
                     }
                 }
                 Err(err) => {
                     println!("------- [Error] Aggregate Call -------");
+                    println!("{:?}", err);
+                    if let Err(err) = storage.record_launch(&decoded_data, block, None).await {
+                        println!("------- [Error] Storage -------");
+                        println!("{:?}", err)
+                    }
+                }
+            }
+        }
+
+        EventType::Transfer(decoded_data) => {
+            // Followed wallets move all kinds of tokens, only the factory's are reported
+            let token_address = to_canonical_hex(&decoded_data.token_address);
+            match storage.memecoin_symbol(&token_address).await {
+                Ok(Some(_)) if historical => {}
+                Ok(Some(symbol)) => {
+                    if let Err(err) = tg_bot.notify_wallet_transfer(&decoded_data, &symbol).await {
+                        println!("------- [Error] Wallet Alert -------");
+                        println!("{:?}", err)
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    println!("------- [Error] Storage -------");
                     println!("{:?}", err)
                 }
             }
         }
-        _ => unreachable!(),
     }
 
     Ok(())
 }
 
-async fn decode_launch_data(event_data: Vec<Felt>) -> anyhow::Result<LaunchEvent, anyhow::Error> {
-    let launch_event: LaunchEvent =
-        LaunchEvent::from_starknet_event_data(event_data).context("Parsing Launch Event")?;
-    Ok(launch_event)
+// One-shot archive import, nothing is broadcast
+async fn import_mode(args: &[String]) {
+    let options = match ImportOptions::parse(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{} ❗️", e);
+            eprintln!(
+                "Usage: meme-sniper import [--from BLOCK] [--to BLOCK] [--chunk BLOCKS] [--metrics] [--restart]"
+            );
+            return;
+        }
+    };
+    let storage = match Storage::connect(StorageConfig::new()).await {
+        Ok(storage) => storage,
+        Err(e) => {
+            eprintln!("Failed to open event history database ❗️ {}", e);
+            return;
+        }
+    };
+    match run_import(&storage, &options).await {
+        Ok(progress) => println!(
+            "Import done ✓ {} creations, {} launches, {} failed",
+            progress.creations, progress.launches, progress.failed
+        ),
+        Err(e) => eprintln!("Import stopped, rerun to resume ❗️ {:#}", e),
+    }
 }
+
+// Events whose block couldn't be resolved by their stream are stamped with the chain head
+async fn current_block() -> Result<BlockRef> {
+    let (block_number, block_timestamp) = get_latest_block().await.context("No block for the event")?;
+    Ok(BlockRef {
+        block_number,
+        block_timestamp,
+    })
+}
+
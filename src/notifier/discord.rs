@@ -0,0 +1,178 @@
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder};
+use serde_json::{json, Value};
+
+use super::{Notifier, NotifyError};
+use crate::constant::network::NETWORK;
+use crate::utils::cohort::describe_cohort;
+use crate::utils::sanitize::HIDDEN_CHARS_WARNING;
+use crate::utils::types::common::{LaunchAlert, LaunchContext, MemecoinInfo, RiskReport};
+
+const DISCORD_API: &str = "https://discord.com/api/v10";
+// Starknet orange
+const EMBED_COLOR: u32 = 0xEC796B;
+
+// Where Discord alerts are posted
+enum DiscordTarget {
+    Webhook(String),
+    Bot { token: String, channel_id: String },
+}
+
+// Posts launch alerts to a Discord channel through a webhook or a bot token
+pub struct DiscordBot {
+    client: Client,
+    target: DiscordTarget,
+}
+
+impl DiscordBot {
+    // DISCORD_WEBHOOK_URL takes precedence over DISCORD_BOT_TOKEN with DISCORD_CHANNEL_ID
+    pub fn from_env() -> Option<Self> {
+        let webhook = std::env::var("DISCORD_WEBHOOK_URL").ok().filter(|url| !url.is_empty());
+        let bot = std::env::var("DISCORD_BOT_TOKEN")
+            .ok()
+            .zip(std::env::var("DISCORD_CHANNEL_ID").ok())
+            .filter(|(token, channel_id)| !token.is_empty() && !channel_id.is_empty());
+        let target = match (webhook, bot) {
+            (Some(url), _) => DiscordTarget::Webhook(url),
+            (None, Some((token, channel_id))) => DiscordTarget::Bot { token, channel_id },
+            (None, None) => return None,
+        };
+        Some(Self {
+            client: Client::new(),
+            target,
+        })
+    }
+
+    fn request(&self) -> RequestBuilder {
+        match &self.target {
+            DiscordTarget::Webhook(url) => self.client.post(url),
+            DiscordTarget::Bot { token, channel_id } => self
+                .client
+                .post(format!("{}/channels/{}/messages", DISCORD_API, channel_id))
+                .header("Authorization", format!("Bot {}", token)),
+        }
+    }
+
+    fn launch_embed(&self, alert: &LaunchAlert) -> Value {
+        let info = &alert.info;
+        let figures = info.formatted();
+        let mut description = format!("**{}** (${}) has landed on Starknet!", figures.name, figures.symbol);
+        if figures.hidden_chars {
+            description.push_str("\n**");
+            description.push_str(HIDDEN_CHARS_WARNING);
+            description.push_str("**");
+        }
+        for context in &alert.context {
+            description.push('\n');
+            description.push_str(&format_context(context));
+        }
+
+        let change = info.market_cap_change_pct();
+        let starting_mcap = if change.is_some() { &info.launch_market_cap } else { &info.market_cap };
+        let mut embed = json!({
+            "title": format!("🚀 New launch: ${}", figures.symbol),
+            "url": format!("{}/{}", NETWORK.explorer_url, info.address),
+            "description": description,
+            "color": EMBED_COLOR,
+            "fields": [
+                { "name": "Address", "value": format!("`{}`", info.address), "inline": false },
+                { "name": "Starting MCAP", "value": format!("${}", format_usd(starting_mcap)), "inline": true },
+                { "name": "Liquidity", "value": format!("${}", format_usd(&info.usd_dex_liquidity)), "inline": true },
+                { "name": "Pool fee", "value": format!("{}%", info.pool_fee_percent), "inline": true },
+                { "name": "Quote token", "value": format_quote_token(info), "inline": true },
+                { "name": "Holders", "value": alert.holders.effective_holder_count.to_string(), "inline": true },
+                { "name": "Risk", "value": format_risk(info.risk.as_ref()), "inline": false },
+            ],
+            "footer": { "text": "starkSnipe" },
+        });
+        if let (Some(change), Some(fields)) = (change, embed["fields"].as_array_mut()) {
+            let value = format!("${} ({:+.1}%)", format_usd(&info.market_cap), change);
+            fields.insert(2, json!({ "name": "MCAP now", "value": value, "inline": true }));
+        }
+        embed
+    }
+}
+
+fn format_usd(value: &str) -> String {
+    format!("{:.2}", value.parse::<f64>().unwrap_or_default())
+}
+
+// Same wording as the Telegram cards, a pool paired with an unlisted token is flagged
+fn format_quote_token(info: &MemecoinInfo) -> String {
+    if info.quote_token_safe {
+        format!("{} ✅", info.quote_symbol)
+    } else if info.quote_token.is_empty() {
+        "Unknown".to_string()
+    } else {
+        format!("⚠️ Unlisted token `{}`", info.quote_token)
+    }
+}
+
+fn format_risk(risk: Option<&RiskReport>) -> String {
+    match risk {
+        Some(risk) => {
+            let mut lines = vec![format!("{} ({}/100)", risk.level().label(), risk.score)];
+            lines.extend(risk.flags.iter().map(|flag| format!("⚠️ {}", flag.describe())));
+            lines.join("\n")
+        }
+        None => "Not assessed".to_string(),
+    }
+}
+
+fn format_context(context: &LaunchContext) -> String {
+    match context {
+        LaunchContext::CloneOf {
+            symbol,
+            launched_at,
+            price_change_pct,
+            same_deployer,
+            ..
+        } => {
+            let performance = price_change_pct
+                .map(|change| format!(" (now {:+.0}%)", change))
+                .unwrap_or_default();
+            format!(
+                "🧬 {} **${}** launched <t:{}:R>{}",
+                if *same_deployer { "Same deployer as" } else { "Looks like a clone of" },
+                symbol,
+                launched_at,
+                performance
+            )
+        }
+        LaunchContext::PendingBuys { count } => {
+            format!("⏳ **{}** buy{} pending", count, if *count == 1 { "" } else { "s" })
+        }
+        LaunchContext::Created { same_block: true, .. } => "🌱 Created and launched in the same block".to_string(),
+        LaunchContext::Created { created_at, .. } => format!("🌱 Created <t:{}:R>, before liquidity", created_at),
+        LaunchContext::Cohort {
+            market_cap_pct,
+            liquidity_pct,
+            ..
+        } => format!("📊 {}", describe_cohort(*market_cap_pct, *liquidity_pct)),
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordBot {
+    fn name(&self) -> String {
+        "discord".to_string()
+    }
+
+    async fn notify_launch(&self, _fingerprint: &str, alert: &LaunchAlert) -> Result<(), NotifyError> {
+        let response = self
+            .request()
+            .json(&json!({ "embeds": [self.launch_embed(alert)] }))
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(NotifyError::Rejected(format!(
+                "status {}: {}",
+                status,
+                response.text().await.unwrap_or_default()
+            )));
+        }
+        Ok(())
+    }
+}
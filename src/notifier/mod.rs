@@ -0,0 +1,264 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+
+use crate::utils::types::common::LaunchAlert;
+
+pub mod discord;
+pub mod webhook;
+
+#[derive(Debug, thiserror::Error)]
+pub enum NotifyError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Destination rejected notification: {0}")]
+    Rejected(String),
+}
+
+// A destination launch alerts are fanned out to
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    // Stable identifier used as the key of delivery records
+    fn name(&self) -> String;
+
+    async fn notify_launch(&self, fingerprint: &str, alert: &LaunchAlert) -> Result<(), NotifyError>;
+}
+
+// Identifies one logical notification independently of the destination it goes to
+pub fn fingerprint(kind: &str, subject: &str) -> String {
+    let digest = Sha256::digest(format!("{}:{}", kind, subject.to_lowercase()).as_bytes());
+    hex::encode(digest)
+}
+
+// Configuration for the notification dispatcher
+#[derive(Debug, Clone)]
+pub struct DispatcherConfig {
+    max_attempts: u32,
+    retry_interval: Duration,
+    record_ttl_secs: u64,
+}
+
+impl DispatcherConfig {
+    pub fn new() -> Self {
+        Self {
+            max_attempts: std::env::var("NOTIFY_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(5),
+            retry_interval: Duration::from_secs(
+                std::env::var("NOTIFY_RETRY_INTERVAL_SECS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(30),
+            ),
+            record_ttl_secs: std::env::var("NOTIFY_RECORD_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(86400),
+        }
+    }
+}
+
+// Which destinations already received a notification
+struct DeliveryRecord {
+    alert: LaunchAlert,
+    delivered: HashSet<String>,
+    attempts: u32,
+    in_flight: bool,
+    created_at: u64,
+}
+
+pub struct NotificationDispatcher {
+    config: DispatcherConfig,
+    notifiers: Vec<Arc<dyn Notifier>>,
+    records: RwLock<HashMap<String, DeliveryRecord>>,
+}
+
+impl NotificationDispatcher {
+    pub fn new(config: DispatcherConfig, notifiers: Vec<Arc<dyn Notifier>>) -> Self {
+        Self {
+            config,
+            notifiers,
+            records: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn destinations(&self) -> Vec<String> {
+        self.notifiers.iter().map(|notifier| notifier.name()).collect()
+    }
+
+    // Sends a launch to every destination that hasn't received it yet and returns how many are still pending
+    pub async fn dispatch_launch(&self, alert: LaunchAlert) -> usize {
+        let fingerprint = fingerprint("launch", &alert.info.address);
+        {
+            let mut records = self.records.write().await;
+            let record = records
+                .entry(fingerprint.clone())
+                .or_insert_with(|| DeliveryRecord {
+                    alert,
+                    delivered: HashSet::new(),
+                    attempts: 0,
+                    in_flight: false,
+                    created_at: now(),
+                });
+            if record.in_flight {
+                return self.notifiers.len() - record.delivered.len();
+            }
+            record.in_flight = true;
+        }
+        self.attempt(&fingerprint).await
+    }
+
+    // Retries destinations that failed earlier and forgets records past their TTL
+    pub async fn run(&self) {
+        let mut interval = tokio::time::interval(self.config.retry_interval);
+        loop {
+            interval.tick().await;
+
+            let retryable: Vec<String> = {
+                let now = now();
+                let mut records = self.records.write().await;
+                records.retain(|_, record| {
+                    record.in_flight || now.saturating_sub(record.created_at) < self.config.record_ttl_secs
+                });
+                records
+                    .iter_mut()
+                    .filter(|(_, record)| {
+                        !record.in_flight
+                            && record.attempts < self.config.max_attempts
+                            && record.delivered.len() < self.notifiers.len()
+                    })
+                    .map(|(fingerprint, record)| {
+                        record.in_flight = true;
+                        fingerprint.clone()
+                    })
+                    .collect()
+            };
+
+            for fingerprint in retryable {
+                let pending = self.attempt(&fingerprint).await;
+                if pending > 0 {
+                    eprintln!(
+                        "Notification {} still pending for {} destination(s)",
+                        fingerprint, pending
+                    );
+                }
+            }
+        }
+    }
+
+    // Expects the record to have been marked in flight by the caller
+    async fn attempt(&self, fingerprint: &str) -> usize {
+        let (alert, delivered) = match self.records.read().await.get(fingerprint) {
+            Some(record) => (record.alert.clone(), record.delivered.clone()),
+            None => return 0,
+        };
+
+        let mut succeeded = Vec::new();
+        for notifier in &self.notifiers {
+            let name = notifier.name();
+            if delivered.contains(&name) {
+                continue;
+            }
+            match notifier.notify_launch(fingerprint, &alert).await {
+                Ok(_) => succeeded.push(name),
+                Err(e) => eprintln!("Failed to notify {} of {}: {}", name, alert.info.address, e),
+            }
+        }
+
+        let mut records = self.records.write().await;
+        match records.get_mut(fingerprint) {
+            Some(record) => {
+                record.delivered.extend(succeeded);
+                record.attempts += 1;
+                record.in_flight = false;
+                self.notifiers.len() - record.delivered.len()
+            }
+            None => 0,
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::types::common::{MemecoinInfo, TokenCategoryResponse};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct FlakyNotifier {
+        name: &'static str,
+        failures_left: AtomicU32,
+        sent: AtomicU32,
+    }
+
+    #[async_trait]
+    impl Notifier for FlakyNotifier {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        async fn notify_launch(&self, _fingerprint: &str, _alert: &LaunchAlert) -> Result<(), NotifyError> {
+            if self.failures_left.load(Ordering::SeqCst) > 0 {
+                self.failures_left.fetch_sub(1, Ordering::SeqCst);
+                return Err(NotifyError::Rejected("unavailable".to_string()));
+            }
+            self.sent.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn notifier(name: &'static str, failures: u32) -> Arc<FlakyNotifier> {
+        Arc::new(FlakyNotifier {
+            name,
+            failures_left: AtomicU32::new(failures),
+            sent: AtomicU32::new(0),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_retries_skip_destinations_already_delivered() {
+        let telegram = notifier("telegram", 0);
+        let webhook = notifier("webhook", 1);
+        let dispatcher = NotificationDispatcher::new(
+            DispatcherConfig {
+                max_attempts: 3,
+                retry_interval: Duration::from_secs(1),
+                record_ttl_secs: 60,
+            },
+            vec![telegram.clone(), webhook.clone()],
+        );
+        let alert = LaunchAlert {
+            info: MemecoinInfo {
+                address: "0x1".to_string(),
+                ..Default::default()
+            },
+            holders: TokenCategoryResponse {
+                token_address: "0x1".to_string(),
+                category: String::new(),
+                holder_count: 0,
+                effective_holder_count: 0,
+                total_holders: 0,
+                top_holders_pct: None,
+                top_holders: Vec::new(),
+            },
+            context: Vec::new(),
+        };
+
+        assert_eq!(dispatcher.dispatch_launch(alert.clone()).await, 1);
+        assert_eq!(dispatcher.dispatch_launch(alert).await, 0);
+        assert_eq!(telegram.sent.load(Ordering::SeqCst), 1);
+        assert_eq!(webhook.sent.load(Ordering::SeqCst), 1);
+        assert_ne!(fingerprint("launch", "0x1"), fingerprint("launch", "0x2"));
+    }
+}
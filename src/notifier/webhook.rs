@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::json;
+
+use super::{Notifier, NotifyError};
+use crate::utils::types::common::LaunchAlert;
+
+// Posts launch alerts as JSON to an arbitrary HTTP endpoint
+pub struct WebhookNotifier {
+    client: Client,
+    url: String,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String) -> Self {
+        Self {
+            client: Client::new(),
+            url,
+        }
+    }
+
+    // One notifier per entry of the comma separated WEBHOOK_URLS
+    pub fn from_env() -> Vec<Self> {
+        std::env::var("WEBHOOK_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(|url| Self::new(url.to_string()))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> String {
+        format!("webhook:{}", self.url)
+    }
+
+    async fn notify_launch(&self, fingerprint: &str, alert: &LaunchAlert) -> Result<(), NotifyError> {
+        let response = self
+            .client
+            .post(&self.url)
+            // Lets receivers drop duplicates should a retry race with a slow success
+            .header("Idempotency-Key", fingerprint)
+            .json(&json!({
+                "kind": "launch",
+                "fingerprint": fingerprint,
+                "coin_info": alert.info,
+                "holders_data": alert.holders,
+                "context": alert.context,
+            }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(NotifyError::Rejected(format!("status {}", response.status())));
+        }
+        Ok(())
+    }
+}
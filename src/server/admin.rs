@@ -0,0 +1,379 @@
+use axum::{
+    extract::{Path, Query, Request, State},
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use super::api::api_error;
+use super::AppState;
+use crate::storage::backfill::backfill as run_backfill;
+use crate::telegram::experiment::{Variant, VariantCopy};
+use crate::telegram::templates::{CardTemplate, ChatKind};
+use crate::utils::address::canonicalize_address;
+use crate::utils::correlation::find_clones;
+use crate::utils::info_aggregator::aggregate_info_with_refresh;
+use crate::utils::types::common::LaunchAlert;
+
+// Upper bound on blocks scanned by one backfill request
+const MAX_BACKFILL_BLOCKS: u64 = 50_000;
+
+const INVALID_TEMPLATE_KEY: &str = "Chat kind must be private, group or channel and variant A or B";
+
+// Rejects requests without `Authorization: Bearer <ADMIN_API_TOKEN>`
+pub async fn require_token(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let provided = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    // Digests have a fixed length, so comparing them doesn't leak the token length
+    let authorized = match (provided, &state.admin_token) {
+        (Some(provided), Some(expected)) => {
+            Sha256::digest(provided.as_bytes()) == Sha256::digest(expected.as_bytes())
+        }
+        _ => false,
+    };
+    if !authorized {
+        return api_error(StatusCode::UNAUTHORIZED, "Invalid or missing admin token");
+    }
+    next.run(request).await
+}
+
+// GET /admin/stats
+pub async fn stats(State(state): State<AppState>) -> Response {
+    let launches = match state.storage.count_launches(None).await {
+        Ok(launches) => launches,
+        Err(e) => {
+            eprintln!("Admin API failed to count launches: {}", e);
+            return api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load stats");
+        }
+    };
+    let experiment = state.bot.experiment();
+    Json(json!({
+        "subscribers": state.bot.subscriber_count().await,
+        "launches_recorded": launches,
+        "notifier_destinations": state.dispatcher.destinations(),
+        "experiment": {
+            "enabled": experiment.is_enabled().await,
+            "stats": experiment.stats().await,
+        },
+        "maintenance": state.bot.maintenance().window().await,
+    }))
+    .into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReprocessParams {
+    #[serde(default)]
+    broadcast: bool,
+}
+
+// POST /admin/reprocess/{address}?broadcast=true
+pub async fn reprocess(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(params): Query<ReprocessParams>,
+) -> Response {
+    let address = match canonicalize_address(&address) {
+        Some(address) => address,
+        None => return api_error(StatusCode::BAD_REQUEST, "Invalid token address"),
+    };
+    let (info, holders) = match aggregate_info_with_refresh(&address, true).await {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("Admin API failed to aggregate {}: {}", address, e);
+            return api_error(StatusCode::BAD_GATEWAY, "Failed to fetch token information");
+        }
+    };
+    let updated = match state.storage.update_launch_metrics(&info, &holders).await {
+        Ok(updated) => updated,
+        Err(e) => {
+            eprintln!("Admin API failed to store metrics of {}: {}", address, e);
+            return api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to store metrics");
+        }
+    };
+
+    // Destinations that already received the launch are skipped by the dispatcher
+    let pending = if params.broadcast {
+        match state.storage.is_blacklisted(&[&address, &info.owner]).await {
+            Ok(false) => {
+                let alert = LaunchAlert {
+                    info: info.clone(),
+                    holders: holders.clone(),
+                    context: find_clones(&state.storage, &info).await,
+                };
+                Some(state.dispatcher.dispatch_launch(alert).await)
+            }
+            Ok(true) => return api_error(StatusCode::CONFLICT, "Token or deployer is blacklisted"),
+            Err(e) => {
+                eprintln!("Admin API failed to check blacklist: {}", e);
+                return api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to check blacklist");
+            }
+        }
+    } else {
+        None
+    };
+
+    Json(json!({
+        "recorded": updated,
+        "pending_destinations": pending,
+        "coin_info": info,
+        "holders_data": holders,
+    }))
+    .into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BackfillRequest {
+    from_block: u64,
+    to_block: u64,
+    #[serde(default)]
+    with_metrics: bool,
+}
+
+// POST /admin/backfill, runs in the background and only records events
+pub async fn backfill(State(state): State<AppState>, Json(request): Json<BackfillRequest>) -> Response {
+    if request.from_block > request.to_block {
+        return api_error(StatusCode::BAD_REQUEST, "from_block must not exceed to_block");
+    }
+    if request.to_block - request.from_block > MAX_BACKFILL_BLOCKS {
+        return api_error(
+            StatusCode::BAD_REQUEST,
+            &format!("Backfill at most {} blocks at a time", MAX_BACKFILL_BLOCKS),
+        );
+    }
+
+    let storage = state.storage.clone();
+    tokio::spawn(async move {
+        match run_backfill(&storage, request.from_block, request.to_block, request.with_metrics).await {
+            Ok(report) => println!(
+                "Backfill {}..{} done: {} creations, {} launches, {} failed",
+                request.from_block, request.to_block, report.creations, report.launches, report.failed
+            ),
+            Err(e) => eprintln!("Backfill {}..{} failed: {}", request.from_block, request.to_block, e),
+        }
+    });
+
+    (
+        StatusCode::ACCEPTED,
+        Json(json!({
+            "status": "started",
+            "from_block": request.from_block,
+            "to_block": request.to_block,
+        })),
+    )
+        .into_response()
+}
+
+// GET /admin/blacklist
+pub async fn list_blacklist(State(state): State<AppState>) -> Response {
+    match state.storage.blacklist().await {
+        Ok(entries) => Json(json!({ "entries": entries })).into_response(),
+        Err(e) => {
+            eprintln!("Admin API failed to load blacklist: {}", e);
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load blacklist")
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BlacklistRequest {
+    reason: Option<String>,
+}
+
+// PUT /admin/blacklist/{address}
+pub async fn add_blacklist(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    request: Option<Json<BlacklistRequest>>,
+) -> Response {
+    let address = match canonicalize_address(&address) {
+        Some(address) => address,
+        None => return api_error(StatusCode::BAD_REQUEST, "Invalid address"),
+    };
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    match state
+        .storage
+        .add_to_blacklist(&address, request.reason.as_deref())
+        .await
+    {
+        Ok(_) => Json(json!({ "address": address, "blacklisted": true })).into_response(),
+        Err(e) => {
+            eprintln!("Admin API failed to update blacklist: {}", e);
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update blacklist")
+        }
+    }
+}
+
+// DELETE /admin/blacklist/{address}
+pub async fn remove_blacklist(State(state): State<AppState>, Path(address): Path<String>) -> Response {
+    let address = match canonicalize_address(&address) {
+        Some(address) => address,
+        None => return api_error(StatusCode::BAD_REQUEST, "Invalid address"),
+    };
+    match state.storage.remove_from_blacklist(&address).await {
+        Ok(true) => Json(json!({ "address": address, "blacklisted": false })).into_response(),
+        Ok(false) => api_error(StatusCode::NOT_FOUND, "Address is not blacklisted"),
+        Err(e) => {
+            eprintln!("Admin API failed to update blacklist: {}", e);
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update blacklist")
+        }
+    }
+}
+
+// GET /admin/settings
+pub async fn settings(State(state): State<AppState>) -> Response {
+    let experiment = state.bot.experiment();
+    let templates: Vec<_> = state
+        .bot
+        .card_templates()
+        .list()
+        .await
+        .into_iter()
+        .map(|(kind, variant, template)| {
+            json!({
+                "chat_kind": kind.as_str(),
+                "variant": variant.as_str(),
+                "template": template,
+            })
+        })
+        .collect();
+    Json(json!({
+        "experiment": {
+            "enabled": experiment.is_enabled().await,
+            "A": experiment.copy(Variant::A).await,
+            "B": experiment.copy(Variant::B).await,
+        },
+        "templates": templates,
+        "maintenance": state.bot.maintenance().window().await,
+    }))
+    .into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExperimentRequest {
+    enabled: Option<bool>,
+    variant: Option<String>,
+    headline: Option<String>,
+    call_to_action: Option<String>,
+}
+
+// POST /admin/settings/experiment, mirrors /admin ab
+pub async fn update_experiment(
+    State(state): State<AppState>,
+    Json(request): Json<ExperimentRequest>,
+) -> Response {
+    let experiment = state.bot.experiment();
+    if let Some(variant) = &request.variant {
+        let variant = match Variant::parse(variant) {
+            Some(variant) => variant,
+            None => return api_error(StatusCode::BAD_REQUEST, "Variant must be A or B"),
+        };
+        match (request.headline, request.call_to_action) {
+            (Some(headline), Some(call_to_action)) => {
+                experiment
+                    .set_copy(
+                        variant,
+                        VariantCopy {
+                            headline,
+                            call_to_action,
+                        },
+                    )
+                    .await
+            }
+            _ => {
+                return api_error(
+                    StatusCode::BAD_REQUEST,
+                    "headline and call_to_action are required with variant",
+                )
+            }
+        }
+    }
+    match request.enabled {
+        Some(true) => experiment.start().await,
+        Some(false) => experiment.stop().await,
+        None => {}
+    }
+    settings(State(state)).await
+}
+
+fn template_key(kind: &str, variant: &str) -> Option<(ChatKind, Variant)> {
+    Some((ChatKind::parse(kind)?, Variant::parse(variant)?))
+}
+
+// PUT /admin/settings/templates/{kind}/{variant}, mirrors /admin template
+pub async fn set_template(
+    State(state): State<AppState>,
+    Path((kind, variant)): Path<(String, String)>,
+    Json(template): Json<CardTemplate>,
+) -> Response {
+    let (kind, variant) = match template_key(&kind, &variant) {
+        Some(key) => key,
+        None => return api_error(StatusCode::BAD_REQUEST, INVALID_TEMPLATE_KEY),
+    };
+    if let Err(e) = state.bot.card_templates().set(kind, variant, template).await {
+        eprintln!("Admin API failed to save card template: {}", e);
+        return api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save template");
+    }
+    settings(State(state)).await
+}
+
+// DELETE /admin/settings/templates/{kind}/{variant}
+pub async fn remove_template(
+    State(state): State<AppState>,
+    Path((kind, variant)): Path<(String, String)>,
+) -> Response {
+    let (kind, variant) = match template_key(&kind, &variant) {
+        Some(key) => key,
+        None => return api_error(StatusCode::BAD_REQUEST, INVALID_TEMPLATE_KEY),
+    };
+    match state.bot.card_templates().remove(kind, variant).await {
+        Ok(true) => settings(State(state)).await,
+        Ok(false) => api_error(StatusCode::NOT_FOUND, "No template for this chat kind and variant"),
+        Err(e) => {
+            eprintln!("Admin API failed to remove card template: {}", e);
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to remove template")
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MaintenanceRequest {
+    start_in_min: u64,
+    duration_min: u64,
+}
+
+// POST /admin/settings/maintenance, mirrors /admin maintenance
+pub async fn schedule_maintenance(
+    State(state): State<AppState>,
+    Json(request): Json<MaintenanceRequest>,
+) -> Response {
+    if request.duration_min == 0 {
+        return api_error(StatusCode::BAD_REQUEST, "duration_min must be positive");
+    }
+    match state
+        .bot
+        .schedule_maintenance(request.start_in_min, request.duration_min)
+        .await
+    {
+        Ok(window) => Json(json!({ "maintenance": window })).into_response(),
+        Err(e) => {
+            eprintln!("Admin API failed to announce maintenance: {:?}", e);
+            api_error(StatusCode::BAD_GATEWAY, "Window scheduled but the announcement failed")
+        }
+    }
+}
+
+// DELETE /admin/settings/maintenance
+pub async fn cancel_maintenance(State(state): State<AppState>) -> Response {
+    if state.bot.cancel_maintenance().await {
+        Json(json!({ "maintenance": null })).into_response()
+    } else {
+        api_error(StatusCode::NOT_FOUND, "No maintenance window is scheduled")
+    }
+}
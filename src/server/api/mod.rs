@@ -0,0 +1,204 @@
+use axum::{
+    extract::{OriginalUri, Path, Query, Request, State},
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::AppState;
+use crate::storage::report::{compile_weekly_report, week_start, WEEK_SECS};
+use crate::storage::{LaunchRecord, LaunchTag};
+use crate::utils::address::canonicalize_address;
+use crate::utils::info_aggregator::{aggregate_info, get_account_holding_info, get_account_holdings};
+use crate::utils::types::common::{MemecoinInfo, TokenCategoryResponse};
+
+pub mod v1;
+pub mod v2;
+
+// Versioned routes share the handler cores below and only differ in the DTOs they serialize,
+// so a version's JSON shape stays put while the internal types change underneath it
+
+pub fn api_error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+// The rejection is boxed, a Response is too large to pass around as an error by value
+fn parse_address(address: &str, kind: &str) -> Result<String, Box<Response>> {
+    canonicalize_address(address)
+        .ok_or_else(|| Box::new(api_error(StatusCode::BAD_REQUEST, &format!("Invalid {} address", kind))))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LaunchesQuery {
+    page: Option<u32>,
+    per_page: Option<u32>,
+    tag: Option<String>,
+}
+
+// A page of stored launches, newest first
+pub struct LaunchesPage {
+    pub launches: Vec<LaunchRecord>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u64,
+}
+
+pub async fn load_launches(state: &AppState, query: LaunchesQuery) -> Result<LaunchesPage, Response> {
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(20).clamp(1, 100);
+    let offset = (page - 1).saturating_mul(per_page);
+    let tag = match query.tag.as_deref().map(LaunchTag::parse) {
+        Some(None) => return Err(api_error(StatusCode::BAD_REQUEST, "Unknown tag, use gem, sus or test")),
+        Some(tag) => tag,
+        None => None,
+    };
+
+    let launches = state.storage.recent_launches(per_page, offset, tag).await.map_err(|e| {
+        eprintln!("API failed to load launches: {}", e);
+        api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load launches")
+    })?;
+    let total = state.storage.count_launches(tag).await.map_err(|e| {
+        eprintln!("API failed to count launches: {}", e);
+        api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load launches")
+    })?;
+    Ok(LaunchesPage {
+        launches,
+        page,
+        per_page,
+        total,
+    })
+}
+
+pub async fn load_token(address: &str) -> Result<(MemecoinInfo, TokenCategoryResponse), Response> {
+    let address = parse_address(address, "token").map_err(|response| *response)?;
+    aggregate_info(&address).await.map_err(|e| {
+        eprintln!("API failed to aggregate {}: {}", address, e);
+        api_error(StatusCode::BAD_GATEWAY, "Failed to fetch token information")
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WeeklyReportQuery {
+    // Any timestamp within the wanted week, the last complete week by default
+    at: Option<u64>,
+}
+
+// GET /api/{version}/reports/weekly?at=
+pub async fn weekly_report(
+    State(state): State<AppState>,
+    Query(query): Query<WeeklyReportQuery>,
+) -> Response {
+    let week = match query.at {
+        Some(at) => week_start(at),
+        None => {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            week_start(now) - WEEK_SECS
+        }
+    };
+    match compile_weekly_report(&state.storage, week).await {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => {
+            eprintln!("API failed to compile weekly report: {}", e);
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to compile weekly report")
+        }
+    }
+}
+
+// GET /api/{version}/wallet/{address}/holdings
+pub async fn wallet_holdings(Path(address): Path<String>) -> Response {
+    let address = match parse_address(&address, "wallet") {
+        Ok(address) => address,
+        Err(response) => return *response,
+    };
+    match get_account_holdings(&address).await {
+        Ok(holdings) => Json(holdings).into_response(),
+        Err(e) => {
+            eprintln!("API failed to fetch holdings of {}: {}", address, e);
+            api_error(StatusCode::BAD_GATEWAY, "Failed to fetch wallet holdings")
+        }
+    }
+}
+
+// GET /api/{version}/wallet/{address}/spot/{token}
+pub async fn wallet_spot(Path((address, token)): Path<(String, String)>) -> Response {
+    let (address, token) = match (parse_address(&address, "wallet"), parse_address(&token, "token")) {
+        (Ok(address), Ok(token)) => (address, token),
+        (Err(response), _) | (_, Err(response)) => return *response,
+    };
+    match get_account_holding_info(&address, &token).await {
+        Ok(info) => Json(info).into_response(),
+        Err(e) => {
+            eprintln!("API failed to fetch {} position of {}: {}", token, address, e);
+            api_error(StatusCode::BAD_GATEWAY, "Failed to fetch token position")
+        }
+    }
+}
+
+// How a superseded API version points integrators at its replacement: a `Deprecation` header,
+// a `Sunset` date once one is announced and a `Link` to the same resource in the next version
+#[derive(Clone)]
+pub struct Deprecation {
+    prefix: &'static str,
+    successor: &'static str,
+    sunset: Option<String>,
+}
+
+impl Deprecation {
+    pub fn new(prefix: &'static str, successor: &'static str, sunset: Option<String>) -> Self {
+        Self {
+            prefix,
+            successor,
+            sunset,
+        }
+    }
+
+    fn successor_path(&self, path: &str) -> Option<String> {
+        let rest = path.strip_prefix(self.prefix)?;
+        (rest.is_empty() || rest.starts_with('/')).then(|| format!("{}{}", self.successor, rest))
+    }
+}
+
+pub async fn deprecated(State(deprecation): State<Deprecation>, request: Request, next: Next) -> Response {
+    // Nested routers see the path without their prefix, the original one is what clients called
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or_else(|| request.uri().path().to_string(), |uri| uri.path().to_string());
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(HeaderName::from_static("deprecation"), HeaderValue::from_static("true"));
+    if let Some(Ok(sunset)) = deprecation.sunset.as_deref().map(HeaderValue::from_str) {
+        headers.insert(HeaderName::from_static("sunset"), sunset);
+    }
+    let link = deprecation.successor_path(&path).map(|successor| {
+        HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", successor))
+    });
+    if let Some(Ok(link)) = link {
+        headers.insert(header::LINK, link);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_successor_path() {
+        let deprecation = Deprecation::new("/api", "/api/v1", None);
+        assert_eq!(deprecation.successor_path("/api/launches").as_deref(), Some("/api/v1/launches"));
+        assert_eq!(deprecation.successor_path("/apix/launches"), None);
+
+        let deprecation = Deprecation::new("/api/v1", "/api/v2", None);
+        assert_eq!(
+            deprecation.successor_path("/api/v1/token/0x1").as_deref(),
+            Some("/api/v2/token/0x1")
+        );
+    }
+}
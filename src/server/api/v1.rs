@@ -0,0 +1,173 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use super::{load_launches, load_token, LaunchesPage, LaunchesQuery};
+use crate::server::AppState;
+use crate::storage::{LaunchRecord, LaunchTag};
+use crate::utils::types::common::{MemecoinInfo, PairActivity, RiskReport, TokenCategoryResponse};
+
+// The shapes integrators have consumed since before versioning, metrics as the strings the
+// aggregator produced. Field for field copies, so they don't move when MemecoinInfo does
+
+#[derive(Debug, Serialize)]
+pub struct CoinInfo {
+    address: String,
+    name: String,
+    symbol: String,
+    total_supply: String,
+    owner: String,
+    team_allocation: String,
+    price: String,
+    market_cap: String,
+    usd_dex_liquidity: String,
+    usd_token_liquidity: String,
+    usd_quote_liquidity: String,
+    pool_fee_percent: String,
+    lp_fee_apr: String,
+    risk: Option<RiskReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    activity: Option<PairActivity>,
+}
+
+impl From<&MemecoinInfo> for CoinInfo {
+    fn from(info: &MemecoinInfo) -> Self {
+        Self {
+            address: info.address.clone(),
+            name: info.name.clone(),
+            symbol: info.symbol.clone(),
+            total_supply: info.total_supply.clone(),
+            owner: info.owner.clone(),
+            team_allocation: info.team_allocation.clone(),
+            price: info.price.clone(),
+            market_cap: info.market_cap.clone(),
+            usd_dex_liquidity: info.usd_dex_liquidity.clone(),
+            usd_token_liquidity: info.usd_token_liquidity.clone(),
+            usd_quote_liquidity: info.usd_quote_liquidity.clone(),
+            pool_fee_percent: info.pool_fee_percent.clone(),
+            lp_fee_apr: info.lp_fee_apr.clone(),
+            risk: info.risk.clone(),
+            activity: info.activity.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Launch {
+    token_address: String,
+    name: Option<String>,
+    symbol: Option<String>,
+    owner: Option<String>,
+    quote_token: String,
+    exchange_name: String,
+    block_number: u64,
+    block_timestamp: u64,
+    coin_info: Option<CoinInfo>,
+    holders_data: Option<TokenCategoryResponse>,
+    tags: Vec<LaunchTag>,
+}
+
+impl From<LaunchRecord> for Launch {
+    fn from(launch: LaunchRecord) -> Self {
+        Self {
+            coin_info: launch.coin_info.as_ref().map(CoinInfo::from),
+            token_address: launch.token_address,
+            name: launch.name,
+            symbol: launch.symbol,
+            owner: launch.owner,
+            quote_token: launch.quote_token,
+            exchange_name: launch.exchange_name,
+            block_number: launch.block_number,
+            block_timestamp: launch.block_timestamp,
+            holders_data: launch.holders_data,
+            tags: launch.tags,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Launches {
+    launches: Vec<Launch>,
+    page: u32,
+    per_page: u32,
+    total: u64,
+}
+
+impl From<LaunchesPage> for Launches {
+    fn from(page: LaunchesPage) -> Self {
+        Self {
+            launches: page.launches.into_iter().map(Launch::from).collect(),
+            page: page.page,
+            per_page: page.per_page,
+            total: page.total,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Token {
+    coin_info: CoinInfo,
+    holders_data: TokenCategoryResponse,
+}
+
+// GET /api/v1/launches?page=&per_page=&tag=
+pub async fn launches(State(state): State<AppState>, Query(query): Query<LaunchesQuery>) -> Response {
+    match load_launches(&state, query).await {
+        Ok(page) => Json(Launches::from(page)).into_response(),
+        Err(response) => response,
+    }
+}
+
+// GET /api/v1/token/{address}
+pub async fn token_info(Path(address): Path<String>) -> Response {
+    match load_token(&address).await {
+        Ok((coin_info, holders_data)) => Json(Token {
+            coin_info: CoinInfo::from(&coin_info),
+            holders_data,
+        })
+        .into_response(),
+        Err(response) => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coin_info_keeps_the_legacy_shape() {
+        let info = MemecoinInfo {
+            address: "0x1".to_string(),
+            price: "0.0012".to_string(),
+            market_cap: "12000".to_string(),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(CoinInfo::from(&info)).unwrap();
+        let mut keys: Vec<&str> = json.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                "address",
+                "lp_fee_apr",
+                "market_cap",
+                "name",
+                "owner",
+                "pool_fee_percent",
+                "price",
+                "risk",
+                "symbol",
+                "team_allocation",
+                "total_supply",
+                "usd_dex_liquidity",
+                "usd_quote_liquidity",
+                "usd_token_liquidity",
+            ]
+        );
+        // Metrics stay strings in v1
+        assert_eq!(json["price"], "0.0012");
+    }
+}
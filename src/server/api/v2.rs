@@ -0,0 +1,199 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use super::{load_launches, load_token, LaunchesPage, LaunchesQuery};
+use crate::server::AppState;
+use crate::storage::{LaunchRecord, LaunchTag};
+use crate::utils::types::common::{
+    MemecoinInfo, PairActivity, RiskFlag, RiskLevel, RiskReport, TokenCategoryResponse,
+};
+
+// Metrics as numbers, null when the aggregator couldn't produce them, grouped by what they
+// describe. Token identity sits next to the metrics rather than inside them
+
+#[derive(Debug, Serialize)]
+pub struct Liquidity {
+    usd: Option<f64>,
+    token_usd: Option<f64>,
+    quote_usd: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Risk {
+    score: u8,
+    level: &'static str,
+    flags: Vec<RiskFlag>,
+}
+
+impl From<&RiskReport> for Risk {
+    fn from(risk: &RiskReport) -> Self {
+        Self {
+            score: risk.score,
+            level: match risk.level() {
+                RiskLevel::Low => "low",
+                RiskLevel::Medium => "medium",
+                RiskLevel::High => "high",
+            },
+            flags: risk.flags.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Metrics {
+    price_usd: Option<f64>,
+    market_cap_usd: Option<f64>,
+    // Raw token units, too large for a JSON number
+    total_supply: String,
+    team_allocation_pct: Option<f64>,
+    liquidity: Liquidity,
+    pool_fee_pct: Option<f64>,
+    lp_fee_apr_pct: Option<f64>,
+    risk: Option<Risk>,
+    activity: Option<PairActivity>,
+}
+
+impl From<&MemecoinInfo> for Metrics {
+    fn from(info: &MemecoinInfo) -> Self {
+        let number = |value: &str| value.parse::<f64>().ok().filter(|value| value.is_finite());
+        Self {
+            price_usd: number(&info.price),
+            market_cap_usd: number(&info.market_cap),
+            total_supply: info.total_supply.clone(),
+            team_allocation_pct: info.formatted().team_allocation_pct,
+            liquidity: Liquidity {
+                usd: number(&info.usd_dex_liquidity),
+                token_usd: number(&info.usd_token_liquidity),
+                quote_usd: number(&info.usd_quote_liquidity),
+            },
+            pool_fee_pct: number(&info.pool_fee_percent),
+            lp_fee_apr_pct: number(&info.lp_fee_apr),
+            risk: info.risk.as_ref().map(Risk::from),
+            activity: info.activity.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Launch {
+    address: String,
+    name: Option<String>,
+    symbol: Option<String>,
+    owner: Option<String>,
+    quote_token: String,
+    exchange: String,
+    block_number: u64,
+    launched_at: u64,
+    tags: Vec<LaunchTag>,
+    // Null for launches recorded while the aggregator was unreachable
+    metrics: Option<Metrics>,
+    holders: Option<TokenCategoryResponse>,
+}
+
+impl From<LaunchRecord> for Launch {
+    fn from(launch: LaunchRecord) -> Self {
+        Self {
+            metrics: launch.coin_info.as_ref().map(Metrics::from),
+            address: launch.token_address,
+            name: launch.name,
+            symbol: launch.symbol,
+            owner: launch.owner,
+            quote_token: launch.quote_token,
+            exchange: launch.exchange_name,
+            block_number: launch.block_number,
+            launched_at: launch.block_timestamp,
+            tags: launch.tags,
+            holders: launch.holders_data,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Pagination {
+    page: u32,
+    per_page: u32,
+    total: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Launches {
+    launches: Vec<Launch>,
+    pagination: Pagination,
+}
+
+impl From<LaunchesPage> for Launches {
+    fn from(page: LaunchesPage) -> Self {
+        Self {
+            launches: page.launches.into_iter().map(Launch::from).collect(),
+            pagination: Pagination {
+                page: page.page,
+                per_page: page.per_page,
+                total: page.total,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Token {
+    address: String,
+    name: String,
+    symbol: String,
+    owner: String,
+    metrics: Metrics,
+    holders: TokenCategoryResponse,
+}
+
+// GET /api/v2/launches?page=&per_page=&tag=
+pub async fn launches(State(state): State<AppState>, Query(query): Query<LaunchesQuery>) -> Response {
+    match load_launches(&state, query).await {
+        Ok(page) => Json(Launches::from(page)).into_response(),
+        Err(response) => response,
+    }
+}
+
+// GET /api/v2/token/{address}
+pub async fn token_info(Path(address): Path<String>) -> Response {
+    match load_token(&address).await {
+        Ok((info, holders)) => Json(Token {
+            metrics: Metrics::from(&info),
+            address: info.address,
+            name: info.name,
+            symbol: info.symbol,
+            owner: info.owner,
+            holders,
+        })
+        .into_response(),
+        Err(response) => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_are_numbers() {
+        let info = MemecoinInfo {
+            price: "0.0012".to_string(),
+            market_cap: "12000".to_string(),
+            usd_dex_liquidity: "n/a".to_string(),
+            pool_fee_percent: "1".to_string(),
+            risk: Some(RiskReport {
+                score: 60,
+                flags: vec![RiskFlag::OwnerNotRenounced],
+            }),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(Metrics::from(&info)).unwrap();
+        assert_eq!(json["price_usd"], 0.0012);
+        assert_eq!(json["market_cap_usd"], 12000.0);
+        assert!(json["liquidity"]["usd"].is_null());
+        assert_eq!(json["pool_fee_pct"], 1.0);
+        assert_eq!(json["risk"]["level"], "high");
+    }
+}
@@ -0,0 +1,121 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::json;
+
+use super::api::api_error;
+use super::AppState;
+
+pub const SIGNATURE_HEADER: &str = "x-signature";
+pub const TIMESTAMP_HEADER: &str = "x-signature-timestamp";
+
+// Signs response bodies so consumers can tell our data from a spoofed endpoint's.
+// The signed message is `<timestamp>.<body>`, binding the time so old responses can't be replayed
+pub struct Attestor {
+    key: SigningKey,
+}
+
+impl Attestor {
+    // `seed` is the hex encoded 32 byte ed25519 secret key
+    pub fn new(seed: &str) -> Result<Self, anyhow::Error> {
+        let bytes = hex::decode(seed.trim().trim_start_matches("0x"))?;
+        let seed: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| anyhow::Error::msg("API signing key must be 32 bytes"))?;
+        Ok(Self {
+            key: SigningKey::from_bytes(&seed),
+        })
+    }
+
+    pub fn public_key(&self) -> String {
+        hex::encode(self.key.verifying_key().to_bytes())
+    }
+
+    pub fn sign(&self, timestamp: u64, body: &[u8]) -> String {
+        let mut message = format!("{}.", timestamp).into_bytes();
+        message.extend_from_slice(body);
+        hex::encode(self.key.sign(&message).to_bytes())
+    }
+}
+
+// Adds the signature headers to responses of the routes it wraps
+pub async fn sign_response(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    let attestor = match &state.attestor {
+        Some(attestor) => attestor,
+        None => return response,
+    };
+
+    let (mut parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            eprintln!("Failed to buffer response for signing: {}", e);
+            return api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to sign response");
+        }
+    };
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let signature = attestor.sign(timestamp, &body);
+    if let (Ok(signature), Ok(timestamp)) = (
+        HeaderValue::from_str(&signature),
+        HeaderValue::from_str(&timestamp.to_string()),
+    ) {
+        parts.headers.insert(SIGNATURE_HEADER, signature);
+        parts.headers.insert(TIMESTAMP_HEADER, timestamp);
+    }
+    Response::from_parts(parts, Body::from(body))
+}
+
+// GET /.well-known/sniq-attestation.json
+pub async fn public_key(State(state): State<AppState>) -> Response {
+    match &state.attestor {
+        Some(attestor) => Json(json!({
+            "algorithm": "ed25519",
+            "public_key": attestor.public_key(),
+            "signature_header": SIGNATURE_HEADER,
+            "timestamp_header": TIMESTAMP_HEADER,
+            "message": "<timestamp>.<body>",
+        }))
+        .into_response(),
+        None => api_error(StatusCode::NOT_FOUND, "Responses are not signed"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    #[test]
+    fn test_signed_body_verifies_against_public_key() {
+        let attestor = Attestor::new(&format!("0x{}", "42".repeat(32))).unwrap();
+        let signature = attestor.sign(1_700_000_000, b"{\"price\":\"1\"}");
+
+        let public_key: [u8; 32] = hex::decode(attestor.public_key()).unwrap().try_into().unwrap();
+        let public_key = VerifyingKey::from_bytes(&public_key).unwrap();
+        let signature: [u8; 64] = hex::decode(signature).unwrap().try_into().unwrap();
+        let signature = Signature::from_bytes(&signature);
+        assert!(public_key
+            .verify(b"1700000000.{\"price\":\"1\"}", &signature)
+            .is_ok());
+        // A tampered body or a replayed signature with another timestamp doesn't verify
+        assert!(public_key
+            .verify(b"1700000000.{\"price\":\"2\"}", &signature)
+            .is_err());
+        assert!(public_key
+            .verify(b"1700000001.{\"price\":\"1\"}", &signature)
+            .is_err());
+
+        assert!(Attestor::new("abcd").is_err());
+        assert!(Attestor::new("not hex").is_err());
+    }
+}
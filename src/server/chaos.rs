@@ -0,0 +1,86 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, put},
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::api::api_error;
+use super::AppState;
+use crate::utils::chaos::{Fault, FaultRule, CHAOS};
+
+// Routes under /admin/chaos, behind the admin token like the rest of /admin
+pub fn routes() -> Router<AppState> {
+    println!("Fault injection enabled under /admin/chaos ⚠️");
+    Router::new()
+        .route("/chaos", get(list).delete(clear_all))
+        .route("/chaos/{fault}", put(inject).delete(clear))
+}
+
+fn parse_fault(fault: &str) -> Result<Fault, Box<Response>> {
+    Fault::parse(fault).ok_or_else(|| {
+        Box::new(api_error(
+            StatusCode::BAD_REQUEST,
+            "Fault must be explorer_error, rpc_timeout or telegram_rate_limit",
+        ))
+    })
+}
+
+// GET /admin/chaos
+pub async fn list() -> Response {
+    Json(json!({
+        "faults": CHAOS.active(),
+        "available": Fault::ALL,
+    }))
+    .into_response()
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FaultRequest {
+    // Defaults to failing every call
+    rate: Option<f64>,
+    // Defaults to failing until cleared
+    count: Option<u32>,
+}
+
+// PUT /admin/chaos/{fault}
+pub async fn inject(Path(fault): Path<String>, request: Option<Json<FaultRequest>>) -> Response {
+    let fault = match parse_fault(&fault) {
+        Ok(fault) => fault,
+        Err(response) => return *response,
+    };
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let rate = request.rate.unwrap_or(1f64);
+    if !(0f64..=1f64).contains(&rate) || request.count == Some(0) {
+        return api_error(StatusCode::BAD_REQUEST, "rate must be within 0 and 1, count positive");
+    }
+    CHAOS.inject(
+        fault,
+        FaultRule {
+            rate,
+            remaining: request.count,
+        },
+    );
+    list().await
+}
+
+// DELETE /admin/chaos/{fault}
+pub async fn clear(Path(fault): Path<String>) -> Response {
+    let fault = match parse_fault(&fault) {
+        Ok(fault) => fault,
+        Err(response) => return *response,
+    };
+    if !CHAOS.clear(Some(fault)) {
+        return api_error(StatusCode::NOT_FOUND, "Fault is not injected");
+    }
+    list().await
+}
+
+// DELETE /admin/chaos
+pub async fn clear_all() -> Response {
+    CHAOS.clear(None);
+    list().await
+}
@@ -0,0 +1,84 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use super::AppState;
+use crate::utils::types::common::MemecoinInfo;
+
+// GET /ws/launches, every launch as a JSON MemecoinInfo text frame as soon as it is aggregated
+pub async fn launches(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    // Subscribed before the upgrade, so nothing launched during the handshake is missed
+    let feed = state.launch_feed.subscribe();
+    ws.on_upgrade(move |socket| stream_launches(socket, feed))
+}
+
+async fn stream_launches(mut socket: WebSocket, mut feed: broadcast::Receiver<MemecoinInfo>) {
+    loop {
+        tokio::select! {
+            launch = next_launch(&mut feed) => {
+                let payload = match launch {
+                    Some(payload) => payload,
+                    None => return,
+                };
+                if socket.send(Message::Text(payload.into())).await.is_err() {
+                    return;
+                }
+            }
+            // Clients only ever close, pings are answered by axum
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+// The next launch as JSON, None once the feed is gone. A client too slow to keep up skips the
+// launches it missed rather than being disconnected
+async fn next_launch(feed: &mut broadcast::Receiver<MemecoinInfo>) -> Option<String> {
+    loop {
+        match feed.recv().await {
+            Ok(info) => match serde_json::to_string(&info) {
+                Ok(payload) => return Some(payload),
+                Err(e) => eprintln!("Failed to serialize launch {}: {}", info.address, e),
+            },
+            Err(RecvError::Lagged(skipped)) => {
+                eprintln!("Launch feed subscriber fell behind, skipped {} launches", skipped)
+            }
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launch(address: &str) -> MemecoinInfo {
+        MemecoinInfo {
+            address: address.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_subscribers_skip_missed_launches() {
+        let (feed, mut subscriber) = broadcast::channel(2);
+        for address in ["0x1", "0x2", "0x3"] {
+            feed.send(launch(address)).unwrap();
+        }
+        // The oldest launch was dropped for this subscriber, the feed carries on from there
+        let payload = next_launch(&mut subscriber).await.unwrap();
+        let info: MemecoinInfo = serde_json::from_str(&payload).unwrap();
+        assert_eq!(info.address, "0x2");
+
+        drop(feed);
+        assert_eq!(next_launch(&mut subscriber).await.map(|_| ()), Some(()));
+        assert_eq!(next_launch(&mut subscriber).await, None);
+    }
+}
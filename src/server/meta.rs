@@ -0,0 +1,69 @@
+use axum::{extract::State, Json};
+use serde::Serialize;
+
+use super::AppState;
+use crate::constant::network::NETWORK;
+use crate::utils::call::get_latest_block;
+
+// Commit the binary was built from, see build.rs
+const GIT_HASH: &str = env!("GIT_HASH");
+
+#[derive(Debug, Serialize)]
+pub struct Contracts {
+    memecoin_factory: String,
+    exchange: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Indexer {
+    start_block: Option<u64>,
+    last_indexed_block: Option<u64>,
+}
+
+// What a deployment runs, for integrators and support checking they talk to the right one
+#[derive(Debug, Serialize)]
+pub struct Meta {
+    network: &'static str,
+    version: &'static str,
+    git_hash: &'static str,
+    contracts: Contracts,
+    indexer: Indexer,
+    // Chain head as seen by the RPC pool, null when it can't be reached
+    current_block: Option<u64>,
+    features: Vec<&'static str>,
+    notifiers: Vec<String>,
+}
+
+// GET /api/v1/meta
+pub async fn meta(State(state): State<AppState>) -> Json<Meta> {
+    let mut features = state.bot.features();
+    if state.admin_token.is_some() {
+        features.push("admin_api");
+    }
+    if state.attestor.is_some() {
+        features.push("signed_responses");
+    }
+    let current_block = match get_latest_block().await {
+        Ok((block_number, _)) => Some(block_number),
+        Err(e) => {
+            eprintln!("Failed to fetch the latest block for /meta: {}", e);
+            None
+        }
+    };
+    Json(Meta {
+        network: NETWORK.network.as_str(),
+        version: env!("CARGO_PKG_VERSION"),
+        git_hash: GIT_HASH,
+        contracts: Contracts {
+            memecoin_factory: NETWORK.memecoin_factory.clone(),
+            exchange: NETWORK.exchange.clone(),
+        },
+        indexer: Indexer {
+            start_block: state.checkpoints.starting_block(),
+            last_indexed_block: state.checkpoints.last_block().await,
+        },
+        current_block,
+        features,
+        notifiers: state.dispatcher.destinations(),
+    })
+}
@@ -0,0 +1,244 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::{
+    extract::State,
+    middleware,
+    routing::{get, post, put},
+    Json, Router,
+};
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+
+use crate::constant::network::NETWORK;
+use crate::indexer::checkpoint::Checkpoints;
+use crate::notifier::NotificationDispatcher;
+use crate::storage::Storage;
+use crate::telegram::experiment::AlertExperiment;
+use crate::telegram::maintenance::Maintenance;
+use crate::telegram::TelegramBot;
+use crate::utils::types::common::MemecoinInfo;
+use crate::utils::rpc::{rpc_pool, ProviderPool};
+use api::Deprecation;
+use attestation::Attestor;
+
+mod admin;
+mod api;
+mod attestation;
+#[cfg(feature = "chaos")]
+mod chaos;
+mod feed;
+mod meta;
+mod redirect;
+mod stats_page;
+mod token_page;
+mod widget;
+
+// Launches a subscriber can fall behind by before it starts skipping them
+const LAUNCH_FEED_CAPACITY: usize = 256;
+
+// Aggregated launches pushed to /ws/launches subscribers
+pub type LaunchFeed = broadcast::Sender<MemecoinInfo>;
+
+pub fn launch_feed() -> LaunchFeed {
+    broadcast::channel(LAUNCH_FEED_CAPACITY).0
+}
+
+// Configuration struct for the HTTP server
+#[derive(Clone)]
+pub struct ServerConfig {
+    port: u16,
+    dex_url: String,
+    // The operator API under /admin is only mounted when a token is set
+    admin_token: Option<String>,
+    // Hex ed25519 secret key, API responses are signed when set
+    signing_key: Option<String>,
+    // HTTP date after which /api/v1 may go away, v1 responses are marked deprecated once set
+    api_v1_sunset: Option<String>,
+}
+
+impl ServerConfig {
+    pub fn new() -> Self {
+        Self {
+            port: std::env::var("PORT")
+                .ok()
+                .and_then(|v| v.parse::<u16>().ok())
+                .unwrap_or(8080),
+            dex_url: std::env::var("DEX_URL").unwrap_or_else(|_| "https://app.avnu.fi".to_string()),
+            admin_token: std::env::var("ADMIN_API_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty()),
+            signing_key: std::env::var("API_SIGNING_KEY")
+                .ok()
+                .filter(|key| !key.is_empty()),
+            api_v1_sunset: std::env::var("API_V1_SUNSET")
+                .ok()
+                .filter(|sunset| !sunset.is_empty()),
+        }
+    }
+}
+
+// Shared state handed to route handlers
+#[derive(Clone)]
+pub struct AppState {
+    pub dex_url: String,
+    pub experiment: Arc<AlertExperiment>,
+    pub maintenance: Arc<Maintenance>,
+    pub storage: Arc<Storage>,
+    pub bot: Arc<TelegramBot>,
+    pub dispatcher: Arc<NotificationDispatcher>,
+    pub admin_token: Option<String>,
+    pub attestor: Option<Arc<Attestor>>,
+    pub checkpoints: Arc<Checkpoints>,
+    pub launch_feed: LaunchFeed,
+    // Uptime on the stats page counts from here
+    pub started_at: Instant,
+}
+
+pub async fn run_server(
+    config: ServerConfig,
+    bot: Arc<TelegramBot>,
+    dispatcher: Arc<NotificationDispatcher>,
+    storage: Arc<Storage>,
+    checkpoints: Arc<Checkpoints>,
+    launch_feed: LaunchFeed,
+) -> Result<(), anyhow::Error> {
+    // A key that is set but invalid must not silently serve unsigned data
+    let attestor = match &config.signing_key {
+        Some(key) => Some(Arc::new(
+            Attestor::new(key).map_err(|e| anyhow::anyhow!("Invalid API_SIGNING_KEY: {}", e))?,
+        )),
+        None => None,
+    };
+    let state = AppState {
+        dex_url: config.dex_url.clone(),
+        experiment: bot.experiment(),
+        maintenance: bot.maintenance(),
+        storage,
+        bot,
+        dispatcher,
+        admin_token: config.admin_token.clone(),
+        attestor,
+        checkpoints,
+        launch_feed,
+        started_at: Instant::now(),
+    };
+
+    // Routes whose JSON shape doesn't depend on the API version
+    let shared = || {
+        Router::new()
+            .route("/meta", get(meta::meta))
+            .route("/reports/weekly", get(api::weekly_report))
+            .route("/widget/latest", get(widget::latest))
+            .route("/wallet/{address}/holdings", get(api::wallet_holdings))
+            .route("/wallet/{address}/spot/{token}", get(api::wallet_spot))
+    };
+    let mut v1 = Router::new()
+        .route("/launches", get(api::v1::launches))
+        .route("/token/{address}", get(api::v1::token_info))
+        .merge(shared());
+    if let Some(sunset) = &config.api_v1_sunset {
+        let deprecation = Deprecation::new("/api/v1", "/api/v2", Some(sunset.clone()));
+        v1 = v1.route_layer(middleware::from_fn_with_state(deprecation, api::deprecated));
+    }
+    let v2 = Router::new()
+        .route("/launches", get(api::v2::launches))
+        .route("/token/{address}", get(api::v2::token_info))
+        .merge(shared());
+    // Unversioned paths from before versioning keep serving v1 shapes
+    let legacy = Router::new()
+        .route("/launches", get(api::v1::launches))
+        .route("/token/{address}", get(api::v1::token_info))
+        .route("/wallet/{address}/holdings", get(api::wallet_holdings))
+        .route("/wallet/{address}/spot/{token}", get(api::wallet_spot))
+        .route_layer(middleware::from_fn_with_state(
+            Deprecation::new("/api", "/api/v1", config.api_v1_sunset.clone()),
+            api::deprecated,
+        ));
+
+    // Data routes, whose responses carry a signature when a signing key is configured
+    let mut data = Router::new()
+        .nest("/api/v1", v1)
+        .nest("/api/v2", v2)
+        .nest("/api", legacy)
+        .route("/t/{address}/metrics.json", get(token_page::token_metrics_json));
+    if let Some(attestor) = &state.attestor {
+        data = data.route_layer(middleware::from_fn_with_state(state.clone(), attestation::sign_response));
+        println!("Signing API responses with key {} ✓", attestor.public_key());
+    }
+
+    let mut app = Router::new()
+        .route("/health", get(health))
+        .route("/stats", get(stats_page::stats_page))
+        .route("/ws/launches", get(feed::launches))
+        .route("/.well-known/sniq-attestation.json", get(attestation::public_key))
+        .route("/t/{address}", get(token_page::token_page))
+        .route("/t/{address}/metrics", get(token_page::token_metrics_partial))
+        .route("/r/{variant}/{address}", get(redirect::track_click))
+        .merge(data);
+
+    if config.admin_token.is_some() {
+        let admin = Router::new()
+            .route("/stats", get(admin::stats))
+            .route("/reprocess/{address}", post(admin::reprocess))
+            .route("/backfill", post(admin::backfill))
+            .route("/blacklist", get(admin::list_blacklist))
+            .route(
+                "/blacklist/{address}",
+                put(admin::add_blacklist).delete(admin::remove_blacklist),
+            )
+            .route("/settings", get(admin::settings))
+            .route("/settings/experiment", post(admin::update_experiment))
+            .route(
+                "/settings/templates/{kind}/{variant}",
+                put(admin::set_template).delete(admin::remove_template),
+            )
+            .route(
+                "/settings/maintenance",
+                post(admin::schedule_maintenance).delete(admin::cancel_maintenance),
+            );
+        // Fault injection only exists in builds with the `chaos` feature
+        #[cfg(feature = "chaos")]
+        let admin = admin.merge(chaos::routes());
+        let admin = admin.route_layer(middleware::from_fn_with_state(state.clone(), admin::require_token));
+        app = app.nest("/admin", admin);
+        println!("Operator API enabled under /admin ✓");
+    }
+    let app = app.with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", config.port)).await?;
+    println!("HTTP server listening on port {} ✓", config.port);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn health(State(state): State<AppState>) -> Json<Value> {
+    let status = if state.maintenance.is_active().await {
+        "maintenance"
+    } else {
+        "ok"
+    };
+    Json(json!({
+        "status": status,
+        "maintenance": state.maintenance.window().await,
+        "network": NETWORK.network.as_str(),
+        "rpc": rpc_pool().map(ProviderPool::status),
+        "schema_version": state.storage.schema_version().await.ok(),
+    }))
+}
+
+// Escapes user-derived strings (token names, symbols) before embedding them in HTML
+pub fn escape_html(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
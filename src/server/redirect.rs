@@ -0,0 +1,50 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use serde::Deserialize;
+use url::Url;
+
+use super::AppState;
+use crate::telegram::experiment::Variant;
+use crate::utils::address::canonicalize_address;
+
+#[derive(Debug, Deserialize)]
+pub struct ClickParams {
+    amount: Option<u64>,
+    symbol: Option<String>,
+}
+
+// Counts a buy button click for the experiment variant, then forwards to the DEX
+pub async fn track_click(
+    State(state): State<AppState>,
+    Path((variant, address)): Path<(String, String)>,
+    Query(params): Query<ClickParams>,
+) -> Response {
+    let address = match canonicalize_address(&address) {
+        Some(address) => address,
+        None => return (StatusCode::BAD_REQUEST, "Invalid token address").into_response(),
+    };
+
+    if let Some(variant) = Variant::parse(&variant) {
+        state.experiment.record_click(variant).await;
+    }
+
+    // Only ever redirect to the configured DEX, never to a caller supplied URL
+    let mut query = vec![("token", address)];
+    if let Some(amount) = params.amount {
+        query.push(("amount", amount.to_string()));
+    }
+    if let Some(symbol) = params.symbol {
+        query.push(("symbol", symbol));
+    }
+
+    match Url::parse_with_params(&state.dex_url, &query) {
+        Ok(url) => Redirect::temporary(url.as_str()).into_response(),
+        Err(e) => {
+            eprintln!("Invalid DEX_URL {}: {}", state.dex_url, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Invalid DEX url").into_response()
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use axum::{
+    extract::State,
+    http::{header, HeaderValue, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+
+use super::AppState;
+use crate::storage::ALERTS_DELIVERED;
+
+// Linked from community pages, a minute old figures are fine
+const CACHE_MAX_AGE_SECS: u64 = 60;
+
+// GET /stats, public usage figures rendered server side
+pub async fn stats_page(State(state): State<AppState>) -> Response {
+    let (launches, alerts) = match tokio::try_join!(
+        state.storage.count_launches(None),
+        state.storage.counter(ALERTS_DELIVERED)
+    ) {
+        Ok(figures) => figures,
+        Err(e) => {
+            eprintln!("Stats page failed to load figures: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to load stats").into_response();
+        }
+    };
+
+    let mut response = Html(format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1">
+  <title>sniQ · stats</title>
+  <style>
+    body {{ font-family: system-ui, sans-serif; background: #0d0f14; color: #e8e8e8; max-width: 640px; margin: 2rem auto; padding: 0 1rem; }}
+    dl {{ display: grid; grid-template-columns: max-content auto; gap: .5rem 1.5rem; font-size: 1.25rem; }}
+    dt {{ color: #9aa4b2; }}
+    dd {{ margin: 0; font-weight: 600; }}
+  </style>
+</head>
+<body>
+  <h1>⚡ sniQ stats</h1>
+  <dl>
+    <dt>Launches indexed</dt><dd>{launches}</dd>
+    <dt>Alerts delivered</dt><dd>{alerts}</dd>
+    <dt>Uptime</dt><dd>{uptime}</dd>
+  </dl>
+</body>
+</html>"#,
+        launches = format_count(launches),
+        alerts = format_count(alerts),
+        uptime = format_uptime(state.started_at.elapsed()),
+    ))
+    .into_response();
+    if let Ok(cache_control) = HeaderValue::from_str(&format!("public, max-age={}", CACHE_MAX_AGE_SECS)) {
+        response.headers_mut().insert(header::CACHE_CONTROL, cache_control);
+    }
+    response
+}
+
+// 1234567 -> 1,234,567
+fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+// Two largest units, e.g. 3d 4h or 12m
+fn format_uptime(uptime: Duration) -> String {
+    let secs = uptime.as_secs();
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1_000), "1,000");
+        assert_eq!(format_count(1_234_567), "1,234,567");
+    }
+
+    #[test]
+    fn test_format_uptime() {
+        assert_eq!(format_uptime(Duration::from_secs(59)), "0m");
+        assert_eq!(format_uptime(Duration::from_secs(2 * 3_600 + 5 * 60)), "2h 5m");
+        assert_eq!(format_uptime(Duration::from_secs(3 * 86_400 + 4 * 3_600 + 59)), "3d 4h");
+    }
+}
@@ -0,0 +1,153 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use super::escape_html;
+use crate::utils::address::canonicalize_address;
+use crate::utils::info_aggregator::aggregate_info;
+
+// How often the page polls the metrics partial
+const REFRESH_SECONDS: u64 = 15;
+
+#[derive(Debug, Serialize)]
+pub struct TokenMetrics {
+    pub address: String,
+    pub name: String,
+    pub symbol: String,
+    pub price: String,
+    pub market_cap: String,
+    pub holders: String,
+    pub liquidity: String,
+}
+
+async fn fetch_metrics(address: &str) -> Result<TokenMetrics, Response> {
+    let address = canonicalize_address(address)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, "Invalid token address").into_response())?;
+
+    match aggregate_info(&address).await {
+        Ok((info, holders)) => Ok(TokenMetrics {
+            address: info.address,
+            name: info.name,
+            symbol: info.symbol,
+            price: info.price,
+            market_cap: info.market_cap,
+            // Holder categories carry Telegram HTML
+            holders: plain_text(&holders.category).trim().to_string(),
+            liquidity: info.usd_dex_liquidity,
+        }),
+        Err(e) => {
+            eprintln!("Failed to fetch metrics for {}: {}", address, e);
+            Err((StatusCode::BAD_GATEWAY, "Failed to fetch token metrics").into_response())
+        }
+    }
+}
+
+// Drops the tags and entities of Telegram HTML, the page escapes the text again itself
+fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+fn format_usd(value: &str) -> String {
+    match value.parse::<f64>() {
+        Ok(value) if value >= 1.0 => format!("${:.2}", value),
+        Ok(value) => format!("${:.8}", value),
+        Err(_) => "—".to_string(),
+    }
+}
+
+pub async fn token_page(Path(address): Path<String>) -> Response {
+    let address = match canonicalize_address(&address) {
+        Some(address) => address,
+        None => return (StatusCode::BAD_REQUEST, "Invalid token address").into_response(),
+    };
+
+    Html(format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1">
+  <title>sniQ · {address}</title>
+  <script src="https://unpkg.com/htmx.org@2.0.4"></script>
+  <style>
+    body {{ font-family: system-ui, sans-serif; background: #0d0f14; color: #e8e8e8; max-width: 640px; margin: 2rem auto; padding: 0 1rem; }}
+    code {{ word-break: break-all; color: #9aa4b2; }}
+    dl {{ display: grid; grid-template-columns: max-content auto; gap: .5rem 1.5rem; }}
+    dt {{ color: #9aa4b2; }}
+  </style>
+</head>
+<body>
+  <h1>⚡ sniQ radar</h1>
+  <code>{address}</code>
+  <div hx-get="/t/{address}/metrics" hx-trigger="load, every {refresh}s" hx-swap="innerHTML">
+    <p>Loading metrics…</p>
+  </div>
+</body>
+</html>"#,
+        address = address,
+        refresh = REFRESH_SECONDS,
+    ))
+    .into_response()
+}
+
+// HTML fragment swapped in by the page every REFRESH_SECONDS
+pub async fn token_metrics_partial(Path(address): Path<String>) -> Response {
+    let metrics = match fetch_metrics(&address).await {
+        Ok(metrics) => metrics,
+        Err(response) => return response,
+    };
+
+    Html(format!(
+        r#"<h2>{name} (${symbol})</h2>
+<dl>
+  <dt>Price</dt><dd>{price}</dd>
+  <dt>Market cap</dt><dd>{market_cap}</dd>
+  <dt>Liquidity</dt><dd>{liquidity}</dd>
+  <dt>Holders</dt><dd>{holders}</dd>
+</dl>"#,
+        name = escape_html(&metrics.name),
+        symbol = escape_html(&metrics.symbol),
+        price = format_usd(&metrics.price),
+        market_cap = format_usd(&metrics.market_cap),
+        liquidity = format_usd(&metrics.liquidity),
+        holders = escape_html(&metrics.holders),
+    ))
+    .into_response()
+}
+
+pub async fn token_metrics_json(Path(address): Path<String>) -> Response {
+    match fetch_metrics(&address).await {
+        Ok(metrics) => Json(metrics).into_response(),
+        Err(response) => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text_holder_category() {
+        assert_eq!(
+            plain_text(" <b>&lt;10</b> — <b>Early bird special! 🌱</b>").trim(),
+            "<10 — Early bird special! 🌱"
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+use super::api::api_error;
+use super::AppState;
+use crate::storage::{LaunchRecord, LaunchTag};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 25;
+// Embedding sites may serve the ticker from their CDN for this long
+const CACHE_MAX_AGE_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidgetField {
+    Address,
+    Name,
+    Symbol,
+    Price,
+    MarketCap,
+    Liquidity,
+    LaunchedAt,
+    Risk,
+    Url,
+}
+
+impl WidgetField {
+    const ALL: [WidgetField; 9] = [
+        WidgetField::Address,
+        WidgetField::Name,
+        WidgetField::Symbol,
+        WidgetField::Price,
+        WidgetField::MarketCap,
+        WidgetField::Liquidity,
+        WidgetField::LaunchedAt,
+        WidgetField::Risk,
+        WidgetField::Url,
+    ];
+    const DEFAULT: [WidgetField; 5] = [
+        WidgetField::Symbol,
+        WidgetField::Address,
+        WidgetField::MarketCap,
+        WidgetField::LaunchedAt,
+        WidgetField::Url,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            WidgetField::Address => "address",
+            WidgetField::Name => "name",
+            WidgetField::Symbol => "symbol",
+            WidgetField::Price => "price",
+            WidgetField::MarketCap => "market_cap",
+            WidgetField::Liquidity => "liquidity",
+            WidgetField::LaunchedAt => "launched_at",
+            WidgetField::Risk => "risk",
+            WidgetField::Url => "url",
+        }
+    }
+
+    fn parse(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|field| field.key() == key)
+    }
+}
+
+// Comma separated field names, the defaults when none are given
+fn parse_fields(fields: Option<&str>) -> Result<Vec<WidgetField>, String> {
+    let fields = match fields.map(str::trim).filter(|fields| !fields.is_empty()) {
+        Some(fields) => fields,
+        None => return Ok(WidgetField::DEFAULT.to_vec()),
+    };
+    let mut parsed = Vec::new();
+    for key in fields.split(',').map(|key| key.trim().to_lowercase()) {
+        let field = WidgetField::parse(&key).ok_or_else(|| {
+            let known: Vec<&str> = WidgetField::ALL.iter().map(|field| field.key()).collect();
+            format!("Unknown field {}, use {}", key, known.join(", "))
+        })?;
+        if !parsed.contains(&field) {
+            parsed.push(field);
+        }
+    }
+    Ok(parsed)
+}
+
+// Metrics are numbers rather than the strings stored, launches recorded without metrics get nulls
+fn widget_entry(launch: &LaunchRecord, fields: &[WidgetField], dex_url: &str) -> Value {
+    let number = |value: Option<&String>| value.and_then(|value| value.parse::<f64>().ok());
+    let info = launch.coin_info.as_ref();
+    let mut entry = Map::new();
+    for field in fields {
+        let value = match field {
+            WidgetField::Address => json!(launch.token_address),
+            WidgetField::Name => json!(launch.name),
+            WidgetField::Symbol => json!(launch.symbol),
+            WidgetField::Price => json!(number(info.map(|info| &info.price))),
+            WidgetField::MarketCap => json!(number(info.map(|info| &info.market_cap))),
+            WidgetField::Liquidity => json!(number(info.map(|info| &info.usd_dex_liquidity))),
+            WidgetField::LaunchedAt => json!(launch.block_timestamp),
+            WidgetField::Risk => json!(info
+                .and_then(|info| info.risk.as_ref())
+                .map(|risk| json!({ "score": risk.score, "level": risk.level().label() }))),
+            WidgetField::Url => json!(format!("{}?token={}", dex_url, launch.token_address)),
+        };
+        entry.insert(field.key().to_string(), value);
+    }
+    Value::Object(entry)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WidgetQuery {
+    limit: Option<u32>,
+    fields: Option<String>,
+    tag: Option<String>,
+}
+
+// GET /api/v1/widget/latest?limit=&fields=&tag=
+pub async fn latest(
+    State(state): State<AppState>,
+    Query(query): Query<WidgetQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let fields = match parse_fields(query.fields.as_deref()) {
+        Ok(fields) => fields,
+        Err(message) => return api_error(StatusCode::BAD_REQUEST, &message),
+    };
+    let tag = match query.tag.as_deref().map(LaunchTag::parse) {
+        Some(None) => return api_error(StatusCode::BAD_REQUEST, "Unknown tag, use gem, sus or test"),
+        Some(tag) => tag,
+        None => None,
+    };
+
+    let launches = match state.storage.recent_launches(limit, 0, tag).await {
+        Ok(launches) => launches,
+        Err(e) => {
+            eprintln!("Widget failed to load launches: {}", e);
+            return api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load launches");
+        }
+    };
+    let entries: Vec<Value> = launches
+        .iter()
+        .map(|launch| widget_entry(launch, &fields, &state.dex_url))
+        .collect();
+    let body = json!({ "launches": entries }).to_string();
+
+    // The ETag only changes with the content, so polling tickers mostly get a bodyless 304
+    let etag = format!("\"{}\"", hex::encode(&Sha256::digest(body.as_bytes())[..16]));
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+
+    let mut response = if not_modified {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        ([(header::CONTENT_TYPE, "application/json")], body).into_response()
+    };
+    let response_headers = response.headers_mut();
+    if let Ok(etag) = HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, etag);
+    }
+    if let Ok(cache_control) = HeaderValue::from_str(&format!("public, max-age={}", CACHE_MAX_AGE_SECS)) {
+        response_headers.insert(header::CACHE_CONTROL, cache_control);
+    }
+    // Read by scripts running on other sites
+    response_headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_widget_fields() {
+        assert_eq!(parse_fields(None).unwrap(), WidgetField::DEFAULT.to_vec());
+        assert_eq!(parse_fields(Some(" ")).unwrap(), WidgetField::DEFAULT.to_vec());
+        assert_eq!(
+            parse_fields(Some("Symbol, market_cap,symbol")).unwrap(),
+            vec![WidgetField::Symbol, WidgetField::MarketCap]
+        );
+        assert!(parse_fields(Some("symbol,owner")).unwrap_err().contains("owner"));
+    }
+}
@@ -0,0 +1,71 @@
+use serde::Serialize;
+
+use super::{BlockRef, Storage};
+use crate::indexer::{decode_event, EventType, SUBSCRIPTIONS};
+use crate::utils::address::to_canonical_hex;
+use crate::utils::call::get_block_timestamp;
+use crate::utils::info_aggregator::aggregate_info;
+
+// Outcome of a backfill run
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct BackfillReport {
+    pub creations: usize,
+    pub launches: usize,
+    pub failed: usize,
+}
+
+// Records factory events from a past block range without broadcasting anything
+pub async fn backfill(
+    storage: &Storage,
+    from_block: u64,
+    to_block: u64,
+    with_metrics: bool,
+) -> anyhow::Result<BackfillReport> {
+    let events = SUBSCRIPTIONS.events(from_block, to_block).await?;
+
+    let mut report = BackfillReport::default();
+    for event in events {
+        let block_number = match event.block_number {
+            Some(block_number) => block_number,
+            // Pending events will reach us through the indexer
+            None => continue,
+        };
+        let block_timestamp = get_block_timestamp(block_number).await?;
+        let block = BlockRef {
+            block_number,
+            block_timestamp,
+        };
+
+        match decode_event(Some(&event.from_address), &event.keys, event.data) {
+            Ok(Some(EventType::Creation(creation))) => {
+                storage.record_creation(&creation, block).await?;
+                report.creations += 1;
+            }
+            Ok(Some(EventType::Launch(launch))) => {
+                let metrics = if with_metrics {
+                    aggregate_info(&to_canonical_hex(&launch.memecoin_address))
+                        .await
+                        .map_err(|e| eprintln!("Backfill failed to aggregate launch: {}", e))
+                        .ok()
+                } else {
+                    None
+                };
+                storage
+                    .record_launch(
+                        &launch,
+                        block,
+                        metrics.as_ref().map(|(info, holders)| (info, holders)),
+                    )
+                    .await?;
+                report.launches += 1;
+            }
+            // Factory events never decode to transfers
+            Ok(Some(EventType::Transfer(_))) | Ok(None) => {}
+            Err(e) => {
+                eprintln!("Backfill failed to decode event at {}: {:#}", block_number, e);
+                report.failed += 1;
+            }
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,167 @@
+use std::time::{Duration, Instant};
+
+use super::backfill::backfill;
+use super::{ImportProgress, Storage};
+use crate::constant::network::NETWORK;
+use crate::utils::call::{get_deployment_block, get_latest_block};
+
+// Attempts per chunk before the import stops, a rerun resumes from the failed chunk
+const MAX_CHUNK_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+// Options of `meme-sniper import`, e.g. `import --from 600000 --chunk 5000 --metrics`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportOptions {
+    // The factory's deployment block by default
+    pub from_block: Option<u64>,
+    // The chain head when the import starts by default, the live indexer covers what follows
+    pub to_block: Option<u64>,
+    pub chunk_blocks: u64,
+    pub with_metrics: bool,
+    // Ignores saved progress and starts over from `from_block`
+    pub restart: bool,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            from_block: None,
+            to_block: None,
+            chunk_blocks: 10_000,
+            with_metrics: false,
+            restart: false,
+        }
+    }
+}
+
+impl ImportOptions {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut number = |name: &str| {
+                args.next()
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .ok_or_else(|| format!("{} expects a block number", name))
+            };
+            match arg.as_str() {
+                "--from" => options.from_block = Some(number("--from")?),
+                "--to" => options.to_block = Some(number("--to")?),
+                "--chunk" => options.chunk_blocks = number("--chunk")?.max(1),
+                "--metrics" => options.with_metrics = true,
+                "--restart" => options.restart = true,
+                unknown => return Err(format!("Unknown import option {}", unknown)),
+            }
+        }
+        let range = options.from_block.zip(options.to_block);
+        if range.is_some_and(|(from_block, to_block)| to_block < from_block) {
+            return Err("--to must not be before --from".to_string());
+        }
+        Ok(options)
+    }
+}
+
+// Records the factory's whole history chunk by chunk without broadcasting anything. Progress is
+// saved after every chunk, so an interrupted import picks up where it stopped
+pub async fn run_import(storage: &Storage, options: &ImportOptions) -> anyhow::Result<ImportProgress> {
+    let factory = &NETWORK.memecoin_factory;
+    let saved = if options.restart {
+        None
+    } else {
+        storage.import_progress(factory).await?
+    };
+    let to_block = match options.to_block {
+        Some(to_block) => to_block,
+        None => get_latest_block().await?.0,
+    };
+    let mut progress = match saved {
+        Some(progress) => {
+            println!(
+                "Resuming import at block {}: {} creations, {} launches so far",
+                progress.next_block, progress.creations, progress.launches
+            );
+            progress
+        }
+        None => {
+            let from_block = match options.from_block {
+                Some(from_block) => from_block,
+                // Blocks before the factory existed hold none of its events
+                None => {
+                    let deployment_block = get_deployment_block(factory, to_block).await?;
+                    println!("Factory deployed at block {}, importing from there", deployment_block);
+                    deployment_block
+                }
+            };
+            ImportProgress {
+                next_block: from_block,
+                ..Default::default()
+            }
+        }
+    };
+
+    let first_block = progress.next_block;
+    let started = Instant::now();
+    while progress.next_block <= to_block {
+        let from_block = progress.next_block;
+        let chunk_end = to_block.min(from_block.saturating_add(options.chunk_blocks - 1));
+
+        let mut attempt = 1;
+        let report = loop {
+            match backfill(storage, from_block, chunk_end, options.with_metrics).await {
+                Ok(report) => break report,
+                Err(e) if attempt < MAX_CHUNK_ATTEMPTS => {
+                    eprintln!("Import of blocks {}..{} failed, retrying: {}", from_block, chunk_end, e);
+                    tokio::time::sleep(RETRY_DELAY * attempt).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.context(format!("Import of blocks {}..{}", from_block, chunk_end))),
+            }
+        };
+
+        progress.creations += report.creations;
+        progress.launches += report.launches;
+        progress.failed += report.failed;
+        progress.next_block = chunk_end + 1;
+        storage.save_import_progress(factory, &progress).await?;
+
+        let done = progress.next_block - first_block;
+        let remaining = to_block + 1 - progress.next_block;
+        let eta = started.elapsed().as_secs_f64() / done as f64 * remaining as f64;
+        println!(
+            "Imported up to block {} ({:.1}%): {} creations, {} launches, {} failed, ~{}s left",
+            chunk_end,
+            done as f64 * 100f64 / (done + remaining) as f64,
+            progress.creations,
+            progress.launches,
+            progress.failed,
+            eta.round()
+        );
+    }
+    Ok(progress)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_import_options() {
+        assert_eq!(ImportOptions::parse(&[]).unwrap(), ImportOptions::default());
+        assert_eq!(
+            ImportOptions::parse(&args(&["--from", "600000", "--to", "700000", "--metrics"])).unwrap(),
+            ImportOptions {
+                from_block: Some(600_000),
+                to_block: Some(700_000),
+                with_metrics: true,
+                ..Default::default()
+            }
+        );
+        assert!(ImportOptions::parse(&args(&["--from"])).is_err());
+        assert!(ImportOptions::parse(&args(&["--from", "10", "--to", "5"])).is_err());
+        assert!(ImportOptions::parse(&args(&["--alerts"])).is_err());
+    }
+}
@@ -0,0 +1,1731 @@
+use serde::Serialize;
+use sqlx::migrate::{MigrateError, Migrator};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::Row;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::utils::address::to_canonical_hex;
+use crate::utils::event_parser::{CreationEvent, LaunchEvent};
+use crate::utils::types::common::{MemecoinInfo, TokenCategoryResponse};
+
+pub mod backfill;
+pub mod import;
+pub mod report;
+
+// Schema changes live in migrations/ and are applied in order at startup, sqlx records
+// applied versions in _sqlx_migrations
+static MIGRATOR: Migrator = sqlx::migrate!();
+
+// Counter of Telegram alerts that reached a chat
+pub const ALERTS_DELIVERED: &str = "alerts_delivered";
+// Broadcasts Telegram refused, e.g. the chat blocked the bot
+pub const DELIVERY_FAILURES: &str = "delivery_failures";
+// Indexed events handled by the consumer, and how many of them failed
+pub const EVENTS_PROCESSED: &str = "events_processed";
+pub const EVENT_ERRORS: &str = "event_errors";
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("Migration error: {0}")]
+    Migration(#[from] MigrateError),
+}
+
+// Configuration for the event history database
+#[derive(Clone)]
+pub struct StorageConfig {
+    database_url: String,
+}
+
+impl StorageConfig {
+    pub fn new() -> Self {
+        Self {
+            database_url: std::env::var("DATABASE_URL")
+                .unwrap_or_else(|_| "sqlite://starksnipe.db".to_string()),
+        }
+    }
+
+    // Throwaway database for tests of the modules built on storage
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self {
+            database_url: "sqlite::memory:".to_string(),
+        }
+    }
+}
+
+// Block an event was observed in
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BlockRef {
+    pub block_number: u64,
+    pub block_timestamp: u64,
+}
+
+// A stored launch joined with its creation event when we saw one
+#[derive(Debug, Clone, Serialize)]
+pub struct LaunchRecord {
+    pub token_address: String,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub owner: Option<String>,
+    pub quote_token: String,
+    pub exchange_name: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub coin_info: Option<MemecoinInfo>,
+    pub holders_data: Option<TokenCategoryResponse>,
+    pub tags: Vec<LaunchTag>,
+}
+
+// Human curation label applied to a launch by curator chats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LaunchTag {
+    Gem,
+    Sus,
+    Test,
+}
+
+impl LaunchTag {
+    pub const ALL: [LaunchTag; 3] = [LaunchTag::Gem, LaunchTag::Sus, LaunchTag::Test];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LaunchTag::Gem => "gem",
+            LaunchTag::Sus => "sus",
+            LaunchTag::Test => "test",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "gem" => Some(LaunchTag::Gem),
+            "sus" => Some(LaunchTag::Sus),
+            "test" => Some(LaunchTag::Test),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LaunchTag::Gem => "💎 gem",
+            LaunchTag::Sus => "⚠️ sus",
+            LaunchTag::Test => "🧪 test",
+        }
+    }
+}
+
+// Launch price next to the price captured a day later, if any
+#[derive(Debug, Clone)]
+pub struct LaunchPerformance {
+    pub token_address: String,
+    pub symbol: Option<String>,
+    pub launch_price: Option<f64>,
+    pub price_24h: Option<f64>,
+}
+
+// Launch metrics of a recorded launch, None when aggregation failed at launch
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaunchStats {
+    pub market_cap: Option<f64>,
+    pub usd_dex_liquidity: Option<f64>,
+}
+
+// How far the archive import got, with the running totals of what it recorded
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ImportProgress {
+    pub next_block: u64,
+    pub creations: usize,
+    pub launches: usize,
+    pub failed: usize,
+}
+
+// A simulated buy, prices are USD per raw unit like MemecoinInfo.price
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaperPosition {
+    pub id: i64,
+    pub chat_id: i64,
+    pub token_address: String,
+    pub symbol: String,
+    pub usd_amount: f64,
+    pub entry_price: f64,
+    pub quantity: f64,
+    pub opened_at: u64,
+    pub last_price: Option<f64>,
+    pub valued_at: Option<u64>,
+    pub exit_price: Option<f64>,
+}
+
+// Configuration a replica last reported, see watcher::drift
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstanceConfig {
+    pub instance_id: String,
+    pub config_hash: String,
+    pub payload: String,
+    pub reported_at: u64,
+}
+
+// Token or deployer whose launches are never broadcast
+#[derive(Debug, Clone, Serialize)]
+pub struct BlacklistEntry {
+    pub address: String,
+    pub reason: Option<String>,
+    pub created_at: u64,
+}
+
+pub struct Storage {
+    pool: SqlitePool,
+}
+
+impl Storage {
+    pub async fn connect(config: StorageConfig) -> Result<Self, StorageError> {
+        let options = SqliteConnectOptions::from_str(&config.database_url)?.create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(5)
+            .connect_with(options)
+            .await?;
+
+        MIGRATOR.run(&pool).await?;
+
+        Ok(Self { pool })
+    }
+
+    // Version of the latest migration applied
+    pub async fn schema_version(&self) -> Result<i64, StorageError> {
+        let version: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(version.unwrap_or_default())
+    }
+
+    // Each token is created once, so replays of the same event overwrite the row
+    pub async fn record_creation(
+        &self,
+        event: &CreationEvent,
+        block: BlockRef,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO creations
+                (token_address, owner, name, symbol, initial_supply, block_number, block_timestamp)
+            VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(to_canonical_hex(&event.memecoin_address))
+        .bind(to_canonical_hex(&event.owner))
+        .bind(&event.name)
+        .bind(&event.symbol)
+        .bind(&event.initial_supply)
+        .bind(block.block_number as i64)
+        .bind(block.block_timestamp as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Block the token was created in, None when its creation wasn't recorded
+    pub async fn creation_block(&self, token_address: &str) -> Result<Option<BlockRef>, StorageError> {
+        let row = sqlx::query("SELECT block_number, block_timestamp FROM creations WHERE token_address = ?")
+            .bind(token_address)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(|row| {
+            Ok(BlockRef {
+                block_number: row.try_get::<i64, _>("block_number")? as u64,
+                block_timestamp: row.try_get::<i64, _>("block_timestamp")? as u64,
+            })
+        })
+        .transpose()
+    }
+
+    // Symbol of a token the factory created, None for any other token
+    pub async fn memecoin_symbol(&self, token_address: &str) -> Result<Option<String>, StorageError> {
+        let symbol: Option<String> =
+            sqlx::query_scalar("SELECT symbol FROM creations WHERE token_address = ?")
+                .bind(token_address)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(symbol)
+    }
+
+    // Metrics are optional so launches are kept even when aggregation failed
+    pub async fn record_launch(
+        &self,
+        event: &LaunchEvent,
+        block: BlockRef,
+        metrics: Option<(&MemecoinInfo, &TokenCategoryResponse)>,
+    ) -> Result<(), StorageError> {
+        let (coin_info, holders_data) = match metrics {
+            Some((info, holders)) => (
+                Some(serde_json::to_string(info)?),
+                Some(serde_json::to_string(holders)?),
+            ),
+            None => (None, None),
+        };
+        // Known metrics are kept when the event is replayed without them
+        sqlx::query(
+            "INSERT INTO launches
+                (token_address, quote_token, exchange_name, block_number, block_timestamp, coin_info, holders_data)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (token_address) DO UPDATE SET
+                quote_token = excluded.quote_token,
+                exchange_name = excluded.exchange_name,
+                block_number = excluded.block_number,
+                block_timestamp = excluded.block_timestamp,
+                coin_info = COALESCE(excluded.coin_info, launches.coin_info),
+                holders_data = COALESCE(excluded.holders_data, launches.holders_data)",
+        )
+        .bind(to_canonical_hex(&event.memecoin_address))
+        .bind(to_canonical_hex(&event.quote_token))
+        .bind(&event.exchange_name)
+        .bind(block.block_number as i64)
+        .bind(block.block_timestamp as i64)
+        .bind(coin_info)
+        .bind(holders_data)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Refreshes aggregated metrics of a launch that is already recorded, returns false otherwise
+    pub async fn update_launch_metrics(
+        &self,
+        info: &MemecoinInfo,
+        holders: &TokenCategoryResponse,
+    ) -> Result<bool, StorageError> {
+        let result = sqlx::query(
+            "UPDATE launches SET coin_info = ?, holders_data = ? WHERE token_address = ?",
+        )
+        .bind(serde_json::to_string(info)?)
+        .bind(serde_json::to_string(holders)?)
+        .bind(&info.address)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Newest launches first, optionally only those curators tagged with `tag`
+    pub async fn recent_launches(
+        &self,
+        limit: u32,
+        offset: u32,
+        tag: Option<LaunchTag>,
+    ) -> Result<Vec<LaunchRecord>, StorageError> {
+        let rows = sqlx::query(&format!(
+            "{}
+            WHERE ?1 IS NULL OR EXISTS (
+                SELECT 1 FROM launch_tags t WHERE t.token_address = l.token_address AND t.tag = ?1
+            )
+            ORDER BY l.block_timestamp DESC, l.block_number DESC, l.token_address
+            LIMIT ?2 OFFSET ?3",
+            SELECT_LAUNCHES
+        ))
+        .bind(tag.map(|tag| tag.as_str()))
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(launch_from_row).collect()
+    }
+
+    // Earlier launches sharing a symbol or name (case-insensitive) or the deployer, oldest first
+    pub async fn similar_launches(
+        &self,
+        token_address: &str,
+        name: &str,
+        symbol: &str,
+        owner: &str,
+        limit: u32,
+    ) -> Result<Vec<LaunchRecord>, StorageError> {
+        let rows = sqlx::query(&format!(
+            "{}
+            WHERE l.token_address != ?1 AND (
+                lower(COALESCE(c.symbol, json_extract(l.coin_info, '$.symbol'))) = lower(?2)
+                OR lower(COALESCE(c.name, json_extract(l.coin_info, '$.name'))) = lower(?3)
+                OR COALESCE(c.owner, json_extract(l.coin_info, '$.owner')) = ?4
+            )
+            ORDER BY l.block_timestamp ASC
+            LIMIT ?5",
+            SELECT_LAUNCHES
+        ))
+        .bind(token_address)
+        .bind(symbol)
+        .bind(name)
+        .bind(owner)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(launch_from_row).collect()
+    }
+
+    pub async fn tag_launch(
+        &self,
+        token_address: &str,
+        curator_id: i64,
+        tag: LaunchTag,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO launch_tags (token_address, curator_id, tag, created_at)
+            VALUES (?, ?, ?, ?)",
+        )
+        .bind(token_address)
+        .bind(curator_id)
+        .bind(tag.as_str())
+        .bind(now() as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // How many curators applied each tag to a token, most used first
+    pub async fn launch_tags(&self, token_address: &str) -> Result<Vec<(LaunchTag, u64)>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT tag, COUNT(*) AS votes FROM launch_tags WHERE token_address = ?
+            GROUP BY tag ORDER BY votes DESC, tag",
+        )
+        .bind(token_address)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut tags = Vec::with_capacity(rows.len());
+        for row in rows {
+            let tag: String = row.try_get("tag")?;
+            if let Some(tag) = LaunchTag::parse(&tag) {
+                tags.push((tag, row.try_get::<i64, _>("votes")? as u64));
+            }
+        }
+        Ok(tags)
+    }
+
+    pub async fn count_launches(&self, tag: Option<LaunchTag>) -> Result<u64, StorageError> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM launches l
+            WHERE ?1 IS NULL OR EXISTS (
+                SELECT 1 FROM launch_tags t WHERE t.token_address = l.token_address AND t.tag = ?1
+            )",
+        )
+        .bind(tag.map(|tag| tag.as_str()))
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count as u64)
+    }
+
+    // Launches that happened between `from` and `to` and have no snapshot yet
+    pub async fn launches_awaiting_snapshot(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<LaunchRecord>, StorageError> {
+        let rows = sqlx::query(&format!(
+            "{}
+            WHERE l.block_timestamp >= ?1 AND l.block_timestamp < ?2
+                AND NOT EXISTS (SELECT 1 FROM launch_snapshots s WHERE s.token_address = l.token_address)",
+            SELECT_LAUNCHES
+        ))
+        .bind(from as i64)
+        .bind(to as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(launch_from_row).collect()
+    }
+
+    pub async fn record_snapshot(&self, token_address: &str, price: f64) -> Result<(), StorageError> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO launch_snapshots (token_address, price, captured_at) VALUES (?, ?, ?)",
+        )
+        .bind(token_address)
+        .bind(price)
+        .bind(now() as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Every launch between `from` and `to` with its launch and next day prices
+    pub async fn launch_performance(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<LaunchPerformance>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT l.token_address,
+                COALESCE(c.symbol, json_extract(l.coin_info, '$.symbol')) AS symbol,
+                json_extract(l.coin_info, '$.price') AS launch_price,
+                s.price AS price_24h
+            FROM launches l
+            LEFT JOIN creations c ON c.token_address = l.token_address
+            LEFT JOIN launch_snapshots s ON s.token_address = l.token_address
+            WHERE l.block_timestamp >= ?1 AND l.block_timestamp < ?2",
+        )
+        .bind(from as i64)
+        .bind(to as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut launches = Vec::with_capacity(rows.len());
+        for row in rows {
+            let launch_price: Option<String> = row.try_get("launch_price")?;
+            launches.push(LaunchPerformance {
+                token_address: row.try_get("token_address")?,
+                symbol: row.try_get("symbol")?,
+                launch_price: launch_price.and_then(|price| price.parse::<f64>().ok()),
+                price_24h: row.try_get("price_24h")?,
+            });
+        }
+        Ok(launches)
+    }
+
+    // Launch metrics of every launch since `from` except `token_address`, what new launches are compared to
+    pub async fn launch_stats(
+        &self,
+        from: u64,
+        token_address: &str,
+    ) -> Result<Vec<LaunchStats>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT json_extract(coin_info, '$.market_cap') AS market_cap,
+                json_extract(coin_info, '$.usd_dex_liquidity') AS usd_dex_liquidity
+            FROM launches
+            WHERE block_timestamp >= ? AND token_address != ? AND coin_info IS NOT NULL",
+        )
+        .bind(from as i64)
+        .bind(token_address)
+        .fetch_all(&self.pool)
+        .await?;
+        let parse = |value: Option<String>| value.and_then(|value| value.parse::<f64>().ok());
+        rows.iter()
+            .map(|row| {
+                Ok(LaunchStats {
+                    market_cap: parse(row.try_get("market_cap")?),
+                    usd_dex_liquidity: parse(row.try_get("usd_dex_liquidity")?),
+                })
+            })
+            .collect()
+    }
+
+    pub async fn report_posted(&self, week_start: u64) -> Result<bool, StorageError> {
+        let found: Option<i64> =
+            sqlx::query_scalar("SELECT week_start FROM weekly_reports WHERE week_start = ?")
+                .bind(week_start as i64)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(found.is_some())
+    }
+
+    // Claims the week's report before it is sent, false if it was already claimed or posted. Only
+    // one poster gets the claim, which keeps the report from going out twice
+    pub async fn claim_report(&self, week_start: u64) -> Result<bool, StorageError> {
+        let result = sqlx::query("INSERT OR IGNORE INTO weekly_reports (week_start, posted_at) VALUES (?, ?)")
+            .bind(week_start as i64)
+            .bind(now() as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Gives up the claim on a report no chat received, so it is retried
+    pub async fn release_report(&self, week_start: u64) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM weekly_reports WHERE week_start = ?")
+            .bind(week_start as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn import_progress(&self, factory: &str) -> Result<Option<ImportProgress>, StorageError> {
+        let row = sqlx::query(
+            "SELECT next_block, creations, launches, failed FROM import_progress WHERE factory = ?",
+        )
+        .bind(factory)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|row| {
+            Ok(ImportProgress {
+                next_block: row.try_get::<i64, _>("next_block")? as u64,
+                creations: row.try_get::<i64, _>("creations")? as usize,
+                launches: row.try_get::<i64, _>("launches")? as usize,
+                failed: row.try_get::<i64, _>("failed")? as usize,
+            })
+        })
+        .transpose()
+    }
+
+    pub async fn save_import_progress(
+        &self,
+        factory: &str,
+        progress: &ImportProgress,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO import_progress
+                (factory, next_block, creations, launches, failed, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(factory)
+        .bind(progress.next_block as i64)
+        .bind(progress.creations as i64)
+        .bind(progress.launches as i64)
+        .bind(progress.failed as i64)
+        .bind(now() as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Records a simulated buy of `usd_amount` at `entry_price` and returns it
+    pub async fn open_paper_position(
+        &self,
+        chat_id: i64,
+        token_address: &str,
+        symbol: &str,
+        usd_amount: f64,
+        entry_price: f64,
+    ) -> Result<PaperPosition, StorageError> {
+        let opened_at = now();
+        let quantity = usd_amount / entry_price;
+        let result = sqlx::query(
+            "INSERT INTO paper_positions
+                (chat_id, token_address, symbol, usd_amount, entry_price, quantity, opened_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(chat_id)
+        .bind(token_address)
+        .bind(symbol)
+        .bind(usd_amount)
+        .bind(entry_price)
+        .bind(quantity)
+        .bind(opened_at as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(PaperPosition {
+            id: result.last_insert_rowid(),
+            chat_id,
+            token_address: token_address.to_string(),
+            symbol: symbol.to_string(),
+            usd_amount,
+            entry_price,
+            quantity,
+            opened_at,
+            last_price: None,
+            valued_at: None,
+            exit_price: None,
+        })
+    }
+
+    // Positions of a chat, oldest first. Closed ones are only included with `closed`
+    pub async fn paper_positions(
+        &self,
+        chat_id: i64,
+        closed: bool,
+    ) -> Result<Vec<PaperPosition>, StorageError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE chat_id = ? AND (closed_at IS NULL OR ?) ORDER BY id",
+            SELECT_PAPER_POSITIONS
+        ))
+        .bind(chat_id)
+        .bind(closed)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(paper_position_from_row).collect()
+    }
+
+    // Closes one of the chat's open positions at `exit_price`, None if it has no such position
+    pub async fn close_paper_position(
+        &self,
+        chat_id: i64,
+        id: i64,
+        exit_price: f64,
+    ) -> Result<Option<PaperPosition>, StorageError> {
+        let result = sqlx::query(
+            "UPDATE paper_positions SET exit_price = ?, closed_at = ?
+            WHERE id = ? AND chat_id = ? AND closed_at IS NULL",
+        )
+        .bind(exit_price)
+        .bind(now() as i64)
+        .bind(id)
+        .bind(chat_id)
+        .execute(&self.pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        let row = sqlx::query(&format!("{} WHERE id = ?", SELECT_PAPER_POSITIONS))
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+        paper_position_from_row(&row).map(Some)
+    }
+
+    // Looks a chat's open position up by id
+    pub async fn paper_position(&self, chat_id: i64, id: i64) -> Result<Option<PaperPosition>, StorageError> {
+        let row = sqlx::query(&format!(
+            "{} WHERE id = ? AND chat_id = ? AND closed_at IS NULL",
+            SELECT_PAPER_POSITIONS
+        ))
+        .bind(id)
+        .bind(chat_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(paper_position_from_row).transpose()
+    }
+
+    // Tokens with at least one open position, with their symbol
+    pub async fn paper_traded_tokens(&self) -> Result<Vec<(String, String)>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT token_address, MAX(symbol) AS symbol FROM paper_positions
+            WHERE closed_at IS NULL GROUP BY token_address",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| Ok((row.try_get("token_address")?, row.try_get("symbol")?)))
+            .collect()
+    }
+
+    // Stores the latest price of a token on its open positions
+    pub async fn record_paper_valuation(&self, token_address: &str, price: f64) -> Result<(), StorageError> {
+        sqlx::query(
+            "UPDATE paper_positions SET last_price = ?, valued_at = ?
+            WHERE token_address = ? AND closed_at IS NULL",
+        )
+        .bind(price)
+        .bind(now() as i64)
+        .bind(token_address)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Tokens launched since `from` with their symbol, the ones whose price history is collected
+    pub async fn launched_since(&self, from: u64) -> Result<Vec<(String, String)>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT l.token_address,
+                COALESCE(c.symbol, json_extract(l.coin_info, '$.symbol'), '') AS symbol
+            FROM launches l
+            LEFT JOIN creations c ON c.token_address = l.token_address
+            WHERE l.block_timestamp >= ?",
+        )
+        .bind(from as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| Ok((row.try_get("token_address")?, row.try_get("symbol")?)))
+            .collect()
+    }
+
+    // Address, symbol, name and launch time of every launch, what token searches run against
+    pub async fn launch_names(&self) -> Result<Vec<(String, String, String, u64)>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT l.token_address,
+                COALESCE(c.symbol, json_extract(l.coin_info, '$.symbol'), '') AS symbol,
+                COALESCE(c.name, json_extract(l.coin_info, '$.name'), '') AS name,
+                l.block_timestamp
+            FROM launches l
+            LEFT JOIN creations c ON c.token_address = l.token_address",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                Ok((
+                    row.try_get("token_address")?,
+                    row.try_get("symbol")?,
+                    row.try_get("name")?,
+                    row.try_get::<i64, _>("block_timestamp")? as u64,
+                ))
+            })
+            .collect()
+    }
+
+    pub async fn record_price(&self, token_address: &str, price: f64) -> Result<(), StorageError> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO price_history (token_address, recorded_at, price) VALUES (?, ?, ?)",
+        )
+        .bind(token_address)
+        .bind(now() as i64)
+        .bind(price)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Prices of a token recorded since `from` as (timestamp, price), oldest first
+    pub async fn price_history(
+        &self,
+        token_address: &str,
+        from: u64,
+    ) -> Result<Vec<(u64, f64)>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT recorded_at, price FROM price_history
+            WHERE token_address = ? AND recorded_at >= ? ORDER BY recorded_at",
+        )
+        .bind(token_address)
+        .bind(from as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| Ok((row.try_get::<i64, _>("recorded_at")? as u64, row.try_get("price")?)))
+            .collect()
+    }
+
+    // Most recent price recorded for a token, from its price history or its next day snapshot
+    pub async fn latest_price(&self, token_address: &str) -> Result<Option<f64>, StorageError> {
+        let price = sqlx::query_scalar(
+            "SELECT price FROM (
+                SELECT price, recorded_at AS at FROM price_history WHERE token_address = ?1
+                UNION ALL
+                SELECT price, captured_at AS at FROM launch_snapshots WHERE token_address = ?1
+            ) ORDER BY at DESC LIMIT 1",
+        )
+        .bind(token_address)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(price)
+    }
+
+    // Drops prices recorded before `before`, returns how many were removed
+    pub async fn prune_price_history(&self, before: u64) -> Result<u64, StorageError> {
+        let result = sqlx::query("DELETE FROM price_history WHERE recorded_at < ?")
+            .bind(before as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn launch_timestamp(&self, token_address: &str) -> Result<Option<u64>, StorageError> {
+        let timestamp: Option<i64> =
+            sqlx::query_scalar("SELECT block_timestamp FROM launches WHERE token_address = ?")
+                .bind(token_address)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(timestamp.map(|timestamp| timestamp as u64))
+    }
+
+    pub async fn record_holders(&self, token_address: &str, holders: u64) -> Result<(), StorageError> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO holder_history (token_address, recorded_at, holders) VALUES (?, ?, ?)",
+        )
+        .bind(token_address)
+        .bind(now() as i64)
+        .bind(holders as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Holder counts of a token recorded since `from` as (timestamp, holders), oldest first
+    pub async fn holder_history(
+        &self,
+        token_address: &str,
+        from: u64,
+    ) -> Result<Vec<(u64, u64)>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT recorded_at, holders FROM holder_history
+            WHERE token_address = ? AND recorded_at >= ? ORDER BY recorded_at",
+        )
+        .bind(token_address)
+        .bind(from as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                Ok((
+                    row.try_get::<i64, _>("recorded_at")? as u64,
+                    row.try_get::<i64, _>("holders")? as u64,
+                ))
+            })
+            .collect()
+    }
+
+    pub async fn prune_holder_history(&self, before: u64) -> Result<u64, StorageError> {
+        let result = sqlx::query("DELETE FROM holder_history WHERE recorded_at < ?")
+            .bind(before as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    // Addresses are expected in canonical form, see utils::address
+    pub async fn add_to_blacklist(
+        &self,
+        address: &str,
+        reason: Option<&str>,
+    ) -> Result<(), StorageError> {
+        sqlx::query("INSERT OR REPLACE INTO blacklist (address, reason, created_at) VALUES (?, ?, ?)")
+            .bind(address)
+            .bind(reason)
+            .bind(now() as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn remove_from_blacklist(&self, address: &str) -> Result<bool, StorageError> {
+        let result = sqlx::query("DELETE FROM blacklist WHERE address = ?")
+            .bind(address)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn blacklist(&self) -> Result<Vec<BlacklistEntry>, StorageError> {
+        let rows = sqlx::query("SELECT address, reason, created_at FROM blacklist ORDER BY created_at DESC")
+            .fetch_all(&self.pool)
+            .await?;
+        let mut entries = Vec::with_capacity(rows.len());
+        for row in rows {
+            entries.push(BlacklistEntry {
+                address: row.try_get("address")?,
+                reason: row.try_get("reason")?,
+                created_at: row.try_get::<i64, _>("created_at")? as u64,
+            });
+        }
+        Ok(entries)
+    }
+
+    // True when any of the addresses (token, owner, ...) is blacklisted
+    pub async fn is_blacklisted(&self, addresses: &[&str]) -> Result<bool, StorageError> {
+        for address in addresses {
+            let found: Option<String> =
+                sqlx::query_scalar("SELECT address FROM blacklist WHERE address = ?")
+                    .bind(address)
+                    .fetch_optional(&self.pool)
+                    .await?;
+            if found.is_some() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    pub async fn follow_wallet(&self, chat_id: i64, wallet: &str) -> Result<(), StorageError> {
+        sqlx::query("INSERT OR IGNORE INTO wallet_follows (chat_id, wallet, created_at) VALUES (?, ?, ?)")
+            .bind(chat_id)
+            .bind(wallet)
+            .bind(now() as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // False when the chat wasn't following the wallet
+    pub async fn unfollow_wallet(&self, chat_id: i64, wallet: &str) -> Result<bool, StorageError> {
+        let result = sqlx::query("DELETE FROM wallet_follows WHERE chat_id = ? AND wallet = ?")
+            .bind(chat_id)
+            .bind(wallet)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn remove_wallet_follows(&self, chat_id: i64) -> Result<u64, StorageError> {
+        let result = sqlx::query("DELETE FROM wallet_follows WHERE chat_id = ?")
+            .bind(chat_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    // Every (wallet, chat_id) follow, to rebuild the in-memory index at startup
+    pub async fn wallet_follows(&self) -> Result<Vec<(String, i64)>, StorageError> {
+        let rows = sqlx::query("SELECT wallet, chat_id FROM wallet_follows ORDER BY created_at")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter()
+            .map(|row| Ok((row.try_get("wallet")?, row.try_get("chat_id")?)))
+            .collect()
+    }
+
+    pub async fn mute_chat(&self, chat_id: i64) -> Result<(), StorageError> {
+        sqlx::query("INSERT OR IGNORE INTO muted_chats (chat_id, created_at) VALUES (?, ?)")
+            .bind(chat_id)
+            .bind(now() as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // False when the chat wasn't muted
+    pub async fn unmute_chat(&self, chat_id: i64) -> Result<bool, StorageError> {
+        let result = sqlx::query("DELETE FROM muted_chats WHERE chat_id = ?")
+            .bind(chat_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn muted_chats(&self) -> Result<Vec<i64>, StorageError> {
+        let chat_ids: Vec<i64> = sqlx::query_scalar("SELECT chat_id FROM muted_chats")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(chat_ids)
+    }
+
+    pub async fn save_stopped_chat(&self, chat_id: i64, stopped_at: u64) -> Result<(), StorageError> {
+        sqlx::query("INSERT OR REPLACE INTO stopped_chats (chat_id, stopped_at) VALUES (?, ?)")
+            .bind(chat_id)
+            .bind(stopped_at as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // Called when a chat resumes or is purged
+    pub async fn remove_stopped_chat(&self, chat_id: i64) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM stopped_chats WHERE chat_id = ?")
+            .bind(chat_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // (chat id, unix time it stopped) of every stopped chat
+    pub async fn stopped_chats(&self) -> Result<Vec<(i64, u64)>, StorageError> {
+        let rows = sqlx::query("SELECT chat_id, stopped_at FROM stopped_chats")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter()
+            .map(|row| Ok((row.try_get("chat_id")?, row.try_get::<i64, _>("stopped_at")? as u64)))
+            .collect()
+    }
+
+    // Codes are derived from the payload, saving the same settings twice keeps the first row
+    pub async fn save_settings_preset(&self, code: &str, payload: &str) -> Result<(), StorageError> {
+        sqlx::query("INSERT OR IGNORE INTO settings_presets (code, payload, created_at) VALUES (?, ?, ?)")
+            .bind(code)
+            .bind(payload)
+            .bind(now() as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn settings_preset(&self, code: &str) -> Result<Option<String>, StorageError> {
+        let payload: Option<String> =
+            sqlx::query_scalar("SELECT payload FROM settings_presets WHERE code = ?")
+                .bind(code)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(payload)
+    }
+
+    pub async fn save_card_template(
+        &self,
+        chat_kind: &str,
+        variant: &str,
+        payload: &str,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO card_templates (chat_kind, variant, payload, updated_at)
+            VALUES (?, ?, ?, ?)",
+        )
+        .bind(chat_kind)
+        .bind(variant)
+        .bind(payload)
+        .bind(now() as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // False when there was no override to remove
+    pub async fn remove_card_template(&self, chat_kind: &str, variant: &str) -> Result<bool, StorageError> {
+        let result = sqlx::query("DELETE FROM card_templates WHERE chat_kind = ? AND variant = ?")
+            .bind(chat_kind)
+            .bind(variant)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // (chat kind, variant, payload) of every override
+    pub async fn card_templates(&self) -> Result<Vec<(String, String, String)>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT chat_kind, variant, payload FROM card_templates ORDER BY chat_kind, variant",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| Ok((row.try_get("chat_kind")?, row.try_get("variant")?, row.try_get("payload")?)))
+            .collect()
+    }
+
+    pub async fn save_chat_kind(&self, chat_id: i64, chat_kind: &str) -> Result<(), StorageError> {
+        sqlx::query("INSERT OR REPLACE INTO chat_kinds (chat_id, chat_kind, updated_at) VALUES (?, ?, ?)")
+            .bind(chat_id)
+            .bind(chat_kind)
+            .bind(now() as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn remove_chat_kind(&self, chat_id: i64) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM chat_kinds WHERE chat_id = ?")
+            .bind(chat_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // (chat id, chat kind) of every chat whose type is known
+    pub async fn chat_kinds(&self) -> Result<Vec<(i64, String)>, StorageError> {
+        let rows = sqlx::query("SELECT chat_id, chat_kind FROM chat_kinds")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter()
+            .map(|row| Ok((row.try_get("chat_id")?, row.try_get("chat_kind")?)))
+            .collect()
+    }
+
+    pub async fn increment_counter(&self, name: &str, by: u64) -> Result<(), StorageError> {
+        sqlx::query(
+            "INSERT INTO counters (name, value, updated_at) VALUES (?1, ?2, ?3)
+            ON CONFLICT(name) DO UPDATE SET value = value + ?2, updated_at = ?3",
+        )
+        .bind(name)
+        .bind(by as i64)
+        .bind(now() as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Zero for a counter that was never incremented
+    pub async fn counter(&self, name: &str) -> Result<u64, StorageError> {
+        let value: Option<i64> = sqlx::query_scalar("SELECT value FROM counters WHERE name = ?")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(value.unwrap_or_default() as u64)
+    }
+
+    pub async fn report_instance_config(
+        &self,
+        instance_id: &str,
+        config_hash: &str,
+        payload: &str,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO instance_configs (instance_id, config_hash, payload, reported_at)
+            VALUES (?, ?, ?, ?)",
+        )
+        .bind(instance_id)
+        .bind(config_hash)
+        .bind(payload)
+        .bind(now() as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Replicas that reported since `from`, stopped ones age out on their own
+    pub async fn instance_configs(&self, from: u64) -> Result<Vec<InstanceConfig>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT instance_id, config_hash, payload, reported_at FROM instance_configs
+            WHERE reported_at >= ? ORDER BY instance_id",
+        )
+        .bind(from as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                Ok(InstanceConfig {
+                    instance_id: row.try_get("instance_id")?,
+                    config_hash: row.try_get("config_hash")?,
+                    payload: row.try_get("payload")?,
+                    reported_at: row.try_get::<i64, _>("reported_at")? as u64,
+                })
+            })
+            .collect()
+    }
+
+    // Last block of `stream` handed over to the consumer, with the source that got there
+    pub async fn checkpoint(&self, stream: &str) -> Result<Option<(String, u64)>, StorageError> {
+        let row = sqlx::query("SELECT source, block_number FROM indexer_checkpoints WHERE stream = ?")
+            .bind(stream)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(|row| Ok((row.try_get("source")?, row.try_get::<i64, _>("block_number")? as u64)))
+            .transpose()
+    }
+
+    pub async fn save_checkpoint(
+        &self,
+        stream: &str,
+        source: &str,
+        block_number: u64,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO indexer_checkpoints (stream, source, block_number, updated_at)
+            VALUES (?, ?, ?, ?)",
+        )
+        .bind(stream)
+        .bind(source)
+        .bind(block_number as i64)
+        .bind(now() as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Records a checkpointed block's hash, only the newest `keep` are kept
+    pub async fn save_block_hash(
+        &self,
+        block_number: u64,
+        block_hash: &str,
+        keep: usize,
+    ) -> Result<(), StorageError> {
+        sqlx::query("INSERT OR REPLACE INTO block_hashes (block_number, block_hash) VALUES (?, ?)")
+            .bind(block_number as i64)
+            .bind(block_hash)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(
+            "DELETE FROM block_hashes WHERE block_number NOT IN
+                (SELECT block_number FROM block_hashes ORDER BY block_number DESC LIMIT ?)",
+        )
+        .bind(keep as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Recorded block hashes, newest first
+    pub async fn block_hashes(&self) -> Result<Vec<(u64, String)>, StorageError> {
+        let rows = sqlx::query("SELECT block_number, block_hash FROM block_hashes ORDER BY block_number DESC")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter()
+            .map(|row| Ok((row.try_get::<i64, _>("block_number")? as u64, row.try_get("block_hash")?)))
+            .collect()
+    }
+
+    // Forgets the creations, launches and block hashes recorded after `block_number`, once their
+    // blocks were reorged out of the chain. Returns how many events were dropped
+    pub async fn rollback_after(&self, block_number: u64) -> Result<u64, StorageError> {
+        let mut tx = self.pool.begin().await?;
+        let mut dropped = 0;
+        for table in ["creations", "launches"] {
+            dropped += sqlx::query(&format!("DELETE FROM {} WHERE block_number > ?", table))
+                .bind(block_number as i64)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+        sqlx::query("DELETE FROM block_hashes WHERE block_number > ?")
+            .bind(block_number as i64)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(dropped)
+    }
+}
+
+const SELECT_LAUNCHES: &str = "SELECT l.token_address, c.name, c.symbol, c.owner, l.quote_token,
+        l.exchange_name, l.block_number, l.block_timestamp, l.coin_info, l.holders_data,
+        (SELECT GROUP_CONCAT(DISTINCT t.tag) FROM launch_tags t
+            WHERE t.token_address = l.token_address) AS tags
+    FROM launches l
+    LEFT JOIN creations c ON c.token_address = l.token_address";
+
+fn launch_from_row(row: &SqliteRow) -> Result<LaunchRecord, StorageError> {
+    let coin_info: Option<String> = row.try_get("coin_info")?;
+    let holders_data: Option<String> = row.try_get("holders_data")?;
+    let tags: Option<String> = row.try_get("tags")?;
+    Ok(LaunchRecord {
+        token_address: row.try_get("token_address")?,
+        name: row.try_get("name")?,
+        symbol: row.try_get("symbol")?,
+        owner: row.try_get("owner")?,
+        quote_token: row.try_get("quote_token")?,
+        exchange_name: row.try_get("exchange_name")?,
+        block_number: row.try_get::<i64, _>("block_number")? as u64,
+        block_timestamp: row.try_get::<i64, _>("block_timestamp")? as u64,
+        coin_info: coin_info.map(|json| serde_json::from_str(&json)).transpose()?,
+        holders_data: holders_data.map(|json| serde_json::from_str(&json)).transpose()?,
+        tags: tags
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(LaunchTag::parse)
+            .collect(),
+    })
+}
+
+const SELECT_PAPER_POSITIONS: &str = "SELECT id, chat_id, token_address, symbol, usd_amount,
+        entry_price, quantity, opened_at, last_price, valued_at, exit_price
+    FROM paper_positions";
+
+fn paper_position_from_row(row: &SqliteRow) -> Result<PaperPosition, StorageError> {
+    Ok(PaperPosition {
+        id: row.try_get("id")?,
+        chat_id: row.try_get("chat_id")?,
+        token_address: row.try_get("token_address")?,
+        symbol: row.try_get("symbol")?,
+        usd_amount: row.try_get("usd_amount")?,
+        entry_price: row.try_get("entry_price")?,
+        quantity: row.try_get("quantity")?,
+        opened_at: row.try_get::<i64, _>("opened_at")? as u64,
+        last_price: row.try_get("last_price")?,
+        valued_at: row.try_get::<Option<i64>, _>("valued_at")?.map(|valued_at| valued_at as u64),
+        exit_price: row.try_get("exit_price")?,
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet_core::types::Felt;
+
+    #[tokio::test]
+    async fn test_connect_applies_every_migration() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        let latest = MIGRATOR.iter().map(|migration| migration.version).max().unwrap();
+        assert_eq!(storage.schema_version().await.unwrap(), latest);
+    }
+
+    #[tokio::test]
+    async fn test_import_progress_is_kept_per_factory() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        assert_eq!(storage.import_progress("0x1").await.unwrap(), None);
+
+        let progress = ImportProgress {
+            next_block: 620_000,
+            creations: 12,
+            launches: 7,
+            failed: 1,
+        };
+        storage.save_import_progress("0x1", &progress).await.unwrap();
+        assert_eq!(storage.import_progress("0x1").await.unwrap(), Some(progress));
+        assert_eq!(storage.import_progress("0x2").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_report_claimed_once() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        assert!(!storage.report_posted(604_800).await.unwrap());
+
+        assert!(storage.claim_report(604_800).await.unwrap());
+        assert!(storage.report_posted(604_800).await.unwrap());
+        assert!(!storage.claim_report(604_800).await.unwrap());
+        assert!(!storage.report_posted(1_209_600).await.unwrap());
+
+        // A released claim can be taken again
+        storage.release_report(604_800).await.unwrap();
+        assert!(!storage.report_posted(604_800).await.unwrap());
+        assert!(storage.claim_report(604_800).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_wallet_follows() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        storage.follow_wallet(1, "0xa").await.unwrap();
+        storage.follow_wallet(1, "0xa").await.unwrap();
+        storage.follow_wallet(2, "0xa").await.unwrap();
+        storage.follow_wallet(2, "0xb").await.unwrap();
+        assert_eq!(storage.wallet_follows().await.unwrap().len(), 3);
+
+        assert!(storage.unfollow_wallet(1, "0xa").await.unwrap());
+        assert!(!storage.unfollow_wallet(1, "0xa").await.unwrap());
+        assert_eq!(storage.remove_wallet_follows(2).await.unwrap(), 2);
+        assert!(storage.wallet_follows().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_muted_chats() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        storage.mute_chat(7).await.unwrap();
+        storage.mute_chat(7).await.unwrap();
+        assert_eq!(storage.muted_chats().await.unwrap(), vec![7]);
+        assert!(storage.unmute_chat(7).await.unwrap());
+        assert!(!storage.unmute_chat(7).await.unwrap());
+        assert!(storage.muted_chats().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stopped_chats() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        storage.save_stopped_chat(7, 100).await.unwrap();
+        storage.save_stopped_chat(8, 200).await.unwrap();
+        // Stopping again restarts the retention period
+        storage.save_stopped_chat(7, 300).await.unwrap();
+        let mut stopped = storage.stopped_chats().await.unwrap();
+        stopped.sort();
+        assert_eq!(stopped, vec![(7, 300), (8, 200)]);
+
+        storage.remove_stopped_chat(7).await.unwrap();
+        assert_eq!(storage.stopped_chats().await.unwrap(), vec![(8, 200)]);
+    }
+
+    #[tokio::test]
+    async fn test_settings_presets() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        storage.save_settings_preset("AB12CD34", r#"{"min_holders":20}"#).await.unwrap();
+        storage.save_settings_preset("AB12CD34", r#"{"min_holders":50}"#).await.unwrap();
+        assert_eq!(
+            storage.settings_preset("AB12CD34").await.unwrap().as_deref(),
+            Some(r#"{"min_holders":20}"#)
+        );
+        assert_eq!(storage.settings_preset("FFFFFFFF").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_card_templates() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        storage.save_card_template("channel", "A", r#"{"layout":"compact"}"#).await.unwrap();
+        storage.save_card_template("channel", "A", r#"{"layout":"full"}"#).await.unwrap();
+        storage.save_card_template("private", "B", "{}").await.unwrap();
+        let templates = storage.card_templates().await.unwrap();
+        assert_eq!(templates.len(), 2);
+        assert_eq!(templates[0].2, r#"{"layout":"full"}"#);
+        assert!(storage.remove_card_template("channel", "A").await.unwrap());
+        assert!(!storage.remove_card_template("channel", "A").await.unwrap());
+        assert_eq!(storage.card_templates().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_instance_configs() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        storage.report_instance_config("b", "hash-1", "{}").await.unwrap();
+        storage.report_instance_config("a", "hash-1", "{}").await.unwrap();
+        storage.report_instance_config("b", "hash-2", "{}").await.unwrap();
+        let configs = storage.instance_configs(0).await.unwrap();
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs[0].instance_id, "a");
+        assert_eq!(configs[1].config_hash, "hash-2");
+        assert!(storage.instance_configs(now() + 60).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_indexer_checkpoints() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        assert_eq!(storage.checkpoint("factory").await.unwrap(), None);
+        storage.save_checkpoint("factory", "polling", 120).await.unwrap();
+        storage.save_checkpoint("factory", "apibara", 150).await.unwrap();
+        assert_eq!(
+            storage.checkpoint("factory").await.unwrap(),
+            Some(("apibara".to_string(), 150))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rollback_after_reorg() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        for block_number in [100u64, 200, 300] {
+            let block = BlockRef {
+                block_number,
+                block_timestamp: block_number * 10,
+            };
+            let event = LaunchEvent {
+                memecoin_address: Felt::from(block_number),
+                quote_token: Felt::from(9u64),
+                exchange_name: "Ekubo".to_string(),
+            };
+            storage.record_launch(&event, block, None).await.unwrap();
+            storage.save_block_hash(block_number, &format!("0x{}", block_number), 2).await.unwrap();
+        }
+        // Only the newest hashes are kept
+        assert_eq!(
+            storage.block_hashes().await.unwrap(),
+            vec![(300, "0x300".to_string()), (200, "0x200".to_string())]
+        );
+
+        assert_eq!(storage.rollback_after(150).await.unwrap(), 2);
+        assert_eq!(storage.count_launches(None).await.unwrap(), 1);
+        assert!(storage.block_hashes().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_counters_accumulate() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        assert_eq!(storage.counter(ALERTS_DELIVERED).await.unwrap(), 0);
+        storage.increment_counter(ALERTS_DELIVERED, 1).await.unwrap();
+        storage.increment_counter(ALERTS_DELIVERED, 4).await.unwrap();
+        assert_eq!(storage.counter(ALERTS_DELIVERED).await.unwrap(), 5);
+        assert_eq!(storage.counter("other").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_paper_positions_open_and_close() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        let position = storage
+            .open_paper_position(1, "0x1", "MEME", 100f64, 0.000_002)
+            .await
+            .unwrap();
+        assert_eq!(storage.paper_position(1, position.id).await.unwrap(), Some(position.clone()));
+        assert_eq!(
+            storage.paper_traded_tokens().await.unwrap(),
+            vec![("0x1".to_string(), "MEME".to_string())]
+        );
+
+        // Another chat can't close it, and a closed position no longer counts as open
+        assert_eq!(storage.close_paper_position(2, position.id, 0.000_004).await.unwrap(), None);
+        let closed = storage
+            .close_paper_position(1, position.id, 0.000_004)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(closed.exit_price, Some(0.000_004));
+        assert!(storage.paper_positions(1, false).await.unwrap().is_empty());
+        assert_eq!(storage.paper_positions(1, true).await.unwrap().len(), 1);
+        assert!(storage.paper_traded_tokens().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_price_history_window_and_pruning() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        storage.record_price("0x1", 0.000_002).await.unwrap();
+        storage.record_price("0x2", 0.5).await.unwrap();
+
+        let history = storage.price_history("0x1", 0).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].1, 0.000_002);
+        assert!(storage.price_history("0x1", now() + 60).await.unwrap().is_empty());
+        assert_eq!(storage.latest_price("0x2").await.unwrap(), Some(0.5));
+        assert_eq!(storage.latest_price("0x3").await.unwrap(), None);
+
+        assert_eq!(storage.prune_price_history(now() + 60).await.unwrap(), 2);
+        // Snapshots outlive the pruned history
+        storage.record_snapshot("0x2", 0.25).await.unwrap();
+        assert_eq!(storage.latest_price("0x2").await.unwrap(), Some(0.25));
+        assert!(storage.price_history("0x2", 0).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_holder_history() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+        assert_eq!(storage.launch_timestamp("0x1").await.unwrap(), None);
+        storage.record_holders("0x1", 12).await.unwrap();
+
+        let history = storage.holder_history("0x1", 0).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].1, 12);
+        assert!(storage.holder_history("0x2", 0).await.unwrap().is_empty());
+        assert_eq!(storage.prune_holder_history(now() + 60).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_recent_launches_newest_first() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+
+        for (address, block_number) in [(1u64, 100u64), (2, 300), (3, 200)] {
+            let event = LaunchEvent {
+                memecoin_address: Felt::from(address),
+                quote_token: Felt::from(9u64),
+                exchange_name: "Ekubo".to_string(),
+            };
+            let block = BlockRef {
+                block_number,
+                block_timestamp: block_number * 10,
+            };
+            storage.record_launch(&event, block, None).await.unwrap();
+        }
+        storage
+            .record_creation(
+                &CreationEvent {
+                    owner: Felt::from(7u64),
+                    name: "Doge".to_string(),
+                    symbol: "DOGE".to_string(),
+                    initial_supply: "1000".to_string(),
+                    memecoin_address: Felt::from(2u64),
+                },
+                BlockRef {
+                    block_number: 290,
+                    block_timestamp: 2900,
+                },
+            )
+            .await
+            .unwrap();
+
+        let launches = storage.recent_launches(2, 0, None).await.unwrap();
+        let blocks: Vec<u64> = launches.iter().map(|launch| launch.block_number).collect();
+        assert_eq!(blocks, vec![300, 200]);
+        assert_eq!(launches[0].symbol.as_deref(), Some("DOGE"));
+        assert_eq!(storage.recent_launches(2, 2, None).await.unwrap().len(), 1);
+        assert_eq!(storage.count_launches(None).await.unwrap(), 3);
+        let created = storage.creation_block(&to_canonical_hex(&Felt::from(2u64))).await.unwrap();
+        assert_eq!(created.map(|block| block.block_number), Some(290));
+        assert!(storage.creation_block(&to_canonical_hex(&Felt::from(3u64))).await.unwrap().is_none());
+        let symbol = storage.memecoin_symbol(&to_canonical_hex(&Felt::from(2u64))).await.unwrap();
+        assert_eq!(symbol.as_deref(), Some("DOGE"));
+
+        let tagged = to_canonical_hex(&Felt::from(3u64));
+        storage.tag_launch(&tagged, 10, LaunchTag::Gem).await.unwrap();
+        storage.tag_launch(&tagged, 11, LaunchTag::Gem).await.unwrap();
+        let gems = storage.recent_launches(10, 0, Some(LaunchTag::Gem)).await.unwrap();
+        assert_eq!(gems.len(), 1);
+        assert_eq!(gems[0].tags, vec![LaunchTag::Gem]);
+        assert_eq!(storage.launch_tags(&tagged).await.unwrap(), vec![(LaunchTag::Gem, 2)]);
+    }
+
+    #[tokio::test]
+    async fn test_blacklist_matches_any_address() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+
+        storage.add_to_blacklist("0xdead", Some("serial rugger")).await.unwrap();
+        assert!(storage.is_blacklisted(&["0x1", "0xdead"]).await.unwrap());
+        assert!(!storage.is_blacklisted(&["0x1"]).await.unwrap());
+        assert!(storage.remove_from_blacklist("0xdead").await.unwrap());
+        assert!(storage.blacklist().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_similar_launches_match_symbol_or_deployer() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+
+        for (address, symbol, owner) in [(1u64, "DOGE", 7u64), (2, "PEPE", 8), (3, "WIF", 7), (4, "doge", 9)] {
+            let block = BlockRef {
+                block_number: address,
+                block_timestamp: address * 100,
+            };
+            storage
+                .record_creation(
+                    &CreationEvent {
+                        owner: Felt::from(owner),
+                        name: symbol.to_string(),
+                        symbol: symbol.to_string(),
+                        initial_supply: "1000".to_string(),
+                        memecoin_address: Felt::from(address),
+                    },
+                    block,
+                )
+                .await
+                .unwrap();
+            let event = LaunchEvent {
+                memecoin_address: Felt::from(address),
+                quote_token: Felt::from(9u64),
+                exchange_name: "Ekubo".to_string(),
+            };
+            storage.record_launch(&event, block, None).await.unwrap();
+        }
+
+        let similar = storage
+            .similar_launches(
+                &to_canonical_hex(&Felt::from(4u64)),
+                "doge",
+                "doge",
+                &to_canonical_hex(&Felt::from(8u64)),
+                10,
+            )
+            .await
+            .unwrap();
+        let symbols: Vec<String> = similar.into_iter().filter_map(|launch| launch.symbol).collect();
+        assert_eq!(symbols, vec!["DOGE".to_string(), "PEPE".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_launch_names() {
+        let storage = Storage::connect(StorageConfig {
+            database_url: "sqlite::memory:".to_string(),
+        })
+        .await
+        .unwrap();
+
+        for (address, symbol) in [(1u64, "DOGE"), (2, "DOGEKING"), (3, "PEPE"), (4, "doge")] {
+            let block = BlockRef {
+                block_number: address,
+                block_timestamp: address * 100,
+            };
+            storage
+                .record_creation(
+                    &CreationEvent {
+                        owner: Felt::from(7u64),
+                        name: symbol.to_string(),
+                        symbol: symbol.to_string(),
+                        initial_supply: "1000".to_string(),
+                        memecoin_address: Felt::from(address),
+                    },
+                    block,
+                )
+                .await
+                .unwrap();
+            let event = LaunchEvent {
+                memecoin_address: Felt::from(address),
+                quote_token: Felt::from(9u64),
+                exchange_name: "Ekubo".to_string(),
+            };
+            storage.record_launch(&event, block, None).await.unwrap();
+        }
+
+        assert_eq!(storage.launch_names().await.unwrap().len(), 4);
+    }
+}
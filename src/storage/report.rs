@@ -0,0 +1,122 @@
+use serde::Serialize;
+
+use super::{LaunchPerformance, Storage, StorageError};
+
+pub const WEEK_SECS: u64 = 7 * 24 * 60 * 60;
+// The Unix epoch was a Thursday, weeks start on Monday 00:00 UTC
+const MONDAY_OFFSET_SECS: u64 = 4 * 24 * 60 * 60;
+// A launch survived its first day when it kept at least half its launch price
+const SURVIVAL_MAX_DROP_PCT: f64 = -50f64;
+// and got rugged when it lost nine tenths of it
+const RUG_MIN_DROP_PCT: f64 = -90f64;
+const TOP_GAINERS: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Gainer {
+    pub token_address: String,
+    pub symbol: Option<String>,
+    pub change_pct: f64,
+}
+
+// Ecosystem rollup of the launches of one week. Rates and the median only cover launches whose
+// next day price was captured, `tracked` of them
+#[derive(Debug, Clone, Serialize)]
+pub struct WeeklyReport {
+    pub week_start: u64,
+    pub week_end: u64,
+    pub launch_count: usize,
+    pub tracked: usize,
+    pub survival_rate_pct: Option<f64>,
+    pub rug_rate_pct: Option<f64>,
+    pub median_change_pct: Option<f64>,
+    pub top_gainers: Vec<Gainer>,
+}
+
+// Start of the week `timestamp` falls in
+pub fn week_start(timestamp: u64) -> u64 {
+    let shifted = timestamp.saturating_sub(MONDAY_OFFSET_SECS);
+    shifted - shifted % WEEK_SECS + MONDAY_OFFSET_SECS
+}
+
+pub async fn compile_weekly_report(storage: &Storage, week_start: u64) -> Result<WeeklyReport, StorageError> {
+    let launches = storage.launch_performance(week_start, week_start + WEEK_SECS).await?;
+    Ok(summarize(week_start, &launches))
+}
+
+fn summarize(week_start: u64, launches: &[LaunchPerformance]) -> WeeklyReport {
+    let mut changes: Vec<(&LaunchPerformance, f64)> = launches
+        .iter()
+        .filter_map(|launch| match (launch.launch_price, launch.price_24h) {
+            (Some(launch_price), Some(price_24h)) if launch_price > 0f64 => {
+                Some((launch, (price_24h - launch_price) * 100f64 / launch_price))
+            }
+            _ => None,
+        })
+        .collect();
+    changes.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let tracked = changes.len();
+    let rate = |count: usize| (tracked > 0).then(|| count as f64 * 100f64 / tracked as f64);
+    let median_change_pct = match tracked {
+        0 => None,
+        n if n % 2 == 1 => Some(changes[n / 2].1),
+        n => Some((changes[n / 2 - 1].1 + changes[n / 2].1) / 2f64),
+    };
+
+    WeeklyReport {
+        week_start,
+        week_end: week_start + WEEK_SECS,
+        launch_count: launches.len(),
+        tracked,
+        survival_rate_pct: rate(changes.iter().filter(|(_, change)| *change > SURVIVAL_MAX_DROP_PCT).count()),
+        rug_rate_pct: rate(changes.iter().filter(|(_, change)| *change <= RUG_MIN_DROP_PCT).count()),
+        median_change_pct,
+        top_gainers: changes
+            .iter()
+            .take(TOP_GAINERS)
+            .filter(|(_, change)| *change > 0f64)
+            .map(|(launch, change)| Gainer {
+                token_address: launch.token_address.clone(),
+                symbol: launch.symbol.clone(),
+                change_pct: *change,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launch(address: &str, launch_price: f64, price_24h: Option<f64>) -> LaunchPerformance {
+        LaunchPerformance {
+            token_address: address.to_string(),
+            symbol: None,
+            launch_price: Some(launch_price),
+            price_24h,
+        }
+    }
+
+    #[test]
+    fn test_summarize_week() {
+        // 2024-06-05 is a Wednesday, its week started on Monday 2024-06-03
+        assert_eq!(week_start(1_717_600_000), 1_717_372_800);
+
+        let launches = [
+            launch("0x1", 1f64, Some(3f64)),
+            launch("0x2", 1f64, Some(0.05)),
+            launch("0x3", 1f64, Some(0.8)),
+            launch("0x4", 1f64, Some(0.4)),
+            launch("0x5", 1f64, None),
+        ];
+        let report = summarize(0, &launches);
+        assert_eq!((report.launch_count, report.tracked), (5, 4));
+        assert_eq!(report.survival_rate_pct, Some(50f64));
+        assert_eq!(report.rug_rate_pct, Some(25f64));
+        assert_eq!(report.median_change_pct.map(|pct| pct.round()), Some(-40f64));
+        assert_eq!(report.top_gainers.len(), 1);
+        assert_eq!(report.top_gainers[0].token_address, "0x1");
+
+        assert_eq!(summarize(0, &[]).median_change_pct, None);
+    }
+}
@@ -0,0 +1,84 @@
+use num_bigint::BigUint;
+use num_traits::Num;
+
+use crate::storage::LaunchTag;
+use crate::utils::address::canonicalize_address;
+
+// Actions carried by inline keyboard buttons
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallbackAction {
+    RefreshToken(String),
+    // Token picked among the matches of a search
+    ShowToken(String),
+    ShowHolders(String),
+    ConfirmTrade,
+    CancelTrade,
+    Tag(LaunchTag, String),
+    // Wallet and whether dust and spam positions are shown
+    Peek(String, bool),
+}
+
+impl CallbackAction {
+    // Telegram caps callback_data at 64 bytes, so addresses are packed in base 36
+    pub fn encode(&self) -> String {
+        let (prefix, address) = match self {
+            CallbackAction::RefreshToken(address) => ("r", address),
+            CallbackAction::ShowToken(address) => ("s", address),
+            CallbackAction::ShowHolders(address) => ("h", address),
+            CallbackAction::ConfirmTrade => return "tc".to_string(),
+            CallbackAction::CancelTrade => return "tx".to_string(),
+            CallbackAction::Tag(LaunchTag::Gem, address) => ("tg", address),
+            CallbackAction::Tag(LaunchTag::Sus, address) => ("ts", address),
+            CallbackAction::Tag(LaunchTag::Test, address) => ("tt", address),
+            CallbackAction::Peek(address, true) => ("pa", address),
+            CallbackAction::Peek(address, false) => ("pm", address),
+        };
+        let packed = BigUint::from_str_radix(address.trim_start_matches("0x"), 16)
+            .map(|value| value.to_str_radix(36))
+            .unwrap_or_default();
+        format!("{}:{}", prefix, packed)
+    }
+
+    pub fn parse(data: &str) -> Option<Self> {
+        match data {
+            "tc" => return Some(CallbackAction::ConfirmTrade),
+            "tx" => return Some(CallbackAction::CancelTrade),
+            _ => {}
+        }
+        let (prefix, packed) = data.split_once(':')?;
+        let value = BigUint::from_str_radix(packed, 36).ok()?;
+        let address = canonicalize_address(&format!("0x{}", value.to_str_radix(16)))?;
+        match prefix {
+            "r" => Some(CallbackAction::RefreshToken(address)),
+            "s" => Some(CallbackAction::ShowToken(address)),
+            "h" => Some(CallbackAction::ShowHolders(address)),
+            "tg" => Some(CallbackAction::Tag(LaunchTag::Gem, address)),
+            "ts" => Some(CallbackAction::Tag(LaunchTag::Sus, address)),
+            "tt" => Some(CallbackAction::Tag(LaunchTag::Test, address)),
+            "pa" => Some(CallbackAction::Peek(address, true)),
+            "pm" => Some(CallbackAction::Peek(address, false)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callback_round_trip_fits_telegram_limit() {
+        let address = "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
+        let action = CallbackAction::ShowHolders(address.to_string());
+        let data = action.encode();
+        assert!(data.len() <= 64);
+        assert_eq!(CallbackAction::parse(&data), Some(action));
+        let tag = CallbackAction::Tag(LaunchTag::Sus, address.to_string());
+        assert_eq!(CallbackAction::parse(&tag.encode()), Some(tag));
+        let peek = CallbackAction::Peek(address.to_string(), true);
+        assert_eq!(CallbackAction::parse(&peek.encode()), Some(peek));
+        let show = CallbackAction::ShowToken(address.to_string());
+        assert_eq!(CallbackAction::parse(&show.encode()), Some(show));
+        assert_eq!(CallbackAction::parse("x:1"), None);
+    }
+}
@@ -0,0 +1,193 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use tokio::sync::RwLock;
+
+// Configuration for the load circuit breaker
+#[derive(Clone)]
+pub struct CircuitConfig {
+    pub check_interval: Duration,
+    // Events waiting for an aggregation worker
+    max_queue_depth: usize,
+    // Share of RPC attempts failing transiently
+    max_rpc_error_rate: f64,
+    // Fewer attempts than this in a check say nothing about the error rate
+    min_rpc_calls: u64,
+    // Healthy checks in a row before paused notifications resume
+    resume_after_checks: u32,
+}
+
+impl CircuitConfig {
+    pub fn new() -> Self {
+        Self {
+            check_interval: Duration::from_secs(
+                std::env::var("CIRCUIT_CHECK_INTERVAL_SECS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(15)
+                    .max(1),
+            ),
+            max_queue_depth: std::env::var("CIRCUIT_MAX_QUEUE_DEPTH")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(50),
+            max_rpc_error_rate: std::env::var("CIRCUIT_MAX_RPC_ERROR_RATE")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.3),
+            min_rpc_calls: 20,
+            resume_after_checks: std::env::var("CIRCUIT_RESUME_AFTER_CHECKS")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(4)
+                .max(1),
+        }
+    }
+}
+
+// Pipeline load seen by one check
+#[derive(Debug, Clone, Copy)]
+pub struct Load {
+    pub queue_depth: usize,
+    pub rpc_calls: u64,
+    pub rpc_failures: u64,
+}
+
+impl Load {
+    // Why the pipeline counts as overloaded, None when it's fine
+    fn overload(&self, config: &CircuitConfig) -> Option<String> {
+        if self.queue_depth > config.max_queue_depth {
+            return Some(format!("{} events queued", self.queue_depth));
+        }
+        if self.rpc_calls >= config.min_rpc_calls {
+            let error_rate = self.rpc_failures as f64 / self.rpc_calls as f64;
+            if error_rate > config.max_rpc_error_rate {
+                return Some(format!("{:.0}% of RPC calls failing", error_rate * 100f64));
+            }
+        }
+        None
+    }
+}
+
+// State changes admins are told about
+#[derive(Debug, PartialEq)]
+pub enum CircuitTransition {
+    Tripped(String),
+    Resumed { paused_for: Duration },
+}
+
+#[derive(Default)]
+struct CircuitState {
+    opened_at: Option<Instant>,
+    healthy_checks: u32,
+}
+
+// Pauses digests, weekly reports and milestone alerts while the pipeline is overloaded, so
+// launch alerts get the RPC and Telegram budget. Trips on the first overloaded check and
+// resumes after a few healthy ones, so it doesn't flap around a threshold
+pub struct CircuitBreaker {
+    pub config: CircuitConfig,
+    queue_depth: AtomicUsize,
+    open: AtomicBool,
+    state: RwLock<CircuitState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitConfig) -> Self {
+        Self {
+            config,
+            queue_depth: AtomicUsize::new(0),
+            open: AtomicBool::new(false),
+            state: RwLock::new(CircuitState::default()),
+        }
+    }
+
+    // Reported by the event consumer as it takes events off the channel
+    pub fn set_queue_depth(&self, depth: usize) {
+        self.queue_depth.store(depth, Ordering::Relaxed);
+    }
+
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth.load(Ordering::Relaxed)
+    }
+
+    // Whether non-critical notifications are paused
+    pub fn is_open(&self) -> bool {
+        self.open.load(Ordering::Relaxed)
+    }
+
+    pub async fn check(&self, load: Load) -> Option<CircuitTransition> {
+        let mut state = self.state.write().await;
+        match (load.overload(&self.config), state.opened_at) {
+            (Some(reason), None) => {
+                state.opened_at = Some(Instant::now());
+                state.healthy_checks = 0;
+                self.open.store(true, Ordering::Relaxed);
+                Some(CircuitTransition::Tripped(reason))
+            }
+            (Some(_), Some(_)) => {
+                state.healthy_checks = 0;
+                None
+            }
+            (None, Some(opened_at)) => {
+                state.healthy_checks += 1;
+                if state.healthy_checks < self.config.resume_after_checks {
+                    return None;
+                }
+                *state = CircuitState::default();
+                self.open.store(false, Ordering::Relaxed);
+                Some(CircuitTransition::Resumed {
+                    paused_for: opened_at.elapsed(),
+                })
+            }
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(queue_depth: usize, rpc_calls: u64, rpc_failures: u64) -> Load {
+        Load {
+            queue_depth,
+            rpc_calls,
+            rpc_failures,
+        }
+    }
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(CircuitConfig {
+            check_interval: Duration::from_secs(15),
+            max_queue_depth: 10,
+            max_rpc_error_rate: 0.5,
+            min_rpc_calls: 4,
+            resume_after_checks: 2,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_trips_on_load_and_resumes_after_healthy_checks() {
+        let breaker = breaker();
+        assert_eq!(breaker.check(load(10, 0, 0)).await, None);
+        // A handful of failed calls isn't a rate
+        assert_eq!(breaker.check(load(0, 3, 3)).await, None);
+        assert!(!breaker.is_open());
+
+        assert_eq!(
+            breaker.check(load(0, 10, 6)).await,
+            Some(CircuitTransition::Tripped("60% of RPC calls failing".to_string()))
+        );
+        assert!(breaker.is_open());
+        assert_eq!(breaker.check(load(0, 0, 0)).await, None);
+        // Load coming back starts the count over
+        assert_eq!(breaker.check(load(11, 0, 0)).await, None);
+        assert_eq!(breaker.check(load(0, 0, 0)).await, None);
+        assert!(matches!(
+            breaker.check(load(0, 0, 0)).await,
+            Some(CircuitTransition::Resumed { .. })
+        ));
+        assert!(!breaker.is_open());
+    }
+}
@@ -0,0 +1,136 @@
+use serde_json::{json, Value};
+
+use super::demo::DEMO_COMMANDS;
+
+// Command menus registered with setMyCommands, one per scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandScope {
+    // Private chats, and any chat without a more specific menu
+    Default,
+    Groups,
+}
+
+impl CommandScope {
+    pub const ALL: [CommandScope; 2] = [CommandScope::Default, CommandScope::Groups];
+
+    pub fn as_json(&self) -> Value {
+        match self {
+            CommandScope::Default => json!({ "type": "default" }),
+            CommandScope::Groups => json!({ "type": "all_group_chats" }),
+        }
+    }
+}
+
+// A command as shown in the menu. Telegram only takes lowercase names, arguments go in the
+// description
+struct MenuCommand {
+    name: &'static str,
+    description: &'static str,
+    // Offered in groups too, personal and wallet commands stay in private chats
+    in_groups: bool,
+    // Only offered when trading is configured
+    trading: bool,
+}
+
+const fn command(name: &'static str, description: &'static str, in_groups: bool) -> MenuCommand {
+    MenuCommand {
+        name,
+        description,
+        in_groups,
+        trading: false,
+    }
+}
+
+// Descriptions are English only for now, Telegram shows them whatever the user's language
+const MENU: [MenuCommand; 28] = [
+    command("start", "Start receiving token alerts", true),
+    command("stop", "Stop receiving token alerts", true),
+    command("status", "Check your current alert status", true),
+    command("help", "Show available commands", true),
+    command("sniq", "<token, $SYMBOL or name> - Get token info", true),
+    command("holders", "<address> - Show a token's largest holders", true),
+    command("peek", "<wallet> [all] - Get wallet info, dust and spam included with all", true),
+    command("spot", "<wallet> <token_address> - Get wallet holdings for a particular token", true),
+    command("recent", "[n] [gem|sus|test] - Show the latest launches, optionally by curator tag", true),
+    command("filters", "Filter which launches you get alerted about", true),
+    command("setbuttons", "<usd> [usd] [usd] - Choose your buy button amounts", false),
+    command("delivery", "<instant|hourly|daily> - Get alerts instantly or as a digest", true),
+    command("radar", "<on|off> - Get told about tokens before they launch", true),
+    command("currency", "<usd|eth|strk> - Show market caps and liquidity in USD, ETH or STRK", true),
+    command("watch", "<token_address> - Get alerts when a token moves", true),
+    command("unwatch", "<token_address> - Stop watching a token", true),
+    command("follow", "<token_or_wallet> - Get every alert about a token, or a wallet's trades", true),
+    command("unfollow", "<token_or_wallet> - Stop following a token or wallet", true),
+    command("watchlist", "Show your watched tokens and followed tokens and wallets", true),
+    command("share_settings", "Get a code to share your filters and watchlist", false),
+    command("import_settings", "<code> - Copy the filters and watchlist behind a code", false),
+    command("alert", "<token> [mcap] [above|below] <value> - Get alerted once a threshold is crossed", false),
+    command("alerts", "Show your price alerts", false),
+    command("delalert", "<id> - Delete a price alert", false),
+    command("papertrade", "buy <token> <usd> - Simulate a buy without risking funds", false),
+    command("portfolio", "Show your paper trading PnL", false),
+    MenuCommand {
+        name: "buy",
+        description: "<token> <amount> - Buy a token with your linked wallet",
+        in_groups: false,
+        trading: true,
+    },
+    MenuCommand {
+        name: "wallet",
+        description: "Link or unlink your trading wallet",
+        in_groups: false,
+        trading: true,
+    },
+];
+
+// The menu of a scope, without trading commands when trading is off and limited to the demo
+// commands on a public demo
+pub fn menu(scope: CommandScope, trading: bool, demo: bool) -> Vec<Value> {
+    MENU.iter()
+        .filter(|command| scope == CommandScope::Default || command.in_groups)
+        .filter(|command| trading || !command.trading)
+        .filter(|command| {
+            !demo
+                || DEMO_COMMANDS
+                    .iter()
+                    .any(|demo_command| demo_command[1..].eq_ignore_ascii_case(command.name))
+        })
+        .map(|command| json!({ "command": command.name, "description": command.description }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(commands: &[Value]) -> Vec<&str> {
+        commands
+            .iter()
+            .map(|command| command["command"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_menus_per_scope() {
+        for command in &MENU {
+            // setMyCommands rejects the whole menu over a single invalid name
+            assert!(command.name.len() <= 32, "{}", command.name);
+            assert!(
+                command.name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'),
+                "{}",
+                command.name
+            );
+            assert!(command.description.len() <= 256, "{}", command.name);
+        }
+
+        let private = menu(CommandScope::Default, false, false);
+        let groups = menu(CommandScope::Groups, false, false);
+        assert!(names(&groups).contains(&"sniq") && !names(&groups).contains(&"portfolio"));
+        assert!(groups.len() < private.len());
+        assert!(!names(&private).contains(&"wallet"));
+        assert!(names(&menu(CommandScope::Default, true, false)).contains(&"wallet"));
+        // Wallet keys are never typed in a group
+        assert!(!names(&menu(CommandScope::Groups, true, false)).contains(&"wallet"));
+        assert_eq!(names(&menu(CommandScope::Default, true, true)).len(), DEMO_COMMANDS.len());
+    }
+}
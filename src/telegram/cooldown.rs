@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tokio::sync::RwLock;
+
+// Commands that aggregate token or wallet data, each one costs explorer and RPC calls
+const HEAVY_COMMANDS: [&str; 8] = [
+    "/sniQ",
+    "/holders",
+    "/peek",
+    "/spot",
+    "/papertrade",
+    "/watch",
+    "/follow",
+    "/portfolio",
+];
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+// Configuration for per-chat command rate limiting
+#[derive(Clone)]
+pub struct CooldownConfig {
+    // Weight a chat may spend per minute, 0 disables the limit
+    budget_per_minute: u32,
+    heavy_weight: u32,
+    // Never limited, on top of the admins
+    exempt_chat_ids: Vec<i64>,
+}
+
+impl CooldownConfig {
+    pub fn new() -> Self {
+        Self {
+            budget_per_minute: std::env::var("COMMAND_BUDGET_PER_MINUTE")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(20),
+            heavy_weight: std::env::var("COMMAND_HEAVY_WEIGHT")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(4)
+                .max(1),
+            exempt_chat_ids: std::env::var("COMMAND_LIMIT_EXEMPT_CHAT_IDS")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|id| id.trim().parse::<i64>().ok())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Throttle {
+    Allow,
+    // Over budget, with the seconds until the command would go through
+    SlowDown(u64),
+    // Over budget and already told so, replying again would only feed the spam
+    Ignore,
+}
+
+#[derive(Default)]
+struct ChatUsage {
+    // When each command in the window was sent, with its weight
+    hits: Vec<(Instant, u32)>,
+    warned: bool,
+}
+
+// Sliding window of weighted commands per chat, so one chat can't exhaust the explorer and RPC
+// quotas everyone shares
+pub struct CommandCooldowns {
+    config: CooldownConfig,
+    usage: RwLock<HashMap<i64, ChatUsage>>,
+}
+
+impl CommandCooldowns {
+    pub fn new(config: CooldownConfig) -> Self {
+        Self {
+            config,
+            usage: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn is_exempt(&self, chat_id: i64) -> bool {
+        self.config.budget_per_minute == 0 || self.config.exempt_chat_ids.contains(&chat_id)
+    }
+
+    // Aggregation commands weigh more, plain text that isn't a command is free
+    fn weight(&self, command: &str) -> u32 {
+        if !command.starts_with('/') {
+            0
+        } else if HEAVY_COMMANDS.iter().any(|heavy| heavy.eq_ignore_ascii_case(command)) {
+            self.config.heavy_weight
+        } else {
+            1
+        }
+    }
+
+    // Records the command when the chat has budget left for it
+    pub async fn check(&self, chat_id: i64, command: &str) -> Throttle {
+        self.check_at(chat_id, command, Instant::now()).await
+    }
+
+    async fn check_at(&self, chat_id: i64, command: &str, now: Instant) -> Throttle {
+        let weight = self.weight(command);
+        if weight == 0 || self.is_exempt(chat_id) {
+            return Throttle::Allow;
+        }
+        let mut usage = self.usage.write().await;
+        // Chats whose commands all left the window are forgotten, they start over with a full budget
+        usage.retain(|_, chat| {
+            chat.hits.retain(|(at, _)| now.duration_since(*at) < WINDOW);
+            !chat.hits.is_empty()
+        });
+        let chat = usage.entry(chat_id).or_default();
+
+        // A heavy command is never over a budget smaller than its weight on its own
+        let budget = self.config.budget_per_minute.max(weight);
+        let mut spent: u32 = chat.hits.iter().map(|(_, weight)| weight).sum();
+        if spent + weight <= budget {
+            chat.hits.push((now, weight));
+            chat.warned = false;
+            return Throttle::Allow;
+        }
+        if chat.warned {
+            return Throttle::Ignore;
+        }
+        chat.warned = true;
+        // Oldest hits leave the window first, wait until enough of them have
+        let mut free_at = now;
+        for (at, hit_weight) in &chat.hits {
+            spent -= hit_weight;
+            free_at = *at + WINDOW;
+            if spent + weight <= budget {
+                break;
+            }
+        }
+        Throttle::SlowDown(free_at.duration_since(now).as_secs().max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cooldowns(budget_per_minute: u32) -> CommandCooldowns {
+        CommandCooldowns::new(CooldownConfig {
+            budget_per_minute,
+            heavy_weight: 4,
+            exempt_chat_ids: vec![7],
+        })
+    }
+
+    #[tokio::test]
+    async fn test_heavy_commands_spend_the_budget_faster() {
+        let cooldowns = cooldowns(10);
+        let start = Instant::now();
+        assert_eq!(cooldowns.check_at(1, "/sniQ", start).await, Throttle::Allow);
+        assert_eq!(cooldowns.check_at(1, "/holders", start).await, Throttle::Allow);
+        // 8 spent, a light command still fits but a third lookup doesn't
+        assert_eq!(cooldowns.check_at(1, "/status", start).await, Throttle::Allow);
+        let later = start + Duration::from_secs(20);
+        assert_eq!(cooldowns.check_at(1, "/peek", later).await, Throttle::SlowDown(40));
+        assert_eq!(cooldowns.check_at(1, "/peek", later).await, Throttle::Ignore);
+        // Text that isn't a command and other chats are unaffected
+        assert_eq!(cooldowns.check_at(1, "hello", later).await, Throttle::Allow);
+        assert_eq!(cooldowns.check_at(2, "/peek", later).await, Throttle::Allow);
+
+        // Once the first lookup leaves the window there is room again
+        let after = start + WINDOW;
+        assert_eq!(cooldowns.check_at(1, "/peek", after).await, Throttle::Allow);
+    }
+
+    #[tokio::test]
+    async fn test_exempt_chats() {
+        let now = Instant::now();
+        let strict = cooldowns(1);
+        for _ in 0..5 {
+            assert_eq!(strict.check_at(7, "/sniQ", now).await, Throttle::Allow);
+        }
+        // A budget of 0 turns limiting off
+        assert!(cooldowns(0).is_exempt(1));
+    }
+
+    #[tokio::test]
+    async fn test_idle_chats_are_forgotten() {
+        let cooldowns = cooldowns(10);
+        let start = Instant::now();
+        assert_eq!(cooldowns.check_at(1, "/watch", start).await, Throttle::Allow);
+        assert_eq!(cooldowns.check_at(2, "/portfolio", start).await, Throttle::Allow);
+        assert_eq!(cooldowns.usage.read().await.len(), 2);
+        assert_eq!(cooldowns.check_at(3, "/help", start + WINDOW).await, Throttle::Allow);
+        assert_eq!(cooldowns.usage.read().await.len(), 1);
+    }
+}
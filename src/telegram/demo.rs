@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tokio::sync::RwLock;
+
+use crate::utils::cache::{default_max_entries, Cache};
+use crate::utils::types::common::{MemecoinInfo, TokenCategoryResponse, TokenHoldings, UserTokenInfo};
+
+// Commands exposed by a public demo deployment
+pub const DEMO_COMMANDS: [&str; 6] = ["/sniQ", "/holders", "/peek", "/spot", "/recent", "/help"];
+
+// Sliding window limiter keyed by chat id
+pub struct RateLimiter {
+    max_requests: usize,
+    window: Duration,
+    hits: RwLock<HashMap<i64, Vec<Instant>>>,
+}
+
+impl RateLimiter {
+    pub fn new(max_requests: usize, window: Duration) -> Self {
+        Self {
+            max_requests,
+            window,
+            hits: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // Records a request and returns false if the chat is over its budget
+    pub async fn allow(&self, chat_id: i64) -> bool {
+        let mut hits = self.hits.write().await;
+        // Chats whose requests all left the window are forgotten, so the map doesn't grow with every
+        // chat that ever tried the demo
+        hits.retain(|_, chat_hits| {
+            chat_hits.retain(|hit| hit.elapsed() < self.window);
+            !chat_hits.is_empty()
+        });
+        let chat_hits = hits.entry(chat_id).or_default();
+        if chat_hits.len() >= self.max_requests {
+            return false;
+        }
+        chat_hits.push(Instant::now());
+        true
+    }
+}
+
+pub struct DemoMode {
+    pub limiter: RateLimiter,
+    pub token_info: Cache<(MemecoinInfo, TokenCategoryResponse)>,
+    pub holdings: Cache<TokenHoldings>,
+    pub spots: Cache<UserTokenInfo>,
+}
+
+impl DemoMode {
+    pub fn new() -> Self {
+        let requests_per_minute = std::env::var("DEMO_RATE_LIMIT_PER_MINUTE")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(3);
+        let cache_ttl = std::env::var("DEMO_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(300);
+        let cache_ttl = Some(Duration::from_secs(cache_ttl));
+
+        Self {
+            limiter: RateLimiter::new(requests_per_minute, Duration::from_secs(60)),
+            token_info: Cache::new("demo_token_info", default_max_entries(), cache_ttl),
+            holdings: Cache::new("demo_holdings", default_max_entries(), cache_ttl),
+            spots: Cache::new("demo_spots", default_max_entries(), cache_ttl),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rate_limiter_forgets_idle_chats() {
+        let limiter = RateLimiter::new(1, Duration::from_millis(20));
+        assert!(limiter.allow(1).await);
+        assert!(!limiter.allow(1).await);
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(limiter.allow(2).await);
+        assert_eq!(limiter.hits.read().await.len(), 1);
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tokio::sync::RwLock;
+
+// Launches listed in a single summary, Telegram caps messages at 4096 characters
+pub const MAX_DIGEST_ENTRIES: usize = 25;
+
+// Key metrics of a launch as they were when it was queued
+#[derive(Debug, Clone)]
+pub struct DigestEntry {
+    pub symbol: String,
+    pub address: String,
+    pub market_cap: String,
+    pub liquidity_usd: String,
+    pub holders: usize,
+}
+
+struct ChatDigest {
+    // The window opens with the first launch queued after the previous summary
+    opened_at: Instant,
+    entries: Vec<DigestEntry>,
+    overflow: usize,
+}
+
+// A summary that is due, launches beyond MAX_DIGEST_ENTRIES are only counted
+pub struct DueDigest {
+    pub chat_id: i64,
+    pub entries: Vec<DigestEntry>,
+    pub overflow: usize,
+    opened_at: Instant,
+}
+
+// Launches held back per chat until their digest is sent
+pub struct DigestQueue {
+    chats: RwLock<HashMap<i64, ChatDigest>>,
+}
+
+impl DigestQueue {
+    pub fn new() -> Self {
+        Self {
+            chats: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn push(&self, chat_id: i64, entry: DigestEntry) {
+        let mut chats = self.chats.write().await;
+        let digest = chats.entry(chat_id).or_insert_with(|| ChatDigest {
+            opened_at: Instant::now(),
+            entries: Vec::new(),
+            overflow: 0,
+        });
+        if digest.entries.len() < MAX_DIGEST_ENTRIES {
+            digest.entries.push(entry);
+        } else {
+            digest.overflow += 1;
+        }
+    }
+
+    // Takes every digest whose window has elapsed. `period_of` gives a chat's digest period,
+    // None once it went back to instant delivery, in which case its queue is flushed right away
+    pub async fn take_due(&self, period_of: impl Fn(i64) -> Option<Duration>) -> Vec<DueDigest> {
+        let mut chats = self.chats.write().await;
+        let due: Vec<i64> = chats
+            .iter()
+            .filter(|(chat_id, digest)| {
+                period_of(**chat_id).is_none_or(|period| digest.opened_at.elapsed() >= period)
+            })
+            .map(|(chat_id, _)| *chat_id)
+            .collect();
+        due.into_iter()
+            .filter_map(|chat_id| chats.remove(&chat_id).map(|digest| (chat_id, digest)))
+            .map(|(chat_id, digest)| DueDigest {
+                chat_id,
+                entries: digest.entries,
+                overflow: digest.overflow,
+                opened_at: digest.opened_at,
+            })
+            .collect()
+    }
+
+    // Puts back a digest that failed to send, ahead of the launches queued since. It keeps its
+    // window so the next pass retries it
+    pub async fn requeue(&self, due: DueDigest) {
+        let mut chats = self.chats.write().await;
+        let digest = chats.entry(due.chat_id).or_insert_with(|| ChatDigest {
+            opened_at: due.opened_at,
+            entries: Vec::new(),
+            overflow: 0,
+        });
+        digest.opened_at = digest.opened_at.min(due.opened_at);
+        let queued_since = std::mem::replace(&mut digest.entries, due.entries);
+        digest.overflow += due.overflow;
+        for entry in queued_since {
+            if digest.entries.len() < MAX_DIGEST_ENTRIES {
+                digest.entries.push(entry);
+            } else {
+                digest.overflow += 1;
+            }
+        }
+    }
+
+    // Drops a chat's pending launches, returns how many there were
+    pub async fn remove_chat(&self, chat_id: i64) -> usize {
+        self.chats
+            .write()
+            .await
+            .remove(&chat_id)
+            .map_or(0, |digest| digest.entries.len() + digest.overflow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(symbol: &str) -> DigestEntry {
+        DigestEntry {
+            symbol: symbol.to_string(),
+            address: "0x1".to_string(),
+            market_cap: "1000".to_string(),
+            liquidity_usd: "500".to_string(),
+            holders: 3,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_digest_is_due_after_its_window() {
+        let queue = DigestQueue::new();
+        queue.push(1, entry("A")).await;
+        queue.push(2, entry("B")).await;
+        for _ in 0..MAX_DIGEST_ENTRIES {
+            queue.push(2, entry("C")).await;
+        }
+
+        let hourly = |_| Some(Duration::from_secs(3600));
+        assert!(queue.take_due(hourly).await.is_empty());
+
+        // Chat 1 switched back to instant delivery, chat 2 is still waiting
+        let due = queue
+            .take_due(|chat_id| if chat_id == 1 { None } else { Some(Duration::from_secs(3600)) })
+            .await;
+        assert_eq!(due.len(), 1);
+        assert_eq!((due[0].chat_id, due[0].entries.len()), (1, 1));
+
+        let due = queue.take_due(|_| Some(Duration::ZERO)).await;
+        assert_eq!((due[0].entries.len(), due[0].overflow), (MAX_DIGEST_ENTRIES, 1));
+        assert_eq!(queue.remove_chat(2).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_digest_is_requeued() {
+        let queue = DigestQueue::new();
+        queue.push(1, entry("A")).await;
+        let mut due = queue.take_due(|_| Some(Duration::ZERO)).await;
+        queue.push(1, entry("B")).await;
+        queue.requeue(due.remove(0)).await;
+
+        let hourly = |_| Some(Duration::from_secs(3600));
+        assert!(queue.take_due(hourly).await.is_empty());
+        let due = queue.take_due(|_| Some(Duration::ZERO)).await;
+        let symbols: Vec<&str> = due[0].entries.iter().map(|entry| entry.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["A", "B"]);
+    }
+}
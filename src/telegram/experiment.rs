@@ -0,0 +1,174 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Variant {
+    A,
+    B,
+}
+
+impl Variant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Variant::A => "A",
+            Variant::B => "B",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_uppercase().as_str() {
+            "A" => Some(Variant::A),
+            "B" => Some(Variant::B),
+            _ => None,
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Variant::A => 0,
+            Variant::B => 1,
+        }
+    }
+}
+
+// Copy that differs between the two alert cards
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantCopy {
+    pub headline: String,
+    pub call_to_action: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct VariantStats {
+    pub impressions: u64,
+    pub clicks: u64,
+}
+
+impl VariantStats {
+    pub fn click_through_rate(&self) -> f64 {
+        if self.impressions == 0 {
+            return 0f64;
+        }
+        self.clicks as f64 * 100f64 / self.impressions as f64
+    }
+}
+
+#[derive(Debug)]
+struct ExperimentState {
+    enabled: bool,
+    // Re-rolled on every start so each experiment gets a fresh random split
+    salt: u64,
+    copies: [VariantCopy; 2],
+    stats: [VariantStats; 2],
+}
+
+// A/B test of launch alert copy, split per subscriber
+pub struct AlertExperiment {
+    state: RwLock<ExperimentState>,
+}
+
+impl AlertExperiment {
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(ExperimentState {
+                enabled: false,
+                salt: 0,
+                copies: [
+                    VariantCopy {
+                        headline: "🚨 ====== <b>FRESH LAUNCH ALERT</b> ====== 🚨".to_string(),
+                        call_to_action: "⚡️ <b>GET IN NOW</b>".to_string(),
+                    },
+                    VariantCopy {
+                        headline: "🆕 ====== <b>NEW MEMECOIN LIVE</b> ====== 🆕".to_string(),
+                        call_to_action: "🎯 <b>Snipe it before the crowd</b>".to_string(),
+                    },
+                ],
+                stats: [VariantStats::default(); 2],
+            }),
+        }
+    }
+
+    pub async fn is_enabled(&self) -> bool {
+        self.state.read().await.enabled
+    }
+
+    // Starts a new experiment, resetting counters and re-splitting subscribers
+    pub async fn start(&self) {
+        let mut state = self.state.write().await;
+        state.enabled = true;
+        state.salt = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        state.stats = [VariantStats::default(); 2];
+    }
+
+    pub async fn stop(&self) {
+        self.state.write().await.enabled = false;
+    }
+
+    pub async fn set_copy(&self, variant: Variant, copy: VariantCopy) {
+        self.state.write().await.copies[variant.index()] = copy;
+    }
+
+    // The variant a chat's cards use, derived from the split without touching counters, so
+    // previews can look it up too. Variant A is always used while no experiment is running
+    pub async fn variant_of(&self, chat_id: i64) -> Variant {
+        let state = self.state.read().await;
+        if !state.enabled {
+            return Variant::A;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        (state.salt, chat_id).hash(&mut hasher);
+        if hasher.finish().is_multiple_of(2) {
+            Variant::A
+        } else {
+            Variant::B
+        }
+    }
+
+    pub async fn copy(&self, variant: Variant) -> VariantCopy {
+        self.state.read().await.copies[variant.index()].clone()
+    }
+
+    pub async fn record_impression(&self, variant: Variant) {
+        let mut state = self.state.write().await;
+        if state.enabled {
+            state.stats[variant.index()].impressions += 1;
+        }
+    }
+
+    pub async fn record_click(&self, variant: Variant) {
+        let mut state = self.state.write().await;
+        if state.enabled {
+            state.stats[variant.index()].clicks += 1;
+        }
+    }
+
+    pub async fn stats(&self) -> [VariantStats; 2] {
+        self.state.read().await.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_variant_lookup_is_read_only() {
+        let experiment = AlertExperiment::new();
+        assert_eq!(experiment.variant_of(1).await, Variant::A);
+
+        experiment.start().await;
+        let variant = experiment.variant_of(1).await;
+        assert_eq!(experiment.variant_of(1).await, variant);
+        // Looking a chat up is not an impression
+        let stats = experiment.stats().await;
+        assert_eq!(stats.map(|stats| stats.impressions), [0, 0]);
+    }
+}
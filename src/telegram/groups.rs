@@ -0,0 +1,72 @@
+// Commands only group admins may send, members can still look tokens up
+pub const GROUP_ADMIN_COMMANDS: [&str; 2] = ["/start", "/stop"];
+
+// Commands acting on the chat's own wallet, trades or the bot itself. Every member of a shared
+// chat would act on the same ones, so they only work in a private chat with the bot
+pub const PRIVATE_COMMANDS: [&str; 4] = ["/wallet", "/buy", "/papertrade", "/admin"];
+
+// Whether a chat is a group or channel rather than a private chat with a user
+pub fn is_shared_chat(chat_type: &str) -> bool {
+    matches!(chat_type, "group" | "supergroup" | "channel")
+}
+
+// Member statuses that let the bot read and post in a chat
+pub fn is_present(status: &str) -> bool {
+    matches!(status, "creator" | "administrator" | "member" | "restricted")
+}
+
+pub fn is_admin_status(status: &str) -> bool {
+    matches!(status, "creator" | "administrator")
+}
+
+// Whether a button was pressed by the user the private chat is with, a private chat's id being
+// its user's id
+pub fn pressed_by_owner(chat_type: &str, chat_id: i64, user_id: i64) -> bool {
+    chat_type == "private" && chat_id == user_id
+}
+
+// In groups commands can be addressed to a bot, as in /start@sniq_bot. The mention is stripped
+// when it names this bot, commands meant for other bots give None
+pub fn addressed_command(text: &str, username: Option<&str>) -> Option<String> {
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    let (first, rest) = text.split_at(end);
+    match first.split_once('@') {
+        Some((command, bot)) if command.starts_with('/') => match username {
+            Some(username) if !username.eq_ignore_ascii_case(bot) => None,
+            _ => Some(format!("{}{}", command, rest)),
+        },
+        _ => Some(text.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_addressed_command() {
+        let username = Some("sniq_bot");
+        assert_eq!(addressed_command("/start", username).as_deref(), Some("/start"));
+        assert_eq!(addressed_command("/sniQ@SNIQ_bot 0x1", username).as_deref(), Some("/sniQ 0x1"));
+        assert_eq!(addressed_command("/start@other_bot", username), None);
+        // Addresses and emails in plain messages are left alone
+        assert_eq!(addressed_command("mail me@x.io", username).as_deref(), Some("mail me@x.io"));
+        assert_eq!(addressed_command("/stop@sniq_bot", None).as_deref(), Some("/stop"));
+    }
+
+    #[test]
+    fn test_member_statuses() {
+        assert!(is_present("administrator") && is_admin_status("creator"));
+        assert!(is_present("member") && !is_admin_status("member"));
+        assert!(!is_present("left") && !is_present("kicked"));
+        assert!(is_shared_chat("supergroup") && !is_shared_chat("private"));
+    }
+
+    #[test]
+    fn test_pressed_by_owner() {
+        assert!(pressed_by_owner("private", 42, 42));
+        assert!(!pressed_by_owner("private", 42, 7));
+        // In a group any member can press a button, the group's wallet is nobody's to trade
+        assert!(!pressed_by_owner("supergroup", -100, -100));
+    }
+}
@@ -0,0 +1,103 @@
+use serde_json::{json, Value};
+
+use crate::utils::address::canonicalize_address;
+
+// Cards offered per inline query, each one is a live lookup
+pub const MAX_INLINE_RESULTS: u32 = 3;
+// How long Telegram may reuse an answer for the same query
+pub const INLINE_CACHE_SECS: u64 = 30;
+// Longest text looked up as a symbol
+const MAX_SYMBOL_LEN: usize = 32;
+
+// What `@bot <query>` asks for
+#[derive(Debug, PartialEq)]
+pub enum InlineSearch {
+    Address(String),
+    // Symbol or name, resolved against the /sniQ search index
+    Symbol(String),
+    // Nothing typed yet, the latest launches are offered
+    Latest,
+}
+
+impl InlineSearch {
+    pub fn parse(query: &str) -> Option<Self> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Some(InlineSearch::Latest);
+        }
+        if query.starts_with("0x") {
+            return canonicalize_address(query).map(InlineSearch::Address);
+        }
+        let symbol = query.trim_start_matches('$');
+        if symbol.is_empty() || symbol.len() > MAX_SYMBOL_LEN {
+            return None;
+        }
+        Some(InlineSearch::Symbol(symbol.to_string()))
+    }
+}
+
+// Buttons of a shared card can't call back into the bot, only links are kept
+pub fn link_buttons(keyboard: Value) -> Value {
+    let rows: Vec<Value> = keyboard["inline_keyboard"]
+        .as_array()
+        .map(|rows| {
+            rows.iter()
+                .filter_map(|row| {
+                    let buttons: Vec<Value> = row
+                        .as_array()?
+                        .iter()
+                        .filter(|button| button.get("url").is_some())
+                        .cloned()
+                        .collect();
+                    (!buttons.is_empty()).then_some(Value::Array(buttons))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    json!({ "inline_keyboard": rows })
+}
+
+// A card offered in the inline results, `card` is what gets posted when it is picked
+pub fn article(id: usize, title: &str, description: &str, card: &str, keyboard: Value) -> Value {
+    json!({
+        "type": "article",
+        "id": id.to_string(),
+        "title": title,
+        "description": description,
+        "input_message_content": {
+            "message_text": card,
+            "parse_mode": "HTML"
+        },
+        "reply_markup": keyboard
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_inline_search() {
+        assert_eq!(InlineSearch::parse("  "), Some(InlineSearch::Latest));
+        assert_eq!(
+            InlineSearch::parse("0x1a"),
+            canonicalize_address("0x1a").map(InlineSearch::Address)
+        );
+        assert_eq!(InlineSearch::parse("0xnothex"), None);
+        assert_eq!(InlineSearch::parse("$doge"), Some(InlineSearch::Symbol("doge".to_string())));
+        assert_eq!(InlineSearch::parse("$"), None);
+    }
+
+    #[test]
+    fn test_link_buttons() {
+        let keyboard = json!({
+            "inline_keyboard": [
+                [{ "text": "🚀 Buy $25", "url": "https://app.avnu.fi" }],
+                [{ "text": "🔄 Refresh", "callback_data": "r:1" }]
+            ]
+        });
+        let links = link_buttons(keyboard);
+        assert_eq!(links["inline_keyboard"].as_array().unwrap().len(), 1);
+        assert_eq!(links["inline_keyboard"][0][0]["text"], "🚀 Buy $25");
+    }
+}
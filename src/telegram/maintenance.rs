@@ -0,0 +1,112 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+use crate::utils::types::common::LaunchAlert;
+
+// Unix timestamps (seconds) bounding a planned downtime
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct MaintenanceWindow {
+    pub starts_at: u64,
+    pub ends_at: u64,
+}
+
+impl MaintenanceWindow {
+    pub fn is_active_at(&self, now: u64) -> bool {
+        now >= self.starts_at && now < self.ends_at
+    }
+}
+
+// State changes subscribers should be told about
+pub enum MaintenanceTransition {
+    Started(MaintenanceWindow),
+    Finished(Vec<LaunchAlert>),
+}
+
+#[derive(Default)]
+struct MaintenanceState {
+    window: Option<MaintenanceWindow>,
+    start_announced: bool,
+    buffered: Vec<LaunchAlert>,
+}
+
+pub struct Maintenance {
+    state: RwLock<MaintenanceState>,
+}
+
+impl Maintenance {
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(MaintenanceState::default()),
+        }
+    }
+
+    pub async fn schedule(&self, start_in_secs: u64, duration_secs: u64) -> MaintenanceWindow {
+        let starts_at = now() + start_in_secs;
+        let window = MaintenanceWindow {
+            starts_at,
+            ends_at: starts_at + duration_secs,
+        };
+        let mut state = self.state.write().await;
+        state.window = Some(window);
+        state.start_announced = false;
+        window
+    }
+
+    // Clears the window and hands back anything buffered while it was active
+    pub async fn cancel(&self) -> Vec<LaunchAlert> {
+        let mut state = self.state.write().await;
+        state.window = None;
+        state.start_announced = false;
+        std::mem::take(&mut state.buffered)
+    }
+
+    pub async fn window(&self) -> Option<MaintenanceWindow> {
+        self.state.read().await.window
+    }
+
+    pub async fn is_active(&self) -> bool {
+        let now = now();
+        self.window()
+            .await
+            .map(|window| window.is_active_at(now))
+            .unwrap_or(false)
+    }
+
+    // Holds a launch back while maintenance is running, otherwise returns it to the caller
+    pub async fn try_buffer(&self, alert: LaunchAlert) -> Option<LaunchAlert> {
+        let mut state = self.state.write().await;
+        match state.window {
+            Some(window) if window.is_active_at(now()) => {
+                state.buffered.push(alert);
+                None
+            }
+            _ => Some(alert),
+        }
+    }
+
+    pub async fn poll(&self) -> Option<MaintenanceTransition> {
+        let now = now();
+        let mut state = self.state.write().await;
+        let window = state.window?;
+
+        if now >= window.starts_at && !state.start_announced {
+            state.start_announced = true;
+            return Some(MaintenanceTransition::Started(window));
+        }
+        if now >= window.ends_at {
+            state.window = None;
+            state.start_announced = false;
+            return Some(MaintenanceTransition::Finished(std::mem::take(&mut state.buffered)));
+        }
+        None
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
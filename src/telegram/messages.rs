@@ -0,0 +1,98 @@
+// Messages are sent with parse_mode HTML. Static copy writes its tags inline, anything a token
+// deployer, user or remote API controls goes through `escape` first: an unescaped `<` or `&`
+// in a token name makes Telegram reject the whole message
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watcher::price_history::PriceChart;
+
+    // Names that broke the Markdown cards, or would break an HTML one left unescaped
+    const HOSTILE_NAMES: [&str; 6] = [
+        "PEPE_2_MOON",
+        "*BOLD*",
+        "[rug](https://evil.example)",
+        "`code",
+        "<b>Free</b> & <i>easy",
+        "a\"b'c>",
+    ];
+
+    // Whether Telegram would accept `html`: only its tags, properly nested, and only the
+    // entities it knows
+    fn is_valid_html(html: &str) -> bool {
+        let mut open: Vec<String> = Vec::new();
+        let mut rest = html;
+        while let Some(start) = rest.find(['<', '&', '>']) {
+            let (marker, tail) = (rest.as_bytes()[start], &rest[start..]);
+            match marker {
+                b'<' => {
+                    let end = match tail.find('>') {
+                        Some(end) => end,
+                        None => return false,
+                    };
+                    let tag = &tail[1..end];
+                    match tag.strip_prefix('/') {
+                        Some(name) => {
+                            if open.pop().as_deref() != Some(name) {
+                                return false;
+                            }
+                        }
+                        None if matches!(tag, "b" | "i" | "code" | "pre") => open.push(tag.to_string()),
+                        None => return false,
+                    }
+                    rest = &tail[end + 1..];
+                }
+                b'&' => {
+                    let entity = ["&amp;", "&lt;", "&gt;", "&quot;"]
+                        .into_iter()
+                        .find(|entity| tail.starts_with(entity));
+                    match entity {
+                        Some(entity) => rest = &tail[entity.len()..],
+                        None => return false,
+                    }
+                }
+                _ => return false,
+            }
+        }
+        open.is_empty()
+    }
+
+    #[test]
+    fn test_escape_hostile_names() {
+        for name in HOSTILE_NAMES {
+            let html = format!("<b>{}</b> has landed", escape(name));
+            assert!(is_valid_html(&html), "{}", html);
+        }
+        assert_eq!(escape("<b>Free</b> & co"), "&lt;b&gt;Free&lt;/b&gt; &amp; co");
+        // Markdown characters mean nothing in HTML and stay as they are
+        assert_eq!(escape("PEPE_2_*MOON*"), "PEPE_2_*MOON*");
+        assert!(!is_valid_html("<b>unclosed"));
+        assert!(!is_valid_html("<b>Tom & Jerry</b>"));
+    }
+
+    #[test]
+    fn test_chart_caption_with_hostile_names() {
+        let chart = PriceChart {
+            png: Vec::new(),
+            change_pct: 5f64,
+            span_secs: 3600,
+        };
+        for name in HOSTILE_NAMES {
+            let caption = chart.caption(&escape(name));
+            assert!(is_valid_html(&caption), "{}", caption);
+        }
+    }
+}
@@ -1,20 +1,115 @@
+use async_trait::async_trait;
+use futures::future::join_all;
 use kanshi::dna::EventData;
-use reqwest::{Client, Error};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, Error, RequestBuilder, Response, StatusCode};
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::format;
+use std::fmt::Write;
 use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 use tokio::sync::RwLock;
 use rust_decimal::prelude::*;
 
-use crate::utils::event_parser::CreationEvent;
-use crate::utils::info_aggregator::{aggregate_info, get_account_holding_info, get_account_holdings};
-use crate::utils::types::common::MemecoinInfo;
+use crate::constant::network::NETWORK;
+use crate::utils::address::{addresses_eq, canonicalize_address, to_canonical_hex};
+use crate::utils::cache::{cache_stats, flush_caches};
+use crate::utils::call::is_memecoin;
+use crate::utils::chart::{render_position, ChartError};
+#[cfg(feature = "chaos")]
+use crate::utils::chaos::{self, Fault, CHAOS};
+use crate::utils::cohort::describe_cohort;
+use crate::utils::correlation::find_clones;
+use crate::utils::risk::{holder_velocity, owner_renounced, score_holder_velocity, RiskConfig};
+use crate::utils::rpc::{rpc_pool, ProviderPool};
+use crate::utils::currency::{denomination, Currency, Denomination};
+use crate::utils::enrichment::pair_activity;
+use crate::utils::format::{format_large_number, format_number};
+use crate::utils::sanitize::{sanitize_display, HIDDEN_CHARS_WARNING};
+use crate::notifier::{Notifier, NotifyError};
+use crate::storage::report::WeeklyReport;
+use crate::storage::{
+    LaunchRecord, LaunchTag, Storage, ALERTS_DELIVERED, DELIVERY_FAILURES, EVENTS_PROCESSED, EVENT_ERRORS,
+};
+use digest::{DigestEntry, DigestQueue, DueDigest};
+use experiment::{AlertExperiment, Variant, VariantCopy};
+use groups::{
+    addressed_command, is_admin_status, is_present, is_shared_chat, pressed_by_owner, GROUP_ADMIN_COMMANDS,
+    PRIVATE_COMMANDS,
+};
+use inline::{article, link_buttons, InlineSearch, INLINE_CACHE_SECS, MAX_INLINE_RESULTS};
+use maintenance::{Maintenance, MaintenanceTransition, MaintenanceWindow};
+use messages::escape;
+use outbox::{retry_after, Outbox, OutboxConfig, Priority};
+use presets::{normalize_code, SettingsPreset};
+use preferences::{
+    parse_buy_amounts, DeliveryMode, UserPreferences, DEFAULT_BUY_AMOUNTS, HOLDER_TIERS, MAX_BUY_AMOUNT_USD,
+    MAX_BUY_BUTTONS,
+};
+use radar::{created_line, LaunchLinking, RadarAlerts};
+use search::{single_match, IndexedToken, TokenIndex, MAX_SEARCH_MATCHES};
+use templates::{CardLayout, CardTemplate, CardTemplates, ChatKind};
+use crate::trade::{TradeConfig, TradeOutcome, Trader};
+use crate::utils::event_parser::{CreationEvent, TransferEvent};
+use crate::utils::portfolio::{exposure, Exposure};
+use crate::utils::position::{position_history, TradeSide};
+use crate::utils::info_aggregator::{
+    aggregate_info, aggregate_info_with_refresh, get_account_holding_info, get_account_holdings,
+    DeadlineExceeded,
+};
+use crate::utils::types::common::{
+    LaunchAlert, LaunchContext, MemecoinInfo, PairActivity, RiskFlag, RiskReport, TokenCategoryResponse,
+    TokenHoldings, TokenPosition, UserTokenInfo,
+};
+use callback::CallbackAction;
+use circuit::{CircuitBreaker, CircuitConfig, CircuitTransition, Load};
+use commands::{menu, CommandScope};
+use cooldown::{CommandCooldowns, CooldownConfig, Throttle};
+use demo::{DemoMode, DEMO_COMMANDS};
 use crate::utils::types::ekubo::Memecoin;
-use crate::EventType;
+use crate::watcher::alerts::{AlertDirection, AlertMetric, AlertRequest, PriceAlert, PriceAlertConfig, PriceAlerts};
+use crate::watcher::papertrade::{has_entry_price, PaperTradeConfig, PaperTrading};
+use crate::watcher::price_history::{PriceHistory, PriceHistoryConfig};
+use crate::watcher::wallets::{WalletTracker, WalletTrackerConfig};
+use crate::watcher::watchlist::{
+    percent_change, Subscription, WatchChange, WatchError, Watchlist, WatchlistConfig,
+};
+
+mod callback;
+mod circuit;
+mod commands;
+mod cooldown;
+mod demo;
+mod digest;
+pub mod experiment;
+mod groups;
+mod inline;
+mod messages;
+mod outbox;
+mod preferences;
+mod presets;
+mod radar;
+mod search;
+pub mod maintenance;
+pub mod templates;
+
+// Bounds for the number of launches listed by /recent
+const RECENT_DEFAULT: u32 = 5;
+const RECENT_MAX: u32 = 20;
+// Positions listed by /peek, the rest are summarised
+const PEEK_MAX_POSITIONS: usize = 10;
+// Sends answered with a 429 are retried after Telegram's retry_after this many times
+const MAX_SEND_ATTEMPTS: u32 = 3;
+// Largest holders listed on the radar card, /holders lists every sampled one
+const RADAR_TOP_HOLDERS: usize = 3;
+// A wallet with this share of its memecoin value in one coin gets a warning on /peek
+const CONCENTRATED_PCT: f64 = 50f64;
+// A single wallet holding this share of the supply or more is marked as a whale
+const WHALE_HOLDER_PCT: f64 = 5f64;
 
 #[derive(Debug, Deserialize)]
 struct Update {
@@ -23,6 +118,15 @@ struct Update {
     message: Option<Message>,
     #[serde(default)]
     callback_query: Option<CallbackQuery>,
+    // `@bot <query>` typed in any chat, needs inline mode enabled with BotFather
+    #[serde(default)]
+    inline_query: Option<InlineQuery>,
+    // Commands posted in channels the bot administers
+    #[serde(default)]
+    channel_post: Option<Message>,
+    // The bot was added to, promoted in or removed from a chat
+    #[serde(default)]
+    my_chat_member: Option<ChatMemberUpdated>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,17 +135,42 @@ struct Message {
     #[serde(default)]
     from: Option<User>,
     chat: Chat,
+    // Set when an anonymous group admin or a channel posts
+    #[serde(default)]
+    sender_chat: Option<Chat>,
     #[serde(default)]
     text: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct ChatMemberUpdated {
+    chat: Chat,
+    old_chat_member: ChatMember,
+    new_chat_member: ChatMember,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMember {
+    status: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct CallbackQuery {
     id: String,
     from: User,
+    #[serde(default)]
+    message: Option<Message>,
     data: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct InlineQuery {
+    id: String,
+    from: User,
+    #[serde(default)]
+    query: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct User {
     id: i64,
@@ -65,6 +194,19 @@ pub struct TelegramConfig {
     token: String,
     dex_url: String,
     explorer_url: String,
+    public_url: Option<String>,
+    admin_chat_ids: Vec<i64>,
+    // Chats allowed to tag launches as gem, sus or test
+    curator_chat_ids: Vec<i64>,
+    demo_mode: bool,
+    // Commands keep working when launch alerts go to other destinations only
+    launch_alerts: bool,
+    // Stopped chats keep their settings this long before being purged, forever when unset
+    purge_inactive_after: Option<Duration>,
+    // Official channel that gets every launch, whatever the filters
+    announcements_chat_id: Option<i64>,
+    // Whether launch alerts reply to, edit or ignore the chat's earlier radar alert
+    launch_linking: LaunchLinking,
 }
 
 impl TelegramConfig {
@@ -72,8 +214,42 @@ impl TelegramConfig {
         Self {
             token: std::env::var("TELEGRAM_TOKEN").expect("TELEGRAM_TOKEN not found"),
             dex_url: std::env::var("DEX_URL").unwrap_or_else(|_| "https://app.avnu.fi".to_string()),
-            explorer_url: std::env::var("EXPLORER")
-                .unwrap_or_else(|_| "https://starkscan.co".to_string()),
+            explorer_url: NETWORK.explorer_url.clone(),
+            public_url: std::env::var("PUBLIC_URL")
+                .ok()
+                .map(|url| url.trim_end_matches('/').to_string()),
+            admin_chat_ids: std::env::var("ADMIN_CHAT_IDS")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|id| id.trim().parse::<i64>().ok())
+                .collect(),
+            curator_chat_ids: std::env::var("CURATOR_CHAT_IDS")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|id| id.trim().parse::<i64>().ok())
+                .collect(),
+            demo_mode: std::env::var("DEMO_MODE")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            // 0 keeps stopped chats forever
+            purge_inactive_after: Some(
+                std::env::var("TELEGRAM_PURGE_INACTIVE_DAYS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(90),
+            )
+            .filter(|days| *days > 0)
+            .map(|days| Duration::from_secs(days * 24 * 3600)),
+            launch_alerts: std::env::var("TELEGRAM_LAUNCH_ALERTS")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(true),
+            announcements_chat_id: std::env::var("TELEGRAM_ANNOUNCEMENTS_CHAT_ID")
+                .ok()
+                .and_then(|id| id.trim().parse::<i64>().ok()),
+            launch_linking: std::env::var("TELEGRAM_LAUNCH_LINKING")
+                .ok()
+                .and_then(|v| LaunchLinking::parse(&v))
+                .unwrap_or(LaunchLinking::Reply),
         }
     }
 }
@@ -82,497 +258,3815 @@ pub struct TelegramBot {
     config: TelegramConfig,
     client: Client,
     base_url: String,
+    // Set from getMe at startup, commands addressed to other bots in groups are ignored
+    username: OnceLock<String>,
+    outbox: Outbox,
     active_users: RwLock<HashMap<i64, bool>>,
+    // Unix time each chat sent /stop, mirrored in storage. Chats stopped long enough are purged
+    stopped_at: RwLock<HashMap<i64, u64>>,
+    // Chats stopped before a restart. Their filters, buttons and watchlist only lived in memory
+    settings_lost: RwLock<HashSet<i64>>,
+    preferences: RwLock<HashMap<i64, UserPreferences>>,
+    digests: DigestQueue,
+    experiment: Arc<AlertExperiment>,
+    // Card copy and layout per chat kind, for experiments beyond the shared A/B copy
+    templates: Arc<CardTemplates>,
+    maintenance: Arc<Maintenance>,
+    circuit: CircuitBreaker,
+    watchlist: Arc<Watchlist>,
+    price_alerts: Arc<PriceAlerts>,
+    paper_trading: Arc<PaperTrading>,
+    price_history: Arc<PriceHistory>,
+    wallets: Arc<WalletTracker>,
+    radar_alerts: RadarAlerts,
+    token_index: TokenIndex,
+    // Chats muted by an admin, loaded from storage at startup
+    muted: RwLock<HashSet<i64>>,
+    cooldowns: CommandCooldowns,
+    demo: Option<DemoMode>,
+    trader: Option<Trader>,
+    storage: Arc<Storage>,
 }
 
 impl TelegramBot {
-    pub fn new(config: TelegramConfig) -> Result<Self, Error> {
+    pub fn new(config: TelegramConfig, storage: Arc<Storage>) -> Result<Self, Error> {
         let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
 
         let base_url = format!("https://api.telegram.org/bot{}", config.token);
+        let demo = if config.demo_mode {
+            println!("Running in public demo mode");
+            Some(DemoMode::new())
+        } else {
+            None
+        };
+        let trader = match TradeConfig::new().map(Trader::new) {
+            Some(Ok(trader)) => {
+                println!("Trading enabled{} ✓", if trader.is_dry_run() { " (dry run)" } else { "" });
+                Some(trader)
+            }
+            Some(Err(e)) => {
+                eprintln!("Failed to open trade keystore ❗️ {}", e);
+                None
+            }
+            None => None,
+        };
 
         Ok(Self {
             config,
             client,
             base_url,
+            username: OnceLock::new(),
+            outbox: Outbox::new(OutboxConfig::new()),
             active_users: RwLock::new(HashMap::new()),
+            stopped_at: RwLock::new(HashMap::new()),
+            settings_lost: RwLock::new(HashSet::new()),
+            preferences: RwLock::new(HashMap::new()),
+            digests: DigestQueue::new(),
+            experiment: Arc::new(AlertExperiment::new()),
+            templates: Arc::new(CardTemplates::new(Arc::clone(&storage))),
+            maintenance: Arc::new(Maintenance::new()),
+            circuit: CircuitBreaker::new(CircuitConfig::new()),
+            watchlist: Arc::new(Watchlist::new(WatchlistConfig::new(), Arc::clone(&storage))),
+            price_alerts: Arc::new(PriceAlerts::new(PriceAlertConfig::new())),
+            paper_trading: Arc::new(PaperTrading::new(PaperTradeConfig::new(), Arc::clone(&storage))),
+            price_history: Arc::new(PriceHistory::new(PriceHistoryConfig::new(), Arc::clone(&storage))),
+            wallets: Arc::new(WalletTracker::new(WalletTrackerConfig::new(), Arc::clone(&storage))),
+            radar_alerts: RadarAlerts::new(),
+            token_index: TokenIndex::default(),
+            muted: RwLock::new(HashSet::new()),
+            cooldowns: CommandCooldowns::new(CooldownConfig::new()),
+            demo,
+            trader,
+            storage,
         })
     }
 
-    pub async fn initialize(&self) -> Result<(), Error> {
-        self.set_commands().await?;
-        Ok(())
+    // Data lookups go through the demo caches when running as a public demo,
+    // where force_refresh is ignored to keep upstream load bounded
+    async fn fetch_token_info(
+        &self,
+        token_address: &str,
+        force_refresh: bool,
+    ) -> Result<(MemecoinInfo, TokenCategoryResponse), anyhow::Error> {
+        let demo = match &self.demo {
+            Some(demo) => demo,
+            None => return aggregate_info_with_refresh(token_address, force_refresh).await,
+        };
+        if let Some(cached) = demo.token_info.get(token_address) {
+            return Ok(cached);
+        }
+        let info = aggregate_info(token_address).await?;
+        demo.token_info.insert(token_address, info.clone());
+        Ok(info)
     }
 
-    async fn set_commands(&self) -> Result<(), Error> {
-        let commands = json!({
-            "commands": [
-                {
-                    "command": "start",
-                    "description": "Start receiving token alerts"
-                },
-                {
-                    "command": "stop",
-                    "description": "Stop receiving token alerts"
-                },
-                {
-                    "command": "status",
-                    "description": "Check your current alert status"
-                },
-                {
-                    "command": "help",
-                    "description": "Show available commands"
-                },
-                {
-                    "command": "sniQ <address>",
-                    "description": "Get token info"
-                },
-                {
-                    "command": "peek <wallet>",
-                    "description": "Get wallet info"
-                },
-                {
-                    "command": "spot <wallet> <token_address>",
-                    "description": "Get wallet holdings for a particular token"
-                }
-            ]
-        });
-
-        let url = format!("{}/setMyCommands", self.base_url);
-        let response = self.client.post(&url).json(&commands).send().await?;
+    async fn fetch_holdings(&self, wallet_address: &str) -> Result<TokenHoldings, anyhow::Error> {
+        let demo = match &self.demo {
+            Some(demo) => demo,
+            None => return get_account_holdings(wallet_address).await,
+        };
+        if let Some(cached) = demo.holdings.get(wallet_address) {
+            return Ok(cached);
+        }
+        let holdings = get_account_holdings(wallet_address).await?;
+        demo.holdings.insert(wallet_address, holdings.clone());
+        Ok(holdings)
+    }
 
-        if !response.status().is_success() {
-            eprintln!("Failed to set commands: {:?}", response.text().await?);
+    async fn fetch_holding_info(
+        &self,
+        wallet_address: &str,
+        token_address: &str,
+    ) -> Result<UserTokenInfo, anyhow::Error> {
+        let demo = match &self.demo {
+            Some(demo) => demo,
+            None => return get_account_holding_info(wallet_address, token_address).await,
+        };
+        let key = format!("{}:{}", wallet_address, token_address);
+        if let Some(cached) = demo.spots.get(&key) {
+            return Ok(cached);
         }
+        let info = get_account_holding_info(wallet_address, token_address).await?;
+        demo.spots.insert(&key, info.clone());
+        Ok(info)
+    }
 
-        Ok(())
+    pub fn experiment(&self) -> Arc<AlertExperiment> {
+        Arc::clone(&self.experiment)
     }
-    
-    fn calculate_team_allocation(&self, total_supply: String, total_team_allocation: String)-> std::string::String {
-        let parsed_total_supply = self.format_large_number(&total_supply).unwrap().parse::<f64>().unwrap();
-        let parsed_team_allocation = self.format_large_number(&total_team_allocation).unwrap().parse::<f64>().unwrap();
 
-        let percentage_team_allocation = (parsed_team_allocation * 100.0) / parsed_total_supply;
+    pub fn card_templates(&self) -> Arc<CardTemplates> {
+        Arc::clone(&self.templates)
+    }
 
-        format!("{:.2}", percentage_team_allocation)
+    pub fn maintenance(&self) -> Arc<Maintenance> {
+        Arc::clone(&self.maintenance)
     }
 
-    pub async fn broadcast_event(&self, event_data: MemecoinInfo) -> Result<(), Error> {
-        let active_users = self.active_users.read().await;
+    pub fn watchlist(&self) -> Arc<Watchlist> {
+        Arc::clone(&self.watchlist)
+    }
 
-        let message = format!(
-            "🚨 ====== *FRESH LAUNCH ALERT* ====== 🚨\n\n\
-                    *{}* ({}) has landed on Starknet!\n\n\
-                    *Address:* {}\n\
-                    *Starting MCAP:* ${}\n\
-                    *Supply:* {}\n\
-                    *Liquidity:* ${}\n\
-                    *Team:* {}%\n\
-                    ⚡️ *GET IN NOW*\n\n\
-                    #Starknet #Memecoin #{}",
-                    event_data.name,
-                    event_data.symbol,
-                    event_data.address,
-            self.format_price(event_data.market_cap),
-            self.format_number(&self.format_large_number(&event_data.total_supply).unwrap()).unwrap(),
-            format!("{:.2}", event_data.usd_dex_liquidity.parse::<f64>().unwrap()),
-            self.format_percentage(self.calculate_team_allocation(event_data.total_supply, event_data.team_allocation)),
-            event_data.symbol
-        );
+    pub fn price_alerts(&self) -> Arc<PriceAlerts> {
+        Arc::clone(&self.price_alerts)
+    }
 
-        let keyboard = self.create_launch_keyboard(&event_data.address, &event_data.symbol);
+    pub fn paper_trading(&self) -> Arc<PaperTrading> {
+        Arc::clone(&self.paper_trading)
+    }
 
-        for (&chat_id, &active) in active_users.iter() {
-            if active {
-                if let Err(e) = self
-                    .send_message_with_markup(chat_id, &message, keyboard.clone(), None)
-                    .await
-                {
-                    eprintln!("Failed to broadcast event to {}: {:?}", chat_id, e);
+    pub fn price_history(&self) -> Arc<PriceHistory> {
+        Arc::clone(&self.price_history)
+    }
+
+    pub fn wallet_tracker(&self) -> Arc<WalletTracker> {
+        Arc::clone(&self.wallets)
+    }
+
+    pub fn launch_alerts_enabled(&self) -> bool {
+        self.config.launch_alerts
+    }
+
+    // Optional bot features this deployment runs with
+    pub fn features(&self) -> Vec<&'static str> {
+        let mut features = Vec::new();
+        if self.config.launch_alerts {
+            features.push("launch_alerts");
+        }
+        if self.demo.is_some() {
+            features.push("demo_mode");
+        }
+        match &self.trader {
+            Some(trader) if trader.is_dry_run() => features.push("trading_dry_run"),
+            Some(_) => features.push("trading"),
+            None => {}
+        }
+        features
+    }
+
+    // Events waiting for a worker, reported by the consumer for the load breaker
+    pub fn record_queue_depth(&self, depth: usize) {
+        self.circuit.set_queue_depth(depth);
+    }
+
+    // Digests, weekly reports and milestone alerts wait while the pipeline is overloaded
+    pub fn notifications_paused(&self) -> bool {
+        self.circuit.is_open()
+    }
+
+    fn is_admin(&self, chat_id: i64) -> bool {
+        self.config.admin_chat_ids.contains(&chat_id)
+    }
+
+    // Admins can't be muted, so they can't lock themselves out
+    async fn is_muted(&self, chat_id: i64) -> bool {
+        !self.is_admin(chat_id) && self.muted.read().await.contains(&chat_id)
+    }
+
+    fn is_curator(&self, chat_id: i64) -> bool {
+        self.config.curator_chat_ids.contains(&chat_id)
+    }
+
+    pub async fn initialize(&self) -> Result<(), Error> {
+        self.set_commands().await?;
+        match self.get_me().await {
+            Ok(Some(username)) => {
+                println!("Telegram bot @{} ✓", username);
+                let _ = self.username.set(username);
+            }
+            Ok(None) => eprintln!("Failed to read the bot username ❗️"),
+            Err(e) => eprintln!("Failed to read the bot username ❗️ {}", e),
+        }
+        if let Some(channel) = self.config.announcements_chat_id {
+            println!("Announcements channel {} gets every launch", channel);
+        }
+        match self.wallets.load().await {
+            Ok(count) => println!("Following {} wallet(s) ✓", count),
+            Err(e) => eprintln!("Failed to load followed wallets ❗️ {}", e),
+        }
+        match self.storage.muted_chats().await {
+            Ok(chat_ids) => *self.muted.write().await = chat_ids.into_iter().collect(),
+            Err(e) => eprintln!("Failed to load muted chats ❗️ {}", e),
+        }
+        match self.storage.stopped_chats().await {
+            Ok(stopped) => {
+                // Stopped chats are known, /start welcomes them back but their settings are gone
+                let mut active_users = self.active_users.write().await;
+                let mut settings_lost = self.settings_lost.write().await;
+                for (chat_id, _) in &stopped {
+                    active_users.entry(*chat_id).or_insert(false);
+                    settings_lost.insert(*chat_id);
                 }
+                *self.stopped_at.write().await = stopped.into_iter().collect();
+            }
+            Err(e) => eprintln!("Failed to load stopped chats ❗️ {}", e),
+        }
+        match self.templates.load().await {
+            Ok(0) => {}
+            Ok(count) => println!("{} card template override(s) ✓", count),
+            Err(e) => eprintln!("Failed to load card templates ❗️ {}", e),
+        }
+        match self.storage.launch_names().await {
+            Ok(launches) => {
+                let tokens = launches
+                    .into_iter()
+                    .map(|(address, symbol, name, launched_at)| IndexedToken {
+                        address,
+                        symbol,
+                        name,
+                        launched_at,
+                    })
+                    .collect();
+                self.token_index.replace(tokens).await;
+                println!("{} launch(es) searchable by name ✓", self.token_index.count().await);
+            }
+            Err(e) => eprintln!("Failed to load the token search index ❗️ {}", e),
+        }
+        Ok(())
+    }
+
+    // Makes a processed launch searchable with /sniQ
+    pub async fn index_launch(&self, info: &MemecoinInfo, launched_at: u64) {
+        self.token_index
+            .insert(IndexedToken {
+                address: info.address.clone(),
+                symbol: info.symbol.clone(),
+                name: info.name.clone(),
+                launched_at,
+            })
+            .await;
+    }
+
+    // Registers the command menu of each scope, group members only see what works in groups
+    async fn set_commands(&self) -> Result<(), Error> {
+        let url = format!("{}/setMyCommands", self.base_url);
+        for scope in CommandScope::ALL {
+            let commands = json!({
+                "commands": menu(scope, self.trader.is_some(), self.demo.is_some()),
+                "scope": scope.as_json(),
+            });
+            let response = self.client.post(&url).json(&commands).send().await?;
+
+            if !response.status().is_success() {
+                eprintln!("Failed to set {:?} commands: {:?}", scope, response.text().await?);
             }
         }
 
         Ok(())
     }
 
-    fn create_launch_keyboard(
+    fn generate_broadcast_message(
         &self,
-        contract_address: &str,
-        token_symbol: &str,
-    ) -> serde_json::Value {
-        json!({
-            "inline_keyboard": [
-                [
-                    {
-                        "text": "🚀 Buy $10",
-                        "url": format!("{}?token={}&amount=10&symbol={}",
-                            self.config.dex_url, contract_address, token_symbol)
-                    },
-                    {
-                        "text": "🚀 Buy $50",
-                        "url": format!("{}?token={}&amount=50&symbol={}",
-                            self.config.dex_url, contract_address, token_symbol)
+        event_data: &MemecoinInfo,
+        // Launch context already worded for the recipient, see format_launch_context
+        context: &[String],
+        copy: &VariantCopy,
+        layout: CardLayout,
+        denomination: &Denomination,
+        delayed: bool,
+    ) -> String {
+        // Both variants and every chat share the formatted figures, the card is written into one buffer
+        let figures = event_data.formatted();
+        let mut message = String::with_capacity(1024);
+        let _ = write!(
+            message,
+            "{}{}\n\n\
+                    <b>{}</b> ({}) has landed on Starknet!\n\n\
+                    <b>Address:</b> {}\n\
+                    <b>Starting MCAP:</b> {}\n",
+            copy.headline,
+            if delayed { " (delayed)" } else { "" },
+            escape(&figures.name),
+            escape(&figures.symbol),
+            event_data.address,
+            match event_data.market_cap_change_pct() {
+                Some(_) => denomination.format(&event_data.launch_market_cap, &figures.launch_market_cap),
+                None => denomination.format(&event_data.market_cap, &figures.market_cap),
+            },
+        );
+        // Alerts sent a while after the launch block show how far the price already moved
+        if let Some(change) = event_data.market_cap_change_pct() {
+            let _ = writeln!(
+                message,
+                "<b>MCAP now:</b> {} ({:+.1}%)",
+                denomination.format(&event_data.market_cap, &figures.market_cap),
+                change
+            );
+        }
+        match layout {
+            CardLayout::Full => {
+                let _ = write!(
+                    message,
+                    "<b>Supply:</b> {}\n\
+                    <b>Liquidity:</b> {} ({} token / {} quote)\n\
+                    <b>Pool:</b> {}\n\
+                    <b>Team:</b> {}\n",
+                    figures.supply,
+                    denomination.format(&event_data.usd_dex_liquidity, &figures.liquidity),
+                    denomination.format(&event_data.usd_token_liquidity, &figures.token_liquidity),
+                    denomination.format(&event_data.usd_quote_liquidity, &figures.quote_liquidity),
+                    self.format_pool(event_data),
+                    match figures.team_allocation_pct {
+                        Some(pct) => format!("{:.1}%", pct),
+                        None => "Unknown".to_string(),
                     },
-                    {
-                        "text": "🚀 Buy $100",
-                        "url": format!("{}?token={}&amount=100&symbol={}",
-                            self.config.dex_url, contract_address, token_symbol)
-                    }
-                ],
-                [
-                    {
-                        "text": "💰 Custom Amount",
-                        "url": format!("{}?token={}",
-                            self.config.dex_url, contract_address)
-                    }
-                ]
-            ]
-        })
+                );
+            }
+            CardLayout::Compact => {
+                let _ = writeln!(
+                    message,
+                    "<b>Liquidity:</b> {}",
+                    denomination.format(&event_data.usd_dex_liquidity, &figures.liquidity)
+                );
+                // The pool line is left out, but an unlisted quote token is worth the space
+                if !event_data.quote_token_safe && !event_data.quote_token.is_empty() {
+                    let _ = writeln!(message, "<b>Quote:</b> {}", self.format_quote_token(event_data));
+                }
+            }
+        }
+        // Owner and risk flags are part of every layout
+        let _ = writeln!(message, "{}", self.format_owner(&event_data.owner));
+        message.push_str(&self.format_risk(event_data.risk.as_ref()));
+        if figures.hidden_chars {
+            let _ = write!(message, "\n<b>{}</b>", HIDDEN_CHARS_WARNING);
+        }
+        message.push('\n');
+        if layout == CardLayout::Full && !context.is_empty() {
+            for item in context {
+                message.push('\n');
+                message.push_str(item);
+            }
+            message.push('\n');
+        }
+        let _ = write!(
+            message,
+            "{}\n\n#Starknet #Memecoin #{}",
+            copy.call_to_action,
+            escape(&figures.symbol)
+        );
+        message
     }
 
-    fn format_number(&self, num_str: &str) -> Result<String, &'static str> {
-        // Parse the string to f64
-        let num = match num_str.parse::<f64>() {
-            Ok(n) => n,
-            Err(_) => return Err("Invalid number format"),
-        };
-    
-        // Define the thresholds and their corresponding suffixes
-        let billion = 1_000_000_000.0;
-        let million = 1_000_000.0;
-        let thousand = 1_000.0;
-    
-        let (value, suffix) = if num >= billion {
-            (num / billion, "B")
-        } else if num >= million {
-            (num / million, "M")
-        } else if num >= thousand {
-            (num / thousand, "K")
-        } else {
-            (num, "")
-        };
-    
-        // Format with up to 2 decimal places, removing trailing zeros
-        let formatted = format!("{:.2}", value)
-            .trim_end_matches('0')
-            .trim_end_matches('.')
-            .to_string();
-    
-        Ok(format!("{}{}", formatted, suffix))
-    }
-
-
-    fn format_large_number(&self, input: &str) -> Result<String, &'static str> {
-        // Validate input is numeric
-    if !input.chars().all(|c| c.is_digit(10)) {
-        return Err("Invalid input: must contain only digits");
-    }
-
-    let input_len = input.len();
-    
-    // If input is less than 18 digits, we need to add decimal places
-    if input_len < 18 {
-        let zeros_needed = 18 - input_len;
-        let mut result = "0.".to_string();
-        // Add necessary leading zeros
-        for _ in 0..zeros_needed {
-            result.push('0');
-        }
-        result.push_str(input.trim_start_matches('0'));
-        if result == "0." {
-            return Ok("0".to_string());
-        }
-        return Ok(result.trim_end_matches('0').trim_end_matches('.').to_string());
-    }
-    
-    // If input is exactly 18 digits, result is 1
-    if input_len == 18 {
-        return Ok("1".to_string());
-    }
-    
-    // If input is more than 18 digits, we need to place a decimal point
-    let decimal_position = input_len - 18;
-    let mut result = input[0..decimal_position].to_string();
-    let fraction = &input[decimal_position..];
-    
-    if fraction != "000000000000000000" {
-        result.push('.');
-        result.push_str(fraction.trim_end_matches('0'));
-    }
-    
-    // Remove leading zeros and handle special case
-    result = result.trim_start_matches('0').to_string();
-    if result.is_empty() || result.starts_with('.') {
-        result = format!("0{}", result);
-    }
-    
-    // Remove trailing decimal if it exists
-    if result.ends_with('.') {
-        result.pop();
-    }
-    
-    Ok(result)
+    fn format_risk(&self, risk: Option<&RiskReport>) -> String {
+        match risk {
+            Some(risk) => {
+                let mut lines = vec![format!("<b>Risk:</b> {} ({}/100)", risk.level().label(), risk.score)];
+                // The owner line above already tells whether ownership was renounced
+                lines.extend(
+                    risk.flags
+                        .iter()
+                        .filter(|flag| !matches!(flag, RiskFlag::OwnerNotRenounced))
+                        .map(|flag| format!("⚠️ {}", flag.describe())),
+                );
+                lines.join("\n")
+            }
+            None => "<b>Risk:</b> Not assessed".to_string(),
+        }
     }
 
-    // Helper functions for formatting
-    fn format_price(&self, price: String) -> String {
-        format!("{:.2}", price)
+    fn format_owner(&self, owner: &str) -> String {
+        if canonicalize_address(owner).is_none() {
+            "<b>Owner:</b> Unknown".to_string()
+        } else if owner_renounced(owner) {
+            "<b>Owner:</b> renounced ✅".to_string()
+        } else {
+            format!("<b>Owner:</b> active ⚠️ ({})", self.format_short_address(owner))
+        }
     }
 
-    fn format_percentage(&self, value_str: String) -> String {
-        // Try to parse the string as f64
-        match value_str.parse::<f64>() {
-            Ok(value) => {
-                format!("{:.1}", value)
+    fn format_launch_context(&self, context: &LaunchContext, on_radar: bool) -> String {
+        match context {
+            LaunchContext::CloneOf {
+                symbol,
+                launched_at,
+                price_change_pct,
+                same_deployer,
+                ..
+            } => {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default();
+                let performance = price_change_pct
+                    .map(|change| format!(" (now {:+.0}%)", change))
+                    .unwrap_or_default();
+                format!(
+                    "🧬 {} <b>${}</b> launched {}{}",
+                    if *same_deployer { "Same deployer as" } else { "Looks like a clone of" },
+                    escape(symbol),
+                    format_age(now.saturating_sub(*launched_at)),
+                    performance
+                )
             }
-            Err(_) => {
-                // If parsing fails, return the original string
-                // You might want to log this error in a production environment
-                eprintln!("Failed to parse percentage string: {}", value_str);
-                value_str
+            LaunchContext::PendingBuys { count } => {
+                format!("⏳ <b>{}</b> buy{} pending", count, if *count == 1 { "" } else { "s" })
             }
+            LaunchContext::Created { created_at, same_block } => {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default();
+                created_line(*created_at, *same_block, on_radar, now)
+            }
+            LaunchContext::Cohort {
+                market_cap_pct,
+                liquidity_pct,
+                ..
+            } => format!("📊 {}", describe_cohort(*market_cap_pct, *liquidity_pct)),
         }
     }
 
-    fn format_short_address(&self, address: &str) -> String {
-        if address.len() > 8 {
-            format!("{}...{}", &address[..6], &address[address.len() - 4..])
+    // Tailors the shared launch keyboard to a chat's buy amounts and curator role
+    fn chat_keyboard(
+        &self,
+        chat_id: i64,
+        shared: &serde_json::Value,
+        event_data: &MemecoinInfo,
+        tracked_variant: Option<Variant>,
+        preferences: Option<&UserPreferences>,
+    ) -> serde_json::Value {
+        let keyboard = match preferences.and_then(|preferences| preferences.buy_amounts.as_deref()) {
+            Some(amounts) => {
+                self.create_launch_keyboard(&event_data.address, &event_data.symbol, tracked_variant, amounts)
+            }
+            None => shared.clone(),
+        };
+        if self.is_curator(chat_id) {
+            self.with_tag_buttons(keyboard, &event_data.address)
         } else {
-            address.to_string()
+            keyboard
         }
     }
 
-    pub async fn handle_updates(&self) -> Result<(), Error> {
-        let mut last_update_id = 0;
+    // Renders the launch card a chat would get for a token, sent to that chat only
+    async fn send_launch_preview(&self, chat_id: i64, alert: &LaunchAlert) -> Result<(), Error> {
+        let experiment_enabled = self.experiment.is_enabled().await;
+        let variant = self.experiment.variant_of(chat_id).await;
+        let kind = self.templates.kind_of(chat_id).await;
+        let template = self.templates.template(kind, variant).await;
+        let copy = template.apply(self.experiment.copy(variant).await);
+        let tracked_variant = if experiment_enabled { Some(variant) } else { None };
+        let denomination = self.chat_denomination(chat_id).await;
+        let on_radar = self
+            .preferences
+            .read()
+            .await
+            .get(&chat_id)
+            .is_some_and(|preferences| preferences.prelaunch_radar);
+        let context: Vec<String> = alert
+            .context
+            .iter()
+            .map(|item| self.format_launch_context(item, on_radar))
+            .collect();
+        let message = self.generate_broadcast_message(
+            &alert.info,
+            &context,
+            &copy,
+            template.layout,
+            &denomination,
+            false,
+        );
+        let shared = self.create_launch_keyboard(
+            &alert.info.address,
+            &alert.info.symbol,
+            tracked_variant,
+            &DEFAULT_BUY_AMOUNTS,
+        );
+        let (keyboard, reach, subscribers) = {
+            let preferences = self.preferences.read().await;
+            let keyboard =
+                self.chat_keyboard(chat_id, &shared, &alert.info, tracked_variant, preferences.get(&chat_id));
+            let liquidity_usd = alert.info.usd_dex_liquidity.parse::<f64>().unwrap_or_default();
+            let team_allocation_pct = alert.info.formatted().team_allocation_pct.unwrap_or_default();
+            let active_users = self.active_users.read().await;
+            let subscribers: Vec<i64> = active_users
+                .iter()
+                .filter(|(_, active)| **active)
+                .map(|(chat_id, _)| *chat_id)
+                .collect();
+            let reach = subscribers
+                .iter()
+                .filter(|chat_id| {
+                    preferences.get(chat_id).is_none_or(|preferences| {
+                        preferences.matches(
+                            liquidity_usd,
+                            team_allocation_pct,
+                            alert.holders.effective_holder_count,
+                        )
+                    })
+                })
+                .count();
+            (keyboard, reach, subscribers.len())
+        };
 
-        loop {
-            match self.get_updates(last_update_id + 1).await {
-                Ok(updates) => {
-                    for update in updates {
-                        if let Some(message) = update.message {
-                            if let Some(text) = message.text {
-                                self.handle_command(&text, message.chat.id).await?;
-                            }
-                        }
-                        last_update_id = update.update_id;
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error getting updates: {:?}", e);
-                    tokio::time::sleep(Duration::from_secs(5)).await;
-                }
-            }
+        self.send_message(
+            chat_id,
+            &format!(
+                "👁 Preview of variant {} for {} chats, would reach {} of {} subscribers. Not broadcast.",
+                variant.as_str(),
+                kind.as_str(),
+                reach,
+                subscribers
+            ),
+            None,
+        )
+        .await?;
+        self.send_message_with_markup(chat_id, &message, keyboard, None).await
+    }
 
-            tokio::time::sleep(Duration::from_secs(1)).await;
+    pub async fn broadcast_event(&self, alert: LaunchAlert) -> Result<(), Error> {
+        // Launches seen during maintenance are held back and sent once it ends
+        match self.maintenance.try_buffer(alert).await {
+            Some(alert) => self.send_launch_alert(alert, false).await,
+            None => Ok(()),
         }
     }
 
-    async fn handle_command(&self, command: &str, chat_id: i64) -> Result<(), Error> {
-        let parts: Vec<&str> = command.split_whitespace().collect();
-        
-        match parts.get(0).map(|s| *s) {
-            Some("/spot") => {
-                match (parts.get(1), parts.get(2)) {
-                    (Some(wallet_addr), Some(token_addr)) => {
-                        match get_account_holding_info(wallet_addr, token_addr).await {
-                            Ok(info) => {
-                                let message = format!(
-                                    "📊 ====== *TOKEN SPOT* ====== 📊\n\n\
-                                    *Wallet:* {}\n\
-                                    *Token:* ${}\n\n\
-                                    *POSITION*\n\
-                                    *Balance:* {}\n\
-                                    *Worth:* ${}\n\n\
-                                    *ACTIONS*\n\
-                                    ⚡️ *Trade Now:* {}",
-                                    self.format_short_address(wallet_addr),
-                                    info.coin_info.symbol,
-                                    self.format_large_number(&info.account_balance).unwrap(),
-                                    info.usd_value,
-                                    self.config.dex_url,
-                                    // token_addr
-                                );
+    async fn send_launch_alert(&self, alert: LaunchAlert, delayed: bool) -> Result<(), Error> {
+        let LaunchAlert {
+            info: event_data,
+            holders,
+            context,
+        } = alert;
+        // Snapshot of the recipients, sending is paced so the locks aren't held meanwhile
+        let mut recipients: Vec<(i64, Option<UserPreferences>)> = {
+            let active_users = self.active_users.read().await;
+            let preferences = self.preferences.read().await;
+            active_users
+                .iter()
+                .filter(|(_, active)| **active)
+                .map(|(chat_id, _)| (*chat_id, preferences.get(chat_id).cloned()))
+                .collect()
+        };
+        // The announcements channel gets every launch as it happens, filters and digests aside
+        if let Some(channel) = self.config.announcements_chat_id {
+            recipients.retain(|(chat_id, _)| *chat_id != channel);
+            recipients.push((channel, None));
+        }
+        let liquidity_usd = event_data.usd_dex_liquidity.parse::<f64>().unwrap_or_default();
+        let team_allocation_pct = event_data.formatted().team_allocation_pct.unwrap_or_default();
+        let experiment_enabled = self.experiment.is_enabled().await;
+        let digest_entry = DigestEntry {
+            symbol: event_data.symbol.clone(),
+            address: event_data.address.clone(),
+            market_cap: event_data.market_cap.clone(),
+            liquidity_usd: event_data.usd_dex_liquidity.clone(),
+            holders: holders.effective_holder_count,
+        };
 
-                                self.send_message(chat_id, &message, None).await?;
-                            }
-                            Err(e) => {
-                                let error_message = format!(
-                                    "❌ Error fetching token info: {}",
-                                    if e.to_string().contains("parse") {
-                                        "Invalid token data format"
-                                    } else if e.to_string().contains("aggregate_info") {
-                                        "Failed to fetch token information"
-                                    } else if e.to_string().contains("get_balance") {
-                                        "Failed to fetch account balance"
-                                    } else {
-                                        "Unexpected error occurred"
-                                    }
-                                );
-                                self.send_message(chat_id, &error_message, None).await?;
+        // Render each variant once per chat kind and currency, subscribers only differ by assignment,
+        // kind, currency and buy amounts. Cards are only rendered once a chat wants them
+        let mut keyboards = Vec::new();
+        for variant in [Variant::A, Variant::B] {
+            let tracked_variant = if experiment_enabled { Some(variant) } else { None };
+            let keyboard = self.create_launch_keyboard(
+                &event_data.address,
+                &event_data.symbol,
+                tracked_variant,
+                &DEFAULT_BUY_AMOUNTS,
+            );
+            keyboards.push((variant, keyboard));
+        }
+        let mut cards: HashMap<(Variant, ChatKind, Currency, bool), String> = HashMap::new();
+        let mut denominations: HashMap<Currency, Denomination> = HashMap::new();
+        // Rendered once and uploaded once, later chats get the photo by its file id
+        let chart = self.price_history.chart(&event_data.address).await;
+        let chart_caption = chart
+            .as_ref()
+            .map(|chart| chart.caption(&escape(&event_data.formatted().symbol)));
+        let mut chart_file_id = None;
+
+        for (chat_id, chat_preferences) in &recipients {
+            let (chat_id, chat_preferences) = (*chat_id, chat_preferences.as_ref());
+            let wanted = chat_preferences.is_none_or(|preferences| {
+                preferences.matches(liquidity_usd, team_allocation_pct, holders.effective_holder_count)
+            });
+            if wanted {
+                // Digest chats get the launch in their next summary instead
+                if let Some(DeliveryMode::Digest(_)) = chat_preferences.map(|preferences| preferences.delivery) {
+                    self.digests.push(chat_id, digest_entry.clone()).await;
+                    continue;
+                }
+                let variant = self.experiment.variant_of(chat_id).await;
+                let currency = chat_preferences.map_or(Currency::Usd, |preferences| preferences.currency);
+                let denomination = match denominations.get(&currency) {
+                    Some(denomination) => *denomination,
+                    None => {
+                        let denomination = denomination(currency).await;
+                        denominations.insert(currency, denomination);
+                        denomination
+                    }
+                };
+                // A currency that couldn't be priced falls back to the USD card
+                let kind = self.templates.kind_of(chat_id).await;
+                let on_radar = chat_preferences.is_some_and(|preferences| preferences.prelaunch_radar);
+                let card = (variant, kind, denomination.currency, on_radar);
+                if let std::collections::hash_map::Entry::Vacant(entry) = cards.entry(card) {
+                    let template = self.templates.template(kind, variant).await;
+                    let copy = template.apply(self.experiment.copy(variant).await);
+                    let context: Vec<String> = context
+                        .iter()
+                        .map(|item| self.format_launch_context(item, on_radar))
+                        .collect();
+                    let message = self.generate_broadcast_message(
+                        &event_data,
+                        &context,
+                        &copy,
+                        template.layout,
+                        &denomination,
+                        delayed,
+                    );
+                    entry.insert(message);
+                }
+                let message = &cards[&card];
+                let (_, keyboard) = keyboards
+                    .iter()
+                    .find(|(keyboard_variant, _)| *keyboard_variant == variant)
+                    .unwrap();
+                let tracked_variant = if experiment_enabled { Some(variant) } else { None };
+                let keyboard =
+                    self.chat_keyboard(chat_id, keyboard, &event_data, tracked_variant, chat_preferences);
+                // Chats that saw the token on the radar get the launch linked to that alert
+                let radar_alert = match self.config.launch_linking {
+                    LaunchLinking::Off => None,
+                    LaunchLinking::Reply | LaunchLinking::Edit => {
+                        self.radar_alerts.take(chat_id, &event_data.address).await
+                    }
+                };
+                let sent = match (self.config.launch_linking, radar_alert) {
+                    (LaunchLinking::Edit, Some(message_id)) => {
+                        match self.broadcast_edit(chat_id, message_id, message, &keyboard).await {
+                            // The radar alert may have been deleted since, the launch is sent as a reply
+                            Ok(false) => {
+                                self.broadcast_reply(chat_id, message, Some(keyboard), radar_alert).await
                             }
+                            edited => edited.map(|_| Some(message_id)),
                         }
                     }
-                    _ => {
-                        self.send_message(
-                            chat_id,
-                            "❌ Invalid command format.\nUsage: `/spot <wallet_address> <token_address>`",
-                            None,
-                        )
-                        .await?;
+                    _ => self.broadcast_reply(chat_id, message, Some(keyboard), radar_alert).await,
+                };
+                match sent {
+                    Ok(_) => self.experiment.record_impression(variant).await,
+                    Err(e) => {
+                        eprintln!("Failed to broadcast event to {}: {:?}", chat_id, e);
+                        continue;
                     }
                 }
-            }
-            Some("/start") => {
-                let mut active_users = self.active_users.write().await;
-                if active_users.insert(chat_id, true).is_none() {
-                    self.send_message(
-                        chat_id,
-                        "⚡️ ====== *WELCOME TO SNIQ BOT* ====== ⚡️\n\n\
-                                Catch the Meme. Beat the Market. 🎯🔥\n\n\
-                                🚀 *FEATURES:*\n\
-                                ✨ Instant Token Sniping – Know what’s hot in seconds.\n\
-                                🔍 Wallet Scanning – Fast, flawless, precise.\n\
-                                💸 One-Tap Trading – Access the market like a pro.\n\n\
-                                ⚡️ *GET STARTED:*\n\
-                                💥 */sniQ <address>* – Scan a token instantly!\n\
-                                👀 */peek <wallet>* – See your memecoin holdings.\n\
-                                🎯 */spot <wallet> <token>* – Track your position on any token.\n\n\
-                                💎 sniq.fun\n\
-                                Fast. Sharp. Ahead. — Sniping Memecoins Like a Pro. ⚡️"
-                                ,
-                        None,
-                    )
-                    .await?;
-                } else {
-                    self.send_message(chat_id, "✅ You are already receiving token alerts!", None)
-                        .await?;
+                if let (Some(chart), Some(caption)) = (&chart, &chart_caption) {
+                    let sent = self
+                        .send_chart(chat_id, &chart.png, caption, &mut chart_file_id, Priority::Broadcast)
+                        .await;
+                    if let Err(e) = sent {
+                        eprintln!("Failed to send chart to {}: {:?}", chat_id, e);
+                    }
                 }
             }
-            Some("/stop") => {
-                let mut active_users = self.active_users.write().await;
-                if active_users.remove(&chat_id).is_some() {
-                    self.send_message(
+        }
+
+        Ok(())
+    }
+
+    // Tells chats on the pre-launch radar about a token that was just created, before it has liquidity
+    pub async fn broadcast_creation(&self, creation: &CreationEvent) -> Result<(), Error> {
+        if self.maintenance.is_active().await {
+            return Ok(());
+        }
+        let token_address = to_canonical_hex(&creation.memecoin_address);
+        let name = sanitize_display(&creation.name);
+        let symbol = sanitize_display(&creation.symbol);
+        let supply = format_large_number(&creation.initial_supply)
+            .and_then(|supply| format_number(&supply))
+            .unwrap_or_else(|_| creation.initial_supply.clone());
+        let message = format!(
+            "🌱 ====== <b>PRE-LAUNCH RADAR</b> ====== 🌱\n\n\
+                    <b>{}</b> (${}) was just created, it has no liquidity yet.\n\n\
+                    <b>Address:</b> <code>{}</code>\n\
+                    <b>Deployer:</b> {}\n\
+                    <b>Supply:</b> {}\n\n\
+                    {}🔍 Research it before it launches, /sniQ works once liquidity is added.",
+            escape(&name.text),
+            escape(&symbol.text),
+            token_address,
+            self.format_short_address(&to_canonical_hex(&creation.owner)),
+            supply,
+            if name.had_hidden_chars || symbol.had_hidden_chars {
+                format!("<b>{}</b>\n\n", HIDDEN_CHARS_WARNING)
+            } else {
+                String::new()
+            },
+        );
+
+        let chat_ids: Vec<i64> = {
+            let active_users = self.active_users.read().await;
+            let preferences = self.preferences.read().await;
+            preferences
+                .iter()
+                .filter(|(chat_id, preferences)| {
+                    preferences.prelaunch_radar && active_users.get(chat_id).copied().unwrap_or(false)
+                })
+                .map(|(chat_id, _)| *chat_id)
+                .collect()
+        };
+        for chat_id in chat_ids {
+            match self.broadcast_reply(chat_id, &message, None, None).await {
+                Ok(Some(message_id)) => self.radar_alerts.record(chat_id, &token_address, message_id).await,
+                Ok(None) => {}
+                Err(e) => eprintln!("Failed to send pre-launch alert to {}: {:?}", chat_id, e),
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn broadcast_lock_owner_change(
+        &self,
+        symbol: &str,
+        token_address: &str,
+        previous_owner: &str,
+        new_owner: &str,
+    ) -> Result<(), Error> {
+        let message = format!(
+            "⚠️ ====== <b>LOCK OWNER CHANGED</b> ====== ⚠️\n\n\
+                    The locked liquidity position of <b>{}</b> changed hands.\n\n\
+                    <b>Address:</b> {}\n\
+                    <b>Previous owner:</b> {}\n\
+                    <b>New owner:</b> {}\n\n\
+                    🔍 Position transfers often precede liquidity games, trade carefully.",
+            escape(symbol),
+            token_address,
+            self.format_short_address(previous_owner),
+            self.format_short_address(new_owner),
+        );
+
+        self.broadcast_notice(&message).await
+    }
+
+    pub async fn broadcast_lock_expiring(
+        &self,
+        symbol: &str,
+        token_address: &str,
+        flag: &RiskFlag,
+    ) -> Result<(), Error> {
+        let message = format!(
+            "⏳ ====== <b>LOCK EXPIRING</b> ====== ⏳\n\n\
+                    The liquidity lock of <b>{}</b> runs out: {}.\n\n\
+                    <b>Address:</b> {}\n\n\
+                    🔍 Once unlocked the liquidity can be pulled, trade carefully.",
+            escape(symbol),
+            flag.describe().to_lowercase(),
+            token_address,
+        );
+
+        self.broadcast_notice(&message).await
+    }
+
+    pub async fn notify_watchers(
+        &self,
+        chat_ids: &[i64],
+        symbol: &str,
+        token_address: &str,
+        changes: &[WatchChange],
+    ) -> Result<(), Error> {
+        // Milestones are dropped under load, price and liquidity moves still matter to holders
+        let paused = self.notifications_paused();
+        let lines: Vec<String> = changes
+            .iter()
+            .filter(|change| !(paused && matches!(change, WatchChange::HolderMilestone { .. })))
+            .map(|change| match change {
+                WatchChange::Price { from, to } => format!(
+                    "{} <b>Price:</b> ${} → ${} ({:+.1}%)",
+                    if to > from { "📈" } else { "📉" },
+                    from,
+                    to,
+                    percent_change(*from, *to)
+                ),
+                WatchChange::Liquidity { from, to } => format!(
+                    "💧 <b>Liquidity:</b> ${} → ${} ({:+.1}%)",
+                    format_number(&from.to_string()).unwrap_or_default(),
+                    format_number(&to.to_string()).unwrap_or_default(),
+                    percent_change(*from, *to)
+                ),
+                WatchChange::Holders { from, to } => {
+                    format!("👥 <b>Holders:</b> {} → {}", from, to)
+                }
+                WatchChange::HolderMilestone { count } => {
+                    format!("🎉 <b>Milestone:</b> {}+ holders", count)
+                }
+                WatchChange::HolderDrop { from, to } => format!(
+                    "⚠️ <b>Holders leaving:</b> {} → {} within the hour ({:+.0}%)",
+                    from,
+                    to,
+                    percent_change(*from as f64, *to as f64)
+                ),
+                WatchChange::WhaleTrade {
+                    buy,
+                    account,
+                    amount,
+                    supply_pct,
+                    style,
+                } => format!(
+                    "🐋 <b>Whale {}:</b> {}{} {} {} tokens ({:.2}% of supply)",
+                    if *buy { "buy" } else { "sell" },
+                    self.format_short_address(account),
+                    style.map(|style| format!(" ({})", style.badge())).unwrap_or_default(),
+                    if *buy { "bought" } else { "sold" },
+                    format_number(&format_large_number(amount).unwrap_or_default())
+                        .unwrap_or_default(),
+                    supply_pct
+                ),
+                WatchChange::LiquidityPulled { from, to, drop_pct } => format!(
+                    "🚨 <b>Liquidity pulled:</b> launch position liquidity {} → {} (-{:.0}%)",
+                    format_number(from).unwrap_or_default(),
+                    format_number(to).unwrap_or_default(),
+                    drop_pct
+                ),
+                WatchChange::Owner { from, to } if addresses_eq(to, "0x0") => format!(
+                    "🔓 <b>Ownership renounced</b> by {}",
+                    self.format_short_address(from)
+                ),
+                WatchChange::Owner { from, to } => format!(
+                    "👑 <b>Ownership transferred:</b> {} → {}",
+                    self.format_short_address(from),
+                    self.format_short_address(to)
+                ),
+                WatchChange::LockOwner { from, to } => format!(
+                    "🚨 <b>Locked liquidity changed hands:</b> {} → {}",
+                    self.format_short_address(from),
+                    self.format_short_address(to)
+                ),
+            })
+            .collect();
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        let message = format!(
+            "👀 ====== <b>WATCHLIST ALERT</b> ====== 👀\n\n\
+            <b>Token:</b> ${}\n\
+            <b>Address:</b> {}\n\n\
+            {}",
+            escape(symbol),
+            token_address,
+            lines.join("\n")
+        );
+
+        for &chat_id in chat_ids {
+            if self.is_stopped(chat_id).await {
+                continue;
+            }
+            if let Err(e) = self.broadcast_message(chat_id, &message, None).await {
+                eprintln!("Failed to send watchlist alert to {}: {:?}", chat_id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Lets chats know a token was dropped from their watchlist after going quiet
+    pub async fn notify_watch_expired(
+        &self,
+        chat_ids: &[i64],
+        symbol: &str,
+        token_address: &str,
+        idle_secs: u64,
+    ) -> Result<(), Error> {
+        let message = format!(
+            "⏳ <b>Watch expired</b>\n\n\
+            <b>${}</b> saw no trades or liquidity for {}, so it was removed from your watchlist.\n\
+            <code>{}</code>\n\n\
+            Use /watch or /follow to track it again.",
+            escape(&sanitize_display(symbol).text),
+            if idle_secs >= 2 * 86_400 {
+                format!("{} days", idle_secs / 86_400)
+            } else {
+                format!("{} hours", (idle_secs / 3_600).max(1))
+            },
+            token_address
+        );
+        for &chat_id in chat_ids {
+            if self.is_stopped(chat_id).await {
+                continue;
+            }
+            if let Err(e) = self.broadcast_message(chat_id, &message, None).await {
+                eprintln!("Failed to send watch expiry to {}: {:?}", chat_id, e);
+            }
+        }
+        Ok(())
+    }
+
+    // Tells the chats following either side of a memecoin transfer, once per chat
+    pub async fn notify_wallet_transfer(&self, transfer: &TransferEvent, symbol: &str) -> Result<(), Error> {
+        if self.maintenance.is_active().await {
+            return Ok(());
+        }
+        let token_address = to_canonical_hex(&transfer.token_address);
+        let from = to_canonical_hex(&transfer.from);
+        let to = to_canonical_hex(&transfer.to);
+        let amount = format_large_number(&transfer.amount)
+            .and_then(|amount| format_number(&amount))
+            .unwrap_or_else(|_| transfer.amount.clone());
+        let symbol = sanitize_display(symbol).text;
+
+        let mut notified = HashSet::new();
+        for (wallet, counterpart, sent) in [(&from, &to, true), (&to, &from, false)] {
+            for chat_id in self.wallets.followers(wallet).await {
+                if !notified.insert(chat_id) || self.is_stopped(chat_id).await {
+                    continue;
+                }
+                let message = format!(
+                    "👛 ====== <b>WALLET ACTIVITY</b> ====== 👛\n\n\
+                    {} {} {} <b>${}</b> {} {}\n\n\
+                    <b>Token:</b> <code>{}</code>",
+                    self.format_short_address(wallet),
+                    if sent { "sent" } else { "received" },
+                    amount,
+                    escape(&symbol),
+                    if sent { "to" } else { "from" },
+                    self.format_short_address(counterpart),
+                    token_address,
+                );
+                if let Err(e) = self.broadcast_message(chat_id, &message, None).await {
+                    eprintln!("Failed to send wallet activity to {}: {:?}", chat_id, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn notify_price_alert(&self, alert: &PriceAlert, value: f64) -> Result<(), Error> {
+        let message = format!(
+            "⏰ ====== <b>PRICE ALERT</b> ====== ⏰\n\n\
+            <b>Token:</b> ${}\n\
+            <b>Address:</b> {}\n\n\
+            {} The {} is now {} your target: {} (target {})\n\n\
+            ℹ️ This alert has been removed, set a new one with /alert.",
+            escape(&alert.symbol),
+            alert.token_address,
+            match alert.direction {
+                AlertDirection::Above => "📈",
+                AlertDirection::Below => "📉",
+            },
+            alert.metric.label(),
+            alert.direction.label(),
+            self.format_alert_value(alert.metric, value),
+            self.format_alert_value(alert.metric, alert.target)
+        );
+        self.broadcast_message(alert.chat_id, &message, None).await
+    }
+
+    fn format_alert_value(&self, metric: AlertMetric, value: f64) -> String {
+        match metric {
+            AlertMetric::Price => format!("${}", value),
+            AlertMetric::MarketCap => {
+                format!("${}", format_number(&format!("{:.0}", value)).unwrap_or_default())
+            }
+        }
+    }
+
+    // Sends a plain text notice to every subscriber
+    async fn broadcast_notice(&self, message: &str) -> Result<(), Error> {
+        let chat_ids: Vec<i64> = {
+            let active_users = self.active_users.read().await;
+            active_users
+                .iter()
+                .filter(|(_, active)| **active)
+                .map(|(chat_id, _)| *chat_id)
+                .collect()
+        };
+
+        for chat_id in chat_ids {
+            if let Err(e) = self.broadcast_message(chat_id, message, None).await {
+                eprintln!("Failed to send notice to {}: {:?}", chat_id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Announces maintenance transitions and flushes launches buffered during the window
+    pub async fn run_maintenance(&self) {
+        loop {
+            match self.maintenance.poll().await {
+                Some(MaintenanceTransition::Started(window)) => {
+                    let message = format!(
+                        "🛠 Maintenance has started, alerts resume in about {} min.\n\
+                        Launches detected meanwhile will be sent afterwards.",
+                        window.ends_at.saturating_sub(window.starts_at).div_ceil(60)
+                    );
+                    if let Err(e) = self.broadcast_notice(&message).await {
+                        eprintln!("Failed to announce maintenance start: {:?}", e);
+                    }
+                }
+                Some(MaintenanceTransition::Finished(buffered)) => {
+                    self.finish_maintenance(buffered, "✅ Maintenance is over, alerts are back on.")
+                        .await;
+                }
+                None => {}
+            }
+
+            tokio::time::sleep(Duration::from_secs(15)).await;
+        }
+    }
+
+    // Checks pipeline load and pauses or resumes non-critical notifications, admins are told
+    // either way
+    pub async fn run_circuit_breaker(&self) {
+        loop {
+            tokio::time::sleep(self.circuit.config.check_interval).await;
+
+            let (rpc_calls, rpc_failures) = rpc_pool().map_or((0, 0), ProviderPool::take_call_stats);
+            let load = Load {
+                queue_depth: self.circuit.queue_depth(),
+                rpc_calls,
+                rpc_failures,
+            };
+            let message = match self.circuit.check(load).await {
+                Some(CircuitTransition::Tripped(reason)) => {
+                    println!("Load breaker tripped: {}", reason);
+                    format!(
+                        "⚡️ <b>Load breaker tripped:</b> {}.\n\
+                        Digests, weekly reports and milestone alerts are paused, launch alerts keep going.",
+                        reason
+                    )
+                }
+                Some(CircuitTransition::Resumed { paused_for }) => {
+                    println!("Load breaker resumed after {}s", paused_for.as_secs());
+                    format!(
+                        "✅ Load is back to normal after {} min, paused notifications resume.",
+                        paused_for.as_secs().div_ceil(60)
+                    )
+                }
+                None => continue,
+            };
+            self.notify_admins(&message).await;
+        }
+    }
+
+    pub async fn notify_admins(&self, message: &str) {
+        for &admin in &self.config.admin_chat_ids {
+            if let Err(e) = self.send_message(admin, message, None).await {
+                eprintln!("Failed to notify admin {}: {:?}", admin, e);
+            }
+        }
+    }
+
+    // Schedules a window and tells subscribers ahead of time
+    pub async fn schedule_maintenance(
+        &self,
+        start_in_min: u64,
+        duration_min: u64,
+    ) -> Result<MaintenanceWindow, Error> {
+        let window = self
+            .maintenance
+            .schedule(start_in_min * 60, duration_min * 60)
+            .await;
+        let message = format!(
+            "🛠 ====== <b>SCHEDULED MAINTENANCE</b> ====== 🛠\n\n\
+            The bot will be under maintenance in {} min for about {} min.\n\
+            Launch alerts detected during the window will be sent afterwards.",
+            start_in_min, duration_min
+        );
+        self.broadcast_notice(&message).await?;
+        Ok(window)
+    }
+
+    // Returns false when there was no window to cancel
+    pub async fn cancel_maintenance(&self) -> bool {
+        if self.maintenance.window().await.is_none() {
+            return false;
+        }
+        let buffered = self.maintenance.cancel().await;
+        self.finish_maintenance(buffered, "✅ Scheduled maintenance was cancelled.")
+            .await;
+        true
+    }
+
+    // Chats paused with /stop get no watchlist or price alerts either
+    pub async fn is_stopped(&self, chat_id: i64) -> bool {
+        self.stopped_at.read().await.contains_key(&chat_id)
+    }
+
+    // Forgets chats that stopped longer ago than the configured retention, settings included
+    pub async fn run_inactive_purge(&self) {
+        let retention = match self.config.purge_inactive_after {
+            Some(retention) => retention,
+            None => return,
+        };
+        loop {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+
+            let now = unix_now();
+            let expired: Vec<i64> = self
+                .stopped_at
+                .read()
+                .await
+                .iter()
+                .filter(|(_, stopped_at)| now.saturating_sub(**stopped_at) >= retention.as_secs())
+                .map(|(chat_id, _)| *chat_id)
+                .collect();
+            if expired.is_empty() {
+                continue;
+            }
+            for chat_id in &expired {
+                self.forget_chat(*chat_id).await;
+            }
+            println!("Purged {} long inactive chat(s)", expired.len());
+        }
+    }
+
+    // Pauses a chat's alerts, stored so the purge still happens after a restart
+    async fn stop_chat(&self, chat_id: i64) {
+        let stopped_at = unix_now();
+        self.stopped_at.write().await.insert(chat_id, stopped_at);
+        if let Err(e) = self.storage.save_stopped_chat(chat_id, stopped_at).await {
+            eprintln!("Failed to save stop of {}: {}", chat_id, e);
+        }
+    }
+
+    async fn resume_chat(&self, chat_id: i64) {
+        if self.stopped_at.write().await.remove(&chat_id).is_some() {
+            if let Err(e) = self.storage.remove_stopped_chat(chat_id).await {
+                eprintln!("Failed to clear stop of {}: {}", chat_id, e);
+            }
+        }
+    }
+
+    // Drops a chat's subscription and settings
+    async fn forget_chat(&self, chat_id: i64) {
+        self.resume_chat(chat_id).await;
+        self.settings_lost.write().await.remove(&chat_id);
+        self.active_users.write().await.remove(&chat_id);
+        self.preferences.write().await.remove(&chat_id);
+        self.watchlist.remove_chat(chat_id).await;
+        self.wallets.remove_chat(chat_id).await;
+        self.price_alerts.remove_chat(chat_id).await;
+        self.digests.remove_chat(chat_id).await;
+        self.templates.remove_chat(chat_id).await;
+    }
+
+    // Sends the summaries of digest chats whose window has elapsed
+    pub async fn run_digests(&self) {
+        loop {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            // Launches keep queueing while paused and go out in the next summary
+            if self.notifications_paused() {
+                continue;
+            }
+
+            let due = {
+                let preferences = self.preferences.read().await;
+                self.digests
+                    .take_due(|chat_id| match preferences.get(&chat_id).map(|preferences| preferences.delivery) {
+                        Some(DeliveryMode::Digest(period)) => Some(period.duration()),
+                        _ => None,
+                    })
+                    .await
+            };
+            for digest in due {
+                // Launches queued before a /stop are dropped with the rest of the alerts
+                if self.is_stopped(digest.chat_id).await {
+                    continue;
+                }
+                // Kept for the next pass rather than lost with the failed send
+                if let Err(e) = self.send_digest(&digest).await {
+                    eprintln!("Failed to send digest to {}: {:?}", digest.chat_id, e);
+                    self.digests.requeue(digest).await;
+                }
+            }
+        }
+    }
+
+    async fn send_digest(&self, digest: &DueDigest) -> Result<(), Error> {
+        let launches = digest.entries.len() + digest.overflow;
+        let mut message = format!(
+            "📰 ====== <b>LAUNCH DIGEST</b> ====== 📰
+
+{} launch{} since your last digest:
+",
+            launches,
+            if launches == 1 { "" } else { "es" }
+        );
+        for (index, entry) in digest.entries.iter().enumerate() {
+            message.push_str(&format!(
+                "\n{}. <b>${}</b> · MCAP ${} · Liq ${} · 👥 {}\n<code>{}</code>\n",
+                index + 1,
+                escape(&entry.symbol),
+                format_number(&entry.market_cap).unwrap_or_default(),
+                format_number(&entry.liquidity_usd).unwrap_or_default(),
+                entry.holders,
+                entry.address
+            ));
+        }
+        if digest.overflow > 0 {
+            message.push_str(&format!("\n…and {} more, see /recent\n", digest.overflow));
+        }
+        message.push_str("\nUse /sniQ &lt;token&gt; for details, or /delivery instant for real-time alerts.");
+        self.broadcast_message(digest.chat_id, &message, None).await
+    }
+
+    pub async fn send_weekly_report(&self, chat_id: i64, report: &WeeklyReport) -> Result<(), Error> {
+        let percent = |value: Option<f64>| value.map_or("n/a".to_string(), |value| format!("{:.0}%", value));
+        let gainers = if report.top_gainers.is_empty() {
+            "None this week".to_string()
+        } else {
+            report
+                .top_gainers
+                .iter()
+                .enumerate()
+                .map(|(index, gainer)| {
+                    format!(
+                        "{}. <b>${}</b> {:+.0}%",
+                        index + 1,
+                        escape(gainer.symbol.as_deref().unwrap_or("?")),
+                        gainer.change_pct
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+        let message = format!(
+            "📊 ====== <b>WEEKLY REPORT</b> ====== 📊\n\n\
+            🚀 <b>Launches:</b> {}\n\
+            🛟 <b>Survived day one:</b> {}\n\
+            💀 <b>Rugged:</b> {}\n\
+            📈 <b>Median 24h:</b> {}\n\n\
+            🏆 <b>Top gainers (24h)</b>\n{}\n\n\
+            Rates cover the {} launches priced a day after launch.",
+            report.launch_count,
+            percent(report.survival_rate_pct),
+            percent(report.rug_rate_pct),
+            report
+                .median_change_pct
+                .map_or("n/a".to_string(), |change| format!("{:+.0}%", change)),
+            gainers,
+            report.tracked,
+        );
+        self.broadcast_message(chat_id, &message, None).await
+    }
+
+    pub async fn subscriber_count(&self) -> usize {
+        self.active_users
+            .read()
+            .await
+            .values()
+            .filter(|&&active| active)
+            .count()
+    }
+
+    async fn finish_maintenance(
+        &self,
+        buffered: Vec<LaunchAlert>,
+        notice: &str,
+    ) {
+        let message = if buffered.is_empty() {
+            notice.to_string()
+        } else {
+            format!("{}\nSending {} delayed launch alert(s).", notice, buffered.len())
+        };
+        if let Err(e) = self.broadcast_notice(&message).await {
+            eprintln!("Failed to announce maintenance end: {:?}", e);
+        }
+        for alert in buffered {
+            if let Err(e) = self.send_launch_alert(alert, true).await {
+                eprintln!("Failed to send delayed launch alert: {:?}", e);
+            }
+        }
+    }
+
+    // Adds the curation row shown to curator chats
+    fn with_tag_buttons(&self, mut keyboard: serde_json::Value, contract_address: &str) -> serde_json::Value {
+        let row: Vec<serde_json::Value> = LaunchTag::ALL
+            .iter()
+            .map(|tag| {
+                json!({
+                    "text": tag.label(),
+                    "callback_data": CallbackAction::Tag(*tag, contract_address.to_string()).encode()
+                })
+            })
+            .collect();
+        if let Some(rows) = keyboard["inline_keyboard"].as_array_mut() {
+            rows.push(json!(row));
+        }
+        keyboard
+    }
+
+    fn create_launch_keyboard(
+        &self,
+        contract_address: &str,
+        token_symbol: &str,
+        tracked_variant: Option<Variant>,
+        buy_amounts: &[u64],
+    ) -> serde_json::Value {
+        let token_symbol = sanitize_display(token_symbol).text;
+        let buy_url = |amount: &str| match (tracked_variant, &self.config.public_url) {
+            // Route clicks through the redirect service so the experiment can count them
+            (Some(variant), Some(public_url)) => format!(
+                "{}/r/{}/{}?amount={}&symbol={}",
+                public_url,
+                variant.as_str(),
+                contract_address,
+                amount,
+                token_symbol
+            ),
+            _ => format!(
+                "{}?token={}&amount={}&symbol={}",
+                self.config.dex_url, contract_address, amount, token_symbol
+            ),
+        };
+
+        let buy_buttons: Vec<serde_json::Value> = buy_amounts
+            .iter()
+            .map(|amount| {
+                json!({
+                    "text": format!("🚀 Buy ${}", amount),
+                    "url": buy_url(&amount.to_string())
+                })
+            })
+            .collect();
+
+        let mut keyboard = json!({
+            "inline_keyboard": [
+                buy_buttons,
+                [
+                    {
+                        "text": "💰 Custom Amount",
+                        "url": format!("{}?token={}",
+                            self.config.dex_url, contract_address)
+                    }
+                ]
+            ]
+        });
+
+        keyboard["inline_keyboard"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!([
+                {
+                    "text": "🔄 Refresh",
+                    "callback_data": CallbackAction::RefreshToken(contract_address.to_string()).encode()
+                },
+                {
+                    "text": "👥 Holders",
+                    "callback_data": CallbackAction::ShowHolders(contract_address.to_string()).encode()
+                }
+            ]));
+
+        // Link the live token page when the HTTP server is publicly reachable
+        if let Some(public_url) = &self.config.public_url {
+            keyboard["inline_keyboard"]
+                .as_array_mut()
+                .unwrap()
+                .push(json!([
+                    {
+                        "text": "🌐 Live Page",
+                        "url": format!("{}/t/{}", public_url, contract_address)
+                    }
+                ]));
+        }
+
+        keyboard
+    }
+
+    // Helper functions for formatting
+    fn format_percentage(&self, value_str: String) -> String {
+        // Try to parse the string as f64
+        match value_str.parse::<f64>() {
+            Ok(value) => {
+                format!("{:.1}", value)
+            }
+            Err(_) => {
+                // If parsing fails, return the original string
+                // You might want to log this error in a production environment
+                eprintln!("Failed to parse percentage string: {}", value_str);
+                value_str
+            }
+        }
+    }
+
+    // Rate to show a chat's figures in, USD unless it picked a currency with /currency
+    async fn chat_denomination(&self, chat_id: i64) -> Denomination {
+        let currency = self
+            .preferences
+            .read()
+            .await
+            .get(&chat_id)
+            .map_or(Currency::Usd, |preferences| preferences.currency);
+        denomination(currency).await
+    }
+
+    // Holders gained per minute after launch, None for tokens the bot didn't see launch
+    async fn holder_velocity(&self, token_address: &str) -> Option<f64> {
+        let launched_at = match self.storage.launch_timestamp(token_address).await {
+            Ok(launched_at) => launched_at?,
+            Err(e) => {
+                eprintln!("Failed to load launch time of {}: {}", token_address, e);
+                return None;
+            }
+        };
+        match self.storage.holder_history(token_address, launched_at).await {
+            Ok(history) => holder_velocity(&history, launched_at),
+            Err(e) => {
+                eprintln!("Failed to load holder history of {}: {}", token_address, e);
+                None
+            }
+        }
+    }
+
+    // Radar card followed by curator tags when the launch has any
+    async fn format_token_card(
+        &self,
+        chat_id: i64,
+        info: &MemecoinInfo,
+        holders: &TokenCategoryResponse,
+    ) -> String {
+        let denomination = self.chat_denomination(chat_id).await;
+        let velocity = self.holder_velocity(&info.address).await;
+        let mut info = info.clone();
+        info.activity = pair_activity(&info.address).await;
+        if let (Some(per_minute), Some(risk)) = (velocity, info.risk.as_mut()) {
+            score_holder_velocity(risk, per_minute, &RiskConfig::new());
+        }
+        let radar = self.format_token_radar(&info, holders, velocity, &denomination);
+        match self.storage.launch_tags(&info.address).await {
+            Ok(tags) if !tags.is_empty() => {
+                let tags: Vec<String> = tags
+                    .iter()
+                    .map(|(tag, votes)| format!("{} ×{}", tag.label(), votes))
+                    .collect();
+                format!("{}\n🏷 <b>Curators:</b> {}", radar.trim_end(), tags.join(", "))
+            }
+            Ok(_) => radar,
+            Err(e) => {
+                eprintln!("Failed to load tags of {}: {}", info.address, e);
+                radar
+            }
+        }
+    }
+
+    fn format_token_radar(
+        &self,
+        info: &MemecoinInfo,
+        holders: &TokenCategoryResponse,
+        holder_velocity: Option<f64>,
+        denomination: &Denomination,
+    ) -> String {
+        let figures = info.formatted();
+        format!("
+                                             ⚡ ====== <b>SNIQ RADAR</b> ======⚡\n\
+                \n\
+                <b>Token:</b> ${}\n\
+                <b>Name:</b> {}\n\
+                <b>Contract:</b> {}\n\n\
+                📊 <b>METRICS</b>\n\
+                💰 <b>Price:</b> ${}\n\
+                📈 <b>MCap:</b> {}\n\
+                {}\
+                💫 <b>Supply:</b> ${}\n\
+                👥 <b>Holders:</b> {}\n\
+                {}\
+                💧 <b>LP:</b> {}\n\
+                ⚖️ <b>LP Split:</b> {} token / {} quote\n\n\
+                {}\
+                {}\
+                🏊 <b>POOL</b>\n\
+                🪙 <b>Quote Token:</b> {}\n\
+                💸 <b>Fee Tier:</b> {}\n\
+                📏 <b>Tick Spacing:</b> {}\n\
+                📆 <b>Est. LP APR:</b> {}\n\n\
+                🛡 <b>SECURITY CHECK</b>\n\
+                {}\n\
+                {}\n\n\
+                🔗 <b>QUICK LINKS</b>\n\
+                🎯 <b>Trade:</b> {}\n\
+                🔍 <b>Explorer:</b> {}\n\
+                ",
+                escape(&figures.symbol),
+                escape(&figures.name),
+                info.address,
+                info.price,
+                denomination.format(&info.market_cap, &figures.market_cap),
+                match info.market_cap_change_pct() {
+                    Some(change) => format!(
+                        "🏁 <b>Launch MCap:</b> {} ({:+.1}% since)\n",
+                        denomination.format(&info.launch_market_cap, &figures.launch_market_cap),
+                        change
+                    ),
+                    None => String::new(),
+                },
+                figures.supply,
+                holders.category,
+                match holder_velocity {
+                    Some(per_minute) => format!("🚀 <b>Velocity:</b> {:+.1} holders/min\n", per_minute),
+                    None => String::new(),
+                },
+                denomination.format(
+                    &info.usd_dex_liquidity,
+                    &format_number(&info.usd_dex_liquidity).unwrap_or_default()
+                ),
+                denomination.format(&info.usd_token_liquidity, &figures.token_liquidity),
+                denomination.format(&info.usd_quote_liquidity, &figures.quote_liquidity),
+                match &info.activity {
+                    Some(activity) => self.format_pair_activity(activity, denomination),
+                    None => String::new(),
+                },
+                if holders.top_holders.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        "🐋 <b>WHALE CONCENTRATION</b>\n{}\n\n",
+                        self.format_top_holders(holders, RADAR_TOP_HOLDERS)
+                    )
+                },
+                self.format_quote_token(info),
+                self.format_fee_tier(&info.pool_fee_percent),
+                if info.pool_tick_spacing.is_empty() { "Unknown" } else { &info.pool_tick_spacing },
+                if info.lp_fee_apr.is_empty() {
+                    "Not enough data yet".to_string()
+                } else {
+                    format!("{}%", self.format_percentage(info.lp_fee_apr.clone()))
+                },
+                self.format_owner(&info.owner),
+                match &info.risk {
+                    Some(risk) if risk.flags.is_empty() && !figures.hidden_chars => {
+                        format!("{}\n✅ No red flags found", self.format_risk(Some(risk)))
+                    }
+                    risk if figures.hidden_chars => {
+                        format!("{}\n<b>{}</b>", self.format_risk(risk.as_ref()), HIDDEN_CHARS_WARNING)
+                    }
+                    risk => self.format_risk(risk.as_ref()),
+                },
+                escape(&self.config.dex_url),
+                escape(&format!("{}/{}",self.config.explorer_url, info.address ))
+        )
+    }
+
+    fn format_pair_activity(&self, activity: &PairActivity, denomination: &Denomination) -> String {
+        let volume = activity.volume_24h_usd.to_string();
+        format!(
+            "📊 <b>24H ACTIVITY</b> ({})\n\
+            🔄 <b>Volume:</b> {}\n\
+            {}\
+            🧾 <b>Txns:</b> {} buys / {} sells\n\n",
+            activity.source,
+            denomination.format(&volume, &format_number(&volume).unwrap_or_default()),
+            match activity.price_change_24h_pct {
+                Some(change) => format!(
+                    "{} <b>Change:</b> {:+.1}%\n",
+                    if change >= 0f64 { "📈" } else { "📉" },
+                    change
+                ),
+                None => String::new(),
+            },
+            activity.buys_24h,
+            activity.sells_24h,
+        )
+    }
+
+    // Share of the sampled top holders, then the `limit` largest of them
+    fn format_top_holders(&self, holders: &TokenCategoryResponse, limit: usize) -> String {
+        let mut lines = vec![format!(
+            "Top {} hold <b>{:.1}%</b> of the supply",
+            holders.top_holders.len(),
+            holders.top_holders_pct.unwrap_or_default()
+        )];
+        lines.extend(holders.top_holders.iter().take(limit).enumerate().map(|(i, holder)| {
+            format!(
+                "{}. <code>{}</code> {:.2}%{}",
+                i + 1,
+                self.format_short_address(&holder.address),
+                holder.pct,
+                if holder.pct >= WHALE_HOLDER_PCT { " 🐋" } else { "" }
+            )
+        }));
+        lines.join("\n")
+    }
+
+    // The pool's quote token, flagged when it isn't on the safe list
+    fn format_quote_token(&self, info: &MemecoinInfo) -> String {
+        if info.quote_token_safe {
+            format!("{} ✅", info.quote_symbol)
+        } else if info.quote_token.is_empty() {
+            "Unknown".to_string()
+        } else {
+            format!("⚠️ Unlisted token <code>{}</code>", info.quote_token)
+        }
+    }
+
+    fn format_pool(&self, info: &MemecoinInfo) -> String {
+        let mut pool = format!(
+            "{} · {} fee",
+            self.format_quote_token(info),
+            self.format_fee_tier(&info.pool_fee_percent)
+        );
+        if !info.pool_tick_spacing.is_empty() {
+            let _ = write!(pool, " · tick spacing {}", info.pool_tick_spacing);
+        }
+        pool
+    }
+
+    fn format_fee_tier(&self, fee_percent: &str) -> String {
+        match fee_percent.parse::<f64>() {
+            // Fee tiers are small, keep enough precision for e.g. 0.05%
+            Ok(fee) => format!(
+                "{}%",
+                format!("{:.4}", fee)
+                    .trim_end_matches('0')
+                    .trim_end_matches('.')
+            ),
+            Err(_) => "Unknown".to_string(),
+        }
+    }
+
+    fn format_recent_launches(&self, launches: &[LaunchRecord]) -> String {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let lines: Vec<String> = launches
+            .iter()
+            .enumerate()
+            .map(|(i, launch)| {
+                let symbol = launch
+                    .coin_info
+                    .as_ref()
+                    .map(|info| info.symbol.clone())
+                    .or_else(|| launch.symbol.clone())
+                    .unwrap_or_else(|| "???".to_string());
+                let metrics = match &launch.coin_info {
+                    Some(info) => format!(
+                        "MCap ${} · Liq ${}",
+                        format_number(&info.market_cap).unwrap_or_default(),
+                        format_number(&info.usd_dex_liquidity).unwrap_or_default()
+                    ),
+                    None => "metrics unavailable".to_string(),
+                };
+                let tags: String = launch
+                    .tags
+                    .iter()
+                    .map(|tag| format!(" {}", tag.label()))
+                    .collect();
+                format!(
+                    "{}. <b>${}</b>{} · {} · {}\n    <code>{}</code>",
+                    i + 1,
+                    escape(&symbol),
+                    tags,
+                    metrics,
+                    format_age(now.saturating_sub(launch.block_timestamp)),
+                    launch.token_address
+                )
+            })
+            .collect();
+        format!("🕘 ====== <b>RECENT LAUNCHES</b> ====== 🕘\n\n{}", lines.join("\n"))
+    }
+
+    // Wallet summary with its meaningful positions, or every position flagged when show_all is set
+    async fn format_bag_check(&self, holdings: &TokenHoldings, show_all: bool) -> String {
+        let shown: Vec<&TokenPosition> = holdings
+            .positions
+            .iter()
+            .filter(|position| show_all || position.is_meaningful())
+            .collect();
+        let mut lines: Vec<String> = shown
+            .iter()
+            .take(PEEK_MAX_POSITIONS)
+            .map(|position| {
+                let value = match position.usd_value {
+                    Some(value) => format!(" (~${:.2})", value),
+                    None => String::new(),
+                };
+                let flag = match (position.dust, position.spam) {
+                    (_, Some(reason)) => format!(" ⚠️ {}", reason.label()),
+                    (true, None) => " 🧹 dust".to_string(),
+                    (false, None) => String::new(),
+                };
+                format!(
+                    "• <b>${}</b> {}{}{}",
+                    escape(&position.symbol),
+                    format_number(&position.formatted_balance)
+                        .unwrap_or_else(|_| position.formatted_balance.clone()),
+                    value,
+                    flag
+                )
+            })
+            .collect();
+        if shown.len() > PEEK_MAX_POSITIONS {
+            lines.push(format!("…and {} more", shown.len() - PEEK_MAX_POSITIONS));
+        }
+        if lines.is_empty() {
+            lines.push("Nothing worth showing".to_string());
+        }
+
+        let hidden = if show_all {
+            String::new()
+        } else {
+            let dust = holdings.positions.iter().filter(|p| p.dust && p.spam.is_none()).count();
+            let spam = holdings.positions.iter().filter(|p| p.spam.is_some()).count();
+            if dust + spam == 0 {
+                String::new()
+            } else {
+                format!("🧹 <b>Hidden:</b> {} dust, {} spam\n\n", dust, spam)
+            }
+        };
+        let exposure = match self.wallet_exposure(holdings).await {
+            Some(exposure) => format!(
+                "⚖️ <b>EXPOSURE</b>\n\
+                {}{:.0}% in one coin (${}), {:.0}% in &lt;1d-old tokens\n\n",
+                if exposure.largest_pct >= CONCENTRATED_PCT { "⚠️ " } else { "" },
+                exposure.largest_pct,
+                escape(&exposure.largest_symbol),
+                exposure.fresh_pct
+            ),
+            None => String::new(),
+        };
+
+        format!(
+            "💼 ====== <b>BAG CHECK</b> ====== 💼\n\n\
+            👛 <b>Wallet:</b> \n{}\n\n\
+            💼 <b>PORTFOLIO</b>\n\
+            🎯 <b>Total Memecoins:</b> {}\n\
+            🔒 <b>Locked / Vaulted:</b> {}\n\n\
+            📊 <b>POSITIONS</b>\n\
+            {}\n\n\
+            {}\
+            {}\
+            💡 <b>TIP:</b> Check token position\n\
+            <b>Use: /spot &lt;wallet&gt; &lt;token&gt;</b>",
+            escape(&holdings.account_address),
+            holdings.total_tokens,
+            holdings.locked_tokens,
+            lines.join("\n"),
+            exposure,
+            hidden
+        )
+    }
+
+    // Launch times come from the event history, tokens launched before it started count as old
+    async fn wallet_exposure(&self, holdings: &TokenHoldings) -> Option<Exposure> {
+        let mut launched_at = HashMap::new();
+        for position in holdings.positions.iter().filter(|position| position.is_meaningful()) {
+            let address = match canonicalize_address(&position.address) {
+                Some(address) => address,
+                None => continue,
+            };
+            match self.storage.launch_timestamp(&address).await {
+                Ok(Some(timestamp)) => {
+                    launched_at.insert(address, timestamp);
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("Failed to load launch time of {}: {}", address, e),
+            }
+        }
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        exposure(&holdings.positions, &launched_at, now)
+    }
+
+    fn create_peek_keyboard(&self, holdings: &TokenHoldings, show_all: bool) -> serde_json::Value {
+        let text = if show_all { "🧹 Hide dust & spam" } else { "👁 Show all" };
+        json!({
+            "inline_keyboard": [[
+                {
+                    "text": text,
+                    "callback_data": CallbackAction::Peek(holdings.account_address.clone(), !show_all).encode()
+                }
+            ]]
+        })
+    }
+
+    fn format_short_address(&self, address: &str) -> String {
+        let address = canonicalize_address(address).unwrap_or_else(|| address.to_string());
+        if address.len() > 8 && address.is_ascii() {
+            format!("{}...{}", escape(&address[..6]), escape(&address[address.len() - 4..]))
+        } else {
+            escape(&address)
+        }
+    }
+
+    pub async fn handle_updates(&self) -> Result<(), Error> {
+        let mut last_update_id = 0;
+
+        loop {
+            match self.get_updates(last_update_id + 1).await {
+                Ok(updates) => {
+                    for update in updates {
+                        if let Some(member) = update.my_chat_member {
+                            let kind = ChatKind::from_chat_type(&member.chat.chat_type);
+                            self.templates.record_kind(member.chat.id, kind).await;
+                            if let Err(e) = self.handle_membership(member).await {
+                                eprintln!("Error handling chat membership: {:?}", e);
+                            }
+                        }
+                        if let Some(message) = update.message.or(update.channel_post) {
+                            let kind = ChatKind::from_chat_type(&message.chat.chat_type);
+                            self.templates.record_kind(message.chat.id, kind).await;
+                            let username = self.username.get().map(String::as_str);
+                            let command = message
+                                .text
+                                .as_deref()
+                                .and_then(|text| addressed_command(text, username));
+                            if let Some(text) = command {
+                                // Never leave a private key sitting in the chat history, deleted before
+                                // anything that could fail on the way. Split like handle_command does, so
+                                // any whitespace between the words still counts
+                                let mut words = text.split_whitespace();
+                                if words.next() == Some("/wallet") && words.next() == Some("link") {
+                                    let deleted =
+                                        self.delete_message(message.chat.id, message.message_id).await;
+                                    if let Err(e) = deleted {
+                                        eprintln!("Failed to delete /wallet link message: {:?}", e);
+                                    }
+                                }
+                                if self.may_send(&message, &text).await? {
+                                    self.handle_command(&text, message.chat.id).await?;
+                                }
+                            }
+                        }
+                        if let Some(query) = update.callback_query {
+                            if let Err(e) = self.handle_callback(query).await {
+                                eprintln!("Error handling callback query: {:?}", e);
+                            }
+                        }
+                        if let Some(query) = update.inline_query {
+                            if let Err(e) = self.handle_inline_query(query).await {
+                                eprintln!("Error handling inline query: {:?}", e);
+                            }
+                        }
+                        last_update_id = update.update_id;
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error getting updates: {:?}", e);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                }
+            }
+
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    // Alerts of a group are started and stopped by its admins. Channel posts and anonymous admins
+    // post as the chat itself, only admins can do that. Wallet, trading and admin commands are
+    // refused outside private chats
+    async fn may_send(&self, message: &Message, command: &str) -> Result<bool, Error> {
+        let name = command.split_whitespace().next().unwrap_or_default();
+        if is_shared_chat(&message.chat.chat_type) && PRIVATE_COMMANDS.contains(&name) {
+            let notice = format!("🔒 {} only works in a private chat with the bot.", name);
+            self.send_message(message.chat.id, &notice, None).await?;
+            return Ok(false);
+        }
+        let is_group = matches!(message.chat.chat_type.as_str(), "group" | "supergroup");
+        if !is_group || !GROUP_ADMIN_COMMANDS.contains(&name) {
+            return Ok(true);
+        }
+        if message.sender_chat.as_ref().is_some_and(|sender| sender.id == message.chat.id) {
+            return Ok(true);
+        }
+        let is_admin = match &message.from {
+            Some(user) => self.is_chat_admin(message.chat.id, user.id).await?,
+            None => false,
+        };
+        if !is_admin {
+            let notice = "⛔️ Only group admins can start or stop alerts here.";
+            self.send_message(message.chat.id, notice, None).await?;
+        }
+        Ok(is_admin)
+    }
+
+    // The bot was added to or removed from a chat, or a user blocked it
+    async fn handle_membership(&self, update: ChatMemberUpdated) -> Result<(), Error> {
+        let chat_id = update.chat.id;
+        let was_present = is_present(&update.old_chat_member.status);
+        let is_now_present = is_present(&update.new_chat_member.status);
+        if !is_shared_chat(&update.chat.chat_type) {
+            // Blocked chats are stopped, so they're purged like any other stopped chat
+            if was_present && !is_now_present {
+                if let Some(active) = self.active_users.write().await.get_mut(&chat_id) {
+                    *active = false;
+                }
+                self.stop_chat(chat_id).await;
+            }
+            return Ok(());
+        }
+        match (was_present, is_now_present) {
+            (false, true) => {
+                println!("Added to {} {}", update.chat.chat_type, chat_id);
+                let message = if update.chat.chat_type == "channel" {
+                    "👋 Thanks for adding sniQ! Post /start in this channel to get launch alerts here."
+                } else {
+                    "👋 Thanks for adding sniQ! A group admin can send /start to get launch alerts here, \
+                    anyone can look tokens up with /sniQ &lt;address&gt;."
+                };
+                self.send_message(chat_id, message, None).await?;
+            }
+            (true, false) => {
+                println!("Removed from {} {}", update.chat.chat_type, chat_id);
+                self.forget_chat(chat_id).await;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_command(&self, command: &str, chat_id: i64) -> Result<(), Error> {
+        let mut parts: Vec<&str> = command.split_whitespace().collect();
+        // The command menu only has lowercase names, /sniq is /sniQ
+        if let Some(name) = parts.first_mut() {
+            if name.eq_ignore_ascii_case("/sniq") {
+                *name = "/sniQ";
+            }
+        }
+
+        // Muted chats are ignored without a reply, admin commands are checked where they're matched
+        if self.is_muted(chat_id).await {
+            return Ok(());
+        }
+
+        if !self.is_admin(chat_id) {
+            let name = parts.first().copied().unwrap_or_default();
+            match self.cooldowns.check(chat_id, name).await {
+                Throttle::Allow => {}
+                Throttle::SlowDown(wait_secs) => {
+                    let message = format!(
+                        "🐢 Easy there! Commands are coming in faster than we can look them up, \
+                        try again in {}s.",
+                        wait_secs
+                    );
+                    self.send_message(chat_id, &message, None).await?;
+                    return Ok(());
+                }
+                Throttle::Ignore => return Ok(()),
+            }
+        }
+
+        if let Some(demo) = &self.demo {
+            let name = parts.first().copied().unwrap_or_default();
+            if !name.starts_with('/') {
+                return Ok(());
+            }
+            if !DEMO_COMMANDS.contains(&name) {
+                self.send_message(
+                    chat_id,
+                    "🔒 This is a public demo. Only /sniQ, /holders, /peek, /spot, /recent and /help are available.",
+                    None,
+                )
+                .await?;
+                return Ok(());
+            }
+            if !demo.limiter.allow(chat_id).await {
+                self.send_message(chat_id, "🐢 Demo rate limit reached, try again in a minute.", None)
+                    .await?;
+                return Ok(());
+            }
+        }
+
+        match parts.get(0).map(|s| *s) {
+            Some("/spot") => {
+                match (parts.get(1), parts.get(2)) {
+                    (Some(wallet_addr), Some(token_addr)) => {
+                        match self.fetch_holding_info(wallet_addr, token_addr).await {
+                            Ok(info) => {
+                                let message = format!(
+                                    "📊 ====== <b>TOKEN SPOT</b> ====== 📊\n\n\
+                                    <b>Wallet:</b> {}\n\
+                                    <b>Token:</b> ${}\n\n\
+                                    <b>POSITION</b>\n\
+                                    <b>Balance:</b> {}\n\
+                                    <b>Worth:</b> ${}\n\n\
+                                    <b>ACTIONS</b>\n\
+                                    ⚡️ <b>Trade Now:</b> {}",
+                                    self.format_short_address(wallet_addr),
+                                    escape(&info.coin_info.symbol),
+                                    format_large_number(&info.account_balance).unwrap(),
+                                    info.usd_value,
+                                    escape(&self.config.dex_url),
+                                    // token_addr
+                                );
+
+                                self.send_message(chat_id, &message, None).await?;
+                                let symbol = &info.coin_info.symbol;
+                                self.send_position_chart(chat_id, wallet_addr, token_addr, symbol).await;
+                            }
+                            Err(e) => {
+                                let error_message = format!(
+                                    "❌ Error fetching token info: {}",
+                                    if e.to_string().contains("parse") {
+                                        "Invalid token data format"
+                                    } else if e.to_string().contains("aggregate_info") {
+                                        "Failed to fetch token information"
+                                    } else if e.to_string().contains("get_balance") {
+                                        "Failed to fetch account balance"
+                                    } else {
+                                        "Unexpected error occurred"
+                                    }
+                                );
+                                self.send_message(chat_id, &error_message, None).await?;
+                            }
+                        }
+                    }
+                    _ => {
+                        self.send_message(
+                            chat_id,
+                            "❌ Invalid command format.\nUsage: <code>/spot &lt;wallet_address&gt; &lt;token_address&gt;</code>",
+                            None,
+                        )
+                        .await?;
+                    }
+                }
+            }
+            Some("/start") => {
+                let previous = self.active_users.write().await.insert(chat_id, true);
+                self.resume_chat(chat_id).await;
+                if previous == Some(false) {
+                    let message = if self.settings_lost.write().await.remove(&chat_id) {
+                        "✅ Welcome back! Alerts resumed with default settings. The bot restarted since \
+                        /stop and your filters, buttons and watchlist were not kept. \
+                        Use /filters and /watch to set them again."
+                    } else {
+                        "✅ Welcome back! Alerts resumed with your previous filters, buttons and watchlist."
+                    };
+                    self.send_message(chat_id, message, None).await?;
+                } else if previous.is_none() {
+                    self.send_message(
+                        chat_id,
+                        "⚡️ ====== <b>WELCOME TO SNIQ BOT</b> ====== ⚡️\n\n\
+                                Catch the Meme. Beat the Market. 🎯🔥\n\n\
+                                🚀 <b>FEATURES:</b>\n\
+                                ✨ Instant Token Sniping – Know what’s hot in seconds.\n\
+                                🔍 Wallet Scanning – Fast, flawless, precise.\n\
+                                💸 One-Tap Trading – Access the market like a pro.\n\n\
+                                ⚡️ <b>GET STARTED:</b>\n\
+                                💥 <b>/sniQ &lt;address&gt;</b> – Scan a token instantly!\n\
+                                👀 <b>/peek &lt;wallet&gt;</b> – See your memecoin holdings.\n\
+                                🎯 <b>/spot &lt;wallet&gt; &lt;token&gt;</b> – Track your position on any token.\n\n\
+                                💎 sniq.fun\n\
+                                Fast. Sharp. Ahead. — Sniping Memecoins Like a Pro. ⚡️"
+                                ,
+                        None,
+                    )
+                    .await?;
+                } else {
+                    self.send_message(chat_id, "✅ You are already receiving token alerts!", None)
+                        .await?;
+                }
+            }
+            Some("/stop") => {
+                // Stopping only pauses the chat, /start restores its settings unless the bot restarted
+                let stopped = match self.active_users.write().await.get_mut(&chat_id) {
+                    Some(active @ true) => {
+                        *active = false;
+                        true
+                    }
+                    _ => false,
+                };
+                if stopped {
+                    self.stop_chat(chat_id).await;
+                    self.send_message(
+                        chat_id,
+                        "🛑 Token alerts stopped. Your settings are kept until the bot restarts, \
+                        use /start to resume.",
+                        None,
+                    )
+                    .await?;
+                } else {
+                    self.send_message(
+                        chat_id,
+                        "❗️ You are not receiving any alerts. Use /start to begin.",
+                        None,
+                    )
+                    .await?;
+                }
+            }
+            Some("/status") => {
+                let active_users = self.active_users.read().await;
+                let status = if active_users.get(&chat_id).copied().unwrap_or(false) {
+                    "🟢 You are currently receiving token alerts."
+                } else {
+                    "🔴 You are not receiving token alerts.\nUse /start to begin."
+                };
+                self.send_message(chat_id, status, None).await?;
+            }
+            Some("/help") if self.demo.is_some() => {
+                self.send_message(
+                    chat_id,
+                    "Available Commands (demo):\n\n\
+                    /help - Show this help message\n\
+                    /spot &lt;wallet&gt; &lt;token&gt; - Get token position for a wallet\n\
+                    /peek &lt;wallet&gt; [all] - Check token positions\n\
+                    /sniQ &lt;token, $SYMBOL or name&gt; - Get info on a particular token\n\
+                    /holders &lt;token&gt; - Show a token's largest holders\n\n\
+                    ℹ️ Alerts and subscriptions are disabled in this demo.",
+                    None,
+                )
+                .await?;
+            }
+            Some("/help") => {
+                let trading = if self.trader.is_some() {
+                    "/wallet - Link or unlink your trading wallet\n\
+                    /buy &lt;token&gt; &lt;amount&gt; - Buy a token with your linked wallet\n"
+                } else {
+                    ""
+                };
+                self.send_message(
+                    chat_id,
+                    &format!(
+                        "Available Commands:\n\n\
+                        /start - Start receiving token alerts\n\
+                        /stop - Stop receiving token alerts\n\
+                        /status - Check your alert status\n\
+                        /help - Show this help message\n\
+                        /spot &lt;wallet&gt; &lt;token&gt; - Get token position for a wallet\n\
+                        /peek &lt;wallet&gt; [all] - Check token positions, dust and spam included with all\n\
+                        /sniQ &lt;token, $SYMBOL or name&gt; - Get info on a particular token\n\
+                        /holders &lt;token&gt; - Show a token's largest holders\n\
+                        /filters - Filter which launches you get alerted about\n\
+                        /setbuttons &lt;usd&gt; [usd] [usd] - Choose your buy button amounts\n\
+                        /delivery &lt;instant|hourly|daily&gt; - Get alerts instantly or as a digest\n\
+                        /radar &lt;on|off&gt; - Get told about tokens as soon as they are created\n\
+                        /currency &lt;usd|eth|strk&gt; - Show market caps and liquidity in USD, ETH or STRK\n\
+                        /watch &lt;token&gt; - Get alerts when a token moves\n\
+                        /unwatch &lt;token&gt; - Stop watching a token\n\
+                        /follow &lt;token&gt; - Get every alert about a token, whale trades included\n\
+                        /follow &lt;wallet&gt; - Get alerted when a wallet sends or receives memecoins\n\
+                        /unfollow &lt;token|wallet&gt; - Stop following a token or wallet\n\
+                        /watchlist - Show your watched tokens and followed tokens and wallets\n\
+                        /share_settings - Get a code others can copy your filters and watchlist with\n\
+                        /import_settings &lt;code&gt; - Copy the filters and watchlist behind a code\n\
+                        /alert &lt;token&gt; [mcap] [above|below] &lt;value&gt; - Get alerted once a threshold is crossed\n\
+                        /alerts - Show your price alerts\n\
+                        /delalert &lt;id&gt; - Delete a price alert\n\
+                        /recent [n] [gem|sus|test] - Show the latest launches\n\
+                        /papertrade buy &lt;token&gt; &lt;usd&gt; - Simulate a buy, /papertrade close &lt;id&gt; sells it\n\
+                        /portfolio - Show your paper trading PnL\n\
+                        {}\n\
+                        💬 Share a token card in any chat by typing @{} &lt;token or $SYMBOL&gt;\n\
+                        ℹ️ You'll receive alerts for new tokens as they're detected.",
+                        trading,
+                        self.username.get().map_or("sniqbot", String::as_str)
+                    ),
+                    None,
+                )
+                .await?;
+            }
+            Some("/peek") => {
+                match (parts.get(1)) {
+                    Some(wallet_address) => {
+                        let show_all = parts.get(2).is_some_and(|arg| arg.eq_ignore_ascii_case("all"));
+                        match self.fetch_holdings(wallet_address).await {
+                            Ok(holdings) => {
+                                let message = self.format_bag_check(&holdings, show_all).await;
+                                let keyboard = self.create_peek_keyboard(&holdings, show_all);
+                                self.send_message_with_markup(chat_id, &message, keyboard, None)
+                                    .await?;
+                            }
+                            Err(e) => {
+                                let error_message = format!("Error peeking into wallet ⁉️");
+                                self.send_message(chat_id, &error_message, None).await?;
+                            }
+                        }
+                    },
+                    None => {
+                        let error_message = format!("Invalid parameters ❗️");
+                        self.send_message(chat_id, &error_message, None).await?;
+                    },
+                }
+            }
+            Some("/sniQ") => {
+                match (parts.get(1)) {
+                    Some(token_address) if token_address.starts_with("0x") => {
+                        self.send_token_details(chat_id, token_address).await?;
+                    },
+                    // Anything else is a symbol or a name, which can have spaces
+                    Some(_) => {
+                        let query = command.split_once(char::is_whitespace).map_or("", |(_, query)| query);
+                        self.search_token(chat_id, query).await?;
+                    },
+                    None => {
+                        let error_message = format!("Invalid parameters ❗️");
+                        self.send_message(chat_id, &error_message, None).await?;
+                    }              
+                }
+            }
+            
+            Some("/holders") => {
+                let message = match parts.get(1) {
+                    Some(token_address) => match self.fetch_token_info(token_address, false).await {
+                        Ok((_, holders)) if holders.top_holders.is_empty() => {
+                            "📭 No holders found for that token yet.".to_string()
+                        }
+                        Ok((info, holders)) => format!(
+                            "🐋 <b>Top holders of ${}</b>\n\n{}\n\n\
+                            Pool and locker contracts are left out, locked tokens count for their owner.",
+                            escape(&info.formatted().symbol),
+                            self.format_top_holders(&holders, holders.top_holders.len())
+                        ),
+                        Err(e) => {
+                            eprintln!("Failed to fetch holders of {}: {:#}", token_address, e);
+                            "Error fetching token details ⁉️".to_string()
+                        }
+                    },
+                    None => "❌ Usage: <code>/holders &lt;token_address&gt;</code>".to_string(),
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            Some("/recent") => {
+                // Usage: /recent [n] [gem|sus|test], in any order
+                let limit = parts[1..]
+                    .iter()
+                    .find_map(|arg| arg.parse::<u32>().ok())
+                    .unwrap_or(RECENT_DEFAULT)
+                    .clamp(1, RECENT_MAX);
+                let tag = parts[1..].iter().find_map(|arg| LaunchTag::parse(arg));
+                let message = match self.storage.recent_launches(limit, 0, tag).await {
+                    Ok(launches) if launches.is_empty() => match tag {
+                        Some(tag) => format!("📭 No launches tagged {} yet.", tag.label()),
+                        None => "📭 No launches recorded yet.".to_string(),
+                    },
+                    Ok(launches) => self.format_recent_launches(&launches),
+                    Err(e) => {
+                        eprintln!("Failed to load recent launches: {}", e);
+                        "Error fetching recent launches ⁉️".to_string()
+                    }
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            Some("/filters") => {
+                self.handle_filters_command(&parts[1..], chat_id).await?;
+            }
+            Some("/delivery") => {
+                self.handle_delivery_command(&parts[1..], chat_id).await?;
+            }
+            Some("/radar") => {
+                self.handle_radar_command(&parts[1..], chat_id).await?;
+            }
+            Some("/currency") => {
+                self.handle_currency_command(&parts[1..], chat_id).await?;
+            }
+            Some("/setbuttons") => {
+                self.handle_setbuttons_command(&parts[1..], chat_id).await?;
+            }
+            Some("/watch") => match parts.get(1) {
+                Some(token_address) => {
+                    let message = match self.watchlist.watch(chat_id, token_address).await {
+                        Ok(symbol) => format!(
+                            "👀 Watching <b>${}</b>. You'll be alerted on price, liquidity and holder changes.",
+                            escape(&symbol)
+                        ),
+                        Err(e) => format!("❌ {}", escape(&e.to_string())),
+                    };
+                    self.send_message(chat_id, &message, None).await?;
+                }
+                None => {
+                    self.send_message(chat_id, "❌ Usage: <code>/watch &lt;token_address&gt;</code>", None)
+                        .await?;
+                }
+            },
+            Some("/unwatch") => match parts.get(1) {
+                Some(token_address) => {
+                    let message = if self.watchlist.unwatch(chat_id, token_address).await {
+                        "🙈 Token removed from your watchlist."
+                    } else {
+                        "❗️ That token is not on your watchlist."
+                    };
+                    self.send_message(chat_id, message, None).await?;
+                }
+                None => {
+                    self.send_message(chat_id, "❌ Usage: <code>/unwatch &lt;token_address&gt;</code>", None)
+                        .await?;
+                }
+            },
+            Some("/follow") => match parts.get(1) {
+                // Addresses the factory didn't deploy are followed as wallets
+                Some(address) => {
+                    let message = match self.watchlist.follow(chat_id, address).await {
+                        Ok(symbol) => format!(
+                            "🔔 Following <b>${}</b>. You'll also hear about whale trades and holder milestones.",
+                            escape(&symbol)
+                        ),
+                        Err(WatchError::Lookup(lookup)) => match is_memecoin(address).await {
+                            Ok(false) => match self.wallets.follow(chat_id, address).await {
+                                Ok(wallet) => format!(
+                                    "👛 Following wallet {}. You'll hear when it sends or \
+                                     receives memecoins.",
+                                    self.format_short_address(&wallet)
+                                ),
+                                Err(e) => format!("❌ {}", escape(&e.to_string())),
+                            },
+                            // A memecoin whose lookup failed, or an RPC that couldn't tell
+                            Ok(true) => format!("❌ {}", escape(&lookup.to_string())),
+                            Err(e) => format!("❌ {}", escape(&e.to_string())),
+                        },
+                        Err(e) => format!("❌ {}", escape(&e.to_string())),
+                    };
+                    self.send_message(chat_id, &message, None).await?;
+                }
+                None => {
+                    self.send_message(
+                        chat_id,
+                        "❌ Usage: <code>/follow &lt;token_or_wallet_address&gt;</code>",
+                        None,
+                    )
+                    .await?;
+                }
+            },
+            Some("/unfollow") => match parts.get(1) {
+                Some(address) => {
+                    let message = if self.watchlist.unwatch(chat_id, address).await {
+                        "🔕 You no longer follow that token."
+                    } else if self.wallets.unfollow(chat_id, address).await {
+                        "🔕 You no longer follow that wallet."
+                    } else {
+                        "❗️ You don't follow that token or wallet."
+                    };
+                    self.send_message(chat_id, message, None).await?;
+                }
+                None => {
+                    self.send_message(
+                        chat_id,
+                        "❌ Usage: <code>/unfollow &lt;token_or_wallet_address&gt;</code>",
+                        None,
+                    )
+                    .await?;
+                }
+            },
+            Some("/watchlist") => {
+                let watched = self.watchlist.watched(chat_id).await;
+                let wallets = self.wallets.followed(chat_id).await;
+                let message = if watched.is_empty() && wallets.is_empty() {
+                    "📭 Your watchlist is empty. Use /watch &lt;token&gt; to add one.".to_string()
+                } else {
+                    let mut lines: Vec<String> = watched
+                        .iter()
+                        .map(|(address, symbol, subscription)| {
+                            let marker = match subscription {
+                                Subscription::Watch => "👀",
+                                Subscription::Follow => "🔔",
+                            };
+                            format!("{} <b>${}</b> {}", marker, escape(symbol), address)
+                        })
+                        .collect();
+                    lines.extend(wallets.iter().map(|wallet| format!("👛 <code>{}</code>", wallet)));
+                    format!("👀 ====== <b>WATCHLIST</b> ====== 👀\n\n{}", lines.join("\n"))
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            // Telegram only lists commands without dashes, the dashed spelling is still understood
+            Some("/share_settings") | Some("/share-settings") => {
+                self.handle_share_settings_command(chat_id).await?;
+            }
+            Some("/import_settings") | Some("/import-settings") => {
+                self.handle_import_settings_command(&parts[1..], chat_id).await?;
+            }
+            Some("/alert") => {
+                let request = AlertRequest::parse(parts.get(2..).unwrap_or_default());
+                let message = match (parts.get(1), request) {
+                    (Some(token_address), Some(request)) => {
+                        match self.price_alerts.add(chat_id, token_address, request).await {
+                            Ok((alert, current)) => format!(
+                                "⏰ Alert #{} set: <b>${}</b> {} {} {} (now {}).",
+                                alert.id,
+                                escape(&alert.symbol),
+                                alert.metric.label(),
+                                alert.direction.label(),
+                                self.format_alert_value(alert.metric, alert.target),
+                                self.format_alert_value(alert.metric, current)
+                            ),
+                            Err(e) => format!("❌ {}", escape(&e.to_string())),
+                        }
+                    }
+                    _ => "❌ Usage: <code>/alert &lt;token_address&gt; above 0.005</code> or <code>/alert &lt;token_address&gt; mcap 1000000</code>"
+                        .to_string(),
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            Some("/alerts") => {
+                let alerts = self.price_alerts.list(chat_id).await;
+                let message = if alerts.is_empty() {
+                    "📭 You have no price alerts. Use /alert &lt;token&gt; above &lt;price&gt; to add one.".to_string()
+                } else {
+                    let lines: Vec<String> = alerts
+                        .iter()
+                        .map(|alert| {
+                            format!(
+                                "#{} <b>${}</b> {} {} {}",
+                                alert.id,
+                                escape(&alert.symbol),
+                                alert.metric.label(),
+                                alert.direction.label(),
+                                self.format_alert_value(alert.metric, alert.target)
+                            )
+                        })
+                        .collect();
+                    format!(
+                        "⏰ ====== <b>PRICE ALERTS</b> ====== ⏰\n\n{}\n\nDelete one with /delalert &lt;id&gt;",
+                        lines.join("\n")
+                    )
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            Some("/delalert") => {
+                let id = parts.get(1).and_then(|id| id.trim_start_matches('#').parse::<u64>().ok());
+                let message = match id {
+                    Some(id) if self.price_alerts.remove(chat_id, id).await => "🗑 Alert deleted.",
+                    Some(_) => "❗️ You have no alert with that id.",
+                    None => "❌ Usage: <code>/delalert &lt;id&gt;</code>",
+                };
+                self.send_message(chat_id, message, None).await?;
+            }
+            Some("/papertrade") => {
+                self.handle_papertrade_command(&parts[1..], chat_id).await?;
+            }
+            Some("/portfolio") => {
+                self.handle_portfolio_command(chat_id).await?;
+            }
+            Some("/wallet") => {
+                self.handle_wallet_command(&parts[1..], chat_id).await?;
+            }
+            Some("/buy") => {
+                self.handle_buy_command(&parts[1..], chat_id).await?;
+            }
+            Some("/admin") => {
+                if !self.is_admin(chat_id) {
+                    self.send_message(chat_id, "⛔️ This command is restricted to bot admins.", None)
+                        .await?;
+                    return Ok(());
+                }
+                self.handle_admin_command(&parts[1..], chat_id).await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // The wallet's balance of the token over time with its buys and sells, sent under /spot
+    async fn send_position_chart(&self, chat_id: i64, wallet: &str, token_address: &str, symbol: &str) {
+        let history = match position_history(token_address, wallet).await {
+            Ok(history) => history,
+            Err(e) => {
+                eprintln!("Failed to load position history of {} in {}: {}", wallet, token_address, e);
+                return;
+            }
+        };
+        let balances: Vec<(u64, f64)> = history
+            .iter()
+            .map(|change| (change.timestamp, change.balance))
+            .collect();
+        let trades: Vec<(u64, f64, bool)> = history
+            .iter()
+            .filter_map(|change| {
+                change
+                    .trade
+                    .map(|side| (change.timestamp, change.balance, side == TradeSide::Buy))
+            })
+            .collect();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let png = match render_position(&balances, &trades, now) {
+            Ok(png) => png,
+            // Wallets that never held the token have nothing to chart
+            Err(ChartError::NotEnoughData) => return,
+            Err(e) => {
+                eprintln!("Failed to render position chart of {}: {}", wallet, e);
+                return;
+            }
+        };
+        let buys = trades.iter().filter(|(_, _, is_buy)| *is_buy).count();
+        let caption = format!(
+            "📊 <b>${}</b> position: {} buys, {} sells",
+            escape(symbol),
+            buys,
+            trades.len() - buys
+        );
+        if let Err(e) = self.send_chart(chat_id, &png, &caption, &mut None, Priority::Reply).await {
+            eprintln!("Failed to send position chart to {}: {:?}", chat_id, e);
+        }
+    }
+
+    // Token card followed by its price chart when there is one
+    async fn send_token_details(&self, chat_id: i64, token_address: &str) -> Result<(), Error> {
+        match self.fetch_token_info(token_address, false).await {
+            Ok(response) => {
+                let message = self.format_token_card(chat_id, &response.0, &response.1).await;
+                self.send_message(chat_id, &message, None).await?;
+                if let Some(chart) = self.price_history.chart(&response.0.address).await {
+                    let symbol = &response.0.formatted().symbol;
+                    let caption = chart.caption(&escape(symbol));
+                    let sent = self
+                        .send_chart(chat_id, &chart.png, &caption, &mut None, Priority::Reply)
+                        .await;
+                    if let Err(e) = sent {
+                        eprintln!("Failed to send chart to {}: {:?}", chat_id, e);
+                    }
+                }
+            }
+            Err(e) if e.downcast_ref::<DeadlineExceeded>().is_some() => {
+                let message = "⏱ Token data is taking too long to load right now, try again in a minute.";
+                self.send_message(chat_id, message, None).await?;
+            }
+            Err(_) => {
+                self.send_message(chat_id, "Error fetching token details ⁉️", None).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // /sniQ by symbol or name, the user picks when several launches match
+    async fn search_token(&self, chat_id: i64, query: &str) -> Result<(), Error> {
+        let matches = self.token_index.search(query, MAX_SEARCH_MATCHES).await;
+        if let Some(found) = single_match(&matches) {
+            return self.send_token_details(chat_id, &found.token.address).await;
+        }
+        if matches.is_empty() {
+            let message = format!(
+                "🔍 No launched token matches <b>{}</b>. Try its $SYMBOL, its name or its address.",
+                escape(query.trim())
+            );
+            return self.send_message(chat_id, &message, None).await;
+        }
+
+        // Button labels are plain text, nothing to escape
+        let rows: Vec<serde_json::Value> = matches
+            .iter()
+            .map(|found| {
+                json!([{
+                    "text": format!(
+                        "${} · {} · {}",
+                        found.token.symbol,
+                        found.token.name,
+                        self.format_short_address(&found.token.address)
+                    ),
+                    "callback_data": CallbackAction::ShowToken(found.token.address.clone()).encode()
+                }])
+            })
+            .collect();
+        let message = format!("🔍 Several tokens match <b>{}</b>, which one?", escape(query.trim()));
+        self.send_message_with_markup(chat_id, &message, json!({ "inline_keyboard": rows }), None)
+            .await
+    }
+
+    async fn handle_callback(&self, query: CallbackQuery) -> Result<(), Error> {
+        let action = query.data.as_deref().and_then(CallbackAction::parse);
+        let (action, message) = match (action, query.message) {
+            (Some(action), Some(message)) => (action, message),
+            _ => {
+                self.answer_callback_query(&query.id, Some("This button has expired"))
+                    .await?;
+                return Ok(());
+            }
+        };
+        let chat_id = message.chat.id;
+
+        if self.is_muted(chat_id).await {
+            self.answer_callback_query(&query.id, None).await?;
+            return Ok(());
+        }
+        if let Some(demo) = &self.demo {
+            if !demo.limiter.allow(query.from.id).await {
+                self.answer_callback_query(&query.id, Some("🐢 Demo rate limit reached"))
+                    .await?;
+                return Ok(());
+            }
+        }
+
+        // Lookups behind buttons cost as much as the commands they stand for
+        let command = match &action {
+            CallbackAction::RefreshToken(_) => Some("/sniQ"),
+            CallbackAction::Peek(..) => Some("/peek"),
+            CallbackAction::ShowHolders(_) => Some("/holders"),
+            _ => None,
+        };
+        if let Some(command) = command.filter(|_| !self.is_admin(chat_id)) {
+            match self.cooldowns.check(chat_id, command).await {
+                Throttle::Allow => {}
+                Throttle::SlowDown(wait_secs) => {
+                    let text = format!("🐢 Easy there! Try again in {}s", wait_secs);
+                    self.answer_callback_query(&query.id, Some(&text)).await?;
+                    return Ok(());
+                }
+                Throttle::Ignore => {
+                    self.answer_callback_query(&query.id, None).await?;
+                    return Ok(());
+                }
+            }
+        }
+
+        // Trades run against the chat's wallet, only its owner may confirm or cancel them
+        let is_trade = matches!(action, CallbackAction::ConfirmTrade | CallbackAction::CancelTrade);
+        if is_trade && !pressed_by_owner(&message.chat.chat_type, chat_id, query.from.id) {
+            self.answer_callback_query(&query.id, Some("Only the wallet owner can do this"))
+                .await?;
+            return Ok(());
+        }
+
+        match action {
+            CallbackAction::RefreshToken(token_address) => {
+                match self.fetch_token_info(&token_address, true).await {
+                    Ok((info, holders)) => {
+                        self.answer_callback_query(&query.id, None).await?;
+                        let message_text = self.format_token_card(chat_id, &info, &holders).await;
+                        let buy_amounts = self
+                            .preferences
+                            .read()
+                            .await
+                            .get(&chat_id)
+                            .map_or(DEFAULT_BUY_AMOUNTS.to_vec(), |preferences| {
+                                preferences.buy_amounts().to_vec()
+                            });
+                        let mut keyboard =
+                            self.create_launch_keyboard(&info.address, &info.symbol, None, &buy_amounts);
+                        if self.is_curator(chat_id) {
+                            keyboard = self.with_tag_buttons(keyboard, &info.address);
+                        }
+                        self.edit_message_text(chat_id, message.message_id, &message_text, keyboard)
+                            .await?;
+                        // Launch alerts go out before any prices are collected, the chart follows
+                        // once someone refreshes the card
+                        if let Some(chart) = self.price_history.chart(&info.address).await {
+                            let caption = chart.caption(&escape(&info.formatted().symbol));
+                            let sent = self
+                                .send_chart(chat_id, &chart.png, &caption, &mut None, Priority::Reply)
+                                .await;
+                            if let Err(e) = sent {
+                                eprintln!("Failed to send chart to {}: {:?}", chat_id, e);
+                            }
+                        }
+                    }
+                    Err(_) => {
+                        self.answer_callback_query(&query.id, Some("Error fetching token details ⁉️"))
+                            .await?;
+                    }
+                }
+            }
+            CallbackAction::Peek(wallet_address, show_all) => match self.fetch_holdings(&wallet_address).await {
+                Ok(holdings) => {
+                    self.answer_callback_query(&query.id, None).await?;
+                    let message_text = self.format_bag_check(&holdings, show_all).await;
+                    let keyboard = self.create_peek_keyboard(&holdings, show_all);
+                    self.edit_message_text(chat_id, message.message_id, &message_text, keyboard)
+                        .await?;
+                }
+                Err(_) => {
+                    self.answer_callback_query(&query.id, Some("Error peeking into wallet ⁉️"))
+                        .await?;
+                }
+            },
+            CallbackAction::ShowToken(token_address) => {
+                self.answer_callback_query(&query.id, None).await?;
+                self.send_token_details(chat_id, &token_address).await?;
+            }
+            CallbackAction::ShowHolders(token_address) => {
+                match self.fetch_token_info(&token_address, false).await {
+                    Ok((info, holders)) => {
+                        self.answer_callback_query(&query.id, None).await?;
+                        let message_text = format!(
+                            "👥 <b>${} holders:</b> {}",
+                            escape(&info.symbol),
+                            holders.category
+                        );
+                        self.send_message(chat_id, &message_text, Some(message.message_id))
+                            .await?;
+                    }
+                    Err(_) => {
+                        self.answer_callback_query(&query.id, Some("Error fetching holders ⁉️"))
+                            .await?;
+                    }
+                }
+            }
+            CallbackAction::Tag(tag, token_address) => {
+                if !self.is_curator(chat_id) {
+                    self.answer_callback_query(&query.id, Some("Only curators can tag launches"))
+                        .await?;
+                    return Ok(());
+                }
+                let reply = match self.storage.tag_launch(&token_address, query.from.id, tag).await {
+                    Ok(_) => format!("Tagged as {}", tag.label()),
+                    Err(e) => {
+                        eprintln!("Failed to tag {}: {}", token_address, e);
+                        "Failed to save tag ⁉️".to_string()
+                    }
+                };
+                self.answer_callback_query(&query.id, Some(&reply)).await?;
+            }
+            CallbackAction::ConfirmTrade => {
+                let trader = match &self.trader {
+                    Some(trader) => trader,
+                    None => {
+                        self.answer_callback_query(&query.id, Some("Trading is disabled")).await?;
+                        return Ok(());
+                    }
+                };
+                self.answer_callback_query(&query.id, Some("Submitting trade...")).await?;
+                let message_text = match trader.confirm(chat_id).await {
+                    Ok(TradeOutcome::DryRun { estimated_fee }) => format!(
+                        "🧪 <b>Dry run:</b> the swap simulated successfully.\n\
+                        <b>Estimated fee:</b> {} STRK\n\n\
+                        No transaction was sent.",
+                        format_large_number(&estimated_fee.to_string())
+                            .unwrap_or_default()
+                    ),
+                    Ok(TradeOutcome::Submitted { transaction_hash }) => format!(
+                        "✅ <b>Swap submitted!</b>\n\n\
+                        🔍 <b>Transaction:</b> <code>{}</code>",
+                        transaction_hash
+                    ),
+                    Err(e) => format!("❌ Trade failed: {}", escape(&e.to_string())),
+                };
+                self.edit_message_text(
+                    chat_id,
+                    message.message_id,
+                    &message_text,
+                    json!({ "inline_keyboard": [] }),
+                )
+                .await?;
+            }
+            CallbackAction::CancelTrade => {
+                if let Some(trader) = &self.trader {
+                    trader.cancel(chat_id).await;
+                }
+                self.answer_callback_query(&query.id, None).await?;
+                self.edit_message_text(
+                    chat_id,
+                    message.message_id,
+                    "❌ Trade cancelled.",
+                    json!({ "inline_keyboard": [] }),
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    // Token cards offered by `@bot <address or symbol>`, posted in whatever chat the user is in
+    async fn handle_inline_query(&self, query: InlineQuery) -> Result<(), Error> {
+        if self.is_muted(query.from.id).await {
+            return self.answer_inline_query(&query.id, Vec::new()).await;
+        }
+        if let Some(demo) = &self.demo {
+            if !demo.limiter.allow(query.from.id).await {
+                return self.answer_inline_query(&query.id, Vec::new()).await;
+            }
+        }
+
+        // Every answered query looks tokens up, so it counts against whoever is typing
+        let search = InlineSearch::parse(&query.query);
+        if search.is_some()
+            && !self.is_admin(query.from.id)
+            && self.cooldowns.check(query.from.id, "/sniQ").await != Throttle::Allow
+        {
+            return self.answer_inline_query(&query.id, Vec::new()).await;
+        }
+
+        let launches = match search {
+            Some(InlineSearch::Address(address)) => Ok(vec![address]),
+            Some(InlineSearch::Symbol(symbol)) => Ok(self
+                .token_index
+                .search(&symbol, MAX_INLINE_RESULTS as usize)
+                .await
+                .into_iter()
+                .map(|found| found.token.address)
+                .collect()),
+            Some(InlineSearch::Latest) => self
+                .storage
+                .recent_launches(MAX_INLINE_RESULTS, 0, None)
+                .await
+                .map(|launches| launches.into_iter().map(|launch| launch.token_address).collect()),
+            None => Ok(Vec::new()),
+        };
+        let addresses: Vec<String> = launches.unwrap_or_else(|e| {
+            eprintln!("Failed to resolve inline query {:?}: {}", query.query, e);
+            Vec::new()
+        });
+
+        // Cards follow the currency and buy buttons of whoever is asking
+        let buy_amounts = self
+            .preferences
+            .read()
+            .await
+            .get(&query.from.id)
+            .map_or(DEFAULT_BUY_AMOUNTS.to_vec(), |preferences| preferences.buy_amounts().to_vec());
+        // Inline answers have a short deadline, so the tokens are looked up side by side
+        let tokens = join_all(addresses.iter().map(|address| async move {
+            let token = self.fetch_token_info(address, false).await;
+            let card = match &token {
+                Ok((info, holders)) => Some(self.format_token_card(query.from.id, info, holders).await),
+                Err(_) => None,
+            };
+            (token, card)
+        }))
+        .await;
+        let mut results = Vec::new();
+        for (address, token) in addresses.iter().zip(tokens) {
+            let (info, card) = match token {
+                (Ok((info, _)), Some(card)) => (info, card),
+                (Err(e), _) => {
+                    eprintln!("Failed to fetch {} for an inline query: {:#}", address, e);
+                    continue;
+                }
+                (Ok(_), None) => continue,
+            };
+            let keyboard = self.create_launch_keyboard(&info.address, &info.symbol, None, &buy_amounts);
+            let figures = info.formatted();
+            results.push(article(
+                results.len(),
+                &format!("${} · {}", figures.symbol, figures.name),
+                &format!("Price ${} · MCap ${}", info.price, figures.market_cap),
+                &card,
+                link_buttons(keyboard),
+            ));
+        }
+        self.answer_inline_query(&query.id, results).await
+    }
+
+    async fn handle_filters_command(&self, args: &[&str], chat_id: i64) -> Result<(), Error> {
+        let mut preferences = self.preferences.write().await;
+        let current = preferences.entry(chat_id).or_default();
+
+        // "off" clears a single filter
+        let updated = match args {
+            ["liquidity", "off"] => {
+                current.min_liquidity_usd = None;
+                true
+            }
+            ["liquidity", value] => match value.trim_start_matches('$').parse::<f64>() {
+                Ok(min_liquidity) if min_liquidity >= 0f64 => {
+                    current.min_liquidity_usd = Some(min_liquidity);
+                    true
+                }
+                _ => false,
+            },
+            ["team", "off"] => {
+                current.max_team_allocation_pct = None;
+                true
+            }
+            ["team", value] => match value.trim_end_matches('%').parse::<f64>() {
+                Ok(max_team) if (0f64..=100f64).contains(&max_team) => {
+                    current.max_team_allocation_pct = Some(max_team);
+                    true
+                }
+                _ => false,
+            },
+            ["holders", "off"] => {
+                current.min_holders = None;
+                true
+            }
+            ["holders", value] => match value.parse::<usize>() {
+                Ok(min_holders) if HOLDER_TIERS.contains(&min_holders) => {
+                    current.min_holders = Some(min_holders);
+                    true
+                }
+                _ => false,
+            },
+            ["reset"] => {
+                *current = UserPreferences::default();
+                true
+            }
+            [] => true,
+            _ => false,
+        };
+
+        if !updated {
+            self.send_message(
+                chat_id,
+                "❌ Invalid filter.\n\n\
+                /filters liquidity &lt;usd&gt; - Minimum USD liquidity\n\
+                /filters team &lt;percent&gt; - Maximum team allocation\n\
+                /filters holders &lt;10|20|50|100&gt; - Minimum holders\n\
+                /filters &lt;name&gt; off - Clear one filter\n\
+                /filters reset - Clear all filters",
+                None,
+            )
+            .await?;
+            return Ok(());
+        }
+
+        let show = |value: Option<String>| value.unwrap_or_else(|| "Any".to_string());
+        let message = format!(
+            "🎚 ====== <b>ALERT FILTERS</b> ====== 🎚\n\n\
+            💧 <b>Min liquidity:</b> {}\n\
+            👨‍💻 <b>Max team allocation:</b> {}\n\
+            👥 <b>Min holders:</b> {}\n\n\
+            Only launches passing every filter will be sent to you.",
+            show(current.min_liquidity_usd.map(|v| format!("${}", v))),
+            show(current.max_team_allocation_pct.map(|v| format!("{}%", v))),
+            show(current.min_holders.map(|v| format!("{}+", v))),
+        );
+        if current.is_default() {
+            preferences.remove(&chat_id);
+        }
+        drop(preferences);
+
+        self.send_message(chat_id, &message, None).await
+    }
+
+    async fn handle_setbuttons_command(&self, args: &[&str], chat_id: i64) -> Result<(), Error> {
+        let mut preferences = self.preferences.write().await;
+        let current = preferences.entry(chat_id).or_default();
+
+        let updated = match args {
+            ["reset"] => {
+                current.buy_amounts = None;
+                true
+            }
+            [] => true,
+            amounts => match parse_buy_amounts(amounts) {
+                Some(amounts) => {
+                    current.buy_amounts = Some(amounts);
+                    true
+                }
+                None => false,
+            },
+        };
+        let amounts: Vec<String> = current.buy_amounts().iter().map(|amount| format!("${}", amount)).collect();
+        if current.is_default() {
+            preferences.remove(&chat_id);
+        }
+        drop(preferences);
+
+        let message = if updated {
+            format!(
+                "🚀 Your buy buttons: {}\n\n\
+                Change them with /setbuttons &lt;usd&gt; [usd] [usd], or /setbuttons reset.",
+                amounts.join(" · ")
+            )
+        } else {
+            format!(
+                "❌ Usage: /setbuttons 25 100 500\n\n\
+                Up to {} whole USD amounts between 1 and {}.",
+                MAX_BUY_BUTTONS, MAX_BUY_AMOUNT_USD
+            )
+        };
+        self.send_message(chat_id, &message, None).await
+    }
+
+    async fn handle_delivery_command(&self, args: &[&str], chat_id: i64) -> Result<(), Error> {
+        let mut preferences = self.preferences.write().await;
+        let current = preferences.entry(chat_id).or_default();
+
+        let updated = match args {
+            [] => true,
+            args => match DeliveryMode::parse(args) {
+                Some(mode) => {
+                    current.delivery = mode;
+                    true
+                }
+                None => false,
+            },
+        };
+        let mode = current.delivery;
+        if current.is_default() {
+            preferences.remove(&chat_id);
+        }
+        drop(preferences);
+
+        let message = match (updated, mode) {
+            (false, _) => "❌ Usage: /delivery instant, /delivery digest hourly or /delivery digest daily"
+                .to_string(),
+            (true, DeliveryMode::Instant) => {
+                "⚡️ Launch alerts are sent as they happen.\n\n\
+                Switch to a summary with /delivery digest hourly or /delivery digest daily."
+                    .to_string()
+            }
+            (true, DeliveryMode::Digest(period)) => format!(
+                "📰 Launch alerts are batched into a {} digest.\n\n\
+                Watchlist and price alerts are still sent right away. Back to real-time with /delivery instant.",
+                period.as_str()
+            ),
+        };
+        self.send_message(chat_id, &message, None).await
+    }
+
+    async fn handle_radar_command(&self, args: &[&str], chat_id: i64) -> Result<(), Error> {
+        let mut preferences = self.preferences.write().await;
+        let current = preferences.entry(chat_id).or_default();
+
+        let updated = match args.first().map(|arg| arg.to_lowercase()).as_deref() {
+            None => true,
+            Some("on") => {
+                current.prelaunch_radar = true;
+                true
+            }
+            Some("off") => {
+                current.prelaunch_radar = false;
+                true
+            }
+            Some(_) => false,
+        };
+        let enabled = current.prelaunch_radar;
+        if current.is_default() {
+            preferences.remove(&chat_id);
+        }
+        drop(preferences);
+
+        let message = match (updated, enabled) {
+            (false, _) => "❌ Usage: /radar on or /radar off",
+            (true, true) => {
+                "🌱 Pre-launch radar is on, you'll hear about tokens as soon as they are created, \
+                before liquidity is added. Turn it off with /radar off."
+            }
+            (true, false) => {
+                "🌱 Pre-launch radar is off, you only get launch alerts. \
+                Hear about tokens as soon as they are created with /radar on."
+            }
+        };
+        self.send_message(chat_id, message, None).await
+    }
+
+    async fn handle_currency_command(&self, args: &[&str], chat_id: i64) -> Result<(), Error> {
+        let mut preferences = self.preferences.write().await;
+        let current = preferences.entry(chat_id).or_default();
+
+        let updated = match args.first() {
+            None => true,
+            Some(arg) => match Currency::parse(arg) {
+                Some(currency) => {
+                    current.currency = currency;
+                    true
+                }
+                None => false,
+            },
+        };
+        let currency = current.currency;
+        if current.is_default() {
+            preferences.remove(&chat_id);
+        }
+        drop(preferences);
+
+        let message = if updated {
+            format!(
+                "💱 Market caps and liquidity are shown in <b>{}</b>.\n\n\
+                Change it with /currency usd, /currency eth or /currency strk.",
+                currency.as_str()
+            )
+        } else {
+            "❌ Usage: /currency usd, /currency eth or /currency strk".to_string()
+        };
+        self.send_message(chat_id, &message, None).await
+    }
+
+    async fn handle_share_settings_command(&self, chat_id: i64) -> Result<(), Error> {
+        let watched = self.watchlist.watched(chat_id).await;
+        let preset = SettingsPreset::from_chat(self.preferences.read().await.get(&chat_id), &watched);
+        if preset.is_empty() {
+            return self
+                .send_message(
+                    chat_id,
+                    "📭 Nothing to share yet. Set /filters or /watch a token first.",
+                    None,
+                )
+                .await;
+        }
+
+        let code = preset.code();
+        let message = match self.storage.save_settings_preset(&code, &preset.to_payload()).await {
+            Ok(()) => format!(
+                "🔗 Your settings code: <code>{}</code>\n\n\
+                It carries your filters, buy buttons, currency and {} watched token(s). \
+                Anyone can copy them with /import_settings {}",
+                code,
+                preset.watch.len() + preset.follow.len(),
+                code
+            ),
+            Err(e) => {
+                eprintln!("Failed to save settings preset of {}: {}", chat_id, e);
+                "❌ Failed to create a code, try again later.".to_string()
+            }
+        };
+        self.send_message(chat_id, &message, None).await
+    }
+
+    async fn handle_import_settings_command(&self, args: &[&str], chat_id: i64) -> Result<(), Error> {
+        let code = match args.first().and_then(|code| normalize_code(code)) {
+            Some(code) => code,
+            None => {
+                return self
+                    .send_message(chat_id, "❌ Usage: /import_settings &lt;code&gt;", None)
+                    .await;
+            }
+        };
+        let preset = match self.storage.settings_preset(&code).await {
+            Ok(Some(payload)) => SettingsPreset::from_payload(&payload),
+            Ok(None) => None,
+            Err(e) => {
+                eprintln!("Failed to load settings preset {}: {}", code, e);
+                return self
+                    .send_message(chat_id, "❌ Failed to load that code, try again later.", None)
+                    .await;
+            }
+        };
+        let preset = match preset {
+            Some(preset) => preset,
+            None => return self.send_message(chat_id, "❗️ No settings found for that code.", None).await,
+        };
+
+        {
+            let mut preferences = self.preferences.write().await;
+            let current = preferences.entry(chat_id).or_default();
+            preset.apply(current);
+            if current.is_default() {
+                preferences.remove(&chat_id);
+            }
+        }
+        // Tokens are added to the chat's watchlist, what it already watches is kept
+        let mut added = 0;
+        let mut failed = Vec::new();
+        let tokens = preset
+            .watch
+            .iter()
+            .map(|token| (token, Subscription::Watch))
+            .chain(preset.follow.iter().map(|token| (token, Subscription::Follow)));
+        for (token_address, subscription) in tokens {
+            let subscribed = match subscription {
+                Subscription::Watch => self.watchlist.watch(chat_id, token_address).await,
+                Subscription::Follow => self.watchlist.follow(chat_id, token_address).await,
+            };
+            match subscribed {
+                Ok(_) => added += 1,
+                Err(e) => failed.push(format!(
+                    "• <code>{}</code> {}",
+                    self.format_short_address(token_address),
+                    escape(&e.to_string())
+                )),
+            }
+        }
+
+        let mut message = format!(
+            "✅ Settings <code>{}</code> imported: filters, buy buttons and currency replaced, \
+            {} token(s) added to your watchlist.",
+            code, added
+        );
+        if !failed.is_empty() {
+            let _ = write!(message, "\n\nNot added:\n{}", failed.join("\n"));
+        }
+        self.send_message(chat_id, &message, None).await
+    }
+
+    async fn handle_papertrade_command(&self, args: &[&str], chat_id: i64) -> Result<(), Error> {
+        let message = match args {
+            ["buy", token_address, usd] => {
+                match usd.trim_start_matches('$').parse::<f64>() {
+                    Ok(usd) => match self.paper_trading.buy(chat_id, token_address, usd).await {
+                        Ok(position) => format!(
+                            "📝 Paper position #{} opened: ${:.2} of <b>${}</b> at the current quote.\n\n\
+                            Track it with /portfolio, sell it with /papertrade close {}",
+                            position.id,
+                            position.usd_amount,
+                            escape(&position.symbol),
+                            position.id
+                        ),
+                        Err(e) => format!("❌ {}", escape(&e.to_string())),
+                    },
+                    Err(_) => "❌ Usage: <code>/papertrade buy &lt;token_address&gt; &lt;usd&gt;</code>".to_string(),
+                }
+            }
+            ["close", id] => match id.trim_start_matches('#').parse::<i64>() {
+                Ok(id) => match self.paper_trading.close(chat_id, id).await {
+                    Ok((position, Some(valuation))) => format!(
+                        "{} Paper position #{} in <b>${}</b> closed at ${:.2}: {:+.2} USD ({:+.1}%)",
+                        if valuation.pnl >= 0f64 { "🟢" } else { "🔴" },
+                        position.id,
+                        escape(&position.symbol),
+                        valuation.value,
+                        valuation.pnl,
+                        valuation.pnl_pct
+                    ),
+                    Ok((position, None)) => format!(
+                        "⚪️ Paper position #{} in <b>${}</b> closed, \
+                        it was opened without a price so it has no PnL",
+                        position.id,
+                        escape(&position.symbol)
+                    ),
+                    Err(e) => format!("❌ {}", escape(&e.to_string())),
+                },
+                Err(_) => "❌ Usage: <code>/papertrade close &lt;id&gt;</code>".to_string(),
+            },
+            _ => "📝 Paper trading simulates buys at the current quote, no funds involved.\n\n\
+                <code>/papertrade buy &lt;token_address&gt; &lt;usd&gt;</code> opens a position\n\
+                <code>/papertrade close &lt;id&gt;</code> sells it\n\
+                /portfolio shows your PnL"
+                .to_string(),
+        };
+        self.send_message(chat_id, &message, None).await
+    }
+
+    async fn handle_portfolio_command(&self, chat_id: i64) -> Result<(), Error> {
+        let portfolio = match self.paper_trading.portfolio(chat_id).await {
+            Ok(portfolio) => portfolio,
+            Err(e) => {
+                eprintln!("Failed to value portfolio of {}: {}", chat_id, e);
+                return self
+                    .send_message(chat_id, "❌ Failed to load your portfolio, try again later.", None)
+                    .await;
+            }
+        };
+        if portfolio.positions.is_empty() && portfolio.realized_pnl == 0f64 {
+            return self
+                .send_message(
+                    chat_id,
+                    "📭 No paper positions yet. Open one with /papertrade buy &lt;token&gt; &lt;usd&gt;.",
+                    None,
+                )
+                .await;
+        }
+
+        let mut invested = 0f64;
+        let mut value = 0f64;
+        let lines: Vec<String> = portfolio
+            .positions
+            .iter()
+            .map(|valued| {
+                let position = &valued.position;
+                let header = format!(
+                    "#{} <b>${}</b> ${:.2}",
+                    position.id,
+                    escape(&position.symbol),
+                    position.usd_amount
+                );
+                match valued.valuation {
+                    Some(valuation) => {
+                        invested += position.usd_amount;
+                        value += valuation.value;
+                        format!(
+                            "{} {} → ${:.2} ({:+.1}%){}",
+                            if valuation.pnl >= 0f64 { "🟢" } else { "🔴" },
+                            header,
+                            valuation.value,
+                            valuation.pnl_pct,
+                            if valued.stale { " ⏳" } else { "" }
+                        )
+                    }
+                    None if has_entry_price(position) => format!("⚪️ {} → no quote", header),
+                    None => format!("⚪️ {} → opened without a price", header),
+                }
+            })
+            .collect();
+
+        let mut message = format!("📝 ====== <b>PAPER PORTFOLIO</b> ====== 📝\n\n{}", lines.join("\n"));
+        if invested > 0f64 {
+            message.push_str(&format!(
+                "\n\n<b>Unrealized:</b> {:+.2} USD ({:+.1}%)",
+                value - invested,
+                (value - invested) * 100f64 / invested
+            ));
+        }
+        if portfolio.realized_pnl != 0f64 {
+            message.push_str(&format!("\n<b>Realized:</b> {:+.2} USD", portfolio.realized_pnl));
+        }
+        if portfolio.positions.iter().any(|valued| valued.stale && valued.valuation.is_some()) {
+            message.push_str("\n\n⏳ Quote unavailable, last known price used");
+        }
+        self.send_message(chat_id, &message, None).await
+    }
+
+    async fn handle_wallet_command(&self, args: &[&str], chat_id: i64) -> Result<(), Error> {
+        let trader = match &self.trader {
+            Some(trader) => trader,
+            None => {
+                self.send_message(chat_id, "🔒 Trading is not enabled on this bot.", None)
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        match args {
+            ["link", account_address, private_key] => {
+                // Group chat ids are negative, keys may only be shared in a private chat
+                if chat_id < 0 {
+                    self.send_message(
                         chat_id,
-                        "🛑 Token alerts stopped. Use /start to resume.",
+                        "⛔️ Link your wallet in a private chat with the bot, never in a group.",
                         None,
                     )
                     .await?;
+                    return Ok(());
+                }
+                let message = match trader.link_wallet(chat_id, account_address, private_key).await {
+                    Ok(address) => format!(
+                        "🔐 Wallet {} linked. Your key is stored encrypted and your message was deleted.",
+                        self.format_short_address(&address)
+                    ),
+                    Err(e) => format!("❌ {}", escape(&e.to_string())),
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            ["unlink"] => {
+                let message = match trader.unlink_wallet(chat_id).await {
+                    Ok(true) => "🗑 Wallet unlinked and key removed.".to_string(),
+                    Ok(false) => "ℹ️ No wallet is linked.".to_string(),
+                    Err(e) => format!("❌ {}", escape(&e.to_string())),
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            _ => {
+                let linked = match trader.wallet(chat_id).await {
+                    Some(address) => format!("👛 <b>Linked wallet:</b> {}\n\n", address),
+                    None => String::new(),
+                };
+                self.send_message(
+                    chat_id,
+                    &format!(
+                        "{}Wallet Commands:\n\n\
+                        /wallet link &lt;account&gt; &lt;private key&gt; - Link a trading account (private chat only)\n\
+                        /wallet unlink - Remove your account and key",
+                        linked
+                    ),
+                    None,
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_buy_command(&self, args: &[&str], chat_id: i64) -> Result<(), Error> {
+        let trader = match &self.trader {
+            Some(trader) => trader,
+            None => {
+                self.send_message(chat_id, "🔒 Trading is not enabled on this bot.", None)
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let (token_address, amount) = match args {
+            [token_address, amount] => (token_address, amount),
+            _ => {
+                self.send_message(
+                    chat_id,
+                    "❌ Invalid command format.\nUsage: <code>/buy &lt;token_address&gt; &lt;amount&gt;</code>",
+                    None,
+                )
+                .await?;
+                return Ok(());
+            }
+        };
+
+        match trader.prepare_buy(chat_id, token_address, amount).await {
+            Ok(trade) => {
+                let format_amount = |raw: &num_bigint::BigUint| {
+                    format_large_number(&raw.to_string())
+                        .and_then(|amount| format_number(&amount))
+                        .unwrap_or_default()
+                };
+                let message = format!(
+                    "🛒 ====== <b>CONFIRM BUY</b> ====== 🛒\n\n\
+                    <b>Token:</b> {}\n\
+                    <b>You pay:</b> {} {} (~${:.2})\n\
+                    <b>You receive:</b> ~{}\n\
+                    <b>Minimum received:</b> {} ({:.2}% slippage)\n\
+                    <b>Est. gas:</b> ${:.4}\n\n\
+                    {}Quote valid for 60 seconds.",
+                    self.format_short_address(&trade.token_address),
+                    trade.sell_amount,
+                    trade.sell_symbol,
+                    trade.sell_amount_usd,
+                    format_amount(&trade.buy_amount),
+                    format_amount(&trade.min_buy_amount),
+                    trade.slippage_bps as f64 / 100f64,
+                    trade.gas_fees_usd,
+                    if trader.is_dry_run() {
+                        "🧪 Dry-run mode: confirming only simulates the swap.\n"
+                    } else {
+                        ""
+                    },
+                );
+                let keyboard = json!({
+                    "inline_keyboard": [[
+                        {
+                            "text": "✅ Confirm",
+                            "callback_data": CallbackAction::ConfirmTrade.encode()
+                        },
+                        {
+                            "text": "❌ Cancel",
+                            "callback_data": CallbackAction::CancelTrade.encode()
+                        }
+                    ]]
+                });
+                self.send_message_with_markup(chat_id, &message, keyboard, None)
+                    .await?;
+            }
+            Err(e) => {
+                self.send_message(
+                    chat_id,
+                    &format!("❌ Could not quote trade: {}", escape(&e.to_string())),
+                    None,
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_admin_command(&self, args: &[&str], chat_id: i64) -> Result<(), Error> {
+        match args {
+            ["ab", "start"] => {
+                self.experiment.start().await;
+                let note = if self.config.public_url.is_none() {
+                    "\n⚠️ PUBLIC_URL is not set, clicks cannot be tracked."
+                } else {
+                    ""
+                };
+                self.send_message(
+                    chat_id,
+                    &format!("🧪 A/B experiment started, subscribers re-split.{}", note),
+                    None,
+                )
+                .await?;
+            }
+            ["ab", "stop"] => {
+                self.experiment.stop().await;
+                self.send_message(chat_id, "🧪 A/B experiment stopped, all alerts use variant A.", None)
+                    .await?;
+            }
+            ["ab", "set", variant, copy @ ..] if !copy.is_empty() => {
+                // Usage: /admin ab set <A|B> <headline> | <call to action>
+                let copy = copy.join(" ");
+                match (Variant::parse(variant), copy.split_once('|')) {
+                    (Some(variant), Some((headline, call_to_action))) => {
+                        self.experiment
+                            .set_copy(
+                                variant,
+                                VariantCopy {
+                                    headline: headline.trim().to_string(),
+                                    call_to_action: call_to_action.trim().to_string(),
+                                },
+                            )
+                            .await;
+                        self.send_message(
+                            chat_id,
+                            &format!("✅ Variant {} copy updated.", variant.as_str()),
+                            None,
+                        )
+                        .await?;
+                    }
+                    _ => {
+                        self.send_message(
+                            chat_id,
+                            "❌ Usage: /admin ab set &lt;A|B&gt; &lt;headline&gt; | &lt;call to action&gt;",
+                            None,
+                        )
+                        .await?;
+                    }
+                }
+            }
+            ["template"] => {
+                let templates = self.templates.list().await;
+                let message = if templates.is_empty() {
+                    "🧩 No card templates, every chat gets the full card with the experiment copy."
+                        .to_string()
                 } else {
-                    self.send_message(
-                        chat_id,
-                        "❗️ You are not receiving any alerts. Use /start to begin.",
-                        None,
-                    )
+                    let lines: Vec<String> = templates
+                        .iter()
+                        .map(|(kind, variant, template)| {
+                            format!(
+                                "• <b>{} {}</b>: {}{}{}",
+                                kind.as_str(),
+                                variant.as_str(),
+                                template.layout.as_str(),
+                                template
+                                    .headline
+                                    .as_ref()
+                                    .map(|headline| format!("\n  Headline: {}", headline))
+                                    .unwrap_or_default(),
+                                template
+                                    .call_to_action
+                                    .as_ref()
+                                    .map(|call_to_action| format!("\n  Call to action: {}", call_to_action))
+                                    .unwrap_or_default(),
+                            )
+                        })
+                        .collect();
+                    format!("🧩 <b>Card templates</b>\n\n{}", lines.join("\n"))
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            ["template", kind, variant, "reset"] => {
+                let message = match (ChatKind::parse(kind), Variant::parse(variant)) {
+                    (Some(kind), Some(variant)) => match self.templates.remove(kind, variant).await {
+                        Ok(true) => format!(
+                            "✅ {} chats get the default card for variant {}.",
+                            kind.as_str(),
+                            variant.as_str()
+                        ),
+                        Ok(false) => "ℹ️ There was no template to reset.".to_string(),
+                        Err(e) => {
+                            eprintln!("Failed to reset card template: {}", e);
+                            "❌ Could not reset the template.".to_string()
+                        }
+                    },
+                    _ => "❌ Usage: /admin template &lt;private|group|channel&gt; &lt;A|B&gt; reset"
+                        .to_string(),
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            ["template", kind, variant, layout, copy @ ..] => {
+                // Usage: /admin template <kind> <A|B> <full|compact> [headline] [| call to action]
+                let parsed = (ChatKind::parse(kind), Variant::parse(variant), CardLayout::parse(layout));
+                let message = match parsed {
+                    (Some(kind), Some(variant), Some(layout)) => {
+                        let copy = copy.join(" ");
+                        let (headline, call_to_action) = copy.split_once('|').unwrap_or((copy.as_str(), ""));
+                        let text = |text: &str| Some(text.trim().to_string()).filter(|text| !text.is_empty());
+                        let template = CardTemplate {
+                            layout,
+                            headline: text(headline),
+                            call_to_action: text(call_to_action),
+                        };
+                        match self.templates.set(kind, variant, template).await {
+                            Ok(()) => format!(
+                                "✅ {} chats get the {} card for variant {}.",
+                                kind.as_str(),
+                                layout.as_str(),
+                                variant.as_str()
+                            ),
+                            Err(e) => {
+                                eprintln!("Failed to save card template: {}", e);
+                                "❌ Could not save the template.".to_string()
+                            }
+                        }
+                    }
+                    _ => "❌ Usage: /admin template &lt;private|group|channel&gt; &lt;A|B&gt; \
+                        &lt;full|compact&gt; [headline] [| call to action]"
+                        .to_string(),
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            ["maintenance", "cancel"] => {
+                if !self.cancel_maintenance().await {
+                    self.send_message(chat_id, "ℹ️ No maintenance window is scheduled.", None)
+                        .await?;
+                    return Ok(());
+                }
+                self.send_message(chat_id, "🛠 Maintenance window cleared.", None)
                     .await?;
+            }
+            ["maintenance", start_in, duration] => {
+                // Usage: /admin maintenance <starts in minutes> <duration minutes>
+                match (start_in.parse::<u64>(), duration.parse::<u64>()) {
+                    (Ok(start_in), Ok(duration)) if duration > 0 => {
+                        self.schedule_maintenance(start_in, duration).await?;
+                        self.send_message(chat_id, "✅ Maintenance window scheduled.", None)
+                            .await?;
+                    }
+                    _ => {
+                        self.send_message(
+                            chat_id,
+                            "❌ Usage: /admin maintenance &lt;starts in minutes&gt; &lt;duration minutes&gt;",
+                            None,
+                        )
+                        .await?;
+                    }
                 }
             }
-            Some("/status") => {
-                let active_users = self.active_users.read().await;
-                let status = if active_users.get(&chat_id).copied().unwrap_or(false) {
-                    "🟢 You are currently receiving token alerts."
+            ["blacklist", "add", address, reason @ ..] => {
+                let message = match canonicalize_address(address) {
+                    Some(address) => {
+                        let reason = if reason.is_empty() { None } else { Some(reason.join(" ")) };
+                        match self.storage.add_to_blacklist(&address, reason.as_deref()).await {
+                            Ok(_) => format!("🚫 {} blacklisted, its launches won't be broadcast.", address),
+                            Err(e) => format!("❌ Failed to update blacklist: {}", escape(&e.to_string())),
+                        }
+                    }
+                    None => "❌ Invalid address".to_string(),
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            ["blacklist", "remove", address] => {
+                let message = match canonicalize_address(address) {
+                    Some(address) => match self.storage.remove_from_blacklist(&address).await {
+                        Ok(true) => format!("✅ {} removed from the blacklist.", address),
+                        Ok(false) => "ℹ️ That address is not blacklisted.".to_string(),
+                        Err(e) => format!("❌ Failed to update blacklist: {}", escape(&e.to_string())),
+                    },
+                    None => "❌ Invalid address".to_string(),
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            ["blacklist"] => {
+                let message = match self.storage.blacklist().await {
+                    Ok(entries) if entries.is_empty() => "📭 The blacklist is empty.".to_string(),
+                    Ok(entries) => {
+                        let lines: Vec<String> = entries
+                            .iter()
+                            .map(|entry| match &entry.reason {
+                                Some(reason) => format!("• <code>{}</code> {}", entry.address, escape(reason)),
+                                None => format!("• <code>{}</code>", entry.address),
+                            })
+                            .collect();
+                        format!("🚫 ====== <b>BLACKLIST</b> ====== 🚫\n\n{}", lines.join("\n"))
+                    }
+                    Err(e) => format!("❌ Failed to load blacklist: {}", escape(&e.to_string())),
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            ["preview", token_address] => {
+                // Same enrichment as a live launch, minus the pending block which only matters at launch
+                match self.fetch_token_info(token_address, true).await {
+                    Ok((info, holders)) => {
+                        let context = find_clones(&self.storage, &info).await;
+                        let alert = LaunchAlert { info, holders, context };
+                        self.send_launch_preview(chat_id, &alert).await?;
+                    }
+                    Err(_) => {
+                        self.send_message(chat_id, "Error fetching token details ⁉️", None)
+                            .await?;
+                    }
+                }
+            }
+            ["cache"] => {
+                let lines: Vec<String> = cache_stats()
+                    .iter()
+                    .map(|stats| {
+                        format!(
+                            "• <code>{}</code> {}/{} entries, {} hits, {} misses ({:.0}%), {} evicted",
+                            stats.name,
+                            stats.entries,
+                            stats.max_entries,
+                            stats.hits,
+                            stats.misses,
+                            stats.hit_rate(),
+                            stats.evictions
+                        )
+                    })
+                    .collect();
+                let message = if lines.is_empty() {
+                    "📭 No cache has been used yet.".to_string()
                 } else {
-                    "🔴 You are not receiving token alerts.\nUse /start to begin."
+                    format!("🗄 ====== <b>CACHES</b> ====== 🗄\n\n{}", lines.join("\n"))
                 };
-                self.send_message(chat_id, status, None).await?;
+                self.send_message(chat_id, &message, None).await?;
             }
-            Some("/help") => {
+            ["cache", "flush", name @ ..] => {
+                let message = match flush_caches(name.first().copied()) {
+                    0 => "❗️ No cache by that name, see /admin cache.".to_string(),
+                    flushed => format!("🧹 Flushed {} cache(s).", flushed),
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            ["broadcast", announcement @ ..] if !announcement.is_empty() => {
+                let text = format!("📢 {}", escape(&announcement.join(" ")));
+                let recipients: Vec<i64> = self
+                    .active_users
+                    .read()
+                    .await
+                    .iter()
+                    .filter(|(_, active)| **active)
+                    .map(|(chat_id, _)| *chat_id)
+                    .collect();
+                let mut sent = 0;
+                for recipient in &recipients {
+                    match self.broadcast_message(*recipient, &text, None).await {
+                        Ok(()) => sent += 1,
+                        Err(e) => eprintln!("Failed to send announcement to {}: {:?}", recipient, e),
+                    }
+                }
                 self.send_message(
                     chat_id,
-                    "Available Commands:\n\n\
-                    /start - Start receiving token alerts\n\
-                    /stop - Stop receiving token alerts\n\
-                    /status - Check your alert status\n\
-                    /help - Show this help message\n\
-                    /spot <wallet> <token> - Get token position for a wallet\n\
-                    /peek <wallet> - Check token position\n\
-                    /sniQ <token> - Get info on a particular token\n\n\
-                    ℹ️ You'll receive alerts for new tokens as they're detected.",
+                    &format!("📢 Announcement sent to {} of {} subscribers.", sent, recipients.len()),
                     None,
                 )
                 .await?;
             }
-            Some("/peek") => {
-                match (parts.get(1)) {
-                    Some(wallet_address) => {
-                        match get_account_holdings(wallet_address).await {
-                            Ok(holdings) => {
-                                let message = format!("
-                                        💼 ====== *BAG CHECK* ====== 💼\n\n\
-                                        👛 *Wallet:* \n{}\n\n\
-                                        💼 *PORTFOLIO*\n\
-                                        🎯 *Total Memecoins:* {}\n\n\
-                                        💡 *TIP:* Check token position\n\
-                                        *Use: /spot <wallet> <token>*
-                                ",
-                                    holdings.account_address,
-                                    holdings.total_tokens
-                                );
-                                self.send_message(chat_id, &message, None).await?;
-                            }
-                            Err(e) => {
-                                let error_message = format!("Error peeking into wallet ⁉️");
-                                self.send_message(chat_id, &error_message, None).await?;
-                            }
+            ["mute", target] | ["unmute", target] => {
+                let mute = args[0] == "mute";
+                let message = match target.parse::<i64>() {
+                    Ok(target) if mute && self.is_admin(target) => "❌ Admins can't be muted.".to_string(),
+                    Ok(target) if mute => match self.storage.mute_chat(target).await {
+                        Ok(()) => {
+                            self.muted.write().await.insert(target);
+                            format!("🔇 Chat {} muted, it gets no messages and is ignored.", target)
                         }
+                        Err(e) => format!("❌ Failed to mute chat: {}", escape(&e.to_string())),
                     },
-                    None => {
-                        let error_message = format!("Invalid parameters ❗️");
-                        self.send_message(chat_id, &error_message, None).await?;
-                    },
-                }
-            }
-            Some("/sniQ") => {
-                match (parts.get(1)) {
-                    Some(token_address) => {
-                        match aggregate_info(token_address).await {
-                            Ok(response) => {
-                                let message = format!("
-                                             ⚡ ====== *SNIQ RADAR* ======⚡\n\
-                                        \n\
-                                        *Token:* ${}\n\
-                                        *Name:* {}\n\
-                                        *Contract:* {}\n\n\
-                                        📊 *METRICS*\n\
-                                        💰 *Price:* ${}\n\
-                                        📈 *MCap:* ${}\n\
-                                        💫 *Supply:* ${}\n\
-                                        👥 *Holders:* {}\n\
-                                        💧 *LP:* ${}\n\n\
-                                        🛡 *SECURITY CHECK*\n\
-                                        🔒 *LP Status:* Locked Forever\n\
-                                        ✅ *Contract:* Verified\n\n\
-                                        🔗 *QUICK LINKS*\n\
-                                        🎯 *Trade:* {}\n\
-                                        🔍 *Explorer:* {}\n\
-                                        ",
-                                        response.0.symbol,
-                                        response.0.name,
-                                        response.0.address,
-                                        response.0.price,
-                                        self.format_number(&response.0.market_cap).unwrap(),
-                                        self.format_number(&self.format_large_number(&response.0.total_supply).unwrap()).unwrap(),
-                                        response.1.category,
-                                        self.format_number(&response.0.usd_dex_liquidity).unwrap(),
-                                        self.config.dex_url,
-                                        format!("{}/{}",self.config.explorer_url, response.0.address )
-                                    );
-                                self.send_message(chat_id,  &message, None).await;
-                            },
-                            Err(error) => {
-                                let error_message = format!("Error fetching token details ⁉️");
-                                self.send_message(chat_id, &error_message, None).await?;
+                    Ok(target) => match self.storage.unmute_chat(target).await {
+                        Ok(_) => {
+                            if self.muted.write().await.remove(&target) {
+                                format!("🔊 Chat {} unmuted.", target)
+                            } else {
+                                "ℹ️ That chat is not muted.".to_string()
                             }
                         }
+                        Err(e) => format!("❌ Failed to unmute chat: {}", escape(&e.to_string())),
                     },
-                    None => {
-                        let error_message = format!("Invalid parameters ❗️");
-                        self.send_message(chat_id, &error_message, None).await?;
-                    }              
+                    Err(_) => format!("❌ Usage: /admin {} &lt;chat_id&gt;", args[0]),
+                };
+                self.send_message(chat_id, &message, None).await?;
+            }
+            ["stats"] => {
+                let stats = self.experiment.stats().await;
+                let status = if self.experiment.is_enabled().await {
+                    "running"
+                } else {
+                    "stopped"
+                };
+                let (known_chats, subscribers) = {
+                    let active_users = self.active_users.read().await;
+                    (active_users.len(), active_users.values().filter(|active| **active).count())
+                };
+                let muted = self.muted.read().await.len();
+                let mut counters = Vec::new();
+                for name in [EVENTS_PROCESSED, EVENT_ERRORS, ALERTS_DELIVERED, DELIVERY_FAILURES] {
+                    counters.push(self.storage.counter(name).await.unwrap_or_else(|e| {
+                        eprintln!("Failed to read counter {}: {}", name, e);
+                        0
+                    }));
                 }
+                let message = format!(
+                    "📊 ====== <b>ADMIN STATS</b> ====== 📊\n\n\
+                    👥 <b>Subscribers:</b> {} active of {} chats, {} muted\n\
+                    ⚙️ <b>Events:</b> {} processed, {} failed\n\
+                    📨 <b>Broadcasts:</b> {} delivered, {} failed\n\
+                    ⚡️ <b>Load breaker:</b> {}, {} events queued\n\n\
+                    🧪 <b>A/B experiment:</b> {}\n\
+                    <b>A:</b> {} sent, {} clicks ({:.1}% CTR)\n\
+                    <b>B:</b> {} sent, {} clicks ({:.1}% CTR)",
+                    subscribers,
+                    known_chats,
+                    muted,
+                    counters[0],
+                    counters[1],
+                    counters[2],
+                    counters[3],
+                    if self.notifications_paused() { "tripped" } else { "closed" },
+                    self.circuit.queue_depth(),
+                    status,
+                    stats[0].impressions,
+                    stats[0].clicks,
+                    stats[0].click_through_rate(),
+                    stats[1].impressions,
+                    stats[1].clicks,
+                    stats[1].click_through_rate(),
+                );
+                self.send_message(chat_id, &message, None).await?;
+            }
+            _ => {
+                self.send_message(
+                    chat_id,
+                    "Admin Commands:\n\n\
+                    /admin stats - Show bot and experiment stats\n\
+                    /admin broadcast &lt;message&gt; - Send an announcement to every subscriber\n\
+                    /admin mute &lt;chat_id&gt; - Ignore a chat and stop messaging it\n\
+                    /admin unmute &lt;chat_id&gt; - Lift a mute\n\
+                    /admin ab start - Start an A/B experiment on alert copy\n\
+                    /admin ab stop - Stop the running experiment\n\
+                    /admin ab set &lt;A|B&gt; &lt;headline&gt; | &lt;call to action&gt; - Edit variant copy\n\
+                    /admin template - List card templates per chat kind\n\
+                    /admin template &lt;private|group|channel&gt; &lt;A|B&gt; &lt;full|compact&gt; \
+                    [headline] [| call to action] - Set the card of a chat kind\n\
+                    /admin template &lt;kind&gt; &lt;A|B&gt; reset - Back to the default card\n\
+                    /admin maintenance &lt;starts in min&gt; &lt;duration min&gt; - Schedule downtime\n\
+                    /admin maintenance cancel - Cancel or end the maintenance window\n\
+                    /admin blacklist - List blacklisted tokens and deployers\n\
+                    /admin blacklist add &lt;address&gt; [reason] - Stop broadcasting their launches\n\
+                    /admin blacklist remove &lt;address&gt; - Lift a blacklist entry\n\
+                    /admin preview &lt;token&gt; - Render a token's launch card to you only\n\
+                    /admin cache - Show cache sizes and hit rates\n\
+                    /admin cache flush [name] - Empty every cache or only the named one",
+                    None,
+                )
+                .await?;
             }
-            
-            _ => {}
         }
         Ok(())
     }
@@ -583,7 +4077,7 @@ impl TelegramBot {
         let params = json!({
             "offset": offset,
             "timeout": 30,
-            "allowed_updates": ["message", "callback_query"]
+            "allowed_updates": ["message", "callback_query", "channel_post", "my_chat_member", "inline_query"]
         });
 
         let response = self.client.post(&url).json(&params).send().await?;
@@ -612,7 +4106,7 @@ impl TelegramBot {
         let mut request = json!({
             "chat_id": chat_id,
             "text": text,
-            "parse_mode": "Markdown"
+            "parse_mode": "HTML"
         });
 
         if let Some(reply_id) = reply_to {
@@ -623,7 +4117,9 @@ impl TelegramBot {
         }
 
         let url = format!("{}/sendMessage", self.base_url);
-        let response = self.client.post(&url).json(&request).send().await?;
+        let response = self
+            .post(chat_id, Priority::Reply, || self.client.post(&url).json(&request))
+            .await?;
 
         if !response.status().is_success() {
             eprintln!("Failed to send message: {:?}", response.text().await?);
@@ -632,6 +4128,298 @@ impl TelegramBot {
         Ok(())
     }
 
+    // Alerts and other messages nobody asked for right now, they wait behind replies
+    async fn broadcast_message(
+        &self,
+        chat_id: i64,
+        text: &str,
+        reply_markup: Option<serde_json::Value>,
+    ) -> Result<(), Error> {
+        self.broadcast_reply(chat_id, text, reply_markup, None).await.map(|_| ())
+    }
+
+    // Broadcasts a message, optionally as a reply, and returns its id once delivered
+    async fn broadcast_reply(
+        &self,
+        chat_id: i64,
+        text: &str,
+        reply_markup: Option<serde_json::Value>,
+        reply_to: Option<i64>,
+    ) -> Result<Option<i64>, Error> {
+        if self.is_muted(chat_id).await {
+            return Ok(None);
+        }
+        let mut request = json!({
+            "chat_id": chat_id,
+            "text": text,
+            "parse_mode": "HTML"
+        });
+
+        if let Some(reply_id) = reply_to {
+            let request = request.as_object_mut().unwrap();
+            request.insert("reply_to_message_id".to_string(), json!(reply_id));
+            // The radar alert may have been deleted since, the launch still goes out
+            request.insert("allow_sending_without_reply".to_string(), json!(true));
+        }
+
+        if let Some(reply_markup) = reply_markup {
+            request
+                .as_object_mut()
+                .unwrap()
+                .insert("reply_markup".to_string(), reply_markup);
+        }
+
+        let url = format!("{}/sendMessage", self.base_url);
+        let response = self
+            .post(chat_id, Priority::Broadcast, || self.client.post(&url).json(&request))
+            .await?;
+
+        let (counter, message_id) = if response.status().is_success() {
+            let sent: serde_json::Value = response.json().await?;
+            (ALERTS_DELIVERED, sent["result"]["message_id"].as_i64())
+        } else {
+            eprintln!("Failed to broadcast message: {:?}", response.text().await?);
+            (DELIVERY_FAILURES, None)
+        };
+        if let Err(e) = self.storage.increment_counter(counter, 1).await {
+            eprintln!("Failed to count {}: {}", counter, e);
+        }
+
+        Ok(message_id)
+    }
+
+    // Edits an earlier alert into a broadcast, false when Telegram refused the edit
+    async fn broadcast_edit(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        text: &str,
+        reply_markup: &serde_json::Value,
+    ) -> Result<bool, Error> {
+        if self.is_muted(chat_id).await {
+            return Ok(true);
+        }
+        let request = json!({
+            "chat_id": chat_id,
+            "message_id": message_id,
+            "text": text,
+            "parse_mode": "HTML",
+            "reply_markup": reply_markup
+        });
+
+        let url = format!("{}/editMessageText", self.base_url);
+        let response = self
+            .post(chat_id, Priority::Broadcast, || self.client.post(&url).json(&request))
+            .await?;
+
+        if !response.status().is_success() {
+            eprintln!("Failed to edit alert into a launch: {:?}", response.text().await?);
+            return Ok(false);
+        }
+        if let Err(e) = self.storage.increment_counter(ALERTS_DELIVERED, 1).await {
+            eprintln!("Failed to count {}: {}", ALERTS_DELIVERED, e);
+        }
+
+        Ok(true)
+    }
+
+    // Sends a request to a chat once the outbox lets it through, retrying after 429s
+    async fn post(
+        &self,
+        chat_id: i64,
+        priority: Priority,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, Error> {
+        let mut attempt = 1;
+        loop {
+            self.outbox.acquire(chat_id, priority).await;
+            #[cfg(feature = "chaos")]
+            let response = if CHAOS.should_fail(Fault::TelegramRateLimit) {
+                chaos::telegram_rate_limited()
+            } else {
+                request().send().await?
+            };
+            #[cfg(not(feature = "chaos"))]
+            let response = request().send().await?;
+            if response.status() != StatusCode::TOO_MANY_REQUESTS || attempt >= MAX_SEND_ATTEMPTS {
+                return Ok(response);
+            }
+
+            let wait = retry_after(&response.text().await?).unwrap_or(1);
+            eprintln!("Rate limited by Telegram sending to {}, retrying in {}s", chat_id, wait);
+            self.outbox.pause(Duration::from_secs(wait)).await;
+            attempt += 1;
+        }
+    }
+
+    // Uploads the chart unless `file_id` holds the id Telegram gave a previous upload of it
+    async fn send_chart(
+        &self,
+        chat_id: i64,
+        png: &[u8],
+        caption: &str,
+        file_id: &mut Option<String>,
+        priority: Priority,
+    ) -> Result<(), Error> {
+        let url = format!("{}/sendPhoto", self.base_url);
+        let response = match file_id.as_deref() {
+            Some(file_id) => {
+                let request = json!({
+                    "chat_id": chat_id,
+                    "photo": file_id,
+                    "caption": caption,
+                    "parse_mode": "HTML"
+                });
+                self.post(chat_id, priority, || self.client.post(&url).json(&request))
+                    .await?
+            }
+            None => {
+                // A form can only be sent once, so every attempt builds its own
+                let form = || {
+                    Form::new()
+                        .text("chat_id", chat_id.to_string())
+                        .text("caption", caption.to_string())
+                        .text("parse_mode", "HTML")
+                        .part("photo", Part::bytes(png.to_vec()).file_name("chart.png"))
+                };
+                self.post(chat_id, priority, || self.client.post(&url).multipart(form()))
+                    .await?
+            }
+        };
+
+        if !response.status().is_success() {
+            eprintln!("Failed to send chart: {:?}", response.text().await?);
+        } else if file_id.is_none() {
+            // Sizes are listed smallest first, the last one is the original
+            let sent: serde_json::Value = response.json().await?;
+            *file_id = sent["result"]["photo"]
+                .as_array()
+                .and_then(|sizes| sizes.last())
+                .and_then(|size| size["file_id"].as_str())
+                .map(str::to_string);
+        }
+
+        Ok(())
+    }
+
+    async fn answer_callback_query(&self, query_id: &str, text: Option<&str>) -> Result<(), Error> {
+        let mut request = json!({ "callback_query_id": query_id });
+
+        if let Some(text) = text {
+            request
+                .as_object_mut()
+                .unwrap()
+                .insert("text".to_string(), json!(text));
+        }
+
+        let url = format!("{}/answerCallbackQuery", self.base_url);
+        let response = self.client.post(&url).json(&request).send().await?;
+
+        if !response.status().is_success() {
+            eprintln!("Failed to answer callback query: {:?}", response.text().await?);
+        }
+
+        Ok(())
+    }
+
+    async fn answer_inline_query(
+        &self,
+        query_id: &str,
+        results: Vec<serde_json::Value>,
+    ) -> Result<(), Error> {
+        let request = json!({
+            "inline_query_id": query_id,
+            "results": results,
+            "cache_time": INLINE_CACHE_SECS,
+            // Answers depend on the asking user's preferences
+            "is_personal": true
+        });
+
+        let url = format!("{}/answerInlineQuery", self.base_url);
+        let response = self.client.post(&url).json(&request).send().await?;
+
+        if !response.status().is_success() {
+            eprintln!("Failed to answer inline query: {:?}", response.text().await?);
+        }
+
+        Ok(())
+    }
+
+    // Username of the bot, None when Telegram didn't answer with one
+    async fn get_me(&self) -> Result<Option<String>, Error> {
+        let url = format!("{}/getMe", self.base_url);
+        let response: serde_json::Value = self.client.get(&url).send().await?.json().await?;
+        Ok(response["result"]["username"].as_str().map(str::to_string))
+    }
+
+    async fn is_chat_admin(&self, chat_id: i64, user_id: i64) -> Result<bool, Error> {
+        let request = json!({
+            "chat_id": chat_id,
+            "user_id": user_id
+        });
+
+        let url = format!("{}/getChatMember", self.base_url);
+        // Not a message, so it doesn't wait for the chat's send interval
+        let response = self.client.post(&url).json(&request).send().await?;
+
+        #[derive(Deserialize)]
+        struct MemberResponse {
+            result: Option<ChatMember>,
+        }
+
+        if !response.status().is_success() {
+            eprintln!("Failed to read chat member: {:?}", response.text().await?);
+            return Ok(false);
+        }
+        let member: MemberResponse = response.json().await?;
+        Ok(member.result.is_some_and(|member| is_admin_status(&member.status)))
+    }
+
+    async fn delete_message(&self, chat_id: i64, message_id: i64) -> Result<(), Error> {
+        let request = json!({
+            "chat_id": chat_id,
+            "message_id": message_id
+        });
+
+        let url = format!("{}/deleteMessage", self.base_url);
+        let response = self
+            .post(chat_id, Priority::Reply, || self.client.post(&url).json(&request))
+            .await?;
+
+        if !response.status().is_success() {
+            eprintln!("Failed to delete message: {:?}", response.text().await?);
+        }
+
+        Ok(())
+    }
+
+    async fn edit_message_text(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        text: &str,
+        reply_markup: serde_json::Value,
+    ) -> Result<(), Error> {
+        let request = json!({
+            "chat_id": chat_id,
+            "message_id": message_id,
+            "text": text,
+            "parse_mode": "HTML",
+            "reply_markup": reply_markup
+        });
+
+        let url = format!("{}/editMessageText", self.base_url);
+        let response = self
+            .post(chat_id, Priority::Reply, || self.client.post(&url).json(&request))
+            .await?;
+
+        if !response.status().is_success() {
+            eprintln!("Failed to edit message: {:?}", response.text().await?);
+        }
+
+        Ok(())
+    }
+
     async fn send_message_with_markup(
         &self,
         chat_id: i64,
@@ -642,7 +4430,7 @@ impl TelegramBot {
         let mut request = json!({
             "chat_id": chat_id,
             "text": text,
-            "parse_mode": "Markdown",
+            "parse_mode": "HTML",
             "reply_markup": reply_markup
         });
 
@@ -654,7 +4442,9 @@ impl TelegramBot {
         }
 
         let url = format!("{}/sendMessage", self.base_url);
-        let response = self.client.post(&url).json(&request).send().await?;
+        let response = self
+            .post(chat_id, Priority::Reply, || self.client.post(&url).json(&request))
+            .await?;
 
         if !response.status().is_success() {
             eprintln!(
@@ -666,3 +4456,33 @@ impl TelegramBot {
         Ok(())
     }
 }
+
+// Launch alerts reach Telegram through the notifier dispatcher like every other channel
+#[async_trait]
+impl Notifier for TelegramBot {
+    fn name(&self) -> String {
+        "telegram".to_string()
+    }
+
+    async fn notify_launch(&self, _fingerprint: &str, alert: &LaunchAlert) -> Result<(), NotifyError> {
+        Ok(self.broadcast_event(alert.clone()).await?)
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+// Compact "how long ago" label for list views
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s ago", secs),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
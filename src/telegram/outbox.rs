@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+// Chats remembered for their per-chat interval before the map is swept
+const SWEEP_THRESHOLD: usize = 10_000;
+// How long a broadcast waits before checking again whether replies are still queued
+const YIELD_TO_REPLIES: Duration = Duration::from_millis(25);
+
+// Replies to commands go out before broadcasts waiting for the same capacity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    Reply,
+    Broadcast,
+}
+
+// Telegram allows about 30 messages per second overall, one per second in a chat and 20 per minute
+// in a group or channel
+#[derive(Clone)]
+pub struct OutboxConfig {
+    messages_per_sec: f64,
+    chat_interval: Duration,
+    group_interval: Duration,
+}
+
+impl OutboxConfig {
+    pub fn new() -> Self {
+        let messages_per_sec = std::env::var("TELEGRAM_MESSAGES_PER_SEC")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|rate| *rate > 0f64)
+            .unwrap_or(25f64);
+        let chat_interval_ms = std::env::var("TELEGRAM_CHAT_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(1000);
+        let group_interval_ms = std::env::var("TELEGRAM_GROUP_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(3000);
+
+        Self {
+            messages_per_sec,
+            chat_interval: Duration::from_millis(chat_interval_ms),
+            group_interval: Duration::from_millis(group_interval_ms),
+        }
+    }
+}
+
+// Why a message can't go out yet, and for how long
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Wait {
+    // The chat's own interval, other chats can be written to meanwhile
+    Chat(Duration),
+    // Overall capacity, shared with every other message
+    Capacity(Duration),
+}
+
+impl Wait {
+    fn duration(self) -> Duration {
+        match self {
+            Wait::Chat(wait) | Wait::Capacity(wait) => wait,
+        }
+    }
+}
+
+// Token bucket shared by every outgoing message, plus when each chat can be written to next
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    // Set from a 429's retry_after, nothing is sent before it
+    paused_until: Option<Instant>,
+    next_in_chat: HashMap<i64, Instant>,
+}
+
+impl Bucket {
+    fn new(capacity: f64, now: Instant) -> Self {
+        Self {
+            tokens: capacity,
+            refilled_at: now,
+            paused_until: None,
+            next_in_chat: HashMap::new(),
+        }
+    }
+
+    // Takes a token for `chat_id`, or says what to wait for before trying again
+    fn try_take(&mut self, chat_id: i64, now: Instant, config: &OutboxConfig) -> Result<(), Wait> {
+        if let Some(paused_until) = self.paused_until {
+            if paused_until > now {
+                return Err(Wait::Capacity(paused_until - now));
+            }
+            self.paused_until = None;
+        }
+
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        // A second worth of messages can go out in a burst
+        self.tokens = (self.tokens + elapsed * config.messages_per_sec).min(config.messages_per_sec);
+        self.refilled_at = now;
+
+        let chat_wait = self
+            .next_in_chat
+            .get(&chat_id)
+            .map_or(Duration::ZERO, |next| next.saturating_duration_since(now));
+        let token_wait = if self.tokens >= 1f64 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1f64 - self.tokens) / config.messages_per_sec)
+        };
+        if chat_wait > token_wait {
+            return Err(Wait::Chat(chat_wait));
+        }
+        if !token_wait.is_zero() {
+            return Err(Wait::Capacity(token_wait));
+        }
+
+        self.tokens -= 1f64;
+        if self.next_in_chat.len() >= SWEEP_THRESHOLD {
+            self.next_in_chat.retain(|_, next| *next > now);
+        }
+        // Group and channel ids are negative
+        let interval = if chat_id < 0 { config.group_interval } else { config.chat_interval };
+        self.next_in_chat.insert(chat_id, now + interval);
+        Ok(())
+    }
+}
+
+// Counts a reply as queued until it is dropped, so a cancelled acquire doesn't hold broadcasts
+// back forever
+struct QueuedReply<'a>(&'a AtomicUsize);
+
+impl<'a> QueuedReply<'a> {
+    fn new(queued: &'a AtomicUsize) -> Self {
+        queued.fetch_add(1, Ordering::SeqCst);
+        Self(queued)
+    }
+}
+
+impl Drop for QueuedReply<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// Paces messages to Telegram so bursts of alerts don't end in 429s
+pub struct Outbox {
+    config: OutboxConfig,
+    bucket: Mutex<Bucket>,
+    queued_replies: AtomicUsize,
+}
+
+impl Outbox {
+    pub fn new(config: OutboxConfig) -> Self {
+        Self {
+            bucket: Mutex::new(Bucket::new(config.messages_per_sec, Instant::now())),
+            config,
+            queued_replies: AtomicUsize::new(0),
+        }
+    }
+
+    // Waits until a message can be sent to `chat_id`. A reply only holds broadcasts back while it
+    // waits for overall capacity, not while its own chat is on its interval
+    pub async fn acquire(&self, chat_id: i64, priority: Priority) {
+        let mut queued = None;
+        loop {
+            let wait = if priority == Priority::Broadcast && self.queued_replies.load(Ordering::SeqCst) > 0 {
+                Err(Wait::Capacity(YIELD_TO_REPLIES))
+            } else {
+                self.bucket.lock().await.try_take(chat_id, Instant::now(), &self.config)
+            };
+            match wait {
+                Ok(()) => break,
+                Err(wait) => {
+                    if priority == Priority::Reply {
+                        match wait {
+                            Wait::Capacity(_) => {
+                                queued.get_or_insert_with(|| QueuedReply::new(&self.queued_replies));
+                            }
+                            Wait::Chat(_) => queued = None,
+                        }
+                    }
+                    tokio::time::sleep(wait.duration()).await
+                }
+            }
+        }
+    }
+
+    // Holds every message back after Telegram answered with a 429
+    pub async fn pause(&self, retry_after: Duration) {
+        let until = Instant::now() + retry_after;
+        let mut bucket = self.bucket.lock().await;
+        if bucket.paused_until.is_none_or(|paused_until| paused_until < until) {
+            bucket.paused_until = Some(until);
+        }
+    }
+}
+
+// retry_after of a 429 response body, in seconds
+pub fn retry_after(body: &str) -> Option<u64> {
+    serde_json::from_str::<serde_json::Value>(body).ok()?["parameters"]["retry_after"].as_u64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_limits_overall_and_per_chat_rate() {
+        let config = OutboxConfig {
+            messages_per_sec: 2f64,
+            chat_interval: Duration::from_secs(1),
+            group_interval: Duration::from_secs(3),
+        };
+        let start = Instant::now();
+        let mut bucket = Bucket::new(config.messages_per_sec, start);
+
+        assert!(bucket.try_take(1, start, &config).is_ok());
+        // The same chat has to wait for its interval, another one doesn't
+        assert_eq!(bucket.try_take(1, start, &config), Err(Wait::Chat(Duration::from_secs(1))));
+        assert!(bucket.try_take(2, start, &config).is_ok());
+        // The burst is spent, the next token comes half a second later
+        assert_eq!(
+            bucket.try_take(3, start, &config),
+            Err(Wait::Capacity(Duration::from_millis(500)))
+        );
+        assert!(bucket.try_take(3, start + Duration::from_millis(500), &config).is_ok());
+
+        bucket.paused_until = Some(start + Duration::from_secs(5));
+        assert_eq!(
+            bucket.try_take(4, start + Duration::from_secs(2), &config),
+            Err(Wait::Capacity(Duration::from_secs(3)))
+        );
+        assert!(bucket.try_take(4, start + Duration::from_secs(5), &config).is_ok());
+
+        // Groups get a slower pace
+        let later = start + Duration::from_secs(10);
+        assert!(bucket.try_take(-100, later, &config).is_ok());
+        assert_eq!(
+            bucket.try_take(-100, later + Duration::from_secs(1), &config),
+            Err(Wait::Chat(Duration::from_secs(2)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_reply_leaves_the_queue() {
+        let outbox = Outbox::new(OutboxConfig {
+            messages_per_sec: 1f64,
+            chat_interval: Duration::from_secs(60),
+            group_interval: Duration::from_secs(60),
+        });
+        outbox.acquire(1, Priority::Reply).await;
+
+        // The chat is on its interval, so the second reply waits until it is timed out
+        let waiting = tokio::time::timeout(Duration::from_millis(50), outbox.acquire(1, Priority::Reply));
+        assert!(waiting.await.is_err());
+        assert_eq!(outbox.queued_replies.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_reply_on_its_chat_interval_lets_broadcasts_through() {
+        let outbox = Outbox::new(OutboxConfig {
+            messages_per_sec: 10f64,
+            chat_interval: Duration::from_secs(60),
+            group_interval: Duration::from_secs(60),
+        });
+        outbox.acquire(1, Priority::Reply).await;
+
+        let reply = outbox.acquire(1, Priority::Reply);
+        let broadcast = outbox.acquire(2, Priority::Broadcast);
+        tokio::select! {
+            _ = reply => panic!("the reply should still be on its chat interval"),
+            _ = broadcast => {}
+            _ = tokio::time::sleep(Duration::from_secs(1)) => panic!("the broadcast yielded to the reply"),
+        }
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let body = r#"{"ok":false,"error_code":429,"parameters":{"retry_after":7}}"#;
+        assert_eq!(retry_after(body), Some(7));
+        assert_eq!(retry_after(r#"{"ok":false}"#), None);
+    }
+}
@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use crate::utils::currency::Currency;
+
+// How often a digest chat receives its summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestPeriod {
+    Hourly,
+    Daily,
+}
+
+impl DigestPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestPeriod::Hourly => "hourly",
+            DigestPeriod::Daily => "daily",
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        match self {
+            DigestPeriod::Hourly => Duration::from_secs(3600),
+            DigestPeriod::Daily => Duration::from_secs(24 * 3600),
+        }
+    }
+}
+
+// Whether launches are pushed as they happen or batched into periodic summaries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeliveryMode {
+    #[default]
+    Instant,
+    Digest(DigestPeriod),
+}
+
+impl DeliveryMode {
+    // Parses `/delivery instant`, `/delivery digest daily` or the `/delivery hourly` shorthand
+    pub fn parse(args: &[&str]) -> Option<Self> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_lowercase()).collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            ["instant"] => Some(DeliveryMode::Instant),
+            ["hourly"] | ["digest", "hourly"] | ["digest"] => Some(DeliveryMode::Digest(DigestPeriod::Hourly)),
+            ["daily"] | ["digest", "daily"] => Some(DeliveryMode::Digest(DigestPeriod::Daily)),
+            _ => None,
+        }
+    }
+}
+
+// Per-chat filters applied to launch alerts before they are sent, and how they are rendered
+#[derive(Debug, Clone, Default)]
+pub struct UserPreferences {
+    pub min_liquidity_usd: Option<f64>,
+    pub max_team_allocation_pct: Option<f64>,
+    pub min_holders: Option<usize>,
+    // USD amounts of the quick buy buttons, the defaults when unset
+    pub buy_amounts: Option<Vec<u64>>,
+    pub delivery: DeliveryMode,
+    // Whether the chat is told about tokens as soon as they are created, before any liquidity
+    pub prelaunch_radar: bool,
+    // Unit market caps and liquidity are shown in
+    pub currency: Currency,
+}
+
+// Holder thresholds mirroring the buckets shown in alerts
+pub const HOLDER_TIERS: [usize; 4] = [10, 20, 50, 100];
+pub const DEFAULT_BUY_AMOUNTS: [u64; 3] = [10, 50, 100];
+// Telegram keyboards get cramped past three buttons in a row
+pub const MAX_BUY_BUTTONS: usize = 3;
+pub const MAX_BUY_AMOUNT_USD: u64 = 1_000_000;
+
+impl UserPreferences {
+    pub fn is_default(&self) -> bool {
+        self.min_liquidity_usd.is_none()
+            && self.max_team_allocation_pct.is_none()
+            && self.min_holders.is_none()
+            && self.buy_amounts.is_none()
+            && self.delivery == DeliveryMode::Instant
+            && !self.prelaunch_radar
+            && self.currency == Currency::Usd
+    }
+
+    pub fn buy_amounts(&self) -> &[u64] {
+        self.buy_amounts.as_deref().unwrap_or(&DEFAULT_BUY_AMOUNTS)
+    }
+
+    pub fn matches(&self, liquidity_usd: f64, team_allocation_pct: f64, holders: usize) -> bool {
+        if let Some(min_liquidity) = self.min_liquidity_usd {
+            if liquidity_usd < min_liquidity {
+                return false;
+            }
+        }
+        if let Some(max_team_allocation) = self.max_team_allocation_pct {
+            if team_allocation_pct > max_team_allocation {
+                return false;
+            }
+        }
+        if let Some(min_holders) = self.min_holders {
+            if holders < min_holders {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+// Parses `/setbuttons 25 100 500` style amounts, sorted and deduplicated
+pub fn parse_buy_amounts(args: &[&str]) -> Option<Vec<u64>> {
+    if args.is_empty() || args.len() > MAX_BUY_BUTTONS {
+        return None;
+    }
+    let mut amounts = args
+        .iter()
+        .map(|arg| arg.trim_start_matches('$').parse::<u64>().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    if amounts.iter().any(|amount| *amount == 0 || *amount > MAX_BUY_AMOUNT_USD) {
+        return None;
+    }
+    amounts.sort_unstable();
+    amounts.dedup();
+    Some(amounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferences_filter_launches() {
+        let preferences = UserPreferences {
+            min_liquidity_usd: Some(1000f64),
+            max_team_allocation_pct: Some(5f64),
+            min_holders: None,
+            buy_amounts: None,
+            delivery: DeliveryMode::Instant,
+            prelaunch_radar: false,
+            currency: Currency::Usd,
+        };
+        assert!(preferences.matches(2500f64, 2f64, 3));
+        assert!(!preferences.matches(500f64, 2f64, 3));
+        assert!(!preferences.matches(2500f64, 10f64, 3));
+        assert!(UserPreferences::default().matches(0f64, 100f64, 0));
+    }
+
+    #[test]
+    fn test_parse_buy_amounts() {
+        assert_eq!(parse_buy_amounts(&["500", "$25", "100"]), Some(vec![25, 100, 500]));
+        assert_eq!(parse_buy_amounts(&["20", "20"]), Some(vec![20]));
+        assert_eq!(parse_buy_amounts(&["0"]), None);
+        assert_eq!(parse_buy_amounts(&["1", "2", "3", "4"]), None);
+        assert_eq!(parse_buy_amounts(&["ten"]), None);
+        assert_eq!(UserPreferences::default().buy_amounts(), &DEFAULT_BUY_AMOUNTS);
+    }
+
+    #[test]
+    fn test_parse_delivery_mode() {
+        assert_eq!(DeliveryMode::parse(&["instant"]), Some(DeliveryMode::Instant));
+        assert_eq!(
+            DeliveryMode::parse(&["Digest", "daily"]),
+            Some(DeliveryMode::Digest(DigestPeriod::Daily))
+        );
+        assert_eq!(DeliveryMode::parse(&["hourly"]), Some(DeliveryMode::Digest(DigestPeriod::Hourly)));
+        assert_eq!(DeliveryMode::parse(&["weekly"]), None);
+        assert_eq!(DeliveryMode::parse(&[]), None);
+    }
+}
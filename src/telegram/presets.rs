@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::preferences::UserPreferences;
+use crate::utils::currency::Currency;
+use crate::watcher::watchlist::Subscription;
+
+// Hex characters of a preset code, short enough to type and unlikely to collide
+const CODE_LENGTH: usize = 8;
+
+// Filters, buy buttons, currency and watchlist of a chat, shared with /share_settings. Delivery
+// and the pre-launch radar are left out, they're about how a chat wants to be reached
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SettingsPreset {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_liquidity_usd: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_team_allocation_pct: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_holders: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buy_amounts: Option<Vec<u64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    // Token addresses, sorted so the same settings always give the same code
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watch: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub follow: Vec<String>,
+}
+
+impl SettingsPreset {
+    pub fn from_chat(preferences: Option<&UserPreferences>, watched: &[(String, String, Subscription)]) -> Self {
+        let mut preset = match preferences {
+            Some(preferences) => SettingsPreset {
+                min_liquidity_usd: preferences.min_liquidity_usd,
+                max_team_allocation_pct: preferences.max_team_allocation_pct,
+                min_holders: preferences.min_holders,
+                buy_amounts: preferences.buy_amounts.clone(),
+                currency: Some(preferences.currency)
+                    .filter(|currency| *currency != Currency::Usd)
+                    .map(|currency| currency.as_str().to_string()),
+                ..Default::default()
+            },
+            None => SettingsPreset::default(),
+        };
+        for (address, _, subscription) in watched {
+            match subscription {
+                Subscription::Watch => preset.watch.push(address.clone()),
+                Subscription::Follow => preset.follow.push(address.clone()),
+            }
+        }
+        preset.watch.sort();
+        preset.follow.sort();
+        preset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == SettingsPreset::default()
+    }
+
+    // Replaces the chat's filters, buy buttons and currency with the preset's
+    pub fn apply(&self, preferences: &mut UserPreferences) {
+        preferences.min_liquidity_usd = self.min_liquidity_usd;
+        preferences.max_team_allocation_pct = self.max_team_allocation_pct;
+        preferences.min_holders = self.min_holders;
+        preferences.buy_amounts = self.buy_amounts.clone();
+        preferences.currency = self.currency.as_deref().and_then(Currency::parse).unwrap_or_default();
+    }
+
+    pub fn to_payload(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn from_payload(payload: &str) -> Option<Self> {
+        serde_json::from_str(payload).ok()
+    }
+
+    // Derived from the settings, chats sharing the same settings share the code
+    pub fn code(&self) -> String {
+        let digest = Sha256::digest(self.to_payload().as_bytes());
+        hex::encode(digest)[..CODE_LENGTH].to_uppercase()
+    }
+}
+
+// Codes are typed by hand, so case and stray characters are forgiven
+pub fn normalize_code(code: &str) -> Option<String> {
+    let code: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase();
+    (code.len() == CODE_LENGTH && code.chars().all(|c| c.is_ascii_hexdigit())).then_some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_round_trip() {
+        let preferences = UserPreferences {
+            min_liquidity_usd: Some(5000f64),
+            min_holders: Some(20),
+            currency: Currency::Eth,
+            prelaunch_radar: true,
+            ..Default::default()
+        };
+        let watched = vec![
+            ("0xb".to_string(), "B".to_string(), Subscription::Watch),
+            ("0xa".to_string(), "A".to_string(), Subscription::Watch),
+            ("0xc".to_string(), "C".to_string(), Subscription::Follow),
+        ];
+        let preset = SettingsPreset::from_chat(Some(&preferences), &watched);
+        assert_eq!(preset.watch, vec!["0xa", "0xb"]);
+        assert_eq!(preset.follow, vec!["0xc"]);
+
+        let imported = SettingsPreset::from_payload(&preset.to_payload()).unwrap();
+        assert_eq!(imported, preset);
+        assert_eq!(imported.code(), preset.code());
+
+        let mut applied = UserPreferences {
+            max_team_allocation_pct: Some(5f64),
+            ..Default::default()
+        };
+        imported.apply(&mut applied);
+        assert_eq!(applied.min_liquidity_usd, Some(5000f64));
+        assert_eq!(applied.max_team_allocation_pct, None);
+        assert_eq!(applied.currency, Currency::Eth);
+        // The radar is the importing chat's own business
+        assert!(!applied.prelaunch_radar);
+
+        assert!(SettingsPreset::from_chat(None, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_normalize_code() {
+        assert_eq!(normalize_code("ab12-cd34"), Some("AB12CD34".to_string()));
+        assert_eq!(normalize_code("`AB12CD34`"), Some("AB12CD34".to_string()));
+        assert_eq!(normalize_code("AB12"), None);
+        assert_eq!(normalize_code("ZZ12CD34"), None);
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tokio::sync::RwLock;
+
+use super::format_age;
+
+// Tokens rarely sit on the radar for long, alerts older than this aren't replied to anymore
+const RADAR_ALERT_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+// How a launch alert follows up on the radar alert a chat got about the same token
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LaunchLinking {
+    // Sent as a reply to the radar alert
+    Reply,
+    // The radar alert is edited into the launch alert, no new message is sent
+    Edit,
+    // Launch alerts are sent on their own
+    Off,
+}
+
+impl LaunchLinking {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "reply" => Some(LaunchLinking::Reply),
+            "edit" => Some(LaunchLinking::Edit),
+            "off" | "none" => Some(LaunchLinking::Off),
+            _ => None,
+        }
+    }
+}
+
+// Pre-launch radar alerts sent to each chat, so the launch alert can reply to the one it follows up
+pub struct RadarAlerts {
+    sent: RwLock<HashMap<(i64, String), (i64, Instant)>>,
+}
+
+impl RadarAlerts {
+    pub fn new() -> Self {
+        Self {
+            sent: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn record(&self, chat_id: i64, token_address: &str, message_id: i64) {
+        let mut sent = self.sent.write().await;
+        sent.retain(|_, (_, sent_at)| sent_at.elapsed() < RADAR_ALERT_TTL);
+        sent.insert((chat_id, token_address.to_string()), (message_id, Instant::now()));
+    }
+
+    // Takes the radar alert a chat got about a token, a launch only follows up on it once
+    pub async fn take(&self, chat_id: i64, token_address: &str) -> Option<i64> {
+        self.sent
+            .write()
+            .await
+            .remove(&(chat_id, token_address.to_string()))
+            .filter(|(_, sent_at)| sent_at.elapsed() < RADAR_ALERT_TTL)
+            .map(|(message_id, _)| message_id)
+    }
+}
+
+// Context line about when a launched token was created. Only chats on the radar were told about
+// it back then, the others just learn it was created before liquidity was added
+pub fn created_line(created_at: u64, same_block: bool, on_radar: bool, now: u64) -> String {
+    if same_block {
+        return "🌱 Created and launched in the same block".to_string();
+    }
+    let age = format_age(now.saturating_sub(created_at));
+    if on_radar {
+        format!("🌱 Created {}, it was on your pre-launch /radar", age)
+    } else {
+        format!("🌱 Created {}, before liquidity was added", age)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_launch_replies_to_radar_alert_once() {
+        let alerts = RadarAlerts::new();
+        alerts.record(1, "0xa", 42).await;
+        alerts.record(2, "0xb", 7).await;
+
+        assert_eq!(alerts.take(1, "0xb").await, None);
+        assert_eq!(alerts.take(1, "0xa").await, Some(42));
+        assert_eq!(alerts.take(1, "0xa").await, None);
+        assert_eq!(alerts.take(2, "0xb").await, Some(7));
+    }
+
+    #[test]
+    fn test_parse_launch_linking() {
+        assert_eq!(LaunchLinking::parse("reply"), Some(LaunchLinking::Reply));
+        assert_eq!(LaunchLinking::parse(" Edit "), Some(LaunchLinking::Edit));
+        assert_eq!(LaunchLinking::parse("off"), Some(LaunchLinking::Off));
+        assert_eq!(LaunchLinking::parse("thread"), None);
+    }
+
+    #[test]
+    fn test_radar_wording_only_for_subscribers() {
+        let now = 10_000;
+        assert!(created_line(now - 600, false, true, now).contains("/radar"));
+        assert!(!created_line(now - 600, false, false, now).contains("/radar"));
+        assert_eq!(
+            created_line(now, true, true, now),
+            created_line(now, true, false, now)
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use tokio::sync::RwLock;
+
+// Matches offered when a search is ambiguous
+pub const MAX_SEARCH_MATCHES: usize = 5;
+// Longest query looked up, anything longer isn't a symbol or a name
+const MAX_QUERY_LEN: usize = 64;
+
+// A launched token as it can be searched
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedToken {
+    pub address: String,
+    pub symbol: String,
+    pub name: String,
+    pub launched_at: u64,
+}
+
+// A token matching a search, higher scores match better
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    pub token: IndexedToken,
+    pub score: u32,
+}
+
+const EXACT_SYMBOL: u32 = 100;
+const EXACT_NAME: u32 = 90;
+
+impl SearchMatch {
+    pub fn is_exact(&self) -> bool {
+        self.score >= EXACT_NAME
+    }
+}
+
+// Symbols and names of launched tokens, loaded from storage at startup and kept up to date as
+// launches are processed so /sniQ can look tokens up without their address
+#[derive(Default)]
+pub struct TokenIndex {
+    tokens: RwLock<HashMap<String, IndexedToken>>,
+}
+
+impl TokenIndex {
+    pub async fn replace(&self, tokens: Vec<IndexedToken>) {
+        *self.tokens.write().await = tokens
+            .into_iter()
+            .map(|token| (token.address.clone(), token))
+            .collect();
+    }
+
+    pub async fn insert(&self, token: IndexedToken) {
+        self.tokens.write().await.insert(token.address.clone(), token);
+    }
+
+    pub async fn count(&self) -> usize {
+        self.tokens.read().await.len()
+    }
+
+    // Best matches first, newest launches first among equal matches
+    pub async fn search(&self, query: &str, limit: usize) -> Vec<SearchMatch> {
+        let query = normalize(query);
+        if query.is_empty() || query.len() > MAX_QUERY_LEN {
+            return Vec::new();
+        }
+        let mut matches: Vec<SearchMatch> = self
+            .tokens
+            .read()
+            .await
+            .values()
+            .filter_map(|token| {
+                Some(SearchMatch {
+                    score: score(&query, token)?,
+                    token: token.clone(),
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then(b.token.launched_at.cmp(&a.token.launched_at))
+        });
+        matches.truncate(limit);
+        matches
+    }
+}
+
+// The match to show right away, None when the user has to pick one
+pub fn single_match(matches: &[SearchMatch]) -> Option<&SearchMatch> {
+    match matches {
+        [only] => Some(only),
+        [first, second, ..] if first.is_exact() && !second.is_exact() => Some(first),
+        _ => None,
+    }
+}
+
+// Lowercase without the $ of symbols, the quotes around names or repeated spaces
+fn normalize(text: &str) -> String {
+    text.trim()
+        .trim_matches(|c| matches!(c, '"' | '\'' | '“' | '”'))
+        .trim_start_matches('$')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn score(query: &str, token: &IndexedToken) -> Option<u32> {
+    let symbol = normalize(&token.symbol);
+    let name = normalize(&token.name);
+    if symbol == query {
+        return Some(EXACT_SYMBOL);
+    }
+    if name == query {
+        return Some(EXACT_NAME);
+    }
+    if symbol.starts_with(query) {
+        return Some(70);
+    }
+    if name.starts_with(query) || name.split(' ').any(|word| word.starts_with(query)) {
+        return Some(60);
+    }
+    if name.contains(query) {
+        return Some(50);
+    }
+    // Typos, one edit allowed for short queries and two for long ones
+    let allowed = match query.chars().count() {
+        0..=3 => return None,
+        4..=7 => 1,
+        _ => 2,
+    };
+    [(&symbol, 40), (&name, 30)]
+        .into_iter()
+        .filter_map(|(candidate, base)| {
+            let distance = edit_distance(query, candidate);
+            (distance <= allowed).then(|| base - distance as u32 * 10)
+        })
+        .max()
+}
+
+// Levenshtein distance over chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(address: &str, symbol: &str, name: &str, launched_at: u64) -> IndexedToken {
+        IndexedToken {
+            address: address.to_string(),
+            symbol: symbol.to_string(),
+            name: name.to_string(),
+            launched_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_and_disambiguates() {
+        let index = TokenIndex::default();
+        index
+            .replace(vec![
+                token("0x1", "PEPE", "Pepe Starknet", 10),
+                token("0x2", "PEPE2", "Pepe Two", 20),
+                token("0x3", "DOGE", "Doge Starknet", 30),
+            ])
+            .await;
+
+        let matches = index.search("$pepe", MAX_SEARCH_MATCHES).await;
+        assert_eq!(matches.len(), 2);
+        assert_eq!(single_match(&matches).map(|m| m.token.address.as_str()), Some("0x1"));
+
+        let matches = index.search("\"Doge  starknet\"", MAX_SEARCH_MATCHES).await;
+        assert_eq!(single_match(&matches).map(|m| m.token.address.as_str()), Some("0x3"));
+
+        // Both names mention Starknet, the user picks
+        let matches = index.search("starknet", MAX_SEARCH_MATCHES).await;
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].token.address, "0x3");
+        assert_eq!(single_match(&matches), None);
+
+        // Typos still find the token, as long as the query is long enough to tell
+        let matches = index.search("doeg starknet", MAX_SEARCH_MATCHES).await;
+        assert_eq!(matches.len(), 1);
+        assert_eq!(index.search("pepw", MAX_SEARCH_MATCHES).await[0].token.address, "0x1");
+        assert!(index.search("dgoe", MAX_SEARCH_MATCHES).await.is_empty());
+        assert!(index.search("zzz", MAX_SEARCH_MATCHES).await.is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("pepe", "pepe"), 0);
+        assert_eq!(edit_distance("pepe", "pepo"), 1);
+        assert_eq!(edit_distance("doge", "dgoe"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use super::experiment::{Variant, VariantCopy};
+use crate::storage::{Storage, StorageError};
+
+// Chats by audience, each kind can get its own launch card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChatKind {
+    Private,
+    Group,
+    Channel,
+}
+
+impl ChatKind {
+    // From the type Telegram gives a chat, supergroups are groups
+    pub fn from_chat_type(chat_type: &str) -> Self {
+        match chat_type {
+            "private" => ChatKind::Private,
+            "channel" => ChatKind::Channel,
+            _ => ChatKind::Group,
+        }
+    }
+
+    // Guess for chats not heard from since startup, only private chats have positive ids
+    fn from_chat_id(chat_id: i64) -> Self {
+        if chat_id > 0 {
+            ChatKind::Private
+        } else {
+            ChatKind::Group
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChatKind::Private => "private",
+            ChatKind::Group => "group",
+            ChatKind::Channel => "channel",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "private" | "dm" => Some(ChatKind::Private),
+            "group" => Some(ChatKind::Group),
+            "channel" => Some(ChatKind::Channel),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CardLayout {
+    #[default]
+    Full,
+    // Price, liquidity and risk only, for chats where long cards are noise
+    Compact,
+}
+
+impl CardLayout {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CardLayout::Full => "full",
+            CardLayout::Compact => "compact",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "full" => Some(CardLayout::Full),
+            "compact" => Some(CardLayout::Compact),
+            _ => None,
+        }
+    }
+}
+
+// Launch card of one chat kind and variant, copy left unset is the experiment's
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CardTemplate {
+    pub layout: CardLayout,
+    #[serde(default)]
+    pub headline: Option<String>,
+    #[serde(default)]
+    pub call_to_action: Option<String>,
+}
+
+impl CardTemplate {
+    pub fn apply(&self, copy: VariantCopy) -> VariantCopy {
+        VariantCopy {
+            headline: self.headline.clone().unwrap_or(copy.headline),
+            call_to_action: self.call_to_action.clone().unwrap_or(copy.call_to_action),
+        }
+    }
+}
+
+// Card templates per chat kind and experiment variant, kept in storage so admins can try
+// different cards in channels, groups and DMs without a redeploy
+pub struct CardTemplates {
+    storage: Arc<Storage>,
+    templates: RwLock<HashMap<(ChatKind, Variant), CardTemplate>>,
+    // Kind of each chat heard from, kept in storage as channels can't be told from groups by id
+    kinds: RwLock<HashMap<i64, ChatKind>>,
+}
+
+impl CardTemplates {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self {
+            storage,
+            templates: RwLock::new(HashMap::new()),
+            kinds: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn load(&self) -> Result<usize, StorageError> {
+        let mut templates = HashMap::new();
+        for (kind, variant, payload) in self.storage.card_templates().await? {
+            let template = serde_json::from_str::<CardTemplate>(&payload).ok();
+            match (ChatKind::parse(&kind), Variant::parse(&variant), template) {
+                (Some(kind), Some(variant), Some(template)) => {
+                    templates.insert((kind, variant), template);
+                }
+                _ => eprintln!("Skipping invalid card template for {} {}", kind, variant),
+            }
+        }
+        let count = templates.len();
+        *self.templates.write().await = templates;
+
+        let kinds = self
+            .storage
+            .chat_kinds()
+            .await?
+            .into_iter()
+            .filter_map(|(chat_id, kind)| ChatKind::parse(&kind).map(|kind| (chat_id, kind)))
+            .collect();
+        *self.kinds.write().await = kinds;
+        Ok(count)
+    }
+
+    pub async fn set(
+        &self,
+        kind: ChatKind,
+        variant: Variant,
+        template: CardTemplate,
+    ) -> Result<(), StorageError> {
+        let payload = serde_json::to_string(&template).unwrap_or_default();
+        self.storage
+            .save_card_template(kind.as_str(), variant.as_str(), &payload)
+            .await?;
+        self.templates.write().await.insert((kind, variant), template);
+        Ok(())
+    }
+
+    // False when the kind and variant had no override
+    pub async fn remove(&self, kind: ChatKind, variant: Variant) -> Result<bool, StorageError> {
+        let removed = self
+            .storage
+            .remove_card_template(kind.as_str(), variant.as_str())
+            .await?;
+        self.templates.write().await.remove(&(kind, variant));
+        Ok(removed)
+    }
+
+    pub async fn list(&self) -> Vec<(ChatKind, Variant, CardTemplate)> {
+        let mut templates: Vec<(ChatKind, Variant, CardTemplate)> = self
+            .templates
+            .read()
+            .await
+            .iter()
+            .map(|((kind, variant), template)| (*kind, *variant, template.clone()))
+            .collect();
+        templates.sort_by_key(|(kind, variant, _)| (kind.as_str(), variant.as_str()));
+        templates
+    }
+
+    // The stored template, or the full card with the experiment's copy
+    pub async fn template(&self, kind: ChatKind, variant: Variant) -> CardTemplate {
+        self.templates
+            .read()
+            .await
+            .get(&(kind, variant))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    // Only a chat whose kind is new or changed is written to storage
+    pub async fn record_kind(&self, chat_id: i64, kind: ChatKind) {
+        if self.kinds.write().await.insert(chat_id, kind) == Some(kind) {
+            return;
+        }
+        if let Err(e) = self.storage.save_chat_kind(chat_id, kind.as_str()).await {
+            eprintln!("Failed to save the kind of chat {}: {}", chat_id, e);
+        }
+    }
+
+    pub async fn kind_of(&self, chat_id: i64) -> ChatKind {
+        self.kinds
+            .read()
+            .await
+            .get(&chat_id)
+            .copied()
+            .unwrap_or_else(|| ChatKind::from_chat_id(chat_id))
+    }
+
+    pub async fn remove_chat(&self, chat_id: i64) {
+        if self.kinds.write().await.remove(&chat_id).is_some() {
+            if let Err(e) = self.storage.remove_chat_kind(chat_id).await {
+                eprintln!("Failed to remove the kind of chat {}: {}", chat_id, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StorageConfig;
+
+    #[tokio::test]
+    async fn test_templates_survive_reload() {
+        let storage = Arc::new(Storage::connect(StorageConfig::in_memory()).await.unwrap());
+        let templates = CardTemplates::new(Arc::clone(&storage));
+        let compact = CardTemplate {
+            layout: CardLayout::Compact,
+            headline: Some("🆕 <b>New launch</b>".to_string()),
+            call_to_action: None,
+        };
+        templates.set(ChatKind::Channel, Variant::A, compact.clone()).await.unwrap();
+
+        let reloaded = CardTemplates::new(storage);
+        assert_eq!(reloaded.load().await.unwrap(), 1);
+        assert_eq!(reloaded.template(ChatKind::Channel, Variant::A).await, compact);
+        assert_eq!(reloaded.template(ChatKind::Group, Variant::A).await, CardTemplate::default());
+
+        // Unset copy falls back to the experiment's
+        let copy = compact.apply(VariantCopy {
+            headline: "headline".to_string(),
+            call_to_action: "cta".to_string(),
+        });
+        assert_eq!((copy.headline.as_str(), copy.call_to_action.as_str()), ("🆕 <b>New launch</b>", "cta"));
+
+        assert!(reloaded.remove(ChatKind::Channel, Variant::A).await.unwrap());
+        assert!(reloaded.list().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_chat_kinds() {
+        let storage = Arc::new(Storage::connect(StorageConfig::in_memory()).await.unwrap());
+        let templates = CardTemplates::new(Arc::clone(&storage));
+        // Channels and supergroups share negative ids, only the recorded type tells them apart
+        assert_eq!(templates.kind_of(42).await, ChatKind::Private);
+        assert_eq!(templates.kind_of(-1001).await, ChatKind::Group);
+        templates.record_kind(-1001, ChatKind::from_chat_type("channel")).await;
+        templates.record_kind(-1002, ChatKind::Channel).await;
+        assert_eq!(templates.kind_of(-1001).await, ChatKind::Channel);
+
+        // A restart still knows the channel, a removed chat is forgotten
+        templates.remove_chat(-1002).await;
+        let reloaded = CardTemplates::new(storage);
+        reloaded.load().await.unwrap();
+        assert_eq!(reloaded.kind_of(-1001).await, ChatKind::Channel);
+        assert_eq!(reloaded.kind_of(-1002).await, ChatKind::Group);
+        assert_eq!(ChatKind::from_chat_type("supergroup"), ChatKind::Group);
+        assert_eq!(ChatKind::parse("DM"), Some(ChatKind::Private));
+    }
+}
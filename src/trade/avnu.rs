@@ -0,0 +1,155 @@
+use num_bigint::BigUint;
+use num_traits::Num;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use starknet::core::utils::get_selector_from_name;
+use starknet_core::types::{Call, Felt};
+
+use super::TradeError;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvnuQuote {
+    pub quote_id: String,
+    pub sell_amount: String,
+    pub buy_amount: String,
+    #[serde(default)]
+    pub sell_amount_in_usd: f64,
+    #[serde(default)]
+    pub buy_amount_in_usd: f64,
+    #[serde(default)]
+    pub gas_fees_in_usd: f64,
+}
+
+impl AvnuQuote {
+    pub fn buy_amount(&self) -> BigUint {
+        parse_hex_amount(&self.buy_amount)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AvnuCall {
+    contract_address: String,
+    entrypoint: String,
+    calldata: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BuildResponse {
+    calls: Vec<AvnuCall>,
+}
+
+// Thin client for the AVNU aggregator swap API
+pub struct AvnuClient {
+    client: Client,
+    base_url: String,
+}
+
+impl AvnuClient {
+    pub fn new(base_url: String) -> Self {
+        Self {
+            client: Client::new(),
+            base_url,
+        }
+    }
+
+    pub async fn quote(
+        &self,
+        sell_token: &str,
+        buy_token: &str,
+        sell_amount: u128,
+        // Only needed for quotes that will be executed
+        taker_address: Option<&str>,
+    ) -> Result<AvnuQuote, TradeError> {
+        let url = format!("{}/swap/v2/quotes", self.base_url);
+        let mut query = vec![
+            ("sellTokenAddress", sell_token.to_string()),
+            ("buyTokenAddress", buy_token.to_string()),
+            ("sellAmount", format!("{:#x}", sell_amount)),
+            ("size", "1".to_string()),
+        ];
+        if let Some(taker_address) = taker_address {
+            query.push(("takerAddress", taker_address.to_string()));
+        }
+        let response = self
+            .client
+            .get(&url)
+            .query(&query)
+            .send()
+            .await
+            .map_err(|e| TradeError::Quote(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(TradeError::Quote(body));
+        }
+
+        let quotes: Vec<AvnuQuote> = response
+            .json()
+            .await
+            .map_err(|e| TradeError::Quote(e.to_string()))?;
+        quotes
+            .into_iter()
+            .next()
+            .ok_or_else(|| TradeError::Quote("no route found".to_string()))
+    }
+
+    // Builds the approve + swap calls for a quote with the given slippage
+    pub async fn build_calls(
+        &self,
+        quote_id: &str,
+        taker_address: &str,
+        slippage_bps: u32,
+    ) -> Result<Vec<Call>, TradeError> {
+        let url = format!("{}/swap/v2/build", self.base_url);
+        let body = json!({
+            "quoteId": quote_id,
+            "takerAddress": taker_address,
+            "slippage": slippage_bps as f64 / 10_000f64,
+            "includeApprove": true
+        });
+        let response = self
+            .client
+            .post(&url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| TradeError::Quote(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(TradeError::Quote(body));
+        }
+
+        let build: BuildResponse = response
+            .json()
+            .await
+            .map_err(|e| TradeError::Quote(e.to_string()))?;
+
+        build
+            .calls
+            .into_iter()
+            .map(|call| {
+                let calldata = call
+                    .calldata
+                    .iter()
+                    .map(|value| Felt::from_hex(value))
+                    .collect::<Result<Vec<Felt>, _>>()
+                    .map_err(|_| TradeError::Quote("invalid calldata".to_string()))?;
+                Ok(Call {
+                    to: Felt::from_hex(&call.contract_address)
+                        .map_err(|_| TradeError::Quote("invalid call target".to_string()))?,
+                    selector: get_selector_from_name(&call.entrypoint)
+                        .map_err(|_| TradeError::Quote("invalid entrypoint".to_string()))?,
+                    calldata,
+                })
+            })
+            .collect()
+    }
+}
+
+fn parse_hex_amount(value: &str) -> BigUint {
+    BigUint::from_str_radix(value.trim_start_matches("0x"), 16).unwrap_or_default()
+}
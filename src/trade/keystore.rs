@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use starknet_core::types::Felt;
+use tokio::sync::RwLock;
+
+use super::TradeError;
+
+// AES-GCM nonces are 96 bits
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredWallet {
+    account_address: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+// Wallet material decrypted for a single trade
+pub struct WalletSecret {
+    pub account_address: Felt,
+    pub private_key: Felt,
+}
+
+// Per-chat signing keys, encrypted at rest with AES-256-GCM.
+// The chat id is bound as associated data so entries can't be swapped between users.
+pub struct Keystore {
+    path: PathBuf,
+    cipher: Aes256Gcm,
+    wallets: RwLock<HashMap<i64, StoredWallet>>,
+}
+
+impl Keystore {
+    pub fn open(path: PathBuf, secret: &str) -> Result<Self, TradeError> {
+        let key = Sha256::digest(secret.as_bytes());
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+
+        let wallets = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| TradeError::Keystore(format!("corrupt keystore: {}", e)))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(TradeError::Keystore(e.to_string())),
+        };
+
+        Ok(Self {
+            path,
+            cipher,
+            wallets: RwLock::new(wallets),
+        })
+    }
+
+    pub async fn insert(
+        &self,
+        chat_id: i64,
+        account_address: &str,
+        private_key: Felt,
+    ) -> Result<(), TradeError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &private_key.to_bytes_be(),
+                    aad: &chat_id.to_be_bytes(),
+                },
+            )
+            .map_err(|_| TradeError::Keystore("failed to encrypt key".to_string()))?;
+
+        let mut wallets = self.wallets.write().await;
+        wallets.insert(
+            chat_id,
+            StoredWallet {
+                account_address: account_address.to_string(),
+                nonce: hex::encode(nonce),
+                ciphertext: hex::encode(ciphertext),
+            },
+        );
+        self.persist(&wallets).await
+    }
+
+    pub async fn remove(&self, chat_id: i64) -> Result<bool, TradeError> {
+        let mut wallets = self.wallets.write().await;
+        let removed = wallets.remove(&chat_id).is_some();
+        if removed {
+            self.persist(&wallets).await?;
+        }
+        Ok(removed)
+    }
+
+    pub async fn account_address(&self, chat_id: i64) -> Option<String> {
+        self.wallets
+            .read()
+            .await
+            .get(&chat_id)
+            .map(|wallet| wallet.account_address.clone())
+    }
+
+    pub async fn unlock(&self, chat_id: i64) -> Result<WalletSecret, TradeError> {
+        let wallet = self
+            .wallets
+            .read()
+            .await
+            .get(&chat_id)
+            .cloned()
+            .ok_or(TradeError::NoWallet)?;
+
+        // A corrupt keystore must not reach Nonce::from_slice, which panics on any other length
+        let nonce = hex::decode(&wallet.nonce)
+            .ok()
+            .filter(|nonce| nonce.len() == NONCE_LEN)
+            .ok_or_else(|| TradeError::Keystore("invalid nonce".to_string()))?;
+        let ciphertext = hex::decode(&wallet.ciphertext)
+            .map_err(|_| TradeError::Keystore("invalid ciphertext".to_string()))?;
+        let plaintext = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: &chat_id.to_be_bytes(),
+                },
+            )
+            .map_err(|_| TradeError::Keystore("failed to decrypt key".to_string()))?;
+
+        Ok(WalletSecret {
+            account_address: Felt::from_hex(&wallet.account_address)
+                .map_err(|_| TradeError::Keystore("invalid account address".to_string()))?,
+            private_key: Felt::from_bytes_be_slice(&plaintext),
+        })
+    }
+
+    // Called with the wallets write lock held, so writes don't interleave. The keystore is replaced
+    // by renaming a fully written temp file, a crash mid-write leaves the previous one intact
+    async fn persist(&self, wallets: &HashMap<i64, StoredWallet>) -> Result<(), TradeError> {
+        let contents = serde_json::to_string_pretty(wallets)
+            .map_err(|e| TradeError::Keystore(e.to_string()))?;
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || write_atomically(&path, contents.as_bytes()))
+            .await
+            .map_err(|e| TradeError::Keystore(e.to_string()))?
+            .map_err(|e| TradeError::Keystore(e.to_string()))
+    }
+}
+
+fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(&temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_keystore_survives_reopen_and_rejects_corrupt_nonces() {
+        let path = std::env::temp_dir().join(format!("keystore-test-{}.json", std::process::id()));
+        let keystore = Keystore::open(path.clone(), "secret").unwrap();
+        keystore.insert(1, "0x1", Felt::from(42u64)).await.unwrap();
+
+        let reopened = Keystore::open(path.clone(), "secret").unwrap();
+        assert_eq!(reopened.unlock(1).await.unwrap().private_key, Felt::from(42u64));
+
+        reopened.wallets.write().await.get_mut(&1).unwrap().nonce = "00ff".to_string();
+        assert!(matches!(reopened.unlock(1).await, Err(TradeError::Keystore(_))));
+        fs::remove_file(&path).unwrap();
+    }
+}
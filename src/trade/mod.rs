@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use num_bigint::BigUint;
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use starknet::accounts::{Account, ExecutionEncoding, SingleOwnerAccount};
+use starknet::macros::selector;
+use starknet::providers::Provider;
+use starknet::signers::{LocalWallet, SigningKey};
+use starknet_core::types::{BlockId, BlockTag, Call, Felt};
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::constant::constants::{Token, ETHER, STRK, USDC, USDT};
+use crate::constant::network::NETWORK;
+use crate::utils::address::{canonicalize_address, to_canonical_hex};
+use crate::utils::rpc::rpc_pool;
+use avnu::{AvnuClient, AvnuQuote};
+use keystore::Keystore;
+
+pub mod avnu;
+mod keystore;
+
+// Quotes older than this must be refreshed before they can be confirmed
+const QUOTE_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Error, Debug)]
+pub enum TradeError {
+    #[error("No wallet linked, use /wallet link first")]
+    NoWallet,
+    #[error("Invalid amount")]
+    InvalidAmount,
+    #[error("Invalid address")]
+    InvalidAddress,
+    #[error("No pending trade, or the quote expired")]
+    NoPendingTrade,
+    #[error("Quote error: {0}")]
+    Quote(String),
+    #[error("Keystore error: {0}")]
+    Keystore(String),
+    #[error("TRADE_KEYSTORE_SECRET is not set")]
+    MissingSecret,
+    #[error("Execution error: {0}")]
+    Execution(String),
+    #[error("Swap rejected, the aggregator returned unexpected calls: {0}")]
+    UnexpectedCalls(String),
+}
+
+// Configuration struct for the trading subsystem
+#[derive(Clone)]
+pub struct TradeConfig {
+    dry_run: bool,
+    // The pool's primary endpoint when unset
+    rpc_url: Option<String>,
+    avnu_url: String,
+    slippage_bps: u32,
+    sell_token: Token,
+    keystore_path: PathBuf,
+    // Checked when the trader starts, so a missing secret disables trading instead of panicking
+    keystore_secret: Option<String>,
+}
+
+impl TradeConfig {
+    // Trading is opt-in: returns None unless TRADE_ENABLED is set
+    pub fn new() -> Option<Self> {
+        let enabled = std::env::var("TRADE_ENABLED")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let sell_token = match std::env::var("TRADE_SELL_TOKEN")
+            .unwrap_or_default()
+            .to_uppercase()
+            .as_str()
+        {
+            "STRK" => STRK,
+            "USDC" => USDC,
+            "USDT" => USDT,
+            _ => ETHER,
+        };
+
+        Some(Self {
+            // Stay in dry-run unless explicitly switched off
+            dry_run: std::env::var("TRADE_DRY_RUN")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(true),
+            rpc_url: std::env::var("TRADE_RPC_URL").ok().filter(|url| !url.is_empty()),
+            avnu_url: std::env::var("AVNU_API_URL")
+                .unwrap_or_else(|_| "https://starknet.api.avnu.fi".to_string()),
+            slippage_bps: std::env::var("TRADE_SLIPPAGE_BPS")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .filter(|bps| *bps < 10_000)
+                .unwrap_or(100),
+            sell_token,
+            keystore_path: std::env::var("TRADE_KEYSTORE_PATH")
+                .unwrap_or_else(|_| "keystore.json".to_string())
+                .into(),
+            keystore_secret: std::env::var("TRADE_KEYSTORE_SECRET").ok().filter(|secret| !secret.is_empty()),
+        })
+    }
+}
+
+// A quote awaiting user confirmation
+#[derive(Debug, Clone)]
+pub struct PendingTrade {
+    pub token_address: String,
+    pub sell_symbol: &'static str,
+    pub sell_amount: String,
+    pub buy_amount: BigUint,
+    pub min_buy_amount: BigUint,
+    pub slippage_bps: u32,
+    pub gas_fees_usd: f64,
+    pub sell_amount_usd: f64,
+    // Raw units of the sell token, the most the swap may be approved to spend
+    sell_amount_raw: u128,
+    quote: AvnuQuote,
+    quoted_at: Instant,
+}
+
+pub enum TradeOutcome {
+    DryRun { estimated_fee: Felt },
+    Submitted { transaction_hash: String },
+}
+
+pub struct Trader {
+    config: TradeConfig,
+    keystore: Keystore,
+    avnu: AvnuClient,
+    pending: RwLock<HashMap<i64, PendingTrade>>,
+}
+
+impl Trader {
+    pub fn new(config: TradeConfig) -> Result<Self, TradeError> {
+        let secret = config.keystore_secret.as_deref().ok_or(TradeError::MissingSecret)?;
+        let keystore = Keystore::open(config.keystore_path.clone(), secret)?;
+        let avnu = AvnuClient::new(config.avnu_url.clone());
+        Ok(Self {
+            config,
+            keystore,
+            avnu,
+            pending: RwLock::new(HashMap::new()),
+        })
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.config.dry_run
+    }
+
+    pub async fn link_wallet(
+        &self,
+        chat_id: i64,
+        account_address: &str,
+        private_key: &str,
+    ) -> Result<String, TradeError> {
+        let account_address =
+            canonicalize_address(account_address).ok_or(TradeError::InvalidAddress)?;
+        let private_key = Felt::from_hex(private_key)
+            .map_err(|_| TradeError::Keystore("invalid private key".to_string()))?;
+        self.keystore
+            .insert(chat_id, &account_address, private_key)
+            .await?;
+        Ok(account_address)
+    }
+
+    pub async fn unlink_wallet(&self, chat_id: i64) -> Result<bool, TradeError> {
+        self.pending.write().await.remove(&chat_id);
+        self.keystore.remove(chat_id).await
+    }
+
+    pub async fn wallet(&self, chat_id: i64) -> Option<String> {
+        self.keystore.account_address(chat_id).await
+    }
+
+    // Quotes a buy and parks it until the user confirms or cancels
+    pub async fn prepare_buy(
+        &self,
+        chat_id: i64,
+        token_address: &str,
+        amount: &str,
+    ) -> Result<PendingTrade, TradeError> {
+        let account_address = self.wallet(chat_id).await.ok_or(TradeError::NoWallet)?;
+        let token_address = canonicalize_address(token_address).ok_or(TradeError::InvalidAddress)?;
+        let sell_token = &self.config.sell_token;
+        let sell_amount = to_raw_amount(amount, sell_token.decimals)?;
+
+        let quote = self
+            .avnu
+            .quote(sell_token.address, &token_address, sell_amount, Some(&account_address))
+            .await?;
+        let buy_amount = quote.buy_amount();
+        let min_buy_amount = &buy_amount * BigUint::from(10_000 - self.config.slippage_bps)
+            / BigUint::from(10_000u32);
+
+        let trade = PendingTrade {
+            token_address,
+            sell_symbol: sell_token.symbol.as_str(),
+            sell_amount: amount.to_string(),
+            buy_amount,
+            min_buy_amount,
+            slippage_bps: self.config.slippage_bps,
+            gas_fees_usd: quote.gas_fees_in_usd,
+            sell_amount_usd: quote.sell_amount_in_usd,
+            sell_amount_raw: sell_amount,
+            quote,
+            quoted_at: Instant::now(),
+        };
+        self.pending.write().await.insert(chat_id, trade.clone());
+        Ok(trade)
+    }
+
+    pub async fn cancel(&self, chat_id: i64) -> Option<PendingTrade> {
+        self.pending.write().await.remove(&chat_id)
+    }
+
+    // Signs the pending swap with the user's key; only estimates fees in dry-run mode
+    pub async fn confirm(&self, chat_id: i64) -> Result<TradeOutcome, TradeError> {
+        let trade = self
+            .pending
+            .write()
+            .await
+            .remove(&chat_id)
+            .filter(|trade| trade.quoted_at.elapsed() < QUOTE_TTL)
+            .ok_or(TradeError::NoPendingTrade)?;
+
+        let wallet = self.keystore.unlock(chat_id).await?;
+        let calls = self
+            .avnu
+            .build_calls(
+                &trade.quote.quote_id,
+                &to_canonical_hex(&wallet.account_address),
+                trade.slippage_bps,
+            )
+            .await?;
+        // The calls are signed with the user's key, so only the approve and swap we asked for go through
+        check_swap_calls(
+            &calls,
+            self.config.sell_token.address,
+            trade.sell_amount_raw,
+            &NETWORK.avnu_exchange,
+        )?;
+
+        let pool =
+            rpc_pool().ok_or_else(|| TradeError::Execution("RPC pool is not initialized".to_string()))?;
+        let rpc_url = self.config.rpc_url.as_deref().unwrap_or(pool.primary_url());
+        let provider = pool
+            .client(rpc_url)
+            .map_err(|e| TradeError::Execution(e.to_string()))?;
+        let chain_id = provider
+            .chain_id()
+            .await
+            .map_err(|e| TradeError::Execution(e.to_string()))?;
+        let signer = LocalWallet::from(SigningKey::from_secret_scalar(wallet.private_key));
+        let mut account = SingleOwnerAccount::new(
+            provider,
+            signer,
+            wallet.account_address,
+            chain_id,
+            ExecutionEncoding::New,
+        );
+        account.set_block_id(BlockId::Tag(BlockTag::Pending));
+
+        let execution = account.execute_v3(calls);
+        if self.config.dry_run {
+            let estimate = execution
+                .estimate_fee()
+                .await
+                .map_err(|e| TradeError::Execution(e.to_string()))?;
+            return Ok(TradeOutcome::DryRun {
+                estimated_fee: estimate.overall_fee,
+            });
+        }
+
+        let result = execution
+            .send()
+            .await
+            .map_err(|e| TradeError::Execution(e.to_string()))?;
+        Ok(TradeOutcome::Submitted {
+            transaction_hash: to_canonical_hex(&result.transaction_hash),
+        })
+    }
+}
+
+// Accepts exactly an approve of at most `sell_amount` of the sell token to the AVNU exchange,
+// followed by a swap on that exchange
+fn check_swap_calls(
+    calls: &[Call],
+    sell_token: &str,
+    sell_amount: u128,
+    exchange: &str,
+) -> Result<(), TradeError> {
+    let unexpected = |reason: &str| Err(TradeError::UnexpectedCalls(reason.to_string()));
+    let sell_token = Felt::from_hex(sell_token).map_err(|_| TradeError::InvalidAddress)?;
+    let exchange = Felt::from_hex(exchange).map_err(|_| TradeError::InvalidAddress)?;
+    let (approve, swap) = match calls {
+        [approve, swap] => (approve, swap),
+        _ => return unexpected(&format!("{} calls instead of an approve and a swap", calls.len())),
+    };
+
+    if approve.to != sell_token || approve.selector != selector!("approve") {
+        return unexpected("the first call isn't an approve of the sell token");
+    }
+    let approved = match approve.calldata.as_slice() {
+        [spender, low, high] if *spender == exchange && *high == Felt::ZERO => low.to_u128(),
+        _ => None,
+    };
+    if approved.is_none_or(|approved| approved > sell_amount) {
+        return unexpected("the approval isn't limited to the sell amount on the exchange");
+    }
+
+    // Swap entrypoints of the AVNU exchange a built route may call
+    let swap_entrypoints = [selector!("multi_route_swap"), selector!("swap_exact_token_to")];
+    if swap.to != exchange || !swap_entrypoints.contains(&swap.selector) {
+        return unexpected("the second call isn't a swap on the exchange");
+    }
+    Ok(())
+}
+
+// Converts a human amount like "0.05" into raw token units
+fn to_raw_amount(amount: &str, decimals: u8) -> Result<u128, TradeError> {
+    let amount = Decimal::from_str(amount).map_err(|_| TradeError::InvalidAmount)?;
+    if amount <= Decimal::ZERO {
+        return Err(TradeError::InvalidAmount);
+    }
+    amount
+        .checked_mul(Decimal::from(10u64.pow(decimals as u32)))
+        .and_then(|raw| raw.trunc().to_u128())
+        .filter(|raw| *raw > 0)
+        .ok_or(TradeError::InvalidAmount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_raw_amount() {
+        assert_eq!(to_raw_amount("0.05", 18).unwrap(), 50_000_000_000_000_000);
+        assert_eq!(to_raw_amount("12.5", 6).unwrap(), 12_500_000);
+        assert!(to_raw_amount("-1", 18).is_err());
+        assert!(to_raw_amount("abc", 18).is_err());
+    }
+
+    #[test]
+    fn test_check_swap_calls() {
+        let exchange = "0x4270219d365d6b017231b52e92b3fb5d7c8378b05e9abc97724537a80e93b0f";
+        let approve = |to: &str, spender: &str, amount: u128| Call {
+            to: Felt::from_hex(to).unwrap(),
+            selector: selector!("approve"),
+            calldata: vec![Felt::from_hex(spender).unwrap(), Felt::from(amount), Felt::ZERO],
+        };
+        let swap = |to: &str, entrypoint: &str| Call {
+            to: Felt::from_hex(to).unwrap(),
+            selector: starknet::core::utils::get_selector_from_name(entrypoint).unwrap(),
+            calldata: Vec::new(),
+        };
+        let route = || swap(exchange, "multi_route_swap");
+        let check = |calls: &[Call]| check_swap_calls(calls, ETHER.address, 1_000, exchange);
+
+        assert!(check(&[approve(ETHER.address, exchange, 1_000), route()]).is_ok());
+        assert!(check(&[approve(ETHER.address, exchange, 500), route()]).is_ok());
+
+        // Approving more, another spender or another token, or calling anything else is refused
+        assert!(check(&[approve(ETHER.address, exchange, 1_001), route()]).is_err());
+        assert!(check(&[approve(ETHER.address, "0xbad", 1_000), route()]).is_err());
+        assert!(check(&[approve(STRK.address, exchange, 1_000), route()]).is_err());
+        let elsewhere = swap("0xbad", "multi_route_swap");
+        assert!(check(&[approve(ETHER.address, exchange, 1_000), elsewhere]).is_err());
+        assert!(check(&[approve(ETHER.address, exchange, 1_000), swap(exchange, "transfer")]).is_err());
+        assert!(check(&[route()]).is_err());
+        let transfer = swap(ETHER.address, "transfer");
+        let calls = [approve(ETHER.address, exchange, 1_000), route(), transfer];
+        assert!(check(&calls).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+use starknet_core::types::Felt;
+
+// Renders a felt in the canonical 0x-prefixed, 64 hex digit form used by explorers
+pub fn to_canonical_hex(felt: &Felt) -> String {
+    format!("0x{}", hex::encode(felt.to_bytes_be()))
+}
+
+// Parses an address with any padding or casing and returns its canonical form
+pub fn canonicalize_address(address: &str) -> Option<String> {
+    Felt::from_hex(address.trim())
+        .ok()
+        .map(|felt| to_canonical_hex(&felt))
+}
+
+// Compares two addresses regardless of padding or casing
+pub fn addresses_eq(a: &str, b: &str) -> bool {
+    match (canonicalize_address(a), canonicalize_address(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_hex_pads_to_64_digits() {
+        let canonical = canonicalize_address(
+            "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7",
+        )
+        .unwrap();
+        assert_eq!(
+            canonical,
+            "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7"
+        );
+        assert_eq!(to_canonical_hex(&Felt::ONE).len(), 66);
+    }
+
+    #[test]
+    fn test_addresses_eq_ignores_padding_and_case() {
+        assert!(addresses_eq(
+            "0x049D36570D4E46F48E99674BD3FCC84644DDD6B96F7C741B1562B82F9E004DC7",
+            "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7"
+        ));
+        assert!(!addresses_eq("0x1", "not an address"));
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use serde::Serialize;
+
+lazy_static! {
+    // Every cache built through Cache::new, for /admin cache
+    static ref REGISTRY: Mutex<Vec<Arc<dyn CacheControl>>> = Mutex::new(Vec::new());
+}
+
+// Entry bound for caches that don't pick their own
+pub fn default_max_entries() -> usize {
+    std::env::var("CACHE_MAX_ENTRIES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|max| *max > 0)
+        .unwrap_or(1_000)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub name: &'static str,
+    pub entries: usize,
+    pub max_entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0f64;
+        }
+        self.hits as f64 * 100f64 / lookups as f64
+    }
+}
+
+// Type-erased view of a cache for inspection and flushing
+trait CacheControl: Send + Sync {
+    fn stats(&self) -> CacheStats;
+    fn flush(&self);
+}
+
+struct Entry<V> {
+    value: V,
+    inserted_at: Instant,
+    last_used: u64,
+}
+
+struct CacheInner<V> {
+    name: &'static str,
+    max_entries: usize,
+    ttl: Option<Duration>,
+    entries: Mutex<HashMap<String, Entry<V>>>,
+    // Monotonic use counter, the entry with the lowest last_used is evicted first
+    clock: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl<V: Clone + Send + 'static> CacheControl for CacheInner<V> {
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            name: self.name,
+            entries: self.entries.lock().unwrap().len(),
+            max_entries: self.max_entries,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    fn flush(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+// Bounded LRU cache keyed by string, with an optional time to live and hit/miss counters
+pub struct Cache<V: Clone + Send + 'static> {
+    inner: Arc<CacheInner<V>>,
+}
+
+impl<V: Clone + Send + 'static> Cache<V> {
+    pub fn new(name: &'static str, max_entries: usize, ttl: Option<Duration>) -> Self {
+        let inner = Arc::new(CacheInner {
+            name,
+            max_entries: max_entries.max(1),
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            clock: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        });
+        REGISTRY.lock().unwrap().push(inner.clone());
+        Self { inner }
+    }
+
+    fn is_fresh(&self, entry: &Entry<V>) -> bool {
+        self.inner.ttl.is_none_or(|ttl| entry.inserted_at.elapsed() < ttl)
+    }
+
+    pub fn get(&self, key: &str) -> Option<V> {
+        let mut entries = self.inner.entries.lock().unwrap();
+        let value = match entries.get_mut(key) {
+            Some(entry) if self.is_fresh(entry) => {
+                entry.last_used = self.inner.clock.fetch_add(1, Ordering::Relaxed);
+                Some(entry.value.clone())
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        };
+        let counter = if value.is_some() { &self.inner.hits } else { &self.inner.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.inner
+            .entries
+            .lock()
+            .unwrap()
+            .get(key)
+            .is_some_and(|entry| self.is_fresh(entry))
+    }
+
+    pub fn insert(&self, key: &str, value: V) {
+        let mut entries = self.inner.entries.lock().unwrap();
+        if !entries.contains_key(key) && entries.len() >= self.inner.max_entries {
+            // Expired entries go first, the least recently used one only if that freed nothing
+            entries.retain(|_, entry| self.is_fresh(entry));
+            if entries.len() >= self.inner.max_entries {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                    self.inner.evictions.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        entries.insert(
+            key.to_string(),
+            Entry {
+                value,
+                inserted_at: Instant::now(),
+                last_used: self.inner.clock.fetch_add(1, Ordering::Relaxed),
+            },
+        );
+    }
+}
+
+pub fn cache_stats() -> Vec<CacheStats> {
+    REGISTRY.lock().unwrap().iter().map(|cache| cache.stats()).collect()
+}
+
+// Empties every cache, or only the one with the given name; returns how many were flushed
+pub fn flush_caches(name: Option<&str>) -> usize {
+    let registry = REGISTRY.lock().unwrap();
+    let mut flushed = 0;
+    for cache in registry.iter() {
+        if name.is_none_or(|name| cache.stats().name == name) {
+            cache.flush();
+            flushed += 1;
+        }
+    }
+    flushed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache: Cache<u32> = Cache::new("test_lru", 2, None);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(cache.get("a"), Some(1));
+        cache.insert("c", 3);
+
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.get("c"), Some(3));
+
+        let stats = cache.inner.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions), (3, 1, 1));
+
+        assert_eq!(flush_caches(Some("test_lru")), 1);
+        assert!(!cache.contains("a"));
+    }
+}
@@ -1,19 +1,38 @@
+use std::future::Future;
+
 use super::types::ekubo::{EkuboPoolParameters, Launch, Liquidity, Memecoin, StartingPrice};
 use num_traits::cast::ToPrimitive;
 use serde::de::value::Error;
-use starknet::core::types::{BlockId, BlockTag, FunctionCall, U256};
-use starknet::core::utils::{get_selector_from_name, normalize_address, parse_cairo_short_string};
+use serde::de::Error as _;
+use starknet::core::types::{
+    BlockId, BlockTag, EmittedEvent, EventFilter, FunctionCall, MaybePendingBlockWithTxHashes,
+    StarknetError, U256,
+};
+use starknet::core::utils::{get_selector_from_name, normalize_address};
 use starknet::macros::selector;
-use starknet::providers::jsonrpc::HttpTransport;
-use starknet::providers::{JsonRpcClient, Provider, ProviderError};
+use starknet::providers::{Provider, ProviderError};
 use starknet_core::types::Felt;
-use url::Url;
 
-use crate::constant::constants::{
-    selector_to_str, Selector, EXCHANGE_ADDRESS, MEMECOIN_FACTORY_ADDRESS,
-    MULTICALL_AGGREGATOR_ADDRESS,
-};
-use crate::utils::event_parser::{parse_and_validate_short_string, u256_to_decimal_str};
+use crate::constant::constants::{selector_to_str, Selector, STRK};
+use crate::constant::network::NETWORK;
+use crate::utils::address::{addresses_eq, to_canonical_hex};
+use crate::utils::cache::{default_max_entries, Cache};
+use crate::utils::event_parser::{decode_token_string, u256_to_decimal_str};
+use crate::utils::rpc::with_provider;
+
+lazy_static::lazy_static! {
+    // Accepted blocks keep their timestamp, so entries never expire and are only evicted by size
+    static ref BLOCK_TIMESTAMPS: Cache<u64> = Cache::new(
+        "block_timestamps",
+        std::env::var("BLOCK_TIMESTAMP_CACHE_SIZE")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(10_000),
+        None,
+    );
+    // A contract's deployment block never changes either
+    static ref DEPLOYMENT_BLOCKS: Cache<u64> = Cache::new("deployment_blocks", default_max_entries(), None);
+}
 
 trait FromFieldBytes: Sized {
     fn from_field_bytes(bytes: [u8; 32]) -> Self;
@@ -28,8 +47,6 @@ impl FromFieldBytes for u128 {
     }
 }
 
-const EKUBO_NFT: &str = "EKUBO_NFT";
-
 #[derive(Debug, thiserror::Error)]
 pub enum AggregateError {
     #[error("Provider error: {0}")]
@@ -43,32 +60,172 @@ pub enum AggregateError {
 
     #[error("Parse error: {0}")]
     Parse(String),
+
+    #[error("RPC rate limited the request, try again in a few seconds")]
+    RateLimited,
+
+    #[error("RPC request timed out: {0}")]
+    Timeout(String),
+
+    #[error("RPC temporarily unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("Contract reverted: {0} (check that the address is a launched memecoin)")]
+    ContractReverted(String),
+
+    #[error("Invalid call parameters: {0}")]
+    InvalidParams(String),
+}
+
+impl AggregateError {
+    // Only transient failures are worth retrying, everything else is surfaced immediately
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            AggregateError::RateLimited
+                | AggregateError::Timeout(_)
+                | AggregateError::Unavailable(_)
+        )
+    }
 }
 
-fn get_provider() -> Result<JsonRpcClient<HttpTransport>, AggregateError> {
-    println!("In get provider");
-    // Create provider with error handling
-    let provider = JsonRpcClient::new(HttpTransport::new(
-        Url::parse("https://starknet-mainnet.public.blastapi.io/rpc/v0_7")
-            .map_err(AggregateError::Url)?,
-    ));
-    Ok(provider)
+// Maps a provider failure to a retryable or permanent AggregateError
+pub fn classify_provider_error(error: ProviderError) -> AggregateError {
+    match error {
+        ProviderError::RateLimited => AggregateError::RateLimited,
+        ProviderError::StarknetError(StarknetError::ContractError(data)) => {
+            AggregateError::ContractReverted(data.revert_error)
+        }
+        ProviderError::StarknetError(StarknetError::ContractNotFound) => {
+            AggregateError::InvalidParams("contract not found, check the address".to_string())
+        }
+        ProviderError::StarknetError(StarknetError::BlockNotFound) => {
+            // The node may simply lag behind the block we asked for
+            AggregateError::Unavailable("block not found on node yet".to_string())
+        }
+        ProviderError::StarknetError(StarknetError::UnexpectedError(message)) => {
+            AggregateError::Unavailable(message)
+        }
+        ProviderError::StarknetError(error) => AggregateError::InvalidParams(error.to_string()),
+        ProviderError::ArrayLengthMismatch => {
+            AggregateError::Parse("RPC response length mismatch".to_string())
+        }
+        ProviderError::Other(error) => {
+            let message = error.to_string();
+            let lowercase = message.to_lowercase();
+            if lowercase.contains("timed out") || lowercase.contains("timeout") {
+                AggregateError::Timeout(message)
+            } else {
+                AggregateError::Unavailable(message)
+            }
+        }
+    }
 }
 
+// Performs a contract call, retrying transient RPC failures on the next healthy endpoint
+pub async fn call_with_retry(call: FunctionCall, block_id: BlockId) -> Result<Vec<Felt>, AggregateError> {
+    with_provider(|provider| {
+        let call = call.clone();
+        async move { provider.call(&call, block_id).await }
+    })
+    .await
+}
+
+// Calls generate_calls packs per memecoin
+const CALLS_PER_MEMECOIN: usize = 10;
+// Positions of the name and symbol calls among them
+const NAME_CALL: usize = 3;
+const SYMBOL_CALL: usize = 4;
+// Memecoins per multicall, keeps each call well within RPC step limits
+const AGGREGATE_BATCH_SIZE: usize = 20;
+
 pub async fn get_aggregate_call_data(address: &str) -> Result<Memecoin, AggregateError> {
     println!("In aggregate call");
     let calls = generate_calls(address);
-    let call_result = multicall_contract(calls).await.unwrap();
+    let call_result = multicall_contract(calls).await?;
     // Parse results with error handling
-    let parsed_result = parse_call_result(address, call_result).await.unwrap();
+    let parsed_result = parse_call_result(address, call_result)
+        .await
+        .map_err(|e| AggregateError::Parse(e.to_string()))?;
     Ok(parsed_result)
 }
 
+// Aggregates the calls of many memecoins into a few multicalls, results come back in input order.
+// Meant for wallets holding many tokens, single token lookups like /spot keep get_aggregate_call_data.
+// An address that isn't a memecoin reverts its whole batch, which is then aggregated token by token
+pub async fn get_aggregate_call_data_many(
+    addresses: &[&str],
+) -> Result<Vec<Result<Memecoin, AggregateError>>, AggregateError> {
+    let mut memecoins = Vec::with_capacity(addresses.len());
+    for batch in addresses.chunks(AGGREGATE_BATCH_SIZE) {
+        let mut calls: Vec<Felt> = vec![Felt::from(batch.len() * CALLS_PER_MEMECOIN)];
+        for address in batch {
+            calls.extend(generate_calls(address).into_iter().skip(1));
+        }
+        let call_result = match multicall_contract(calls).await {
+            Ok(call_result) => call_result,
+            Err(AggregateError::ContractReverted(reason)) => {
+                eprintln!("Batched aggregation reverted, retrying token by token: {}", reason);
+                for address in batch {
+                    memecoins.push(get_aggregate_call_data(address).await);
+                }
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        memecoins.extend(parse_many_call_result(batch, &call_result).await?);
+    }
+    Ok(memecoins)
+}
+
+// Splits a batched multicall result into the single token layout parse_call_result expects
+async fn parse_many_call_result(
+    addresses: &[&str],
+    call_result: &[Felt],
+) -> Result<Vec<Result<Memecoin, AggregateError>>, AggregateError> {
+    let truncated = || AggregateError::Parse("Truncated multicall result".to_string());
+    let block_number = *call_result.first().ok_or_else(truncated)?;
+    let spans = call_spans(call_result)?;
+    if spans.len() != addresses.len() * CALLS_PER_MEMECOIN {
+        return Err(truncated());
+    }
+
+    let mut memecoins = Vec::with_capacity(addresses.len());
+    for (address, spans) in addresses.iter().zip(spans.chunks(CALLS_PER_MEMECOIN)) {
+        let mut single = vec![block_number, Felt::from(CALLS_PER_MEMECOIN)];
+        for span in spans {
+            single.extend_from_slice(span);
+        }
+        memecoins.push(
+            parse_call_result(address, single)
+                .await
+                .map_err(|e| AggregateError::Parse(e.to_string())),
+        );
+    }
+    Ok(memecoins)
+}
+
+// Splits a multicall result after its block number and call count into each call's result, its
+// length followed by that many felts
+fn call_spans(call_result: &[Felt]) -> Result<Vec<&[Felt]>, AggregateError> {
+    let truncated = || AggregateError::Parse("Truncated multicall result".to_string());
+    let mut spans: Vec<&[Felt]> = Vec::new();
+    let mut index = 2;
+    while index < call_result.len() {
+        let length = call_result[index].to_usize().ok_or_else(truncated)?;
+        let end = index.checked_add(length).ok_or_else(truncated)?;
+        let span = call_result.get(index..=end).ok_or_else(truncated)?;
+        spans.push(span);
+        index = end + 1;
+    }
+    Ok(spans)
+}
+
 fn generate_calls(address: &str) -> Vec<starknet_core::types::Felt> {
     println!("In generate call");
     let mut calls: Vec<Felt> = vec![Felt::from(10)];
 
-    let factory_address = MEMECOIN_FACTORY_ADDRESS;
+    let factory_address = &NETWORK.memecoin_factory;
     let ekubo_id: String = 1.to_string();
 
     let factory_calls = [
@@ -113,25 +270,35 @@ fn generate_calls(address: &str) -> Vec<starknet_core::types::Felt> {
 async fn parse_call_result(address: &str, call_result: Vec<Felt>) -> Result<Memecoin, Error> {
     println!("In parse call");
     let is_memecoin = call_result[3] != Felt::ZERO;
-    let exchange = normalize_address(Felt::from_bytes_be(&call_result[5].to_bytes_be()))
-        .to_hex_string()
-        .eq(EXCHANGE_ADDRESS);
+    let exchange = addresses_eq(
+        &to_canonical_hex(&normalize_address(call_result[5])),
+        &NETWORK.exchange,
+    );
 
     if !is_memecoin || !exchange {
-        panic!("Invalid Memecoin");
+        return Err(Error::custom("Invalid Memecoin"));
     }
 
     let has_liquidity = call_result[6] > Felt::ZERO;
     if !has_liquidity {
-        panic!("No Liquidity");
+        return Err(Error::custom("No Liquidity"));
     }
 
-    let name =
-        parse_cairo_short_string(&Felt::from_bytes_be(&call_result[12].to_bytes_be())).unwrap();
-
-    let symbol =
-        parse_and_validate_short_string(&Felt::from_bytes_be(&call_result[14].to_bytes_be()))
-            .unwrap();
+    // Names and symbols returned as ByteArrays span several felts, the offsets below expect one
+    // felt each, so both are decoded first and collapsed
+    let spans = call_spans(&call_result).map_err(|e| Error::custom(e.to_string()))?;
+    let (name, symbol) = match (spans.get(NAME_CALL), spans.get(SYMBOL_CALL)) {
+        (Some(name), Some(symbol)) => (decode_token_string(&name[1..]), decode_token_string(&symbol[1..])),
+        _ => return Err(Error::custom("Truncated call result")),
+    };
+    let mut collapsed = call_result[..2].to_vec();
+    for (call, span) in spans.iter().enumerate() {
+        match call {
+            NAME_CALL | SYMBOL_CALL => collapsed.extend([Felt::ONE, Felt::ZERO]),
+            _ => collapsed.extend_from_slice(span),
+        }
+    }
+    let call_result = collapsed;
 
     let total_supply = match (call_result.get(16), call_result.get(17)) {
         (Some(low), Some(high)) => parse_u256_from_felts(low, high),
@@ -150,11 +317,9 @@ async fn parse_call_result(address: &str, call_result: Vec<Felt>) -> Result<Meme
     let mut index = 28;
     let ekubo_pool_params = parse_ekubo_pool_parameters(&call_result, &mut index);
     let liquidity = Liquidity {
-        launch_manager: normalize_address(Felt::from_bytes_be(&call_result[8].to_bytes_be()))
-            .to_hex_string(),
-        ekubo_id: EKUBO_NFT.to_string(),
-        quote_token: normalize_address(Felt::from_bytes_be(&call_result[33].to_bytes_be()))
-            .to_hex_string(),
+        launch_manager: to_canonical_hex(&normalize_address(call_result[8])),
+        ekubo_id: call_result[10].to_hex_string(),
+        quote_token: to_canonical_hex(&normalize_address(call_result[33])),
         starting_tick: ekubo_pool_params.starting_price.mag.to_i64().unwrap_or(0)
             * if ekubo_pool_params.starting_price.sign {
                 1
@@ -167,7 +332,7 @@ async fn parse_call_result(address: &str, call_result: Vec<Felt>) -> Result<Meme
         name,
         symbol,
         total_supply,
-        owner: owner.to_hex_string(),
+        owner: to_canonical_hex(&owner),
         is_launched: true,
         launch: Launch {
             team_allocation,
@@ -245,65 +410,331 @@ pub fn decode_short_string(felt: &str) -> String {
 
 async fn multicall_contract(calls: Vec<Felt>) -> Result<Vec<Felt>, AggregateError> {
     println!("In multicall contract");
-    let provider = get_provider().unwrap();
-
     // Make contract call with error handling
-    let call_result = match provider
-        .call(
-            FunctionCall {
-                contract_address: Felt::from_hex(MULTICALL_AGGREGATOR_ADDRESS)
-                    .map_err(|e| AggregateError::ContractCall(format!("Invalid address: {}", e)))?,
-                entry_point_selector: selector!("aggregate"),
-                calldata: calls,
-            },
-            BlockId::Tag(BlockTag::Latest),
-        )
-        .await
+    let call_result = call_with_retry(
+        FunctionCall {
+            contract_address: Felt::from_hex(&NETWORK.multicall_aggregator)
+                .map_err(|e| AggregateError::InvalidParams(format!("Invalid address: {}", e)))?,
+            entry_point_selector: selector!("aggregate"),
+            calldata: calls,
+        },
+        BlockId::Tag(BlockTag::Latest),
+    )
+    .await?;
+    println!("Contract call successful!");
+
+    Ok(call_result)
+}
+
+// Timestamp of a block from the shared cache, fetching the header on a miss
+pub async fn get_block_timestamp(block_number: u64) -> Result<u64, AggregateError> {
+    if let Some(timestamp) = BLOCK_TIMESTAMPS.get(&block_number.to_string()) {
+        return Ok(timestamp);
+    }
+    match with_provider(|provider| async move {
+        provider
+            .get_block_with_tx_hashes(BlockId::Number(block_number))
+            .await
+    })
+    .await?
     {
-        std::result::Result::Ok(result) => {
-            println!("Contract call successful!");
-            result
+        MaybePendingBlockWithTxHashes::Block(block) => {
+            record_block_timestamp(block_number, block.timestamp);
+            Ok(block.timestamp)
         }
-        Err(e) => {
-            println!("Contract call failed: {:?}", e);
-            return Err(AggregateError::ContractCall(format!(
-                "Contract call failed: {:?}",
-                e
-            )));
+        // Not final yet, so not cached
+        MaybePendingBlockWithTxHashes::PendingBlock(block) => Ok(block.timestamp),
+    }
+}
+
+// Hash of an accepted block, None while it is still pending
+pub async fn get_block_hash(block_number: u64) -> Result<Option<Felt>, AggregateError> {
+    match with_provider(|provider| async move {
+        provider
+            .get_block_with_tx_hashes(BlockId::Number(block_number))
+            .await
+    })
+    .await?
+    {
+        MaybePendingBlockWithTxHashes::Block(block) => Ok(Some(block.block_hash)),
+        MaybePendingBlockWithTxHashes::PendingBlock(_) => Ok(None),
+    }
+}
+
+// Hashes of a block's transactions in execution order, the timestamp is cached on the way
+pub async fn get_block_transactions(block_number: u64) -> Result<Vec<Felt>, AggregateError> {
+    match with_provider(|provider| async move {
+        provider
+            .get_block_with_tx_hashes(BlockId::Number(block_number))
+            .await
+    })
+    .await?
+    {
+        MaybePendingBlockWithTxHashes::Block(block) => {
+            record_block_timestamp(block_number, block.timestamp);
+            Ok(block.transactions)
         }
+        MaybePendingBlockWithTxHashes::PendingBlock(block) => Ok(block.transactions),
+    }
+}
+
+// Seeds the block timestamp cache with a block the indexer already has the header of
+pub fn record_block_timestamp(block_number: u64, timestamp: u64) {
+    BLOCK_TIMESTAMPS.insert(&block_number.to_string(), timestamp);
+}
+
+// Number and timestamp of the latest accepted block
+pub async fn get_latest_block() -> Result<(u64, u64), AggregateError> {
+    let block_number = with_provider(|provider| async move { provider.block_number().await }).await?;
+    Ok((block_number, get_block_timestamp(block_number).await?))
+}
+
+// Calls a view function on any contract at the latest block
+pub async fn call_contract(
+    contract_address: &str,
+    entry_point: &str,
+    calldata: Vec<Felt>,
+) -> Result<Vec<Felt>, AggregateError> {
+    call_with_retry(
+        FunctionCall {
+            contract_address: Felt::from_hex(contract_address)
+                .map_err(|e| AggregateError::InvalidParams(format!("Invalid address: {}", e)))?,
+            entry_point_selector: get_selector_from_name(entry_point)
+                .map_err(|e| AggregateError::InvalidParams(e.to_string()))?,
+            calldata,
+        },
+        BlockId::Tag(BlockTag::Latest),
+    )
+    .await
+}
+
+pub async fn get_class_hash_at(contract_address: &str) -> Result<String, AggregateError> {
+    let contract_address = Felt::from_hex(contract_address)
+        .map_err(|e| AggregateError::InvalidParams(format!("Invalid address: {}", e)))?;
+    let class_hash = with_provider(|provider| async move {
+        provider
+            .get_class_hash_at(BlockId::Tag(BlockTag::Latest), contract_address)
+            .await
+    })
+    .await?;
+    Ok(to_canonical_hex(&class_hash))
+}
+
+// First block a contract exists at, found by bisecting get_class_hash_at up to `latest_block`
+pub async fn get_deployment_block(contract_address: &str, latest_block: u64) -> Result<u64, AggregateError> {
+    let contract_address = Felt::from_hex(contract_address)
+        .map_err(|e| AggregateError::InvalidParams(format!("Invalid address: {}", e)))?;
+    let key = to_canonical_hex(&contract_address);
+    if let Some(block_number) = DEPLOYMENT_BLOCKS.get(&key) {
+        return Ok(block_number);
+    }
+    let deployed_at = |block_number: u64| {
+        with_provider(move |provider| async move {
+            match provider
+                .get_class_hash_at(BlockId::Number(block_number), contract_address)
+                .await
+            {
+                Ok(_) => Ok(true),
+                Err(ProviderError::StarknetError(StarknetError::ContractNotFound)) => Ok(false),
+                Err(e) => Err(e),
+            }
+        })
     };
+    if !deployed_at(latest_block).await? {
+        return Err(AggregateError::InvalidParams(format!(
+            "{} is not deployed at block {}",
+            to_canonical_hex(&contract_address),
+            latest_block
+        )));
+    }
+    let block_number = first_block_where(latest_block, deployed_at).await?;
+    DEPLOYMENT_BLOCKS.insert(&key, block_number);
+    Ok(block_number)
+}
 
-    Ok(call_result)
+// Lowest block in 0..=last_block matching `matches`, which has to hold from some block on and at
+// `last_block` itself
+async fn first_block_where<F, Fut>(last_block: u64, matches: F) -> Result<u64, AggregateError>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Result<bool, AggregateError>>,
+{
+    let (mut low, mut high) = (0, last_block);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if matches(middle).await? {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    Ok(low)
+}
+
+// Finds who sent an account its first STRK, usually whoever funded its deployment. Only the
+// `lookback_blocks` before the deployment are searched rather than the whole chain
+pub async fn get_first_funder(
+    account: &str,
+    latest_block: u64,
+    lookback_blocks: u64,
+) -> Result<Option<String>, AggregateError> {
+    let deployed_at = get_deployment_block(account, latest_block).await?;
+    let account = Felt::from_hex(account)
+        .map_err(|e| AggregateError::InvalidParams(format!("Invalid address: {}", e)))?;
+    let filter = EventFilter {
+        from_block: Some(BlockId::Number(deployed_at.saturating_sub(lookback_blocks))),
+        to_block: Some(BlockId::Number(latest_block)),
+        address: Some(Felt::from_hex_unchecked(STRK.address)),
+        keys: Some(vec![vec![selector!("Transfer")], vec![], vec![account]]),
+    };
+    let page = with_provider(|provider| {
+        let filter = filter.clone();
+        async move { provider.get_events(filter, None, 1).await }
+    })
+    .await?;
+    Ok(page
+        .events
+        .first()
+        .and_then(|event| event.keys.get(1))
+        .map(to_canonical_hex))
+}
+
+// Events with one of `selectors` as first key a contract emitted in a block range
+pub async fn get_contract_events(
+    contract_address: Felt,
+    selectors: Vec<Felt>,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<EmittedEvent>, AggregateError> {
+    get_all_events(EventFilter {
+        from_block: Some(BlockId::Number(from_block)),
+        to_block: Some(BlockId::Number(to_block)),
+        address: Some(contract_address),
+        keys: Some(vec![selectors]),
+    })
+    .await
+}
+
+// Transfer events of a token in a block range, keys are [selector, from, to] and data the u256 amount
+pub async fn get_transfer_events(
+    token_address: &str,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<EmittedEvent>, AggregateError> {
+    get_token_events(token_address, selector!("Transfer"), from_block, to_block).await
+}
+
+// Ownership handovers of a token, renounces included, in a block range
+pub async fn get_ownership_events(
+    token_address: &str,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<EmittedEvent>, AggregateError> {
+    get_token_events(token_address, selector!("OwnershipTransferred"), from_block, to_block).await
+}
+
+async fn get_token_events(
+    token_address: &str,
+    selector: Felt,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<EmittedEvent>, AggregateError> {
+    get_all_events(EventFilter {
+        from_block: Some(BlockId::Number(from_block)),
+        to_block: Some(BlockId::Number(to_block)),
+        address: Some(
+            Felt::from_hex(token_address)
+                .map_err(|e| AggregateError::InvalidParams(format!("Invalid address: {}", e)))?,
+        ),
+        keys: Some(vec![vec![selector]]),
+    })
+    .await
+}
+
+// Transfer events of a token sent (outgoing) or received by an account, from `from_block` or genesis
+pub async fn get_account_transfer_events(
+    token_address: &str,
+    account: &str,
+    outgoing: bool,
+    from_block: Option<u64>,
+) -> Result<Vec<EmittedEvent>, AggregateError> {
+    let parse = |address: &str| {
+        Felt::from_hex(address).map_err(|e| AggregateError::InvalidParams(format!("Invalid address: {}", e)))
+    };
+    let account = parse(account)?;
+    // An empty key set matches anything in that position
+    let keys = if outgoing {
+        vec![vec![selector!("Transfer")], vec![account]]
+    } else {
+        vec![vec![selector!("Transfer")], vec![], vec![account]]
+    };
+    get_all_events(EventFilter {
+        from_block: from_block.map(BlockId::Number),
+        to_block: None,
+        address: Some(parse(token_address)?),
+        keys: Some(keys),
+    })
+    .await
+}
+
+// Transfer events of any token sent (outgoing) or received by one of the accounts in a block range
+pub async fn get_wallet_transfer_events(
+    accounts: &[Felt],
+    outgoing: bool,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<EmittedEvent>, AggregateError> {
+    let keys = if outgoing {
+        vec![vec![selector!("Transfer")], accounts.to_vec()]
+    } else {
+        vec![vec![selector!("Transfer")], vec![], accounts.to_vec()]
+    };
+    get_all_events(EventFilter {
+        from_block: Some(BlockId::Number(from_block)),
+        to_block: Some(BlockId::Number(to_block)),
+        address: None,
+        keys: Some(keys),
+    })
+    .await
+}
+
+async fn get_all_events(filter: EventFilter) -> Result<Vec<EmittedEvent>, AggregateError> {
+    // Continuation tokens are only meaningful to the node that issued them,
+    // so a failover restarts the range on the next endpoint
+    with_provider(|provider| {
+        let filter = filter.clone();
+        async move {
+            let mut events = Vec::new();
+            let mut continuation_token = None;
+            loop {
+                let page = provider
+                    .get_events(filter.clone(), continuation_token, 100)
+                    .await?;
+                events.extend(page.events);
+                match page.continuation_token {
+                    Some(token) => continuation_token = Some(token),
+                    None => return Ok(events),
+                }
+            }
+        }
+    })
+    .await
 }
 
 pub async fn get_balance(contract_address: &str, account: &str) -> Result<String, AggregateError> {
     println!("In get balance");
-    let provider = get_provider().unwrap();
     // Make contract call with error handling
-    let call_result = match provider
-        .call(
-            FunctionCall {
-                contract_address: Felt::from_hex(contract_address)
-                    .map_err(|e| AggregateError::ContractCall(format!("Invalid address: {}", e)))?,
-                entry_point_selector: selector!("balance_of"),
-                calldata: vec![Felt::from_hex_unchecked(account)],
-            },
-            BlockId::Tag(BlockTag::Latest),
-        )
-        .await
-    {
-        Ok(result) => {
-            println!("Contract call successful!");
-            result
-        }
-        Err(e) => {
-            println!("Contract call failed: {:?}", e);
-            return Err(AggregateError::ContractCall(format!(
-                "Contract call failed: {:?}",
-                e
-            )));
-        }
-    };
+    let call_result = call_with_retry(
+        FunctionCall {
+            contract_address: Felt::from_hex(contract_address)
+                .map_err(|e| AggregateError::InvalidParams(format!("Invalid address: {}", e)))?,
+            entry_point_selector: selector!("balance_of"),
+            calldata: vec![Felt::from_hex_unchecked(account)],
+        },
+        BlockId::Tag(BlockTag::Latest),
+    )
+    .await?;
+    println!("Contract call successful!");
 
     let balance = match (call_result.get(0), call_result.get(1)) {
         (Some(low), Some(high)) => parse_u256_from_felts(low, high),
@@ -313,31 +744,45 @@ pub async fn get_balance(contract_address: &str, account: &str) -> Result<String
     Ok(balance)
 }
 
-pub async fn validate_memecoins(addresses: Vec<&str>) -> Result<Vec<&str>, Error> {
+// Whether the factory deployed `address`, RPC failures are errors rather than a no
+pub async fn is_memecoin(address: &str) -> Result<bool, AggregateError> {
+    let address = Felt::from_hex(address)
+        .map_err(|e| AggregateError::InvalidParams(format!("Invalid address: {}", e)))?;
+    let result = call_contract(&NETWORK.memecoin_factory, "is_memecoin", vec![address]).await?;
+    Ok(result.first().is_some_and(|flag| *flag != Felt::ZERO))
+}
+
+pub async fn validate_memecoins(addresses: Vec<&str>) -> Result<Vec<&str>, AggregateError> {
     println!("In validate memecall");
+    if addresses.is_empty() {
+        return Ok(addresses);
+    }
     let calls = generate_validate_calls(addresses.clone());
-    let call_result = multicall_contract(calls).await.unwrap();
-    let mut memecoin_addresses: Vec<&str> = Vec::new();
-    // Iterate over each data item in call_result (starting from index 2)
-    for (index, data) in call_result
-        .iter()
-        .skip(2)
-        .take(addresses.len() - 2)
-        .enumerate()
-    {
-        let is_memecoin = *data > Felt::ZERO;
+    let call_result = multicall_contract(calls).await?;
+    parse_validate_result(addresses, &call_result)
+}
 
-        if is_memecoin {
-            memecoin_addresses.push(addresses[index]);
-        }
+// Keeps the addresses whose is_memecoin call answered true, one call result per address
+fn parse_validate_result<'a>(
+    addresses: Vec<&'a str>,
+    call_result: &[Felt],
+) -> Result<Vec<&'a str>, AggregateError> {
+    let spans = call_spans(call_result)?;
+    if spans.len() != addresses.len() {
+        return Err(AggregateError::Parse("Truncated multicall result".to_string()));
     }
-    Ok(memecoin_addresses)
+    Ok(addresses
+        .into_iter()
+        .zip(spans)
+        .filter(|(_, span)| span.get(1).is_some_and(|flag| *flag != Felt::ZERO))
+        .map(|(address, _)| address)
+        .collect())
 }
 
 fn generate_validate_calls(addresses: Vec<&str>) -> Vec<Felt> {
     println!("In generate validate calls");
     let mut calls: Vec<Felt> = vec![Felt::from(addresses.len())];
-    let factory_address = MEMECOIN_FACTORY_ADDRESS;
+    let factory_address = &NETWORK.memecoin_factory;
     for address in addresses {
         calls.push(Felt::from_hex_unchecked(factory_address));
         calls.push(get_selector_from_name("is_memecoin").unwrap());
@@ -346,3 +791,204 @@ fn generate_validate_calls(addresses: Vec<&str>) -> Vec<Felt> {
     }
     calls
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::event_parser::{CreationEvent, FromStarknetEventData, LaunchEvent};
+    use serde_json::Value;
+    use starknet::core::types::ContractErrorData;
+    use starknet::providers::ProviderImplError;
+
+    // Stands in for the transport failures a JSON-RPC client reports
+    #[derive(Debug, thiserror::Error)]
+    #[error("{0}")]
+    struct TransportError(&'static str);
+
+    impl ProviderImplError for TransportError {
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_parse_validate_result() {
+        let fixtures = load_fixtures();
+        let addresses: Vec<&str> = fixtures
+            .iter()
+            .map(|fixture| fixture["token_address"].as_str().unwrap())
+            .chain(["0x1234"])
+            .collect();
+        // is_memecoin comes first in the recorded aggregate results, the last address isn't one
+        let mut call_result = vec![Felt::from(1_025_802u64), Felt::from(addresses.len())];
+        for fixture in &fixtures {
+            call_result.extend_from_slice(&felts(fixture, "aggregate_result")[2..4]);
+        }
+        call_result.extend([Felt::ONE, Felt::ZERO]);
+
+        assert_eq!(parse_validate_result(addresses.clone(), &call_result).unwrap(), addresses[..2]);
+        assert!(parse_validate_result(addresses[2..].to_vec(), &call_result[..2]).is_err());
+
+        // A single address, the layout that used to underflow
+        let single = vec![Felt::from(1_025_802u64), Felt::ONE, Felt::ONE, Felt::ONE];
+        assert_eq!(parse_validate_result(vec![addresses[0]], &single).unwrap(), vec![addresses[0]]);
+        assert!(parse_validate_result(Vec::new(), &single[..2]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_classify_provider_error() {
+        let other = |message| ProviderError::Other(Box::new(TransportError(message)));
+        let cases = [
+            (other("operation timed out"), "timeout", true),
+            (other("Request Timeout"), "timeout", true),
+            (ProviderError::RateLimited, "rate limited", true),
+            (other("connection refused"), "unavailable", true),
+            (ProviderError::StarknetError(StarknetError::BlockNotFound), "unavailable", true),
+            (
+                ProviderError::StarknetError(StarknetError::UnexpectedError("node busy".to_string())),
+                "unavailable",
+                true,
+            ),
+            (
+                ProviderError::StarknetError(StarknetError::ContractError(ContractErrorData {
+                    revert_error: "ENTRYPOINT_NOT_FOUND".to_string(),
+                })),
+                "reverted",
+                false,
+            ),
+            (ProviderError::StarknetError(StarknetError::ContractNotFound), "invalid", false),
+            (ProviderError::StarknetError(StarknetError::PageSizeTooBig), "invalid", false),
+            (ProviderError::ArrayLengthMismatch, "parse", false),
+        ];
+        for (error, expected, retryable) in cases {
+            let description = error.to_string();
+            let classified = classify_provider_error(error);
+            let class = match &classified {
+                AggregateError::Timeout(_) => "timeout",
+                AggregateError::RateLimited => "rate limited",
+                AggregateError::Unavailable(_) => "unavailable",
+                AggregateError::ContractReverted(_) => "reverted",
+                AggregateError::InvalidParams(_) => "invalid",
+                AggregateError::Parse(_) => "parse",
+                _ => "other",
+            };
+            assert_eq!(class, expected, "{}", description);
+            assert_eq!(classified.is_retryable(), retryable, "{}", description);
+        }
+    }
+
+    // Canonical launch payloads replayed through decoding and enrichment without any IO
+    const FIXTURES: [&str; 2] = [
+        include_str!("../../tests/fixtures/events/eth_quoted_launch.json"),
+        include_str!("../../tests/fixtures/events/strk_quoted_launch.json"),
+    ];
+
+    fn load_fixtures() -> Vec<Value> {
+        FIXTURES
+            .iter()
+            .map(|fixture| serde_json::from_str(fixture).unwrap())
+            .collect()
+    }
+
+    fn felts(fixture: &Value, key: &str) -> Vec<Felt> {
+        fixture[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|value| Felt::from_hex(value.as_str().unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_replay_creation_events() {
+        for fixture in load_fixtures() {
+            let event =
+                CreationEvent::from_starknet_event_data(felts(&fixture, "creation_event")).unwrap();
+            let expected = &fixture["expected"]["creation"];
+            assert_eq!(to_canonical_hex(&event.owner), expected["owner"]);
+            assert_eq!(event.name, expected["name"]);
+            assert_eq!(event.symbol, expected["symbol"]);
+            assert_eq!(event.initial_supply, expected["initial_supply"]);
+            assert_eq!(to_canonical_hex(&event.memecoin_address), expected["memecoin_address"]);
+        }
+    }
+
+    #[test]
+    fn test_replay_launch_events() {
+        for fixture in load_fixtures() {
+            let event =
+                LaunchEvent::from_starknet_event_data(felts(&fixture, "launch_event")).unwrap();
+            let expected = &fixture["expected"]["launch"];
+            assert_eq!(to_canonical_hex(&event.memecoin_address), expected["memecoin_address"]);
+            assert_eq!(to_canonical_hex(&event.quote_token), expected["quote_token"]);
+            assert_eq!(event.exchange_name, expected["exchange_name"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recorded_block_timestamps() {
+        // Blocks seen by the indexer are answered without a get_block_with_tx_hashes call
+        record_block_timestamp(42, 1_700_000_000);
+        assert_eq!(get_block_timestamp(42).await.unwrap(), 1_700_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_first_block_where() {
+        for deployment in [0, 1, 615_321, 999_999] {
+            let found = first_block_where(999_999, |block| async move { Ok(block >= deployment) })
+                .await
+                .unwrap();
+            assert_eq!(found, deployment);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_aggregate_snapshot() {
+        for fixture in load_fixtures() {
+            let token_address = fixture["token_address"].as_str().unwrap();
+            let memecoin =
+                parse_call_result(token_address, felts(&fixture, "aggregate_result"))
+                    .await
+                    .unwrap();
+            assert_eq!(
+                serde_json::to_value(&memecoin).unwrap(),
+                fixture["expected"]["memecoin"],
+                "snapshot mismatch for {}",
+                fixture["description"]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_batched_aggregate_result() {
+        let fixtures = load_fixtures();
+        let addresses: Vec<&str> = fixtures
+            .iter()
+            .map(|fixture| fixture["token_address"].as_str().unwrap())
+            .collect();
+
+        // One multicall answering both fixtures: shared header, then every call result in order
+        let mut batched = vec![Felt::ZERO, Felt::from(addresses.len() * CALLS_PER_MEMECOIN)];
+        for fixture in &fixtures {
+            batched.extend(felts(fixture, "aggregate_result").into_iter().skip(2));
+        }
+        let memecoins = parse_many_call_result(&addresses, &batched).await.unwrap();
+        for (memecoin, fixture) in memecoins.into_iter().zip(&fixtures) {
+            assert_eq!(serde_json::to_value(memecoin.unwrap()).unwrap(), fixture["expected"]["memecoin"]);
+        }
+
+        assert!(parse_many_call_result(&addresses, &batched[..batched.len() - 1]).await.is_err());
+    }
+
+    #[test]
+    fn test_call_spans_reject_bogus_lengths() {
+        let header = [Felt::ZERO, Felt::ONE];
+        let overflowing = [&header[..], &[Felt::from(u64::MAX), Felt::ONE]].concat();
+        assert!(call_spans(&overflowing).is_err());
+        let past_the_end = [&header[..], &[Felt::from(3u64), Felt::ONE]].concat();
+        assert!(call_spans(&past_the_end).is_err());
+
+        let spans = [&header[..], &[Felt::ONE, Felt::TWO, Felt::ZERO]].concat();
+        assert_eq!(call_spans(&spans).unwrap(), vec![&[Felt::ONE, Felt::TWO][..], &[Felt::ZERO][..]]);
+    }
+}
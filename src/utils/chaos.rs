@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use super::retry::jitter;
+
+lazy_static! {
+    pub static ref CHAOS: Chaos = Chaos::default();
+}
+
+// Upstream failures that can be injected, each one goes down the same path a real one would
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fault {
+    // Explorer API answering 500
+    ExplorerError,
+    // RPC calls timing out, which fails over to the next endpoint
+    RpcTimeout,
+    // Telegram answering 429 with a retry_after
+    TelegramRateLimit,
+}
+
+impl Fault {
+    pub const ALL: [Fault; 3] = [Fault::ExplorerError, Fault::RpcTimeout, Fault::TelegramRateLimit];
+
+    pub fn parse(input: &str) -> Option<Self> {
+        match input {
+            "explorer_error" => Some(Fault::ExplorerError),
+            "rpc_timeout" => Some(Fault::RpcTimeout),
+            "telegram_rate_limit" => Some(Fault::TelegramRateLimit),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FaultRule {
+    // Share of calls failing, 1.0 fails every call
+    pub rate: f64,
+    // Failures left before the fault clears itself, None keeps it until it is cleared
+    pub remaining: Option<u32>,
+}
+
+// Fault injection for staging, only compiled in with the `chaos` feature
+#[derive(Default)]
+pub struct Chaos {
+    faults: Mutex<HashMap<Fault, FaultRule>>,
+}
+
+impl Chaos {
+    pub fn inject(&self, fault: Fault, rule: FaultRule) {
+        println!("Chaos: injecting {:?} {:?}", fault, rule);
+        self.faults.lock().unwrap().insert(fault, rule);
+    }
+
+    // Clears one fault, or every fault with None. Returns whether anything was cleared
+    pub fn clear(&self, fault: Option<Fault>) -> bool {
+        let mut faults = self.faults.lock().unwrap();
+        match fault {
+            Some(fault) => faults.remove(&fault).is_some(),
+            None => {
+                let cleared = !faults.is_empty();
+                faults.clear();
+                cleared
+            }
+        }
+    }
+
+    pub fn active(&self) -> HashMap<Fault, FaultRule> {
+        self.faults.lock().unwrap().clone()
+    }
+
+    // Whether this call should fail, counting it against the fault's remaining failures
+    pub fn should_fail(&self, fault: Fault) -> bool {
+        let mut faults = self.faults.lock().unwrap();
+        let rule = match faults.get_mut(&fault) {
+            Some(rule) => rule,
+            None => return false,
+        };
+        if jitter() >= rule.rate {
+            return false;
+        }
+        match rule.remaining.as_mut() {
+            Some(1) => {
+                faults.remove(&fault);
+            }
+            Some(remaining) => *remaining -= 1,
+            None => {}
+        }
+        println!("Chaos: {:?} injected", fault);
+        true
+    }
+}
+
+// Body of the 429 injected into Telegram sends
+const RATE_LIMITED_BODY: &str =
+    r#"{"ok":false,"error_code":429,"description":"Too Many Requests","parameters":{"retry_after":1}}"#;
+
+// A 500 from the explorer, as reqwest reports it after error_for_status
+pub fn explorer_error() -> reqwest::Error {
+    let response = axum::http::Response::builder()
+        .status(500)
+        .body("Internal Server Error (chaos)")
+        .unwrap_or_default();
+    match reqwest::Response::from(response).error_for_status() {
+        Err(e) => e,
+        Ok(_) => unreachable!("a 500 is always an error status"),
+    }
+}
+
+// A 429 the way Telegram sends it
+pub fn telegram_rate_limited() -> reqwest::Response {
+    let response = axum::http::Response::builder()
+        .status(429)
+        .body(RATE_LIMITED_BODY)
+        .unwrap_or_default();
+    reqwest::Response::from(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fault_clears_after_remaining_failures() {
+        let chaos = Chaos::default();
+        assert!(!chaos.should_fail(Fault::RpcTimeout));
+        chaos.inject(
+            Fault::RpcTimeout,
+            FaultRule {
+                rate: 1f64,
+                remaining: Some(2),
+            },
+        );
+        assert!(chaos.should_fail(Fault::RpcTimeout));
+        assert!(!chaos.should_fail(Fault::ExplorerError));
+        assert!(chaos.should_fail(Fault::RpcTimeout));
+        assert!(!chaos.should_fail(Fault::RpcTimeout));
+        assert!(chaos.active().is_empty());
+    }
+
+    #[test]
+    fn test_injected_responses() {
+        let error = explorer_error();
+        assert_eq!(error.status().map(|status| status.as_u16()), Some(500));
+        let response = telegram_rate_limited();
+        assert_eq!(response.status().as_u16(), 429);
+    }
+}
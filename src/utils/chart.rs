@@ -0,0 +1,145 @@
+use plotters::prelude::*;
+
+// Small enough to sit under an alert without pushing it off screen
+const WIDTH: u32 = 480;
+const HEIGHT: u32 = 180;
+const BACKGROUND: RGBColor = RGBColor(0x13, 0x17, 0x22);
+const RISING: RGBColor = RGBColor(0x16, 0xc7, 0x84);
+const FALLING: RGBColor = RGBColor(0xea, 0x39, 0x43);
+const POSITION: RGBColor = RGBColor(0x4c, 0x8b, 0xf5);
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChartError {
+    #[error("Not enough points for a chart")]
+    NotEnoughData,
+    #[error("Failed to draw chart: {0}")]
+    Draw(String),
+    #[error("Failed to encode chart: {0}")]
+    Encode(#[from] png::EncodingError),
+}
+
+// Renders (timestamp, price) points as a PNG sparkline, green when the last price is at or above
+// the first. There are no axis labels, captions carry the figures so no font has to be bundled
+pub fn render_sparkline(points: &[(u64, f64)]) -> Result<Vec<u8>, ChartError> {
+    let points: Vec<(u64, f64)> = points
+        .iter()
+        .copied()
+        .filter(|(_, price)| price.is_finite() && *price > 0f64)
+        .collect();
+    if points.len() < 2 {
+        return Err(ChartError::NotEnoughData);
+    }
+
+    let (first_time, first_price) = points[0];
+    let (last_time, last_price) = points[points.len() - 1];
+    let low = points.iter().map(|(_, price)| *price).fold(f64::MAX, f64::min);
+    let high = points.iter().map(|(_, price)| *price).fold(f64::MIN, f64::max);
+    // Keeps the line off the edges, and a flat price in the middle rather than on the floor
+    let padding = ((high - low) * 0.1).max(high * 0.01);
+    let color = if last_price >= first_price { RISING } else { FALLING };
+    let draw_error = |e: DrawingAreaErrorKind<_>| ChartError::Draw(e.to_string());
+
+    let mut pixels = vec![0u8; (WIDTH * HEIGHT * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut pixels, (WIDTH, HEIGHT)).into_drawing_area();
+        root.fill(&BACKGROUND).map_err(draw_error)?;
+        let mut chart = ChartBuilder::on(&root)
+            .margin(10)
+            .build_cartesian_2d(first_time..last_time.max(first_time + 1), (low - padding)..(high + padding))
+            .map_err(draw_error)?;
+        chart
+            .draw_series(
+                AreaSeries::new(points.iter().copied(), low - padding, color.mix(0.2))
+                    .border_style(color.stroke_width(2)),
+            )
+            .map_err(draw_error)?;
+        root.present().map_err(draw_error)?;
+    }
+
+    encode(&pixels)
+}
+
+// Renders a position's balance over time as a step line from its first transfer to `now`, with
+// (timestamp, balance, is_buy) trades marked by green and red triangles
+pub fn render_position(
+    balances: &[(u64, f64)],
+    trades: &[(u64, f64, bool)],
+    now: u64,
+) -> Result<Vec<u8>, ChartError> {
+    let (first_time, _) = *balances.first().ok_or(ChartError::NotEnoughData)?;
+    // Each balance holds until the next transfer, and the last one until now
+    let mut steps: Vec<(u64, f64)> = Vec::with_capacity(balances.len() * 2 + 1);
+    for (time, balance) in balances {
+        if let Some((_, previous)) = steps.last().copied() {
+            steps.push((*time, previous));
+        }
+        steps.push((*time, *balance));
+    }
+    let last_time = now.max(first_time + 1);
+    steps.push((last_time, steps[steps.len() - 1].1));
+
+    let high = steps.iter().map(|(_, balance)| *balance).fold(0f64, f64::max);
+    let padding = (high * 0.1).max(f64::EPSILON);
+    let draw_error = |e: DrawingAreaErrorKind<_>| ChartError::Draw(e.to_string());
+
+    let mut pixels = vec![0u8; (WIDTH * HEIGHT * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut pixels, (WIDTH, HEIGHT)).into_drawing_area();
+        root.fill(&BACKGROUND).map_err(draw_error)?;
+        let mut chart = ChartBuilder::on(&root)
+            .margin(10)
+            .build_cartesian_2d(first_time..last_time, (-padding)..(high + padding))
+            .map_err(draw_error)?;
+        chart
+            .draw_series(LineSeries::new(steps, POSITION.stroke_width(2)))
+            .map_err(draw_error)?;
+        chart
+            .draw_series(trades.iter().map(|(time, balance, is_buy)| {
+                let color = if *is_buy { RISING } else { FALLING };
+                TriangleMarker::new((*time, *balance), 6, color.filled())
+            }))
+            .map_err(draw_error)?;
+        root.present().map_err(draw_error)?;
+    }
+    encode(&pixels)
+}
+
+fn encode(pixels: &[u8]) -> Result<Vec<u8>, ChartError> {
+    let mut image = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut image, WIDTH, HEIGHT);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(pixels)?;
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_sparkline() {
+        let image = render_sparkline(&[(100, 0.000_002), (160, 0.000_003), (220, 0.000_002_5)]).unwrap();
+        assert!(image.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // A flat price still renders, unusable points don't count towards the minimum
+        assert!(render_sparkline(&[(100, 1f64), (160, 1f64)]).is_ok());
+        assert!(matches!(
+            render_sparkline(&[(100, 1f64), (160, f64::NAN), (220, 0f64)]),
+            Err(ChartError::NotEnoughData)
+        ));
+    }
+
+    #[test]
+    fn test_render_position() {
+        let balances = [(100, 1_000f64), (160, 1_500f64), (220, 0f64)];
+        let trades = [(100, 1_000f64, true), (220, 0f64, false)];
+        let image = render_position(&balances, &trades, 300).unwrap();
+        assert!(image.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // A single transfer still charts as a flat line up to now
+        assert!(render_position(&balances[..1], &[], 100).is_ok());
+        assert!(matches!(render_position(&[], &[], 300), Err(ChartError::NotEnoughData)));
+    }
+}
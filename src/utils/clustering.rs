@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use tokio::task::JoinSet;
+
+use super::address::canonicalize_address;
+use super::cache::Cache;
+use super::call::{get_first_funder, get_latest_block};
+
+// Holders are many, so the funder cache gets a larger bound than the default
+const FUNDER_CACHE_MAX_ENTRIES: usize = 10_000;
+
+lazy_static! {
+    // Funding sources never change, so lookups only leave the cache when evicted
+    static ref FUNDER_CACHE: Cache<Option<String>> = Cache::new("funders", FUNDER_CACHE_MAX_ENTRIES, None);
+}
+
+// A holder as reported by the explorer
+#[derive(Debug, Clone)]
+pub struct HolderSample {
+    pub address: String,
+    pub balance: f64,
+    pub last_transfer_time: u64,
+}
+
+// Configuration for holder clustering heuristics
+#[derive(Clone)]
+pub struct ClusterConfig {
+    lockstep_window_secs: u64,
+    balance_tolerance_pct: f64,
+    max_funding_lookups: usize,
+    // Blocks before a holder's deployment searched for its funding, accounts are funded to deploy
+    funding_lookback_blocks: u64,
+    ignored_funders: Vec<String>,
+}
+
+impl ClusterConfig {
+    pub fn new() -> Self {
+        Self {
+            lockstep_window_secs: std::env::var("CLUSTER_LOCKSTEP_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(60),
+            balance_tolerance_pct: std::env::var("CLUSTER_BALANCE_TOLERANCE_PCT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(2f64),
+            max_funding_lookups: std::env::var("CLUSTER_MAX_FUNDING_LOOKUPS")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(25),
+            funding_lookback_blocks: std::env::var("CLUSTER_FUNDING_LOOKBACK_BLOCKS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(50_000),
+            // Exchange hot wallets fund thousands of unrelated users and must not link them
+            ignored_funders: std::env::var("CLUSTER_IGNORED_FUNDERS")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(canonicalize_address)
+                .collect(),
+        }
+    }
+}
+
+// Resolves the first STRK sender of each holder, bounded by max_funding_lookups uncached calls
+pub async fn get_funding_sources(
+    holders: &[HolderSample],
+    config: &ClusterConfig,
+) -> HashMap<String, String> {
+    let missing: Vec<String> = holders
+        .iter()
+        .filter(|holder| !FUNDER_CACHE.contains(&holder.address))
+        .map(|holder| holder.address.clone())
+        .collect();
+
+    let latest_block = if missing.is_empty() {
+        None
+    } else {
+        match get_latest_block().await {
+            Ok((block_number, _)) => Some(block_number),
+            Err(e) => {
+                eprintln!("Failed to load the latest block for funding lookups: {}", e);
+                None
+            }
+        }
+    };
+    let mut lookups = JoinSet::new();
+    let lookback_blocks = config.funding_lookback_blocks;
+    for address in missing.into_iter().take(config.max_funding_lookups) {
+        let latest_block = match latest_block {
+            Some(latest_block) => latest_block,
+            None => break,
+        };
+        lookups.spawn(async move {
+            let funder = get_first_funder(&address, latest_block, lookback_blocks).await;
+            (address, funder)
+        });
+    }
+    while let Some(result) = lookups.join_next().await {
+        match result {
+            Ok((address, Ok(funder))) => {
+                FUNDER_CACHE.insert(&address, funder);
+            }
+            Ok((address, Err(e))) => eprintln!("Failed to fetch funder of {}: {}", address, e),
+            Err(e) => eprintln!("Funding lookup task failed: {}", e),
+        }
+    }
+
+    holders
+        .iter()
+        .filter_map(|holder| {
+            FUNDER_CACHE
+                .get(&holder.address)
+                .flatten()
+                .filter(|funder| !config.ignored_funders.contains(funder))
+                .map(|funder| (holder.address.clone(), funder))
+        })
+        .collect()
+}
+
+// Groups holders funded by the same wallet or that last traded together with near-equal balances
+pub fn cluster_holders(
+    holders: &[HolderSample],
+    funders: &HashMap<String, String>,
+    config: &ClusterConfig,
+) -> Vec<Vec<usize>> {
+    let mut parents: Vec<usize> = (0..holders.len()).collect();
+
+    fn find(parents: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parents[root] != root {
+            root = parents[root];
+        }
+        parents[i] = root;
+        root
+    }
+
+    let mut by_funder: HashMap<&String, usize> = HashMap::new();
+    for (i, holder) in holders.iter().enumerate() {
+        if let Some(funder) = funders.get(&holder.address) {
+            match by_funder.get(funder) {
+                Some(&j) => {
+                    let (a, b) = (find(&mut parents, i), find(&mut parents, j));
+                    parents[a] = b;
+                }
+                None => {
+                    by_funder.insert(funder, i);
+                }
+            }
+        }
+    }
+
+    for i in 0..holders.len() {
+        for j in (i + 1)..holders.len() {
+            if in_lockstep(&holders[i], &holders[j], config) {
+                let (a, b) = (find(&mut parents, i), find(&mut parents, j));
+                parents[a] = b;
+            }
+        }
+    }
+
+    let mut clusters: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..holders.len() {
+        let root = find(&mut parents, i);
+        clusters.entry(root).or_default().push(i);
+    }
+    clusters.into_values().collect()
+}
+
+fn in_lockstep(a: &HolderSample, b: &HolderSample, config: &ClusterConfig) -> bool {
+    if a.last_transfer_time.abs_diff(b.last_transfer_time) > config.lockstep_window_secs {
+        return false;
+    }
+    let larger = a.balance.max(b.balance);
+    if larger <= 0f64 {
+        return false;
+    }
+    (a.balance - b.balance).abs() * 100f64 / larger <= config.balance_tolerance_pct
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holder(address: &str, balance: f64, last_transfer_time: u64) -> HolderSample {
+        HolderSample {
+            address: address.to_string(),
+            balance,
+            last_transfer_time,
+        }
+    }
+
+    #[test]
+    fn test_cluster_holders_links_funders_and_lockstep_buys() {
+        let config = ClusterConfig {
+            lockstep_window_secs: 60,
+            balance_tolerance_pct: 2f64,
+            max_funding_lookups: 0,
+            funding_lookback_blocks: 0,
+            ignored_funders: Vec::new(),
+        };
+        let holders = vec![
+            holder("0x1", 1000f64, 100),
+            holder("0x2", 1005f64, 130),
+            holder("0x3", 5000f64, 5000),
+            holder("0x4", 42f64, 9000),
+            holder("0x5", 7f64, 20000),
+        ];
+        let funders = HashMap::from([
+            ("0x3".to_string(), "0xf".to_string()),
+            ("0x4".to_string(), "0xf".to_string()),
+        ]);
+
+        let clusters = cluster_holders(&holders, &funders, &config);
+        assert_eq!(clusters.len(), 3);
+        assert!(clusters.iter().any(|cluster| cluster.len() == 2 && cluster.contains(&0)));
+        assert!(clusters.iter().any(|cluster| cluster.len() == 2 && cluster.contains(&2)));
+    }
+}
@@ -0,0 +1,107 @@
+use super::types::common::{LaunchContext, MemecoinInfo};
+use crate::storage::report::WEEK_SECS;
+use crate::storage::Storage;
+
+// Fewer launches than this in the week make percentiles meaningless
+const MIN_COHORT_SIZE: usize = 10;
+
+// Places a launch's starting market cap and liquidity among this week's launches
+pub async fn compare_to_cohort(
+    storage: &Storage,
+    info: &MemecoinInfo,
+    launched_at: u64,
+) -> Option<LaunchContext> {
+    let cohort = match storage
+        .launch_stats(launched_at.saturating_sub(WEEK_SECS), &info.address)
+        .await
+    {
+        Ok(cohort) => cohort,
+        Err(e) => {
+            eprintln!("Failed to load the launch cohort of {}: {}", info.address, e);
+            return None;
+        }
+    };
+    let market_caps: Vec<f64> = cohort.iter().filter_map(|launch| launch.market_cap).collect();
+    let liquidities: Vec<f64> = cohort.iter().filter_map(|launch| launch.usd_dex_liquidity).collect();
+    let market_cap_pct = info
+        .market_cap
+        .parse::<f64>()
+        .ok()
+        .and_then(|market_cap| percentile(market_cap, &market_caps));
+    let liquidity_pct = info
+        .usd_dex_liquidity
+        .parse::<f64>()
+        .ok()
+        .and_then(|liquidity| percentile(liquidity, &liquidities));
+    if market_cap_pct.is_none() && liquidity_pct.is_none() {
+        return None;
+    }
+    Some(LaunchContext::Cohort {
+        cohort_size: cohort.len(),
+        market_cap_pct,
+        liquidity_pct,
+    })
+}
+
+// Share of the cohort below `value`, None when the cohort is too small to tell
+fn percentile(value: f64, cohort: &[f64]) -> Option<f64> {
+    if cohort.len() < MIN_COHORT_SIZE {
+        return None;
+    }
+    let below = cohort.iter().filter(|other| **other < value).count();
+    Some(below as f64 * 100f64 / cohort.len() as f64)
+}
+
+// Where a percentile falls, worded for alerts
+pub fn describe_percentile(pct: f64) -> &'static str {
+    if pct >= 90f64 {
+        "in the top 10%"
+    } else if pct >= 75f64 {
+        "in the top 25%"
+    } else if pct >= 50f64 {
+        "above median"
+    } else if pct > 25f64 {
+        "below median"
+    } else {
+        "in the bottom 25%"
+    }
+}
+
+// "Starting mcap is in the top 10% of launches this week; liquidity above median"
+pub fn describe_cohort(market_cap_pct: Option<f64>, liquidity_pct: Option<f64>) -> String {
+    let mut parts = Vec::new();
+    if let Some(pct) = market_cap_pct {
+        parts.push(format!("Starting mcap is {} of launches this week", describe_percentile(pct)));
+    }
+    if let Some(pct) = liquidity_pct {
+        parts.push(if parts.is_empty() {
+            format!("Liquidity is {} of launches this week", describe_percentile(pct))
+        } else {
+            format!("liquidity {}", describe_percentile(pct))
+        });
+    }
+    parts.join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_and_wording() {
+        let cohort: Vec<f64> = (1..=10).map(|value| value as f64 * 1000f64).collect();
+        assert_eq!(percentile(20000f64, &cohort), Some(100f64));
+        assert_eq!(percentile(5500f64, &cohort), Some(50f64));
+        assert_eq!(percentile(500f64, &cohort), Some(0f64));
+        assert_eq!(percentile(500f64, &cohort[..9]), None);
+
+        assert_eq!(
+            describe_cohort(Some(95f64), Some(60f64)),
+            "Starting mcap is in the top 10% of launches this week; liquidity above median"
+        );
+        assert_eq!(
+            describe_cohort(None, Some(10f64)),
+            "Liquidity is in the bottom 25% of launches this week"
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use super::address::addresses_eq;
+use super::types::common::{LaunchContext, MemecoinInfo};
+use crate::storage::Storage;
+use crate::watcher::watchlist::percent_change;
+
+// Look-alikes attached to a single alert
+const MAX_CLONE_MATCHES: usize = 2;
+// Candidates pulled from the store before ranking
+const CLONE_CANDIDATES: u32 = 10;
+
+// Finds earlier launches this one imitates or shares a deployer with.
+// Every factory memecoin is deployed from the same class, so the class hash can't tell clones apart.
+pub async fn find_clones(storage: &Storage, info: &MemecoinInfo) -> Vec<LaunchContext> {
+    let mut candidates = match storage
+        .similar_launches(&info.address, &info.name, &info.symbol, &info.owner, CLONE_CANDIDATES)
+        .await
+    {
+        Ok(candidates) => candidates,
+        Err(e) => {
+            eprintln!("Failed to look up clones of {}: {}", info.address, e);
+            return Vec::new();
+        }
+    };
+    // Repeat deployers are the stronger signal, keep them first
+    candidates.sort_by_key(|candidate| {
+        !candidate
+            .owner
+            .as_deref()
+            .or(candidate.coin_info.as_ref().map(|coin| coin.owner.as_str()))
+            .is_some_and(|owner| addresses_eq(owner, &info.owner))
+    });
+
+    let mut clones = Vec::new();
+    for candidate in candidates.into_iter().take(MAX_CLONE_MATCHES) {
+        let symbol = candidate
+            .symbol
+            .clone()
+            .or(candidate.coin_info.as_ref().map(|coin| coin.symbol.clone()))
+            .unwrap_or_default();
+        let owner = candidate
+            .owner
+            .clone()
+            .or(candidate.coin_info.as_ref().map(|coin| coin.owner.clone()))
+            .unwrap_or_default();
+        let launch_price = candidate
+            .coin_info
+            .as_ref()
+            .and_then(|coin| coin.price.parse::<f64>().ok())
+            .filter(|price| *price > 0f64);
+        // Prices collected in the background, quoting the source live would hold up the alert
+        let price_change_pct = match launch_price {
+            Some(launch_price) => match storage.latest_price(&candidate.token_address).await {
+                Ok(price) => price.map(|price| percent_change(launch_price, price)),
+                Err(e) => {
+                    eprintln!("Failed to load the price of clone source {}: {}", candidate.token_address, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        clones.push(LaunchContext::CloneOf {
+            token_address: candidate.token_address,
+            symbol,
+            launched_at: candidate.block_timestamp,
+            price_change_pct,
+            same_deployer: !owner.is_empty() && addresses_eq(&owner, &info.owner),
+        });
+    }
+    clones
+}
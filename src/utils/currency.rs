@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+
+use super::cache::Cache;
+use super::format::format_number;
+use super::price::PRICE_ORACLE;
+use crate::constant::constants::{Token, ETHER, STRK};
+
+// Quote tokens move slowly next to memecoins, a minute old rate is fine for a card
+const RATE_TTL_SECS: u64 = 60;
+
+lazy_static! {
+    // USD value of one whole ETH or STRK
+    static ref USD_RATES: Cache<f64> = Cache::new("currency_rates", 4, Some(Duration::from_secs(RATE_TTL_SECS)));
+}
+
+// Unit market caps and liquidity are shown in, picked per chat with /currency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Currency {
+    #[default]
+    Usd,
+    Eth,
+    Strk,
+}
+
+impl Currency {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim_start_matches('$').to_lowercase().as_str() {
+            "usd" => Some(Currency::Usd),
+            "eth" => Some(Currency::Eth),
+            "strk" => Some(Currency::Strk),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Currency::Usd => "USD",
+            Currency::Eth => "ETH",
+            Currency::Strk => "STRK",
+        }
+    }
+
+    fn token(&self) -> Option<Token> {
+        match self {
+            Currency::Usd => None,
+            Currency::Eth => Some(ETHER),
+            Currency::Strk => Some(STRK),
+        }
+    }
+}
+
+// Converts USD figures into a currency at a fixed rate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Denomination {
+    pub currency: Currency,
+    // USD value of one whole unit of the currency
+    pub usd_rate: f64,
+}
+
+impl Denomination {
+    pub const USD: Denomination = Denomination {
+        currency: Currency::Usd,
+        usd_rate: 1f64,
+    };
+
+    // `usd` is the raw USD amount, `formatted_usd` how the card already shows it, which USD chats
+    // keep so their alerts don't change
+    pub fn format(&self, usd: &str, formatted_usd: &str) -> String {
+        if self.currency == Currency::Usd {
+            return format!("${}", formatted_usd);
+        }
+        let value = usd.parse::<f64>().unwrap_or_default() / self.usd_rate;
+        let amount = if value >= 1f64 {
+            format_number(&value.to_string()).unwrap_or_default()
+        } else {
+            // Liquidity of fresh launches is often a fraction of an ETH
+            let amount = format!("{:.4}", value);
+            amount.trim_end_matches('0').trim_end_matches('.').to_string()
+        };
+        format!("{} {}", amount, self.currency.as_str())
+    }
+}
+
+// Rate for a currency from the price oracle, cached. USD is used when the quote token can't be
+// priced, figures are better shown in the wrong unit than not at all
+pub async fn denomination(currency: Currency) -> Denomination {
+    let token = match currency.token() {
+        Some(token) => token,
+        None => return Denomination::USD,
+    };
+    if let Some(usd_rate) = USD_RATES.get(currency.as_str()) {
+        return Denomination { currency, usd_rate };
+    }
+    match PRICE_ORACLE.usd_price_per_unit(token.address, token.symbol.as_str()).await {
+        Ok(usd_per_unit) => {
+            let usd_rate = usd_per_unit * 10f64.powi(token.decimals as i32);
+            USD_RATES.insert(currency.as_str(), usd_rate);
+            Denomination { currency, usd_rate }
+        }
+        Err(e) => {
+            eprintln!("Failed to price {}, showing USD: {}", currency.as_str(), e);
+            Denomination::USD
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_in_currency() {
+        assert_eq!(Denomination::USD.format("1250000", "1.25M"), "$1.25M");
+
+        let eth = Denomination {
+            currency: Currency::Eth,
+            usd_rate: 2500f64,
+        };
+        assert_eq!(eth.format("1250000", "1.25M"), "500 ETH");
+        assert_eq!(eth.format("125", "125.00"), "0.05 ETH");
+        assert_eq!(eth.format("0", "0.00"), "0 ETH");
+
+        let strk = Denomination {
+            currency: Currency::Strk,
+            usd_rate: 0.5f64,
+        };
+        assert_eq!(strk.format("6000", "6K"), "12K STRK");
+    }
+
+    #[test]
+    fn test_parse_currency() {
+        assert_eq!(Currency::parse("ETH"), Some(Currency::Eth));
+        assert_eq!(Currency::parse("$usd"), Some(Currency::Usd));
+        assert_eq!(Currency::parse("strk"), Some(Currency::Strk));
+        assert_eq!(Currency::parse("btc"), None);
+    }
+}
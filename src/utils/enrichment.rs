@@ -0,0 +1,251 @@
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+use super::cache::{default_max_entries, Cache};
+use super::retry::get_json;
+use super::types::common::PairActivity;
+use crate::constant::network::{Network, NETWORK};
+
+const DEXSCREENER_API: &str = "https://api.dexscreener.com/latest/dex/tokens";
+const GECKOTERMINAL_API: &str = "https://api.geckoterminal.com/api/v2/networks/starknet-alpha/tokens";
+// Aggregators refresh every few minutes themselves
+const ACTIVITY_TTL_SECS: u64 = 300;
+
+lazy_static! {
+    static ref ENRICHMENT_CONFIG: EnrichmentConfig = EnrichmentConfig::new();
+    // Tokens without coverage are cached too, so they aren't looked up on every /sniQ
+    static ref ACTIVITY: Cache<Option<PairActivity>> =
+        Cache::new("pair_activity", default_max_entries(), Some(Duration::from_secs(ACTIVITY_TTL_SECS)));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnrichmentSource {
+    Dexscreener,
+    GeckoTerminal,
+}
+
+// Configuration for pair activity lookups, aggregators only index mainnet
+#[derive(Clone)]
+pub struct EnrichmentConfig {
+    source: Option<EnrichmentSource>,
+}
+
+impl EnrichmentConfig {
+    pub fn new() -> Self {
+        let default = match NETWORK.network {
+            Network::Mainnet => Some(EnrichmentSource::Dexscreener),
+            Network::Sepolia => None,
+        };
+        Self {
+            source: match std::env::var("ENRICHMENT_SOURCE").map(|v| v.to_lowercase()).as_deref() {
+                Ok("dexscreener") => Some(EnrichmentSource::Dexscreener),
+                Ok("geckoterminal") => Some(EnrichmentSource::GeckoTerminal),
+                Ok("off") | Ok("none") => None,
+                _ => default,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DexscreenerResponse {
+    #[serde(default)]
+    pairs: Option<Vec<DexscreenerPair>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DexscreenerPair {
+    chain_id: String,
+    pair_address: String,
+    #[serde(default)]
+    liquidity: Option<DexscreenerLiquidity>,
+    #[serde(default)]
+    volume: Option<DexscreenerWindow<f64>>,
+    #[serde(default)]
+    price_change: Option<DexscreenerWindow<f64>>,
+    #[serde(default)]
+    txns: Option<DexscreenerWindow<Transactions>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DexscreenerLiquidity {
+    #[serde(default)]
+    usd: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DexscreenerWindow<T> {
+    #[serde(default)]
+    h24: Option<T>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct Transactions {
+    #[serde(default)]
+    buys: u64,
+    #[serde(default)]
+    sells: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeckoResponse {
+    #[serde(default)]
+    data: Vec<GeckoPool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeckoPool {
+    attributes: GeckoPoolAttributes,
+}
+
+// GeckoTerminal sends amounts as strings
+#[derive(Debug, Deserialize)]
+struct GeckoPoolAttributes {
+    address: String,
+    #[serde(default)]
+    reserve_in_usd: Option<String>,
+    #[serde(default)]
+    volume_usd: Option<GeckoWindow<String>>,
+    #[serde(default)]
+    price_change_percentage: Option<GeckoWindow<String>>,
+    #[serde(default)]
+    transactions: Option<GeckoWindow<Transactions>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeckoWindow<T> {
+    #[serde(default)]
+    h24: Option<T>,
+}
+
+// The most liquid Starknet pair of the token, Dexscreener lists every chain it's seen on
+fn pick_dexscreener(response: DexscreenerResponse) -> Option<PairActivity> {
+    let pair = response
+        .pairs
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|pair| pair.chain_id == "starknet")
+        .max_by(|a, b| {
+            let liquidity = |pair: &DexscreenerPair| {
+                pair.liquidity.as_ref().and_then(|liquidity| liquidity.usd).unwrap_or_default()
+            };
+            liquidity(a).total_cmp(&liquidity(b))
+        })?;
+    let txns = pair.txns.and_then(|txns| txns.h24).unwrap_or_default();
+    Some(PairActivity {
+        source: "Dexscreener".to_string(),
+        pool_address: pair.pair_address,
+        volume_24h_usd: pair.volume.and_then(|volume| volume.h24).unwrap_or_default(),
+        price_change_24h_pct: pair.price_change.and_then(|change| change.h24),
+        buys_24h: txns.buys,
+        sells_24h: txns.sells,
+    })
+}
+
+fn pick_geckoterminal(response: GeckoResponse) -> Option<PairActivity> {
+    let parse = |value: Option<&String>| value.and_then(|value| value.parse::<f64>().ok());
+    let pool = response.data.into_iter().map(|pool| pool.attributes).max_by(|a, b| {
+        let reserve = |pool: &GeckoPoolAttributes| parse(pool.reserve_in_usd.as_ref()).unwrap_or_default();
+        reserve(a).total_cmp(&reserve(b))
+    })?;
+    let volume = pool.volume_usd.as_ref().and_then(|volume| volume.h24.as_ref());
+    let price_change = pool.price_change_percentage.as_ref().and_then(|change| change.h24.as_ref());
+    let (volume_24h_usd, price_change_24h_pct) = (parse(volume).unwrap_or_default(), parse(price_change));
+    let txns = pool.transactions.and_then(|txns| txns.h24).unwrap_or_default();
+    Some(PairActivity {
+        source: "GeckoTerminal".to_string(),
+        volume_24h_usd,
+        price_change_24h_pct,
+        pool_address: pool.address,
+        buys_24h: txns.buys,
+        sells_24h: txns.sells,
+    })
+}
+
+async fn fetch_activity(
+    source: EnrichmentSource,
+    token_address: &str,
+) -> Result<Option<PairActivity>, reqwest::Error> {
+    match source {
+        EnrichmentSource::Dexscreener => {
+            let url = format!("{}/{}", DEXSCREENER_API, token_address);
+            Ok(pick_dexscreener(get_json("Dexscreener pairs", &url).await?))
+        }
+        EnrichmentSource::GeckoTerminal => {
+            let url = format!("{}/{}/pools", GECKOTERMINAL_API, token_address);
+            match get_json("GeckoTerminal pools", &url).await {
+                Ok(response) => Ok(pick_geckoterminal(response)),
+                // Tokens GeckoTerminal hasn't indexed are a 404
+                Err(e) if e.status().is_some_and(|status| status.as_u16() == 404) => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
+    }
+}
+
+// 24h trading activity of a launched token's main pool. None when enrichment is off, the
+// aggregator doesn't cover the token yet or can't be reached, the card just goes without it
+pub async fn pair_activity(token_address: &str) -> Option<PairActivity> {
+    let source = ENRICHMENT_CONFIG.source?;
+    if let Some(activity) = ACTIVITY.get(token_address) {
+        return activity;
+    }
+    match fetch_activity(source, token_address).await {
+        Ok(activity) => {
+            ACTIVITY.insert(token_address, activity.clone());
+            activity
+        }
+        Err(e) => {
+            eprintln!("Failed to load pair activity of {}: {}", token_address, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_dexscreener_pair() {
+        let response: DexscreenerResponse = serde_json::from_str(
+            r#"{"pairs":[
+                {"chainId":"base","pairAddress":"0xbase","liquidity":{"usd":90000}},
+                {"chainId":"starknet","pairAddress":"0xsmall","liquidity":{"usd":100}},
+                {"chainId":"starknet","pairAddress":"0xmain","liquidity":{"usd":5000},
+                 "volume":{"h24":1234.5},"priceChange":{"h24":-12.5},"txns":{"h24":{"buys":30,"sells":12}}}
+            ]}"#,
+        )
+        .unwrap();
+        let activity = pick_dexscreener(response).unwrap();
+        assert_eq!(activity.pool_address, "0xmain");
+        assert_eq!(activity.volume_24h_usd, 1234.5);
+        assert_eq!(activity.price_change_24h_pct, Some(-12.5));
+        assert_eq!((activity.buys_24h, activity.sells_24h), (30, 12));
+
+        // Tokens Dexscreener hasn't seen come back with null pairs
+        let response: DexscreenerResponse = serde_json::from_str(r#"{"pairs":null}"#).unwrap();
+        assert_eq!(pick_dexscreener(response), None);
+    }
+
+    #[test]
+    fn test_pick_geckoterminal_pool() {
+        let response: GeckoResponse = serde_json::from_str(
+            r#"{"data":[
+                {"attributes":{"address":"0xa","reserve_in_usd":"10.5"}},
+                {"attributes":{"address":"0xb","reserve_in_usd":"2500.1",
+                 "volume_usd":{"h24":"800.25"},"price_change_percentage":{"h24":"4.2"},
+                 "transactions":{"h24":{"buys":5,"sells":3}}}}
+            ]}"#,
+        )
+        .unwrap();
+        let activity = pick_geckoterminal(response).unwrap();
+        assert_eq!(activity.pool_address, "0xb");
+        assert_eq!(activity.volume_24h_usd, 800.25);
+        assert_eq!(activity.price_change_24h_pct, Some(4.2));
+        assert_eq!(activity.buys_24h, 5);
+    }
+}
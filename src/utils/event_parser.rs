@@ -1,8 +1,10 @@
 use anyhow::Context;
+use num_traits::ToPrimitive;
 use starknet::core::types::{Felt, U256};
 use starknet::core::utils::{normalize_address, parse_cairo_short_string};
 
 use super::call::get_aggregate_call_data;
+use super::sanitize::is_hidden_char;
 pub trait FromFieldBytes: Sized {
     fn from_field_bytes(bytes: [u8; 32]) -> Self;
 }
@@ -44,16 +46,61 @@ pub fn u256_to_decimal_str(value: U256) -> String {
     format!("{}", value)
 }
 
-pub fn parse_and_validate_short_string(felt: &Felt) -> anyhow::Result<String> {
-    let result = parse_cairo_short_string(felt)?;
+// Decodes a token name or symbol, a felt252 short string or a ByteArray spanning several felts.
+// Metadata that is neither, or has unprintable characters, becomes a truncated hex label so the
+// token still gets its alert
+pub fn decode_token_string(felts: &[Felt]) -> String {
+    let decoded = match felts {
+        [felt] => parse_short_string(felt),
+        _ => parse_byte_array(felts),
+    }
+    .filter(|decoded| is_printable(decoded, |c| !c.is_control()));
+    match decoded {
+        Some(decoded) => decoded,
+        None => unnamed(felts.first().copied().unwrap_or_default()),
+    }
+}
+
+// Hidden characters are kept so alerts can flag them, sanitize_display strips them
+fn is_printable(decoded: &str, allowed: impl Fn(char) -> bool) -> bool {
+    decoded.chars().filter(|c| !is_hidden_char(*c)).all(allowed)
+}
 
-    if !result
-        .chars()
-        .all(|c| c.is_ascii_graphic() || c.is_ascii_whitespace())
-    {
-        return Ok(felt.to_string());
+// Short strings are read as UTF-8 like ByteArrays, names with emoji fit in one felt too
+fn parse_short_string(felt: &Felt) -> Option<String> {
+    let bytes = felt.to_bytes_be();
+    if bytes[0] != 0 {
+        return None;
+    }
+    let start = bytes.iter().position(|byte| *byte != 0).unwrap_or(bytes.len());
+    String::from_utf8(bytes[start..].to_vec()).ok()
+}
+
+// ByteArray layout: the number of full 31 byte words, the words, then the pending word and its
+// length in bytes
+fn parse_byte_array(felts: &[Felt]) -> Option<String> {
+    let (full_words, rest) = felts.split_first()?;
+    let full_words = full_words.to_usize()?;
+    // The length is read from the event, it can't be trusted to fit
+    if full_words > rest.len() || full_words.checked_add(2)? != rest.len() {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(full_words.checked_mul(31)?.checked_add(31)?);
+    for word in &rest[..full_words] {
+        bytes.extend_from_slice(&word.to_bytes_be()[1..]);
+    }
+    let pending_len = rest[full_words + 1].to_usize().filter(|len| *len < 31)?;
+    bytes.extend_from_slice(&rest[full_words].to_bytes_be()[32 - pending_len..]);
+    String::from_utf8(bytes).ok()
+}
+
+fn unnamed(felt: Felt) -> String {
+    let hex = felt.to_hex_string();
+    if hex.len() > 12 {
+        format!("{}…{} (unnamed)", &hex[..6], &hex[hex.len() - 4..])
+    } else {
+        format!("{} (unnamed)", hex)
     }
-    Ok(result)
 }
 
 #[derive(Debug, Clone)]
@@ -80,23 +127,135 @@ pub struct LaunchEvent {
     pub exchange_name: String,
 }
 
+// ERC20 Transfer emitted by a memecoin, the amount is raw
+#[derive(Debug, Clone)]
+pub struct TransferEvent {
+    pub token_address: Felt,
+    pub from: Felt,
+    pub to: Felt,
+    pub amount: String,
+}
+
+impl TransferEvent {
+    // Cairo 1 tokens put from and to in the keys, older ones emit every member as data
+    pub fn from_starknet_event(token_address: Felt, keys: &[Felt], data: &[Felt]) -> anyhow::Result<Self> {
+        let (from, to, low, high) = match (keys, data) {
+            ([_, from, to], [low, high]) => (from, to, low, high),
+            ([_], [from, to, low, high]) => (from, to, low, high),
+            _ => anyhow::bail!("Unexpected Transfer layout, {} keys and {} data", keys.len(), data.len()),
+        };
+        Ok(Self {
+            token_address: normalize_address(token_address),
+            from: normalize_address(*from),
+            to: normalize_address(*to),
+            amount: u256_to_decimal_str(U256::from_words(
+                u128::from_field_bytes(low.to_bytes_be()),
+                u128::from_field_bytes(high.to_bytes_be()),
+            )),
+        })
+    }
+}
+
+// OwnershipTransferred emitted by Ownable tokens, to the zero address when ownership is renounced
+#[derive(Debug, Clone)]
+pub struct OwnershipTransferredEvent {
+    pub previous_owner: Felt,
+    pub new_owner: Felt,
+}
+
+impl OwnershipTransferredEvent {
+    // OpenZeppelin's Cairo 1 component puts both owners in the keys, older ones emit them as data
+    pub fn from_starknet_event(keys: &[Felt], data: &[Felt]) -> anyhow::Result<Self> {
+        let (previous_owner, new_owner) = match (keys, data) {
+            ([_, previous_owner, new_owner], []) => (previous_owner, new_owner),
+            ([_], [previous_owner, new_owner]) => (previous_owner, new_owner),
+            _ => anyhow::bail!(
+                "Unexpected OwnershipTransferred layout, {} keys and {} data",
+                keys.len(),
+                data.len()
+            ),
+        };
+        Ok(Self {
+            previous_owner: normalize_address(*previous_owner),
+            new_owner: normalize_address(*new_owner),
+        })
+    }
+}
+
+// Decoders event subscriptions dispatch to, see EVENT_SUBSCRIPTIONS in the indexer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventDecoder {
+    MemecoinCreated,
+    MemecoinLaunched,
+}
+
+impl EventDecoder {
+    pub const ALL: [EventDecoder; 2] = [EventDecoder::MemecoinCreated, EventDecoder::MemecoinLaunched];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventDecoder::MemecoinCreated => "memecoin_created",
+            EventDecoder::MemecoinLaunched => "memecoin_launched",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|decoder| decoder.name() == name)
+    }
+
+    // Decoder of an event emitted under this name by Unruggable factories
+    pub fn for_event(event_name: &str) -> Option<Self> {
+        match event_name {
+            "MemecoinCreated" => Some(EventDecoder::MemecoinCreated),
+            "MemecoinLaunched" => Some(EventDecoder::MemecoinLaunched),
+            _ => None,
+        }
+    }
+}
+
 pub trait FromStarknetEventData: Sized {
     fn from_starknet_event_data(data: Vec<Felt>) -> anyhow::Result<Self>;
 }
 
+// felt252 layout of MemecoinCreated: owner, name, symbol, initial_supply as two felts, memecoin
+const CREATION_FELTS: usize = 6;
+
+// Reads a name or symbol, its ByteArray spanning a felt per full word plus three
+fn next_token_string(
+    data: &mut std::slice::Iter<Felt>,
+    byte_array: bool,
+    field: &str,
+) -> anyhow::Result<String> {
+    let first = *data.next().with_context(|| format!("Missing {}", field))?;
+    if !byte_array {
+        return Ok(decode_token_string(&[first]));
+    }
+    let full_words = first.to_usize().with_context(|| format!("Invalid {} length", field))?;
+    // Bounded by what is left of the event before anything is allocated or read
+    let remaining = full_words
+        .checked_add(2)
+        .filter(|remaining| *remaining <= data.len())
+        .with_context(|| format!("Truncated {}", field))?;
+    let mut felts = Vec::with_capacity(remaining + 1);
+    felts.push(first);
+    for _ in 0..remaining {
+        felts.push(*data.next().with_context(|| format!("Truncated {}", field))?);
+    }
+    Ok(decode_token_string(&felts))
+}
+
 impl FromStarknetEventData for CreationEvent {
     fn from_starknet_event_data(data: Vec<Felt>) -> Result<Self, anyhow::Error> {
+        // Factories built with newer Cairo emit names and symbols as ByteArrays, which is the only
+        // way the event gets longer than the felt252 layout
+        let byte_array = data.len() > CREATION_FELTS;
         let mut data = data.iter();
 
         let owner = normalize_address(Felt::from_bytes_be(
             &data.next().context("Missing owner")?.to_bytes_be(),
         ));
-        let name: String = parse_cairo_short_string(&Felt::from_bytes_be(
-            &data.next().context("Missing name")?.to_bytes_be(),
-        ))?;
-        let symbol: String = parse_and_validate_short_string(&Felt::from_bytes_be(
-            &data.next().context("Missing symbol")?.to_bytes_be(),
-        ))?;
+        let name = next_token_string(&mut data, byte_array, "name")?;
+        let symbol = next_token_string(&mut data, byte_array, "symbol")?;
         let initial_supply = u256_to_decimal_str(U256::from_words(
             u128::from_field_bytes(
                 data.next()
@@ -152,3 +311,80 @@ impl FromStarknetEventData for LaunchEvent {
         Ok(launch_data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet::core::utils::cairo_short_string_to_felt;
+
+    #[test]
+    fn test_decode_token_string() {
+        assert_eq!(decode_token_string(&[cairo_short_string_to_felt("DOGE").unwrap()]), "DOGE");
+
+        // "A name longer than thirty-one bytes 🐶" as a ByteArray: one full word and a pending one
+        let name = "A name longer than thirty-one bytes 🐶";
+        let (full, pending) = name.as_bytes().split_at(31);
+        let byte_array = [
+            Felt::ONE,
+            Felt::from_bytes_be_slice(full),
+            Felt::from_bytes_be_slice(pending),
+            Felt::from(pending.len()),
+        ];
+        assert_eq!(decode_token_string(&byte_array), name);
+
+        // Short strings are UTF-8, not one char per byte
+        assert_eq!(decode_token_string(&[Felt::from_bytes_be_slice("PEPE 🐸".as_bytes())]), "PEPE 🐸");
+
+        // Neither a short string nor a ByteArray
+        assert_eq!(decode_token_string(&[Felt::from_bytes_be_slice(&[0x41, 0xe9])]), "0x41e9 (unnamed)");
+        assert_eq!(decode_token_string(&[Felt::from(0xffu8)]), "0xff (unnamed)");
+        assert_eq!(
+            decode_token_string(&[Felt::from_hex_unchecked("0x1f00ff00ff00ff00ff00ff00ff00ff00ff")]),
+            "0x1f00…00ff (unnamed)"
+        );
+        assert_eq!(decode_token_string(&[Felt::TWO, Felt::ONE]), "0x2 (unnamed)");
+        // A word count that overflows once the pending word is added
+        let overflowing = [Felt::from(usize::MAX), Felt::ONE, Felt::ONE];
+        assert_eq!(decode_token_string(&overflowing), unnamed(Felt::from(usize::MAX)));
+    }
+
+    #[test]
+    fn test_ownership_transferred_layouts() {
+        let (selector, previous, new) = (Felt::ONE, Felt::from(0xabcu64), Felt::ZERO);
+        let keyed = OwnershipTransferredEvent::from_starknet_event(&[selector, previous, new], &[]).unwrap();
+        assert_eq!((keyed.previous_owner, keyed.new_owner), (previous, new));
+        let legacy = OwnershipTransferredEvent::from_starknet_event(&[selector], &[previous, new]).unwrap();
+        assert_eq!((legacy.previous_owner, legacy.new_owner), (previous, new));
+        assert!(OwnershipTransferredEvent::from_starknet_event(&[selector, previous], &[new]).is_err());
+    }
+
+    #[test]
+    fn test_creation_event_with_byte_arrays() {
+        let short = |text: &str| cairo_short_string_to_felt(text).unwrap();
+        let owner = Felt::from(0xabcu64);
+        let memecoin = Felt::from(0xdefu64);
+        let supply = [Felt::from(1_000u64), Felt::ZERO];
+
+        let felts = [vec![owner, short("Doge"), short("DOGE")], supply.to_vec(), vec![memecoin]].concat();
+        let event = CreationEvent::from_starknet_event_data(felts).unwrap();
+        assert_eq!((event.name.as_str(), event.symbol.as_str()), ("Doge", "DOGE"));
+
+        // Both strings as ByteArrays without full words, the layout is told apart by its length
+        let felts = [
+            vec![owner, Felt::ZERO, short("Doge"), Felt::from(4u8)],
+            vec![Felt::ZERO, short("DOGE"), Felt::from(4u8)],
+            supply.to_vec(),
+            vec![memecoin],
+        ]
+        .concat();
+        let event = CreationEvent::from_starknet_event_data(felts).unwrap();
+        assert_eq!((event.name.as_str(), event.symbol.as_str()), ("Doge", "DOGE"));
+        assert_eq!((event.initial_supply.as_str(), event.memecoin_address), ("1000", memecoin));
+
+        // A name claiming more words than the event holds
+        let name = [Felt::from(usize::MAX), short("Doge"), Felt::from(4u8)];
+        let symbol = short("DOGE");
+        let felts = [vec![owner], name.to_vec(), vec![symbol], supply.to_vec(), vec![memecoin]].concat();
+        assert!(CreationEvent::from_starknet_event_data(felts).is_err());
+    }
+}
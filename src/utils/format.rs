@@ -0,0 +1,100 @@
+use std::fmt::Write;
+
+// Memecoins have 18 decimals
+const DECIMALS: usize = 18;
+
+// Compact USD style amount, e.g. 1.25M or 950.5, with up to 2 decimals
+pub fn format_number(num_str: &str) -> Result<String, &'static str> {
+    let num = num_str.parse::<f64>().map_err(|_| "Invalid number format")?;
+
+    let (value, suffix) = if num >= 1_000_000_000f64 {
+        (num / 1_000_000_000f64, "B")
+    } else if num >= 1_000_000f64 {
+        (num / 1_000_000f64, "M")
+    } else if num >= 1_000f64 {
+        (num / 1_000f64, "K")
+    } else {
+        (num, "")
+    };
+
+    // Written straight into the result, trailing zeros are then cut in place
+    let mut formatted = String::with_capacity(12);
+    let _ = write!(formatted, "{:.2}", value);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.').len();
+    formatted.truncate(trimmed);
+    formatted.push_str(suffix);
+    Ok(formatted)
+}
+
+// Raw 18 decimals amount as a plain decimal string, e.g. 1500000000000000000 -> 1.5
+pub fn format_large_number(input: &str) -> Result<String, &'static str> {
+    if !input.bytes().all(|b| b.is_ascii_digit()) {
+        return Err("Invalid input: must contain only digits");
+    }
+
+    let split = input.len().saturating_sub(DECIMALS);
+    let (int_part, frac_part) = input.split_at(split);
+    // Zeros the fraction is missing on the left when the amount is below one
+    let padding = DECIMALS - frac_part.len();
+    let int_part = int_part.trim_start_matches('0');
+    let frac_part = frac_part.trim_end_matches('0');
+
+    let mut result = String::with_capacity(int_part.len() + padding + frac_part.len() + 2);
+    if int_part.is_empty() {
+        result.push('0');
+    } else {
+        result.push_str(int_part);
+    }
+    if !frac_part.is_empty() {
+        result.push('.');
+        result.extend(std::iter::repeat_n('0', padding));
+        result.push_str(frac_part);
+    }
+    Ok(result)
+}
+
+// Share of the supply held by the team, both raw amounts
+pub fn team_allocation_pct(total_supply: &str, team_allocation: &str) -> Option<f64> {
+    let total_supply = format_large_number(total_supply).ok()?.parse::<f64>().ok()?;
+    let team_allocation = format_large_number(team_allocation).ok()?.parse::<f64>().ok()?;
+    if total_supply == 0f64 {
+        return None;
+    }
+    Some(team_allocation * 100f64 / total_supply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number("1250000").unwrap(), "1.25M");
+        assert_eq!(format_number("3000000000").unwrap(), "3B");
+        assert_eq!(format_number("999.999").unwrap(), "1000");
+        assert_eq!(format_number("1500.5").unwrap(), "1.5K");
+        assert_eq!(format_number("0.004").unwrap(), "0");
+        assert_eq!(format_number("12.30").unwrap(), "12.3");
+        assert!(format_number("").is_err());
+        assert!(format_number("abc").is_err());
+    }
+
+    #[test]
+    fn test_format_large_number() {
+        assert_eq!(format_large_number("1000000000000000000000000000").unwrap(), "1000000000");
+        assert_eq!(format_large_number("1500000000000000000").unwrap(), "1.5");
+        assert_eq!(format_large_number("500000000000000000").unwrap(), "0.5");
+        assert_eq!(format_large_number("5").unwrap(), "0.000000000000000005");
+        assert_eq!(format_large_number("0").unwrap(), "0");
+        assert_eq!(format_large_number("").unwrap(), "0");
+        assert!(format_large_number("1e18").is_err());
+    }
+
+    #[test]
+    fn test_team_allocation_pct() {
+        let pct = team_allocation_pct("1000000000000000000000000000", "25000000000000000000000000").unwrap();
+        assert!((pct - 2.5).abs() < 1e-9);
+        assert_eq!(team_allocation_pct("0", "0"), None);
+        assert_eq!(team_allocation_pct("abc", "0"), None);
+    }
+}
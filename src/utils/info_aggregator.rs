@@ -1,63 +1,231 @@
 use std::collections::HashSet;
+use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use super::call::{get_aggregate_call_data, get_balance, validate_memecoins};
-use super::market_cap::calculate_market_cap;
+use lazy_static::lazy_static;
+
+use super::address::{addresses_eq, canonicalize_address};
+use super::cache::{default_max_entries, Cache};
+use super::clustering::{cluster_holders, get_funding_sources, ClusterConfig, HolderSample};
+use super::call::{
+    get_aggregate_call_data, get_aggregate_call_data_many, get_balance, get_block_timestamp,
+    validate_memecoins,
+};
+use super::liquidity::{fee_to_percent, get_launch_market_cap, get_pool_reserves};
+use super::lockers::{attribute_holder, get_locked_balances, Attribution};
+use super::market_cap::{calculate_market_cap, get_usd_price_per_unit};
+use super::portfolio::{classify_positions, PortfolioConfig};
+use super::retry::get_json;
+use super::risk::assess_risk;
+use crate::constant::constants::{quote_token, DECIMALS};
+use crate::constant::network::NETWORK;
 use super::types::common::{
-    FilteredTokenData, HolderApiResponse, Holders, HoldingApiResponse, MemecoinInfo,
-    TokenCategoryResponse, TokenHoldings, UserTokenInfo,
+    FilteredTokenData, HolderApiResponse, Holders, HoldingApiResponse, MemecoinInfo, RiskReport,
+    TokenCategoryResponse, TokenHoldings, TopHolder, UserTokenInfo,
 };
 use super::types::ekubo::Memecoin;
 
-async fn fetch_holders_data(token_address: &str) -> Result<TokenCategoryResponse, anyhow::Error> {
-    let explorer_env = std::env::var("EXPLORER_API").expect("EXPLORER_API must be set.");
+lazy_static! {
+    // Short lived so /sniQ, /spot and broadcasts of the same token share one round of upstream calls
+    static ref AGGREGATE_CACHE: Cache<(MemecoinInfo, TokenCategoryResponse)> = Cache::new(
+        "token_info",
+        default_max_entries(),
+        Some(Duration::from_secs(
+            std::env::var("AGGREGATE_CACHE_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(30),
+        )),
+    );
+    // Lets /peek toggle between meaningful and all positions without re-pricing every token
+    static ref HOLDINGS_CACHE: Cache<TokenHoldings> = Cache::new(
+        "holdings",
+        default_max_entries(),
+        Some(Duration::from_secs(
+            std::env::var("HOLDINGS_CACHE_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(120),
+        )),
+    );
+    // Overall budget of one token aggregation or wallet scan, on top of each upstream's own timeout
+    static ref AGGREGATE_DEADLINE: Duration = Duration::from_secs(
+        std::env::var("AGGREGATE_DEADLINE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(45),
+    );
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{0} took longer than {1:?}")]
+pub struct DeadlineExceeded(&'static str, Duration);
+
+// Runs an enrichment under the overall deadline. Running out drops the work, which cancels every
+// upstream call still in flight at its next await instead of leaving it to hold the worker
+pub async fn with_deadline<T>(
+    what: &'static str,
+    work: impl Future<Output = Result<T, anyhow::Error>>,
+) -> Result<T, anyhow::Error> {
+    within(*AGGREGATE_DEADLINE, what, work).await
+}
+
+async fn within<T>(
+    deadline: Duration,
+    what: &'static str,
+    work: impl Future<Output = Result<T, anyhow::Error>>,
+) -> Result<T, anyhow::Error> {
+    match tokio::time::timeout(deadline, work).await {
+        Ok(result) => result,
+        Err(_) => {
+            eprintln!("{} took longer than {:?}, cancelled", what, deadline);
+            Err(DeadlineExceeded(what, deadline).into())
+        }
+    }
+}
+
+// Holders sampled per token, also the explorer page size
+const HOLDER_PAGE_SIZE: usize = 100;
+// Largest holders whose combined share is checked for concentration
+const TOP_HOLDERS: usize = 10;
+
+// Only the first page is fetched, every earlier page of a paginated list is full
+fn count_holder_pages(response: &HolderApiResponse) -> usize {
+    if response.has_more {
+        (response.last_page.max(1) as usize - 1) * HOLDER_PAGE_SIZE
+    } else {
+        response.items.len()
+    }
+}
+
+// Holder count alone for samplers that need nothing else, from a cached aggregation when there is one
+pub async fn count_holders(token_address: &str) -> Result<usize, anyhow::Error> {
+    let canonical_address = canonicalize_address(token_address)
+        .ok_or_else(|| anyhow::anyhow!("Invalid token address: {}", token_address))?;
+    if let Some((_, holders)) = AGGREGATE_CACHE.get(&canonical_address) {
+        return Ok(holders.total_holders);
+    }
+    let url = format!(
+        "{}/{}/holders?ps={}&type=erc20",
+        NETWORK.explorer_api, canonical_address, HOLDER_PAGE_SIZE
+    );
+    let response = get_json::<HolderApiResponse>("Explorer holders", &url).await?;
+    Ok(count_holder_pages(&response))
+}
+
+async fn fetch_holders_data(
+    token_address: &str,
+    total_supply: &str,
+) -> Result<TokenCategoryResponse, anyhow::Error> {
+    let explorer_env = &NETWORK.explorer_api;
 
     let url = format!(
-        "{}/{}/holders?ps=100&type=erc20",
-        explorer_env, token_address
+        "{}/{}/holders?ps={}&type=erc20",
+        explorer_env, token_address, HOLDER_PAGE_SIZE
     );
 
-    let response = reqwest::get(&url)
-        .await?
-        .json::<HolderApiResponse>()
-        .await?;
+    let response = get_json::<HolderApiResponse>("Explorer holders", &url).await?;
+    let total_holders = count_holder_pages(&response);
 
     let filtered_items: Vec<Holders> = response
         .items
         .into_iter()
         .filter(|holder| {
             !matches!(
-                holder.contractAlias.as_deref(),
+                holder.contract_alias.as_deref(),
                 Some("Unruggable.meme") | Some("Ekubo: Core")
             )
         })
         .collect();
 
-    let category = if response.hasMore {
-        format!("🌑 *>100 hodlers* — *Moon phase incoming!*")
+    // Balances sitting in lock positions count towards their owner, vault balances are dropped
+    let mut samples: Vec<HolderSample> = Vec::new();
+    for holder in filtered_items.iter() {
+        let mut address =
+            canonicalize_address(&holder.holder).unwrap_or_else(|| holder.holder.clone());
+        match attribute_holder(&address).await {
+            Ok(Some(Attribution::Owner(owner))) => address = owner,
+            Ok(Some(Attribution::Vault)) => continue,
+            Ok(None) => {}
+            Err(e) => eprintln!("Failed to attribute holder {}: {}", address, e),
+        }
+        let balance = holder.balance.parse::<f64>().unwrap_or_default();
+        match samples.iter_mut().find(|sample| sample.address == address) {
+            Some(sample) => sample.balance += balance,
+            None => samples.push(HolderSample {
+                address,
+                balance,
+                last_transfer_time: holder.last_transfer_time,
+            }),
+        }
+    }
+    let cluster_config = ClusterConfig::new();
+    let funders = get_funding_sources(&samples, &cluster_config).await;
+    let effective_holder_count = cluster_holders(&samples, &funders, &cluster_config).len();
+
+    // Bucket on effective holders so sybil wallets don't make a token look well distributed
+    let mut category = if response.has_more && effective_holder_count == samples.len() {
+        "🌑 <b>&gt;100 hodlers</b> — <b>Moon phase incoming!</b>".to_string()
     } else {
-        match filtered_items.len() {
-            0..=9 => format!(" *<10* — *Early bird special! 🌱*"),
-            10..=19 => format!(" *>10* — *FOMO vibes! 🚀 *"),
-            20..=49 => format!(" *>20* — *It’s heating up! 🔥*"),
-            _ => format!(" *>50* — *Time to jump in! 💥*"),
+        match effective_holder_count {
+            0..=9 => " <b>&lt;10</b> — <b>Early bird special! 🌱</b>".to_string(),
+            10..=19 => " <b>&gt;10</b> — <b>FOMO vibes! 🚀 </b>".to_string(),
+            20..=49 => " <b>&gt;20</b> — <b>It’s heating up! 🔥</b>".to_string(),
+            _ => " <b>&gt;50</b> — <b>Time to jump in! 💥</b>".to_string(),
         }
     };
+    if effective_holder_count < samples.len() {
+        category.push_str(&format!(
+            "\n🕸 <b>Clusters:</b> {} wallets ≈ {} effective holders",
+            samples.len(),
+            effective_holder_count
+        ));
+    }
+
+    let total_supply = total_supply.parse::<f64>().unwrap_or_default();
+    let top_holders = get_top_holders(&samples, total_supply, TOP_HOLDERS);
+    let top_holders_pct = if total_supply > 0f64 {
+        Some(top_holders.iter().map(|holder| holder.pct).sum())
+    } else {
+        None
+    };
 
     let result = TokenCategoryResponse {
         token_address: token_address.to_string(),
         category: category.to_string(),
+        holder_count: samples.len(),
+        effective_holder_count,
+        total_holders: total_holders.max(samples.len()),
+        top_holders_pct,
+        top_holders,
     };
 
     Ok(result)
 }
 
+// The `n` largest holders with their share of the supply. Samples already leave out the pool and
+// vaults and credit locked balances to their owners
+pub fn get_top_holders(samples: &[HolderSample], total_supply: f64, n: usize) -> Vec<TopHolder> {
+    if total_supply <= 0f64 {
+        return Vec::new();
+    }
+    let mut ranked: Vec<&HolderSample> = samples.iter().collect();
+    ranked.sort_by(|a, b| b.balance.total_cmp(&a.balance));
+    ranked
+        .into_iter()
+        .take(n)
+        .map(|sample| TopHolder {
+            address: sample.address.clone(),
+            pct: sample.balance * 100f64 / total_supply,
+        })
+        .collect()
+}
+
 async fn is_valid_account(account: &str) -> Result<bool, anyhow::Error> {
-    let explorer_env = std::env::var("EXPLORER_API").expect("EXPLORER_API must be set.");
+    let explorer_env = &NETWORK.explorer_api;
     let url = format!("{}/{}/", explorer_env, account);
-    let response = reqwest::get(&url)
-        .await?
-        .json::<serde_json::Value>()
-        .await?;
+    let response = get_json::<serde_json::Value>("Explorer account", &url).await?;
 
     Ok(response
         .get("isAccount")
@@ -71,14 +239,11 @@ async fn fetch_account_holdings(account: &str) -> Result<Vec<FilteredTokenData>,
         println!("{} is not a valid account", account);
     }
 
-    let explorer_env = std::env::var("EXPLORER_API").expect("EXPLORER_API must be set.");
+    let explorer_env = &NETWORK.explorer_api;
     let url = format!("{}/{}/token-balances", explorer_env, account);
 
     // Send the request and fetch the response
-    let response = reqwest::get(&url)
-        .await?
-        .json::<HoldingApiResponse>()
-        .await?;
+    let response = get_json::<HoldingApiResponse>("Explorer balances", &url).await?;
 
     // Filter and parse the response to get only tokens with 18 decimals
     let filtered_tokens = parse_token_data(&response);
@@ -89,7 +254,7 @@ async fn fetch_account_holdings(account: &str) -> Result<Vec<FilteredTokenData>,
 fn parse_token_data(api_response: &HoldingApiResponse) -> Vec<FilteredTokenData> {
     let mut filtered_tokens = Vec::new();
 
-    for token in &api_response.erc20TokenBalances {
+    for token in &api_response.erc20_token_balances {
         // Convert decimals from hex to u32 and check if it's 18
         let decimals = u32::from_str_radix(&token.decimals[2..], 16).unwrap_or(0);
 
@@ -99,7 +264,7 @@ fn parse_token_data(api_response: &HoldingApiResponse) -> Vec<FilteredTokenData>
                 name: token.name.clone(),
                 address: token.address.clone(),
                 balance: token.balance.clone(),
-                formatted_balance: token.formattedBalance.clone(),
+                formatted_balance: token.formatted_balance.clone(),
                 symbol: token.symbol.clone(),
             });
         }
@@ -111,42 +276,209 @@ fn parse_token_data(api_response: &HoldingApiResponse) -> Vec<FilteredTokenData>
 pub async fn aggregate_info(
     token_address: &str,
 ) -> Result<(MemecoinInfo, TokenCategoryResponse), anyhow::Error> {
-    let ekubo_core = std::env::var("EKUBO_CORE_ADDRESS").expect("EKUBO_CORE_ADDRESS must be set.");
+    aggregate_info_with_refresh(token_address, false).await
+}
+
+// Bypasses the cache when force_refresh is set, the fresh result replaces the cached one
+pub async fn aggregate_info_with_refresh(
+    token_address: &str,
+    force_refresh: bool,
+) -> Result<(MemecoinInfo, TokenCategoryResponse), anyhow::Error> {
+    let canonical_address = canonicalize_address(token_address)
+        .ok_or_else(|| anyhow::anyhow!("Invalid token address: {}", token_address))?;
+    if !force_refresh {
+        if let Some(cached) = AGGREGATE_CACHE.get(&canonical_address) {
+            return Ok(cached);
+        }
+    }
+    let info = with_deadline("Token aggregation", fetch_aggregate_info(&canonical_address)).await?;
+    AGGREGATE_CACHE.insert(&canonical_address, info.clone());
+    Ok(info)
+}
+
+async fn fetch_aggregate_info(
+    token_address: &str,
+) -> Result<(MemecoinInfo, TokenCategoryResponse), anyhow::Error> {
+    let ekubo_core = &NETWORK.ekubo_core;
     let aggregated_data: Memecoin = get_aggregate_call_data(&token_address).await?;
-    let data = calculate_market_cap(&aggregated_data.total_supply, &aggregated_data.symbol).await;
+    let data =
+        calculate_market_cap(&token_address, &aggregated_data.total_supply, &aggregated_data.symbol).await;
     let mut price = String::new();
     let mut market_cap = String::new();
     if data.is_ok() {
         (price, market_cap) = data.unwrap();
     }
-    let holders_data: TokenCategoryResponse = fetch_holders_data(&token_address).await?;
-    let ekubo_core_balance = get_balance(&token_address, &ekubo_core).await?;
-    let ekubo_core_balance_f64: f64 = ekubo_core_balance.parse()?;
+    let holders_data: TokenCategoryResponse =
+        fetch_holders_data(&token_address, &aggregated_data.total_supply).await?;
+    let risk = assess_risk(&aggregated_data, holders_data.top_holders_pct).await;
     let price_f64: f64 = price.parse()?;
-    let liquidity = (ekubo_core_balance_f64 * price_f64).to_string();
-    Ok((
-        MemecoinInfo {
-            address: token_address.to_string(),
-            name: aggregated_data.name,
-            symbol: aggregated_data.symbol,
-            total_supply: aggregated_data.total_supply,
-            owner: aggregated_data.owner,
-            team_allocation: aggregated_data.launch.team_allocation,
-            price,
-            market_cap,
-            usd_dex_liquidity: liquidity,
-        },
-        holders_data,
-    ))
+    let pool = match get_pool_liquidity(&token_address, &aggregated_data, price_f64).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            // Fall back to the token side held by Ekubo core
+            eprintln!("Failed to read pool reserves for {}: {}", token_address, e);
+            let ekubo_core_balance = get_balance(&token_address, &ekubo_core).await?;
+            let ekubo_core_balance_f64: f64 = ekubo_core_balance.parse()?;
+            PoolLiquidity {
+                token_usd: ekubo_core_balance_f64 * price_f64,
+                ..Default::default()
+            }
+        }
+    };
+    let launch_market_cap = if aggregated_data.is_launched {
+        match get_launch_market_cap(&aggregated_data).await {
+            Ok(launch_market_cap) => launch_market_cap.to_string(),
+            Err(e) => {
+                eprintln!("Failed to price {} at its launch block: {}", token_address, e);
+                String::new()
+            }
+        }
+    } else {
+        String::new()
+    };
+    let lp_fee_apr = estimate_lp_fee_apr(
+        pool.fees_usd,
+        pool.token_usd + pool.quote_usd,
+        aggregated_data.launch.block_number,
+    )
+    .await;
+    let enrichment = Enrichment {
+        price,
+        market_cap,
+        launch_market_cap,
+        pool,
+        lp_fee_apr,
+        risk,
+    };
+    Ok((build_memecoin_info(token_address, aggregated_data, enrichment), holders_data))
+}
+
+// What a token is enriched with from price feeds and the chain, looked up before the info is
+// assembled so the assembly itself can be replayed without any IO
+#[derive(Debug, Default)]
+struct Enrichment {
+    price: String,
+    market_cap: String,
+    launch_market_cap: String,
+    pool: PoolLiquidity,
+    lp_fee_apr: Option<f64>,
+    risk: RiskReport,
+}
+
+fn build_memecoin_info(token_address: &str, memecoin: Memecoin, enrichment: Enrichment) -> MemecoinInfo {
+    let quote_address = canonicalize_address(&memecoin.liquidity.quote_token).unwrap_or_default();
+    let quote = quote_token(&quote_address);
+    let pool = enrichment.pool;
+    let total_liquidity = pool.token_usd + pool.quote_usd;
+    MemecoinInfo {
+        address: token_address.to_string(),
+        name: memecoin.name,
+        symbol: memecoin.symbol,
+        total_supply: memecoin.total_supply,
+        owner: memecoin.owner,
+        team_allocation: memecoin.launch.team_allocation,
+        price: enrichment.price,
+        market_cap: enrichment.market_cap,
+        launch_market_cap: enrichment.launch_market_cap,
+        usd_dex_liquidity: total_liquidity.to_string(),
+        usd_token_liquidity: pool.token_usd.to_string(),
+        usd_quote_liquidity: pool.quote_usd.to_string(),
+        pool_fee_percent: pool.fee_percent.map(|fee| fee.to_string()).unwrap_or_default(),
+        pool_tick_spacing: pool.tick_spacing.map(|spacing| spacing.to_string()).unwrap_or_default(),
+        quote_symbol: quote.map(|quote| quote.symbol.as_str().to_string()).unwrap_or_default(),
+        quote_token_safe: quote.is_some(),
+        quote_token: quote_address,
+        lp_fee_apr: enrichment.lp_fee_apr.map(|apr| format!("{:.2}", apr)).unwrap_or_default(),
+        risk: Some(enrichment.risk),
+        ..Default::default()
+    }
+}
+
+#[derive(Debug, Default)]
+struct PoolLiquidity {
+    token_usd: f64,
+    quote_usd: f64,
+    fees_usd: f64,
+    fee_percent: Option<f64>,
+    tick_spacing: Option<u128>,
+}
+
+// USD value of the token and quote sides of the launch pool, plus fees it accrued
+async fn get_pool_liquidity(
+    token_address: &str,
+    memecoin: &Memecoin,
+    price: f64,
+) -> Result<PoolLiquidity, anyhow::Error> {
+    let reserves = get_pool_reserves(token_address, &memecoin.liquidity).await?;
+    let token_usd = reserves.token_reserve as f64 * price;
+    let mut fees_usd = reserves.token_fees as f64 * price;
+
+    let quote_usd = match quote_token(&reserves.quote_token) {
+        Some(quote) => {
+            let quote_price = get_usd_price_per_unit(quote.symbol.as_str(), quote.decimals).await?;
+            fees_usd += reserves.quote_fees as f64 * quote_price;
+            reserves.quote_reserve as f64 * quote_price
+        }
+        None => {
+            eprintln!("Unknown quote token {}, skipping quote side", reserves.quote_token);
+            0f64
+        }
+    };
+
+    Ok(PoolLiquidity {
+        token_usd,
+        quote_usd,
+        fees_usd,
+        fee_percent: Some(fee_to_percent(&reserves.fee)),
+        tick_spacing: Some(reserves.tick_spacing),
+    })
+}
+
+// Annualizes the fees the launch position earned since launch, None while the sample is too young
+async fn estimate_lp_fee_apr(fees_usd: f64, liquidity_usd: f64, launch_block: u64) -> Option<f64> {
+    let min_sample_hours = std::env::var("APR_MIN_SAMPLE_HOURS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(1);
+
+    if liquidity_usd <= 0f64 || launch_block == 0 {
+        return None;
+    }
+
+    let launched_at = match get_block_timestamp(launch_block).await {
+        Ok(timestamp) => timestamp,
+        Err(e) => {
+            eprintln!("Failed to fetch launch block timestamp: {}", e);
+            return None;
+        }
+    };
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    let age_secs = now.saturating_sub(launched_at);
+    if age_secs < min_sample_hours * 60 * 60 {
+        return None;
+    }
+
+    let seconds_per_year = 365f64 * 24f64 * 60f64 * 60f64;
+    Some(fees_usd / liquidity_usd * (seconds_per_year / age_secs as f64) * 100f64)
 }
 
 pub async fn get_account_holdings(account: &str) -> Result<TokenHoldings, anyhow::Error> {
+    let cache_key = canonicalize_address(account).unwrap_or_else(|| account.to_string());
+    if let Some(cached) = HOLDINGS_CACHE.get(&cache_key) {
+        return Ok(cached);
+    }
+    let holdings = with_deadline("Wallet scan", fetch_account_holdings_summary(account)).await?;
+    HOLDINGS_CACHE.insert(&cache_key, holdings.clone());
+    Ok(holdings)
+}
+
+async fn fetch_account_holdings_summary(account: &str) -> Result<TokenHoldings, anyhow::Error> {
     let token_data: Vec<FilteredTokenData> = fetch_account_holdings(account).await?;
     let addresses: Vec<&str> = token_data
         .iter()
         .map(|token| token.address.as_str())
         .collect();
-    let valid_addresses = validate_memecoins(addresses).await.unwrap();
+    let valid_addresses = validate_memecoins(addresses).await?;
     let valid_address_set: HashSet<String> =
         valid_addresses.into_iter().map(|s| s.to_string()).collect();
 
@@ -155,12 +487,99 @@ pub async fn get_account_holdings(account: &str) -> Result<TokenHoldings, anyhow
         .into_iter()
         .filter(|token| valid_address_set.contains(&token.address))
         .collect();
+
+    // Memecoins locked or vaulted by this wallet still belong to it
+    let held: HashSet<String> = filtered_tokens
+        .iter()
+        .filter_map(|token| canonicalize_address(&token.address))
+        .collect();
+    // Locks can't always be read, /peek still shows what the explorer has and calls them unknown
+    let locked = match locked_memecoins(account).await {
+        Ok(locked) => Some(locked),
+        Err(e) => {
+            eprintln!("Failed to read locked balances of {}: {}", account, e);
+            None
+        }
+    };
+    let locked_only: Vec<&str> = match &locked {
+        Some((_, locked_memecoins)) => locked_memecoins.difference(&held).map(String::as_str).collect(),
+        None => Vec::new(),
+    };
+
+    // Locked memecoins are missing from the explorer balances, name them with one batched call
+    let mut position_tokens = filtered_tokens.clone();
+    if let Some((balances, _)) = locked.as_ref().filter(|_| !locked_only.is_empty()) {
+        match get_aggregate_call_data_many(&locked_only).await {
+            Ok(memecoins) => {
+                for (address, memecoin) in locked_only.iter().zip(memecoins) {
+                    let memecoin = match memecoin {
+                        Ok(memecoin) => memecoin,
+                        Err(e) => {
+                            eprintln!("Failed to read locked memecoin {}: {}", address, e);
+                            continue;
+                        }
+                    };
+                    let balance = locked_amount(balances, address);
+                    position_tokens.push(FilteredTokenData {
+                        name: memecoin.name,
+                        address: address.to_string(),
+                        balance: balance.to_string(),
+                        formatted_balance: (balance / 10f64.powi(DECIMALS as i32)).to_string(),
+                        symbol: memecoin.symbol,
+                    });
+                }
+            }
+            Err(e) => eprintln!("Failed to read locked memecoins of {}: {}", account, e),
+        }
+    }
+    let positions = classify_positions(account, &position_tokens, &PortfolioConfig::new()).await;
+
     Ok(TokenHoldings {
-        account_address: account.to_string(),
-        total_tokens: filtered_tokens.len().to_string(),
+        account_address: canonicalize_address(account).unwrap_or_else(|| account.to_string()),
+        total_tokens: (filtered_tokens.len() + locked_only.len()).to_string(),
+        locked_tokens: locked_count(locked.as_ref().map(|(_, locked_memecoins)| locked_memecoins.len())),
+        positions,
     })
 }
 
+// The wallet's locked and vaulted balances with the memecoins among them
+async fn locked_memecoins(
+    account: &str,
+) -> Result<(Vec<(String, String)>, HashSet<String>), anyhow::Error> {
+    let locked = get_locked_balances(account).await?;
+    let locked_addresses: Vec<&str> = locked
+        .iter()
+        .filter(|(_, amount)| amount != "0")
+        .map(|(token, _)| token.as_str())
+        .collect();
+    let memecoins: HashSet<String> = if locked_addresses.is_empty() {
+        HashSet::new()
+    } else {
+        validate_memecoins(locked_addresses)
+            .await?
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect()
+    };
+    Ok((locked, memecoins))
+}
+
+// Raw amount of a token across every lock and vault it sits in
+fn locked_amount(locked: &[(String, String)], token_address: &str) -> f64 {
+    locked
+        .iter()
+        .filter(|(token, _)| addresses_eq(token, token_address))
+        .map(|(_, amount)| amount.parse::<f64>().unwrap_or_default())
+        .sum()
+}
+
+fn locked_count(locked: Option<usize>) -> String {
+    match locked {
+        Some(count) => count.to_string(),
+        None => "Unknown".to_string(),
+    }
+}
+
 pub async fn get_account_holding_info(
     account: &str,
     token_address: &str,
@@ -183,13 +602,91 @@ pub async fn get_account_holding_info(
 mod tests {
     use super::*;
     use std::env;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
     use dotenv::dotenv;
+    use crate::utils::rpc::{init_rpc_pool, RpcConfig};
+
+    #[tokio::test]
+    async fn test_deadline_cancels_work() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let work = {
+            let finished = Arc::clone(&finished);
+            async move {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                finished.store(true, Ordering::SeqCst);
+                Ok(())
+            }
+        };
+        let result = within(Duration::from_millis(10), "Slow upstream", work).await;
+        assert!(result.unwrap_err().downcast_ref::<DeadlineExceeded>().is_some());
+        // The work was dropped rather than left running in the background
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(!finished.load(Ordering::SeqCst));
+
+        let quick = within(Duration::from_millis(100), "Quick upstream", async { Ok(1) }).await;
+        assert_eq!(quick.unwrap(), 1);
+    }
+
+    // Decoded launch fixtures enriched with the lookups they recorded in place of live IO
+    #[test]
+    fn test_replay_enrichment_snapshot() {
+        let fixtures = [
+            include_str!("../../tests/fixtures/events/eth_quoted_launch.json"),
+            include_str!("../../tests/fixtures/events/strk_quoted_launch.json"),
+        ];
+        for fixture in fixtures {
+            let fixture: serde_json::Value = serde_json::from_str(fixture).unwrap();
+            let memecoin: Memecoin = serde_json::from_value(fixture["expected"]["memecoin"].clone()).unwrap();
+            let io = &fixture["enrichment"];
+            let enrichment = Enrichment {
+                price: io["price"].as_str().unwrap().to_string(),
+                market_cap: io["market_cap"].as_str().unwrap().to_string(),
+                launch_market_cap: io["launch_market_cap"].as_str().unwrap().to_string(),
+                pool: PoolLiquidity {
+                    token_usd: io["pool"]["token_usd"].as_f64().unwrap(),
+                    quote_usd: io["pool"]["quote_usd"].as_f64().unwrap(),
+                    fees_usd: io["pool"]["fees_usd"].as_f64().unwrap(),
+                    fee_percent: io["pool"]["fee_percent"].as_f64(),
+                    tick_spacing: io["pool"]["tick_spacing"].as_u64().map(u128::from),
+                },
+                lp_fee_apr: io["lp_fee_apr"].as_f64(),
+                risk: serde_json::from_value(io["risk"].clone()).unwrap(),
+            };
+            let token_address = canonicalize_address(fixture["token_address"].as_str().unwrap()).unwrap();
+
+            let info = build_memecoin_info(&token_address, memecoin, enrichment);
+            assert_eq!(
+                serde_json::to_value(&info).unwrap(),
+                fixture["expected"]["memecoin_info"],
+                "snapshot mismatch for {}",
+                fixture["description"]
+            );
+        }
+    }
+
+    #[test]
+    fn test_locked_balances_summary() {
+        let locked = vec![
+            ("0x0abc".to_string(), "1000".to_string()),
+            ("0xdef".to_string(), "5".to_string()),
+            ("0xABC".to_string(), "500".to_string()),
+        ];
+        // A token locked twice, once in a locker and once in a vault
+        assert_eq!(locked_amount(&locked, "0xabc"), 1500f64);
+        assert_eq!(locked_amount(&locked, "0x123"), 0f64);
+
+        assert_eq!(locked_count(Some(2)), "2");
+        // Lockers that couldn't be read don't pass for an empty wallet
+        assert_eq!(locked_count(None), "Unknown");
+    }
 
     async fn setup() {
         dotenv().ok();
         // Ensure required environment variables are set
         env::var("EXPLORER_API").expect("EXPLORER_API must be set");
         env::var("EKUBO_CORE_ADDRESS").expect("EKUBO_CORE_ADDRESS must be set");
+        init_rpc_pool(RpcConfig::new()).expect("STARKNET_RPC_URLS must contain valid URLs");
     }
 
     #[tokio::test]
@@ -253,4 +750,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_get_top_holders() {
+        let sample = |address: &str, balance: f64| HolderSample {
+            address: address.to_string(),
+            balance,
+            last_transfer_time: 0,
+        };
+        let samples = vec![sample("0x1", 50f64), sample("0x2", 300f64), sample("0x3", 150f64)];
+
+        let top = get_top_holders(&samples, 1000f64, 2);
+        assert_eq!(
+            top,
+            vec![
+                TopHolder { address: "0x2".to_string(), pct: 30f64 },
+                TopHolder { address: "0x3".to_string(), pct: 15f64 },
+            ]
+        );
+        assert!(get_top_holders(&samples, 0f64, 2).is_empty());
+    }
+
+    #[test]
+    fn test_count_holder_pages() {
+        let response = |last_page: u32, has_more: bool| HolderApiResponse {
+            items: Vec::new(),
+            last_page,
+            has_more,
+        };
+        assert_eq!(count_holder_pages(&response(3, true)), 2 * HOLDER_PAGE_SIZE);
+        assert_eq!(count_holder_pages(&response(1, false)), 0);
+    }
 }
\ No newline at end of file
@@ -1,62 +1,58 @@
-use std::str::FromStr;
+use crate::constant::constants::{
+    get_checksum_address, quote_token, Token, TokenSymbol, DECIMALS, EKUBO_TICK_SIZE,
+    LIQUIDITY_LOCK_FOREVER_TIMESTAMP, USDC,
+};
+use crate::constant::network::NETWORK;
 
-use crate::constant::constants::{DECIMALS, EKUBO_TICK_SIZE, LIQUIDITY_LOCK_FOREVER_TIMESTAMP, QUOTE_TOKENS};
-use crate::utils::types::fraction::Rounding;
-
-use super::call::{parse_u256_from_felts, AggregateError};
-use super::types::ekubo::{Bound, Bounds, EkuboLiquidityLockPosition, EkuboMemecoin, Liquidity, PoolKey};
-use super::types::fraction::Fraction;
+use super::address::{addresses_eq, to_canonical_hex};
+use super::cache::{default_max_entries, Cache};
+use super::call::{call_with_retry, parse_u256_from_felts, AggregateError};
+use super::types::ekubo::{Bound, Bounds, EkuboLiquidityLockPosition, Liquidity, Memecoin, PoolKey};
 use num_bigint::BigUint;
-use num_traits::{FromPrimitive, One};
+use num_traits::ToPrimitive;
 use starknet::core::types::{BlockId, BlockTag, FunctionCall};
 use starknet::macros::selector;
-use starknet::providers::jsonrpc::HttpTransport;
-use starknet::providers::{JsonRpcClient, Provider};
 use starknet_core::types::Felt;
-use url::Url;
 
-fn get_provider() -> Result<JsonRpcClient<HttpTransport>, AggregateError> {
-    Ok(JsonRpcClient::new(HttpTransport::new(
-        Url::parse("https://starknet-mainnet.public.blastapi.io/rpc/v0_7")
-            .map_err(AggregateError::Url)?
-    )))
+lazy_static::lazy_static! {
+    // A launch's market cap never changes, so the launch block is only read once per token
+    static ref LAUNCH_MARKET_CAPS: Cache<f64> = Cache::new("launch_market_caps", default_max_entries(), None);
 }
 
 #[derive(Debug, Clone)]
-pub struct LiquidityParams {
-    pub is_quote_token_safe: bool,
-    pub parsed_starting_mcap: String,
+pub struct PoolReserves {
+    pub token_reserve: u128,
+    pub quote_reserve: u128,
+    pub token_fees: u128,
+    pub quote_fees: u128,
+    pub quote_token: String,
+    pub fee: BigUint,
+    pub tick_spacing: u128,
 }
 
 pub async fn get_ekubo_liquidity_lock_position(
     liquidity: &Liquidity
-) -> Result<(EkuboLiquidityLockPosition), Box<dyn std::error::Error>> {
-    let provider = get_provider()?;
+) -> Result<EkuboLiquidityLockPosition, AggregateError> {
     // Call the contract to get the details
-    let call_result = match provider
-    .call(
+    let call_result = call_with_retry(
         FunctionCall {
             contract_address: Felt::from_hex(&liquidity.launch_manager)
-                .map_err(|e| AggregateError::ContractCall(format!("Invalid address: {}", e)))?,
+                .map_err(|e| AggregateError::InvalidParams(format!("Invalid address: {}", e)))?,
             entry_point_selector: selector!("liquidity_position_details"),
-            calldata: vec![Felt::from_hex(&liquidity.ekubo_id)?],
+            calldata: vec![Felt::from_hex(&liquidity.ekubo_id)
+                .map_err(|e| AggregateError::InvalidParams(format!("Invalid ekubo id: {}", e)))?],
         },
         BlockId::Tag(BlockTag::Latest),
     )
-    .await {
-        Ok(result) => {
-            println!("Contract call successful!");
-            result
-        }
-        Err(e) => {
-            println!("Contract call failed: {:?}", e);
-            return Err(Box::new(AggregateError::ContractCall(format!("Contract call failed: {:?}", e))));
-        }
-    };
+    .await?;
+
+    if call_result.len() < 11 {
+        return Err(AggregateError::Parse("Unexpected liquidity position layout".to_string()));
+    }
 
     Ok(EkuboLiquidityLockPosition {
         unlock_time: LIQUIDITY_LOCK_FOREVER_TIMESTAMP,
-        owner: call_result[0].to_hex_string(),
+        owner: to_canonical_hex(&call_result[0]),
         pool_key: PoolKey {
             token0: call_result[2].to_hex_string(),
             token1: call_result[3].to_hex_string(),
@@ -76,101 +72,200 @@ pub async fn get_ekubo_liquidity_lock_position(
         },
     })
 }
-pub async fn get_price(pair: String, block_identifier: BlockId) -> Result<Fraction, Box<dyn std::error::Error>> {
-    if pair == "" {return Ok(Fraction::new(BigUint::from(10u64).pow(DECIMALS), Some(BigUint::one()))?)}
-
-    let provider = get_provider()?;
-    let call_result = match provider
-        .call(
-            FunctionCall {
-                contract_address: Felt::from_hex(&pair)
-                    .map_err(|e| AggregateError::ContractCall(format!("Invalid address: {}", e)))?,
-                entry_point_selector: selector!("get_reserves"),
-                calldata: vec![],
-            },
-            block_identifier,
-        )
-        .await {
-            Ok(result) => {
-                result
-            }
-            Err(e) => {
-                println!("Contract call failed: {:?}", e);
-                return Err(Box::new(AggregateError::ContractCall(format!("Contract call failed: {:?}", e))));
-            }
-        };
-        let reserve0 = if let (Some(low), Some(high)) = (call_result.get(0), call_result.get(1)) {
-            BigUint::from_str(&parse_u256_from_felts(low, high))?
-        } else {
-            eprintln!("Failed to decode reserve0");
-            return Err(Box::new(AggregateError::ContractCall("Failed to decode reserve0".to_string())));
-        };
-        let reserve1 = if let (Some(low), Some(high)) = (call_result.get(2), call_result.get(3)) {
-            BigUint::from_str(&parse_u256_from_felts(low, high))?
-        } else {
-            eprintln!("Failed to decode reserve1");
-            return Err(Box::new(AggregateError::ContractCall("Failed to decode reserve1".to_string())));
-        };
-
-        // println!("{}", reserve0);
-        // println!("{}", reserve1);
-
-    // Perform the fraction operation (reserve1 / reserve0) * 10^12 for scaling
-    let scale = BigUint::from(10u64).pow(12);
-    let fraction = Fraction::new(reserve1, Some(reserve0))? * Fraction::new(scale.clone(), Some(BigUint::one()))?;
-    Ok(fraction)
+// Ekubo positions' view of the locked launch position: sqrt_ratio (low, high), tick (mag, sign),
+// liquidity, amount0, amount1, fees0, fees1
+async fn get_token_info(
+    liquidity: &Liquidity,
+    position: &EkuboLiquidityLockPosition,
+) -> Result<Vec<Felt>, AggregateError> {
+    let calldata = vec![
+        parse_felt(&liquidity.ekubo_id)?,
+        parse_felt(&position.pool_key.token0)?,
+        parse_felt(&position.pool_key.token1)?,
+        parse_felt(&position.pool_key.fee)?,
+        parse_felt(&position.pool_key.tick_spacing)?,
+        parse_felt(&position.pool_key.extension)?,
+        parse_felt(&position.bounds.lower.mag)?,
+        parse_felt(&position.bounds.lower.sign)?,
+        parse_felt(&position.bounds.upper.mag)?,
+        parse_felt(&position.bounds.upper.sign)?,
+    ];
+
+    let call_result = call_with_retry(
+        FunctionCall {
+            contract_address: Felt::from_hex_unchecked(&NETWORK.ekubo_positions),
+            entry_point_selector: selector!("get_token_info"),
+            calldata,
+        },
+        BlockId::Tag(BlockTag::Latest),
+    )
+    .await?;
+    if call_result.len() < 9 {
+        return Err(AggregateError::Parse("Failed to decode position amounts".to_string()));
+    }
+    Ok(call_result)
 }
 
+fn parse_felt(value: &str) -> Result<Felt, AggregateError> {
+    Felt::from_hex(value)
+        .or_else(|_| Felt::from_dec_str(value))
+        .map_err(|e| AggregateError::Parse(format!("Invalid position field {}: {}", value, e)))
+}
+
+// Liquidity of the launch position, unlike its amounts it doesn't move with trades, only when
+// liquidity is withdrawn
+pub async fn get_position_liquidity(
+    liquidity: &Liquidity,
+    position: &EkuboLiquidityLockPosition,
+) -> Result<u128, AggregateError> {
+    let call_result = get_token_info(liquidity, position).await?;
+    Ok(call_result[4].to_u128().unwrap_or(0))
+}
+
+// Reads both sides of the locked launch position from Ekubo positions
+pub async fn get_pool_reserves(
+    memecoin_address: &str,
+    liquidity: &Liquidity,
+) -> Result<PoolReserves, AggregateError> {
+    let position = get_ekubo_liquidity_lock_position(liquidity).await?;
+    let call_result = get_token_info(liquidity, &position).await?;
+
+    let amount0 = call_result[5].to_u128().unwrap_or(0);
+    let amount1 = call_result[6].to_u128().unwrap_or(0);
+    let fees0 = call_result[7].to_u128().unwrap_or(0);
+    let fees1 = call_result[8].to_u128().unwrap_or(0);
+
+    let fee = parse_felt(&position.pool_key.fee)?.to_biguint();
+    let is_token0 = addresses_eq(&position.pool_key.token0, memecoin_address);
+    let (token_reserve, quote_reserve, token_fees, quote_fees, quote_token) = if is_token0 {
+        (amount0, amount1, fees0, fees1, position.pool_key.token1)
+    } else {
+        (amount1, amount0, fees1, fees0, position.pool_key.token0)
+    };
+
+    Ok(PoolReserves {
+        token_reserve,
+        quote_reserve,
+        token_fees,
+        quote_fees,
+        quote_token,
+        fee,
+        tick_spacing: parse_felt(&position.pool_key.tick_spacing)?.to_u128().unwrap_or_default(),
+    })
+}
+
+// Ekubo fees are 0.128 fixed point numbers, returns the fee as a percentage (e.g. 0.3)
+pub fn fee_to_percent(fee: &BigUint) -> f64 {
+    let fee = fee.to_f64().unwrap_or(0f64);
+    fee / 2f64.powi(128) * 100f64
+}
+
+// Ekubo prices are 1.000001^tick, in raw quote units per raw memecoin unit
 pub fn get_initial_price(starting_tick: i64) -> f64 {
-    let log_tick_size = EKUBO_TICK_SIZE.ln();
-    (starting_tick as f64) * log_tick_size   
+    EKUBO_TICK_SIZE.powf(starting_tick as f64)
 }
 
-pub async fn parse_liquidity_params(memecoin: &EkuboMemecoin) -> Result<LiquidityParams, Box<dyn std::error::Error>> {
-    // println!("{:?}", memecoin);
-    
-    // Quote token info check
-    let quote_token_infos = QUOTE_TOKENS.get(&memecoin.liquidity.quote_token as &str);
-    let is_quote_token_safe = quote_token_infos.is_some();
-
-    // Get Ether price at launch
-    let quote_token_price_at_launch = get_price(quote_token_infos.unwrap().usdc_pair.to_string(),starknet::core::types::BlockId::Number(memecoin.launch.block_number)).await?;
-    // println!("{:?}", quote_token_price_at_launch);
-    
-    // Calculate initial price and starting market cap
-    let initial_price = get_initial_price(memecoin.liquidity.starting_tick);
-    // println!("{:?}", initial_price);
-    
-    // Now we can safely convert the scaled price to BigUint
-    let price = BigUint::from_f64(initial_price).unwrap();
-
-    // println!("{:?}", price);
-
-    let starting_mcap = if is_quote_token_safe {
-
-        let supply = Fraction::new(memecoin.total_supply.clone(), Some(BigUint::from(1u64)))?;
-        // println!("{:?}", supply);
-        let decimals = Fraction::new(BigUint::from(10u64.pow(DECIMALS as u32)), Some(BigUint::from(1u64)))?* Fraction::new(BigUint::from(10u64).pow(48), Some(BigUint::one()))?;
-        // println!("{:?}", decimals);
-        let price_fraction = Fraction::new(price, Some(BigUint::one()))?* Fraction::new(BigUint::from(10u64).pow(DECIMALS), Some(BigUint::one()))?;
-        // println!("{:?}", price_fraction);
-        Some((price_fraction * quote_token_price_at_launch *supply)
-                           /decimals)
+// USD price of one whole quote token at a block, read from its JediSwap pair against USDC
+async fn get_quote_usd_price_at(quote: &Token, block_number: u64) -> Result<f64, AggregateError> {
+    if matches!(quote.symbol, TokenSymbol::USDC | TokenSymbol::DAI) {
+        return Ok(1f64);
+    }
+    if quote.usdc_pair.is_empty() {
+        return Err(AggregateError::InvalidParams(format!(
+            "No USDC pair to price {} with",
+            quote.symbol.as_str()
+        )));
+    }
+
+    let call_result = call_with_retry(
+        FunctionCall {
+            contract_address: Felt::from_hex(quote.usdc_pair)
+                .map_err(|e| AggregateError::InvalidParams(format!("Invalid pair address: {}", e)))?,
+            entry_point_selector: selector!("get_reserves"),
+            calldata: vec![],
+        },
+        BlockId::Number(block_number),
+    )
+    .await?;
+
+    // reserve0 (low, high), reserve1 (low, high), block timestamp
+    if call_result.len() < 4 {
+        return Err(AggregateError::Parse("Failed to decode pair reserves".to_string()));
+    }
+    let reserve0: f64 = parse_u256_from_felts(&call_result[0], &call_result[1]).parse().unwrap_or_default();
+    let reserve1: f64 = parse_u256_from_felts(&call_result[2], &call_result[3]).parse().unwrap_or_default();
+
+    // JediSwap pairs order their tokens by address
+    let quote_is_token0 = Felt::from_hex_unchecked(quote.address) < Felt::from_hex_unchecked(USDC.address);
+    let (quote_reserve, usdc_reserve) = if quote_is_token0 {
+        (reserve0, reserve1)
     } else {
-        None
+        (reserve1, reserve0)
     };
+    if quote_reserve <= 0f64 {
+        return Err(AggregateError::Parse(format!(
+            "Empty {} pair at block {}",
+            quote.symbol.as_str(),
+            block_number
+        )));
+    }
+    let usdc_amount = usdc_reserve / 10f64.powi(USDC.decimals as i32);
+    Ok(usdc_amount / (quote_reserve / 10f64.powi(quote.decimals as i32)))
+}
+
+// USD market cap of a memecoin at its launch block, from the pool's starting tick and what the
+// quote token was worth then
+pub async fn get_launch_market_cap(memecoin: &Memecoin) -> Result<f64, AggregateError> {
+    let key = get_checksum_address(&memecoin.address);
+    if let Some(market_cap) = LAUNCH_MARKET_CAPS.get(&key) {
+        return Ok(market_cap);
+    }
+    let quote = quote_token(&memecoin.liquidity.quote_token).ok_or_else(|| {
+        AggregateError::InvalidParams(format!("Unknown quote token {}", memecoin.liquidity.quote_token))
+    })?;
+    let quote_usd = get_quote_usd_price_at(quote, memecoin.launch.block_number).await?;
+    let supply: f64 = memecoin.total_supply.parse().map_err(|e| {
+        AggregateError::Parse(format!("Invalid total supply {}: {}", memecoin.total_supply, e))
+    })?;
+    let market_cap = starting_market_cap(memecoin.liquidity.starting_tick, quote.decimals, quote_usd, supply);
+    LAUNCH_MARKET_CAPS.insert(&key, market_cap);
+    Ok(market_cap)
+}
 
-    let starting_mcap_value = starting_mcap.unwrap()?;
+// `supply` in raw units, `quote_usd` the price of one whole quote token
+fn starting_market_cap(starting_tick: i64, quote_decimals: u8, quote_usd: f64, supply: f64) -> f64 {
+    let raw_price = get_initial_price(starting_tick);
+    // Whole memecoins per whole quote token, the tick prices raw units of both
+    let price = raw_price * 10f64.powi(DECIMALS as i32 - quote_decimals as i32);
+    price * quote_usd * supply / 10f64.powi(DECIMALS as i32)
+}
 
-    // println!("{:?}", starting_mcap_value.to_formatted_string());
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
 
-    // Format the starting market cap
-    let parsed_starting_mcap = starting_mcap_value.to_significant_digits(0, Rounding::RoundDown)?;
+    #[test]
+    fn test_fee_to_percent() {
+        // 0.3% fee tier as used by Unruggable launches
+        let fee = BigUint::from_str("1020847100762815390390123822295304634").unwrap();
+        assert!((fee_to_percent(&fee) - 0.3).abs() < 1e-9);
+        assert_eq!(fee_to_percent(&BigUint::from(0u8)), 0f64);
+    }
 
-    // println!("{}", parsed_starting_mcap);
+    #[test]
+    fn test_starting_market_cap() {
+        assert_eq!(get_initial_price(0), 1f64);
+        // ln(1000) / ln(1.000001) ticks below parity is a thousandth of a quote token per memecoin
+        let tick = -6907758;
+        assert!((get_initial_price(tick) - 0.001).abs() < 1e-6);
 
-    Ok(LiquidityParams {
-        is_quote_token_safe,
-        parsed_starting_mcap,
-    })
-}
\ No newline at end of file
+        // 1B memecoins at 0.001 ETH with ETH at $2000
+        let supply = 1e9 * 1e18;
+        let mcap = starting_market_cap(tick, 18, 2000f64, supply);
+        assert!((mcap - 2e9).abs() / 2e9 < 1e-3);
+        // A 6 decimals quote token prices a raw memecoin unit 1e12 times lower
+        let mcap = starting_market_cap(tick - 27631035, 6, 1f64, supply);
+        assert!((mcap - 1e6).abs() / 1e6 < 1e-3);
+    }
+}
@@ -0,0 +1,158 @@
+use futures::future::join_all;
+use lazy_static::lazy_static;
+use starknet_core::types::Felt;
+
+use super::address::{canonicalize_address, to_canonical_hex};
+use super::cache::{default_max_entries, Cache};
+use super::call::{call_contract, get_class_hash_at, parse_u256_from_felts, AggregateError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockerKind {
+    // Locker deploying one position contract per lock, queried with get_lock_details
+    TokenLocker,
+    // ERC-4626 vault, shares are converted back to assets per depositor
+    Erc4626Vault,
+}
+
+#[derive(Debug, Clone)]
+pub struct LockerSpec {
+    pub kind: LockerKind,
+    pub address: String,
+    pub class_hash: String,
+}
+
+// Who a locker or vault balance really belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Attribution {
+    Owner(String),
+    Vault,
+}
+
+lazy_static! {
+    // Registry entries look like `token_locker:<locker address>:<lock position class hash>`
+    // or `erc4626:<vault address>:<vault class hash>`, comma separated in LOCKER_REGISTRY
+    pub static ref LOCKER_REGISTRY: Vec<LockerSpec> = std::env::var("LOCKER_REGISTRY")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(parse_locker_spec)
+        .collect();
+    static ref CLASS_HASH_CACHE: Cache<String> = Cache::new("class_hashes", default_max_entries(), None);
+}
+
+fn parse_locker_spec(entry: &str) -> Option<LockerSpec> {
+    let mut parts = entry.trim().split(':');
+    let kind = match parts.next()? {
+        "token_locker" => LockerKind::TokenLocker,
+        "erc4626" => LockerKind::Erc4626Vault,
+        _ => return None,
+    };
+    Some(LockerSpec {
+        kind,
+        address: canonicalize_address(parts.next()?)?,
+        class_hash: canonicalize_address(parts.next()?)?,
+    })
+}
+
+async fn class_hash_of(address: &str) -> Result<String, AggregateError> {
+    if let Some(class_hash) = CLASS_HASH_CACHE.get(address) {
+        return Ok(class_hash);
+    }
+    let class_hash = get_class_hash_at(address).await?;
+    CLASS_HASH_CACHE.insert(address, class_hash.clone());
+    Ok(class_hash)
+}
+
+// Resolves a holder that is a known lock position or vault; plain wallets return None
+pub async fn attribute_holder(holder: &str) -> Result<Option<Attribution>, AggregateError> {
+    if LOCKER_REGISTRY.is_empty() {
+        return Ok(None);
+    }
+    let holder = canonicalize_address(holder)
+        .ok_or_else(|| AggregateError::InvalidParams(format!("Invalid address: {}", holder)))?;
+    let class_hash = class_hash_of(&holder).await?;
+
+    let spec = match LOCKER_REGISTRY.iter().find(|spec| spec.class_hash == class_hash) {
+        Some(spec) => spec,
+        None => return Ok(None),
+    };
+    match spec.kind {
+        LockerKind::TokenLocker => {
+            // TokenLock { token, amount: u256, unlock_time, owner }
+            let details = call_contract(
+                &spec.address,
+                "get_lock_details",
+                vec![Felt::from_hex_unchecked(&holder)],
+            )
+            .await?;
+            let owner = details
+                .get(4)
+                .ok_or_else(|| AggregateError::Parse("short get_lock_details result".to_string()))?;
+            Ok(Some(Attribution::Owner(to_canonical_hex(owner))))
+        }
+        // Depositors can't be enumerated from the vault, so it is not counted as a holder
+        LockerKind::Erc4626Vault => Ok(Some(Attribution::Vault)),
+    }
+}
+
+// Lists (token address, raw amount) a wallet has sitting in registered lockers and vaults. Every
+// locker is queried at once, one failing fails the listing since it would be incomplete
+pub async fn get_locked_balances(account: &str) -> Result<Vec<(String, String)>, AggregateError> {
+    if LOCKER_REGISTRY.is_empty() {
+        return Ok(Vec::new());
+    }
+    let account = Felt::from_hex(account)
+        .map_err(|e| AggregateError::InvalidParams(format!("Invalid address: {}", e)))?;
+    let lookups = join_all(LOCKER_REGISTRY.iter().map(|spec| locked_in(spec, account))).await;
+    let mut balances = Vec::new();
+    for lookup in lookups {
+        balances.extend(lookup?);
+    }
+    Ok(balances)
+}
+
+async fn locked_in(spec: &LockerSpec, account: Felt) -> Result<Vec<(String, String)>, AggregateError> {
+    let mut balances = Vec::new();
+    match spec.kind {
+        LockerKind::TokenLocker => {
+            let length = call_contract(&spec.address, "user_locks_length", vec![account])
+                .await?
+                .first()
+                .and_then(|length| length.to_biguint().try_into().ok())
+                .unwrap_or(0u64);
+            for index in 0..length {
+                let lock_address = call_contract(
+                    &spec.address,
+                    "user_lock_at",
+                    vec![account, Felt::from(index)],
+                )
+                .await?;
+                let lock_address = match lock_address.first() {
+                    Some(lock_address) => *lock_address,
+                    None => continue,
+                };
+                let details =
+                    call_contract(&spec.address, "get_lock_details", vec![lock_address])
+                        .await?;
+                if let (Some(token), Some(low), Some(high)) =
+                    (details.first(), details.get(1), details.get(2))
+                {
+                    balances.push((to_canonical_hex(token), parse_u256_from_felts(low, high)));
+                }
+            }
+        }
+        LockerKind::Erc4626Vault => {
+            let shares = call_contract(&spec.address, "balance_of", vec![account]).await?;
+            if shares.iter().all(|word| *word == Felt::ZERO) {
+                return Ok(balances);
+            }
+            let assets = call_contract(&spec.address, "convert_to_assets", shares).await?;
+            let asset = call_contract(&spec.address, "asset", vec![]).await?;
+            if let (Some(token), Some(low), Some(high)) =
+                (asset.first(), assets.first(), assets.get(1))
+            {
+                balances.push((to_canonical_hex(token), parse_u256_from_felts(low, high)));
+            }
+        }
+    }
+    Ok(balances)
+}
@@ -1,46 +1,41 @@
+use super::price::PRICE_ORACLE;
+use super::retry::retry;
 use super::types::ekubo::QuoteResponseApi;
+use crate::constant::network::NETWORK;
 
-async fn get_ekubo_quote(
+pub async fn get_ekubo_quote(
     amount: String,
     from_token: &str,
     to_token: &str,
 ) -> Result<QuoteResponseApi, anyhow::Error> {
     let client = reqwest::Client::new();
     let url = format!(
-        "https://mainnet-api.ekubo.org/quote/{}/{}/{}",
-        amount, from_token, to_token
+        "{}/quote/{}/{}/{}",
+        NETWORK.ekubo_api_url, amount, from_token, to_token
     );
 
-    let response = client
-        .get(&url)
-        .timeout(std::time::Duration::from_secs(10)) // 10-second timeout
-        .send()
-        .await?;
-
-    let status = response.status();
-    if !status.is_success() {
-        return Err(anyhow::Error::msg(format!(
-            "API call failed with status: {}",
-            status
-        )));
-    }
-
-    let quote: QuoteResponseApi = response.json().await?;
+    let quote = retry("Ekubo quote", || async {
+        client
+            .get(&url)
+            .timeout(std::time::Duration::from_secs(10)) // 10-second timeout
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<QuoteResponseApi>()
+            .await
+    })
+    .await?;
     Ok(quote)
 }
 
+// Price per raw unit and market cap in USD, from the configured price sources
 pub async fn calculate_market_cap(
+    token_address: &str,
     total_supply: &str,
     symbol: &str,
 ) -> Result<(String, String), anyhow::Error> {
-    let amount = 10u64.pow(6).to_string();
-
-    // Try to get quote with better error handling
-    let response = match get_ekubo_quote(amount, "USDT", &symbol).await {
-        Ok(response) => {
-            // println!("Received quote: {:?}", response);
-            response
-        }
+    let token_price = match PRICE_ORACLE.usd_price_per_unit(token_address, symbol).await {
+        Ok(price) => price,
         Err(err) => {
             eprintln!("Error while getting quote: {:?}", err);
             return Err(anyhow::Error::msg(err.to_string()));
@@ -54,18 +49,23 @@ pub async fn calculate_market_cap(
         }
     };
 
-    // Parse response total safely
-    let response_total_num: f64 = match response.total.parse() {
-        Ok(num) => num,
-        Err(_) => {
-            eprintln!("Failed to parse response total: {}", response.total);
-            return Err(anyhow::Error::msg("Failed to parse response total"));
-        }
-    };
-
-    // Perform the calculation
-    let market_cap = total_supply_num / response_total_num;
-    let token_price: f64 = 1f64 / response_total_num;
+    let market_cap = total_supply_num * token_price;
 
     Ok((token_price.to_string(), market_cap.to_string()))
 }
+
+// USD value of one raw unit of a quote token (e.g. 1 wei of ETH)
+pub async fn get_usd_price_per_unit(symbol: &str, decimals: u8) -> Result<f64, anyhow::Error> {
+    if symbol == "USDT" {
+        return Ok(1f64 / 10f64.powi(decimals as i32));
+    }
+
+    let amount = 10u64.pow(6).to_string();
+    let response = get_ekubo_quote(amount, "USDT", symbol).await?;
+    let response_total_num: f64 = response
+        .total
+        .parse()
+        .map_err(|_| anyhow::Error::msg("Failed to parse response total"))?;
+
+    Ok(1f64 / response_total_num)
+}
@@ -1,6 +1,26 @@
+pub mod address;
+pub mod cache;
 pub mod call;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod chart;
+pub mod clustering;
+pub mod cohort;
+pub mod correlation;
+pub mod currency;
+pub mod enrichment;
 pub mod event_parser;
+pub mod format;
 pub mod types;
-// pub mod liquidity;
+pub mod liquidity;
+pub mod lockers;
 pub mod info_aggregator;
 pub mod market_cap;
+pub mod pending;
+pub mod portfolio;
+pub mod position;
+pub mod price;
+pub mod retry;
+pub mod risk;
+pub mod sanitize;
+pub mod rpc;
@@ -0,0 +1,107 @@
+use num_traits::ToPrimitive;
+use starknet::core::types::{
+    BlockId, BlockTag, InvokeTransaction, MaybePendingBlockWithTxs, Transaction,
+};
+use starknet::providers::Provider;
+use starknet_core::types::Felt;
+
+use super::call::AggregateError;
+use super::rpc::with_provider;
+use crate::constant::network::NETWORK;
+
+// Calls decoded from the `__execute__` calldata of a Cairo 1 account: [n, (to, selector, len, data..)*n]
+fn decode_calls(calldata: &[Felt]) -> Option<Vec<(Felt, &[Felt])>> {
+    let count = calldata.first()?.to_usize()?;
+    let mut calls = Vec::with_capacity(count.min(calldata.len()));
+    let mut cursor = 1;
+    for _ in 0..count {
+        let to = *calldata.get(cursor)?;
+        let len = calldata.get(cursor + 2)?.to_usize()?;
+        let end = len.checked_add(cursor + 3)?;
+        calls.push((to, calldata.get(cursor + 3..end)?));
+        cursor = end;
+    }
+    Some(calls)
+}
+
+// A buy routes quote tokens through a swap router towards the memecoin. Sells have to approve or
+// transfer the memecoin itself first, which is what tells the two apart.
+fn is_pending_buy(calldata: &[Felt], memecoin: Felt, routers: &[Felt]) -> bool {
+    let calls = match decode_calls(calldata) {
+        Some(calls) => calls,
+        // Legacy account encodings are rare enough to ignore
+        None => return false,
+    };
+    let routes_memecoin = calls
+        .iter()
+        .any(|(to, data)| routers.contains(to) && data.contains(&memecoin));
+    let moves_memecoin = calls.iter().any(|(to, _)| *to == memecoin);
+    routes_memecoin && !moves_memecoin
+}
+
+// Counts not yet accepted transactions buying the memecoin, None when the node has no pending block
+pub async fn count_pending_buys(memecoin_address: &str) -> Result<Option<usize>, AggregateError> {
+    let memecoin = Felt::from_hex(memecoin_address)
+        .map_err(|e| AggregateError::InvalidParams(format!("Invalid address: {}", e)))?;
+    let routers = [
+        Felt::from_hex_unchecked(&NETWORK.ekubo_router),
+        Felt::from_hex_unchecked(&NETWORK.avnu_exchange),
+    ];
+
+    let block = with_provider(|provider| async move {
+        provider
+            .get_block_with_txs(BlockId::Tag(BlockTag::Pending))
+            .await
+    })
+    .await?;
+    let transactions = match block {
+        MaybePendingBlockWithTxs::PendingBlock(block) => block.transactions,
+        // Nodes without pending support answer with the latest accepted block instead
+        MaybePendingBlockWithTxs::Block(_) => return Ok(None),
+    };
+
+    let count = transactions
+        .iter()
+        .filter(|transaction| {
+            let calldata = match transaction {
+                Transaction::Invoke(InvokeTransaction::V1(tx)) => &tx.calldata,
+                Transaction::Invoke(InvokeTransaction::V3(tx)) => &tx.calldata,
+                _ => return false,
+            };
+            is_pending_buy(calldata, memecoin, &routers)
+        })
+        .count();
+    Ok(Some(count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buys_are_told_apart_from_sells() {
+        let memecoin = Felt::from(0xabc_u64);
+        let quote = Felt::from(0xdef_u64);
+        let router = Felt::from(0x123_u64);
+        let selector = Felt::from(1_u64);
+
+        // approve(quote) then swap on the router
+        let buy = vec![
+            Felt::from(2_u64),
+            quote, selector, Felt::from(2_u64), router, Felt::from(100_u64),
+            router, selector, Felt::from(3_u64), memecoin, quote, Felt::from(100_u64),
+        ];
+        assert!(is_pending_buy(&buy, memecoin, &[router]));
+
+        // transfer(memecoin) then swap on the router
+        let sell = vec![
+            Felt::from(2_u64),
+            memecoin, selector, Felt::from(2_u64), router, Felt::from(100_u64),
+            router, selector, Felt::from(2_u64), memecoin, quote,
+        ];
+        assert!(!is_pending_buy(&sell, memecoin, &[router]));
+
+        // Truncated calldata never panics
+        assert!(!is_pending_buy(&buy[..6], memecoin, &[router]));
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use futures::future::join_all;
+
+use super::address::{addresses_eq, canonicalize_address, to_canonical_hex};
+use super::call::{get_account_transfer_events, get_deployment_block, get_latest_block, AggregateError};
+use super::market_cap::get_usd_price_per_unit;
+use super::types::common::{FilteredTokenData, SpamReason, TokenPosition};
+use crate::constant::constants::{quote_token, DECIMALS};
+use crate::constant::network::NETWORK;
+
+// Tokens launched less than a day ago count as fresh in the exposure readout
+const FRESH_TOKEN_SECS: u64 = 24 * 60 * 60;
+// Positions quoted and scanned at once, each costs an Ekubo quote and up to two event scans
+const MAX_CONCURRENT_POSITIONS: usize = 4;
+
+// Configuration for telling meaningful wallet positions apart from dust and spam
+#[derive(Clone)]
+pub struct PortfolioConfig {
+    dust_usd: f64,
+    spam_checks: bool,
+}
+
+impl PortfolioConfig {
+    pub fn new() -> Self {
+        Self {
+            dust_usd: std::env::var("PEEK_DUST_USD")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(1f64),
+            spam_checks: std::env::var("PEEK_SPAM_CHECKS")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(true),
+        }
+    }
+}
+
+// Values each memecoin position of a wallet and flags the dust and spam among them
+pub async fn classify_positions(
+    account: &str,
+    tokens: &[FilteredTokenData],
+    config: &PortfolioConfig,
+) -> Vec<TokenPosition> {
+    // Transfer scans start at each token's deployment, found by bisecting up to the head
+    let latest_block = match get_latest_block().await {
+        Ok((block_number, _)) => Some(block_number),
+        Err(e) => {
+            eprintln!("Failed to load the latest block for {}: {}", account, e);
+            None
+        }
+    };
+    let mut positions = Vec::with_capacity(tokens.len());
+    for chunk in tokens.chunks(MAX_CONCURRENT_POSITIONS) {
+        let classified = chunk
+            .iter()
+            .map(|token| classify_position(account, token, config, latest_block));
+        positions.extend(join_all(classified).await);
+    }
+    positions.sort_by(|a, b| b.usd_value.unwrap_or_default().total_cmp(&a.usd_value.unwrap_or_default()));
+    positions
+}
+
+async fn classify_position(
+    account: &str,
+    token: &FilteredTokenData,
+    config: &PortfolioConfig,
+    latest_block: Option<u64>,
+) -> TokenPosition {
+    let balance = token.formatted_balance.parse::<f64>().unwrap_or_default();
+    // Ekubo finds no route for tokens without liquidity
+    let usd_value = get_usd_price_per_unit(&token.address, DECIMALS as u8)
+        .await
+        .ok()
+        .map(|unit_price| unit_price * 10f64.powi(DECIMALS as i32) * balance);
+    let dust = usd_value.is_some_and(|value| value < config.dust_usd);
+
+    let spam = match usd_value {
+        _ if !config.spam_checks => None,
+        None => Some(SpamReason::NoLiquidity),
+        // Dust is hidden either way, skip the event scan
+        Some(_) if dust => None,
+        Some(_) => match has_interacted(account, &token.address, latest_block).await {
+            Ok(true) => None,
+            Ok(false) => Some(SpamReason::Airdropped),
+            Err(e) => {
+                eprintln!("Failed to check transfers of {} for {}: {}", token.address, account, e);
+                None
+            }
+        },
+    };
+
+    TokenPosition {
+        address: token.address.clone(),
+        symbol: token.symbol.clone(),
+        formatted_balance: token.formatted_balance.clone(),
+        usd_value,
+        dust,
+        spam,
+    }
+}
+
+// How a wallet's memecoin value is spread, shares are percentages of the valued positions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exposure {
+    pub largest_symbol: String,
+    pub largest_pct: f64,
+    pub fresh_pct: f64,
+}
+
+// Share of the value in the largest position and in tokens launched within the last day.
+// `launched_at` maps canonical token addresses to launch timestamps, tokens the bot never saw
+// launch count as old. Dust, spam and quote tokens like ETH or STRK are left out, None when
+// nothing has a value
+pub fn exposure(
+    positions: &[TokenPosition],
+    launched_at: &HashMap<String, u64>,
+    now: u64,
+) -> Option<Exposure> {
+    let valued: Vec<(&TokenPosition, f64)> = positions
+        .iter()
+        .filter(|position| position.is_meaningful() && quote_token(&position.address).is_none())
+        .filter_map(|position| position.usd_value.map(|value| (position, value)))
+        .filter(|(_, value)| *value > 0f64)
+        .collect();
+    let total: f64 = valued.iter().map(|(_, value)| value).sum();
+    let (largest, largest_value) = valued.iter().max_by(|a, b| a.1.total_cmp(&b.1))?;
+    let fresh: f64 = valued
+        .iter()
+        .filter(|(position, _)| {
+            canonicalize_address(&position.address)
+                .and_then(|address| launched_at.get(&address))
+                .is_some_and(|launched_at| now.saturating_sub(*launched_at) < FRESH_TOKEN_SECS)
+        })
+        .map(|(_, value)| value)
+        .sum();
+    Some(Exposure {
+        largest_symbol: largest.symbol.clone(),
+        largest_pct: largest_value * 100f64 / total,
+        fresh_pct: fresh * 100f64 / total,
+    })
+}
+
+// Whether the wallet ever moved the token, or got it through a swap or a mint rather than
+// an unsolicited transfer
+async fn has_interacted(
+    account: &str,
+    token_address: &str,
+    latest_block: Option<u64>,
+) -> Result<bool, AggregateError> {
+    let from_block = match latest_block {
+        Some(latest_block) => Some(get_deployment_block(token_address, latest_block).await?),
+        None => None,
+    };
+    if !get_account_transfer_events(token_address, account, true, from_block).await?.is_empty() {
+        return Ok(true);
+    }
+    let received = get_account_transfer_events(token_address, account, false, from_block).await?;
+    Ok(received.iter().any(|event| {
+        event
+            .keys
+            .get(1)
+            .is_some_and(|from| is_interaction_source(&to_canonical_hex(from)))
+    }))
+}
+
+// Senders a wallet only receives from by trading, launching or being allocated the token
+fn is_interaction_source(from: &str) -> bool {
+    // Mints come from the zero address, e.g. team allocations
+    addresses_eq(from, "0x0")
+        || [
+            &NETWORK.ekubo_core,
+            &NETWORK.ekubo_router,
+            &NETWORK.avnu_exchange,
+            &NETWORK.exchange,
+            &NETWORK.memecoin_factory,
+        ]
+        .iter()
+        .any(|source| addresses_eq(from, source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant::constants::ETHER;
+
+    #[test]
+    fn test_swaps_and_mints_count_as_interaction() {
+        assert!(is_interaction_source("0x0"));
+        assert!(is_interaction_source(&NETWORK.ekubo_core));
+        assert!(!is_interaction_source(
+            "0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"
+        ));
+    }
+
+    #[test]
+    fn test_exposure() {
+        let position = |address: &str, usd_value: Option<f64>, dust: bool| TokenPosition {
+            address: address.to_string(),
+            symbol: address.to_uppercase(),
+            formatted_balance: "1".to_string(),
+            usd_value,
+            dust,
+            spam: None,
+        };
+        let positions = vec![
+            position("0xa", Some(820f64), false),
+            position("0xb", Some(180f64), false),
+            position("0xc", Some(0.5), true),
+            position("0xd", None, false),
+            // Gas money isn't memecoin exposure
+            position(ETHER.address, Some(5_000f64), false),
+        ];
+        let now = 1_000_000;
+        let launched_at: HashMap<String, u64> = [
+            (canonicalize_address("0xa").unwrap(), now - 3600),
+            (canonicalize_address("0xb").unwrap(), now - 2 * FRESH_TOKEN_SECS),
+        ]
+        .into_iter()
+        .collect();
+
+        let exposure = exposure(&positions, &launched_at, now).unwrap();
+        assert_eq!(exposure.largest_symbol, "0XA");
+        assert!((exposure.largest_pct - 82f64).abs() < 1e-9);
+        assert!((exposure.fresh_pct - 82f64).abs() < 1e-9);
+        assert_eq!(super::exposure(&positions[2..], &launched_at, now), None);
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use futures::future::join_all;
+use starknet_core::types::{EmittedEvent, Felt};
+
+use super::address::{addresses_eq, to_canonical_hex};
+use super::call::{
+    get_account_transfer_events, get_block_timestamp, get_block_transactions, get_deployment_block,
+    get_latest_block,
+};
+use super::event_parser::TransferEvent;
+use super::info_aggregator::with_deadline;
+use crate::constant::constants::DECIMALS;
+use crate::constant::network::NETWORK;
+
+// Blocks looked up at once, each cache miss is a header fetch
+const MAX_CONCURRENT_BLOCK_LOOKUPS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+// A wallet's balance of a token right after one of its transfers
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionChange {
+    pub timestamp: u64,
+    pub balance: f64,
+    // None for transfers that aren't swaps, e.g. airdrops or moves between wallets
+    pub trade: Option<TradeSide>,
+}
+
+// A transfer in or out of the wallet, amounts in whole tokens
+struct PositionTransfer {
+    block_number: u64,
+    transaction_hash: Felt,
+    amount: f64,
+    incoming: bool,
+    counterparty: String,
+}
+
+// The wallet's position in the token after each of its transfers, oldest first, replayed from
+// the token's Transfer events since its deployment
+pub async fn position_history(
+    token_address: &str,
+    wallet: &str,
+) -> Result<Vec<PositionChange>, anyhow::Error> {
+    with_deadline("Position history", fetch_position_history(token_address, wallet)).await
+}
+
+async fn fetch_position_history(
+    token_address: &str,
+    wallet: &str,
+) -> Result<Vec<PositionChange>, anyhow::Error> {
+    let (latest_block, _) = get_latest_block().await?;
+    let deployed_at = get_deployment_block(token_address, latest_block).await?;
+    let mut transfers = Vec::new();
+    for incoming in [true, false] {
+        let events = get_account_transfer_events(token_address, wallet, !incoming, Some(deployed_at)).await?;
+        transfers.extend(events.iter().filter_map(|event| position_transfer(event, incoming)));
+    }
+
+    // Each direction comes back in event order, only blocks with both need their transactions
+    let mut directions: HashMap<u64, (bool, bool)> = HashMap::new();
+    for transfer in &transfers {
+        let seen = directions.entry(transfer.block_number).or_default();
+        if transfer.incoming {
+            seen.0 = true;
+        } else {
+            seen.1 = true;
+        }
+    }
+    let mixed: Vec<u64> = directions
+        .iter()
+        .filter(|(_, (incoming, outgoing))| *incoming && *outgoing)
+        .map(|(block_number, _)| *block_number)
+        .collect();
+    let mut transactions = HashMap::new();
+    for chunk in mixed.chunks(MAX_CONCURRENT_BLOCK_LOOKUPS) {
+        let lookups = join_all(chunk.iter().map(|block_number| get_block_transactions(*block_number))).await;
+        for (block_number, hashes) in chunk.iter().zip(lookups) {
+            transactions.insert(*block_number, hashes?);
+        }
+    }
+    order_transfers(&mut transfers, &transactions);
+
+    let blocks: Vec<u64> = directions.into_keys().collect();
+    let mut timestamps = HashMap::new();
+    for chunk in blocks.chunks(MAX_CONCURRENT_BLOCK_LOOKUPS) {
+        let lookups = join_all(chunk.iter().map(|block_number| get_block_timestamp(*block_number))).await;
+        for (block_number, timestamp) in chunk.iter().zip(lookups) {
+            timestamps.insert(*block_number, timestamp?);
+        }
+    }
+
+    let changes = transfers
+        .iter()
+        .map(|transfer| {
+            (
+                timestamps[&transfer.block_number],
+                if transfer.incoming { transfer.amount } else { -transfer.amount },
+                trade_side(transfer),
+            )
+        })
+        .collect();
+    Ok(replay(changes))
+}
+
+// Sorts by block, then by transaction where the block's order is known. Stable, so events of one
+// transaction keep the order they were emitted in
+fn order_transfers(transfers: &mut [PositionTransfer], transactions: &HashMap<u64, Vec<Felt>>) {
+    transfers.sort_by_key(|transfer| {
+        let position = transactions
+            .get(&transfer.block_number)
+            .and_then(|hashes| hashes.iter().position(|hash| *hash == transfer.transaction_hash));
+        (transfer.block_number, position)
+    });
+}
+
+fn position_transfer(event: &EmittedEvent, incoming: bool) -> Option<PositionTransfer> {
+    // Pending transfers show up in the chart once they are in a block
+    let block_number = event.block_number?;
+    let transfer = TransferEvent::from_starknet_event(event.from_address, &event.keys, &event.data).ok()?;
+    let amount = transfer.amount.parse::<f64>().ok()? / 10f64.powi(DECIMALS as i32);
+    let counterparty = if incoming { transfer.from } else { transfer.to };
+    Some(PositionTransfer {
+        block_number,
+        transaction_hash: event.transaction_hash,
+        amount,
+        incoming,
+        counterparty: to_canonical_hex(&counterparty),
+    })
+}
+
+// Buys are paid out by a pool, router or aggregator, sells are paid into one
+fn trade_side(transfer: &PositionTransfer) -> Option<TradeSide> {
+    let swap = [
+        &NETWORK.ekubo_core,
+        &NETWORK.ekubo_router,
+        &NETWORK.avnu_exchange,
+        &NETWORK.exchange,
+    ]
+    .iter()
+    .any(|venue| addresses_eq(&transfer.counterparty, venue));
+    match (swap, transfer.incoming) {
+        (true, true) => Some(TradeSide::Buy),
+        (true, false) => Some(TradeSide::Sell),
+        (false, _) => None,
+    }
+}
+
+// Running balance over (timestamp, signed amount, trade) changes
+fn replay(changes: Vec<(u64, f64, Option<TradeSide>)>) -> Vec<PositionChange> {
+    let mut balance = 0f64;
+    changes
+        .into_iter()
+        .map(|(timestamp, amount, trade)| {
+            // Rounding can't leave a fully sold position slightly negative
+            balance = (balance + amount).max(0f64);
+            PositionChange {
+                timestamp,
+                balance,
+                trade,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_position() {
+        let changes = replay(vec![
+            (100, 1_000f64, Some(TradeSide::Buy)),
+            (200, 500f64, None),
+            (300, -1_500.000_001, Some(TradeSide::Sell)),
+        ]);
+        let balances: Vec<f64> = changes.iter().map(|change| change.balance).collect();
+        assert_eq!(balances, vec![1_000f64, 1_500f64, 0f64]);
+        assert_eq!(changes[2].trade, Some(TradeSide::Sell));
+    }
+
+    #[test]
+    fn test_swaps_are_trades() {
+        let transfer = |counterparty: &str, incoming: bool| PositionTransfer {
+            block_number: 1,
+            transaction_hash: Felt::ONE,
+            amount: 1f64,
+            incoming,
+            counterparty: counterparty.to_string(),
+        };
+        assert_eq!(trade_side(&transfer(&NETWORK.ekubo_core, true)), Some(TradeSide::Buy));
+        assert_eq!(trade_side(&transfer(&NETWORK.avnu_exchange, false)), Some(TradeSide::Sell));
+        assert_eq!(trade_side(&transfer("0x123", true)), None);
+    }
+
+    #[test]
+    fn test_transfers_follow_transaction_order() {
+        let transfer = |block_number: u64, transaction: u64, incoming: bool| PositionTransfer {
+            block_number,
+            transaction_hash: Felt::from(transaction),
+            amount: 1f64,
+            incoming,
+            counterparty: "0x123".to_string(),
+        };
+        // Incoming transfers are fetched first, in block 7 the sale came before the buy
+        let mut transfers = vec![
+            transfer(7, 20, true),
+            transfer(3, 10, true),
+            transfer(7, 11, false),
+            transfer(3, 12, false),
+        ];
+        let transactions: HashMap<u64, Vec<Felt>> = [(7, vec![Felt::from(11u64), Felt::from(20u64)])].into();
+        order_transfers(&mut transfers, &transactions);
+        let order: Vec<(u64, bool)> = transfers
+            .iter()
+            .map(|transfer| (transfer.block_number, transfer.incoming))
+            .collect();
+        assert_eq!(order, vec![(3, true), (3, false), (7, false), (7, true)]);
+    }
+}
@@ -0,0 +1,290 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use starknet_core::types::Felt;
+use tokio::task::JoinSet;
+
+use super::address::addresses_eq;
+use super::call::{call_contract, parse_u256_from_felts};
+use super::market_cap::get_ekubo_quote;
+use crate::constant::constants::{ETHER, JEDISWAP_FACTORY, USDC};
+use crate::constant::network::{Network, NETWORK};
+use crate::trade::avnu::AvnuClient;
+
+// A venue a memecoin can be priced on
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // USD value of one raw unit of the token
+    async fn usd_price_per_unit(&self, token_address: &str, symbol: &str) -> Result<f64, anyhow::Error>;
+}
+
+// Ekubo's quoter API, the only source that routes by symbol rather than address
+pub struct EkuboQuoter;
+
+#[async_trait]
+impl PriceSource for EkuboQuoter {
+    fn name(&self) -> &'static str {
+        "ekubo"
+    }
+
+    async fn usd_price_per_unit(&self, _token_address: &str, symbol: &str) -> Result<f64, anyhow::Error> {
+        let response = get_ekubo_quote(10u64.pow(6).to_string(), "USDT", symbol).await?;
+        let total: f64 = response
+            .total
+            .parse()
+            .map_err(|_| anyhow::Error::msg("Failed to parse response total"))?;
+        Ok(1f64 / total)
+    }
+}
+
+// AVNU aggregator quotes, covering every venue AVNU routes through
+pub struct AvnuQuotes {
+    client: AvnuClient,
+}
+
+#[async_trait]
+impl PriceSource for AvnuQuotes {
+    fn name(&self) -> &'static str {
+        "avnu"
+    }
+
+    async fn usd_price_per_unit(&self, token_address: &str, _symbol: &str) -> Result<f64, anyhow::Error> {
+        // Buys with 1 USDC, no taker since the quote is never executed
+        let sell_amount = 10u128.pow(USDC.decimals as u32);
+        let quote = self.client.quote(USDC.address, token_address, sell_amount, None).await?;
+        let bought = quote.buy_amount().to_f64().unwrap_or_default();
+        Ok(1f64 / bought)
+    }
+}
+
+// Spot price from the token's JediSwap pair against ETH, valued with the ETH/USDC pair
+pub struct JediSwapReserves;
+
+impl JediSwapReserves {
+    // Reserves of `token` and of the other side of `pair`
+    async fn reserves(pair: &str, token: &str) -> Result<(f64, f64), anyhow::Error> {
+        let token0 = call_contract(pair, "token0", vec![]).await?;
+        let reserves = call_contract(pair, "get_reserves", vec![]).await?;
+        if token0.is_empty() || reserves.len() < 4 {
+            return Err(anyhow::Error::msg("Failed to decode pair reserves"));
+        }
+        let reserve0 = BigUint::from_str(&parse_u256_from_felts(&reserves[0], &reserves[1]))?;
+        let reserve1 = BigUint::from_str(&parse_u256_from_felts(&reserves[2], &reserves[3]))?;
+        let (reserve0, reserve1) = (
+            reserve0.to_f64().unwrap_or_default(),
+            reserve1.to_f64().unwrap_or_default(),
+        );
+        if addresses_eq(&token0[0].to_hex_string(), token) {
+            Ok((reserve0, reserve1))
+        } else {
+            Ok((reserve1, reserve0))
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for JediSwapReserves {
+    fn name(&self) -> &'static str {
+        "jediswap"
+    }
+
+    async fn usd_price_per_unit(&self, token_address: &str, _symbol: &str) -> Result<f64, anyhow::Error> {
+        let calldata = vec![Felt::from_hex(token_address)?, Felt::from_hex(ETHER.address)?];
+        let pair = call_contract(JEDISWAP_FACTORY, "get_pair", calldata).await?;
+        let pair = match pair.first() {
+            Some(pair) if *pair != Felt::ZERO => pair.to_hex_string(),
+            _ => return Err(anyhow::Error::msg("No JediSwap pair against ETH")),
+        };
+
+        let (token_reserve, eth_reserve) = Self::reserves(&pair, token_address).await?;
+        let (wei_reserve, usdc_reserve) = Self::reserves(ETHER.usdc_pair, ETHER.address).await?;
+        let usd_per_wei = usdc_reserve / 10f64.powi(USDC.decimals as i32) / wei_reserve;
+        Ok(eth_reserve / token_reserve * usd_per_wei)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceMode {
+    // First source that answers, in configured order
+    Fallback,
+    // Median of every source that answers
+    Median,
+}
+
+// Configuration for pricing memecoins
+#[derive(Clone)]
+pub struct PriceConfig {
+    sources: Vec<String>,
+    mode: PriceMode,
+    avnu_url: String,
+}
+
+impl PriceConfig {
+    pub fn new() -> Self {
+        // Neither AVNU nor JediSwap are deployed on Sepolia
+        let default_sources = match NETWORK.network {
+            Network::Sepolia => "ekubo",
+            _ => "ekubo,avnu,jediswap",
+        };
+
+        Self {
+            sources: std::env::var("PRICE_SOURCES")
+                .unwrap_or_else(|_| default_sources.to_string())
+                .split(',')
+                .map(|source| source.trim().to_lowercase())
+                .filter(|source| !source.is_empty())
+                .collect(),
+            mode: match std::env::var("PRICE_MODE").unwrap_or_default().to_lowercase().as_str() {
+                "median" => PriceMode::Median,
+                _ => PriceMode::Fallback,
+            },
+            avnu_url: std::env::var("AVNU_API_URL")
+                .unwrap_or_else(|_| "https://starknet.api.avnu.fi".to_string()),
+        }
+    }
+}
+
+// Prices memecoins from the configured sources
+pub struct PriceOracle {
+    sources: Vec<Arc<dyn PriceSource>>,
+    mode: PriceMode,
+}
+
+impl PriceOracle {
+    pub fn new(config: PriceConfig) -> Self {
+        let mut sources: Vec<Arc<dyn PriceSource>> = Vec::new();
+        for source in &config.sources {
+            match source.as_str() {
+                "ekubo" => sources.push(Arc::new(EkuboQuoter)),
+                "avnu" => sources.push(Arc::new(AvnuQuotes {
+                    client: AvnuClient::new(config.avnu_url.clone()),
+                })),
+                "jediswap" => sources.push(Arc::new(JediSwapReserves)),
+                unknown => eprintln!("Ignoring unknown price source: {}", unknown),
+            }
+        }
+        if sources.is_empty() {
+            sources.push(Arc::new(EkuboQuoter));
+        }
+
+        Self {
+            sources,
+            mode: config.mode,
+        }
+    }
+
+    pub async fn usd_price_per_unit(&self, token_address: &str, symbol: &str) -> Result<f64, anyhow::Error> {
+        match self.mode {
+            PriceMode::Fallback => {
+                let mut last_error = None;
+                for source in &self.sources {
+                    match checked(source.usd_price_per_unit(token_address, symbol).await) {
+                        Ok(price) => return Ok(price),
+                        Err(e) => {
+                            eprintln!("Price source {} failed for {}: {}", source.name(), token_address, e);
+                            last_error = Some(e);
+                        }
+                    }
+                }
+                Err(last_error.unwrap_or_else(|| anyhow::Error::msg("No price source configured")))
+            }
+            PriceMode::Median => {
+                let mut set = JoinSet::new();
+                for source in &self.sources {
+                    let source = source.clone();
+                    let (token_address, symbol) = (token_address.to_string(), symbol.to_string());
+                    set.spawn(async move {
+                        let price = checked(source.usd_price_per_unit(&token_address, &symbol).await);
+                        (source.name(), price)
+                    });
+                }
+
+                let mut prices = Vec::new();
+                while let Some(joined) = set.join_next().await {
+                    match joined {
+                        Ok((_, Ok(price))) => prices.push(price),
+                        Ok((name, Err(e))) => {
+                            eprintln!("Price source {} failed for {}: {}", name, token_address, e)
+                        }
+                        Err(e) => eprintln!("Price source task failed: {}", e),
+                    }
+                }
+                median(&mut prices).ok_or_else(|| anyhow::Error::msg("No price source responded"))
+            }
+        }
+    }
+}
+
+// Quotes of illiquid tokens can come back empty, which must not read as a price
+fn checked(price: Result<f64, anyhow::Error>) -> Result<f64, anyhow::Error> {
+    match price {
+        Ok(price) if price.is_finite() && price > 0f64 => Ok(price),
+        Ok(price) => Err(anyhow::Error::msg(format!("Invalid price {}", price))),
+        Err(e) => Err(e),
+    }
+}
+
+fn median(prices: &mut [f64]) -> Option<f64> {
+    prices.sort_by(|a, b| a.total_cmp(b));
+    match prices.len() {
+        0 => None,
+        n if n % 2 == 1 => Some(prices[n / 2]),
+        n => Some((prices[n / 2 - 1] + prices[n / 2]) / 2f64),
+    }
+}
+
+lazy_static! {
+    pub static ref PRICE_ORACLE: PriceOracle = PriceOracle::new(PriceConfig::new());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static str, Option<f64>);
+
+    #[async_trait]
+    impl PriceSource for Fixed {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        async fn usd_price_per_unit(&self, _token_address: &str, _symbol: &str) -> Result<f64, anyhow::Error> {
+            self.1.ok_or_else(|| anyhow::Error::msg("unavailable"))
+        }
+    }
+
+    fn oracle(mode: PriceMode, prices: &[Option<f64>]) -> PriceOracle {
+        PriceOracle {
+            sources: prices
+                .iter()
+                .map(|price| Arc::new(Fixed("fixed", *price)) as Arc<dyn PriceSource>)
+                .collect(),
+            mode,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_price_modes() {
+        let prices = [None, Some(0f64), Some(3f64), Some(1f64), Some(2.5)];
+        let fallback = oracle(PriceMode::Fallback, &prices);
+        assert_eq!(fallback.usd_price_per_unit("0x1", "A").await.unwrap(), 3f64);
+
+        // Failed and zero quotes are left out of the median
+        let median = oracle(PriceMode::Median, &prices);
+        assert_eq!(median.usd_price_per_unit("0x1", "A").await.unwrap(), 2.5);
+        let even = oracle(PriceMode::Median, &[Some(1f64), Some(2f64)]);
+        assert_eq!(even.usd_price_per_unit("0x1", "A").await.unwrap(), 1.5);
+
+        for mode in [PriceMode::Fallback, PriceMode::Median] {
+            let failing = oracle(mode, &[None, Some(f64::NAN)]);
+            assert!(failing.usd_price_per_unit("0x1", "A").await.is_err());
+        }
+    }
+}
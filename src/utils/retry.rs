@@ -0,0 +1,185 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use lazy_static::lazy_static;
+use serde::de::DeserializeOwned;
+
+use super::call::AggregateError;
+#[cfg(feature = "chaos")]
+use super::chaos::{explorer_error, Fault, CHAOS};
+
+lazy_static! {
+    // Shared by explorer, Ekubo and RPC calls
+    pub static ref RETRY_CONFIG: RetryConfig = RetryConfig::new();
+}
+
+// Configuration for retrying transient failures of outbound calls
+#[derive(Clone)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+impl RetryConfig {
+    pub fn new() -> Self {
+        Self {
+            max_attempts: std::env::var("RETRY_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(3)
+                .max(1),
+            base_delay: Duration::from_millis(
+                std::env::var("RETRY_BASE_DELAY_MS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(250),
+            ),
+            max_delay: Duration::from_millis(
+                std::env::var("RETRY_MAX_DELAY_MS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(4000),
+            ),
+        }
+    }
+
+    // Delay after the given failed attempt, doubling each time up to the cap. `jitter` in [0, 1)
+    // picks a point in the upper half so callers failing together don't retry together
+    pub fn backoff(&self, attempt: u32, jitter: f64) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        exponential.mul_f64(0.5 + jitter.clamp(0f64, 1f64) / 2f64)
+    }
+}
+
+// Sub-second clock noise, random enough to spread retries without pulling in an RNG
+pub fn jitter() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or_default();
+    (nanos % 1000) as f64 / 1000f64
+}
+
+// Whether a failure is transient and worth another attempt
+pub trait Retryable {
+    fn is_retryable(&self) -> bool;
+}
+
+impl Retryable for reqwest::Error {
+    fn is_retryable(&self) -> bool {
+        match self.status() {
+            Some(status) => status.as_u16() == 429 || status.is_server_error(),
+            None => self.is_timeout() || self.is_connect() || self.is_request(),
+        }
+    }
+}
+
+impl Retryable for AggregateError {
+    fn is_retryable(&self) -> bool {
+        AggregateError::is_retryable(self)
+    }
+}
+
+impl Retryable for anyhow::Error {
+    fn is_retryable(&self) -> bool {
+        if let Some(error) = self.downcast_ref::<reqwest::Error>() {
+            return Retryable::is_retryable(error);
+        }
+        self.downcast_ref::<AggregateError>()
+            .is_some_and(AggregateError::is_retryable)
+    }
+}
+
+// Runs an operation until it succeeds, fails permanently or runs out of attempts
+pub async fn retry<T, E, F, Fut>(label: &str, operation: F) -> Result<T, E>
+where
+    E: Retryable + Display,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        match operation().await {
+            Ok(result) => return Ok(result),
+            Err(e) if e.is_retryable() && attempt < RETRY_CONFIG.max_attempts => {
+                println!("{} failed (attempt {}): {}, retrying", label, attempt, e);
+                tokio::time::sleep(RETRY_CONFIG.backoff(attempt, jitter())).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// GET returning JSON, retried on timeouts, connection errors, 429 and 5xx
+pub async fn get_json<T: DeserializeOwned>(label: &str, url: &str) -> Result<T, reqwest::Error> {
+    retry(label, || async {
+        #[cfg(feature = "chaos")]
+        if label.starts_with("Explorer") && CHAOS.should_fail(Fault::ExplorerError) {
+            return Err(explorer_error());
+        }
+        reqwest::get(url).await?.error_for_status()?.json::<T>().await
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn config() -> RetryConfig {
+        RetryConfig {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let config = config();
+        assert_eq!(config.backoff(1, 0.999), Duration::from_micros(99_950));
+        assert_eq!(config.backoff(1, 0f64), Duration::from_millis(50));
+        assert_eq!(config.backoff(3, 0f64), Duration::from_millis(200));
+        assert_eq!(config.backoff(10, 0f64), Duration::from_millis(250));
+        assert!(config.backoff(10, 0.999) <= Duration::from_millis(500));
+        assert!((0f64..1f64).contains(&jitter()));
+    }
+
+    #[test]
+    fn test_classifies_aggregate_errors() {
+        let error = anyhow::Error::new(AggregateError::RateLimited);
+        assert!(Retryable::is_retryable(&error));
+        let error = anyhow::Error::new(AggregateError::Parse("felt".to_string()));
+        assert!(!Retryable::is_retryable(&error));
+        assert!(!Retryable::is_retryable(&anyhow::anyhow!("bad input")));
+    }
+
+    #[tokio::test]
+    async fn test_retry_stops_on_permanent_errors() {
+        let calls = AtomicU32::new(0);
+        let result: Result<(), AggregateError> = retry("test", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(AggregateError::Parse("felt".to_string()))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let calls = AtomicU32::new(0);
+        let result = retry("test", || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(AggregateError::RateLimited),
+                attempt => Ok(attempt),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 1);
+    }
+}
@@ -0,0 +1,251 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::address::addresses_eq;
+use super::liquidity::get_ekubo_liquidity_lock_position;
+use super::lockers::LOCKER_REGISTRY;
+use super::types::common::{RiskFlag, RiskReport};
+use super::types::ekubo::Memecoin;
+use crate::constant::constants::quote_token;
+
+// Points each flag adds to the score, capped at 100
+const TEAM_ALLOCATION_WEIGHT: u32 = 25;
+const OWNER_NOT_RENOUNCED_WEIGHT: u32 = 15;
+const UNKNOWN_QUOTE_TOKEN_WEIGHT: u32 = 30;
+const LOCK_UNVERIFIED_WEIGHT: u32 = 15;
+const LOCK_EXPIRING_WEIGHT: u32 = 30;
+const HOLDER_CONCENTRATION_WEIGHT: u32 = 25;
+const SLOW_HOLDER_GROWTH_WEIGHT: u32 = 15;
+
+// Holder velocity is measured over this long after launch
+pub const HOLDER_VELOCITY_WINDOW_SECS: u64 = 30 * 60;
+// Samples closer together than this make for a noisy velocity
+const MIN_VELOCITY_SPAN_SECS: u64 = 5 * 60;
+
+// Thresholds past which a launch gets flagged
+#[derive(Debug, Clone)]
+pub struct RiskConfig {
+    max_team_allocation_pct: f64,
+    max_top_holders_pct: f64,
+    min_lock_secs: u64,
+    min_holders_per_minute: f64,
+}
+
+impl RiskConfig {
+    pub fn new() -> Self {
+        let min_lock_days = std::env::var("RISK_MIN_LOCK_DAYS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30);
+
+        Self {
+            max_team_allocation_pct: std::env::var("RISK_MAX_TEAM_PCT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(10f64),
+            max_top_holders_pct: std::env::var("RISK_MAX_TOP_HOLDERS_PCT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(50f64),
+            min_lock_secs: min_lock_days * 86_400,
+            min_holders_per_minute: std::env::var("RISK_MIN_HOLDERS_PER_MIN")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.5),
+        }
+    }
+}
+
+// What the score is computed from, gathered by assess_risk
+#[derive(Debug, Clone)]
+pub struct RiskInputs {
+    pub team_allocation_pct: f64,
+    pub owner_renounced: bool,
+    pub quote_token_known: bool,
+    // None when the lock position could not be read
+    pub unlock_time: Option<u64>,
+    pub top_holders_pct: Option<f64>,
+}
+
+pub fn score_risk(inputs: &RiskInputs, config: &RiskConfig, now: u64) -> RiskReport {
+    let mut flags = Vec::new();
+    if inputs.team_allocation_pct > config.max_team_allocation_pct {
+        flags.push(RiskFlag::HighTeamAllocation {
+            pct: inputs.team_allocation_pct,
+        });
+    }
+    if !inputs.owner_renounced {
+        flags.push(RiskFlag::OwnerNotRenounced);
+    }
+    if !inputs.quote_token_known {
+        flags.push(RiskFlag::UnknownQuoteToken);
+    }
+    match inputs.unlock_time {
+        None => flags.push(RiskFlag::LockUnverified),
+        Some(unlock_time) if unlock_time < now + config.min_lock_secs => {
+            flags.push(RiskFlag::LockExpiring { unlock_time })
+        }
+        Some(_) => {}
+    }
+    if let Some(pct) = inputs.top_holders_pct.filter(|pct| *pct > config.max_top_holders_pct) {
+        flags.push(RiskFlag::HolderConcentration { pct });
+    }
+
+    RiskReport {
+        score: weigh(&flags),
+        flags,
+    }
+}
+
+fn weigh(flags: &[RiskFlag]) -> u8 {
+    let score: u32 = flags
+        .iter()
+        .map(|flag| match flag {
+            RiskFlag::HighTeamAllocation { .. } => TEAM_ALLOCATION_WEIGHT,
+            RiskFlag::OwnerNotRenounced => OWNER_NOT_RENOUNCED_WEIGHT,
+            RiskFlag::UnknownQuoteToken => UNKNOWN_QUOTE_TOKEN_WEIGHT,
+            RiskFlag::LockUnverified => LOCK_UNVERIFIED_WEIGHT,
+            RiskFlag::LockExpiring { .. } => LOCK_EXPIRING_WEIGHT,
+            RiskFlag::HolderConcentration { .. } => HOLDER_CONCENTRATION_WEIGHT,
+            RiskFlag::SlowHolderGrowth { .. } => SLOW_HOLDER_GROWTH_WEIGHT,
+        })
+        .sum();
+    score.min(100) as u8
+}
+
+// Holders gained per minute in the first half hour after launch, from (timestamp, holders)
+// samples oldest first. None until the samples span a few minutes of that window
+pub fn holder_velocity(history: &[(u64, u64)], launched_at: u64) -> Option<f64> {
+    let window_end = launched_at + HOLDER_VELOCITY_WINDOW_SECS;
+    let mut samples = history
+        .iter()
+        .filter(|(recorded_at, _)| (launched_at..=window_end).contains(recorded_at));
+    let first = samples.next()?;
+    let last = samples.next_back()?;
+    let span = last.0 - first.0;
+    if span < MIN_VELOCITY_SPAN_SECS {
+        return None;
+    }
+    Some((last.1 as f64 - first.1 as f64) * 60f64 / span as f64)
+}
+
+// Velocity is only known after launch, so it is scored onto an existing report
+pub fn score_holder_velocity(report: &mut RiskReport, per_minute: f64, config: &RiskConfig) {
+    report.flags.retain(|flag| !matches!(flag, RiskFlag::SlowHolderGrowth { .. }));
+    if per_minute < config.min_holders_per_minute {
+        report.flags.push(RiskFlag::SlowHolderGrowth { per_minute });
+    }
+    report.score = weigh(&report.flags);
+}
+
+// Scores a launched memecoin, only the liquidity lock needs an extra call
+// Unruggable memecoins renounce by transferring ownership to the zero address, handing it to a
+// registered locker takes it out of the team's hands just the same
+pub fn owner_renounced(owner: &str) -> bool {
+    addresses_eq(owner, "0x0") || LOCKER_REGISTRY.iter().any(|locker| addresses_eq(owner, &locker.address))
+}
+
+pub async fn assess_risk(memecoin: &Memecoin, top_holders_pct: Option<f64>) -> RiskReport {
+    let total_supply = memecoin.total_supply.parse::<f64>().unwrap_or_default();
+    let team_allocation = memecoin.launch.team_allocation.parse::<f64>().unwrap_or_default();
+    let team_allocation_pct = if total_supply > 0f64 {
+        team_allocation * 100f64 / total_supply
+    } else {
+        0f64
+    };
+
+    let unlock_time = match get_ekubo_liquidity_lock_position(&memecoin.liquidity).await {
+        Ok(position) => Some(position.unlock_time),
+        Err(e) => {
+            eprintln!("Failed to read liquidity lock of {}: {}", memecoin.address, e);
+            None
+        }
+    };
+
+    let inputs = RiskInputs {
+        team_allocation_pct,
+        owner_renounced: owner_renounced(&memecoin.owner),
+        quote_token_known: quote_token(&memecoin.liquidity.quote_token).is_some(),
+        unlock_time,
+        top_holders_pct,
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    score_risk(&inputs, &RiskConfig::new(), now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant::constants::LIQUIDITY_LOCK_FOREVER_TIMESTAMP;
+
+    fn config() -> RiskConfig {
+        RiskConfig {
+            max_team_allocation_pct: 10f64,
+            max_top_holders_pct: 50f64,
+            min_lock_secs: 30 * 86_400,
+            min_holders_per_minute: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_score_risk() {
+        let safe = RiskInputs {
+            team_allocation_pct: 2f64,
+            owner_renounced: true,
+            quote_token_known: true,
+            unlock_time: Some(LIQUIDITY_LOCK_FOREVER_TIMESTAMP),
+            top_holders_pct: Some(20f64),
+        };
+        let report = score_risk(&safe, &config(), 1_700_000_000);
+        assert_eq!(report, RiskReport::default());
+
+        let risky = RiskInputs {
+            team_allocation_pct: 15f64,
+            owner_renounced: false,
+            quote_token_known: false,
+            unlock_time: Some(1_700_000_000 + 86_400),
+            top_holders_pct: Some(80f64),
+        };
+        let report = score_risk(&risky, &config(), 1_700_000_000);
+        assert_eq!(report.score, 100);
+        assert_eq!(report.flags.len(), 5);
+        assert!(report.flags.contains(&RiskFlag::LockExpiring {
+            unlock_time: 1_700_000_000 + 86_400
+        }));
+
+        let unverified = RiskInputs {
+            unlock_time: None,
+            top_holders_pct: None,
+            ..safe
+        };
+        assert_eq!(score_risk(&unverified, &config(), 0).flags, vec![RiskFlag::LockUnverified]);
+    }
+
+    #[test]
+    fn test_holder_velocity() {
+        let launched_at = 1_000;
+        // The last sample is past the window and left out
+        let history = [(1_000, 10), (1_600, 25), (2_200, 40), (9_000, 500)];
+        assert_eq!(holder_velocity(&history, launched_at), Some(1.5));
+        assert_eq!(holder_velocity(&history[..1], launched_at), None);
+        assert_eq!(holder_velocity(&[(1_000, 10), (1_100, 30)], launched_at), None);
+
+        let mut report = RiskReport::default();
+        score_holder_velocity(&mut report, 0.2, &config());
+        assert_eq!(report.flags, vec![RiskFlag::SlowHolderGrowth { per_minute: 0.2 }]);
+        assert_eq!(report.score, 15);
+        score_holder_velocity(&mut report, 3.2, &config());
+        assert_eq!(report, RiskReport::default());
+    }
+
+    #[test]
+    fn test_owner_renounced() {
+        assert!(owner_renounced("0x0"));
+        assert!(owner_renounced(&format!("0x{}", "0".repeat(64))));
+        assert!(!owner_renounced("0xabc"));
+        // Unknown owners are never taken for renounced ones
+        assert!(!owner_renounced(""));
+    }
+}
@@ -0,0 +1,375 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::{JsonRpcClient, Provider, ProviderError};
+use url::Url;
+
+use super::call::{classify_provider_error, AggregateError};
+#[cfg(feature = "chaos")]
+use super::chaos::{Fault, CHAOS};
+use super::retry::{jitter, RETRY_CONFIG};
+use crate::constant::network::NETWORK;
+
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Shared by every read-only RPC call so clients and rate limits aren't rebuilt per call, built by
+// init_rpc_pool at startup
+static RPC_POOL: OnceLock<ProviderPool> = OnceLock::new();
+
+// Builds the shared pool, a pool that is already built is kept
+pub fn init_rpc_pool(config: RpcConfig) -> Result<&'static ProviderPool, AggregateError> {
+    if let Some(pool) = RPC_POOL.get() {
+        return Ok(pool);
+    }
+    let pool = ProviderPool::new(config)?;
+    Ok(RPC_POOL.get_or_init(|| pool))
+}
+
+// None until init_rpc_pool succeeded
+pub fn rpc_pool() -> Option<&'static ProviderPool> {
+    RPC_POOL.get()
+}
+
+// Configuration for the RPC provider pool
+#[derive(Clone)]
+pub struct RpcConfig {
+    urls: Vec<String>,
+    max_rps: u32,
+    cooldown: Duration,
+    health_interval: Duration,
+    // An attempt taking longer fails over to the next endpoint
+    timeout: Duration,
+    // Sent with every request, e.g. API keys of paid endpoints
+    headers: Vec<(String, String)>,
+}
+
+impl RpcConfig {
+    pub fn new() -> Self {
+        let urls: Vec<String> = std::env::var("STARKNET_RPC_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(String::from)
+            .collect();
+        Self {
+            urls: if urls.is_empty() {
+                vec![NETWORK.rpc_url.clone()]
+            } else {
+                urls
+            },
+            max_rps: std::env::var("RPC_MAX_RPS")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .filter(|rps| *rps > 0)
+                .unwrap_or(10),
+            cooldown: Duration::from_secs(
+                std::env::var("RPC_COOLDOWN_SECS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(30),
+            ),
+            health_interval: Duration::from_secs(
+                std::env::var("RPC_HEALTH_INTERVAL_SECS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(60),
+            ),
+            timeout: Duration::from_secs(
+                std::env::var("RPC_TIMEOUT_SECS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .filter(|secs| *secs > 0)
+                    .unwrap_or(30),
+            ),
+            headers: parse_headers(&std::env::var("RPC_HEADERS").unwrap_or_default()),
+        }
+    }
+}
+
+// RPC_HEADERS as "name: value" pairs separated by semicolons, e.g. "x-api-key: 0123"
+fn parse_headers(spec: &str) -> Vec<(String, String)> {
+    spec.split(';')
+        .map(str::trim)
+        .filter(|header| !header.is_empty())
+        .filter_map(|header| match header.split_once(':') {
+            Some((name, value)) if !name.trim().is_empty() => {
+                Some((name.trim().to_string(), value.trim().to_string()))
+            }
+            // The value may be a secret, so only the position is logged
+            _ => {
+                eprintln!("Ignoring RPC_HEADERS entry without a name, expected name: value ❗️");
+                None
+            }
+        })
+        .collect()
+}
+
+struct Endpoint {
+    url: String,
+    client: Arc<JsonRpcClient<HttpTransport>>,
+    // Earliest instant the next request may be sent
+    next_slot: tokio::sync::Mutex<Instant>,
+    unhealthy_until: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.unhealthy_until
+            .lock()
+            .unwrap()
+            .is_none_or(|until| until <= now)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStatus {
+    pub endpoints: usize,
+    pub healthy: usize,
+}
+
+// Endpoints in order of preference, each with its own rate limit and health state
+pub struct ProviderPool {
+    endpoints: Vec<Endpoint>,
+    min_interval: Duration,
+    cooldown: Duration,
+    health_interval: Duration,
+    timeout: Duration,
+    headers: Vec<(String, String)>,
+    // Attempts and transient failures since the last take_call_stats, read by the load breaker
+    calls: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl ProviderPool {
+    pub fn new(config: RpcConfig) -> Result<Self, AggregateError> {
+        let endpoints = config
+            .urls
+            .iter()
+            .map(|url| {
+                Ok(Endpoint {
+                    url: url.clone(),
+                    client: Arc::new(client(url, &config.headers)?),
+                    next_slot: tokio::sync::Mutex::new(Instant::now()),
+                    unhealthy_until: Mutex::new(None),
+                })
+            })
+            .collect::<Result<Vec<_>, AggregateError>>()?;
+        Ok(Self {
+            endpoints,
+            min_interval: Duration::from_secs(1) / config.max_rps,
+            cooldown: config.cooldown,
+            health_interval: config.health_interval,
+            timeout: config.timeout,
+            headers: config.headers,
+            calls: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        })
+    }
+
+    // A client of its own for `url` with the pool's headers, for callers that need to own their
+    // provider such as accounts signing transactions
+    pub fn client(&self, url: &str) -> Result<JsonRpcClient<HttpTransport>, AggregateError> {
+        client(url, &self.headers)
+    }
+
+    // The preferred endpoint, the first of STARKNET_RPC_URLS
+    pub fn primary_url(&self) -> &str {
+        &self.endpoints[0].url
+    }
+
+    pub fn status(&self) -> PoolStatus {
+        let now = Instant::now();
+        PoolStatus {
+            endpoints: self.endpoints.len(),
+            healthy: self.endpoints.iter().filter(|e| e.is_healthy(now)).count(),
+        }
+    }
+
+    // Attempts and transient failures since the previous call
+    pub fn take_call_stats(&self) -> (u64, u64) {
+        (self.calls.swap(0, Ordering::Relaxed), self.failures.swap(0, Ordering::Relaxed))
+    }
+
+    // First healthy endpoint, or the one that recovers soonest when all are cooling down
+    fn pick(&self) -> usize {
+        let now = Instant::now();
+        if let Some(index) = self.endpoints.iter().position(|e| e.is_healthy(now)) {
+            return index;
+        }
+        (0..self.endpoints.len())
+            .min_by_key(|index| *self.endpoints[*index].unhealthy_until.lock().unwrap())
+            .unwrap_or(0)
+    }
+
+    // Waits until the endpoint's rate limit allows another request
+    async fn wait_for_slot(&self, index: usize) {
+        let slot = {
+            let mut next_slot = self.endpoints[index].next_slot.lock().await;
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + self.min_interval;
+            slot
+        };
+        tokio::time::sleep_until(slot.into()).await;
+    }
+
+    fn mark_failed(&self, index: usize, error: &AggregateError) {
+        let endpoint = &self.endpoints[index];
+        let mut unhealthy_until = endpoint.unhealthy_until.lock().unwrap();
+        if unhealthy_until.is_none() {
+            eprintln!("RPC endpoint {} marked unhealthy: {}", endpoint.url, error);
+        }
+        *unhealthy_until = Some(Instant::now() + self.cooldown);
+    }
+
+    fn mark_healthy(&self, index: usize) {
+        let endpoint = &self.endpoints[index];
+        if endpoint.unhealthy_until.lock().unwrap().take().is_some() {
+            println!("RPC endpoint {} is healthy again", endpoint.url);
+        }
+    }
+
+    // Runs an RPC operation, failing over to the next endpoint on transient errors
+    pub async fn with_provider<T, F, Fut>(&self, operation: F) -> Result<T, AggregateError>
+    where
+        F: Fn(Arc<JsonRpcClient<HttpTransport>>) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        let max_attempts = self.endpoints.len().max(RETRY_CONFIG.max_attempts as usize);
+        let mut attempt = 1;
+        loop {
+            let index = self.pick();
+            self.wait_for_slot(index).await;
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            #[cfg(feature = "chaos")]
+            let result = if CHAOS.should_fail(Fault::RpcTimeout) {
+                Err(AggregateError::Timeout("operation timed out (chaos)".to_string()))
+            } else {
+                self.attempt(index, &operation).await
+            };
+            #[cfg(not(feature = "chaos"))]
+            let result = self.attempt(index, &operation).await;
+            match result {
+                Ok(result) => {
+                    self.mark_healthy(index);
+                    return Ok(result);
+                }
+                Err(error) => {
+                    if !error.is_retryable() {
+                        return Err(error);
+                    }
+                    self.failures.fetch_add(1, Ordering::Relaxed);
+                    self.mark_failed(index, &error);
+                    if attempt >= max_attempts {
+                        println!("RPC call failed: {}", error);
+                        return Err(error);
+                    }
+                    println!("RPC call failed (attempt {}): {}, retrying", attempt, error);
+                    tokio::time::sleep(RETRY_CONFIG.backoff(attempt as u32, jitter())).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    async fn attempt<T, F, Fut>(&self, index: usize, operation: &F) -> Result<T, AggregateError>
+    where
+        F: Fn(Arc<JsonRpcClient<HttpTransport>>) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        match tokio::time::timeout(self.timeout, operation(self.endpoints[index].client.clone())).await {
+            Ok(result) => result.map_err(classify_provider_error),
+            Err(_) => Err(AggregateError::Timeout(format!(
+                "no response within {}s",
+                self.timeout.as_secs()
+            ))),
+        }
+    }
+
+    // Probes every endpoint periodically so recovered ones are preferred again
+    pub async fn run_health_checks(&self) {
+        let mut interval = tokio::time::interval(self.health_interval);
+        loop {
+            interval.tick().await;
+            for (index, endpoint) in self.endpoints.iter().enumerate() {
+                self.wait_for_slot(index).await;
+                match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, endpoint.client.block_number()).await {
+                    Ok(Ok(_)) => self.mark_healthy(index),
+                    Ok(Err(e)) => self.mark_failed(index, &classify_provider_error(e)),
+                    Err(_) => self.mark_failed(index, &AggregateError::Timeout("health check".to_string())),
+                }
+            }
+        }
+    }
+}
+
+fn client(url: &str, headers: &[(String, String)]) -> Result<JsonRpcClient<HttpTransport>, AggregateError> {
+    let transport = headers
+        .iter()
+        .fold(HttpTransport::new(Url::parse(url)?), |transport, (name, value)| {
+            transport.with_header(name.clone(), value.clone())
+        });
+    Ok(JsonRpcClient::new(transport))
+}
+
+pub async fn with_provider<T, F, Fut>(operation: F) -> Result<T, AggregateError>
+where
+    F: Fn(Arc<JsonRpcClient<HttpTransport>>) -> Fut,
+    Fut: Future<Output = Result<T, ProviderError>>,
+{
+    match RPC_POOL.get() {
+        Some(pool) => pool.with_provider(operation).await,
+        None => Err(AggregateError::Unavailable("RPC pool is not initialized".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(urls: &[&str]) -> ProviderPool {
+        ProviderPool::new(RpcConfig {
+            urls: urls.iter().map(|url| url.to_string()).collect(),
+            max_rps: 10,
+            cooldown: Duration::from_secs(30),
+            health_interval: Duration::from_secs(60),
+            timeout: Duration::from_secs(30),
+            headers: Vec::new(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_headers() {
+        assert_eq!(
+            parse_headers("x-api-key: 0123 ; Authorization: Bearer a:b;"),
+            vec![
+                ("x-api-key".to_string(), "0123".to_string()),
+                ("Authorization".to_string(), "Bearer a:b".to_string()),
+            ]
+        );
+        assert!(parse_headers("").is_empty());
+        assert!(parse_headers("no-colon; : value").is_empty());
+    }
+
+    #[test]
+    fn test_pick_fails_over_to_next_healthy_endpoint() {
+        let pool = pool(&["http://primary.rpc", "http://secondary.rpc"]);
+        assert_eq!(pool.pick(), 0);
+
+        pool.mark_failed(0, &AggregateError::RateLimited);
+        assert_eq!(pool.pick(), 1);
+        assert_eq!(pool.status().healthy, 1);
+
+        // With every endpoint down, the one that failed first is retried first
+        pool.mark_failed(1, &AggregateError::RateLimited);
+        assert_eq!(pool.pick(), 0);
+
+        pool.mark_healthy(1);
+        assert_eq!(pool.pick(), 1);
+    }
+}
@@ -0,0 +1,94 @@
+// Shown under a launch whose name or symbol had characters removed
+pub const HIDDEN_CHARS_WARNING: &str = "Symbol contains hidden characters ⚠️";
+
+// Token names and symbols as they should be shown, with what was taken out of them noted
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisplayText {
+    pub text: String,
+    // Zero-width, bidi or other invisible characters were removed
+    pub had_hidden_chars: bool,
+}
+
+// Characters that render as nothing or reorder the text around them, enough to make one ticker
+// look like another in an alert
+pub fn is_hidden_char(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}'
+            | '\u{034F}'
+            | '\u{061C}'
+            | '\u{115F}'
+            | '\u{1160}'
+            | '\u{17B4}'
+            | '\u{17B5}'
+            | '\u{180E}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{2069}'
+            | '\u{3164}'
+            | '\u{FEFF}'
+            | '\u{FFA0}'
+            | '\u{E0000}'..='\u{E007F}'
+    ) || (c.is_control() && !c.is_whitespace())
+}
+
+// Zero-width joiner, part of emoji sequences like 👨‍👩‍👧 but invisible between letters
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+// Drops hidden characters and folds any run of whitespace, newlines included, into one space
+pub fn sanitize_display(text: &str) -> DisplayText {
+    let mut sanitized = DisplayText {
+        text: String::with_capacity(text.len()),
+        had_hidden_chars: false,
+    };
+    let mut pending_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            pending_space = !sanitized.text.is_empty();
+        } else if c == ZERO_WIDTH_JOINER
+            && !pending_space
+            && sanitized.text.chars().last().is_some_and(|last| !last.is_ascii() && !last.is_alphanumeric())
+        {
+            sanitized.text.push(c);
+        } else if is_hidden_char(c) {
+            sanitized.had_hidden_chars = true;
+        } else {
+            if pending_space {
+                sanitized.text.push(' ');
+                pending_space = false;
+            }
+            sanitized.text.push(c);
+        }
+    }
+    sanitized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_display() {
+        let plain = sanitize_display("  Starknet   Doge ");
+        assert_eq!(plain.text, "Starknet Doge");
+        assert!(!plain.had_hidden_chars);
+
+        let zero_width = sanitize_display("PE\u{200B}PE");
+        assert_eq!(zero_width.text, "PEPE");
+        assert!(zero_width.had_hidden_chars);
+
+        // An override flips how the rest reads, "EGOD" shows as "DOGE"
+        let reversed = sanitize_display("\u{202E}EGOD\u{202C}");
+        assert_eq!(reversed.text, "EGOD");
+        assert!(reversed.had_hidden_chars);
+
+        let fake_line = sanitize_display("MEME\n\u{0007}Verified");
+        assert_eq!(fake_line.text, "MEME Verified");
+        assert!(fake_line.had_hidden_chars);
+
+        // Emoji keep their variation selectors and joiners, a joiner between letters goes
+        assert!(!sanitize_display("🚀\u{FE0F}").had_hidden_chars);
+        assert!(!sanitize_display("👨\u{200D}👩").had_hidden_chars);
+        assert_eq!(sanitize_display("DO\u{200D}GE").text, "DOGE");
+    }
+}
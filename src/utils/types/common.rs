@@ -1,5 +1,11 @@
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use serde::{Deserialize, Serialize};
 
+use crate::utils::format::{format_large_number, format_number, team_allocation_pct};
+use crate::utils::sanitize::sanitize_display;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct MemecoinInfo {
     pub address: String,
@@ -10,30 +16,260 @@ pub struct MemecoinInfo {
     pub team_allocation: String,
     pub price: String,
     pub market_cap: String,
+    // Market cap at the launch block, empty when the quote token couldn't be priced back then
+    #[serde(default)]
+    pub launch_market_cap: String,
     pub usd_dex_liquidity: String,
+    pub usd_token_liquidity: String,
+    pub usd_quote_liquidity: String,
+    pub pool_fee_percent: String,
+    #[serde(default)]
+    pub pool_tick_spacing: String,
+    pub lp_fee_apr: String,
+    // Token the pool is paired with, empty for launches recorded before it was tracked
+    #[serde(default)]
+    pub quote_token: String,
+    // Symbol of the quote token when it's on the QUOTE_TOKENS safe list
+    #[serde(default)]
+    pub quote_symbol: String,
+    #[serde(default)]
+    pub quote_token_safe: bool,
+    // None for launches recorded before risk scoring existed
+    #[serde(default)]
+    pub risk: Option<RiskReport>,
+    // Trading activity from an aggregator, only looked up for /sniQ
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<PairActivity>,
+    // Filled on first use, read it through MemecoinInfo::formatted
+    #[serde(skip)]
+    pub formatted_cache: OnceLock<FormattedFields>,
+}
+
+// Display strings of a launch, formatted once and shared by every card rendered from it
+#[derive(Debug, Clone, Default)]
+pub struct FormattedFields {
+    pub name: String,
+    pub symbol: String,
+    // The name or symbol had invisible characters, a sign of a spoofed ticker
+    pub hidden_chars: bool,
+    pub market_cap: String,
+    pub launch_market_cap: String,
+    pub supply: String,
+    pub liquidity: String,
+    pub token_liquidity: String,
+    pub quote_liquidity: String,
+    pub team_allocation_pct: Option<f64>,
 }
 
+impl MemecoinInfo {
+    pub fn formatted(&self) -> &FormattedFields {
+        self.formatted_cache.get_or_init(|| {
+            let name = sanitize_display(&self.name);
+            let symbol = sanitize_display(&self.symbol);
+            FormattedFields {
+                hidden_chars: name.had_hidden_chars || symbol.had_hidden_chars,
+                name: name.text,
+                symbol: symbol.text,
+                market_cap: format_number(&self.market_cap).unwrap_or_else(|_| self.market_cap.clone()),
+                launch_market_cap: format_number(&self.launch_market_cap).unwrap_or_default(),
+                supply: format_large_number(&self.total_supply)
+                    .and_then(|supply| format_number(&supply))
+                    .unwrap_or_else(|_| self.total_supply.clone()),
+                liquidity: self
+                    .usd_dex_liquidity
+                    .parse::<f64>()
+                    .map(|liquidity| format!("{:.2}", liquidity))
+                    .unwrap_or_else(|_| self.usd_dex_liquidity.clone()),
+                token_liquidity: format_number(&self.usd_token_liquidity).unwrap_or_default(),
+                quote_liquidity: format_number(&self.usd_quote_liquidity).unwrap_or_default(),
+                team_allocation_pct: team_allocation_pct(&self.total_supply, &self.team_allocation),
+            }
+        })
+    }
+
+    // Change of the market cap since the launch block, None when either isn't known
+    pub fn market_cap_change_pct(&self) -> Option<f64> {
+        let launch = self.launch_market_cap.parse::<f64>().ok().filter(|launch| *launch > 0f64)?;
+        let current = self.market_cap.parse::<f64>().ok()?;
+        Some((current - launch) / launch * 100f64)
+    }
+}
+
+// Explorer payloads are camelCase. Aliases accept the renames seen across explorer versions and
+// fields we don't read default, so vendor drift doesn't take wallet commands down
 #[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct Holders {
+    #[serde(alias = "address")]
     pub holder: String,
     pub balance: String,
-    pub lastTransferTime: u64,
+    #[serde(default, alias = "last_transfer_time")]
+    pub last_transfer_time: u64,
+    #[serde(default)]
     pub decimals: String,
-    pub balanceSeparated: String,
-    pub contractAlias: Option<String>,
+    #[serde(default, alias = "balance_separated")]
+    pub balance_separated: String,
+    #[serde(default, alias = "contract_alias", alias = "alias")]
+    pub contract_alias: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct HolderApiResponse {
     pub items: Vec<Holders>,
-    pub lastPage: u32,
-    pub hasMore: bool,
+    #[serde(default, alias = "last_page")]
+    pub last_page: u32,
+    #[serde(default, alias = "has_more")]
+    pub has_more: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TokenCategoryResponse {
     pub token_address: String,
     pub category: String,
+    #[serde(default)]
+    pub holder_count: usize,
+    // Holder count after merging wallets that look controlled by the same entity
+    #[serde(default)]
+    pub effective_holder_count: usize,
+    // All holders including those past the sampled page, a lower bound when paginated
+    #[serde(default)]
+    pub total_holders: usize,
+    // Share of the supply held by the largest sampled holders, pool and lockers excluded
+    #[serde(default)]
+    pub top_holders_pct: Option<f64>,
+    // Largest sampled holders, biggest first
+    #[serde(default)]
+    pub top_holders: Vec<TopHolder>,
+}
+
+// 24h trading of a token's most liquid pool, as reported by Dexscreener or GeckoTerminal
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PairActivity {
+    pub source: String,
+    pub pool_address: String,
+    pub volume_24h_usd: f64,
+    pub price_change_24h_pct: Option<f64>,
+    pub buys_24h: u64,
+    pub sells_24h: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TopHolder {
+    pub address: String,
+    // Share of the total supply
+    pub pct: f64,
+}
+
+// A reason a launch looks risky, each one weighs into the RiskReport score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RiskFlag {
+    HighTeamAllocation { pct: f64 },
+    OwnerNotRenounced,
+    UnknownQuoteToken,
+    LockUnverified,
+    LockExpiring { unlock_time: u64 },
+    HolderConcentration { pct: f64 },
+    // Holders gained per minute in the first half hour after launch
+    SlowHolderGrowth { per_minute: f64 },
+}
+
+impl RiskFlag {
+    pub fn describe(&self) -> String {
+        match self {
+            RiskFlag::HighTeamAllocation { pct } => format!("Team holds {:.1}% of the supply", pct),
+            RiskFlag::OwnerNotRenounced => "Ownership not renounced".to_string(),
+            RiskFlag::UnknownQuoteToken => "Paired with an unknown quote token".to_string(),
+            RiskFlag::LockUnverified => "Liquidity lock could not be verified".to_string(),
+            RiskFlag::LockExpiring { unlock_time } => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default();
+                match unlock_time.saturating_sub(now) / 86_400 {
+                    0 => "Liquidity unlocks within a day".to_string(),
+                    days => format!("Liquidity unlocks in {} days", days),
+                }
+            }
+            RiskFlag::HolderConcentration { pct } => format!("Top holders own {:.1}% of the supply", pct),
+            RiskFlag::SlowHolderGrowth { per_minute } => {
+                format!("Few new holders after launch ({:+.1}/min)", per_minute)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "🟢 Low",
+            RiskLevel::Medium => "🟡 Medium",
+            RiskLevel::High => "🔴 High",
+        }
+    }
+}
+
+// Rug risk of a launch from 0 (nothing found) to 100, with the flags behind it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RiskReport {
+    pub score: u8,
+    pub flags: Vec<RiskFlag>,
+}
+
+impl RiskReport {
+    pub fn level(&self) -> RiskLevel {
+        match self.score {
+            0..=24 => RiskLevel::Low,
+            25..=49 => RiskLevel::Medium,
+            _ => RiskLevel::High,
+        }
+    }
+}
+
+// Extra findings attached to a launch alert
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LaunchContext {
+    // An earlier launch with the same symbol or name, or from the same deployer
+    CloneOf {
+        token_address: String,
+        symbol: String,
+        launched_at: u64,
+        price_change_pct: Option<f64>,
+        same_deployer: bool,
+    },
+    // Buys of the token waiting in the pending block when the launch was picked up
+    PendingBuys {
+        count: usize,
+    },
+    // When the token was created, it was on the pre-launch radar since then
+    Created {
+        created_at: u64,
+        // Created and launched in one block, so nobody could see it coming
+        same_block: bool,
+    },
+    // Where the starting market cap and liquidity rank among the week's launches, in percentiles
+    Cohort {
+        cohort_size: usize,
+        market_cap_pct: Option<f64>,
+        liquidity_pct: Option<f64>,
+    },
+}
+
+// Everything broadcast about a single launch
+#[derive(Debug, Clone, Serialize)]
+pub struct LaunchAlert {
+    pub info: MemecoinInfo,
+    pub holders: TokenCategoryResponse,
+    pub context: Vec<LaunchContext>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -41,32 +277,85 @@ pub struct InfoResponse {
     pub coin_info: MemecoinInfo,
     pub holders_data: TokenCategoryResponse,
 }
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TokenHoldings {
     pub account_address: String,
     pub total_tokens: String,
+    #[serde(default)]
+    pub locked_tokens: String,
+    // Held memecoins, most valuable first
+    #[serde(default)]
+    pub positions: Vec<TokenPosition>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SpamReason {
+    NoLiquidity,
+    Airdropped,
+}
+
+impl SpamReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SpamReason::NoLiquidity => "no liquidity",
+            SpamReason::Airdropped => "airdropped",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TokenPosition {
+    pub address: String,
+    pub symbol: String,
+    pub formatted_balance: String,
+    pub usd_value: Option<f64>,
+    pub dust: bool,
+    pub spam: Option<SpamReason>,
+}
+
+impl TokenPosition {
+    pub fn is_meaningful(&self) -> bool {
+        !self.dust && self.spam.is_none()
+    }
 }
 
 #[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct HoldingApiResponse {
-    pub erc20TokenBalances: Vec<TokenBalance>,
-    pub verfiedTokensCount: u32,
-    pub totalTokensCount: u32,
-    pub totalUsdValue: String,
+    #[serde(alias = "erc20_token_balances", alias = "tokenBalances")]
+    pub erc20_token_balances: Vec<TokenBalance>,
+    // Misspelled by the explorer
+    #[serde(
+        default,
+        rename = "verfiedTokensCount",
+        alias = "verifiedTokensCount",
+        alias = "verified_tokens_count"
+    )]
+    pub verified_tokens_count: u32,
+    #[serde(default, alias = "total_tokens_count")]
+    pub total_tokens_count: u32,
+    #[serde(default, alias = "total_usd_value")]
+    pub total_usd_value: String,
 }
 
 #[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct TokenBalance {
     pub name: String,
     pub address: String,
     pub balance: String,
-    pub usdBalance: Option<String>,          // Could be null
-    pub usdFormattedBalance: Option<String>, // Could be null
+    #[serde(default, alias = "usd_balance")]
+    pub usd_balance: Option<String>, // Could be null
+    #[serde(default, alias = "usd_formatted_balance")]
+    pub usd_formatted_balance: Option<String>, // Could be null
     pub decimals: String,
     pub symbol: String,
-    pub formattedBalance: String,
-    pub iconName: String,
-    pub isVerified: bool,
+    #[serde(alias = "formatted_balance")]
+    pub formatted_balance: String,
+    #[serde(default, alias = "icon_name")]
+    pub icon_name: String,
+    #[serde(default, alias = "is_verified")]
+    pub is_verified: bool,
 }
 
 #[derive(Serialize, Debug, Deserialize, Clone)]
@@ -78,9 +367,60 @@ pub struct FilteredTokenData {
     pub symbol: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct UserTokenInfo {
     pub coin_info: MemecoinInfo,
     pub account_balance: String,
     pub usd_value: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Holder and token balance payloads of every explorer version we parse
+    const HOLDER_FIXTURES: [&str; 2] = [
+        include_str!("../../../tests/fixtures/explorer/voyager_holders.json"),
+        include_str!("../../../tests/fixtures/explorer/renamed_holders.json"),
+    ];
+    const BALANCE_FIXTURES: [&str; 2] = [
+        include_str!("../../../tests/fixtures/explorer/voyager_token_balances.json"),
+        include_str!("../../../tests/fixtures/explorer/renamed_token_balances.json"),
+    ];
+
+    #[test]
+    fn test_explorer_payload_compatibility() {
+        for fixture in HOLDER_FIXTURES {
+            let response: HolderApiResponse = serde_json::from_str(fixture).unwrap();
+            assert_eq!((response.last_page, response.has_more), (3, true));
+            assert_eq!(response.items.len(), 2);
+            assert_eq!(response.items[0].last_transfer_time, 1717000000);
+            assert_eq!(response.items[1].contract_alias.as_deref(), Some("Ekubo: Core"));
+        }
+        for fixture in BALANCE_FIXTURES {
+            let response: HoldingApiResponse = serde_json::from_str(fixture).unwrap();
+            assert_eq!(response.erc20_token_balances.len(), 1);
+            let token = &response.erc20_token_balances[0];
+            assert_eq!((token.symbol.as_str(), token.decimals.as_str()), ("SDOGE", "0x12"));
+            assert_eq!(token.formatted_balance, "1500000");
+        }
+    }
+
+    #[test]
+    fn test_market_cap_change() {
+        let info = MemecoinInfo {
+            market_cap: "15000".to_string(),
+            launch_market_cap: "10000".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(info.market_cap_change_pct(), Some(50f64));
+        // Launches cached before launch market caps were recorded
+        let cached: MemecoinInfo = serde_json::from_value(serde_json::json!({
+            "address": "0x1", "name": "", "symbol": "", "total_supply": "", "owner": "",
+            "team_allocation": "", "price": "", "market_cap": "15000", "usd_dex_liquidity": "",
+            "usd_token_liquidity": "", "usd_quote_liquidity": "", "pool_fee_percent": "", "lp_fee_apr": "",
+        }))
+        .unwrap();
+        assert_eq!(cached.market_cap_change_pct(), None);
+    }
+}
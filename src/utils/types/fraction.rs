@@ -1,11 +1,18 @@
 use std::ops::{Add, Sub, Mul, Div};
 use std::cmp::Ordering;
-use num_bigint::{BigInt, BigUint};
+use num_bigint::BigInt;
 use num_traits::{One, Zero};
 use num_integer::Integer;
 use std::cmp::min;
 use thiserror::Error;
 
+// Decimals computed by to_formatted_string, its scale is built once rather than on every call
+const FORMAT_PRECISION: usize = 18;
+
+lazy_static::lazy_static! {
+    static ref FORMAT_SCALE: BigInt = BigInt::from(10u64).pow(FORMAT_PRECISION as u32);
+}
+
 /// Represents errors that can occur when working with Fraction
 #[derive(Error, Debug)]
 pub enum FractionError {
@@ -86,61 +93,36 @@ impl Fraction {
             return Ok("0".to_string());
         }
 
-        let mut numerator = self.numerator.clone();
-        let denominator = self.denominator.clone();
-        
-        // Calculate the integer result with extra precision for rounding
-        let precision = 18; // Use high precision for calculation
-        let scale = BigUint::from(10u64).pow(precision);
-        numerator *= BigInt::from(scale);
-        let (quotient, remainder) = numerator.div_rem(&denominator);
-        
-        // Round up if necessary
-        let remainder_as_biguint: BigUint = remainder.to_biguint().unwrap();
-        let rounded = if remainder_as_biguint * BigUint::from(2u64) >= denominator.to_biguint().unwrap() {
-            quotient + BigInt::from(1u64)
-        } else {
-            quotient
-        };
+        // Calculate the integer result with extra precision for rounding, rounding half up
+        let (mut rounded, remainder) = (&self.numerator * &*FORMAT_SCALE).div_rem(&self.denominator);
+        if &remainder * 2u32 >= self.denominator {
+            rounded += 1u32;
+        }
 
-        // Convert to string and handle decimal point placement
-        let mut str_value = rounded.to_string();
-        
-        // Pad with leading zeros if necessary
-        while str_value.len() <= precision.try_into().unwrap() {
-            str_value.insert(0, '0');
+        // Pad with leading zeros so there is at least one integer digit
+        let mut digits = rounded.to_string();
+        if digits.len() <= FORMAT_PRECISION {
+            digits = format!("{:0>width$}", digits, width = FORMAT_PRECISION + 1);
         }
 
-        // Insert decimal point
-        let decimal_pos = str_value.len() - precision as usize;
-        let int_part = &str_value[..decimal_pos];
-        let frac_part = &str_value[decimal_pos..];
+        // Split at the decimal point and remove trailing zeros after it
+        let (int_part, frac_part) = digits.split_at(digits.len() - FORMAT_PRECISION);
+        let frac_part = frac_part.trim_end_matches('0');
 
-        // Remove trailing zeros after decimal and handle formatting
-        let mut formatted = if frac_part.chars().all(|c| c == '0') {
-            int_part.to_string()
-        } else {
-            format!("{}.{}", int_part, frac_part.trim_end_matches('0'))
-        };
-
-        // Add thousand separators to the integer part
-        let dot_pos = formatted.find('.');
-        let int_end = dot_pos.unwrap_or(formatted.len());
-        let mut with_separators = String::new();
-        let int_chars: Vec<char> = formatted[..int_end].chars().collect();
-        
-        for (i, &c) in int_chars.iter().enumerate() {
-            if i > 0 && (int_chars.len() - i) % 3 == 0 {
-                with_separators.push(',');
+        // Write the integer part with thousand separators, then the decimals, into one buffer
+        let mut formatted = String::with_capacity(int_part.len() * 4 / 3 + frac_part.len() + 1);
+        for (i, c) in int_part.chars().enumerate() {
+            if i > 0 && (int_part.len() - i).is_multiple_of(3) {
+                formatted.push(',');
             }
-            with_separators.push(c);
+            formatted.push(c);
         }
-
-        if let Some(dot_pos) = dot_pos {
-            with_separators.push_str(&formatted[dot_pos..]);
+        if !frac_part.is_empty() {
+            formatted.push('.');
+            formatted.push_str(frac_part);
         }
 
-        Ok(with_separators)
+        Ok(formatted)
     }
 
     pub fn to_significant_digits(&self, digits: usize, rounding: Rounding) -> Result<String, Box<dyn std::error::Error>> {
@@ -259,13 +241,49 @@ impl Div for Fraction {
 // Implement comparison operations
 impl PartialEq for Fraction {
     fn eq(&self, other: &Self) -> bool {
-        self.numerator.clone() * &other.denominator == other.numerator.clone() * &self.denominator
+        &self.numerator * &other.denominator == &other.numerator * &self.denominator
     }
 }
 
 impl PartialOrd for Fraction {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        (self.numerator.clone() * &other.denominator)
-            .partial_cmp(&(other.numerator.clone() * &self.denominator))
+        (&self.numerator * &other.denominator).partial_cmp(&(&other.numerator * &self.denominator))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fraction(numerator: u128, denominator: u128) -> Fraction {
+        Fraction::new(BigInt::from(numerator), Some(BigInt::from(denominator))).unwrap()
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_to_formatted_string() {
+        assert_eq!(fraction(0, 7).to_formatted_string().unwrap(), "0");
+        assert_eq!(fraction(1_234_567, 1).to_formatted_string().unwrap(), "1,234,567");
+        assert_eq!(fraction(5, 2).to_formatted_string().unwrap(), "2.5");
+        assert_eq!(fraction(2, 3).to_formatted_string().unwrap(), "0.666666666666666667");
+        assert_eq!(fraction(1, 3).to_formatted_string().unwrap(), "0.333333333333333333");
+        // Half of the last decimal rounds up, less than half is dropped
+        let half = fraction(1, 2_000_000_000_000_000_000);
+        assert_eq!(half.to_formatted_string().unwrap(), "0.000000000000000001");
+        assert_eq!(fraction(1, 3_000_000_000_000_000_000).to_formatted_string().unwrap(), "0");
+        assert_eq!(
+            fraction(123_456_789_012_345_678_901, 1_000).to_formatted_string().unwrap(),
+            "123,456,789,012,345,678.901"
+        );
+        assert_eq!(fraction(999, 1_000).to_significant_digits(0, Rounding::RoundDown).unwrap(), "0");
+        assert_eq!(
+            fraction(1_234_567_891, 1_000).to_significant_digits(0, Rounding::RoundDown).unwrap(),
+            "1,234,567"
+        );
+    }
+
+    #[test]
+    fn test_comparisons_across_denominators() {
+        assert!(fraction(1, 2) == fraction(2, 4));
+        assert!(fraction(1, 3) < fraction(1, 2));
+    }
+}
@@ -1,3 +1,3 @@
 pub mod ekubo;
-// pub mod fraction;
+pub mod fraction;
 pub mod common;
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::RwLock;
+
+use crate::telegram::TelegramBot;
+use crate::utils::address::canonicalize_address;
+use crate::utils::info_aggregator::aggregate_info;
+use crate::utils::market_cap::calculate_market_cap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertMetric {
+    Price,
+    MarketCap,
+}
+
+impl AlertMetric {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertMetric::Price => "price",
+            AlertMetric::MarketCap => "market cap",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertDirection {
+    Above,
+    Below,
+}
+
+impl AlertDirection {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertDirection::Above => "above",
+            AlertDirection::Below => "below",
+        }
+    }
+
+    fn is_crossed(&self, value: f64, target: f64) -> bool {
+        match self {
+            AlertDirection::Above => value >= target,
+            AlertDirection::Below => value <= target,
+        }
+    }
+}
+
+// Threshold as typed by the user, the direction is inferred from the current value when omitted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AlertRequest {
+    pub metric: AlertMetric,
+    pub direction: Option<AlertDirection>,
+    pub target: f64,
+}
+
+impl AlertRequest {
+    // Parses the arguments following the token, e.g. `above 0.005`, `mcap 1000000`, `mcap below 50000`
+    pub fn parse(args: &[&str]) -> Option<Self> {
+        let mut metric = AlertMetric::Price;
+        let mut direction = None;
+        let mut target = None;
+        for arg in args {
+            match arg.to_lowercase().as_str() {
+                "price" => metric = AlertMetric::Price,
+                "mcap" | "marketcap" => metric = AlertMetric::MarketCap,
+                "above" | ">" => direction = Some(AlertDirection::Above),
+                "below" | "<" => direction = Some(AlertDirection::Below),
+                value => {
+                    let value = value.trim_start_matches('$').replace(',', "").parse::<f64>().ok()?;
+                    if target.is_some() || !value.is_finite() || value <= 0f64 {
+                        return None;
+                    }
+                    target = Some(value);
+                }
+            }
+        }
+        Some(Self {
+            metric,
+            direction,
+            target: target?,
+        })
+    }
+}
+
+// A one-shot threshold on a token's price or market cap
+#[derive(Debug, Clone)]
+pub struct PriceAlert {
+    pub id: u64,
+    pub chat_id: i64,
+    pub token_address: String,
+    pub symbol: String,
+    total_supply: String,
+    pub metric: AlertMetric,
+    pub direction: AlertDirection,
+    pub target: f64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AlertError {
+    #[error("Invalid token address")]
+    InvalidAddress,
+    #[error("You can set up to {0} alerts")]
+    LimitReached(usize),
+    #[error("The {0} is already {1} that")]
+    AlreadyCrossed(&'static str, &'static str),
+    #[error("Token not found or not an Unruggable memecoin")]
+    Lookup(anyhow::Error),
+}
+
+// Configuration for the price alert monitor
+#[derive(Clone)]
+pub struct PriceAlertConfig {
+    check_interval: Duration,
+    max_alerts_per_chat: usize,
+}
+
+impl PriceAlertConfig {
+    pub fn new() -> Self {
+        let check_interval = std::env::var("PRICE_ALERT_CHECK_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(60);
+
+        Self {
+            check_interval: Duration::from_secs(check_interval),
+            max_alerts_per_chat: std::env::var("PRICE_ALERT_MAX_PER_CHAT")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(10),
+        }
+    }
+}
+
+// Per-chat price and market cap alerts, re-quoted periodically and dropped once they fire
+pub struct PriceAlerts {
+    config: PriceAlertConfig,
+    next_id: AtomicU64,
+    alerts: RwLock<HashMap<u64, PriceAlert>>,
+}
+
+impl PriceAlerts {
+    pub fn new(config: PriceAlertConfig) -> Self {
+        Self {
+            config,
+            next_id: AtomicU64::new(1),
+            alerts: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // Registers an alert and returns it along with the current value of the metric
+    pub async fn add(
+        &self,
+        chat_id: i64,
+        token_address: &str,
+        request: AlertRequest,
+    ) -> Result<(PriceAlert, f64), AlertError> {
+        let token_address = canonicalize_address(token_address).ok_or(AlertError::InvalidAddress)?;
+        if self.list(chat_id).await.len() >= self.config.max_alerts_per_chat {
+            return Err(AlertError::LimitReached(self.config.max_alerts_per_chat));
+        }
+
+        let (info, _) = aggregate_info(&token_address).await.map_err(AlertError::Lookup)?;
+        let current = match request.metric {
+            AlertMetric::Price => info.price.parse::<f64>().unwrap_or_default(),
+            AlertMetric::MarketCap => info.market_cap.parse::<f64>().unwrap_or_default(),
+        };
+        let direction = match request.direction {
+            Some(direction) if direction.is_crossed(current, request.target) => {
+                return Err(AlertError::AlreadyCrossed(request.metric.label(), direction.label()));
+            }
+            Some(direction) => direction,
+            None if request.target > current => AlertDirection::Above,
+            None => AlertDirection::Below,
+        };
+
+        let alert = PriceAlert {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            chat_id,
+            token_address,
+            symbol: info.symbol,
+            total_supply: info.total_supply,
+            metric: request.metric,
+            direction,
+            target: request.target,
+        };
+        self.alerts.write().await.insert(alert.id, alert.clone());
+        Ok((alert, current))
+    }
+
+    // Deletes one of the chat's alerts, returns false if it has no alert with that id
+    pub async fn remove(&self, chat_id: i64, id: u64) -> bool {
+        let mut alerts = self.alerts.write().await;
+        match alerts.get(&id) {
+            Some(alert) if alert.chat_id == chat_id => alerts.remove(&id).is_some(),
+            _ => false,
+        }
+    }
+
+    // Alerts of a chat, oldest first
+    pub async fn list(&self, chat_id: i64) -> Vec<PriceAlert> {
+        let mut alerts: Vec<PriceAlert> = self
+            .alerts
+            .read()
+            .await
+            .values()
+            .filter(|alert| alert.chat_id == chat_id)
+            .cloned()
+            .collect();
+        alerts.sort_by_key(|alert| alert.id);
+        alerts
+    }
+
+    // Drops every alert of a chat, returns how many were removed
+    pub async fn remove_chat(&self, chat_id: i64) -> usize {
+        let mut alerts = self.alerts.write().await;
+        let before = alerts.len();
+        alerts.retain(|_, alert| alert.chat_id != chat_id);
+        before - alerts.len()
+    }
+
+    pub async fn run(&self, tg_bot: Arc<TelegramBot>) {
+        loop {
+            tokio::time::sleep(self.config.check_interval).await;
+
+            // One quote per token however many alerts are set on it
+            let mut tokens: HashMap<String, (String, String)> = HashMap::new();
+            for alert in self.alerts.read().await.values() {
+                tokens
+                    .entry(alert.token_address.clone())
+                    .or_insert_with(|| (alert.symbol.clone(), alert.total_supply.clone()));
+            }
+
+            for (token_address, (symbol, total_supply)) in tokens {
+                let (price, market_cap) = match calculate_market_cap(&token_address, &total_supply, &symbol).await {
+                    Ok((price, market_cap)) => (
+                        price.parse::<f64>().unwrap_or_default(),
+                        market_cap.parse::<f64>().unwrap_or_default(),
+                    ),
+                    Err(e) => {
+                        eprintln!("Failed to re-quote {} for price alerts: {}", token_address, e);
+                        continue;
+                    }
+                };
+
+                let value_of = |metric: AlertMetric| match metric {
+                    AlertMetric::Price => price,
+                    AlertMetric::MarketCap => market_cap,
+                };
+                let crossed: Vec<(u64, i64)> = self
+                    .alerts
+                    .read()
+                    .await
+                    .values()
+                    .filter(|alert| alert.token_address == token_address)
+                    .filter(|alert| {
+                        let value = value_of(alert.metric);
+                        // A failed quote reads as zero, which must not trip "below" alerts
+                        value > 0f64 && alert.direction.is_crossed(value, alert.target)
+                    })
+                    .map(|alert| (alert.id, alert.chat_id))
+                    .collect();
+
+                let mut triggered = Vec::new();
+                for (id, chat_id) in crossed {
+                    // Alerts of stopped chats wait until they /start again
+                    if tg_bot.is_stopped(chat_id).await {
+                        continue;
+                    }
+                    if let Some(alert) = self.alerts.write().await.remove(&id) {
+                        triggered.push(alert);
+                    }
+                }
+
+                for alert in triggered {
+                    if let Err(e) = tg_bot.notify_price_alert(&alert, value_of(alert.metric)).await {
+                        eprintln!("Failed to send price alert {} to {}: {:?}", alert.id, alert.chat_id, e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_alert_request() {
+        assert_eq!(
+            AlertRequest::parse(&["above", "0.005"]),
+            Some(AlertRequest {
+                metric: AlertMetric::Price,
+                direction: Some(AlertDirection::Above),
+                target: 0.005,
+            })
+        );
+        assert_eq!(
+            AlertRequest::parse(&["mcap", "1,000,000"]),
+            Some(AlertRequest {
+                metric: AlertMetric::MarketCap,
+                direction: None,
+                target: 1_000_000f64,
+            })
+        );
+        assert!(AlertRequest::parse(&["mcap", "below"]).is_none());
+        assert!(AlertRequest::parse(&["above", "-1"]).is_none());
+        assert!(AlertRequest::parse(&["1", "2"]).is_none());
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
+
+use crate::notifier::DispatcherConfig;
+use crate::storage::{InstanceConfig, Storage};
+use crate::telegram::TelegramBot;
+use crate::utils::risk::RiskConfig;
+use crate::watcher::liquidity_guard::LiquidityGuardConfig;
+use crate::watcher::watchlist::WatchlistConfig;
+
+// Configuration for config drift detection between replicas
+#[derive(Clone)]
+pub struct DriftConfig {
+    instance_id: String,
+    interval: Duration,
+}
+
+impl DriftConfig {
+    pub fn new() -> Self {
+        Self {
+            // Render and most container platforms set HOSTNAME per replica
+            instance_id: std::env::var("INSTANCE_ID")
+                .or_else(|_| std::env::var("HOSTNAME"))
+                .unwrap_or_else(|_| format!("pid-{}", std::process::id())),
+            interval: Duration::from_secs(
+                std::env::var("CONFIG_DRIFT_INTERVAL_SECS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(300)
+                    .max(30),
+            ),
+        }
+    }
+}
+
+// Settings that decide which launches get alerted and where, as hashes per section so webhook
+// URLs and the like never reach the shared store
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectiveConfig {
+    sections: BTreeMap<String, String>,
+}
+
+impl EffectiveConfig {
+    pub fn collect(destinations: &[String], launch_alerts: bool) -> Self {
+        let mut destinations = destinations.to_vec();
+        destinations.sort();
+        Self::from_sections([
+            ("risk thresholds", format!("{:?}", RiskConfig::new())),
+            ("watchlist thresholds", format!("{:?}", WatchlistConfig::new())),
+            ("liquidity guard thresholds", format!("{:?}", LiquidityGuardConfig::new())),
+            ("notification retries", format!("{:?}", DispatcherConfig::new())),
+            ("destinations", destinations.join(",")),
+            ("telegram launch alerts", launch_alerts.to_string()),
+        ])
+    }
+
+    fn from_sections<const N: usize>(sections: [(&str, String); N]) -> Self {
+        Self {
+            sections: sections
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), short_hash(&value)))
+                .collect(),
+        }
+    }
+
+    pub fn hash(&self) -> String {
+        short_hash(&self.to_payload())
+    }
+
+    pub fn to_payload(&self) -> String {
+        serde_json::to_string(&self.sections).unwrap_or_default()
+    }
+
+    pub fn from_payload(payload: &str) -> Option<Self> {
+        serde_json::from_str(payload).ok().map(|sections| Self { sections })
+    }
+
+    // Sections set differently on the other replica, or missing on either side
+    pub fn diverging_sections(&self, other: &EffectiveConfig) -> Vec<String> {
+        let mut names: Vec<&String> = self.sections.keys().chain(other.sections.keys()).collect();
+        names.sort();
+        names.dedup();
+        names
+            .into_iter()
+            .filter(|name| self.sections.get(*name) != other.sections.get(*name))
+            .cloned()
+            .collect()
+    }
+}
+
+fn short_hash(value: &str) -> String {
+    hex::encode(Sha256::digest(value.as_bytes()))[..12].to_string()
+}
+
+// Reports this replica's effective configuration to the shared store and tells admins when
+// live replicas disagree. Only the first live replica by id alerts, so admins hear it once
+pub struct DriftJob {
+    config: DriftConfig,
+    storage: Arc<Storage>,
+    effective: EffectiveConfig,
+}
+
+impl DriftJob {
+    pub fn new(config: DriftConfig, storage: Arc<Storage>, effective: EffectiveConfig) -> Self {
+        Self {
+            config,
+            storage,
+            effective,
+        }
+    }
+
+    pub async fn run(&self, tg_bot: Arc<TelegramBot>) {
+        let hash = self.effective.hash();
+        let payload = self.effective.to_payload();
+        println!("Config fingerprint {} reported as {}", hash, self.config.instance_id);
+        // The drift admins were last told about, so a lasting drift is reported once
+        let mut reported: Option<String> = None;
+        loop {
+            if let Err(e) = self
+                .storage
+                .report_instance_config(&self.config.instance_id, &hash, &payload)
+                .await
+            {
+                eprintln!("Failed to report config fingerprint: {}", e);
+            }
+
+            // Replicas that missed two reports are considered stopped
+            let from = now().saturating_sub(2 * self.config.interval.as_secs() + 60);
+            match self.storage.instance_configs(from).await {
+                Ok(instances) => {
+                    let leader = instances.first().map(|instance| instance.instance_id.as_str());
+                    let drift = self.describe_drift(&instances);
+                    if drift != reported && leader == Some(self.config.instance_id.as_str()) {
+                        let message = match &drift {
+                            Some(drift) => format!("⚠️ <b>Config drift between replicas</b>\n\n{}", drift),
+                            None => "✅ Every replica runs the same configuration again.".to_string(),
+                        };
+                        tg_bot.notify_admins(&message).await;
+                    }
+                    reported = drift;
+                }
+                Err(e) => eprintln!("Failed to load replica configs: {}", e),
+            }
+
+            tokio::time::sleep(self.config.interval).await;
+        }
+    }
+
+    // One line per replica whose configuration differs from this one, None when all agree
+    fn describe_drift(&self, instances: &[InstanceConfig]) -> Option<String> {
+        let lines: Vec<String> = instances
+            .iter()
+            .filter(|instance| instance.instance_id != self.config.instance_id)
+            .filter_map(|instance| {
+                let sections = match EffectiveConfig::from_payload(&instance.payload) {
+                    Some(other) => self.effective.diverging_sections(&other),
+                    None => vec!["unreadable fingerprint".to_string()],
+                };
+                (!sections.is_empty()).then(|| {
+                    format!(
+                        "<code>{}</code> differs from <code>{}</code> in {}",
+                        instance.instance_id,
+                        self.config.instance_id,
+                        sections.join(", ")
+                    )
+                })
+            })
+            .collect();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StorageConfig;
+
+    fn config(risk: &str, destinations: &str) -> EffectiveConfig {
+        EffectiveConfig::from_sections([
+            ("risk thresholds", risk.to_string()),
+            ("destinations", destinations.to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_diverging_sections() {
+        let local = config("max_team_allocation_pct: 10.0", "discord,telegram");
+        assert_eq!(local.hash(), config("max_team_allocation_pct: 10.0", "discord,telegram").hash());
+
+        let other = config("max_team_allocation_pct: 15.0", "discord,telegram");
+        assert_ne!(local.hash(), other.hash());
+        assert_eq!(local.diverging_sections(&other), vec!["risk thresholds"]);
+
+        let imported = EffectiveConfig::from_payload(&other.to_payload()).unwrap();
+        assert_eq!(imported, other);
+        assert!(local.to_payload().contains("destinations"));
+        assert!(!local.to_payload().contains("discord"));
+    }
+
+    #[tokio::test]
+    async fn test_describe_drift() {
+        let storage = Arc::new(Storage::connect(StorageConfig::in_memory()).await.unwrap());
+        let local = config("10", "telegram");
+        let job = DriftJob::new(
+            DriftConfig {
+                instance_id: "a".to_string(),
+                interval: Duration::from_secs(300),
+            },
+            storage,
+            local.clone(),
+        );
+        let instance = |id: &str, effective: &EffectiveConfig| InstanceConfig {
+            instance_id: id.to_string(),
+            config_hash: effective.hash(),
+            payload: effective.to_payload(),
+            reported_at: 0,
+        };
+        let agreeing = [instance("a", &local), instance("b", &local)];
+        assert_eq!(job.describe_drift(&agreeing), None);
+
+        let drifting = [instance("a", &local), instance("b", &config("10", "telegram,discord"))];
+        assert_eq!(
+            job.describe_drift(&drifting).as_deref(),
+            Some("<code>b</code> differs from <code>a</code> in destinations")
+        );
+    }
+}
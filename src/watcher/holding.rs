@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use starknet_core::types::{EmittedEvent, Felt};
+use tokio::sync::RwLock;
+
+use crate::constant::network::NETWORK;
+use crate::storage::Storage;
+use crate::utils::address::{addresses_eq, to_canonical_hex};
+use crate::utils::call::{get_block_timestamp, get_wallet_transfer_events};
+
+// How far back a wallet's trades are looked up
+const HISTORY_BLOCKS: u64 = 300_000;
+// Most recent factory tokens sampled per wallet, each one costs block lookups
+const MAX_SAMPLED_TOKENS: usize = 10;
+// Fewer trades than this say nothing about a wallet
+const MIN_SAMPLED_TOKENS: usize = 2;
+// Median holding times splitting the styles
+const FLIPPER_MAX_SECS: u64 = 60 * 60;
+const HOLDER_MAX_SECS: u64 = 7 * 24 * 60 * 60;
+// A wallet's style barely moves, whales trading often aren't looked up on every trade
+const CACHE_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+// How long a wallet usually keeps factory tokens, from its first buy to its first sell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HoldingStyle {
+    Flipper,
+    Holder,
+    DiamondHands,
+}
+
+impl HoldingStyle {
+    // Median of the holding times, tokens still held count up to now
+    pub fn classify(mut held_secs: Vec<u64>) -> Option<Self> {
+        if held_secs.len() < MIN_SAMPLED_TOKENS {
+            return None;
+        }
+        held_secs.sort_unstable();
+        // Both indexes are the middle one for an odd count
+        let len = held_secs.len();
+        let median = (held_secs[(len - 1) / 2] + held_secs[len / 2]) / 2;
+        Some(if median < FLIPPER_MAX_SECS {
+            HoldingStyle::Flipper
+        } else if median < HOLDER_MAX_SECS {
+            HoldingStyle::Holder
+        } else {
+            HoldingStyle::DiamondHands
+        })
+    }
+
+    pub fn badge(&self) -> &'static str {
+        match self {
+            HoldingStyle::Flipper => "🔁 flipper",
+            HoldingStyle::Holder => "🤝 holder",
+            HoldingStyle::DiamondHands => "💎 diamond hands",
+        }
+    }
+}
+
+// First buy and sell block of each factory token a wallet traded on Ekubo
+#[derive(Debug, Default)]
+struct TokenTrades {
+    first_buy: Option<u64>,
+    first_sell: Option<u64>,
+}
+
+// Classifies whale wallets from their past trades of factory tokens
+pub struct HoldingClassifier {
+    storage: Arc<Storage>,
+    cache: RwLock<HashMap<String, (Instant, Option<HoldingStyle>)>>,
+}
+
+impl HoldingClassifier {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self {
+            storage,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // The wallet's style, None when it can't be told
+    pub async fn style(
+        &self,
+        account: &str,
+        latest_block: u64,
+        latest_timestamp: u64,
+    ) -> Option<HoldingStyle> {
+        if let Some((at, style)) = self.cache.read().await.get(account) {
+            if at.elapsed() < CACHE_TTL {
+                return *style;
+            }
+        }
+        let style = match self.held_secs(account, latest_block, latest_timestamp).await {
+            Ok(held_secs) => HoldingStyle::classify(held_secs),
+            Err(e) => {
+                eprintln!("Failed to classify wallet {}: {}", account, e);
+                // Not cached so the next trade tries again
+                return None;
+            }
+        };
+        self.cache
+            .write()
+            .await
+            .insert(account.to_string(), (Instant::now(), style));
+        style
+    }
+
+    async fn held_secs(
+        &self,
+        account: &str,
+        latest_block: u64,
+        latest_timestamp: u64,
+    ) -> Result<Vec<u64>, anyhow::Error> {
+        let wallet = Felt::from_hex(account)?;
+        let from_block = latest_block.saturating_sub(HISTORY_BLOCKS);
+
+        let incoming = get_wallet_transfer_events(&[wallet], false, from_block, latest_block).await?;
+        let outgoing = get_wallet_transfer_events(&[wallet], true, from_block, latest_block).await?;
+        let bought = first_trades(&incoming, &outgoing, &NETWORK.ekubo_core);
+
+        let mut held_secs = Vec::new();
+        for (token, first_buy, first_sell) in bought {
+            if held_secs.len() >= MAX_SAMPLED_TOKENS {
+                break;
+            }
+            if self.storage.memecoin_symbol(&token).await?.is_none() {
+                continue;
+            }
+            let bought_at = get_block_timestamp(first_buy).await?;
+            let sold_at = match first_sell {
+                Some(first_sell) => Some(get_block_timestamp(first_sell).await?),
+                None => None,
+            };
+            held_secs.push(holding_time(bought_at, sold_at, latest_timestamp));
+        }
+        Ok(held_secs)
+    }
+}
+
+// (token, first buy block, first sell block) of each token a wallet bought from `pool`, the most
+// recent buys first. Buys come from the pool, sells go to it
+fn first_trades(
+    incoming: &[EmittedEvent],
+    outgoing: &[EmittedEvent],
+    pool: &str,
+) -> Vec<(String, u64, Option<u64>)> {
+    let mut trades: HashMap<String, TokenTrades> = HashMap::new();
+    for (events, is_sell) in [(incoming, false), (outgoing, true)] {
+        for event in events {
+            let (block_number, counterparty) =
+                match (event.block_number, event.keys.get(if is_sell { 2 } else { 1 })) {
+                    (Some(block_number), Some(counterparty)) => (block_number, counterparty),
+                    _ => continue,
+                };
+            if !addresses_eq(&to_canonical_hex(counterparty), pool) {
+                continue;
+            }
+            let token = trades.entry(to_canonical_hex(&event.from_address)).or_default();
+            let first = if is_sell { &mut token.first_sell } else { &mut token.first_buy };
+            *first = Some(first.map_or(block_number, |first| first.min(block_number)));
+        }
+    }
+
+    let mut bought: Vec<(String, u64, Option<u64>)> = trades
+        .into_iter()
+        .filter_map(|(token, trades)| {
+            let first_buy = trades.first_buy?;
+            // A sell before the first buy in range is from an older position
+            let first_sell = trades.first_sell.filter(|sell| *sell >= first_buy);
+            Some((token, first_buy, first_sell))
+        })
+        .collect();
+    bought.sort_by_key(|(_, first_buy, _)| std::cmp::Reverse(*first_buy));
+    bought
+}
+
+// Seconds from the first buy to the first sell, a token still held counts up to `now`
+fn holding_time(bought_at: u64, sold_at: Option<u64>, now: u64) -> u64 {
+    sold_at.unwrap_or(now).saturating_sub(bought_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_by_median_holding_time() {
+        assert_eq!(HoldingStyle::classify(vec![60]), None);
+        // One long hold doesn't make a flipper a holder
+        assert_eq!(
+            HoldingStyle::classify(vec![60, 120, 30 * 24 * 60 * 60]),
+            Some(HoldingStyle::Flipper)
+        );
+        assert_eq!(
+            HoldingStyle::classify(vec![30 * 60, 3 * 60 * 60]),
+            Some(HoldingStyle::Holder)
+        );
+        assert_eq!(
+            HoldingStyle::classify(vec![60, 10 * 24 * 60 * 60, 8 * 24 * 60 * 60]),
+            Some(HoldingStyle::DiamondHands)
+        );
+    }
+
+    #[test]
+    fn test_held_seconds() {
+        let (pool, wallet, other) = (Felt::from(0x5u64), Felt::from(0x7u64), Felt::from(0x9u64));
+        let transfer = |token: u64, from: Felt, to: Felt, block_number: u64| EmittedEvent {
+            from_address: Felt::from(token),
+            keys: vec![Felt::ONE, from, to],
+            data: vec![Felt::ONE, Felt::ZERO],
+            block_hash: None,
+            block_number: Some(block_number),
+            transaction_hash: Felt::ZERO,
+        };
+        let incoming = [
+            transfer(0xa, pool, wallet, 100),
+            transfer(0xa, pool, wallet, 150),
+            transfer(0xb, pool, wallet, 300),
+            // Sent by another wallet, not a buy
+            transfer(0xc, other, wallet, 400),
+        ];
+        // 0xa was sold at 200, a sell of 0xb before its buy is from an older position
+        let outgoing = [
+            transfer(0xa, wallet, pool, 200),
+            transfer(0xa, wallet, pool, 250),
+            transfer(0xb, wallet, pool, 50),
+        ];
+        let trades = first_trades(&incoming, &outgoing, &to_canonical_hex(&pool));
+        assert_eq!(
+            trades,
+            vec![
+                (to_canonical_hex(&Felt::from(0xbu64)), 300, None),
+                (to_canonical_hex(&Felt::from(0xau64)), 100, Some(200)),
+            ]
+        );
+
+        assert_eq!(holding_time(1_000, Some(4_600), 9_000), 3_600);
+        assert_eq!(holding_time(1_000, None, 9_000), 8_000);
+        // Block timestamps running ahead of the clock don't wrap around
+        assert_eq!(holding_time(1_000, None, 900), 0);
+    }
+}
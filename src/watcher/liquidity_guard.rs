@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::RwLock;
+
+use super::watchlist::{WatchChange, Watchlist};
+use crate::telegram::TelegramBot;
+use crate::utils::address::addresses_eq;
+use crate::utils::call::{get_aggregate_call_data, AggregateError};
+use crate::utils::liquidity::{get_ekubo_liquidity_lock_position, get_position_liquidity};
+use crate::utils::types::ekubo::{EkuboLiquidityLockPosition, Liquidity};
+
+// Configuration for the liquidity guard of watched tokens
+#[derive(Debug, Clone)]
+pub struct LiquidityGuardConfig {
+    check_interval: Duration,
+    // Drop of the launch position's liquidity between two checks alerted on
+    drop_pct: f64,
+}
+
+impl LiquidityGuardConfig {
+    pub fn new() -> Self {
+        let check_interval = std::env::var("LIQUIDITY_GUARD_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30);
+
+        Self {
+            check_interval: Duration::from_secs(check_interval.max(10)),
+            drop_pct: std::env::var("LIQUIDITY_GUARD_DROP_PCT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(30f64),
+        }
+    }
+}
+
+// Last observed pool side of a watched token
+#[derive(Debug, Clone)]
+struct GuardedPool {
+    liquidity: Liquidity,
+    // The launch position's pool and bounds never change, only its owner does
+    position: EkuboLiquidityLockPosition,
+    // Liquidity of the launch position, which trades leave alone
+    position_liquidity: u128,
+    // None until the lock could be read
+    lock_owner: Option<String>,
+}
+
+impl GuardedPool {
+    // Sharp liquidity drops and lock handovers between two checks, the owner is only compared
+    // when both checks could read it
+    fn changes(&self, position_liquidity: u128, lock_owner: Option<&str>, drop_pct: f64) -> Vec<WatchChange> {
+        let mut changes = Vec::new();
+        let from = self.position_liquidity as f64;
+        let to = position_liquidity as f64;
+        if from > 0f64 && (from - to) * 100f64 / from >= drop_pct {
+            changes.push(WatchChange::LiquidityPulled {
+                from: self.position_liquidity.to_string(),
+                to: position_liquidity.to_string(),
+                drop_pct: (from - to) * 100f64 / from,
+            });
+        }
+        if let (Some(from), Some(to)) = (&self.lock_owner, lock_owner) {
+            if !addresses_eq(from, to) {
+                changes.push(WatchChange::LockOwner {
+                    from: from.clone(),
+                    to: to.to_string(),
+                });
+            }
+        }
+        changes
+    }
+}
+
+// Checks the Ekubo side of watched tokens more often than the watchlist refreshes them, pulled
+// liquidity and a locked position changing hands are told to every subscribed chat right away
+pub struct LiquidityGuard {
+    config: LiquidityGuardConfig,
+    watchlist: Arc<Watchlist>,
+    pools: RwLock<HashMap<String, GuardedPool>>,
+}
+
+impl LiquidityGuard {
+    pub fn new(config: LiquidityGuardConfig, watchlist: Arc<Watchlist>) -> Self {
+        Self {
+            config,
+            watchlist,
+            pools: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn run(&self, tg_bot: Arc<TelegramBot>) {
+        loop {
+            tokio::time::sleep(self.config.check_interval).await;
+
+            let subscribed = self.watchlist.subscribers().await;
+            // Forget tokens nobody watches anymore, they start over if watched again
+            self.pools
+                .write()
+                .await
+                .retain(|address, _| subscribed.iter().any(|(watched, _, _)| watched == address));
+
+            for (token_address, symbol, chat_ids) in subscribed {
+                let changes = match self.check(&token_address).await {
+                    Ok(changes) => changes,
+                    Err(e) => {
+                        eprintln!("Failed to check the liquidity of {}: {}", token_address, e);
+                        continue;
+                    }
+                };
+                if changes.is_empty() {
+                    continue;
+                }
+                println!("Liquidity guard for {}: {:?}", token_address, changes);
+                if let Err(e) = tg_bot
+                    .notify_watchers(&chat_ids, &symbol, &token_address, &changes)
+                    .await
+                {
+                    eprintln!("Failed to notify watchers of {}: {:?}", token_address, e);
+                }
+            }
+        }
+    }
+
+    // Reads the pool side of a token and returns what changed since the last check
+    async fn check(&self, token_address: &str) -> Result<Vec<WatchChange>, AggregateError> {
+        let known = self.pools.read().await.get(token_address).cloned();
+        let (liquidity, position) = match &known {
+            Some(pool) => (pool.liquidity.clone(), pool.position.clone()),
+            None => {
+                let liquidity = get_aggregate_call_data(token_address).await?.liquidity;
+                let position = get_ekubo_liquidity_lock_position(&liquidity).await?;
+                (liquidity, position)
+            }
+        };
+        let position_liquidity = get_position_liquidity(&liquidity, &position).await?;
+        // The first check just read the lock, later ones read it again for its owner. Failing to
+        // doesn't hold back the liquidity check
+        let lock_owner = match &known {
+            None => Some(position.owner.clone()),
+            Some(_) => match get_ekubo_liquidity_lock_position(&liquidity).await {
+                Ok(lock) => Some(lock.owner),
+                Err(e) => {
+                    eprintln!("Failed to read the liquidity lock of {}: {}", token_address, e);
+                    None
+                }
+            },
+        };
+
+        let changes = known
+            .as_ref()
+            .map(|pool| pool.changes(position_liquidity, lock_owner.as_deref(), self.config.drop_pct))
+            .unwrap_or_default();
+        let lock_owner = lock_owner.or(known.and_then(|pool| pool.lock_owner));
+        self.pools.write().await.insert(
+            token_address.to_string(),
+            GuardedPool {
+                liquidity,
+                position,
+                position_liquidity,
+                lock_owner,
+            },
+        );
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::types::ekubo::{Bound, Bounds, PoolKey};
+
+    #[test]
+    fn test_pool_changes() {
+        let bound = Bound {
+            mag: "0".to_string(),
+            sign: "0".to_string(),
+        };
+        let pool = GuardedPool {
+            liquidity: Liquidity::default(),
+            position: EkuboLiquidityLockPosition {
+                unlock_time: 0,
+                owner: "0x1".to_string(),
+                pool_key: PoolKey {
+                    token0: "0x1".to_string(),
+                    token1: "0x2".to_string(),
+                    fee: "0".to_string(),
+                    tick_spacing: "0".to_string(),
+                    extension: "0x0".to_string(),
+                },
+                bounds: Bounds {
+                    lower: bound.clone(),
+                    upper: bound,
+                },
+            },
+            position_liquidity: 1000,
+            lock_owner: Some("0x1".to_string()),
+        };
+        // Trades leave the position's liquidity alone, a small change isn't news either
+        assert!(pool.changes(1000, Some("0x01"), 30f64).is_empty());
+        assert!(pool.changes(900, Some("0x01"), 30f64).is_empty());
+        assert!(matches!(
+            pool.changes(200, Some("0x1"), 30f64)[..],
+            [WatchChange::LiquidityPulled { drop_pct, .. }] if drop_pct == 80f64
+        ));
+        assert!(matches!(
+            pool.changes(1000, Some("0x2"), 30f64)[..],
+            [WatchChange::LockOwner { .. }]
+        ));
+        // An owner that couldn't be read isn't a handover
+        assert!(pool.changes(1000, None, 30f64).is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::RwLock;
+
+use crate::telegram::TelegramBot;
+use crate::utils::address::addresses_eq;
+use crate::utils::call::get_aggregate_call_data;
+use crate::utils::liquidity::get_ekubo_liquidity_lock_position;
+use crate::utils::types::common::RiskFlag;
+use crate::utils::types::ekubo::Liquidity;
+
+#[derive(Debug, Clone)]
+struct TrackedLock {
+    symbol: String,
+    liquidity: Liquidity,
+    owner: String,
+    tracked_since: Instant,
+    // Set once the holders were warned the lock runs out, so the warning isn't repeated
+    expiry_alerted: bool,
+}
+
+// Configuration for the lock owner watcher
+#[derive(Clone)]
+pub struct LockWatcherConfig {
+    check_interval: Duration,
+    track_duration: Duration,
+    expiry_warning_secs: u64,
+}
+
+impl LockWatcherConfig {
+    pub fn new() -> Self {
+        let check_interval = std::env::var("LOCK_CHECK_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(300);
+        let track_hours = std::env::var("LOCK_TRACK_HOURS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(72);
+        let expiry_warning_hours = std::env::var("LOCK_EXPIRY_WARNING_HOURS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(24);
+
+        Self {
+            check_interval: Duration::from_secs(check_interval),
+            track_duration: Duration::from_secs(track_hours * 60 * 60),
+            expiry_warning_secs: expiry_warning_hours * 60 * 60,
+        }
+    }
+}
+
+// Re-checks locked liquidity positions of launched tokens and alerts on owner changes
+pub struct LockWatcher {
+    config: LockWatcherConfig,
+    tracked: RwLock<HashMap<String, TrackedLock>>,
+}
+
+impl LockWatcher {
+    pub fn new(config: LockWatcherConfig) -> Self {
+        Self {
+            config,
+            tracked: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // Records the lock owner at launch so later changes can be detected
+    pub async fn track(&self, token_address: &str) -> Result<(), anyhow::Error> {
+        let memecoin = get_aggregate_call_data(token_address).await?;
+        let position = get_ekubo_liquidity_lock_position(&memecoin.liquidity).await?;
+
+        let mut tracked = self.tracked.write().await;
+        tracked.insert(
+            token_address.to_string(),
+            TrackedLock {
+                symbol: memecoin.symbol,
+                liquidity: memecoin.liquidity,
+                owner: position.owner,
+                tracked_since: Instant::now(),
+                expiry_alerted: false,
+            },
+        );
+        Ok(())
+    }
+
+    pub async fn run(&self, tg_bot: Arc<TelegramBot>) {
+        loop {
+            tokio::time::sleep(self.config.check_interval).await;
+
+            // Stop tracking tokens past the watch window
+            self.tracked
+                .write()
+                .await
+                .retain(|_, lock| lock.tracked_since.elapsed() < self.config.track_duration);
+
+            let snapshot: Vec<(String, TrackedLock)> = self
+                .tracked
+                .read()
+                .await
+                .iter()
+                .map(|(address, lock)| (address.clone(), lock.clone()))
+                .collect();
+
+            for (token_address, lock) in snapshot {
+                let position = match get_ekubo_liquidity_lock_position(&lock.liquidity).await {
+                    Ok(position) => position,
+                    Err(e) => {
+                        eprintln!("Failed to re-check lock for {}: {}", token_address, e);
+                        continue;
+                    }
+                };
+
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default();
+                let expiring = lock_expiring(position.unlock_time, now, self.config.expiry_warning_secs);
+                if let (Some(flag), false) = (expiring, lock.expiry_alerted) {
+                    println!("Lock of {} unlocks at {}", token_address, position.unlock_time);
+                    if let Err(e) = tg_bot
+                        .broadcast_lock_expiring(&lock.symbol, &token_address, &flag)
+                        .await
+                    {
+                        eprintln!("Failed to broadcast lock expiry: {:?}", e);
+                    }
+                    if let Some(tracked) = self.tracked.write().await.get_mut(&token_address) {
+                        tracked.expiry_alerted = true;
+                    }
+                }
+
+                if addresses_eq(&position.owner, &lock.owner) {
+                    continue;
+                }
+
+                println!(
+                    "Lock owner changed for {}: {} -> {}",
+                    token_address, lock.owner, position.owner
+                );
+                if let Err(e) = tg_bot
+                    .broadcast_lock_owner_change(
+                        &lock.symbol,
+                        &token_address,
+                        &lock.owner,
+                        &position.owner,
+                    )
+                    .await
+                {
+                    eprintln!("Failed to broadcast lock owner change: {:?}", e);
+                }
+
+                if let Some(tracked) = self.tracked.write().await.get_mut(&token_address) {
+                    tracked.owner = position.owner;
+                }
+            }
+        }
+    }
+}
+
+// Flags a lock whose liquidity unlocks within the warning window, an unlock past it is no news yet
+fn lock_expiring(unlock_time: u64, now: u64, warning_secs: u64) -> Option<RiskFlag> {
+    (unlock_time < now.saturating_add(warning_secs)).then_some(RiskFlag::LockExpiring { unlock_time })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant::constants::LIQUIDITY_LOCK_FOREVER_TIMESTAMP;
+
+    #[test]
+    fn test_lock_expiring_within_warning_window() {
+        let now = 1_700_000_000;
+        let day = 86_400;
+
+        assert_eq!(
+            lock_expiring(now + 3600, now, day),
+            Some(RiskFlag::LockExpiring { unlock_time: now + 3600 })
+        );
+        assert!(lock_expiring(now - 1, now, day).is_some());
+        assert_eq!(lock_expiring(now + 2 * day, now, day), None);
+        assert_eq!(lock_expiring(LIQUIDITY_LOCK_FOREVER_TIMESTAMP, now, day), None);
+    }
+}
@@ -0,0 +1,10 @@
+pub mod alerts;
+pub mod drift;
+pub mod holding;
+pub mod liquidity_guard;
+pub mod lock;
+pub mod papertrade;
+pub mod price_history;
+pub mod report;
+pub mod wallets;
+pub mod watchlist;
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::storage::{PaperPosition, Storage, StorageError};
+use crate::utils::address::canonicalize_address;
+use crate::utils::call::get_aggregate_call_data;
+use crate::utils::price::PRICE_ORACLE;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PaperTradeError {
+    #[error("Invalid token address")]
+    InvalidAddress,
+    #[error("Amount must be between $1 and ${0}")]
+    InvalidAmount(f64),
+    #[error("You can hold up to {0} paper positions, close one with /papertrade close <id>")]
+    LimitReached(usize),
+    #[error("You have no open paper position with that id")]
+    UnknownPosition,
+    #[error("Token not found or not a launched Unruggable memecoin")]
+    NotTradable,
+    #[error("Failed to quote the token, try again later")]
+    Quote(anyhow::Error),
+    #[error("The token has no price yet, try again once it trades")]
+    Unpriced,
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+}
+
+// Configuration for paper trading
+#[derive(Clone)]
+pub struct PaperTradeConfig {
+    valuation_interval: Duration,
+    max_positions_per_chat: usize,
+    max_usd_amount: f64,
+}
+
+impl PaperTradeConfig {
+    pub fn new() -> Self {
+        let valuation_interval = std::env::var("PAPERTRADE_VALUATION_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(300);
+
+        Self {
+            valuation_interval: Duration::from_secs(valuation_interval),
+            max_positions_per_chat: std::env::var("PAPERTRADE_MAX_POSITIONS")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(20),
+            max_usd_amount: std::env::var("PAPERTRADE_MAX_USD")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(100_000f64),
+        }
+    }
+}
+
+// Value of a position at `price`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Valuation {
+    pub value: f64,
+    pub pnl: f64,
+    pub pnl_pct: f64,
+}
+
+// None for positions opened at a zero quote, their quantity and PnL mean nothing
+pub fn value_at(position: &PaperPosition, price: f64) -> Option<Valuation> {
+    if !has_entry_price(position) {
+        return None;
+    }
+    let value = position.quantity * price;
+    let pnl = value - position.usd_amount;
+    Some(Valuation {
+        value,
+        pnl,
+        pnl_pct: pnl * 100f64 / position.usd_amount,
+    })
+}
+
+pub fn has_entry_price(position: &PaperPosition) -> bool {
+    position.entry_price > 0f64 && position.quantity.is_finite()
+}
+
+// A position with its current value, None when it could not be priced at all
+pub struct ValuedPosition {
+    pub position: PaperPosition,
+    pub valuation: Option<Valuation>,
+    // Whether the price is the job's last one rather than a fresh quote
+    pub stale: bool,
+}
+
+pub struct Portfolio {
+    pub positions: Vec<ValuedPosition>,
+    pub realized_pnl: f64,
+}
+
+// Simulated positions per chat, re-quoted by the valuation job
+pub struct PaperTrading {
+    config: PaperTradeConfig,
+    storage: Arc<Storage>,
+}
+
+impl PaperTrading {
+    pub fn new(config: PaperTradeConfig, storage: Arc<Storage>) -> Self {
+        Self { config, storage }
+    }
+
+    pub async fn buy(
+        &self,
+        chat_id: i64,
+        token_address: &str,
+        usd_amount: f64,
+    ) -> Result<PaperPosition, PaperTradeError> {
+        let token_address = canonicalize_address(token_address).ok_or(PaperTradeError::InvalidAddress)?;
+        if !usd_amount.is_finite() || usd_amount < 1f64 || usd_amount > self.config.max_usd_amount {
+            return Err(PaperTradeError::InvalidAmount(self.config.max_usd_amount));
+        }
+        if self.storage.paper_positions(chat_id, false).await?.len() >= self.config.max_positions_per_chat {
+            return Err(PaperTradeError::LimitReached(self.config.max_positions_per_chat));
+        }
+
+        let memecoin = match get_aggregate_call_data(&token_address).await {
+            Ok(memecoin) if memecoin.is_launched => memecoin,
+            _ => return Err(PaperTradeError::NotTradable),
+        };
+        let price = PRICE_ORACLE
+            .usd_price_per_unit(&token_address, &memecoin.symbol)
+            .await
+            .map_err(PaperTradeError::Quote)?;
+        // The quantity is the amount over the price, a token without one can't be bought
+        if !(price.is_finite() && price > 0f64) {
+            return Err(PaperTradeError::Unpriced);
+        }
+        Ok(self
+            .storage
+            .open_paper_position(chat_id, &token_address, &memecoin.symbol, usd_amount, price)
+            .await?)
+    }
+
+    // Sells a position at the current quote
+    pub async fn close(
+        &self,
+        chat_id: i64,
+        id: i64,
+    ) -> Result<(PaperPosition, Option<Valuation>), PaperTradeError> {
+        let position = self
+            .storage
+            .paper_position(chat_id, id)
+            .await?
+            .ok_or(PaperTradeError::UnknownPosition)?;
+        let price = PRICE_ORACLE
+            .usd_price_per_unit(&position.token_address, &position.symbol)
+            .await
+            .map_err(PaperTradeError::Quote)?;
+        let position = self
+            .storage
+            .close_paper_position(chat_id, id, price)
+            .await?
+            .ok_or(PaperTradeError::UnknownPosition)?;
+        let valuation = value_at(&position, price);
+        Ok((position, valuation))
+    }
+
+    // Open positions re-quoted, one quote per token, along with the PnL of closed ones
+    pub async fn portfolio(&self, chat_id: i64) -> Result<Portfolio, PaperTradeError> {
+        let mut realized_pnl = 0f64;
+        let mut open = Vec::new();
+        for position in self.storage.paper_positions(chat_id, true).await? {
+            match position.exit_price {
+                Some(exit_price) => {
+                    realized_pnl += value_at(&position, exit_price).map_or(0f64, |valuation| valuation.pnl)
+                }
+                None => open.push(position),
+            }
+        }
+
+        let mut prices: HashMap<String, Option<f64>> = HashMap::new();
+        let mut positions = Vec::with_capacity(open.len());
+        for position in open {
+            if !prices.contains_key(&position.token_address) {
+                let price = PRICE_ORACLE
+                    .usd_price_per_unit(&position.token_address, &position.symbol)
+                    .await
+                    .map_err(|e| eprintln!("Failed to re-quote paper position {}: {}", position.id, e))
+                    .ok();
+                prices.insert(position.token_address.clone(), price);
+            }
+            let (price, stale) = match prices[&position.token_address] {
+                Some(price) => (Some(price), false),
+                None => (position.last_price, true),
+            };
+            positions.push(ValuedPosition {
+                valuation: price.and_then(|price| value_at(&position, price)),
+                position,
+                stale,
+            });
+        }
+        Ok(Portfolio {
+            positions,
+            realized_pnl,
+        })
+    }
+
+    // Re-quotes every paper traded token so portfolios have a recent price to fall back on
+    pub async fn run(&self) {
+        loop {
+            tokio::time::sleep(self.config.valuation_interval).await;
+
+            let tokens = match self.storage.paper_traded_tokens().await {
+                Ok(tokens) => tokens,
+                Err(e) => {
+                    eprintln!("Failed to load paper traded tokens: {}", e);
+                    continue;
+                }
+            };
+            for (token_address, symbol) in tokens {
+                match PRICE_ORACLE.usd_price_per_unit(&token_address, &symbol).await {
+                    Ok(price) => {
+                        if let Err(e) = self.storage.record_paper_valuation(&token_address, price).await {
+                            eprintln!("Failed to record valuation of {}: {}", token_address, e);
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to value paper positions in {}: {}", token_address, e),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_at() {
+        let position = PaperPosition {
+            id: 1,
+            chat_id: 1,
+            token_address: "0x1".to_string(),
+            symbol: "MEME".to_string(),
+            usd_amount: 100f64,
+            entry_price: 0.000_002,
+            quantity: 100f64 / 0.000_002,
+            opened_at: 0,
+            last_price: None,
+            valued_at: None,
+            exit_price: None,
+        };
+        let up = value_at(&position, 0.000_003).unwrap();
+        assert_eq!((up.value.round(), up.pnl.round(), up.pnl_pct.round()), (150f64, 50f64, 50f64));
+        assert_eq!(value_at(&position, 0.000_001).unwrap().pnl_pct.round(), -50f64);
+
+        // Opened at a zero quote, the quantity is infinite and there's no PnL to show
+        let unpriced = PaperPosition {
+            entry_price: 0f64,
+            quantity: 100f64 / 0f64,
+            ..position
+        };
+        assert!(!has_entry_price(&unpriced));
+        assert_eq!(value_at(&unpriced, 0.000_003), None);
+    }
+}
@@ -0,0 +1,195 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::storage::Storage;
+use crate::utils::chart::{render_sparkline, ChartError};
+use crate::utils::info_aggregator::count_holders;
+use crate::utils::price::PRICE_ORACLE;
+use crate::utils::risk::HOLDER_VELOCITY_WINDOW_SECS;
+
+// Fewer quotes than this make a chart that says nothing
+const MIN_CHART_POINTS: usize = 3;
+
+// Configuration for the price history collector
+#[derive(Clone)]
+pub struct PriceHistoryConfig {
+    interval: Duration,
+    // Tokens launched within this window are quoted, and charts cover the same span
+    window_secs: u64,
+    retention_secs: u64,
+}
+
+impl PriceHistoryConfig {
+    pub fn new() -> Self {
+        let interval = std::env::var("PRICE_HISTORY_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(300);
+        let window_hours = std::env::var("PRICE_HISTORY_WINDOW_HOURS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(24);
+        let retention_days = std::env::var("PRICE_HISTORY_RETENTION_DAYS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(7);
+
+        Self {
+            interval: Duration::from_secs(interval.max(30)),
+            window_secs: window_hours.max(1) * 60 * 60,
+            retention_secs: retention_days.max(1) * 24 * 60 * 60,
+        }
+    }
+}
+
+// A rendered chart with the change it shows
+pub struct PriceChart {
+    pub png: Vec<u8>,
+    pub change_pct: f64,
+    pub span_secs: u64,
+}
+
+impl PriceChart {
+    pub fn caption(&self, symbol: &str) -> String {
+        let hours = self.span_secs as f64 / 3600f64;
+        let span = if hours < 1f64 {
+            format!("{}m", (self.span_secs / 60).max(1))
+        } else {
+            format!("{:.0}h", hours)
+        };
+        format!(
+            "{} <b>${}</b> {}: {:+.1}%",
+            if self.change_pct >= 0f64 { "📈" } else { "📉" },
+            symbol,
+            span,
+            self.change_pct
+        )
+    }
+}
+
+// Quotes recent launches on an interval so alerts and /sniQ replies can show where a token went.
+// Holder counts are sampled alongside while a launch's holder velocity is being measured
+pub struct PriceHistory {
+    config: PriceHistoryConfig,
+    storage: Arc<Storage>,
+}
+
+impl PriceHistory {
+    pub fn new(config: PriceHistoryConfig, storage: Arc<Storage>) -> Self {
+        Self { config, storage }
+    }
+
+    // Chart of the token's collected prices, None while there are too few of them
+    pub async fn chart(&self, token_address: &str) -> Option<PriceChart> {
+        let from = now().saturating_sub(self.config.window_secs);
+        let history = match self.storage.price_history(token_address, from).await {
+            Ok(history) => history,
+            Err(e) => {
+                eprintln!("Failed to load price history of {}: {}", token_address, e);
+                return None;
+            }
+        };
+        if history.len() < MIN_CHART_POINTS {
+            return None;
+        }
+
+        match render_sparkline(&history) {
+            Ok(png) => {
+                let (first_time, first_price) = history[0];
+                let (last_time, last_price) = history[history.len() - 1];
+                Some(PriceChart {
+                    png,
+                    change_pct: (last_price - first_price) * 100f64 / first_price,
+                    span_secs: last_time - first_time,
+                })
+            }
+            Err(ChartError::NotEnoughData) => None,
+            Err(e) => {
+                eprintln!("Failed to render chart of {}: {}", token_address, e);
+                None
+            }
+        }
+    }
+
+    async fn sample_holders(&self) {
+        let from = now().saturating_sub(HOLDER_VELOCITY_WINDOW_SECS);
+        let tokens = match self.storage.launched_since(from).await {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                eprintln!("Failed to load fresh launches for holder history: {}", e);
+                return;
+            }
+        };
+        for (token_address, _) in tokens {
+            match count_holders(&token_address).await {
+                Ok(holders) => {
+                    if let Err(e) = self.storage.record_holders(&token_address, holders as u64).await {
+                        eprintln!("Failed to record holders of {}: {}", token_address, e);
+                    }
+                }
+                Err(e) => eprintln!("Failed to count holders of {}: {}", token_address, e),
+            }
+        }
+    }
+
+    pub async fn run(&self) {
+        loop {
+            let from = now().saturating_sub(self.config.window_secs);
+            match self.storage.launched_since(from).await {
+                Ok(tokens) => {
+                    for (token_address, symbol) in tokens {
+                        match PRICE_ORACLE.usd_price_per_unit(&token_address, &symbol).await {
+                            Ok(price) => {
+                                if let Err(e) = self.storage.record_price(&token_address, price).await {
+                                    eprintln!("Failed to record price of {}: {}", token_address, e);
+                                }
+                            }
+                            Err(e) => {
+                                eprintln!("Failed to quote {} for its price history: {}", token_address, e)
+                            }
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Failed to load recent launches for price history: {}", e),
+            }
+            self.sample_holders().await;
+
+            let before = now().saturating_sub(self.config.retention_secs);
+            if let Err(e) = self.storage.prune_price_history(before).await {
+                eprintln!("Failed to prune price history: {}", e);
+            }
+            if let Err(e) = self.storage.prune_holder_history(before).await {
+                eprintln!("Failed to prune holder history: {}", e);
+            }
+            tokio::time::sleep(self.config.interval).await;
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chart_caption() {
+        let chart = PriceChart {
+            png: Vec::new(),
+            change_pct: 12.34,
+            span_secs: 6 * 3600,
+        };
+        assert_eq!(chart.caption("MEME"), "📈 <b>$MEME</b> 6h: +12.3%");
+        let chart = PriceChart {
+            png: Vec::new(),
+            change_pct: -40f64,
+            span_secs: 600,
+        };
+        assert_eq!(chart.caption("MEME"), "📉 <b>$MEME</b> 10m: -40.0%");
+    }
+}
@@ -0,0 +1,123 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::storage::report::{compile_weekly_report, week_start, WEEK_SECS};
+use crate::storage::Storage;
+use crate::telegram::TelegramBot;
+use crate::utils::market_cap::calculate_market_cap;
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+// Launches get their next day price captured during this many hours after the day is over
+const SNAPSHOT_WINDOW_SECS: u64 = 6 * 60 * 60;
+
+// Configuration for the weekly ecosystem report
+#[derive(Clone)]
+pub struct ReportConfig {
+    // Telegram chats and channels the report is posted to, it is only served by the API when empty
+    chat_ids: Vec<i64>,
+}
+
+impl ReportConfig {
+    pub fn new() -> Self {
+        Self {
+            chat_ids: std::env::var("WEEKLY_REPORT_CHAT_IDS")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|id| id.trim().parse::<i64>().ok())
+                .collect(),
+        }
+    }
+}
+
+// Captures next day prices of launches and posts the weekly report once the week's are in
+pub struct ReportJob {
+    config: ReportConfig,
+    storage: Arc<Storage>,
+}
+
+impl ReportJob {
+    pub fn new(config: ReportConfig, storage: Arc<Storage>) -> Self {
+        Self { config, storage }
+    }
+
+    pub async fn run(&self, tg_bot: Arc<TelegramBot>) {
+        loop {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            self.capture_snapshots(now).await;
+            // The report isn't marked posted, so it goes out once the load breaker resumes
+            if tg_bot.notifications_paused() {
+                continue;
+            }
+            if let Err(e) = self.post_report(now, &tg_bot).await {
+                eprintln!("Failed to post weekly report: {}", e);
+            }
+        }
+    }
+
+    async fn capture_snapshots(&self, now: u64) {
+        let from = now.saturating_sub(DAY_SECS + SNAPSHOT_WINDOW_SECS);
+        let launches = match self.storage.launches_awaiting_snapshot(from, now - DAY_SECS).await {
+            Ok(launches) => launches,
+            Err(e) => {
+                eprintln!("Failed to load launches awaiting a snapshot: {}", e);
+                return;
+            }
+        };
+        for launch in launches {
+            // Launches recorded without metrics have nothing to compare against
+            let info = match launch.coin_info {
+                Some(info) => info,
+                None => continue,
+            };
+            match calculate_market_cap(&launch.token_address, &info.total_supply, &info.symbol).await {
+                Ok((price, _)) => {
+                    let price = price.parse::<f64>().unwrap_or_default();
+                    if let Err(e) = self.storage.record_snapshot(&launch.token_address, price).await {
+                        eprintln!("Failed to record snapshot of {}: {}", launch.token_address, e);
+                    }
+                }
+                Err(e) => eprintln!("Failed to re-quote {} for its snapshot: {}", launch.token_address, e),
+            }
+        }
+    }
+
+    // Last week's report goes out once its final launches had their day and snapshot window
+    async fn post_report(&self, now: u64, tg_bot: &TelegramBot) -> Result<(), anyhow::Error> {
+        if self.config.chat_ids.is_empty() {
+            return Ok(());
+        }
+        let current_week = week_start(now);
+        if now < current_week + DAY_SECS + SNAPSHOT_WINDOW_SECS {
+            return Ok(());
+        }
+        let last_week = current_week - WEEK_SECS;
+        if self.storage.report_posted(last_week).await? {
+            return Ok(());
+        }
+
+        let report = compile_weekly_report(&self.storage, last_week).await?;
+        // Claimed before sending, so a concurrent tick or instance doesn't post it as well
+        if !self.storage.claim_report(last_week).await? {
+            return Ok(());
+        }
+        println!("Posting weekly report: {} launches", report.launch_count);
+        let mut posted = false;
+        for chat_id in &self.config.chat_ids {
+            match tg_bot.send_weekly_report(*chat_id, &report).await {
+                Ok(()) => posted = true,
+                Err(e) => eprintln!("Failed to post weekly report to {}: {:?}", chat_id, e),
+            }
+        }
+
+        // Only a report that reached a chat counts as posted, otherwise the next tick retries it
+        if !posted {
+            self.storage.release_report(last_week).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use starknet_core::types::Felt;
+use tokio::sync::RwLock;
+
+use crate::storage::{Storage, StorageError};
+use crate::utils::address::canonicalize_address;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FollowError {
+    #[error("Invalid wallet address")]
+    InvalidAddress,
+    #[error("You can follow up to {0} wallets")]
+    LimitReached(usize),
+    #[error("Failed to save the wallet, try again later")]
+    Storage(#[from] StorageError),
+}
+
+// Configuration for wallet follows
+#[derive(Clone)]
+pub struct WalletTrackerConfig {
+    max_wallets_per_chat: usize,
+}
+
+impl WalletTrackerConfig {
+    pub fn new() -> Self {
+        Self {
+            max_wallets_per_chat: std::env::var("WALLET_FOLLOW_MAX")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(10),
+        }
+    }
+}
+
+// Wallets followed with /follow. Follows are stored, the index of chats per wallet is kept in
+// memory so every indexed transfer doesn't hit the database
+pub struct WalletTracker {
+    config: WalletTrackerConfig,
+    storage: Arc<Storage>,
+    followers: RwLock<HashMap<String, HashSet<i64>>>,
+}
+
+impl WalletTracker {
+    pub fn new(config: WalletTrackerConfig, storage: Arc<Storage>) -> Self {
+        Self {
+            config,
+            storage,
+            followers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // Rebuilds the index from storage, returns how many wallets are followed
+    pub async fn load(&self) -> Result<usize, StorageError> {
+        let mut followers: HashMap<String, HashSet<i64>> = HashMap::new();
+        for (wallet, chat_id) in self.storage.wallet_follows().await? {
+            followers.entry(wallet).or_default().insert(chat_id);
+        }
+        let count = followers.len();
+        *self.followers.write().await = followers;
+        Ok(count)
+    }
+
+    // Follows a wallet for a chat and returns its canonical address
+    pub async fn follow(&self, chat_id: i64, wallet: &str) -> Result<String, FollowError> {
+        let wallet = canonicalize_address(wallet).ok_or(FollowError::InvalidAddress)?;
+        let followed = self.followed(chat_id).await;
+        if followed.contains(&wallet) {
+            return Ok(wallet);
+        }
+        if followed.len() >= self.config.max_wallets_per_chat {
+            return Err(FollowError::LimitReached(self.config.max_wallets_per_chat));
+        }
+
+        self.storage.follow_wallet(chat_id, &wallet).await?;
+        self.followers
+            .write()
+            .await
+            .entry(wallet.clone())
+            .or_default()
+            .insert(chat_id);
+        Ok(wallet)
+    }
+
+    // Returns false if the chat wasn't following the wallet
+    pub async fn unfollow(&self, chat_id: i64, wallet: &str) -> bool {
+        let wallet = match canonicalize_address(wallet) {
+            Some(wallet) => wallet,
+            None => return false,
+        };
+        if let Err(e) = self.storage.unfollow_wallet(chat_id, &wallet).await {
+            eprintln!("Failed to unfollow wallet {} for {}: {}", wallet, chat_id, e);
+        }
+
+        let mut followers = self.followers.write().await;
+        let removed = match followers.get_mut(&wallet) {
+            Some(chats) => chats.remove(&chat_id),
+            None => false,
+        };
+        if followers.get(&wallet).is_some_and(|chats| chats.is_empty()) {
+            followers.remove(&wallet);
+        }
+        removed
+    }
+
+    // Drops every wallet a chat follows, returns how many there were
+    pub async fn remove_chat(&self, chat_id: i64) -> usize {
+        if let Err(e) = self.storage.remove_wallet_follows(chat_id).await {
+            eprintln!("Failed to remove followed wallets of {}: {}", chat_id, e);
+        }
+        let mut followers = self.followers.write().await;
+        let mut removed = 0;
+        followers.retain(|_, chats| {
+            if chats.remove(&chat_id) {
+                removed += 1;
+            }
+            !chats.is_empty()
+        });
+        removed
+    }
+
+    pub async fn followed(&self, chat_id: i64) -> Vec<String> {
+        let mut wallets: Vec<String> = self
+            .followers
+            .read()
+            .await
+            .iter()
+            .filter(|(_, chats)| chats.contains(&chat_id))
+            .map(|(wallet, _)| wallet.clone())
+            .collect();
+        wallets.sort();
+        wallets
+    }
+
+    pub async fn followers(&self, wallet: &str) -> Vec<i64> {
+        self.followers
+            .read()
+            .await
+            .get(wallet)
+            .map(|chats| chats.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    // Every followed wallet, as the transfer stream filters on them
+    pub async fn wallets(&self) -> Vec<Felt> {
+        self.followers
+            .read()
+            .await
+            .keys()
+            .filter_map(|wallet| Felt::from_hex(wallet).ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StorageConfig;
+
+    #[tokio::test]
+    async fn test_follow_index_survives_restart() {
+        let storage = Arc::new(Storage::connect(StorageConfig::in_memory()).await.unwrap());
+        let config = WalletTrackerConfig { max_wallets_per_chat: 1 };
+        let tracker = WalletTracker::new(config.clone(), Arc::clone(&storage));
+        let wallet = tracker.follow(1, "0x0ABC").await.unwrap();
+        assert_eq!(tracker.follow(1, "0xabc").await.unwrap(), wallet);
+        assert!(matches!(tracker.follow(1, "0xdef").await, Err(FollowError::LimitReached(1))));
+        assert!(matches!(tracker.follow(1, "wallet").await, Err(FollowError::InvalidAddress)));
+        tracker.follow(2, "0xabc").await.unwrap();
+
+        let restarted = WalletTracker::new(config, storage);
+        assert_eq!(restarted.load().await.unwrap(), 1);
+        let mut followers = restarted.followers(&wallet).await;
+        followers.sort();
+        assert_eq!(followers, vec![1, 2]);
+        assert!(restarted.unfollow(1, &wallet).await);
+        assert!(!restarted.unfollow(1, &wallet).await);
+        assert_eq!(restarted.remove_chat(2).await, 1);
+        assert!(restarted.wallets().await.is_empty());
+    }
+}
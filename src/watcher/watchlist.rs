@@ -0,0 +1,799 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::future::join_all;
+use starknet_core::types::EmittedEvent;
+use tokio::sync::RwLock;
+
+use super::holding::{HoldingClassifier, HoldingStyle};
+use crate::constant::network::NETWORK;
+use crate::storage::Storage;
+use crate::telegram::TelegramBot;
+use crate::utils::address::{addresses_eq, canonicalize_address, to_canonical_hex};
+use crate::utils::call::{get_latest_block, get_ownership_events, get_transfer_events, parse_u256_from_felts};
+use crate::utils::event_parser::OwnershipTransferredEvent;
+use crate::utils::info_aggregator::{aggregate_info, aggregate_info_with_refresh};
+use crate::utils::types::common::{MemecoinInfo, TokenCategoryResponse};
+
+// Holder counts worth celebrating with followers
+const HOLDER_MILESTONES: [usize; 3] = [100, 500, 1000];
+// Window a holder exodus is measured over
+const HOLDER_DROP_WINDOW_SECS: u64 = 3600;
+// Below this many holders a few wallets leaving is noise
+const HOLDER_DROP_MIN_PEAK: usize = 20;
+// Upper bound on blocks scanned for trades in one pass, older ones are skipped
+const MAX_TRADE_SCAN_BLOCKS: u64 = 1_000;
+// Whale wallets classified at once, each one costs event and block lookups
+const MAX_CONCURRENT_CLASSIFICATIONS: usize = 4;
+
+// Ownership handovers of a followed token, every one of them even when a few land between refreshes
+fn owner_changes(events: &[EmittedEvent]) -> Vec<WatchChange> {
+    events
+        .iter()
+        .filter_map(|event| {
+            match OwnershipTransferredEvent::from_starknet_event(&event.keys, &event.data) {
+                Ok(handover) => Some(WatchChange::Owner {
+                    from: to_canonical_hex(&handover.previous_owner),
+                    to: to_canonical_hex(&handover.new_owner),
+                }),
+                Err(e) => {
+                    eprintln!("Failed to decode ownership transfer of {:#x}: {}", event.from_address, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+// Last sign of life of a watched token, a price move with liquidity still in the pool
+#[derive(Debug, Clone, Copy)]
+struct Activity {
+    // Price at the last refresh, None until the first one
+    price: Option<f64>,
+    active_at: u64,
+}
+
+// Last observed metrics of a watched token
+#[derive(Debug, Clone)]
+struct TokenSnapshot {
+    symbol: String,
+    price: f64,
+    usd_dex_liquidity: f64,
+    holders: String,
+    total_supply: f64,
+}
+
+impl TokenSnapshot {
+    fn from_info(info: &MemecoinInfo, holders: &TokenCategoryResponse) -> Self {
+        Self {
+            symbol: info.symbol.clone(),
+            price: info.price.parse::<f64>().unwrap_or_default(),
+            usd_dex_liquidity: info.usd_dex_liquidity.parse::<f64>().unwrap_or_default(),
+            holders: holders.category.clone(),
+            total_supply: info.total_supply.parse::<f64>().unwrap_or_default(),
+        }
+    }
+}
+
+// How much a chat hears about a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subscription {
+    // Price, liquidity and holder category changes
+    Watch,
+    // Everything watched tokens get plus whale trades and holder milestones
+    Follow,
+}
+
+// A notable difference between two snapshots of a watched token
+#[derive(Debug, Clone)]
+pub enum WatchChange {
+    Price { from: f64, to: f64 },
+    Liquidity { from: f64, to: f64 },
+    Holders { from: String, to: String },
+    HolderMilestone { count: usize },
+    HolderDrop { from: usize, to: usize },
+    WhaleTrade {
+        buy: bool,
+        account: String,
+        amount: String,
+        supply_pct: f64,
+        // How long the wallet usually holds factory tokens, None when it can't be told
+        style: Option<HoldingStyle>,
+    },
+    // Liquidity of the launch position, see LiquidityGuard
+    LiquidityPulled { from: String, to: String, drop_pct: f64 },
+    LockOwner { from: String, to: String },
+    // Token ownership handed over, to the zero address when renounced
+    Owner { from: String, to: String },
+}
+
+impl WatchChange {
+    // Changes only sent to chats following the token
+    fn follow_only(&self) -> bool {
+        matches!(
+            self,
+            WatchChange::HolderMilestone { .. }
+                | WatchChange::HolderDrop { .. }
+                | WatchChange::WhaleTrade { .. }
+                | WatchChange::Owner { .. }
+        )
+    }
+}
+
+// Recent holder counts of a followed token
+#[derive(Debug, Default)]
+struct HolderTrend {
+    samples: VecDeque<(u64, usize)>,
+    // Highest milestone already announced, so estimates wobbling around it don't repeat it
+    milestone: usize,
+}
+
+impl HolderTrend {
+    // Milestones the token had already passed when first seen aren't news
+    fn new(now: u64, count: usize) -> Self {
+        Self {
+            samples: VecDeque::from([(now, count)]),
+            milestone: HOLDER_MILESTONES
+                .iter()
+                .rev()
+                .find(|milestone| count >= **milestone)
+                .copied()
+                .unwrap_or_default(),
+        }
+    }
+
+    fn observe(&mut self, now: u64, count: usize, drop_pct: f64) -> Vec<WatchChange> {
+        let mut changes = Vec::new();
+        if let Some(milestone) = HOLDER_MILESTONES
+            .iter()
+            .rev()
+            .find(|milestone| count >= **milestone && **milestone > self.milestone)
+        {
+            self.milestone = *milestone;
+            changes.push(WatchChange::HolderMilestone { count: *milestone });
+        }
+
+        self.samples
+            .retain(|(seen_at, _)| now.saturating_sub(*seen_at) <= HOLDER_DROP_WINDOW_SECS);
+        let peak = self.samples.iter().map(|(_, count)| *count).max().unwrap_or_default();
+        if peak >= HOLDER_DROP_MIN_PEAK && percent_change(peak as f64, count as f64) <= -drop_pct {
+            changes.push(WatchChange::HolderDrop {
+                from: peak,
+                to: count,
+            });
+            // Report an exodus once rather than on every check of the window
+            self.samples.clear();
+        }
+        self.samples.push_back((now, count));
+        changes
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WatchError {
+    #[error("Invalid token address")]
+    InvalidAddress,
+    #[error("You can watch up to {0} tokens")]
+    LimitReached(usize),
+    #[error("Token not found or not an Unruggable memecoin")]
+    Lookup(anyhow::Error),
+}
+
+// Configuration for the watchlist monitor
+#[derive(Debug, Clone)]
+pub struct WatchlistConfig {
+    check_interval: Duration,
+    price_change_pct: f64,
+    liquidity_change_pct: f64,
+    max_tokens_per_chat: usize,
+    whale_supply_pct: f64,
+    holder_drop_pct: f64,
+    // Tokens idle this long are dropped from every watchlist, 0 keeps them forever
+    expiry_secs: u64,
+    // Pools below this much USD liquidity count as idle whatever the price does
+    dead_liquidity_usd: f64,
+}
+
+impl WatchlistConfig {
+    pub fn new() -> Self {
+        let check_interval = std::env::var("WATCHLIST_CHECK_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(120);
+
+        Self {
+            check_interval: Duration::from_secs(check_interval),
+            price_change_pct: std::env::var("WATCHLIST_PRICE_CHANGE_PCT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(10f64),
+            liquidity_change_pct: std::env::var("WATCHLIST_LIQUIDITY_CHANGE_PCT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(10f64),
+            max_tokens_per_chat: std::env::var("WATCHLIST_MAX_TOKENS")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(20),
+            whale_supply_pct: std::env::var("WATCHLIST_WHALE_SUPPLY_PCT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.5),
+            holder_drop_pct: std::env::var("WATCHLIST_HOLDER_DROP_PCT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(25f64),
+            expiry_secs: std::env::var("WATCHLIST_EXPIRY_HOURS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(72)
+                * 60
+                * 60,
+            dead_liquidity_usd: std::env::var("WATCHLIST_DEAD_LIQUIDITY_USD")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(100f64),
+        }
+    }
+}
+
+// Per-chat token watchlists, re-aggregated periodically and diffed against the last snapshot
+pub struct Watchlist {
+    config: WatchlistConfig,
+    watchers: RwLock<HashMap<String, HashMap<i64, Subscription>>>,
+    snapshots: RwLock<HashMap<String, TokenSnapshot>>,
+    // Last block scanned for trades of each followed token
+    scanned_blocks: RwLock<HashMap<String, u64>>,
+    holder_trends: RwLock<HashMap<String, HolderTrend>>,
+    activity: RwLock<HashMap<String, Activity>>,
+    holding: HoldingClassifier,
+}
+
+impl Watchlist {
+    pub fn new(config: WatchlistConfig, storage: Arc<Storage>) -> Self {
+        Self {
+            config,
+            watchers: RwLock::new(HashMap::new()),
+            snapshots: RwLock::new(HashMap::new()),
+            scanned_blocks: RwLock::new(HashMap::new()),
+            holder_trends: RwLock::new(HashMap::new()),
+            activity: RwLock::new(HashMap::new()),
+            holding: HoldingClassifier::new(storage),
+        }
+    }
+
+    // Adds a token to a chat's watchlist and returns its symbol
+    pub async fn watch(&self, chat_id: i64, token_address: &str) -> Result<String, WatchError> {
+        self.subscribe(chat_id, token_address, Subscription::Watch).await
+    }
+
+    // Subscribes a chat to everything about a token and returns its symbol
+    pub async fn follow(&self, chat_id: i64, token_address: &str) -> Result<String, WatchError> {
+        self.subscribe(chat_id, token_address, Subscription::Follow).await
+    }
+
+    async fn subscribe(
+        &self,
+        chat_id: i64,
+        token_address: &str,
+        subscription: Subscription,
+    ) -> Result<String, WatchError> {
+        let token_address = canonicalize_address(token_address).ok_or(WatchError::InvalidAddress)?;
+
+        let watched = self.watched(chat_id).await;
+        if let Some((_, symbol, _)) = watched.iter().find(|(address, _, _)| *address == token_address) {
+            // Switching between /watch and /follow keeps the existing baseline
+            if let Some(chats) = self.watchers.write().await.get_mut(&token_address) {
+                chats.insert(chat_id, subscription);
+            }
+            self.touch(&token_address, unix_now()).await;
+            return Ok(symbol.clone());
+        }
+        if watched.len() >= self.config.max_tokens_per_chat {
+            return Err(WatchError::LimitReached(self.config.max_tokens_per_chat));
+        }
+
+        // Seed a snapshot the first time a token is watched so the first diff is meaningful
+        let known = self.snapshots.read().await.get(&token_address).cloned();
+        let symbol = match known {
+            Some(snapshot) => snapshot.symbol,
+            None => {
+                let (info, holders) = aggregate_info(&token_address)
+                    .await
+                    .map_err(WatchError::Lookup)?;
+                let snapshot = TokenSnapshot::from_info(&info, &holders);
+                let symbol = snapshot.symbol.clone();
+                self.snapshots
+                    .write()
+                    .await
+                    .insert(token_address.clone(), snapshot);
+                symbol
+            }
+        };
+
+        self.watchers
+            .write()
+            .await
+            .entry(token_address.clone())
+            .or_default()
+            .insert(chat_id, subscription);
+        // A fresh subscription gives the token another full expiry period
+        self.touch(&token_address, unix_now()).await;
+        Ok(symbol)
+    }
+
+    // Drops a watched or followed token, returns false if the chat wasn't subscribed to it
+    pub async fn unwatch(&self, chat_id: i64, token_address: &str) -> bool {
+        let token_address = match canonicalize_address(token_address) {
+            Some(address) => address,
+            None => return false,
+        };
+
+        let mut watchers = self.watchers.write().await;
+        let removed = match watchers.get_mut(&token_address) {
+            Some(chats) => chats.remove(&chat_id).is_some(),
+            None => false,
+        };
+        if watchers.get(&token_address).is_some_and(|chats| chats.is_empty()) {
+            watchers.remove(&token_address);
+            self.forget(&token_address).await;
+        }
+        removed
+    }
+
+    // Drops everything tracked about a token nobody is subscribed to anymore
+    async fn forget(&self, token_address: &str) {
+        self.snapshots.write().await.remove(token_address);
+        self.scanned_blocks.write().await.remove(token_address);
+        self.holder_trends.write().await.remove(token_address);
+        self.activity.write().await.remove(token_address);
+    }
+
+    // Drops every subscription of a chat, returns how many tokens it was subscribed to
+    pub async fn remove_chat(&self, chat_id: i64) -> usize {
+        let tokens: Vec<String> = self
+            .watched(chat_id)
+            .await
+            .into_iter()
+            .map(|(address, _, _)| address)
+            .collect();
+        for token_address in &tokens {
+            self.unwatch(chat_id, token_address).await;
+        }
+        tokens.len()
+    }
+
+    // Lists (token address, symbol, subscription) of the tokens a chat is subscribed to
+    pub async fn watched(&self, chat_id: i64) -> Vec<(String, String, Subscription)> {
+        let watchers = self.watchers.read().await;
+        let snapshots = self.snapshots.read().await;
+        watchers
+            .iter()
+            .filter_map(|(address, chats)| {
+                let subscription = *chats.get(&chat_id)?;
+                let symbol = snapshots
+                    .get(address)
+                    .map(|snapshot| snapshot.symbol.clone())
+                    .unwrap_or_default();
+                Some((address.clone(), symbol, subscription))
+            })
+            .collect()
+    }
+
+    // Every subscribed token with its symbol and the chats watching or following it
+    pub async fn subscribers(&self) -> Vec<(String, String, Vec<i64>)> {
+        let snapshots = self.snapshots.read().await;
+        self.watchers
+            .read()
+            .await
+            .iter()
+            .map(|(address, chats)| {
+                let symbol = snapshots
+                    .get(address)
+                    .map(|snapshot| snapshot.symbol.clone())
+                    .unwrap_or_default();
+                (address.clone(), symbol, chats.keys().copied().collect())
+            })
+            .collect()
+    }
+
+    pub async fn run(&self, tg_bot: Arc<TelegramBot>) {
+        loop {
+            tokio::time::sleep(self.config.check_interval).await;
+
+            let watched: Vec<(String, HashMap<i64, Subscription>)> = self
+                .watchers
+                .read()
+                .await
+                .iter()
+                .map(|(address, chats)| (address.clone(), chats.clone()))
+                .collect();
+            // Chain head number and timestamp, only needed for followed tokens
+            let latest = if watched
+                .iter()
+                .any(|(_, chats)| chats.values().any(|s| *s == Subscription::Follow))
+            {
+                get_latest_block()
+                    .await
+                    .map_err(|e| eprintln!("Failed to fetch latest block for followed tokens: {}", e))
+                    .ok()
+            } else {
+                None
+            };
+
+            for (token_address, chats) in watched {
+                let refreshed = aggregate_info_with_refresh(&token_address, true).await;
+                // Rugged tokens often stop aggregating altogether, failures count as idle time
+                let refreshed_info = refreshed.as_ref().ok().map(|(info, _)| info);
+                let idle_secs = self.record_activity(&token_address, refreshed_info, unix_now()).await;
+                if self.config.expiry_secs > 0 && idle_secs >= self.config.expiry_secs {
+                    self.expire(&tg_bot, &token_address, idle_secs).await;
+                    continue;
+                }
+                let (info, holders) = match refreshed {
+                    Ok(result) => result,
+                    Err(e) => {
+                        eprintln!("Failed to refresh watched token {}: {}", token_address, e);
+                        continue;
+                    }
+                };
+                let current = TokenSnapshot::from_info(&info, &holders);
+
+                // Keep the baseline until something is reported so slow drifts still add up
+                let previous = self.snapshots.read().await.get(&token_address).cloned();
+                let mut changes = previous
+                    .as_ref()
+                    .map(|previous| self.diff(previous, &current))
+                    .unwrap_or_default();
+                let followed = chats.values().any(|s| *s == Subscription::Follow);
+                if followed {
+                    changes.extend(self.holder_changes(&token_address, &holders).await);
+                    if let Some(latest) = latest {
+                        changes.extend(self.onchain_changes(&token_address, &current, latest).await);
+                    }
+                } else {
+                    // Start over should the token be followed again
+                    self.scanned_blocks.write().await.remove(&token_address);
+                    self.holder_trends.write().await.remove(&token_address);
+                }
+
+                if previous.is_none() || changes.iter().any(|change| !change.follow_only()) {
+                    self.snapshots
+                        .write()
+                        .await
+                        .insert(token_address.clone(), current.clone());
+                }
+                if changes.is_empty() {
+                    continue;
+                }
+
+                let watch_changes: Vec<WatchChange> =
+                    changes.iter().filter(|change| !change.follow_only()).cloned().collect();
+                let routed = [
+                    (Subscription::Watch, &watch_changes),
+                    (Subscription::Follow, &changes),
+                ];
+                for (subscription, changes) in routed {
+                    let chat_ids: Vec<i64> = chats
+                        .iter()
+                        .filter(|(_, s)| **s == subscription)
+                        .map(|(chat_id, _)| *chat_id)
+                        .collect();
+                    if chat_ids.is_empty() || changes.is_empty() {
+                        continue;
+                    }
+                    if let Err(e) = tg_bot
+                        .notify_watchers(&chat_ids, &current.symbol, &token_address, changes)
+                        .await
+                    {
+                        eprintln!("Failed to notify watchers of {}: {:?}", token_address, e);
+                    }
+                }
+            }
+        }
+    }
+
+    // Restarts a token's expiry period
+    async fn touch(&self, token_address: &str, now: u64) {
+        self.activity
+            .write()
+            .await
+            .entry(token_address.to_string())
+            .and_modify(|activity| activity.active_at = now)
+            .or_insert(Activity {
+                price: None,
+                active_at: now,
+            });
+    }
+
+    // Records a refresh of a token, None when it failed, and returns how long it has been idle
+    async fn record_activity(&self, token_address: &str, info: Option<&MemecoinInfo>, now: u64) -> u64 {
+        let mut activity = self.activity.write().await;
+        let activity = activity.entry(token_address.to_string()).or_insert(Activity {
+            price: None,
+            active_at: now,
+        });
+        if let Some(info) = info {
+            let price = info.price.parse::<f64>().unwrap_or_default();
+            let liquidity = info.usd_dex_liquidity.parse::<f64>().unwrap_or_default();
+            // Ekubo prices only move on swaps, an unchanged price means nobody traded
+            let traded = activity.price.is_some_and(|previous| previous != price);
+            if traded && liquidity >= self.config.dead_liquidity_usd {
+                activity.active_at = now;
+            }
+            activity.price = Some(price);
+        }
+        now.saturating_sub(activity.active_at)
+    }
+
+    // Unsubscribes every chat from an idle token and lets them know why
+    async fn expire(&self, tg_bot: &TelegramBot, token_address: &str, idle_secs: u64) {
+        let symbol = self
+            .snapshots
+            .read()
+            .await
+            .get(token_address)
+            .map(|snapshot| snapshot.symbol.clone())
+            .unwrap_or_default();
+        let chat_ids: Vec<i64> = match self.watchers.write().await.remove(token_address) {
+            Some(chats) => chats.into_keys().collect(),
+            None => return,
+        };
+        self.forget(token_address).await;
+        println!(
+            "Watch on {} expired after {}h idle, dropped for {} chats",
+            token_address,
+            idle_secs / 3600,
+            chat_ids.len()
+        );
+        if let Err(e) = tg_bot
+            .notify_watch_expired(&chat_ids, &symbol, token_address, idle_secs)
+            .await
+        {
+            eprintln!("Failed to notify watchers of {} expiry: {:?}", token_address, e);
+        }
+    }
+
+    // Holder milestones crossed and exoduses within the last hour
+    async fn holder_changes(&self, token_address: &str, holders: &TokenCategoryResponse) -> Vec<WatchChange> {
+        let count = holders.total_holders.max(holders.holder_count);
+        let now = unix_now();
+        let mut trends = self.holder_trends.write().await;
+        match trends.get_mut(token_address) {
+            Some(trend) => trend.observe(now, count, self.config.holder_drop_pct),
+            None => {
+                trends.insert(token_address.to_string(), HolderTrend::new(now, count));
+                Vec::new()
+            }
+        }
+    }
+
+    // Whale trades and ownership handovers in the blocks scanned since the last refresh
+    async fn onchain_changes(
+        &self,
+        token_address: &str,
+        snapshot: &TokenSnapshot,
+        (latest_block, latest_timestamp): (u64, u64),
+    ) -> Vec<WatchChange> {
+        let scanned = self.scanned_blocks.read().await.get(token_address).copied();
+        // The first pass only sets the starting point, what happened before the follow isn't news
+        let from_block = match scanned {
+            Some(scanned) if scanned < latest_block => {
+                (scanned + 1).max(latest_block.saturating_sub(MAX_TRADE_SCAN_BLOCKS))
+            }
+            Some(_) => return Vec::new(),
+            None => {
+                self.scanned_blocks
+                    .write()
+                    .await
+                    .insert(token_address.to_string(), latest_block);
+                return Vec::new();
+            }
+        };
+
+        // Both are fetched before the range counts as scanned, so a failure retries all of it
+        let transfers = match get_transfer_events(token_address, from_block, latest_block).await {
+            Ok(events) => events,
+            Err(e) => {
+                eprintln!("Failed to fetch trades of {}: {}", token_address, e);
+                return Vec::new();
+            }
+        };
+        let handovers = match get_ownership_events(token_address, from_block, latest_block).await {
+            Ok(events) => events,
+            Err(e) => {
+                eprintln!("Failed to fetch ownership transfers of {}: {}", token_address, e);
+                return Vec::new();
+            }
+        };
+        self.scanned_blocks
+            .write()
+            .await
+            .insert(token_address.to_string(), latest_block);
+
+        let mut changes = owner_changes(&handovers);
+        changes.extend(self.whale_trades(snapshot, &transfers, latest_block, latest_timestamp).await);
+        changes
+    }
+
+    // Swaps against the Ekubo pool moving at least the configured share of the supply
+    async fn whale_trades(
+        &self,
+        snapshot: &TokenSnapshot,
+        events: &[EmittedEvent],
+        latest_block: u64,
+        latest_timestamp: u64,
+    ) -> Vec<WatchChange> {
+        if snapshot.total_supply <= 0f64 {
+            return Vec::new();
+        }
+        // Trades are told apart from plain transfers by the pool side
+        let ekubo_core = &NETWORK.ekubo_core;
+        let trades: Vec<(bool, String, String, f64)> = events
+            .iter()
+            .filter_map(|event| {
+                let from = to_canonical_hex(event.keys.get(1)?);
+                let to = to_canonical_hex(event.keys.get(2)?);
+                let amount = parse_u256_from_felts(event.data.first()?, event.data.get(1)?);
+                let supply_pct = amount.parse::<f64>().ok()? * 100f64 / snapshot.total_supply;
+                if supply_pct < self.config.whale_supply_pct {
+                    return None;
+                }
+                let (buy, account) = if addresses_eq(&from, ekubo_core) {
+                    (true, to)
+                } else if addresses_eq(&to, ekubo_core) {
+                    (false, from)
+                } else {
+                    return None;
+                };
+                Some((buy, account, amount, supply_pct))
+            })
+            .collect();
+
+        // Each wallet is classified once, a few of them side by side
+        let mut accounts: Vec<&str> = trades.iter().map(|(_, account, _, _)| account.as_str()).collect();
+        accounts.sort_unstable();
+        accounts.dedup();
+        let mut styles: HashMap<&str, Option<HoldingStyle>> = HashMap::new();
+        for batch in accounts.chunks(MAX_CONCURRENT_CLASSIFICATIONS) {
+            let classified = join_all(
+                batch
+                    .iter()
+                    .map(|account| self.holding.style(account, latest_block, latest_timestamp)),
+            )
+            .await;
+            styles.extend(batch.iter().copied().zip(classified));
+        }
+
+        let mut changes = Vec::new();
+        for (buy, account, amount, supply_pct) in &trades {
+            changes.push(WatchChange::WhaleTrade {
+                buy: *buy,
+                account: account.clone(),
+                amount: amount.clone(),
+                supply_pct: *supply_pct,
+                style: styles.get(account.as_str()).copied().flatten(),
+            });
+        }
+        changes
+    }
+
+    fn diff(&self, previous: &TokenSnapshot, current: &TokenSnapshot) -> Vec<WatchChange> {
+        let mut changes = Vec::new();
+
+        if percent_change(previous.price, current.price).abs() >= self.config.price_change_pct {
+            changes.push(WatchChange::Price {
+                from: previous.price,
+                to: current.price,
+            });
+        }
+        if percent_change(previous.usd_dex_liquidity, current.usd_dex_liquidity).abs()
+            >= self.config.liquidity_change_pct
+        {
+            changes.push(WatchChange::Liquidity {
+                from: previous.usd_dex_liquidity,
+                to: current.usd_dex_liquidity,
+            });
+        }
+        if previous.holders != current.holders {
+            changes.push(WatchChange::Holders {
+                from: previous.holders.clone(),
+                to: current.holders.clone(),
+            });
+        }
+        changes
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn percent_change(from: f64, to: f64) -> f64 {
+    if from == 0f64 {
+        return 0f64;
+    }
+    (to - from) * 100f64 / from
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StorageConfig;
+    use starknet_core::types::Felt;
+
+    #[test]
+    fn test_holder_trend_milestones_and_drops() {
+        let mut trend = HolderTrend::new(0, 120);
+        assert!(trend.observe(60, 150, 25f64).is_empty());
+        assert!(matches!(
+            trend.observe(120, 600, 25f64)[..],
+            [WatchChange::HolderMilestone { count: 500 }]
+        ));
+        // Dipping below and crossing 500 again isn't celebrated twice
+        assert!(trend.observe(180, 490, 25f64).is_empty());
+        assert!(trend.observe(240, 510, 25f64).is_empty());
+
+        assert!(matches!(
+            trend.observe(300, 400, 25f64)[..],
+            [WatchChange::HolderDrop { from: 600, to: 400 }]
+        ));
+        assert!(trend.observe(360, 390, 25f64).is_empty());
+
+        // Peaks older than the window no longer count
+        let mut trend = HolderTrend::new(0, 600);
+        assert!(trend.observe(HOLDER_DROP_WINDOW_SECS + 1, 400, 25f64).is_empty());
+    }
+
+    #[test]
+    fn test_owner_changes() {
+        let event = |keys: Vec<Felt>, data: Vec<Felt>| EmittedEvent {
+            from_address: Felt::from(0xau64),
+            keys,
+            data,
+            block_hash: None,
+            block_number: Some(1),
+            transaction_hash: Felt::ZERO,
+        };
+        let selector = Felt::ONE;
+        let (deployer, team) = (Felt::from(0x1u64), Felt::from(0x2u64));
+        // Handed over and renounced between two refreshes, both are reported
+        let changes = owner_changes(&[
+            event(vec![selector, deployer, team], vec![]),
+            event(vec![selector], vec![team, Felt::ZERO]),
+            event(vec![selector], vec![]),
+        ]);
+        assert_eq!(changes.len(), 2);
+        assert!(matches!(
+            &changes[1],
+            WatchChange::Owner { from, to } if addresses_eq(from, "0x2") && addresses_eq(to, "0x0")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_idle_tokens_expire() {
+        let storage = Arc::new(Storage::connect(StorageConfig::in_memory()).await.unwrap());
+        let watchlist = Watchlist::new(WatchlistConfig::new(), storage);
+        let info = |price: &str, liquidity: &str| MemecoinInfo {
+            price: price.to_string(),
+            usd_dex_liquidity: liquidity.to_string(),
+            ..Default::default()
+        };
+
+        watchlist.touch("0xa", 1_000).await;
+        assert_eq!(watchlist.record_activity("0xa", Some(&info("1", "5000")), 2_000).await, 1_000);
+        // A trade resets the idle time, a flat price or a failed refresh doesn't
+        assert_eq!(watchlist.record_activity("0xa", Some(&info("2", "5000")), 3_000).await, 0);
+        assert_eq!(watchlist.record_activity("0xa", Some(&info("2", "5000")), 4_000).await, 1_000);
+        assert_eq!(watchlist.record_activity("0xa", None, 5_000).await, 2_000);
+        // Dust trades in a drained pool don't keep a rugged token alive
+        assert_eq!(watchlist.record_activity("0xa", Some(&info("3", "10")), 6_000).await, 3_000);
+
+        watchlist.touch("0xa", 6_000).await;
+        assert_eq!(watchlist.record_activity("0xa", None, 6_500).await, 500);
+    }
+}